@@ -0,0 +1,134 @@
+//! `git blame`-style listing of one file with a header above every function
+//!
+//! Each header shows the function's current complexity and how it moved since
+//! a baseline revision (`HEAD` by default), so a reviewer reading the file in
+//! a terminal sees which functions a change made harder to follow.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use codemetrics_core::analyzers::CodeAnalyzer;
+use codemetrics_core::parsers::ParsedFile;
+use codemetrics_core::thresholds::{ThresholdLevel, Thresholds};
+use codemetrics_core::vcs;
+
+/// Current metrics of one function and its complexity at the baseline revision
+#[derive(Debug, PartialEq)]
+pub struct FunctionAnnotation {
+    pub name: String,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub parameters: u32,
+    pub complexity: u32,
+    /// `None` when the function, or the whole file, did not exist at the baseline
+    pub baseline: Option<u32>,
+}
+
+pub fn run(analyzer: &CodeAnalyzer, file: &Path, against: &str) -> Result<()> {
+    let source = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let current = analyzer.parse_source(file, &source)?;
+
+    let baseline_source = match vcs::detect(file) {
+        Ok(vcs) => vcs.show(against, file)?,
+        Err(e) => {
+            eprintln!("Warning: no baseline to compare with - {}", e);
+            None
+        }
+    };
+    let baseline = baseline_source
+        .map(|text| analyzer.parse_source(file, &text))
+        .transpose()?;
+
+    let annotations = annotate(&current, baseline.as_ref());
+    print!("{}", render(&source, &annotations, &analyzer.config().thresholds, against));
+    Ok(())
+}
+
+/// Pair every current function with its baseline complexity, matched by name
+pub fn annotate(current: &ParsedFile, baseline: Option<&ParsedFile>) -> Vec<FunctionAnnotation> {
+    let before: HashMap<&str, u32> = baseline
+        .map(|file| file.functions.iter().map(|function| (function.name.as_str(), function.complexity)).collect())
+        .unwrap_or_default();
+
+    current.functions.iter()
+        .map(|function| FunctionAnnotation {
+            name: function.name.clone(),
+            line_start: function.line_start,
+            line_end: function.line_end,
+            parameters: function.parameters,
+            complexity: function.complexity,
+            baseline: before.get(function.name.as_str()).copied(),
+        })
+        .collect()
+}
+
+/// The source with line numbers, each function preceded by its header
+pub fn render(source: &str, annotations: &[FunctionAnnotation], thresholds: &Thresholds, against: &str) -> String {
+    let mut headers: BTreeMap<u32, Vec<&FunctionAnnotation>> = BTreeMap::new();
+    for annotation in annotations {
+        headers.entry(annotation.line_start).or_default().push(annotation);
+    }
+
+    let mut out = String::new();
+    for (index, line) in source.lines().enumerate() {
+        let number = index as u32 + 1;
+        for annotation in headers.get(&number).into_iter().flatten() {
+            // Writing to a String cannot fail
+            let _ = writeln!(out, "{}", header(annotation, thresholds, against));
+        }
+        let _ = writeln!(out, "{:>5} │ {}", number, line);
+    }
+    out
+}
+
+fn header(annotation: &FunctionAnnotation, thresholds: &Thresholds, against: &str) -> String {
+    let delta = match annotation.baseline {
+        Some(before) if before == annotation.complexity => format!("unchanged vs {}", against),
+        Some(before) => format!("{:+} vs {}", annotation.complexity as i64 - before as i64, against),
+        None => format!("new since {}", against),
+    };
+    let level = match thresholds.function_level(annotation.complexity) {
+        ThresholdLevel::Ok => "",
+        ThresholdLevel::Warn => " ⚠",
+        ThresholdLevel::Error => " ✖",
+        ThresholdLevel::Critical => " ✖✖",
+    };
+    format!(
+        "      ┌─ {} · complexity {} ({}){} · {} params · lines {}-{}",
+        annotation.name,
+        annotation.complexity,
+        delta,
+        level,
+        annotation.parameters,
+        annotation.line_start,
+        annotation.line_end
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codemetrics_core::parsers::LanguageParser;
+
+    #[test]
+    fn test_headers_show_delta_against_baseline() {
+        let parser = LanguageParser::new();
+        let old = "fn steady() {}\nfn grows(x: i32) { if x > 0 {} }\n";
+        let new = "fn steady() {}\nfn grows(x: i32) { if x > 0 {} if x > 1 {} }\nfn fresh() {}\n";
+        let before = parser.parse_file("lib.rs", old).unwrap();
+        let after = parser.parse_file("lib.rs", new).unwrap();
+
+        let annotations = annotate(&after, Some(&before));
+        let rendered = render(new, &annotations, &Thresholds::default(), "HEAD");
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].contains("steady") && lines[0].contains("unchanged vs HEAD"));
+        assert_eq!(lines[1], "    1 │ fn steady() {}");
+        assert!(lines[2].contains("grows") && lines[2].contains("+1 vs HEAD"));
+        assert!(lines[4].contains("fresh") && lines[4].contains("new since HEAD"));
+    }
+}
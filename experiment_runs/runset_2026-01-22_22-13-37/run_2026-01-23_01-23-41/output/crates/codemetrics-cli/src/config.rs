@@ -0,0 +1,427 @@
+//! Project configuration from `codemetrics.toml`
+//!
+//! A team checks one config file into the repository root and every run of
+//! `analyze` picks it up. Flags given on the command line always win over
+//! file values, which in turn win over the built-in defaults.
+//!
+//! ```toml
+//! exclude = ["vendor", "generated"]
+//! format = "json"
+//! profile = "deep"
+//! min_complexity = 8
+//! min_function_statements = 3   # one-line getters stay out of averages
+//! languages = ["rust", "python"]
+//! exclude_languages = ["js"]
+//! max_duplication_pct = 5.0
+//! fail_on = ["issues.critical>0", "complexity.avg>8"]
+//!
+//! [thresholds]
+//! function_complexity_warn = 12
+//!
+//! # Relative weights of the health score's dimensions
+//! [health]
+//! docs = 0.05
+//! tests = 0.3
+//!
+//! [rules]
+//! enable = ["CM0103"]
+//! disable = ["long-function"]
+//! severity = { too-many-parameters = "error", deep-nesting = "info" }
+//!
+//! # Paths are relative to the config file
+//! [[overrides]]
+//! path = "legacy"
+//! thresholds = { function_complexity_warn = 25, function_complexity_error = 40 }
+//!
+//! # Accepted until the date passes, then reported again
+//! [[suppress]]
+//! rule = "complexity"
+//! path = "src/parser.rs"
+//! function = "parse_expression"
+//! until = "2025-06-01"
+//! reason = "rewrite tracked in #123"
+//! ```
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use codemetrics_core::analyzers::AnalysisConfigBuilder;
+use codemetrics_core::error_handling::{ErrorHandling, ErrorHandlingConfig};
+use codemetrics_core::health::HealthModel;
+use codemetrics_core::rules::{self, RuleSet};
+use codemetrics_core::suppressions::Suppression;
+use codemetrics_core::thresholds::Thresholds;
+use crate::AnalyzeArgs;
+
+/// File names looked up in the project root, in order of preference
+pub const CONFIG_FILES: [&str; 2] = ["codemetrics.toml", ".codemetricsrc"];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Path patterns skipped in addition to the defaults
+    pub exclude: Vec<String>,
+    pub format: Option<String>,
+    /// `quick`, `standard` or `deep`
+    pub profile: Option<String>,
+    pub min_complexity: Option<u32>,
+    /// Functions with fewer statements than this are left out of averages and the distribution
+    pub min_function_statements: Option<u32>,
+    pub include_tests: Option<bool>,
+    pub languages: Vec<String>,
+    pub exclude_languages: Vec<String>,
+    /// Fail the run when more than this percentage of lines is duplicated
+    pub max_duplication_pct: Option<f64>,
+    /// Quality gates in `--fail-on` syntax, checked on every run
+    pub fail_on: Vec<String>,
+    pub thresholds: ThresholdOverrides,
+    pub rules: RuleConfig,
+    /// Complexity weights of `try`/`catch`, `?` and `if err != nil`, per language
+    pub error_handling: ErrorHandlingConfig,
+    /// Health score weights; dimensions left out keep their default weight
+    pub health: HealthModel,
+    pub overrides: Vec<DirectoryOverride>,
+    /// Accepted issues, optionally with an expiry date
+    pub suppress: Vec<Suppression>,
+    /// Directory holding the config file, which override paths are relative to
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+/// Thresholds to change; anything unset keeps its inherited value
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThresholdOverrides {
+    pub function_complexity_warn: Option<u32>,
+    pub function_complexity_error: Option<u32>,
+    pub file_complexity_warn: Option<u32>,
+    pub file_complexity_error: Option<u32>,
+    pub max_nesting_depth: Option<u32>,
+    pub max_parameters: Option<u32>,
+    pub max_function_lines: Option<u32>,
+    pub max_generic_bounds: Option<u32>,
+}
+
+/// Which rules run and at what severity; rules are named by ID or name
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleConfig {
+    pub enable: Vec<String>,
+    pub disable: Vec<String>,
+    /// Severity level (info, warning, error, critical) per rule
+    pub severity: BTreeMap<String, String>,
+}
+
+impl RuleConfig {
+    /// These settings layered under command-line enables, disables and `RULE=LEVEL` severities
+    ///
+    /// A rule the command line enables is not disabled by the file and vice
+    /// versa; command-line severities come last so they win.
+    pub fn merged_with(&self, enable: &[String], disable: &[String], severities: &[String]) -> (Vec<String>, Vec<String>, Vec<String>) {
+        let enabled = self.enable.iter()
+            .filter(|key| !disable.contains(key))
+            .chain(enable)
+            .cloned()
+            .collect();
+        let disabled = self.disable.iter()
+            .filter(|key| !enable.contains(key))
+            .chain(disable)
+            .cloned()
+            .collect();
+        let severities = self.severity.iter()
+            .map(|(rule, level)| format!("{}={}", rule, level))
+            .chain(severities.iter().cloned())
+            .collect();
+        (enabled, disabled, severities)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DirectoryOverride {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub thresholds: ThresholdOverrides,
+}
+
+impl ThresholdOverrides {
+    /// Parse `name=value` entries from `--threshold`, named like the `[thresholds]` keys
+    pub fn parse(entries: &[String]) -> Result<Self> {
+        let mut overrides = Self::default();
+        for entry in entries {
+            let (name, value) = entry.split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid threshold '{}', expected NAME=VALUE", entry))?;
+            let value: u32 = value.trim().parse()
+                .with_context(|| format!("Invalid value for threshold {}", name))?;
+            let slot = match name.trim() {
+                "function_complexity_warn" => &mut overrides.function_complexity_warn,
+                "function_complexity_error" => &mut overrides.function_complexity_error,
+                "file_complexity_warn" => &mut overrides.file_complexity_warn,
+                "file_complexity_error" => &mut overrides.file_complexity_error,
+                "max_nesting_depth" => &mut overrides.max_nesting_depth,
+                "max_parameters" => &mut overrides.max_parameters,
+                "max_function_lines" => &mut overrides.max_function_lines,
+                "max_generic_bounds" => &mut overrides.max_generic_bounds,
+                other => anyhow::bail!("Unknown threshold '{}' (see [thresholds] in codemetrics.toml)", other),
+            };
+            *slot = Some(value);
+        }
+        Ok(overrides)
+    }
+
+    /// Every threshold as set in `thresholds`
+    pub fn of(thresholds: &Thresholds) -> Self {
+        Self {
+            function_complexity_warn: Some(thresholds.function_complexity_warn),
+            function_complexity_error: Some(thresholds.function_complexity_error),
+            file_complexity_warn: Some(thresholds.file_complexity_warn),
+            file_complexity_error: Some(thresholds.file_complexity_error),
+            max_nesting_depth: Some(thresholds.max_nesting_depth),
+            max_parameters: Some(thresholds.max_parameters),
+            max_function_lines: Some(thresholds.max_function_lines),
+            max_generic_bounds: Some(thresholds.max_generic_bounds),
+        }
+    }
+
+    /// Each threshold by its `[thresholds]` key
+    pub fn entries(&self) -> [(&'static str, Option<u32>); 8] {
+        [
+            ("function_complexity_warn", self.function_complexity_warn),
+            ("function_complexity_error", self.function_complexity_error),
+            ("file_complexity_warn", self.file_complexity_warn),
+            ("file_complexity_error", self.file_complexity_error),
+            ("max_nesting_depth", self.max_nesting_depth),
+            ("max_parameters", self.max_parameters),
+            ("max_function_lines", self.max_function_lines),
+            ("max_generic_bounds", self.max_generic_bounds),
+        ]
+    }
+
+    pub fn apply(&self, base: &Thresholds) -> Thresholds {
+        Thresholds {
+            function_complexity_warn: self.function_complexity_warn.unwrap_or(base.function_complexity_warn),
+            function_complexity_error: self.function_complexity_error.unwrap_or(base.function_complexity_error),
+            file_complexity_warn: self.file_complexity_warn.unwrap_or(base.file_complexity_warn),
+            file_complexity_error: self.file_complexity_error.unwrap_or(base.file_complexity_error),
+            max_nesting_depth: self.max_nesting_depth.unwrap_or(base.max_nesting_depth),
+            max_parameters: self.max_parameters.unwrap_or(base.max_parameters),
+            max_function_lines: self.max_function_lines.unwrap_or(base.max_function_lines),
+            max_generic_bounds: self.max_generic_bounds.unwrap_or(base.max_generic_bounds),
+            function_complexity_critical: base.function_complexity_critical,
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// The config file of the project at `path`, if it has one
+    pub fn discover(path: &Path) -> Result<Option<Self>> {
+        Self::discover_file(path).map(|file| Self::load(&file)).transpose()
+    }
+
+    /// The file `discover` reads for the project at `path`
+    pub fn discover_file(path: &Path) -> Option<PathBuf> {
+        let root = if path.is_file() { path.parent().unwrap_or(Path::new(".")) } else { path };
+        CONFIG_FILES.iter()
+            .map(|name| root.join(name))
+            .find(|candidate| candidate.is_file())
+    }
+
+    pub fn load(file: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read config file {}", file.display()))?;
+        let mut config: Self = toml::from_str(&content)
+            .with_context(|| format!("Invalid config file {}", file.display()))?;
+        config.health.validate()
+            .with_context(|| format!("Invalid [health] table in {}", file.display()))?;
+        config.root = file.parent().unwrap_or(Path::new(".")).to_path_buf();
+        Ok(config)
+    }
+
+    /// Fill in every option of `args` that was not given on the command line
+    pub fn apply_to(&self, args: &mut AnalyzeArgs, given: impl Fn(&str) -> bool) {
+        if let Some(format) = &self.format {
+            if !given("format") {
+                args.format = format.clone();
+            }
+        }
+        if let Some(profile) = &self.profile {
+            if !given("profile") {
+                args.profile = profile.clone();
+            }
+        }
+        if let Some(min_complexity) = self.min_complexity {
+            if !given("min_complexity") {
+                args.min_complexity = min_complexity;
+            }
+        }
+        if let Some(min_function_statements) = self.min_function_statements {
+            if !given("min_function_statements") {
+                args.min_function_statements = min_function_statements;
+            }
+        }
+        if let Some(include_tests) = self.include_tests {
+            if !given("include_tests") {
+                args.include_tests = include_tests;
+            }
+        }
+        if !given("languages") && !self.languages.is_empty() {
+            args.languages = self.languages.clone();
+        }
+        if !given("exclude_languages") && !self.exclude_languages.is_empty() {
+            args.exclude_languages = self.exclude_languages.clone();
+        }
+        (args.enable_rules, args.disable_rules, args.rule_severity) =
+            self.rules.merged_with(&args.enable_rules, &args.disable_rules, &args.rule_severity);
+        // Gates add up: the file's limit applies alongside any `--fail-on` flags. A duplication
+        // limit would pass unmeasured without clone detection, so it is dropped with a warning
+        let duplication_measured = RuleSet::from_overrides(&args.enable_rules, &args.disable_rules)
+            .is_ok_and(|rules| rules.is_enabled(rules::DUPLICATED_CODE));
+        for gate in self.gates() {
+            if duplication_measured || !gate.starts_with("duplication") {
+                args.fail_on.push(gate);
+            } else {
+                eprintln!("Warning: gate {} from the config ignored - the duplicated-code rule is disabled", gate);
+            }
+        }
+    }
+
+    /// Gates in `--fail-on` syntax: `fail_on` and `max_duplication_pct`
+    pub fn gates(&self) -> Vec<String> {
+        self.max_duplication_pct.iter()
+            .map(|max| format!("duplication>{}", max))
+            .chain(self.fail_on.iter().cloned())
+            .collect()
+    }
+
+    /// The `[error_handling]` weights with `--error-handling` flags on top
+    pub fn error_handling(&self, flags: &[String]) -> Result<ErrorHandling> {
+        let mut error_handling = ErrorHandling::from_config(&self.error_handling)
+            .context("Invalid [error_handling] table")?;
+        error_handling.override_with(flags)?;
+        Ok(error_handling)
+    }
+
+    /// Settings with no command-line equivalent: exclusions, thresholds, suppressions and health weights
+    pub fn configure(&self, mut builder: AnalysisConfigBuilder) -> AnalysisConfigBuilder {
+        let thresholds = self.thresholds.apply(&Thresholds::default());
+        for path in &self.exclude {
+            builder = builder.exclude_path(path);
+        }
+        for entry in &self.suppress {
+            let mut suppression = entry.clone();
+            suppression.path = entry.path.as_ref().map(|path| self.root.join(path));
+            builder = builder.suppression(suppression);
+        }
+        for entry in &self.overrides {
+            builder = builder.directory_thresholds(self.root.join(&entry.path), entry.thresholds.apply(&thresholds));
+        }
+        builder.thresholds(thresholds).health_model(self.health.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codemetrics_core::analyzers::AnalysisConfig;
+    use crate::{Cli, Commands};
+    use clap::Parser;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_config_file_is_layered_under_cli_flags() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join(".codemetricsrc"), r#"
+            exclude = ["vendor"]
+            format = "json"
+            min_complexity = 8
+            min_function_statements = 3
+            languages = ["rust"]
+            max_duplication_pct = 5
+            fail_on = ["issues.critical>0"]
+
+            [thresholds]
+            function_complexity_warn = 12
+
+            [health]
+            docs = 0.5
+
+            [[overrides]]
+            path = "legacy"
+            thresholds = { function_complexity_error = 40 }
+
+            [[suppress]]
+            rule = "CM0101"
+            path = "legacy/parser.rs"
+            until = "2025-06-01"
+        "#).unwrap();
+
+        let config = ProjectConfig::discover(temp_dir.path()).unwrap().unwrap();
+        let cli = Cli::try_parse_from(["codemetrics", "analyze", "--format", "html", "."]).unwrap();
+        let Commands::Analyze(mut args) = cli.command else {
+            unreachable!()
+        };
+        config.apply_to(&mut args, |id| id == "format");
+        assert_eq!(args.format, "html");
+        assert_eq!(args.min_complexity, 8);
+        assert_eq!(args.min_function_statements, 3);
+        assert_eq!(args.languages, vec!["rust"]);
+        assert_eq!(args.fail_on, vec!["duplication>5", "issues.critical>0"]);
+
+        let analysis = config.configure(AnalysisConfig::builder()).build().unwrap();
+        assert!(analysis.excluded_paths.contains(&"vendor".to_string()));
+        assert_eq!(analysis.thresholds.function_complexity_warn, 12);
+        assert_eq!((analysis.health_model.docs, analysis.health_model.tests), (0.5, 0.2));
+        let legacy = &analysis.directory_thresholds[0];
+        assert_eq!(legacy.directory, temp_dir.path().join("legacy"));
+        assert_eq!((legacy.thresholds.function_complexity_warn, legacy.thresholds.function_complexity_error), (12, 40));
+        assert_eq!(analysis.suppressions[0].path, Some(temp_dir.path().join("legacy/parser.rs")));
+
+        std::fs::write(temp_dir.path().join("codemetrics.toml"), "max_complexity = 3").unwrap();
+        assert!(ProjectConfig::discover(temp_dir.path()).is_err());
+        std::fs::write(temp_dir.path().join("codemetrics.toml"), "[health]\ndeps = -1").unwrap();
+        assert!(ProjectConfig::discover(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_rule_settings_yield_to_cli_flags() {
+        let config: ProjectConfig = toml::from_str(r#"
+            [rules]
+            enable = ["CM0103"]
+            disable = ["long-function", "deep-nesting"]
+            severity = { too-many-parameters = "error" }
+        "#).unwrap();
+        let cli = Cli::try_parse_from([
+            "codemetrics", "analyze", "--enable-rules", "deep-nesting", "--rule-severity", "too-many-parameters=info", ".",
+        ]).unwrap();
+        let Commands::Analyze(mut args) = cli.command else {
+            unreachable!()
+        };
+        config.apply_to(&mut args, |_| false);
+
+        assert_eq!(args.enable_rules, vec!["CM0103", "deep-nesting"]);
+        assert_eq!(args.disable_rules, vec!["long-function"]);
+        let mut rules = RuleSet::from_overrides(&args.enable_rules, &args.disable_rules).unwrap();
+        rules.override_severities(&args.rule_severity).unwrap();
+        assert_eq!(rules.severity("CM0201"), Some(&codemetrics_core::core::IssueSeverity::Info));
+
+        let thresholds = ThresholdOverrides::parse(&["max_parameters=5".to_string()]).unwrap();
+        assert_eq!(thresholds.apply(&Thresholds::default()).max_parameters, 5);
+        assert!(ThresholdOverrides::parse(&["max_params=5".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_duplication_limit_is_dropped_without_clone_detection() {
+        let config: ProjectConfig = toml::from_str(r#"
+            max_duplication_pct = 5
+            fail_on = ["issues.critical>0"]
+        "#).unwrap();
+        let cli = Cli::try_parse_from(["codemetrics", "analyze", "--disable-rules", "duplicated-code", "."]).unwrap();
+        let Commands::Analyze(mut args) = cli.command else {
+            unreachable!()
+        };
+        config.apply_to(&mut args, |_| false);
+        assert_eq!(args.fail_on, vec!["issues.critical>0"]);
+    }
+}
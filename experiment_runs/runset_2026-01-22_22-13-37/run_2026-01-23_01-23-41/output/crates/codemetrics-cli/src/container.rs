@@ -0,0 +1,107 @@
+//! Machine-readable outcome of `codemetrics scan`, the container entrypoint
+//!
+//! A container step has no one watching its terminal, so the scan writes its
+//! reports into the output directory and sums the run up in `status.json`
+//! next to them. The process exit code carries the same verdict: 0 when every
+//! gate passed, 1 when a gate failed, 2 when the scan itself could not run.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+use codemetrics_core::analyzers::AnalysisResults;
+use codemetrics_core::gates::GateFailure;
+
+pub const STATUS_FILE: &str = "status.json";
+
+pub const EXIT_PASSED: i32 = 0;
+pub const EXIT_GATE_FAILED: i32 = 1;
+pub const EXIT_ERROR: i32 = 2;
+
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct ScanStatus {
+    pub version: u32,
+    /// `passed`, `gate_failed` or `error`
+    pub status: &'static str,
+    pub exit_code: i32,
+    pub files_analyzed: usize,
+    pub issues: usize,
+    pub parse_errors: usize,
+    pub gate_failures: Vec<FailedGate>,
+    /// Report files written next to the status, e.g. `report.sarif`
+    pub artifacts: Vec<String>,
+    /// Why the scan could not run, for `error`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FailedGate {
+    pub gate: String,
+    pub actual: f64,
+}
+
+impl ScanStatus {
+    pub fn finished(results: &AnalysisResults, failures: &[GateFailure], artifacts: Vec<String>) -> Self {
+        let (status, exit_code) = if failures.is_empty() {
+            ("passed", EXIT_PASSED)
+        } else {
+            ("gate_failed", EXIT_GATE_FAILED)
+        };
+        Self {
+            version: FORMAT_VERSION,
+            status,
+            exit_code,
+            files_analyzed: results.files_analyzed,
+            issues: results.issue_count(),
+            parse_errors: results.errors.len(),
+            gate_failures: failures.iter()
+                .map(|failure| FailedGate { gate: failure.gate.to_string(), actual: failure.actual })
+                .collect(),
+            artifacts,
+            error: None,
+        }
+    }
+
+    pub fn error(error: &anyhow::Error) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            status: "error",
+            exit_code: EXIT_ERROR,
+            files_analyzed: 0,
+            issues: 0,
+            parse_errors: 0,
+            gate_failures: Vec::new(),
+            artifacts: Vec::new(),
+            error: Some(format!("{:#}", error)),
+        }
+    }
+
+    /// Write `status.json` into `dir`, creating it if needed
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(STATUS_FILE);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_status_is_machine_readable() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let error = anyhow::anyhow!("no such directory").context("Failed to analyze /src");
+        ScanStatus::error(&error).write(temp_dir.path()).unwrap();
+
+        let status: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(temp_dir.path().join(STATUS_FILE)).unwrap()).unwrap();
+        assert_eq!(status["status"], "error");
+        assert_eq!(status["exit_code"], EXIT_ERROR);
+        assert_eq!(status["error"], "Failed to analyze /src: no such directory");
+    }
+}
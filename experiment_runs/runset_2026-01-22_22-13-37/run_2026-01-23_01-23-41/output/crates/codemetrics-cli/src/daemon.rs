@@ -0,0 +1,325 @@
+//! Warm-start analysis daemon and the thin client that talks to it
+//!
+//! Every `codemetrics analyze` pays for process start-up, for compiling the
+//! tree-sitter queries of each grammar and for setting up a parser per
+//! grammar per thread. `daemon --listen <SOCKET>` pays once: compiled queries
+//! stay in the process-wide query cache, the parsing threads and their
+//! parsers are shared by every request, and the last reports are kept in
+//! memory. `client --socket <SOCKET> analyze ...` forwards its arguments, its
+//! working directory and its `CODEMETRICS_*` variables over the Unix socket
+//! and prints the report the daemon renders. When no source file, config,
+//! baseline, history or git ref has changed since the same request last ran,
+//! the stored report is returned without analyzing anything.
+//!
+//! One line of JSON each way: the request
+//! `{"cwd": "/repo", "args": ["analyze", ".", "--format", "json"], "env": {}}`
+//! and the response `{"output": "...", "error": null, "cached": false}`.
+//! Relative paths in the request are resolved against its `cwd`; the daemon's
+//! own working directory and environment are never changed or consulted.
+
+use anyhow::{Context, Result};
+use clap::{CommandFactory, FromArgMatches};
+use serde::{Deserialize, Serialize};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use codemetrics_core::analyzers::CodeAnalyzer;
+use codemetrics_core::gates::Gate;
+use codemetrics_core::{baseline, history};
+use codemetrics_report::sink::MemorySink;
+use codemetrics_report::Reporter;
+
+use crate::config::CONFIG_FILES;
+use crate::{deliver_to, run_analysis, AnalyzeArgs, Cli, Commands};
+
+/// Responses kept for repeated requests; the least recently used goes first
+const REMEMBERED: usize = 32;
+
+/// How long a client may take to send its request line
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Prefix of the variables `analyze` reads its options from
+const ENV_PREFIX: &str = "CODEMETRICS_";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Request {
+    cwd: PathBuf,
+    args: Vec<String>,
+    /// The client's `CODEMETRICS_*` variables
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Response {
+    /// The rendered report, empty when the run failed before reporting
+    output: String,
+    /// Why the run failed, including quality gate failures after the report
+    error: Option<String>,
+    /// Served from the previous identical run
+    #[serde(default)]
+    cached: bool,
+}
+
+/// State kept between requests
+#[derive(Default)]
+pub struct Daemon {
+    /// Recent requests, most recently used last, with the fingerprint each response was computed for
+    last: VecDeque<(Request, u64, Response)>,
+}
+
+impl Daemon {
+    fn handle(&mut self, request: &Request) -> Response {
+        self.run(request).unwrap_or_else(|error| Response {
+            output: String::new(),
+            error: Some(format!("{:#}", error)),
+            cached: false,
+        })
+    }
+
+    fn run(&mut self, request: &Request) -> Result<Response> {
+        let (matches, Commands::Analyze(mut args)) = parse(request)? else {
+            anyhow::bail!("The daemon only runs `analyze`");
+        };
+        resolve_paths(&mut args, &request.cwd);
+        if matches!(args.format.as_str(), "parquet" | "xlsx") {
+            anyhow::bail!("--format {} is binary; run it without the daemon", args.format);
+        }
+        if args.format == "ndjson" {
+            anyhow::bail!("--format ndjson streams while analyzing; run it without the daemon");
+        }
+        // Rejected before the (possibly long) analysis runs
+        args.fail_on.iter().try_for_each(|spec| Gate::parse(spec).map(drop))?;
+
+        let fingerprint = fingerprint(&args.path)?;
+        let remembered = self.last.iter().position(|(previous, _, _)| previous == request)
+            .and_then(|index| self.last.remove(index));
+        if let Some((_, previous, response)) = remembered.filter(|(_, previous, _)| *previous == fingerprint) {
+            self.last.push_back((request.clone(), previous, response.clone()));
+            return Ok(Response { cached: true, ..response });
+        }
+
+        let results = run_analysis(&mut args, matches.subcommand_matches("analyze"))?;
+        let mut sink = MemorySink::default();
+        let outcome = deliver_to(&args, results, &mut sink);
+        let output = sink.get(&Reporter::new(&args.format).artifact_name())
+            .map(|content| String::from_utf8_lossy(content).into_owned())
+            .unwrap_or_default();
+        let response = Response {
+            output,
+            error: outcome.err().map(|error| format!("{:#}", error)),
+            cached: false,
+        };
+        self.last.push_back((request.clone(), fingerprint, response.clone()));
+        if self.last.len() > REMEMBERED {
+            self.last.pop_front();
+        }
+        Ok(response)
+    }
+}
+
+/// The request's command line, with its client's `CODEMETRICS_*` variables in place of the daemon's
+///
+/// Options set by a variable are appended as flags, unless the arguments
+/// already give them, which is the precedence the variables have when
+/// `analyze` runs on its own.
+fn parse(request: &Request) -> Result<(ArgMatches, Commands)> {
+    let mut from_env = Vec::new();
+    let command = Cli::command().mut_subcommand("analyze", |analyze| analyze.mut_args(|arg| {
+        let value = arg.get_env().and_then(|name| request.env.get(name.to_str()?));
+        if let (Some(value), Some(long)) = (value, arg.get_long()) {
+            let flag = if arg.get_action().takes_values() {
+                Some(format!("--{}={}", long, value))
+            } else {
+                // Flags read from a variable are off for these, as clap has it
+                (!matches!(value.to_ascii_lowercase().as_str(), "" | "0" | "false" | "no" | "off")).then(|| format!("--{}", long))
+            };
+            from_env.extend(flag.map(|flag| (arg.get_id().to_string(), flag)));
+        }
+        arg.env(None::<&'static str>)
+    }));
+
+    let argv: Vec<String> = std::iter::once("codemetrics".to_string()).chain(request.args.iter().cloned()).collect();
+    let mut matches = command.clone().try_get_matches_from(&argv)?;
+    if let Some(analyze) = matches.subcommand_matches("analyze") {
+        let unset: Vec<String> = from_env.into_iter()
+            .filter(|(id, _)| analyze.value_source(id) != Some(ValueSource::CommandLine))
+            .map(|(_, flag)| flag)
+            .collect();
+        if !unset.is_empty() {
+            matches = command.try_get_matches_from(argv.into_iter().chain(unset))?;
+        }
+    }
+    let cli = Cli::from_arg_matches(&matches)?;
+    Ok((matches, cli.command))
+}
+
+/// Resolve the request's relative paths against the client's working directory
+fn resolve_paths(args: &mut AnalyzeArgs, cwd: &Path) {
+    args.path = cwd.join(&args.path);
+    for path in [&mut args.pinned_bands, &mut args.config, &mut args.cache_dir, &mut args.vocabulary_out, &mut args.baseline, &mut args.history]
+        .into_iter()
+        .flatten()
+    {
+        *path = cwd.join(&*path);
+    }
+}
+
+/// Changes whenever anything an analysis of `root` reads may have changed
+fn fingerprint(root: &Path) -> Result<u64> {
+    let mut paths = CodeAnalyzer::new().discover_files(root)?;
+    // Read by `analyze` without being source files; git refs cover history-based passes
+    let extra = CONFIG_FILES.iter().chain(&[baseline::BASELINE_FILE, history::HISTORY_FILE, ".git/HEAD", ".git/index"]);
+    paths.extend(extra.map(|name| root.join(name)));
+    paths.sort();
+
+    let mut hasher = DefaultHasher::new();
+    for path in paths {
+        path.hash(&mut hasher);
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            metadata.len().hash(&mut hasher);
+            metadata.modified().ok().hash(&mut hasher);
+        }
+    }
+    Ok(hasher.finish())
+}
+
+/// Answer clients on `socket` until the process is stopped
+#[cfg(unix)]
+pub fn serve(socket: &Path) -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            anyhow::bail!("A daemon is already listening on {}", socket.display());
+        }
+        // Left behind by a daemon that did not shut down cleanly
+        std::fs::remove_file(socket)
+            .with_context(|| format!("Failed to remove stale socket {}", socket.display()))?;
+    }
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("Failed to listen on {}", socket.display()))?;
+    eprintln!("Listening on {}", socket.display());
+
+    let mut daemon = Daemon::default();
+    for stream in listener.incoming() {
+        let served = stream.map_err(anyhow::Error::from).and_then(|stream| {
+            // A client that connects and sends nothing would hold up every other one
+            stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line)?;
+            let response = match serde_json::from_str::<Request>(&line) {
+                Ok(request) => daemon.handle(&request),
+                Err(e) => Response { output: String::new(), error: Some(format!("Invalid request: {}", e)), cached: false },
+            };
+            writeln!(&stream, "{}", serde_json::to_string(&response)?)?;
+            Ok(())
+        });
+        if let Err(e) = served {
+            eprintln!("Warning: client connection failed - {:#}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Run `args` (e.g. `analyze . --format json`) on the daemon at `socket` and print the report
+#[cfg(unix)]
+pub fn request(socket: &Path, args: Vec<String>) -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let stream = UnixStream::connect(socket)
+        .with_context(|| format!("No daemon listening on {} (start one with `daemon --listen {}`)", socket.display(), socket.display()))?;
+    let env = std::env::vars().filter(|(name, _)| name.starts_with(ENV_PREFIX)).collect();
+    let request = Request { cwd: std::env::current_dir()?, args, env };
+    writeln!(&stream, "{}", serde_json::to_string(&request)?)?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line).context("Failed to read the daemon's response")?;
+    let response: Response = serde_json::from_str(&line).context("Invalid response from daemon")?;
+    if !response.output.is_empty() {
+        print!("{}", response.output);
+        if !response.output.ends_with('\n') {
+            println!();
+        }
+    }
+    match response.error {
+        Some(error) => Err(anyhow::anyhow!(error)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn serve(_socket: &Path) -> Result<()> {
+    anyhow::bail!("The daemon needs Unix domain sockets, which this platform lacks")
+}
+
+#[cfg(not(unix))]
+pub fn request(_socket: &Path, _args: Vec<String>) -> Result<()> {
+    anyhow::bail!("The daemon needs Unix domain sockets, which this platform lacks")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_unchanged_project_is_answered_from_memory() {
+        let temp_dir = tempdir().unwrap();
+        let source = temp_dir.path().join("lib.rs");
+        std::fs::write(&source, "fn main() {\n    if true { println!(\"hi\"); }\n}\n").unwrap();
+
+        let mut daemon = Daemon::default();
+        let request = Request {
+            cwd: temp_dir.path().to_path_buf(),
+            args: vec!["analyze".to_string(), ".".to_string(), "--no-cache".to_string()],
+            // The client's variables apply, the daemon's (e.g. a CI's own CODEMETRICS_FORMAT) do not
+            env: BTreeMap::from([("CODEMETRICS_FORMAT".to_string(), "json".to_string())]),
+        };
+        let working_dir = std::env::current_dir().unwrap();
+
+        let first = daemon.handle(&request);
+        assert_eq!(first.error, None);
+        assert!(first.output.contains("\"files_analyzed\": 1"));
+        assert!(!first.cached);
+        assert!(daemon.handle(&request).cached);
+        assert_eq!(std::env::current_dir().unwrap(), working_dir);
+
+        std::fs::write(&source, "fn main() {}\nfn other() {}\n").unwrap();
+        assert!(!daemon.handle(&request).cached);
+
+        let rejected = daemon.handle(&Request { args: vec!["languages".to_string()], ..request });
+        assert!(rejected.error.unwrap().contains("only runs `analyze`"));
+    }
+
+    #[test]
+    fn test_command_line_options_win_over_forwarded_variables() {
+        let request = Request {
+            cwd: PathBuf::from("/repo"),
+            args: vec!["analyze".to_string(), "--format".to_string(), "sarif".to_string()],
+            env: BTreeMap::from([
+                ("CODEMETRICS_FORMAT".to_string(), "json".to_string()),
+                ("CODEMETRICS_LANGUAGES".to_string(), "rust,go".to_string()),
+                ("CODEMETRICS_QUIET".to_string(), "false".to_string()),
+                ("CODEMETRICS_NO_CACHE".to_string(), "1".to_string()),
+            ]),
+        };
+        let Ok((_, Commands::Analyze(mut args))) = parse(&request) else {
+            panic!("expected an analyze command");
+        };
+        resolve_paths(&mut args, &request.cwd);
+
+        assert_eq!(args.format, "sarif");
+        assert_eq!(args.languages, ["rust", "go"]);
+        assert!(!args.quiet);
+        assert!(args.no_cache);
+        assert_eq!(args.path, Path::new("/repo/."));
+    }
+}
@@ -0,0 +1,284 @@
+//! `config show`: the settings `analyze` would run with, and where each came from
+//!
+//! Settings are layered: built-in defaults, then the config file, then the
+//! profile, then `CODEMETRICS_*` environment variables and command-line flags. Thresholds can also
+//! change per directory through `[[overrides]]`, and error-handling weights per
+//! language. Every setting lists the layers that changed it, so "why did this
+//! threshold apply?" is answered by one line.
+
+use anyhow::Result;
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use codemetrics_core::analyzers::AnalysisConfig;
+use codemetrics_core::cache;
+use codemetrics_core::core::Language;
+use codemetrics_core::error_handling::{ErrorHandling, ErrorHandlingWeights};
+use codemetrics_core::thresholds::Thresholds;
+
+use crate::config::{ProjectConfig, ThresholdOverrides};
+use crate::profiles::Profile;
+use crate::AnalyzeArgs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Source {
+    Default,
+    ConfigFile,
+    Profile,
+    Environment,
+    Flag,
+}
+
+impl Source {
+    fn label(self) -> &'static str {
+        match self {
+            Source::Default => "default",
+            Source::ConfigFile => "config file",
+            Source::Profile => "profile",
+            Source::Environment => "environment",
+            Source::Flag => "flag",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Setting {
+    pub name: String,
+    pub value: String,
+    /// Every layer that changed the value, lowest first; only `Default` when none did
+    pub sources: Vec<Source>,
+}
+
+impl Setting {
+    fn new(name: impl Into<String>, value: String, sources: Vec<Source>) -> Self {
+        let sources = if sources.is_empty() { vec![Source::Default] } else { sources };
+        Self { name: name.into(), value, sources }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EffectiveConfig {
+    pub config_file: Option<PathBuf>,
+    pub profile: String,
+    pub settings: Vec<Setting>,
+}
+
+impl EffectiveConfig {
+    /// Layer the settings of `args` as `analyze` does, noting what each layer changed
+    pub fn resolve(args: &mut AnalyzeArgs, matches: Option<&ArgMatches>) -> Result<Self> {
+        let source = |id: &str| matches.and_then(|m| m.value_source(id));
+        let given = |id: &str| crate::given(matches, id);
+        // The layer a flag's value came from: the command line, or its `CODEMETRICS_*` variable
+        let set_by = |id: &str| match source(id) {
+            Some(ValueSource::EnvVariable) => Source::Environment,
+            _ => Source::Flag,
+        };
+        let config_file = match &args.config {
+            Some(file) => Some(file.clone()),
+            None => ProjectConfig::discover_file(&args.path),
+        };
+        let project_config = config_file.as_deref().map(ProjectConfig::load).transpose()?.unwrap_or_default();
+
+        let defaults = options(args);
+        project_config.apply_to(args, given);
+        let from_file = options(args);
+        Profile::parse(&args.profile)?.apply_to(args, given);
+        let effective = options(args);
+
+        let mut settings = Vec::new();
+        for (((name, default), (_, file)), (_, value)) in defaults.into_iter().zip(from_file).zip(effective) {
+            let mut sources = Vec::new();
+            if file != default {
+                sources.push(Source::ConfigFile);
+            }
+            if value != file {
+                sources.push(Source::Profile);
+            }
+            match source(name) {
+                Some(ValueSource::EnvVariable) => sources.push(Source::Environment),
+                Some(ValueSource::CommandLine) => sources.push(Source::Flag),
+                _ => {}
+            }
+            settings.push(Setting::new(name, value, sources));
+        }
+
+        let file_thresholds = &project_config.thresholds;
+        let flag_thresholds = ThresholdOverrides::parse(&args.threshold)?;
+        let thresholds = flag_thresholds.apply(&file_thresholds.apply(&Thresholds::default()));
+        let layers = ThresholdOverrides::of(&thresholds).entries().into_iter()
+            .zip(file_thresholds.entries())
+            .zip(flag_thresholds.entries());
+        for (((name, value), (_, file)), (_, flag)) in layers {
+            let sources = [(file, Source::ConfigFile), (flag, set_by("threshold"))].into_iter()
+                .filter_map(|(set, source)| set.map(|_| source))
+                .collect();
+            settings.push(Setting::new(format!("thresholds.{}", name), value.unwrap_or_default().to_string(), sources));
+        }
+        for entry in &project_config.overrides {
+            for (name, value) in entry.thresholds.entries() {
+                if let Some(value) = value {
+                    let name = format!("thresholds.{} in {}", name, entry.path.display());
+                    settings.push(Setting::new(name, value.to_string(), vec![Source::ConfigFile]));
+                }
+            }
+        }
+
+        let builtin = ErrorHandling::default();
+        let from_file = project_config.error_handling(&[])?;
+        let error_handling = project_config.error_handling(&args.error_handling)?;
+        for language in [Language::Rust, Language::JavaScript, Language::TypeScript, Language::Python, Language::Go, Language::Ruby] {
+            let (default, file, value) = (builtin.for_language(&language), from_file.for_language(&language), error_handling.for_language(&language));
+            let mut sources = Vec::new();
+            if file != default {
+                sources.push(Source::ConfigFile);
+            }
+            if value != file {
+                sources.push(set_by("error_handling"));
+            }
+            let name = format!("error_handling.{:?}", language).to_lowercase();
+            settings.push(Setting::new(name, weights(value), sources));
+        }
+
+        Ok(Self { config_file, profile: args.profile.clone(), settings })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let config_file = self.config_file.as_deref().map_or_else(|| "(none)".to_string(), |file| file.display().to_string());
+        let _ = writeln!(out, "Config file: {}", config_file);
+        let _ = writeln!(out, "Profile:     {}", self.profile);
+        let _ = writeln!(out);
+
+        let name_width = self.settings.iter().map(|setting| setting.name.len()).max().unwrap_or(0);
+        let value_width = self.settings.iter().map(|setting| setting.value.len()).max().unwrap_or(0).min(48);
+        for setting in &self.settings {
+            let sources: Vec<&str> = setting.sources.iter().map(|source| source.label()).collect();
+            let _ = writeln!(out, "{:<name_width$}  {:<value_width$}  {}", setting.name, setting.value, sources.join(" + "));
+        }
+        out
+    }
+}
+
+/// Command-line options that a config file or profile can also set, by clap ID
+fn options(args: &AnalyzeArgs) -> Vec<(&'static str, String)> {
+    let list = |values: &[String]| if values.is_empty() { "(none)".to_string() } else { values.join(", ") };
+    let path = |path: &Option<PathBuf>| path.as_deref().map_or_else(|| "(none)".to_string(), |path: &Path| path.display().to_string());
+    vec![
+        ("format", args.format.clone()),
+        ("profile", args.profile.clone()),
+        ("languages", list(&args.languages)),
+        ("exclude_languages", list(&args.exclude_languages)),
+        ("include_tests", args.include_tests.to_string()),
+        ("min_complexity", args.min_complexity.to_string()),
+        ("min_function_statements", args.min_function_statements.to_string()),
+        ("enable_rules", list(&args.enable_rules)),
+        ("disable_rules", list(&args.disable_rules)),
+        ("rule_severity", list(&args.rule_severity)),
+        ("severity_bands", args.severity_bands.clone()),
+        ("pinned_bands", path(&args.pinned_bands)),
+        ("fail_on", list(&args.fail_on)),
+        ("snippet_lines", args.snippet_lines.to_string()),
+        ("cluster_threshold", args.cluster_threshold.to_string()),
+        ("stability", args.stability.to_string()),
+        ("hotspots", args.hotspots.to_string()),
+        ("hotspot_days", args.hotspot_days.to_string()),
+        ("line_complexity", args.line_complexity.to_string()),
+        ("vocabulary", args.vocabulary.to_string()),
+        ("types", args.types.to_string()),
+        ("rust_api", args.rust_api.to_string()),
+        ("call_graph", args.call_graph.to_string()),
+        ("baseline", path(&args.baseline)),
+        ("cache_dir", path(&if args.no_cache { None } else { args.cache_dir.clone().or_else(cache::user_cache_dir) })),
+        ("max_file_size", args.max_file_size.unwrap_or(AnalysisConfig::default().max_file_size).to_string()),
+        ("jobs", args.jobs.map_or_else(|| "(one per CPU)".to_string(), |jobs| jobs.to_string())),
+        ("history", path(&args.history)),
+    ]
+}
+
+fn weights(weights: &ErrorHandlingWeights) -> String {
+    format!(
+        "try={} handler={} finally={} propagation={} error_check={}",
+        weights.try_block, weights.handler, weights.finally, weights.propagation, weights.error_check
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cli, ConfigCommand, Commands};
+    use clap::{CommandFactory, FromArgMatches};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_each_setting_names_the_layers_that_changed_it() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("codemetrics.toml"), r#"
+            format = "json"
+            min_complexity = 8
+
+            [thresholds]
+            max_parameters = 4
+
+            [error_handling.languages.go]
+            error_check = 0
+
+            [[overrides]]
+            path = "legacy"
+            thresholds = { function_complexity_warn = 25 }
+        "#).unwrap();
+
+        let dir = temp_dir.path().to_string_lossy().into_owned();
+        let argv = ["codemetrics", "config", "show", dir.as_str(), "--min-complexity", "3", "--profile", "deep", "--threshold", "max_parameters=6"];
+        let matches = Cli::command().try_get_matches_from(argv).unwrap();
+        let Commands::Config(ConfigCommand::Show(mut args)) = Cli::from_arg_matches(&matches).unwrap().command else {
+            unreachable!()
+        };
+        let matches = matches.subcommand_matches("config").and_then(|m| m.subcommand_matches("show"));
+        let effective = EffectiveConfig::resolve(&mut args.analyze, matches).unwrap();
+
+        let setting = |name: &str| {
+            let setting = effective.settings.iter().find(|setting| setting.name == name).unwrap();
+            (setting.value.as_str(), setting.sources.clone())
+        };
+        assert_eq!(effective.config_file, Some(temp_dir.path().join("codemetrics.toml")));
+        assert_eq!(setting("format"), ("json", vec![Source::ConfigFile]));
+        assert_eq!(setting("min_complexity"), ("3", vec![Source::Flag]));
+        assert_eq!(setting("vocabulary"), ("true", vec![Source::Profile]));
+        assert_eq!(setting("call_graph"), ("true", vec![Source::Profile]));
+        assert_eq!(setting("snippet_lines"), ("3", vec![Source::Default]));
+        assert_eq!(setting("thresholds.max_parameters"), ("6", vec![Source::ConfigFile, Source::Flag]));
+        assert_eq!(setting("thresholds.max_nesting_depth").1, vec![Source::Default]);
+        assert_eq!(setting("error_handling.go").1, vec![Source::ConfigFile]);
+        assert!(effective.settings.iter().any(|setting| setting.name.starts_with("thresholds.function_complexity_warn in ") && setting.value == "25"));
+        assert!(effective.to_text().contains("config file + flag"));
+    }
+
+    #[test]
+    fn test_environment_outranks_the_config_file() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("codemetrics.toml"), "exclude_languages = [\"js\"]\n").unwrap();
+        // Tests run in parallel and share the environment; no other test depends on these two
+        std::env::set_var("CODEMETRICS_EXCLUDE_LANGUAGES", "python");
+        std::env::set_var("CODEMETRICS_JOBS", "2");
+
+        let dir = temp_dir.path().to_string_lossy().into_owned();
+        let matches = Cli::command().try_get_matches_from(["codemetrics", "config", "show", dir.as_str()]);
+        std::env::remove_var("CODEMETRICS_EXCLUDE_LANGUAGES");
+        std::env::remove_var("CODEMETRICS_JOBS");
+        let matches = matches.unwrap();
+        let Commands::Config(ConfigCommand::Show(mut args)) = Cli::from_arg_matches(&matches).unwrap().command else {
+            unreachable!()
+        };
+        let matches = matches.subcommand_matches("config").and_then(|m| m.subcommand_matches("show"));
+        let effective = EffectiveConfig::resolve(&mut args.analyze, matches).unwrap();
+
+        let setting = |name: &str| effective.settings.iter().find(|setting| setting.name == name).unwrap();
+        assert_eq!(setting("exclude_languages").value, "python");
+        assert_eq!(setting("exclude_languages").sources, vec![Source::Environment]);
+        assert_eq!((setting("jobs").value.as_str(), setting("jobs").sources.clone()), ("2", vec![Source::Environment]));
+    }
+}
@@ -0,0 +1,305 @@
+//! `init`: a starting `codemetrics.toml` written from what the project contains
+//!
+//! The repository is inspected the way `analyze` would see it (same discovery,
+//! same ignore rules): which languages it has and how much of each, where its
+//! tests live, whether it is a monorepo and which directories hold vendored
+//! code. The detected facts go into comments next to the settings they
+//! informed, so the file explains itself to whoever reviews it. A CI job that
+//! runs the analysis can be written alongside.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::{Component, Path};
+
+use codemetrics_core::analyzers::{self, AnalysisConfig, CodeAnalyzer};
+
+use crate::config::{ProjectConfig, CONFIG_FILES};
+
+/// Directory names that hold a project's tests
+const TEST_DIRECTORIES: [&str; 5] = ["tests", "test", "__tests__", "spec", "specs"];
+
+/// Directory names of copied-in third-party or generated code
+const VENDORED_DIRECTORIES: [&str; 4] = ["vendor", "third_party", "generated", "__generated__"];
+
+/// Files whose presence makes a directory a package of its own
+const MANIFESTS: [&str; 6] = ["Cargo.toml", "package.json", "go.mod", "pyproject.toml", "setup.py", "Gemfile"];
+
+/// Above this many files the quick profile keeps runs short
+const LARGE_PROJECT_FILES: usize = 20_000;
+
+/// What `init` found in the project
+#[derive(Debug, Default)]
+pub struct Detected {
+    pub files: usize,
+    /// File count per language, most files first
+    pub languages: Vec<(&'static str, usize)>,
+    /// Test directories relative to the root, e.g. `tests` or `web/__tests__`
+    pub test_directories: BTreeSet<String>,
+    /// Test files next to the code they test (`foo_test.go`, `foo.test.ts`, `test_foo.py`)
+    pub colocated_tests: usize,
+    /// The root file that declares a workspace, when there is one
+    pub workspace: Option<&'static str>,
+    /// Directories below the root with a manifest of their own
+    pub packages: BTreeSet<String>,
+    pub vendored: BTreeSet<String>,
+}
+
+/// Inspect the project at `root`
+pub fn detect(root: &Path) -> Result<Detected> {
+    let config = AnalysisConfig::builder().include_tests(true).quiet(true).build()?;
+    let files = CodeAnalyzer::with_config(config).discover_files(root)?;
+
+    let mut detected = Detected { files: files.len(), workspace: workspace_marker(root), ..Detected::default() };
+    let mut languages: BTreeMap<&'static str, usize> = BTreeMap::new();
+    for file in &files {
+        let relative = file.strip_prefix(root).unwrap_or(file);
+        if let Some(language) = file.extension().and_then(|extension| extension.to_str()).and_then(analyzers::language_for_extension) {
+            *languages.entry(language).or_insert(0) += 1;
+        }
+
+        let directories: Vec<&str> = relative.parent().into_iter()
+            .flat_map(Path::components)
+            .filter_map(|component| match component {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            })
+            .collect();
+        if let Some(index) = directories.iter().position(|name| TEST_DIRECTORIES.contains(name)) {
+            detected.test_directories.insert(directories[..=index].join("/"));
+        } else if is_colocated_test(relative) {
+            detected.colocated_tests += 1;
+        }
+        if let Some(index) = directories.iter().position(|name| VENDORED_DIRECTORIES.contains(name)) {
+            detected.vendored.insert(directories[..=index].join("/"));
+        }
+        for depth in 1..=directories.len() {
+            let directory = directories[..depth].join("/");
+            if !detected.packages.contains(&directory) && MANIFESTS.iter().any(|manifest| root.join(&directory).join(manifest).is_file()) {
+                detected.packages.insert(directory);
+            }
+        }
+    }
+
+    detected.languages = languages.into_iter().collect();
+    detected.languages.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    Ok(detected)
+}
+
+impl Detected {
+    /// A commented config file holding the detected defaults
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# codemetrics configuration, generated by `codemetrics init`");
+        let _ = writeln!(out, "# Flags and CODEMETRICS_* variables override anything set here;");
+        let _ = writeln!(out, "# `codemetrics config show` prints the merged result.");
+        let _ = writeln!(out);
+
+        let _ = writeln!(out, "# Detected {} source files:", self.files);
+        for (language, count) in &self.languages {
+            let _ = writeln!(out, "#   {:<12} {}", language, count);
+        }
+        if self.languages.len() > 1 {
+            let _ = writeln!(out, "# Uncomment to analyze only some of them");
+        } else {
+            let _ = writeln!(out, "# Listing languages keeps files in other languages from being analyzed");
+        }
+        let names: Vec<String> = self.languages.iter().map(|(language, _)| format!("{:?}", language.to_lowercase())).collect();
+        let _ = writeln!(out, "# languages = [{}]", names.join(", "));
+        let _ = writeln!(out);
+
+        if self.files > LARGE_PROJECT_FILES {
+            let _ = writeln!(out, "# Over {} files: the quick profile measures size and complexity only", LARGE_PROJECT_FILES);
+            let _ = writeln!(out, "profile = \"quick\"");
+        } else {
+            let _ = writeln!(out, "# quick (size, complexity), standard (+issues, dependencies) or deep (+duplication, history, security, call graph, types)");
+            let _ = writeln!(out, "profile = \"standard\"");
+        }
+        let _ = writeln!(out);
+
+        let mut tests: Vec<String> = self.test_directories.iter().map(|directory| format!("{}/", directory)).collect();
+        if self.colocated_tests > 0 {
+            tests.push(format!("{} files next to the code they test", self.colocated_tests));
+        }
+        if tests.is_empty() {
+            let _ = writeln!(out, "# No tests found");
+        } else {
+            let _ = writeln!(out, "# Tests: {}", tests.join(", "));
+        }
+        let _ = writeln!(out, "# Test code is left out of the metrics unless this is true");
+        let _ = writeln!(out, "include_tests = false");
+        let _ = writeln!(out);
+
+        if self.vendored.is_empty() {
+            let _ = writeln!(out, "# Paths to skip besides node_modules, target, dist and build");
+            let _ = writeln!(out, "exclude = []");
+        } else {
+            let _ = writeln!(out, "# Vendored and generated code found; skipped besides node_modules, target, dist and build");
+            let paths: Vec<String> = self.vendored.iter().map(|path| format!("{:?}", path)).collect();
+            let _ = writeln!(out, "exclude = [{}]", paths.join(", "));
+        }
+        let _ = writeln!(out);
+
+        let _ = writeln!(out, "# Exit nonzero when a gate holds, on every run");
+        let _ = writeln!(out, "fail_on = [\"issues.critical>0\"]");
+        let _ = writeln!(out);
+
+        let _ = writeln!(out, "[thresholds]");
+        let _ = writeln!(out, "# Defaults shown; lower them as the code improves");
+        let _ = writeln!(out, "# function_complexity_warn = 10");
+        let _ = writeln!(out, "# max_parameters = 5");
+
+        if self.workspace.is_some() || self.packages.len() > 1 {
+            let _ = writeln!(out);
+            match self.workspace {
+                Some(marker) => { let _ = writeln!(out, "# Monorepo ({}) with {} packages:", marker, self.packages.len()); }
+                None => { let _ = writeln!(out, "# {} packages:", self.packages.len()); }
+            }
+            for package in &self.packages {
+                let _ = writeln!(out, "#   {}", package);
+            }
+            let _ = writeln!(out, "# Give a package its own thresholds, e.g. while it is being cleaned up");
+            let example = self.packages.iter().next().map_or("packages/legacy", String::as_str);
+            let _ = writeln!(out, "# [[overrides]]");
+            let _ = writeln!(out, "# path = {:?}", example);
+            let _ = writeln!(out, "# thresholds = {{ function_complexity_warn = 20 }}");
+        }
+        out
+    }
+}
+
+/// Where a CI job for `provider` goes and what it runs
+pub fn ci_job(provider: &str) -> Result<(&'static str, &'static str)> {
+    match provider {
+        "github" => Ok((".github/workflows/codemetrics.yml", GITHUB_WORKFLOW)),
+        "gitlab" => Ok((".gitlab/codemetrics.yml", GITLAB_JOB)),
+        other => anyhow::bail!("Unknown CI provider '{}' (expected github or gitlab)", other),
+    }
+}
+
+const GITHUB_WORKFLOW: &str = r#"name: codemetrics
+on: [push, pull_request]
+
+jobs:
+  codemetrics:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          fetch-depth: 0  # history for hotspots and stability
+      - uses: dtolnay/rust-toolchain@stable
+      - run: cargo install --git https://github.com/alice-bob/codemetrics codemetrics-cli
+      # Findings become annotations; fail_on in codemetrics.toml decides the exit code
+      - run: codemetrics analyze . --format github
+"#;
+
+const GITLAB_JOB: &str = r#"# Add to .gitlab-ci.yml:
+#   include:
+#     - local: .gitlab/codemetrics.yml
+codemetrics:
+  image: rust:latest
+  script:
+    - cargo install --git https://github.com/alice-bob/codemetrics codemetrics-cli
+    # fail_on in codemetrics.toml decides the exit code
+    - codemetrics analyze . --format gitlab > gl-code-quality-report.json
+  artifacts:
+    when: always
+    reports:
+      codequality: gl-code-quality-report.json
+"#;
+
+/// Write the config (and CI job) for the project at `root`, or print them with `print`
+pub fn run(root: &Path, ci: Option<&str>, force: bool, print: bool) -> Result<()> {
+    let ci = ci.map(ci_job).transpose()?;
+    let config = detect(root)?.to_toml();
+    // What `init` writes must load, or the next `analyze` would fail on it
+    toml::from_str::<ProjectConfig>(&config).context("Generated config does not parse")?;
+
+    let mut files = vec![(CONFIG_FILES[0], config.as_str())];
+    files.extend(ci);
+    if print {
+        for (path, content) in files {
+            println!("# --- {}\n{}", path, content);
+        }
+        return Ok(());
+    }
+
+    if !force {
+        if let Some(existing) = ProjectConfig::discover_file(root) {
+            anyhow::bail!("{} already exists; pass --force to replace it", existing.display());
+        }
+        if let Some((path, _)) = files.iter().skip(1).find(|(path, _)| root.join(path).exists()) {
+            anyhow::bail!("{} already exists; pass --force to replace it", root.join(path).display());
+        }
+    }
+    for (path, content) in files {
+        let path = root.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// The root file that declares a workspace or monorepo, if any
+fn workspace_marker(root: &Path) -> Option<&'static str> {
+    let contains = |file: &str, needle: &str| std::fs::read_to_string(root.join(file)).is_ok_and(|content| content.contains(needle));
+    if contains("Cargo.toml", "[workspace]") {
+        Some("Cargo.toml [workspace]")
+    } else if contains("package.json", "\"workspaces\"") {
+        Some("package.json workspaces")
+    } else {
+        ["pnpm-workspace.yaml", "lerna.json", "nx.json", "go.work"].into_iter()
+            .find(|file| root.join(file).is_file())
+    }
+}
+
+/// `foo_test.go`, `foo.test.ts`, `foo.spec.js`, `test_foo.py`, `foo_spec.rb`
+fn is_colocated_test(path: &Path) -> bool {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let stem = name.split('.').next().unwrap_or_default();
+    stem.ends_with("_test") || stem.ends_with("_spec") || stem.starts_with("test_")
+        || name.contains(".test.") || name.contains(".spec.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_init_describes_a_monorepo_and_writes_a_loadable_config() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n");
+        write("crates/core/Cargo.toml", "[package]\nname = \"core\"\n");
+        write("crates/core/src/lib.rs", "pub fn add(a: i32, b: i32) -> i32 { a + b }\n");
+        write("crates/core/tests/add.rs", "#[test]\nfn adds() {}\n");
+        write("web/package.json", "{}\n");
+        write("web/src/app.ts", "export const app = 1;\n");
+        write("web/src/app.test.ts", "test('app', () => {});\n");
+        write("vendor/lib/util.py", "def util():\n    pass\n");
+
+        let detected = detect(root).unwrap();
+        assert_eq!(detected.workspace, Some("Cargo.toml [workspace]"));
+        assert_eq!(detected.packages, BTreeSet::from(["crates/core".to_string(), "web".to_string()]));
+        assert!(detected.test_directories.contains("crates/core/tests"));
+        assert_eq!(detected.colocated_tests, 1);
+        assert_eq!(detected.vendored, BTreeSet::from(["vendor".to_string()]));
+
+        run(root, Some("github"), false, false).unwrap();
+        let config = ProjectConfig::load(&root.join("codemetrics.toml")).unwrap();
+        assert_eq!(config.exclude, vec!["vendor"]);
+        assert!(root.join(".github/workflows/codemetrics.yml").is_file());
+        assert!(run(root, None, false, false).is_err());
+    }
+}
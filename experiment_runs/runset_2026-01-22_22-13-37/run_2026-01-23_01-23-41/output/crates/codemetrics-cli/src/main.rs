@@ -0,0 +1,1730 @@
+use anyhow::{Context, Result};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::Shell;
+use std::path::{Path, PathBuf};
+
+mod annotate;
+mod config;
+mod container;
+mod daemon;
+mod effective;
+mod init;
+mod profiles;
+mod progress;
+mod rpc;
+mod serve;
+mod watch;
+
+use codemetrics_core::{
+    analyzers, anonymize, attribution, baseline, build_graph, cache, capabilities, clusters, gates, history, hotspots, parsers,
+    release_report, rules, schema, shard, snippets, stability, stack, thresholds, vcs, vocabulary,
+};
+use codemetrics_core::cargo_workspace::CargoWorkspace;
+use codemetrics_core::custom_rules;
+use codemetrics_core::cycles;
+use codemetrics_core::dead_code;
+use codemetrics_core::gate_preview;
+use codemetrics_core::dependency_analyzer::{self, DependencyAnalyzer, GraphView};
+use codemetrics_core::diff::{DiffSide, ResultsDiff};
+use codemetrics_core::js_project::JsProject;
+use codemetrics_core::language_provider;
+use codemetrics_core::ASTAnalyzer;
+use codemetrics_report::sink::{DirectorySink, OutputSink, StdoutSink};
+use codemetrics_report::reporters::ndjson::NdjsonWriter;
+use codemetrics_report::Reporter;
+
+use analyzers::{AnalysisConfig, AnalysisResults, CodeAnalyzer};
+use attribution::{AttributionGrouping, DateRange};
+use baseline::Baseline;
+use build_graph::BuildGraph;
+use config::{ProjectConfig, ThresholdOverrides};
+use effective::EffectiveConfig;
+use gates::Gate;
+use hotspots::HotspotAnalyzer;
+use parsers::LanguageParser;
+use profiles::{Passes, Profile};
+use release_report::ReleaseReport;
+use rules::RuleSet;
+use snippets::SnippetOptions;
+use stack::{DetectedFramework, StackReport};
+use stability::StabilityClassifier;
+use thresholds::{PercentileBands, SeverityBands};
+
+/// The workspace's `crates` directory, analyzed by `codemetrics self`
+const SELF_SOURCE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/..");
+
+/// A powerful code analysis tool for understanding codebases
+#[derive(Parser)]
+#[command(name = "codemetrics", author, version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Analyze code complexity and metrics
+    Analyze(AnalyzeArgs),
+    /// Re-analyze whenever files change and print what got better or worse
+    Watch(AnalyzeArgs),
+    /// Serve the live dashboard and push metric updates to it as files change
+    Serve(ServeArgs),
+    /// Serve JSON-RPC over stdio for editor plugins (analyzeFile, analyzeBuffer, getThresholds)
+    Rpc(AnalyzeArgs),
+    /// Keep a warm analysis process on a Unix socket for `client` to use
+    Daemon(DaemonArgs),
+    /// Run `analyze` through a running daemon, e.g. `client --socket S analyze . --format json`
+    Client(ClientArgs),
+    /// Record current issues as accepted, so `analyze` only reports new ones
+    #[command(subcommand)]
+    Baseline(BaselineCommand),
+    /// Record analysis snapshots per commit and show how metrics trend
+    #[command(subcommand)]
+    History(HistoryCommand),
+    /// Write a commented codemetrics.toml with defaults detected from the project, and optionally a CI job
+    Init(InitArgs),
+    /// Show which findings and gates would change under another threshold profile
+    Gate(GateArgs),
+    /// Inspect the settings `analyze` runs with
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Generate detailed reports
+    Report(ReportArgs),
+    /// Print a file with per-function complexity headers and deltas vs a revision
+    Annotate(AnnotateArgs),
+    /// Combine the JSON results of `analyze --shard` runs into one result set
+    Merge(MergeArgs),
+    /// Compare per-function metrics of two directories, JSON result files or git revisions
+    Diff(DiffArgs),
+    /// Summarize code health changes between two release tags
+    ReleaseReport(ReleaseReportArgs),
+    /// Attribute complexity added in a date range to workstreams
+    Attribution(AttributionArgs),
+    /// Inspect external dependencies and where they are used
+    #[command(alias = "deps")]
+    Dependencies(DependenciesArgs),
+    /// List functions, types and exports that nothing in the project references
+    DeadCode(DeadCodeArgs),
+    /// Report rule findings per file, including custom rules from .codemetrics/rules.toml
+    Lint(LintArgs),
+    /// Non-interactive entrypoint for container steps: reports and status.json into an output directory
+    Scan(ScanArgs),
+    /// Analyze codemetrics' own source against its checked-in baseline and gates
+    #[command(name = "self")]
+    SelfCheck(SelfCheckArgs),
+    /// Show supported languages and features
+    Languages,
+    /// Print the JSON Schema of `analyze --format json` output, whose `schema_version` it describes
+    Schema,
+    /// List every issue rule with its ID, description and default
+    Rules {
+        #[command(subcommand)]
+        action: Option<RulesCommand>,
+    },
+    /// Print a shell completion script, e.g. `codemetrics completions zsh > _codemetrics`
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Write man pages for codemetrics and every subcommand into DIR
+    #[command(hide = true)]
+    Man {
+        #[arg(value_name = "DIR")]
+        out_dir: PathBuf,
+    },
+}
+
+/// Options that set a setting can also come from a `CODEMETRICS_*` environment
+/// variable, e.g. `CODEMETRICS_FORMAT=json` or `CODEMETRICS_THRESHOLDS=max_parameters=5`.
+/// Either overrides the config file and profile; a flag overrides the variable.
+#[derive(Args)]
+pub struct AnalyzeArgs {
+    /// Path to analyze
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    /// Only analyze these languages, e.g. `--languages rust,python`
+    #[arg(short, long = "languages", alias = "language", env = "CODEMETRICS_LANGUAGES", value_delimiter = ',')]
+    pub languages: Vec<String>,
+
+    /// Skip these languages entirely, e.g. `--exclude-languages js`
+    #[arg(long, env = "CODEMETRICS_EXCLUDE_LANGUAGES", value_delimiter = ',')]
+    pub exclude_languages: Vec<String>,
+
+    /// Output format (text, json, html, markdown, sarif, badge, csv, xlsx, parquet, github, gitlab, ndjson, prometheus)
+    ///
+    /// `ndjson` prints one record per file as soon as it is analyzed, then a summary record.
+    /// `prometheus` prints gauges in the Prometheus text exposition format.
+    /// `csv`, `xlsx` and `parquet` have one row per function.
+    #[arg(short, long, env = "CODEMETRICS_FORMAT", default_value = "text")]
+    pub format: String,
+
+    /// Include test files in analysis
+    #[arg(long, env = "CODEMETRICS_INCLUDE_TESTS")]
+    pub include_tests: bool,
+
+    /// Minimum complexity threshold to report
+    #[arg(long, env = "CODEMETRICS_MIN_COMPLEXITY", default_value = "5")]
+    pub min_complexity: u32,
+
+    /// Leave functions with fewer than N statements out of average complexity and the distribution (still counted in totals)
+    #[arg(long, value_name = "N", env = "CODEMETRICS_MIN_FUNCTION_STATEMENTS", default_value = "0")]
+    pub min_function_statements: u32,
+
+    /// Show detailed function-level metrics
+    #[arg(long)]
+    pub detailed: bool,
+
+    /// Classify functions as stable/active/volatile using git history
+    #[arg(long)]
+    pub stability: bool,
+
+    /// Rank files by complexity times how often git history shows them changing, plus largely duplicated files
+    #[arg(long)]
+    pub hotspots: bool,
+
+    /// Days of git history read for --hotspots
+    #[arg(long, value_name = "DAYS", default_value = "90", value_parser = clap::value_parser!(i64).range(0..))]
+    pub hotspot_days: i64,
+
+    /// Enable rules by ID or name, e.g. `--enable-rules CM0103`
+    #[arg(long, env = "CODEMETRICS_ENABLE_RULES", value_delimiter = ',')]
+    pub enable_rules: Vec<String>,
+
+    /// Disable rules by ID or name, e.g. `--disable-rules long-function`
+    #[arg(long, env = "CODEMETRICS_DISABLE_RULES", value_delimiter = ',')]
+    pub disable_rules: Vec<String>,
+
+    /// Report a rule's findings at another severity, e.g. `--rule-severity long-function=warning`
+    #[arg(long, value_name = "RULE=LEVEL", env = "CODEMETRICS_RULE_SEVERITY", value_delimiter = ',')]
+    pub rule_severity: Vec<String>,
+
+    /// Change a threshold for this run, e.g. `--threshold max_parameters=5`
+    #[arg(long, value_name = "NAME=VALUE", env = "CODEMETRICS_THRESHOLDS", value_delimiter = ',')]
+    pub threshold: Vec<String>,
+
+    /// Weigh error handling in complexity by preset (standard, handlers, paths, ignore), or per language as `LANG=PRESET`
+    #[arg(long, value_name = "PRESET", env = "CODEMETRICS_ERROR_HANDLING", value_delimiter = ',')]
+    pub error_handling: Vec<String>,
+
+    /// How function severity is decided (fixed, percentile)
+    #[arg(long, env = "CODEMETRICS_SEVERITY_BANDS", default_value = "fixed")]
+    pub severity_bands: String,
+
+    /// Reuse band cutoffs saved from a previous run's `severity_cutoffs` JSON
+    #[arg(long, value_name = "FILE")]
+    pub pinned_bands: Option<PathBuf>,
+
+    /// List every skipped path with the reason it was not analyzed
+    #[arg(long)]
+    pub explain_skips: bool,
+
+    /// Fail the run when files fail to parse instead of only warning
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Percentage of files allowed to fail parsing under `--strict`
+    #[arg(long, value_name = "PCT", default_value_t = 0.0, requires = "strict")]
+    pub max_error_rate: f64,
+
+    /// Exit nonzero when a gate holds, e.g. complexity>15, issues.critical>0, maintainability<50
+    #[arg(long, value_name = "GATE", env = "CODEMETRICS_FAIL_ON", value_delimiter = ',')]
+    pub fail_on: Vec<String>,
+
+    /// Group metrics by Bazel/Buck target, read from BUILD, BUILD.bazel and BUCK files
+    #[arg(long)]
+    pub build_targets: bool,
+
+    /// Lines of source shown around each finding in JSON and HTML output (0 disables)
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub snippet_lines: usize,
+
+    /// Keep likely secrets in snippets instead of masking them
+    #[arg(long)]
+    pub no_redact: bool,
+
+    /// Config file to use instead of the project's codemetrics.toml or .codemetricsrc
+    #[arg(long, value_name = "FILE", env = "CODEMETRICS_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Parse every file instead of reusing results cached in the user cache directory
+    #[arg(long, env = "CODEMETRICS_NO_CACHE")]
+    pub no_cache: bool,
+
+    /// Keep the parse cache here instead of in the user cache directory (e.g. ~/.cache/codemetrics)
+    #[arg(long, value_name = "DIR", env = "CODEMETRICS_CACHE_DIR", conflicts_with = "no_cache")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Skip files larger than this, in bytes or with a K, M or G suffix (default: 1M)
+    #[arg(long, value_name = "SIZE", env = "CODEMETRICS_MAX_FILE_SIZE", value_parser = parse_size)]
+    pub max_file_size: Option<usize>,
+
+    /// Read and parse files on N threads (default: one per CPU)
+    #[arg(long, value_name = "N", env = "CODEMETRICS_JOBS")]
+    pub jobs: Option<usize>,
+
+    /// Count every copy of byte-identical files in totals, not just the first
+    #[arg(long)]
+    pub count_repeated: bool,
+
+    /// Collapse a rule's findings in one directory into a cluster once there are N of them (0 lists every finding)
+    #[arg(long, value_name = "N", default_value_t = clusters::DEFAULT_CLUSTER_THRESHOLD)]
+    pub cluster_threshold: usize,
+
+    /// Include the complexity each line adds (`line_complexity` in JSON), for editor heatmaps
+    #[arg(long)]
+    pub line_complexity: bool,
+
+    /// Measure identifier vocabulary size and entropy per module (on in the deep profile)
+    #[arg(long)]
+    pub vocabulary: bool,
+
+    /// Write each module's raw identifier frequencies as JSON (implies --vocabulary)
+    #[arg(long, value_name = "FILE")]
+    pub vocabulary_out: Option<PathBuf>,
+
+    /// Measure classes and structs: methods, fields, WMC, LCOM cohesion, inheritance depth (on in the deep profile)
+    #[arg(long)]
+    pub types: bool,
+
+    /// Count Rust impl blocks per type and trait bounds and lifetimes per signature (on in the deep profile)
+    #[arg(long)]
+    pub rust_api: bool,
+
+    /// Count Rust unsafe blocks per crate, with and without `// SAFETY:` comments (on in the deep profile)
+    #[arg(long)]
+    pub unsafe_density: bool,
+
+    /// Rank functions by how many others call them (fan-in) and how many they call (on in the deep profile)
+    #[arg(long)]
+    pub call_graph: bool,
+
+    /// Replace paths and identifiers with per-run pseudonyms, for sharing reports externally
+    #[arg(long)]
+    pub anonymize: bool,
+
+    /// Which passes run: quick (LOC, complexity), standard (+issues, deps), deep (+duplication, churn, security, call graph, vocabulary, types, Rust API, unsafe density)
+    #[arg(long, env = "CODEMETRICS_PROFILE", default_value = "standard")]
+    pub profile: String,
+
+    /// Hide issues recorded in this baseline (defaults to .codemetrics/baseline.json when present)
+    #[arg(long, value_name = "FILE", env = "CODEMETRICS_BASELINE")]
+    pub baseline: Option<PathBuf>,
+
+    /// Report every issue, even those recorded in a baseline
+    #[arg(long, conflicts_with = "baseline")]
+    pub no_baseline: bool,
+
+    /// No file count or progress bar
+    #[arg(short, long, env = "CODEMETRICS_QUIET")]
+    pub quiet: bool,
+
+    /// Analyze only part INDEX of COUNT of the files, e.g. `2/8`; combine the JSON outputs with `merge`
+    #[arg(long, value_name = "INDEX/COUNT")]
+    pub shard: Option<String>,
+
+    /// What each shard keeps together: file (even load), directory (top level) or owner (CODEOWNERS)
+    #[arg(long, value_name = "GROUPING", default_value = "file", requires = "shard")]
+    pub shard_by: String,
+
+    /// Include the snapshots of this history store in the results; HTML reports chart
+    /// .codemetrics/history.jsonl without it when the file is present
+    #[arg(long, value_name = "FILE")]
+    pub history: Option<PathBuf>,
+}
+
+/// Every setting can come from the environment, so a container needs no arguments
+#[derive(Args)]
+struct ScanArgs {
+    /// Mounted source directory to analyze
+    #[arg(long, env = "CODEMETRICS_SOURCE", default_value = "/src")]
+    source: PathBuf,
+
+    /// Directory receiving the reports and status.json
+    #[arg(long, env = "CODEMETRICS_OUTPUT_DIR", default_value = "/out")]
+    output_dir: PathBuf,
+
+    /// Report formats to write, comma-separated (json, html, markdown, sarif, badge, csv, xlsx, parquet, github, gitlab, prometheus, text)
+    #[arg(long, env = "CODEMETRICS_FORMATS", value_delimiter = ',', default_value = "json,sarif")]
+    formats: Vec<String>,
+
+    /// Which passes run (quick, standard, deep)
+    #[arg(long, env = "CODEMETRICS_PROFILE", default_value = "standard")]
+    profile: String,
+
+    /// Gates that fail the scan, comma-separated, e.g. `issues.critical>0,maintainability<50`
+    #[arg(long, env = "CODEMETRICS_FAIL_ON", value_delimiter = ',')]
+    fail_on: Vec<String>,
+
+    /// Baseline of accepted issues (defaults to .codemetrics/baseline.json in the source when present)
+    #[arg(long, env = "CODEMETRICS_BASELINE", value_name = "FILE")]
+    baseline: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct SelfCheckArgs {
+    /// Output format (text, json, html, markdown, sarif)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+
+    /// Accept every current issue into the checked-in baseline instead of gating on it
+    #[arg(long)]
+    update_baseline: bool,
+}
+
+#[derive(Subcommand)]
+enum BaselineCommand {
+    /// Write a snapshot of every current issue
+    Generate(BaselineGenerateArgs),
+}
+
+#[derive(Args)]
+struct BaselineGenerateArgs {
+    #[command(flatten)]
+    analyze: AnalyzeArgs,
+
+    /// Where to write the baseline (defaults to .codemetrics/baseline.json under PATH)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// Analyze PATH and store its headline metrics under the current commit
+    Record(Box<HistoryRecordArgs>),
+    /// List recorded snapshots with the change since the previous one
+    Show(HistoryShowArgs),
+}
+
+#[derive(Args)]
+struct InitArgs {
+    /// Project root to inspect and write the config into
+    #[arg(value_name = "PATH", default_value = ".")]
+    path: PathBuf,
+
+    /// Also write a CI job that runs the analysis (github, gitlab)
+    #[arg(long, value_name = "PROVIDER")]
+    ci: Option<String>,
+
+    /// Replace an existing config file or CI job
+    #[arg(long)]
+    force: bool,
+
+    /// Print what would be written instead of writing it
+    #[arg(long)]
+    print: bool,
+}
+
+#[derive(Args)]
+struct GateArgs {
+    /// Directory to analyze, or a results file from `analyze --format json`
+    #[arg(value_name = "PATH", default_value = ".")]
+    target: PathBuf,
+
+    /// Thresholds to judge by: strict, default, relaxed, or a config file whose [thresholds] and fail_on apply
+    #[arg(long, value_name = "PROFILE")]
+    profile: String,
+
+    /// Report what would change without failing when a gate fails under the profile
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Gates in force now besides those in the config file, e.g. `issues.high>0`
+    #[arg(long, value_name = "GATE", value_delimiter = ',')]
+    fail_on: Vec<String>,
+
+    /// Config file with the current settings (defaults to the project's codemetrics.toml)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the merged settings (defaults, config file, profile, flags) and where each came from
+    Show(ConfigShowArgs),
+}
+
+#[derive(Args)]
+struct ConfigShowArgs {
+    /// The flags `analyze` would be given; settings are resolved as it would resolve them
+    #[command(flatten)]
+    analyze: AnalyzeArgs,
+
+    /// Print JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Subcommand)]
+enum RulesCommand {
+    /// Why a rule matters, how to fix its findings, and further reading
+    Explain {
+        /// Rule ID (`CM0101`) or name (`high-cyclomatic-complexity`)
+        #[arg(value_name = "RULE")]
+        rule: String,
+    },
+}
+
+#[derive(Args)]
+struct HistoryRecordArgs {
+    #[command(flatten)]
+    analyze: AnalyzeArgs,
+
+    /// History store to append to (defaults to .codemetrics/history.jsonl under PATH)
+    #[arg(long, value_name = "FILE")]
+    store: Option<PathBuf>,
+
+    /// Record even when tracked files have uncommitted changes; the snapshot is marked dirty
+    #[arg(long)]
+    allow_dirty: bool,
+}
+
+#[derive(Args)]
+struct HistoryShowArgs {
+    /// Analyzed root whose history to show
+    #[arg(value_name = "PATH", default_value = ".")]
+    path: PathBuf,
+
+    /// History store to read (defaults to .codemetrics/history.jsonl under PATH)
+    #[arg(long, value_name = "FILE")]
+    store: Option<PathBuf>,
+
+    /// Only show the most recent N snapshots
+    #[arg(long, value_name = "N")]
+    last: Option<usize>,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct ReportArgs {
+    /// Path to analyze
+    #[arg(value_name = "PATH")]
+    path: PathBuf,
+
+    /// Output file (stdout if not specified)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Report template (html, markdown, json, sarif)
+    #[arg(short, long, default_value = "html")]
+    template: String,
+}
+
+#[derive(Args)]
+struct AnnotateArgs {
+    /// File to annotate
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// Revision to compare complexity against
+    #[arg(long, default_value = "HEAD")]
+    against: String,
+
+    /// Config file to use instead of the nearest codemetrics.toml or .codemetricsrc above FILE
+    #[arg(long, value_name = "FILE", env = "CODEMETRICS_CONFIG")]
+    config: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct MergeArgs {
+    /// JSON outputs of `analyze --shard I/N --format json`, one per shard
+    #[arg(value_name = "RESULTS", required = true)]
+    results: Vec<PathBuf>,
+
+    /// Source root the shards analyzed; its codemetrics.toml applies
+    #[arg(long, value_name = "PATH", default_value = ".")]
+    root: PathBuf,
+
+    /// Output format (text, json, html, markdown, sarif, badge, parquet, github, gitlab)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+
+    /// Exit nonzero when a gate holds for the merged results
+    #[arg(long, value_name = "GATE")]
+    fail_on: Vec<String>,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// Older side: a directory, an `analyze --format json` file, or a git revision
+    #[arg(value_name = "OLD")]
+    old: String,
+
+    /// Newer side, in any of the same forms
+    #[arg(value_name = "NEW")]
+    new: String,
+
+    /// Repository revisions are checked out from
+    #[arg(long, value_name = "PATH", default_value = ".")]
+    repo: PathBuf,
+
+    /// Output format (text, json, markdown)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct ReleaseReportArgs {
+    /// Repository to compare (defaults to current directory)
+    #[arg(value_name = "PATH", default_value = ".")]
+    path: PathBuf,
+
+    /// Older release tag or revision
+    #[arg(long)]
+    from: String,
+
+    /// Newer release tag or revision
+    #[arg(long)]
+    to: String,
+
+    /// Output format (markdown, json)
+    #[arg(short, long, default_value = "markdown")]
+    format: String,
+
+    /// Output file (stdout if not specified)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct DeadCodeArgs {
+    /// Path to analyze
+    #[arg(value_name = "PATH", default_value = ".")]
+    path: PathBuf,
+
+    /// Only list findings at least this likely to be dead (high, medium, low)
+    #[arg(long, value_name = "LEVEL", default_value = "low")]
+    min_confidence: String,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct DaemonArgs {
+    /// Unix socket to listen on
+    #[arg(long, value_name = "SOCKET")]
+    listen: PathBuf,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    #[command(flatten)]
+    analyze: AnalyzeArgs,
+
+    /// Address for the dashboard page, its WebSocket and the Prometheus `/metrics` endpoint
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    listen: String,
+}
+
+#[derive(Args)]
+struct ClientArgs {
+    /// Socket the daemon listens on
+    #[arg(long, value_name = "SOCKET")]
+    socket: PathBuf,
+
+    /// The command to run and its arguments, as given to `codemetrics` directly
+    #[arg(value_name = "COMMAND", required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    command: Vec<String>,
+}
+
+#[derive(Args)]
+struct LintArgs {
+    /// Path to lint
+    #[arg(value_name = "PATH", default_value = ".")]
+    path: PathBuf,
+
+    /// Custom rule file (default: .codemetrics/rules.toml or rules.yaml under PATH)
+    #[arg(long, value_name = "FILE")]
+    rules: Option<PathBuf>,
+
+    /// Enable rules by ID or name, e.g. `--enable-rules CM0103`
+    #[arg(long, value_delimiter = ',')]
+    enable_rules: Vec<String>,
+
+    /// Disable built-in rules by ID or name, e.g. `--disable-rules long-function`
+    #[arg(long, value_delimiter = ',')]
+    disable_rules: Vec<String>,
+
+    /// Report a rule's findings at another severity, e.g. `--rule-severity long-function=warning`
+    #[arg(long, value_name = "RULE=LEVEL", value_delimiter = ',')]
+    rule_severity: Vec<String>,
+
+    /// Weigh error handling in complexity by preset (standard, handlers, paths, ignore), or per language as `LANG=PRESET`
+    #[arg(long, value_name = "PRESET", value_delimiter = ',')]
+    error_handling: Vec<String>,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct DependenciesArgs {
+    /// Path to analyze
+    #[arg(value_name = "PATH", default_value = ".")]
+    path: PathBuf,
+
+    /// Show every module importing this package and the symbols it uses
+    #[arg(long, value_name = "NAME")]
+    external: Option<String>,
+
+    /// Show modules grouped into dependency layers and the longest import chain
+    #[arg(long, conflicts_with = "external")]
+    layers: bool,
+
+    /// Show circular dependencies between modules and between packages
+    #[arg(long, conflicts_with_all = ["external", "layers"])]
+    cycles: bool,
+
+    /// Roll imports up to Bazel/Buck targets and flag those missing from `deps`
+    #[arg(long, conflicts_with_all = ["external", "layers", "cycles"])]
+    targets: bool,
+
+    /// Stop listing cycles after this many per granularity
+    #[arg(long, value_name = "N", default_value_t = cycles::DEFAULT_CYCLE_LIMIT)]
+    max_cycles: usize,
+
+    /// With dot or mermaid: one node per directory, truncated to N path components
+    #[arg(long, value_name = "N")]
+    depth: Option<usize>,
+
+    /// Output format (text, json, or the module graph as dot or mermaid)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct AttributionArgs {
+    /// Path to analyze (must be inside a git repository)
+    #[arg(value_name = "PATH", default_value = ".")]
+    path: PathBuf,
+
+    /// Start of the date range (YYYY-MM-DD)
+    #[arg(long)]
+    since: String,
+
+    /// End of the date range (YYYY-MM-DD, defaults to today)
+    #[arg(long)]
+    until: Option<String>,
+
+    /// Group by commit author instead of top-level directory.
+    /// Opt-in only: this exposes author names in the output.
+    #[arg(long)]
+    by_author: bool,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+fn main() -> Result<()> {
+    // Kept so config files can tell flags given on the command line from defaults
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    match cli.command {
+        Commands::Analyze(args) => analyze(args, matches.subcommand_matches("analyze"))?,
+        Commands::Watch(mut args) => {
+            let (project_config, passes) = layer_settings(&mut args, matches.subcommand_matches("watch"))?;
+            let stack = detect_stack(&args, passes)?;
+            let frameworks = stack.as_ref().map(|report| report.frameworks.as_slice()).unwrap_or_default();
+            let analyzer = progress::analyzer(analysis_config(&args, project_config.as_ref(), frameworks)?)?;
+            watch::run(&analyzer, &args.path)?;
+        }
+        Commands::Serve(mut args) => {
+            let (project_config, passes) = layer_settings(&mut args.analyze, matches.subcommand_matches("serve"))?;
+            let stack = detect_stack(&args.analyze, passes)?;
+            let frameworks = stack.as_ref().map(|report| report.frameworks.as_slice()).unwrap_or_default();
+            let analyzer = progress::analyzer(analysis_config(&args.analyze, project_config.as_ref(), frameworks)?)?;
+            serve::run(&analyzer, &args.analyze.path, &args.listen)?;
+        }
+        Commands::Rpc(mut args) => {
+            let (project_config, passes) = layer_settings(&mut args, matches.subcommand_matches("rpc"))?;
+            let stack = detect_stack(&args, passes)?;
+            let frameworks = stack.as_ref().map(|report| report.frameworks.as_slice()).unwrap_or_default();
+            let analyzer = progress::analyzer(analysis_config(&args, project_config.as_ref(), frameworks)?)?;
+            rpc::run(&analyzer)?;
+        }
+        Commands::Daemon(args) => daemon::serve(&args.listen)?,
+        Commands::Client(args) => daemon::request(&args.socket, args.command)?,
+        Commands::Baseline(BaselineCommand::Generate(mut args)) => {
+            let matches = matches.subcommand_matches("baseline").and_then(|m| m.subcommand_matches("generate"));
+            let output = args.output.unwrap_or_else(|| args.analyze.path.join(baseline::BASELINE_FILE));
+            let accepted = generate_baseline(&mut args.analyze, matches, &output)?;
+            println!("Baseline with {} accepted issues written to {}", accepted, output.display());
+        }
+        Commands::History(HistoryCommand::Record(mut args)) => {
+            let matches = matches.subcommand_matches("history").and_then(|m| m.subcommand_matches("record"));
+            let store = args.store.unwrap_or_else(|| args.analyze.path.join(history::HISTORY_FILE));
+            let vcs = vcs::detect(&args.analyze.path)?;
+            let commit = vcs.head()?;
+            let dirty = vcs.is_dirty()?;
+            if dirty && !args.allow_dirty {
+                anyhow::bail!("{} has uncommitted changes; commit them first, or pass --allow-dirty to record a snapshot marked dirty", args.analyze.path.display());
+            }
+            let results = run_analysis(&mut args.analyze, matches)?;
+            let snapshot = history::Snapshot::from_results(&results, &commit, dirty);
+            let short_commit = snapshot.short_commit();
+            history::record(&store, snapshot)?;
+            println!("Recorded snapshot for {} in {}", short_commit, store.display());
+        }
+        Commands::History(HistoryCommand::Show(args)) => {
+            let store = args.store.unwrap_or_else(|| args.path.join(history::HISTORY_FILE));
+            let mut snapshots = history::load(&store)?;
+            if let Some(last) = args.last {
+                snapshots.drain(..snapshots.len().saturating_sub(last));
+            }
+            match args.format.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&snapshots)?),
+                _ => show_history(&snapshots, &store),
+            }
+        }
+        Commands::Init(args) => init::run(&args.path, args.ci.as_deref(), args.force, args.print)?,
+        Commands::Gate(args) => preview_gates(&args)?,
+        Commands::Config(ConfigCommand::Show(mut args)) => {
+            let matches = matches.subcommand_matches("config").and_then(|m| m.subcommand_matches("show"));
+            let effective = EffectiveConfig::resolve(&mut args.analyze, matches)?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&effective)?);
+            } else {
+                print!("{}", effective.to_text());
+            }
+        }
+        Commands::Scan(args) => {
+            let status = scan(&args).unwrap_or_else(|e| container::ScanStatus::error(&e));
+            status.write(&args.output_dir)?;
+            if let Some(error) = &status.error {
+                eprintln!("Error: {}", error);
+            }
+            std::process::exit(status.exit_code);
+        }
+        Commands::SelfCheck(args) => {
+            let root = PathBuf::from(SELF_SOURCE);
+            if !root.is_dir() {
+                anyhow::bail!("`codemetrics self` needs the source tree it was built from ({} is missing)", root.display());
+            }
+
+            // The same as `codemetrics analyze <root>`, so crates/codemetrics.toml and the baseline apply
+            let argv = ["codemetrics", "analyze", SELF_SOURCE, "--format", args.format.as_str()];
+            let matches = Cli::command().try_get_matches_from(argv)?;
+            let Commands::Analyze(mut analyze_args) = Cli::from_arg_matches(&matches)?.command else {
+                unreachable!("argv starts with `analyze`");
+            };
+            let matches = matches.subcommand_matches("analyze");
+
+            let baseline_path = root.join(baseline::BASELINE_FILE);
+            if args.update_baseline {
+                // stdout carries the report in other runs, so status goes to stderr here too
+                let accepted = generate_baseline(&mut analyze_args, matches, &baseline_path)?;
+                eprintln!("Baseline with {} accepted issues written to {}; commit it", accepted, baseline_path.display());
+                return Ok(());
+            }
+            if !baseline_path.is_file() {
+                anyhow::bail!(
+                    "{} is missing; it is checked in, so restore it or run `codemetrics self --update-baseline` and commit the result",
+                    baseline_path.display()
+                );
+            }
+            analyze(analyze_args, matches)?;
+        }
+        Commands::Report(args) => {
+            let analyzer = progress::analyzer(AnalysisConfig::default())?;
+            let mut results = analyzer.analyze_path(&args.path)?;
+            record_stack(&mut results, Some(stack::detect(&args.path)?));
+
+            let reporter = Reporter::new(&args.template);
+            reporter.generate_report(&results, args.output.as_deref())?;
+        }
+        Commands::Annotate(args) => {
+            annotate::run(&annotate_analyzer(&args)?, &args.file, &args.against)?;
+        }
+        Commands::Merge(args) => merge(&args)?,
+        Commands::Diff(args) => {
+            // Every function's complexity, so resolved issues show where they ended up
+            let analyzer = CodeAnalyzer::with_config(AnalysisConfig::builder().function_records(true).quiet(true).build()?);
+            let old = DiffSide::load(&analyzer, &args.old, &args.repo)?;
+            let new = DiffSide::load(&analyzer, &args.new, &args.repo)?;
+            let diff = ResultsDiff::compare(&old, &new);
+
+            match args.format.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&diff)?),
+                "markdown" => print!("{}", diff.to_markdown()),
+                _ => print!("{}", diff.to_text()),
+            }
+        }
+        Commands::ReleaseReport(args) => {
+            let analyzer = progress::analyzer(AnalysisConfig::default())?;
+            let report = ReleaseReport::generate(&analyzer, &args.path, &args.from, &args.to)?;
+
+            let rendered = match args.format.as_str() {
+                "json" => serde_json::to_string_pretty(&report)?,
+                _ => report.to_markdown(),
+            };
+
+            match args.output {
+                Some(path) => std::fs::write(&path, rendered)?,
+                None => println!("{}", rendered),
+            }
+        }
+        Commands::Attribution(args) => {
+            let range = DateRange::parse(&args.since, args.until.as_deref())?;
+            let grouping = if args.by_author {
+                AttributionGrouping::Author
+            } else {
+                AttributionGrouping::Directory
+            };
+
+            let analyzer = progress::analyzer(AnalysisConfig::default())?;
+            let parsed_files: Vec<_> = analyzer.parse_path(&args.path)?
+                .into_iter()
+                .filter_map(|parsed| parsed.ok())
+                .collect();
+
+            let vcs = vcs::detect(&args.path)?;
+            let report = attribution::attribute_complexity(&parsed_files, vcs.as_ref(), &args.path, range, grouping);
+            match args.format.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+                _ => print!("{}", report.to_text(grouping)),
+            }
+        }
+        Commands::DeadCode(args) => {
+            let min_confidence = dead_code::Confidence::parse(&args.min_confidence)?;
+            let mut report = dead_code::find_dead_code(&args.path)?;
+            report.symbols.retain(|symbol| symbol.confidence >= min_confidence);
+            match args.format.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+                _ => print!("{}", report.to_text()),
+            }
+        }
+        Commands::Lint(args) => {
+            let custom = match &args.rules {
+                Some(file) => custom_rules::load(file)?,
+                None => custom_rules::discover(&args.path)?,
+            };
+            // `[rules]`, `[thresholds]` and `[error_handling]` from codemetrics.toml, under the flags
+            let project_config = ProjectConfig::discover(&args.path)?.unwrap_or_default();
+            let (enable, disable, severities) = project_config.rules.merged_with(&args.enable_rules, &args.disable_rules, &args.rule_severity);
+            let mut rules = RuleSet::from_overrides(&enable, &disable)?;
+            rules.override_severities(&severities)?;
+            let mut analyzer = ASTAnalyzer::new()?
+                .with_rules(rules)
+                .with_thresholds(project_config.thresholds.apply(&thresholds::Thresholds::default()))
+                .with_error_handling(project_config.error_handling(&args.error_handling)?)
+                .with_custom_rules(custom)?;
+            let report = custom_rules::lint(&args.path, &mut analyzer)?;
+            match args.format.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+                _ => print!("{}", report.to_text()),
+            }
+        }
+        Commands::Dependencies(args) => {
+            let import_data = dependency_analyzer::collect_import_data(&args.path)?;
+            let mut analyzer = DependencyAnalyzer::new(args.path.clone())
+                .with_cycle_limit(args.max_cycles);
+            if let Some(workspace) = CargoWorkspace::load(&args.path)? {
+                analyzer = analyzer.with_cargo_workspace(workspace);
+            }
+            if let Some(project) = JsProject::load(&args.path)? {
+                analyzer = analyzer.with_js_project(project);
+            }
+            let analysis = analyzer.analyze(import_data)?;
+
+            match args.external {
+                None if matches!(args.format.as_str(), "dot" | "mermaid") => {
+                    if args.cycles || args.layers || args.targets {
+                        anyhow::bail!("--format {} exports the whole module graph; drop --cycles, --layers and --targets", args.format);
+                    }
+                    let graph = match (args.depth, args.format.as_str()) {
+                        (Some(depth), "dot") => analysis.collapsed(GraphView::Directory { depth }).to_dot(),
+                        (Some(depth), _) => analysis.collapsed(GraphView::Directory { depth }).to_mermaid(),
+                        (None, "dot") => analysis.to_dot(),
+                        (None, _) => analysis.to_mermaid(),
+                    };
+                    print!("{}", graph);
+                }
+                None if args.cycles => {
+                    let cycles: Vec<_> = analysis.circular_dependencies.iter()
+                        .chain(&analysis.package_cycles)
+                        .collect();
+                    match args.format.as_str() {
+                        "json" => println!("{}", serde_json::to_string_pretty(&cycles)?),
+                        _ => {
+                            println!("Circular dependencies ({}):", cycles.len());
+                            for cycle in cycles {
+                                println!(
+                                    "  [{:?} {:?}] {}",
+                                    cycle.granularity,
+                                    cycle.severity,
+                                    cycle.cycle.join(" → ")
+                                );
+                            }
+                            if analysis.cycles_truncated {
+                                println!("  ... more cycles exist; raise --max-cycles to list them");
+                            }
+                        }
+                    }
+                }
+                None if args.targets => {
+                    let build_graph = BuildGraph::load(&args.path)?;
+                    let dependencies = build_graph.target_dependencies(&analysis);
+                    match args.format.as_str() {
+                        "json" => println!("{}", serde_json::to_string_pretty(&dependencies)?),
+                        _ => {
+                            let undeclared = dependencies.iter().filter(|dep| !dep.declared).count();
+                            println!("Target dependencies ({}, {} undeclared):", dependencies.len(), undeclared);
+                            for dep in &dependencies {
+                                println!(
+                                    "  {} → {}  {} imports{}",
+                                    dep.from,
+                                    dep.to,
+                                    dep.imports,
+                                    if dep.declared { "" } else { "  [undeclared]" }
+                                );
+                            }
+                        }
+                    }
+                }
+                None if args.layers => {
+                    let layers = analysis.layers();
+                    match args.format.as_str() {
+                        "json" => println!("{}", serde_json::to_string_pretty(&layers)?),
+                        _ => print!("{}", layers.to_text()),
+                    }
+                }
+                Some(package) => {
+                    let usage = analyzer.external_usage(&package);
+                    match args.format.as_str() {
+                        "json" => println!("{}", serde_json::to_string_pretty(&usage)?),
+                        _ => print!("{}", usage.to_text()),
+                    }
+                }
+                None => {
+                    let mut external: Vec<_> = analysis.external_dependencies.into_iter().collect();
+                    external.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+                    match args.format.as_str() {
+                        "json" => println!("{}", serde_json::to_string_pretty(&external)?),
+                        _ => {
+                            println!("External dependencies ({}):", external.len());
+                            for (name, modules) in external {
+                                let dependency = analysis.cargo.as_ref()
+                                    .and_then(|cargo| cargo.dependencies.values().find(|dep| dep.package == name));
+                                let label = match dependency {
+                                    Some(dep) if dep.features.is_empty() => format!("{} {}", name, dep.version),
+                                    Some(dep) => format!("{} {} (features: {})", name, dep.version, dep.features.join(", ")),
+                                    None => name,
+                                };
+                                println!("  {:<40} {} modules", label, modules);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Commands::Languages => {
+            println!("Supported languages:");
+            for lang in LanguageParser::supported_languages() {
+                println!("  - {}", lang);
+            }
+            for provider in language_provider::registered() {
+                println!("  - {} (plugin: .{})", provider.name(), provider.extensions().join(", ."));
+            }
+        }
+        Commands::Schema => print!("{}", schema::RESULTS_SCHEMA),
+        Commands::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "codemetrics", &mut std::io::stdout());
+        }
+        Commands::Man { out_dir } => {
+            std::fs::create_dir_all(&out_dir)
+                .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+            clap_mangen::generate_to(Cli::command(), &out_dir)
+                .with_context(|| format!("Failed to write man pages to {}", out_dir.display()))?;
+        }
+        Commands::Rules { action: Some(RulesCommand::Explain { rule }) } => {
+            let rule = rules::find_rule(&rule)
+                .with_context(|| format!("Unknown rule '{}' (run `rules` to list them)", rule))?;
+            explain_rule(rule, &thresholds::Thresholds::default());
+        }
+        Commands::Rules { action: None } => {
+            let thresholds = thresholds::Thresholds::default();
+            println!("{:<8} {:<28} {:<16} {:<8} Default", "ID", "Name", "Category", "Enabled");
+            for rule in rules::RULES {
+                println!(
+                    "{:<8} {:<28} {:<16} {:<8} {}",
+                    rule.id,
+                    rule.name,
+                    rule.category,
+                    if rule.enabled_by_default { "yes" } else { "no" },
+                    rule.default_setting(&thresholds).unwrap_or_else(|| "-".to_string()),
+                );
+                println!("         {}", rule.description);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The `rules explain` page: what the rule flags, why it matters and how to fix it
+fn explain_rule(rule: &rules::Rule, thresholds: &thresholds::Thresholds) {
+    println!("{} {} ({})", rule.id, rule.name, rule.category);
+    println!();
+    println!("{}.", rule.description);
+    println!("Enabled by default: {}", if rule.enabled_by_default { "yes" } else { "no" });
+    if let Some(setting) = rule.default_setting(thresholds) {
+        println!("Default threshold: {}", setting);
+    }
+    println!();
+    println!("Why it matters");
+    println!("  {}", rule.docs.why);
+    println!();
+    println!("How to fix");
+    println!("  {}", rule.docs.fix);
+    if !rule.docs.references.is_empty() {
+        println!();
+        println!("References");
+        for reference in rule.docs.references {
+            println!("  - {}", reference);
+        }
+    }
+}
+
+/// The `analyze` command: analysis, baseline, reporting, then gates
+fn analyze(mut args: AnalyzeArgs, matches: Option<&ArgMatches>) -> Result<()> {
+    // Rejected before the (possibly long) analysis runs
+    args.fail_on.iter().try_for_each(|spec| Gate::parse(spec).map(drop))?;
+    if args.format == "ndjson" {
+        return stream_analysis(&mut args, matches);
+    }
+    let results = run_analysis(&mut args, matches)?;
+    deliver(&args, results)
+}
+
+/// `analyze --format ndjson`: print each file's record as it is analyzed, then deliver the summary
+///
+/// Parsed files are dropped once counted, so memory stays flat on very large trees;
+/// passes that read every file back are skipped.
+fn stream_analysis(args: &mut AnalyzeArgs, matches: Option<&ArgMatches>) -> Result<()> {
+    let (project_config, passes) = layer_settings(args, matches)?;
+    let stack = detect_stack(args, passes)?;
+    let frameworks = stack.as_ref().map(|report| report.frameworks.as_slice()).unwrap_or_default();
+    let analyzer = progress::analyzer(analysis_config(args, project_config.as_ref(), frameworks)?)?;
+
+    // One anonymizer for the file records and the summary, so their pseudonyms agree
+    let anonymizer = anonymize::Anonymizer::new();
+    // Loaded up front: records are printed before the summary is suppressed
+    let baseline = load_baseline(args)?;
+    let mut writer = NdjsonWriter::new(std::io::stdout().lock());
+    let mut results = analyzer.analyze_streaming(&args.path, |record| {
+        if baseline.is_none() && !args.anonymize {
+            return writer.file(record);
+        }
+        let mut record = record.clone();
+        if let Some(baseline) = &baseline {
+            baseline.mark_record(&mut record, &args.path);
+        }
+        if args.anonymize {
+            anonymizer.file_record(&mut record);
+        }
+        writer.file(&record)
+    })?;
+    drop(writer);
+    record_stack(&mut results, stack);
+    annotate_history(args, &mut results)?;
+    deliver_with(args, results, &mut StdoutSink, &anonymizer)
+}
+
+/// Everything `analyze` does once results exist: baseline, report, then the exit status
+fn deliver(args: &AnalyzeArgs, results: AnalysisResults) -> Result<()> {
+    deliver_to(args, results, &mut StdoutSink)
+}
+
+/// Like `deliver`, handing the report to `sink` instead of printing it
+fn deliver_to(args: &AnalyzeArgs, results: AnalysisResults, sink: &mut dyn OutputSink) -> Result<()> {
+    deliver_with(args, results, sink, &anonymize::Anonymizer::new())
+}
+
+/// Like `deliver_to`, with the pseudonyms of `anonymizer` when `--anonymize` is set
+fn deliver_with(args: &AnalyzeArgs, mut results: AnalysisResults, sink: &mut dyn OutputSink, anonymizer: &anonymize::Anonymizer) -> Result<()> {
+    // Parsed again: the project config may have added gates such as `max_duplication_pct`
+    let gates = args.fail_on.iter().map(|spec| Gate::parse(spec)).collect::<Result<Vec<_>>>()?;
+
+    suppress_baseline(args, &mut results)?;
+    // Only the HTML trend chart picks the store up unasked; other formats would just grow with it
+    let history_path = args.history.clone().or_else(|| {
+        Some(args.path.join(history::HISTORY_FILE)).filter(|path| args.format == "html" && path.is_file())
+    });
+    if let Some(path) = history_path {
+        results.history = history::load(&path)?;
+    }
+
+    if args.anonymize {
+        anonymizer.apply(&mut results);
+    }
+    if args.explain_skips {
+        explain_skips(&results);
+    }
+    if let Some(path) = &args.vocabulary_out {
+        vocabulary::write_raw(&results.vocabulary, path)?;
+    }
+
+    let reporter = Reporter::new(&args.format);
+    reporter.write_report(&results, sink)?;
+
+    // Checked after reporting so CI logs still show what was analyzed
+    if args.strict && results.parse_error_rate() > args.max_error_rate {
+        anyhow::bail!(
+            "{} of {} files failed to parse ({:.1}%), above the allowed {:.1}% (--max-error-rate)",
+            results.errors.len(),
+            results.files_analyzed + results.errors.len(),
+            results.parse_error_rate(),
+            args.max_error_rate
+        );
+    }
+
+    let failures = gates::evaluate(&gates, &results);
+    if !failures.is_empty() {
+        let violated: Vec<String> = failures.iter()
+            .map(|failure| format!("{} (actual {:.2})", failure.gate, failure.actual))
+            .collect();
+        anyhow::bail!("Quality gate failed: {}", violated.join(", "));
+    }
+    Ok(())
+}
+
+/// Aggregate the parsed files of every shard, then report as `analyze` would
+fn merge(args: &MergeArgs) -> Result<()> {
+    let mut argv = vec![
+        "codemetrics".to_string(),
+        "analyze".to_string(),
+        args.root.to_string_lossy().into_owned(),
+        "--format".to_string(),
+        args.format.clone(),
+    ];
+    for gate in &args.fail_on {
+        argv.extend(["--fail-on".to_string(), gate.clone()]);
+    }
+    let matches = Cli::command().try_get_matches_from(argv)?;
+    let Commands::Analyze(mut analyze_args) = Cli::from_arg_matches(&matches)?.command else {
+        unreachable!("argv starts with `analyze`");
+    };
+    analyze_args.fail_on.iter().try_for_each(|spec| Gate::parse(spec).map(drop))?;
+
+    let (project_config, passes) = layer_settings(&mut analyze_args, matches.subcommand_matches("analyze"))?;
+    let stack = detect_stack(&analyze_args, passes)?;
+    let frameworks = stack.as_ref().map(|report| report.frameworks.as_slice()).unwrap_or_default();
+    let analyzer = progress::analyzer(analysis_config(&analyze_args, project_config.as_ref(), frameworks)?)?;
+
+    let outputs = shard::read_outputs(&args.results)?;
+    eprintln!("Merging {} shards", outputs.len());
+    let mut results = shard::merge(&analyzer, &args.root, outputs)?;
+    record_stack(&mut results, stack);
+    deliver(&analyze_args, results)
+}
+
+/// The analyzer `analyze` would use on the project holding `args.file`, so its thresholds apply
+fn annotate_analyzer(args: &AnnotateArgs) -> Result<CodeAnalyzer> {
+    let root = args.file.ancestors().skip(1)
+        .find(|dir| ProjectConfig::discover_file(dir).is_some())
+        .or_else(|| args.file.parent())
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut argv = vec![
+        "codemetrics".to_string(),
+        "analyze".to_string(),
+        root.to_string_lossy().into_owned(),
+        "--quiet".to_string(),
+    ];
+    if let Some(file) = &args.config {
+        argv.extend(["--config".to_string(), file.to_string_lossy().into_owned()]);
+    }
+    let matches = Cli::command().try_get_matches_from(argv)?;
+    let Commands::Analyze(mut analyze_args) = Cli::from_arg_matches(&matches)?.command else {
+        unreachable!("argv starts with `analyze`");
+    };
+
+    let (project_config, passes) = layer_settings(&mut analyze_args, matches.subcommand_matches("analyze"))?;
+    let stack = detect_stack(&analyze_args, passes)?;
+    let frameworks = stack.as_ref().map(|report| report.frameworks.as_slice()).unwrap_or_default();
+    Ok(CodeAnalyzer::with_config(analysis_config(&analyze_args, project_config.as_ref(), frameworks)?))
+}
+
+/// Judge one result set by the current policy and by `--profile`, and list the difference
+fn preview_gates(args: &GateArgs) -> Result<()> {
+    let root = if args.target.is_dir() { args.target.as_path() } else { Path::new(".") };
+    let project_config = match &args.config {
+        Some(file) => ProjectConfig::load(file)?,
+        None => ProjectConfig::discover(root)?.unwrap_or_default(),
+    };
+
+    let set = if args.target.is_dir() {
+        let config = project_config.configure(AnalysisConfig::builder()).function_records(true).quiet(true).build()?;
+        gate_preview::ResultSet::from_results(&CodeAnalyzer::with_config(config).analyze_path(&args.target)?)
+    } else {
+        let json = std::fs::read_to_string(&args.target)
+            .with_context(|| format!("Failed to read {}", args.target.display()))?;
+        gate_preview::ResultSet::from_json(&json)
+            .with_context(|| format!("Failed to load results from {}", args.target.display()))?
+    };
+
+    let parse_gates = |specs: Vec<String>| -> Result<Vec<Gate>> {
+        specs.iter().chain(&args.fail_on).map(|spec| Gate::parse(spec)).collect()
+    };
+    let current = gate_preview::Policy {
+        name: "current".to_string(),
+        thresholds: set.thresholds.clone(),
+        gates: parse_gates(project_config.gates())?,
+    };
+    let proposed = if thresholds::PRESETS.contains(&args.profile.as_str()) {
+        gate_preview::Policy {
+            name: args.profile.clone(),
+            thresholds: thresholds::Thresholds::preset(&args.profile)?,
+            gates: current.gates.clone(),
+        }
+    } else {
+        let file = Path::new(&args.profile);
+        if !file.is_file() {
+            anyhow::bail!(
+                "Unknown profile '{}': expected {} or a config file",
+                args.profile,
+                thresholds::PRESETS.join(", ")
+            );
+        }
+        let profile = ProjectConfig::load(file)?;
+        let specs = profile.gates();
+        gate_preview::Policy {
+            name: file.display().to_string(),
+            thresholds: profile.thresholds.apply(&thresholds::Thresholds::default()),
+            gates: if specs.is_empty() { current.gates.clone() } else { parse_gates(specs)? },
+        }
+    };
+    proposed.thresholds.validate()?;
+
+    let preview = gate_preview::preview(&set, &current, &proposed);
+    match args.format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&preview)?),
+        "text" => print!("{}", preview.to_text()),
+        other => anyhow::bail!("Unknown format '{}': expected text or json", other),
+    }
+
+    if !args.dry_run {
+        let failed: Vec<&str> = preview.gates.iter().filter(|verdict| verdict.fails_proposed).map(|verdict| verdict.gate.as_str()).collect();
+        if !failed.is_empty() {
+            anyhow::bail!("Quality gate failed under {}: {}", proposed.name, failed.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// One line per snapshot, oldest first, with the change in key metrics since the one before
+fn show_history(snapshots: &[history::Snapshot], store: &Path) {
+    if snapshots.is_empty() {
+        println!("No snapshots in {}; add one with `codemetrics history record <PATH>`", store.display());
+        return;
+    }
+
+    println!("{:<10} {:<21} {:>7} {:>9} {:>14} {:>14} {:>7} {:>16}", "Commit", "Recorded", "Files", "Lines", "Avg complexity", "Weighted (LOC)", "Issues", "Maintainability");
+    let mut previous: Option<&history::Snapshot> = None;
+    for snapshot in snapshots {
+        let delta = |current: f64, before: Option<f64>| match before {
+            Some(before) if (current - before).abs() >= 0.05 => format!(" ({:+.1})", current - before),
+            _ => String::new(),
+        };
+        let complexity = format!(
+            "{:.1}{}",
+            snapshot.average_complexity,
+            delta(snapshot.average_complexity, previous.map(|before| before.average_complexity))
+        );
+        let weighted = snapshot.weighted_average_complexity
+            .map(|average| format!("{:.1}{}", average, delta(average, previous.and_then(|before| before.weighted_average_complexity))))
+            .unwrap_or_else(|| "-".to_string());
+        let issues = format!(
+            "{}{}",
+            snapshot.issues,
+            delta(snapshot.issues as f64, previous.map(|before| before.issues as f64))
+        );
+        let maintainability = snapshot.maintainability
+            .map(|index| format!("{:.0}{}", index, delta(index, previous.and_then(|before| before.maintainability))))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<10} {:<21} {:>7} {:>9} {:>14} {:>14} {:>7} {:>16}",
+            snapshot.short_commit(),
+            snapshot.recorded_at,
+            snapshot.files_analyzed,
+            snapshot.total_lines,
+            complexity,
+            weighted,
+            issues,
+            maintainability
+        );
+        previous = Some(snapshot);
+    }
+}
+
+/// Hide issues recorded in the baseline `args` select, if any
+fn suppress_baseline(args: &AnalyzeArgs, results: &mut AnalysisResults) -> Result<()> {
+    if let Some(baseline) = load_baseline(args)? {
+        results.baseline_suppressed = baseline.suppress(results, &args.path);
+    }
+    Ok(())
+}
+
+/// `--baseline`, else the project's committed baseline; `None` with `--no-baseline`
+fn load_baseline(args: &AnalyzeArgs) -> Result<Option<Baseline>> {
+    let baseline_path = args.baseline.clone()
+        .or_else(|| Some(args.path.join(baseline::BASELINE_FILE)).filter(|path| path.is_file()));
+    baseline_path.filter(|_| !args.no_baseline).map(|path| Baseline::load(&path)).transpose()
+}
+
+/// Analyze the mounted source and write every requested report into the output directory
+fn scan(args: &ScanArgs) -> Result<container::ScanStatus> {
+    if !args.source.is_dir() {
+        anyhow::bail!("Source directory {} does not exist; mount it or set CODEMETRICS_SOURCE", args.source.display());
+    }
+    const FORMATS: [&str; 12] = ["json", "html", "markdown", "sarif", "badge", "csv", "xlsx", "parquet", "github", "gitlab", "prometheus", "text"];
+    if let Some(unknown) = args.formats.iter().find(|format| !FORMATS.contains(&format.as_str())) {
+        anyhow::bail!("Unknown report format '{}' (expected one of {})", unknown, FORMATS.join(", "));
+    }
+    let reporters: Vec<Reporter> = args.formats.iter().map(|format| Reporter::new(format)).collect();
+    for (index, reporter) in reporters.iter().enumerate() {
+        if reporters[..index].iter().any(|earlier| earlier.artifact_name() == reporter.artifact_name()) {
+            anyhow::bail!("Two of the requested formats both write {}; keep only one", reporter.artifact_name());
+        }
+    }
+
+    // Parsed like `codemetrics analyze`, so codemetrics.toml in the source still applies
+    let mut argv: Vec<String> = ["analyze", "--quiet", "--profile", args.profile.as_str()].iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    argv.push(args.source.to_string_lossy().into_owned());
+    // Row-per-function formats need every function recorded; csv and xlsx also measure their shape
+    let records_format = ["csv", "xlsx", "parquet"].into_iter()
+        .find(|wanted| args.formats.iter().any(|format| format == wanted));
+    if let Some(format) = records_format {
+        argv.extend(["--format".to_string(), format.to_string()]);
+    }
+    for gate in &args.fail_on {
+        argv.extend(["--fail-on".to_string(), gate.clone()]);
+    }
+    if let Some(baseline) = &args.baseline {
+        argv.extend(["--baseline".to_string(), baseline.to_string_lossy().into_owned()]);
+    }
+    let matches = Cli::command().try_get_matches_from(std::iter::once("codemetrics".to_string()).chain(argv))?;
+    let Commands::Analyze(mut analyze_args) = Cli::from_arg_matches(&matches)?.command else {
+        unreachable!("argv starts with `analyze`");
+    };
+    // The source is mounted read-only, so the parse cache is kept with the reports
+    if analyze_args.cache_dir.is_none() && !analyze_args.no_cache {
+        analyze_args.cache_dir = Some(args.output_dir.join(cache::CACHE_DIR));
+    }
+
+    analyze_args.fail_on.iter().try_for_each(|spec| Gate::parse(spec).map(drop))?;
+    let mut results = run_analysis(&mut analyze_args, matches.subcommand_matches("analyze"))?;
+    let gates = analyze_args.fail_on.iter().map(|spec| Gate::parse(spec)).collect::<Result<Vec<_>>>()?;
+    suppress_baseline(&analyze_args, &mut results)?;
+
+    let mut sink = DirectorySink::new(&args.output_dir);
+    for reporter in &reporters {
+        reporter.write_report(&results, &mut sink)?;
+    }
+    let artifacts = reporters.iter().map(Reporter::artifact_name).collect();
+
+    let failures = gates::evaluate(&gates, &results);
+    Ok(container::ScanStatus::finished(&results, &failures, artifacts))
+}
+
+/// Record every current issue under `args.path` as accepted, returning how many there are
+fn generate_baseline(args: &mut AnalyzeArgs, matches: Option<&ArgMatches>, output: &Path) -> Result<usize> {
+    let results = run_analysis(args, matches)?;
+    let baseline = Baseline::from_results(&results, &args.path);
+    baseline.save(output)?;
+    Ok(baseline.fingerprints.len())
+}
+
+/// Analyze `args.path` with every pass the settings select
+fn run_analysis(args: &mut AnalyzeArgs, matches: Option<&ArgMatches>) -> Result<AnalysisResults> {
+    let (project_config, passes) = layer_settings(args, matches)?;
+    let stack = detect_stack(args, passes)?;
+    let frameworks = stack.as_ref().map(|report| report.frameworks.as_slice()).unwrap_or_default();
+    let analyzer = progress::analyzer(analysis_config(args, project_config.as_ref(), frameworks)?)?;
+    let mut results = analyzer.analyze_path(&args.path)?;
+    record_stack(&mut results, stack);
+    annotate_history(args, &mut results)?;
+    Ok(results)
+}
+
+/// Stability and hotspots from version control, when requested
+fn annotate_history(args: &AnalyzeArgs, results: &mut AnalysisResults) -> Result<()> {
+    if !args.stability && !args.hotspots {
+        return Ok(());
+    }
+    if args.hotspots {
+        HotspotAnalyzer::new().annotate_duplication(results);
+    }
+    // Missing history degrades the report instead of failing the run
+    let vcs = match vcs::detect(&args.path) {
+        Ok(vcs) => vcs,
+        Err(e) => {
+            eprintln!("Warning: stability and hotspots skipped - {}", e);
+            results.capabilities.unavailable(capabilities::GIT_HISTORY, e.to_string());
+            return Ok(());
+        }
+    };
+    if args.stability {
+        StabilityClassifier::new().annotate(results, vcs.as_ref())?;
+    }
+    if args.hotspots {
+        HotspotAnalyzer::new().with_window_days(args.hotspot_days).annotate(results, vcs.as_ref())?;
+    }
+    results.capabilities.measured(capabilities::GIT_HISTORY);
+    Ok(())
+}
+
+/// Whether `id` was set by a flag or its environment variable, either of which
+/// outranks the config file and profile
+fn given(matches: Option<&ArgMatches>, id: &str) -> bool {
+    matches!(
+        matches.and_then(|m| m.value_source(id)),
+        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+    )
+}
+
+/// Fill in every setting not given by a flag or the environment, from the project's
+/// config file and then from the selected profile
+fn layer_settings(args: &mut AnalyzeArgs, matches: Option<&ArgMatches>) -> Result<(Option<ProjectConfig>, Passes)> {
+    let given = |id: &str| given(matches, id);
+
+    let project_config = match &args.config {
+        Some(file) => Some(ProjectConfig::load(file)?),
+        None => ProjectConfig::discover(&args.path)?,
+    };
+    if let Some(project_config) = &project_config {
+        project_config.apply_to(args, given);
+    }
+
+    let passes = Profile::parse(&args.profile)?.apply_to(args, given);
+    Ok((project_config, passes))
+}
+
+/// `None` when the profile skips dependency scanning
+fn detect_stack(args: &AnalyzeArgs, passes: Passes) -> Result<Option<StackReport>> {
+    passes.dependencies.then(|| stack::detect(&args.path)).transpose()
+}
+
+fn record_stack(results: &mut AnalysisResults, stack: Option<StackReport>) {
+    match stack {
+        Some(report) if report.manifests > 0 => {
+            results.capabilities.measured(capabilities::MANIFESTS);
+            results.stack = report.frameworks;
+        }
+        Some(report) => {
+            results.capabilities.unavailable(capabilities::MANIFESTS, "no dependency manifests found");
+            results.stack = report.frameworks;
+        }
+        None => results.capabilities.unavailable(capabilities::MANIFESTS, "skipped by the quick profile"),
+    }
+}
+
+/// Build the analysis configuration selected on the command line
+fn analysis_config(
+    args: &AnalyzeArgs,
+    project_config: Option<&ProjectConfig>,
+    stack: &[DetectedFramework],
+) -> Result<AnalysisConfig> {
+    let mut builder = AnalysisConfig::builder();
+    if let Some(project_config) = project_config {
+        builder = project_config.configure(builder);
+    }
+    builder = builder
+        .min_complexity(args.min_complexity)
+        .min_function_statements(args.min_function_statements)
+        .include_tests(args.include_tests)
+        .exclude_languages(&args.exclude_languages)
+        .count_repeated_files(args.count_repeated)
+        .cluster_threshold(args.cluster_threshold)
+        .line_complexity(args.line_complexity)
+        .vocabulary(args.vocabulary || args.vocabulary_out.is_some())
+        .type_metrics(args.types)
+        .rust_api(args.rust_api)
+        .unsafe_density(args.unsafe_density)
+        .call_graph(args.call_graph)
+        .function_records(matches!(args.format.as_str(), "csv" | "xlsx" | "parquet"))
+        .function_shape(matches!(args.format.as_str(), "csv" | "xlsx" | "parquet"))
+        // Progress output would break the record stream
+        .quiet(args.quiet || args.format == "ndjson")
+        .rules(rule_set(args, stack)?)
+        .error_handling(match project_config {
+            Some(config) => config.error_handling(&args.error_handling)?,
+            None => ProjectConfig::default().error_handling(&args.error_handling)?,
+        })
+        .severity_bands(severity_bands(args)?)
+        .snippets(SnippetOptions {
+            context_lines: args.snippet_lines,
+            redact_secrets: !args.no_redact,
+        });
+    if !args.threshold.is_empty() {
+        let base = project_config.map_or_else(thresholds::Thresholds::default, |config| config.thresholds.apply(&thresholds::Thresholds::default()));
+        builder = builder.thresholds(ThresholdOverrides::parse(&args.threshold)?.apply(&base));
+    }
+    if !args.languages.is_empty() {
+        builder = builder.focus_languages(&args.languages);
+    }
+    if args.build_targets {
+        builder = builder.build_graph(BuildGraph::load(&args.path)?);
+    }
+    if let Some(spec) = &args.shard {
+        builder = builder.shard(shard::Shard::parse(spec)?, shard::ShardBy::parse(&args.shard_by, &args.path)?);
+    }
+    if let Some(bytes) = args.max_file_size {
+        builder = builder.max_file_size(bytes);
+    }
+    if let Some(jobs) = args.jobs {
+        builder = builder.jobs(jobs);
+    }
+    if let Some(dir) = &args.cache_dir {
+        builder = builder.cache_dir(dir);
+    } else if let Some(dir) = cache::user_cache_dir().filter(|_| !args.no_cache) {
+        builder = builder.cache_dir(dir);
+    }
+
+    builder.build()
+}
+
+/// `4096`, `512K`, `2M` or `1G`; suffixes are binary multiples and case-insensitive
+fn parse_size(text: &str) -> Result<usize, String> {
+    let text = text.trim();
+    let (digits, multiplier) = match text.char_indices().last() {
+        Some((index, 'k' | 'K')) => (&text[..index], 1 << 10),
+        Some((index, 'm' | 'M')) => (&text[..index], 1 << 20),
+        Some((index, 'g' | 'G')) => (&text[..index], 1 << 30),
+        _ => (text, 1),
+    };
+    digits.trim().parse::<usize>().ok()
+        .and_then(|size| size.checked_mul(multiplier))
+        .ok_or_else(|| format!("'{}' is not a size such as 4096, 512K or 2M", text))
+}
+
+/// Rules enabled by the detected stack and the flags, with changed severities
+fn rule_set(args: &AnalyzeArgs, stack: &[DetectedFramework]) -> Result<RuleSet> {
+    let mut rules = RuleSet::for_stack(stack, &args.enable_rules, &args.disable_rules)?;
+    rules.override_severities(&args.rule_severity)?;
+    Ok(rules)
+}
+
+fn severity_bands(args: &AnalyzeArgs) -> Result<SeverityBands> {
+    if let Some(path) = &args.pinned_bands {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read pinned bands from {}", path.display()))?;
+        let cutoffs = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid pinned bands in {}", path.display()))?;
+        return Ok(SeverityBands::Pinned(cutoffs));
+    }
+
+    match args.severity_bands.as_str() {
+        "fixed" => Ok(SeverityBands::Fixed),
+        "percentile" => Ok(SeverityBands::Percentile(PercentileBands::default())),
+        other => anyhow::bail!("Unknown severity bands '{}', expected fixed or percentile", other),
+    }
+}
+
+/// Print every skipped path to stderr, keeping stdout clean for JSON output
+fn explain_skips(results: &AnalysisResults) {
+    eprintln!("Skipped {} paths:", results.skipped.len());
+    for skipped in &results.skipped {
+        eprintln!("  {:<24} {}", skipped.reason.label(), skipped.path.display());
+    }
+    eprintln!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use std::fs;
+
+    #[test]
+    fn test_man_pages_and_completions_use_the_binary_name() {
+        let temp_dir = tempdir().unwrap();
+        clap_mangen::generate_to(Cli::command(), temp_dir.path()).unwrap();
+        assert!(temp_dir.path().join("codemetrics.1").exists());
+        assert!(temp_dir.path().join("codemetrics-analyze.1").exists());
+
+        let mut script = Vec::new();
+        clap_complete::generate(clap_complete::Shell::Bash, &mut Cli::command(), "codemetrics", &mut script);
+        assert!(!String::from_utf8(script).unwrap().contains("codemetrics-cli"));
+    }
+
+    #[test]
+    fn test_parse_size_accepts_binary_suffixes() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("512k"), Ok(512 * 1024));
+        assert_eq!(parse_size("2M"), Ok(2 * 1024 * 1024));
+        assert!(parse_size("2MB").is_err());
+        assert!(parse_size("M").is_err());
+    }
+
+    #[test]
+    fn test_scan_leaves_the_source_untouched() -> Result<()> {
+        let source = tempdir()?;
+        let output = tempdir()?;
+        fs::write(source.path().join("lib.rs"), "fn id(x: u8) -> u8 { x }\n")?;
+        let args = ScanArgs {
+            source: source.path().to_path_buf(),
+            output_dir: output.path().to_path_buf(),
+            formats: vec!["json".to_string(), "badge".to_string()],
+            profile: "standard".to_string(),
+            fail_on: Vec::new(),
+            baseline: None,
+        };
+
+        let status = scan(&args)?;
+        assert_eq!((status.status, status.files_analyzed), ("passed", 1));
+        assert_eq!(fs::read_dir(source.path())?.count(), 1);
+        assert!(output.path().join("report.json").is_file());
+        assert!(fs::read_to_string(output.path().join("badge.json"))?.contains("schemaVersion"));
+        assert!(output.path().join(cache::CACHE_DIR).is_dir());
+        Ok(())
+    }
+
+    #[test]
+    fn test_basic_analysis() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let test_file = temp_dir.path().join("test.rs");
+
+        fs::write(&test_file, r#"
+            fn simple_function() -> i32 {
+                42
+            }
+
+            fn complex_function(x: i32) -> i32 {
+                if x > 0 {
+                    if x > 10 {
+                        x * 2
+                    } else {
+                        x + 1
+                    }
+                } else {
+                    0
+                }
+            }
+        "#)?;
+
+        let analyzer = codemetrics_core::analyzer::CodeAnalyzer::new(temp_dir.path().to_path_buf())?;
+        let metrics_config = analyzer.configure_metrics(None)?;
+        let results = analyzer.analyze(metrics_config)?;
+
+        assert!(results.files_analyzed > 0);
+        assert!(results.total_lines > 0);
+
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,147 @@
+//! Named analysis profiles
+//!
+//! A profile picks which passes run, so common flag combinations have a name:
+//!
+//! | Profile    | Passes                                                                                                           |
+//! |------------|------------------------------------------------------------------------------------------------------------------|
+//! | `quick`    | lines of code and complexity                                                                                     |
+//! | `standard` | + issues and dependency/stack detection (default)                                                                |
+//! | `deep`     | + duplication, churn (stability, hotspots), security, call graph, vocabulary, types, Rust API and unsafe density |
+//!
+//! Profiles only fill in flags that were not given on the command line, so
+//! `--profile quick --stability` still classifies stability.
+
+use anyhow::Result;
+
+use codemetrics_core::rules;
+use codemetrics_core::vcs;
+use crate::AnalyzeArgs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Quick,
+    Standard,
+    Deep,
+}
+
+/// Optional analysis passes selected by a profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Passes {
+    /// Issue rules and their source snippets
+    pub issues: bool,
+    /// Manifest and import scanning, which also selects framework rule packs
+    pub dependencies: bool,
+    /// Clone detection, even where the config disables `duplicated-code`
+    pub duplication: bool,
+    pub churn: bool,
+    pub security: bool,
+    /// Fan-in and fan-out of every function
+    pub call_graph: bool,
+    /// Identifier vocabulary and entropy per module
+    pub vocabulary: bool,
+    /// Class and struct metrics (LCOM, WMC, DIT)
+    pub types: bool,
+    /// Rust impl surface, generic bounds per signature and unsafe blocks per crate
+    pub rust_api: bool,
+}
+
+impl Profile {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "quick" => Ok(Profile::Quick),
+            "standard" => Ok(Profile::Standard),
+            "deep" => Ok(Profile::Deep),
+            other => anyhow::bail!("Unknown profile '{}', expected quick, standard or deep", other),
+        }
+    }
+
+    pub fn passes(self) -> Passes {
+        match self {
+            Profile::Quick => Passes {
+                issues: false, dependencies: false, duplication: false, churn: false, security: false, call_graph: false,
+                vocabulary: false, types: false, rust_api: false,
+            },
+            Profile::Standard => Passes {
+                issues: true, dependencies: true, duplication: false, churn: false, security: false, call_graph: false,
+                vocabulary: false, types: false, rust_api: false,
+            },
+            Profile::Deep => Passes {
+                issues: true, dependencies: true, duplication: true, churn: true, security: true, call_graph: true,
+                vocabulary: true, types: true, rust_api: true,
+            },
+        }
+    }
+
+    /// Set the flags behind this profile's passes, except those `given` on the command line
+    pub fn apply_to(self, args: &mut AnalyzeArgs, given: impl Fn(&str) -> bool) -> Passes {
+        let passes = self.passes();
+
+        if !passes.issues {
+            if !given("disable_rules") {
+                args.disable_rules = rules::RULES.iter().map(|rule| rule.id.to_string()).collect();
+            }
+            if !given("snippet_lines") {
+                args.snippet_lines = 0;
+            }
+        }
+        // Churn needs history; outside a repository the pass is skipped rather than failing
+        if passes.churn && !given("stability") {
+            args.stability = vcs::detect(&args.path).is_ok();
+        }
+        if passes.churn && !given("hotspots") {
+            args.hotspots = vcs::detect(&args.path).is_ok();
+        }
+        if passes.duplication {
+            args.enable_rules.push(rules::DUPLICATED_CODE.to_string());
+        }
+        if passes.security {
+            args.enable_rules.extend(rules::RULES.iter()
+                .filter(|rule| rule.category == "security")
+                .map(|rule| rule.id.to_string()));
+        }
+        if passes.call_graph && !given("call_graph") {
+            args.call_graph = true;
+        }
+        if passes.vocabulary && !given("vocabulary") {
+            args.vocabulary = true;
+        }
+        if passes.types && !given("types") {
+            args.types = true;
+        }
+        if passes.rust_api && !given("rust_api") {
+            args.rust_api = true;
+        }
+        if passes.rust_api && !given("unsafe_density") {
+            args.unsafe_density = true;
+        }
+
+        passes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cli, Commands};
+    use clap::Parser;
+
+    #[test]
+    fn test_profiles_fill_in_only_unset_flags() {
+        let Commands::Analyze(mut args) = Cli::try_parse_from(["codemetrics", "analyze", "--snippet-lines", "5", "."])
+            .unwrap()
+            .command
+        else {
+            unreachable!()
+        };
+        let passes = Profile::parse("quick").unwrap().apply_to(&mut args, |id| id == "snippet_lines");
+        assert!(!passes.dependencies);
+        assert_eq!(args.disable_rules.len(), rules::RULES.len());
+        assert_eq!(args.snippet_lines, 5);
+
+        let deep = Profile::parse("deep").unwrap().apply_to(&mut args, |_| false);
+        assert!(deep.security && deep.duplication);
+        assert!(args.call_graph);
+        assert!(args.enable_rules.iter().any(|rule| rule == rules::DUPLICATED_CODE));
+        assert!(Profile::parse("thorough").is_err());
+    }
+}
@@ -0,0 +1,39 @@
+//! The progress bar shown while `analyze` and friends parse files
+
+use anyhow::{Context, Result};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::path::Path;
+
+use codemetrics_core::analyzers::{AnalysisConfig, CodeAnalyzer};
+use codemetrics_core::progress::Progress;
+
+/// An analyzer for `config` that draws a progress bar unless the config is quiet
+pub fn analyzer(config: AnalysisConfig) -> Result<CodeAnalyzer> {
+    let style = ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+        .context("Failed to set progress style")?;
+    Ok(CodeAnalyzer::with_config(config).with_progress(Bar(ProgressBar::new(0).with_style(style))))
+}
+
+struct Bar(ProgressBar);
+
+impl Progress for Bar {
+    fn start(&self, files: usize) {
+        // Beside the bar on stderr, so machine-readable reports on stdout stay parseable
+        eprintln!("Found {} files to analyze", files);
+        self.0.reset();
+        self.0.set_length(files as u64);
+    }
+
+    fn parsed(&self, path: &Path) {
+        self.0.inc(1);
+        self.0.set_message(format!("Analyzed {}", path.display()));
+    }
+
+    fn finish(&self, cached: Option<usize>) {
+        match cached {
+            Some(hits) => self.0.finish_with_message(format!("Analysis complete ({} unchanged files cached)", hits)),
+            None => self.0.finish_with_message("Analysis complete"),
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! JSON-RPC 2.0 over stdio, for editor plugins that do not need a full LSP
+//!
+//! Each request is one line of JSON on stdin and each response one line on
+//! stdout. Requests without an `id` are notifications and get no response.
+//! The methods and their payloads are described in `docs/editor-protocol.md`.
+//!
+//! A session keeps the syntax tree of every buffer it has analyzed, so the
+//! next version of the same path is re-parsed incrementally.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use codemetrics_core::analyzers::CodeAnalyzer;
+use codemetrics_core::line_complexity;
+use codemetrics_core::parsers::RetainedTree;
+use codemetrics_core::thresholds::thresholds_for;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    fn internal(error: anyhow::Error) -> Self {
+        Self::new(INTERNAL_ERROR, format!("{:#}", error))
+    }
+}
+
+/// Serve requests until stdin is closed
+pub fn run(analyzer: &CodeAnalyzer) -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout().lock();
+    let mut session = Session::new(analyzer)?;
+
+    for line in stdin.lock().lines() {
+        let line = line.context("Failed to read request from stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = session.handle_line(&line) {
+            writeln!(stdout, "{}", response).context("Failed to write response")?;
+            stdout.flush().context("Failed to write response")?;
+        }
+    }
+    Ok(())
+}
+
+/// One client's connection: the analyzer plus the last parse of each buffer
+pub struct Session<'a> {
+    analyzer: &'a CodeAnalyzer,
+    buffers: HashMap<String, RetainedTree>,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(analyzer: &'a CodeAnalyzer) -> Result<Self> {
+        Ok(Self { analyzer, buffers: HashMap::new() })
+    }
+
+    /// Answer one line of input; `None` for notifications
+    pub fn handle_line(&mut self, line: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string()))),
+        };
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return Some(error_response(id.unwrap_or(Value::Null), RpcError::new(INVALID_REQUEST, "missing method")));
+        };
+        let params = request.get("params").unwrap_or(&Value::Null);
+
+        let outcome = self.dispatch(method, params);
+        let id = id?;
+        Some(match outcome {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn dispatch(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "analyzeFile" => {
+                let path = string_param(params, "path")?;
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Failed to read {}: {}", path, e)))?;
+                self.analyze(path, text)
+            }
+            "analyzeBuffer" => {
+                let path = string_param(params, "path")?;
+                let text = string_param(params, "text")?;
+                self.analyze(path, text.to_string())
+            }
+            "closeBuffer" => {
+                let path = string_param(params, "path")?;
+                self.buffers.remove(path);
+                Ok(Value::Null)
+            }
+            "getThresholds" => {
+                let config = self.analyzer.config();
+                let thresholds = match params.get("path").and_then(Value::as_str) {
+                    Some(path) => thresholds_for(Path::new(path), &config.thresholds, &config.directory_thresholds),
+                    None => &config.thresholds,
+                };
+                serde_json::to_value(thresholds).map_err(|e| RpcError::internal(e.into()))
+            }
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+        }
+    }
+
+    /// Functions and per-line complexity of one file's text, from the previous parse of `path` when there is one
+    ///
+    /// Measured by the same parser as `analyze`, so both report the same numbers.
+    fn analyze(&mut self, path: &str, text: String) -> Result<Value, RpcError> {
+        let previous = self.buffers.remove(path);
+        let (parsed, retained) = self.analyzer.parse_retained(Path::new(path), text, previous)
+            .map_err(RpcError::internal)?;
+
+        let config = self.analyzer.config();
+        let thresholds = thresholds_for(Path::new(path), &config.thresholds, &config.directory_thresholds);
+        let functions: Vec<Value> = parsed.functions.iter()
+            .map(|function| json!({
+                "name": function.name,
+                "complexity": function.complexity,
+                "level": thresholds.function_level(function.complexity),
+                "lineStart": function.line_start,
+                "lineEnd": function.line_end,
+                "parameters": function.parameters,
+            }))
+            .collect();
+        let weights = config.error_handling.for_language(&retained.language().language());
+        let result = json!({
+            "path": path,
+            "lines": parsed.line_count,
+            "functions": functions,
+            "lineComplexity": line_complexity::for_tree(retained.root_node(), retained.source(), weights),
+        });
+
+        self.buffers.insert(path.to_string(), retained);
+        Ok(result)
+    }
+}
+
+fn string_param<'a>(params: &'a Value, name: &str) -> Result<&'a str, RpcError> {
+    params.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("missing string parameter '{}'", name)))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_notifications_and_errors() {
+        let analyzer = CodeAnalyzer::new();
+        let mut session = Session::new(&analyzer).unwrap();
+
+        let response = session.handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"getThresholds"}"#).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["function_complexity_warn"], 10);
+
+        let response = session.handle_line(
+            r#"{"jsonrpc":"2.0","id":2,"method":"analyzeBuffer","params":{"path":"a.rs","text":"fn f(x: i32) {\n    if x > 0 {}\n}\n"}}"#,
+        ).unwrap();
+        assert_eq!(response["result"]["functions"][0]["name"], "f");
+        assert_eq!(response["result"]["lineComplexity"][0]["line"], 2);
+
+        let response = session.handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"format"}"#).unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        let response = session.handle_line(r#"{"jsonrpc":"2.0","id":4,"method":"analyzeBuffer","params":{}}"#).unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        assert_eq!(session.handle_line("not json").unwrap()["error"]["code"], PARSE_ERROR);
+
+        assert!(session.handle_line(r#"{"jsonrpc":"2.0","method":"getThresholds"}"#).is_none());
+    }
+
+    #[test]
+    fn test_later_buffer_versions_reuse_the_previous_tree() {
+        let analyzer = CodeAnalyzer::new();
+        let mut session = Session::new(&analyzer).unwrap();
+
+        session.handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"analyzeBuffer","params":{"path":"a.rs","text":"fn f(x: i32) {\n    if x > 0 {}\n}\n"}}"#);
+        assert!(session.buffers.contains_key("a.rs"));
+
+        let response = session.handle_line(
+            r#"{"jsonrpc":"2.0","id":2,"method":"analyzeBuffer","params":{"path":"a.rs","text":"fn f(x: i32) {\n    if x > 0 {}\n    if x > 1 {}\n}\n"}}"#,
+        ).unwrap();
+        assert_eq!(response["result"]["functions"][0]["complexity"], 3);
+        assert_eq!(response["result"]["lineComplexity"][1]["line"], 3);
+
+        session.handle_line(r#"{"jsonrpc":"2.0","method":"closeBuffer","params":{"path":"a.rs"}}"#);
+        assert!(session.buffers.is_empty());
+    }
+
+    #[test]
+    fn test_buffers_measure_the_same_as_analyze() -> Result<()> {
+        let analyzer = CodeAnalyzer::new();
+        let mut session = Session::new(&analyzer)?;
+        let versions = [
+            "def grade(n):\n    if n > 90:\n        return 'a'\n    return 'b'\n",
+            "def grade(n):\n    if n > 90:\n        return 'a'\n    elif n > 80 and n < 85:\n        return 'b'\n    return 'c'\n\ndef noop():\n    pass\n",
+        ];
+
+        for text in versions {
+            let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "analyzeBuffer", "params": { "path": "grade.py", "text": text } });
+            let response = session.handle_line(&request.to_string()).unwrap();
+            let parsed = analyzer.parse_source(Path::new("grade.py"), text)?;
+
+            let functions = response["result"]["functions"].as_array().unwrap();
+            assert_eq!(functions.len(), parsed.functions.len());
+            for (reported, expected) in functions.iter().zip(&parsed.functions) {
+                assert_eq!(reported["name"], expected.name);
+                assert_eq!(reported["complexity"], expected.complexity);
+                assert_eq!((reported["lineStart"].clone(), reported["lineEnd"].clone()), (json!(expected.line_start), json!(expected.line_end)));
+            }
+            assert_eq!(response["result"]["lines"], parsed.line_count);
+        }
+        Ok(())
+    }
+}
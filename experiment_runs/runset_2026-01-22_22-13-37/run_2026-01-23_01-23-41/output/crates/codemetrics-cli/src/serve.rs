@@ -0,0 +1,449 @@
+//! Live dashboard over HTTP, kept current over a WebSocket
+//!
+//! `serve` analyzes the project, then follows file changes the way `watch`
+//! does. On the listen address, `GET /` returns the dashboard page with its
+//! styles and chart script inlined, `GET /metrics.json` the latest update,
+//! `GET /metrics` the latest results for Prometheus to scrape, and `GET /ws`
+//! upgrades to a WebSocket. Every connected dashboard receives the
+//! latest update as soon as it connects, then one per batch of changes, as a
+//! JSON text message:
+//!
+//! `{"timestamp": "...", "files_analyzed": 120, "total_lines": 8400,
+//! "average_complexity": 3.2, "high_complexity_functions": 4, "health": 81.5,
+//! "analysis_ms": 35, "changed": [...], "most_complex_functions": [...]}`
+//!
+//! Requests are answered by a fixed set of worker threads. Only requests
+//! naming the host as `localhost` or by IP address are answered, and a
+//! browser's `Origin` must match that host, so a page elsewhere cannot reach
+//! the server by pointing its own domain at this address. Dashboards only
+//! listen; what they send is read now and then to answer pings and closes.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+use codemetrics_core::analyzers::{AnalysisResults, CodeAnalyzer, HighComplexityFunction};
+use codemetrics_report::reporters::prometheus;
+use codemetrics_report::visualization::{VisualizationConfig, VisualizationEngine};
+
+use crate::watch::{self, FunctionDelta};
+
+/// A dashboard that stops reading is dropped instead of stalling the others
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A client gets this long to send its request line and headers
+const HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest request line and headers accepted, in bytes
+const MAX_HEAD: u64 = 16 * 1024;
+
+/// Threads answering HTTP requests; further connections wait for one
+const WORKERS: usize = 8;
+
+/// How often dashboards are checked for pings and closes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Most complex functions listed in each update
+const MOST_COMPLEX: usize = 10;
+
+/// One message to the dashboards
+#[derive(Serialize)]
+struct Update<'a> {
+    timestamp: String,
+    files_analyzed: usize,
+    total_lines: u32,
+    average_complexity: f64,
+    high_complexity_functions: usize,
+    health: Option<f64>,
+    /// Time to re-parse the changed files and aggregate
+    analysis_ms: u64,
+    /// Functions whose complexity changed in this batch
+    changed: &'a [FunctionDelta],
+    /// Not `hotspots`, which in the results are files ranked by complexity and churn or by duplication
+    most_complex_functions: &'a [HighComplexityFunction],
+}
+
+impl<'a> Update<'a> {
+    fn new(results: &'a AnalysisResults, changed: &'a [FunctionDelta], elapsed: Duration) -> Self {
+        Self {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            files_analyzed: results.files_analyzed,
+            total_lines: results.total_lines,
+            average_complexity: results.average_complexity,
+            high_complexity_functions: results.high_complexity_functions.len(),
+            health: results.health.as_ref().map(|health| health.overall),
+            analysis_ms: elapsed.as_millis() as u64,
+            changed,
+            most_complex_functions: &results.high_complexity_functions[..results.high_complexity_functions.len().min(MOST_COMPLEX)],
+        }
+    }
+}
+
+type Dashboard = Arc<Mutex<WebSocket<TcpStream>>>;
+
+/// Connected dashboards and the update they are all on
+///
+/// Each dashboard has a lock of its own, so sending to a slow one holds up
+/// neither connecting dashboards nor HTTP requests.
+#[derive(Default)]
+struct Hub {
+    latest: Mutex<String>,
+    /// The latest results in the Prometheus text format
+    metrics: Mutex<String>,
+    sockets: Mutex<Vec<Dashboard>>,
+}
+
+impl Hub {
+    /// Send `message` to every dashboard, dropping those that went away; returns how many are left
+    fn publish(&self, message: String) -> usize {
+        let sockets = {
+            let sockets = lock(&self.sockets);
+            *lock(&self.latest) = message.clone();
+            sockets.clone()
+        };
+        let gone: Vec<Dashboard> = sockets.into_iter()
+            .filter(|socket| lock(socket).send(Message::Text(message.clone())).is_err())
+            .collect();
+        self.drop_sockets(&gone)
+    }
+
+    fn join(&self, socket: WebSocket<TcpStream>) {
+        let socket = Arc::new(Mutex::new(socket));
+        // Held until `latest` is sent, so a concurrent publish reaches this dashboard after it
+        let mut sending = lock(&socket);
+        let latest = {
+            let mut sockets = lock(&self.sockets);
+            sockets.push(Arc::clone(&socket));
+            lock(&self.latest).clone()
+        };
+        if !latest.is_empty() && sending.send(Message::Text(latest)).is_err() {
+            drop(sending);
+            self.drop_sockets(&[socket]);
+        }
+    }
+
+    /// Answer pings and let go of dashboards that closed, without waiting on any of them
+    fn poll(&self) -> usize {
+        let sockets = lock(&self.sockets).clone();
+        let gone: Vec<Dashboard> = sockets.into_iter()
+            .filter(|socket| !read_control_frames(&mut lock(socket)))
+            .collect();
+        self.drop_sockets(&gone)
+    }
+
+    fn drop_sockets(&self, gone: &[Dashboard]) -> usize {
+        let mut sockets = lock(&self.sockets);
+        sockets.retain(|socket| !gone.iter().any(|gone| Arc::ptr_eq(socket, gone)));
+        sockets.len()
+    }
+
+    fn latest(&self) -> String {
+        lock(&self.latest).clone()
+    }
+
+    fn set_metrics(&self, metrics: String) {
+        *lock(&self.metrics) = metrics;
+    }
+
+    fn metrics(&self) -> String {
+        lock(&self.metrics).clone()
+    }
+}
+
+/// Read whatever a dashboard sent without blocking; false once it closed or failed
+///
+/// tungstenite queues the pong for a ping and the reply to a close as it
+/// reads them; the flush sends them.
+fn read_control_frames(socket: &mut WebSocket<TcpStream>) -> bool {
+    if socket.get_ref().set_nonblocking(true).is_err() {
+        return false;
+    }
+    let open = loop {
+        match socket.read() {
+            Ok(Message::Close(_)) => break false,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => break true,
+            Err(_) => break false,
+        }
+    };
+    let blocking = socket.get_ref().set_nonblocking(false).is_ok();
+    let flushed = socket.flush().is_ok();
+    open && blocking && flushed
+}
+
+/// A panicking connection thread leaves the data intact, so poisoning is ignored
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub fn run(analyzer: &CodeAnalyzer, root: &Path, listen: &str) -> Result<()> {
+    let listener = TcpListener::bind(listen).with_context(|| format!("Failed to listen on {}", listen))?;
+    let address = listener.local_addr()?;
+
+    let project_name = root.canonicalize().ok()
+        .and_then(|root| root.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "Project".to_string());
+    let config = VisualizationConfig { inline_assets: true, ..VisualizationConfig::default() };
+    let page: Arc<str> = VisualizationEngine::new()?
+        .render_live_dashboard(&project_name, &config, address.port())?
+        .into();
+
+    let hub = Arc::new(Hub::default());
+    start(listener, page, Arc::clone(&hub));
+
+    watch::follow(analyzer, root, |previous, results, deltas, elapsed| {
+        hub.set_metrics(prometheus::metrics(results));
+        let dashboards = hub.publish(serde_json::to_string(&Update::new(results, deltas, elapsed))?);
+        match previous {
+            Some(_) => println!(
+                "[{}] {} function(s) changed, average complexity {:.2}; sent to {} dashboard(s)",
+                chrono::Local::now().format("%H:%M:%S"),
+                deltas.len(),
+                results.average_complexity,
+                dashboards
+            ),
+            None => println!(
+                "Dashboard for {} ({} files) at http://{}/. Press Ctrl+C to stop.",
+                root.display(),
+                results.files_analyzed,
+                address
+            ),
+        }
+        Ok(())
+    })
+}
+
+/// Accept connections on `listener` for the worker threads, and poll dashboards in the background
+fn start(listener: TcpListener, page: Arc<str>, hub: Arc<Hub>) {
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(WORKERS);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..WORKERS {
+        let (hub, page, receiver) = (Arc::clone(&hub), Arc::clone(&page), Arc::clone(&receiver));
+        thread::spawn(move || loop {
+            // Released before handling, so one idle worker takes each connection
+            let Ok(stream) = lock(&receiver).recv() else {
+                break;
+            };
+            if let Err(e) = handle(stream, &page, &hub) {
+                eprintln!("Warning: dashboard connection failed - {:#}", e);
+            }
+        });
+    }
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if sender.send(stream).is_err() {
+                break;
+            }
+        }
+    });
+    thread::spawn(move || loop {
+        thread::sleep(POLL_INTERVAL);
+        hub.poll();
+    });
+}
+
+/// Answer one HTTP request, handing WebSocket upgrades to the hub
+fn handle(mut stream: TcpStream, page: &str, hub: &Hub) -> Result<()> {
+    stream.set_read_timeout(Some(HEADER_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?).take(MAX_HEAD);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    if reader.limit() == 0 {
+        return respond(&mut stream, "431 Request Header Fields Too Large", "text/plain", "Request headers are too large");
+    }
+    stream.set_read_timeout(None)?;
+    if !is_same_host(headers.get("host"), headers.get("origin")) {
+        return respond(&mut stream, "403 Forbidden", "text/plain", "Only local hosts and same-origin pages are served");
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default();
+    match (method, path) {
+        ("GET", "/ws") => {
+            let Some(key) = headers.get("sec-websocket-key") else {
+                return respond(&mut stream, "400 Bad Request", "text/plain", "Expected a WebSocket upgrade");
+            };
+            write!(
+                stream,
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                derive_accept_key(key.as_bytes())
+            )?;
+            stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            hub.join(WebSocket::from_raw_socket(stream, Role::Server, None));
+            Ok(())
+        }
+        ("GET", "/" | "/index.html") => respond(&mut stream, "200 OK", "text/html; charset=utf-8", page),
+        ("GET", "/metrics.json") => respond(&mut stream, "200 OK", "application/json", &hub.latest()),
+        ("GET", "/metrics") => respond(&mut stream, "200 OK", "text/plain; version=0.0.4; charset=utf-8", &hub.metrics()),
+        ("GET", _) => respond(&mut stream, "404 Not Found", "text/plain", "Not found"),
+        _ => respond(&mut stream, "405 Method Not Allowed", "text/plain", "Only GET is supported"),
+    }
+}
+
+/// Whether `host` names this machine in a way no other site's domain can, and `origin`, if sent, is that same host
+///
+/// DNS rebinding points a hostile domain at this address; the browser then
+/// sends that domain as the host, which is neither `localhost` nor an IP.
+fn is_same_host(host: Option<&String>, origin: Option<&String>) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    let name = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    if !name.eq_ignore_ascii_case("localhost") && name.parse::<IpAddr>().is_err() {
+        return false;
+    }
+    origin.is_none_or(|origin| {
+        let authority = origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://"));
+        authority.is_some_and(|authority| authority.eq_ignore_ascii_case(host))
+    })
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dashboards_get_the_latest_update_on_connect() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let hub = Arc::new(Hub::default());
+        hub.publish(r#"{"files_analyzed":3}"#.to_string());
+        hub.set_metrics("codemetrics_files_analyzed 3\n".to_string());
+
+        let server_hub = Arc::clone(&hub);
+        let server = thread::spawn(move || -> Result<()> {
+            for _ in 0..3 {
+                let (stream, _) = listener.accept()?;
+                handle(stream, "<html></html>", &server_hub)?;
+            }
+            Ok(())
+        });
+
+        let mut page = TcpStream::connect(address)?;
+        page.write_all(b"GET /metrics.json HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+        let mut response = String::new();
+        page.read_to_string(&mut response)?;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(r#"{"files_analyzed":3}"#));
+
+        let mut scrape = TcpStream::connect(address)?;
+        scrape.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+        let mut response = String::new();
+        scrape.read_to_string(&mut response)?;
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(response.ends_with("codemetrics_files_analyzed 3\n"));
+
+        let (mut socket, _) = tungstenite::client(format!("ws://{}/ws", address), TcpStream::connect(address)?)
+            .map_err(|e| anyhow::anyhow!("WebSocket handshake failed: {}", e))?;
+        assert_eq!(socket.read()?, Message::Text(r#"{"files_analyzed":3}"#.to_string()));
+        server.join().unwrap()?;
+
+        assert_eq!(hub.publish(r#"{"files_analyzed":4}"#.to_string()), 1);
+        assert_eq!(socket.read()?, Message::Text(r#"{"files_analyzed":4}"#.to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_requests_for_other_hosts_are_refused() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let server = thread::spawn(move || -> Result<()> {
+            for _ in 0..3 {
+                let (stream, _) = listener.accept()?;
+                handle(stream, "<html></html>", &Hub::default())?;
+            }
+            Ok(())
+        });
+
+        let status = |request: &str| -> Result<String> {
+            let mut stream = TcpStream::connect(address)?;
+            stream.write_all(request.as_bytes())?;
+            let mut response = String::new();
+            stream.read_to_string(&mut response)?;
+            Ok(response.lines().next().unwrap_or_default().to_string())
+        };
+        assert_eq!(status("GET / HTTP/1.1\r\nHost: rebound.example:8080\r\n\r\n")?, "HTTP/1.1 403 Forbidden");
+        assert_eq!(
+            status("GET / HTTP/1.1\r\nHost: 127.0.0.1:8080\r\nOrigin: http://rebound.example:8080\r\n\r\n")?,
+            "HTTP/1.1 403 Forbidden"
+        );
+        assert_eq!(status("GET / HTTP/1.1\r\nHost: [::1]:8080\r\nOrigin: http://[::1]:8080\r\n\r\n")?, "HTTP/1.1 200 OK");
+        server.join().unwrap()
+    }
+
+    #[test]
+    fn test_pings_are_answered_and_closed_dashboards_dropped() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let hub = Arc::new(Hub::default());
+        let server_hub = Arc::clone(&hub);
+        let server = thread::spawn(move || -> Result<()> {
+            let (stream, _) = listener.accept()?;
+            handle(stream, "<html></html>", &server_hub)
+        });
+        let (mut socket, _) = tungstenite::client(format!("ws://{}/ws", address), TcpStream::connect(address)?)
+            .map_err(|e| anyhow::anyhow!("WebSocket handshake failed: {}", e))?;
+        server.join().unwrap()?;
+
+        socket.get_ref().set_read_timeout(Some(Duration::from_millis(10)))?;
+        socket.send(Message::Ping(vec![7]))?;
+        let mut answered = false;
+        for _ in 0..100 {
+            hub.poll();
+            if let Ok(message) = socket.read() {
+                answered = message == Message::Pong(vec![7]);
+                break;
+            }
+        }
+        assert!(answered);
+
+        socket.close(None)?;
+        let mut dashboards = 1;
+        for _ in 0..100 {
+            dashboards = hub.poll();
+            if dashboards == 0 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(dashboards, 0);
+        Ok(())
+    }
+}
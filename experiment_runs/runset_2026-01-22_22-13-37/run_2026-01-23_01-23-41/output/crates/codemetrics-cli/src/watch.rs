@@ -0,0 +1,215 @@
+//! Continuous re-analysis while files change
+//!
+//! Only files reported by the filesystem notifier are re-parsed, and only
+//! their counts are taken out of and put back into the running totals (see
+//! `LiveResults`). After each batch of changes the summary delta is printed
+//! along with the functions whose complexity moved; `serve` pushes the same
+//! updates to dashboards instead.
+
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use codemetrics_core::analyzers::live::LiveResults;
+use codemetrics_core::analyzers::{AnalysisResults, CodeAnalyzer};
+use codemetrics_core::parsers::ParsedFile;
+use serde::Serialize;
+
+/// Editors often write a file several times in quick succession
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub fn run(analyzer: &CodeAnalyzer, root: &Path) -> Result<()> {
+    follow(analyzer, root, |previous, results, deltas, _| {
+        match previous {
+            Some(previous) => print_delta(previous, results, deltas),
+            None => println!(
+                "Watching {} ({} files, average complexity {:.2}). Press Ctrl+C to stop.",
+                root.display(),
+                results.files_analyzed,
+                results.average_complexity
+            ),
+        }
+        Ok(())
+    })
+}
+
+/// Analyze `root`, then re-analyze the files that change until the watcher stops
+///
+/// `on_update` gets the previous results (`None` for the first analysis), the
+/// new ones, the functions whose complexity changed and how long that took.
+/// Any change to a parse is reported, even when no function's complexity moved.
+pub fn follow(
+    analyzer: &CodeAnalyzer,
+    root: &Path,
+    mut on_update: impl FnMut(Option<&AnalysisResults>, &AnalysisResults, &[FunctionDelta], Duration) -> Result<()>,
+) -> Result<()> {
+    // The notifier reports absolute paths, so parse under the absolute root too
+    let root = &root.canonicalize()
+        .with_context(|| format!("Failed to resolve {}", root.display()))?;
+    let cache = analyzer.open_cache()?;
+
+    let started = Instant::now();
+    let mut live = LiveResults::new(analyzer, analyzer.parse_path(root)?.into_iter().flatten());
+    let mut results = live.results()?;
+    on_update(None, &results, &[], started.elapsed())?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).context("Failed to start file watcher")?;
+    watcher.watch(root, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", root.display()))?;
+
+    while let Ok(event) = receiver.recv() {
+        let mut changed = BTreeSet::new();
+        collect_paths(event, &mut changed);
+        while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+            collect_paths(event, &mut changed);
+        }
+
+        let started = Instant::now();
+        let mut deltas = Vec::new();
+        let mut any_changed = false;
+        for path in changed {
+            let file = if analyzer.accepts(&path) {
+                analyzer.parse_one(&path, cache.as_ref())
+                    .inspect_err(|e| eprintln!("Warning: Failed to parse {} - {}", path.display(), e))
+                    .ok()
+            } else {
+                None
+            };
+            any_changed |= update(&mut live, &path.to_string_lossy(), file, &mut deltas);
+        }
+        if !any_changed {
+            continue;
+        }
+
+        let updated = live.results()?;
+        on_update(Some(&results), &updated, &deltas, started.elapsed())?;
+        results = updated;
+    }
+
+    Ok(())
+}
+
+/// Count `file` as `path`'s parse, noting which functions moved; false when the parse is unchanged
+fn update(live: &mut LiveResults, path: &str, file: Option<ParsedFile>, deltas: &mut Vec<FunctionDelta>) -> bool {
+    if live.file(path) == file.as_ref() {
+        return false;
+    }
+    deltas.extend(function_deltas(live.file(path), file.as_ref()));
+    live.replace(path, file);
+    true
+}
+
+fn collect_paths(event: notify::Result<Event>, changed: &mut BTreeSet<PathBuf>) {
+    match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => changed.extend(event.paths),
+        Ok(_) => {}
+        Err(e) => eprintln!("Warning: file watcher error - {}", e),
+    }
+}
+
+/// A function whose complexity changed; `None` means it did not exist on that side
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FunctionDelta {
+    pub file_path: String,
+    pub name: String,
+    pub before: Option<u32>,
+    pub after: Option<u32>,
+}
+
+/// Functions of one file whose complexity differs between two parses
+pub fn function_deltas(before: Option<&ParsedFile>, after: Option<&ParsedFile>) -> Vec<FunctionDelta> {
+    let complexities = |file: Option<&ParsedFile>| -> BTreeMap<String, u32> {
+        file.map(|file| file.functions.iter().map(|function| (function.name.clone(), function.complexity)).collect())
+            .unwrap_or_default()
+    };
+    let Some(file_path) = after.or(before).map(|file| file.path.clone()) else {
+        return Vec::new();
+    };
+    let old = complexities(before);
+    let new = complexities(after);
+
+    let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    names.into_iter()
+        .map(|name| FunctionDelta {
+            file_path: file_path.clone(),
+            name: name.clone(),
+            before: old.get(name).copied(),
+            after: new.get(name).copied(),
+        })
+        .filter(|delta| delta.before != delta.after)
+        .collect()
+}
+
+fn print_delta(before: &AnalysisResults, after: &AnalysisResults, deltas: &[FunctionDelta]) {
+    let health = |results: &AnalysisResults| results.health.as_ref().map_or(0.0, |health| health.overall);
+    println!(
+        "\n[{}] lines {} → {}, average complexity {:.2} → {:.2}, hotspots {} → {}, health {:.0} → {:.0}",
+        chrono::Local::now().format("%H:%M:%S"),
+        before.total_lines,
+        after.total_lines,
+        before.average_complexity,
+        after.average_complexity,
+        before.high_complexity_functions.len(),
+        after.high_complexity_functions.len(),
+        health(before),
+        health(after),
+    );
+
+    for delta in deltas {
+        let (marker, change) = match (delta.before, delta.after) {
+            (Some(old), Some(new)) if new > old => ("▲ worse ", format!("{} → {}", old, new)),
+            (Some(old), Some(new)) => ("▼ better", format!("{} → {}", old, new)),
+            (None, Some(new)) => ("+ added ", format!("complexity {}", new)),
+            (Some(old), None) => ("- removed", format!("was {}", old)),
+            (None, None) => continue,
+        };
+        println!("  {} {} ({}) {}", marker, delta.name, delta.file_path, change);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codemetrics_core::parsers::LanguageParser;
+
+    #[test]
+    fn test_function_deltas_report_only_changes() {
+        let parser = LanguageParser::new();
+        let before = parser.parse_file("lib.rs", "fn steady() {}\nfn grows(x: i32) { if x > 0 {} }\nfn gone() {}\n").unwrap();
+        let after = parser.parse_file(
+            "lib.rs",
+            "fn steady() {}\nfn grows(x: i32) { if x > 0 {} if x > 1 {} if x > 2 {} }\nfn fresh() {}\n",
+        ).unwrap();
+
+        let deltas = function_deltas(Some(&before), Some(&after));
+        let summary: Vec<(&str, Option<u32>, Option<u32>)> = deltas.iter()
+            .map(|delta| (delta.name.as_str(), delta.before, delta.after))
+            .collect();
+        assert_eq!(summary.len(), 3);
+        assert!(summary.iter().any(|(name, old, new)| *name == "grows" && new > old));
+        assert!(summary.contains(&("fresh", None, Some(1))));
+        assert!(summary.iter().any(|(name, _, new)| *name == "gone" && new.is_none()));
+
+        assert_eq!(function_deltas(Some(&before), None).len(), 3);
+    }
+
+    #[test]
+    fn test_a_change_that_only_moves_lines_is_reported() -> Result<()> {
+        let analyzer = CodeAnalyzer::new();
+        let parser = LanguageParser::new();
+        let source = "fn steady(x: i32) { if x > 0 {} }\n";
+        let mut live = LiveResults::new(&analyzer, [parser.parse_file("lib.rs", source)?]);
+        let mut deltas = Vec::new();
+
+        assert!(!update(&mut live, "lib.rs", Some(parser.parse_file("lib.rs", source)?), &mut deltas));
+        let padded = format!("// a comment\n\n{}", source);
+        assert!(update(&mut live, "lib.rs", Some(parser.parse_file("lib.rs", &padded)?), &mut deltas));
+        assert!(deltas.is_empty());
+        assert_eq!(live.results()?.total_lines, 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+//! Parsing with a fresh `Parser` per file versus the per-thread pool
+//!
+//! Run with `cargo bench -p codemetrics-core --bench parser_pool`. The
+//! difference is the parser setup cost the pool removes from every file after
+//! a thread's first.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use tree_sitter::Parser;
+
+use codemetrics_core::parsers::{pool, SupportedLanguage};
+
+const RUST_SOURCE: &str = include_str!("../examples/sample_code.rs");
+const PYTHON_SOURCE: &str = "def total(items):\n    return sum(item.price for item in items if item.active)\n";
+
+fn fresh_parser(language: SupportedLanguage, source: &str) -> tree_sitter::Tree {
+    let mut parser = Parser::new();
+    parser.set_language(&language.get_language()).unwrap();
+    parser.parse(source, None).unwrap()
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, language, source) in [
+        ("rust", SupportedLanguage::Rust, RUST_SOURCE),
+        ("python-small", SupportedLanguage::Python, PYTHON_SOURCE),
+    ] {
+        group.bench_with_input(BenchmarkId::new("fresh", name), source, |b, source| {
+            b.iter(|| fresh_parser(language, black_box(source)))
+        });
+        group.bench_with_input(BenchmarkId::new("pooled", name), source, |b, source| {
+            b.iter(|| pool::parse(language, black_box(source)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -9,12 +9,11 @@
 //! - Performance profiling
 
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::Path;
 use std::time::Instant;
 
-// Import our analyzer modules (this would be a proper crate import in real usage)
-use codemetrics::analyzer::CodeAnalyzer;
-use codemetrics::core::types::Language;
+use codemetrics_core::ast_analyzer::ASTAnalyzer;
+use codemetrics_core::core::types::Language;
 
 fn main() -> Result<()> {
     println!("🚀 Enhanced Code Analysis Demo");
@@ -174,20 +173,23 @@ async fn fetch_and_process_data(urls: Vec<String>) -> Result<Vec<ProcessedItem>,
 }
 "#;
 
-    // Analyze the code
     let start = Instant::now();
-    // In a real scenario, you'd analyze this through the analyzer
+    let (metrics, _, functions, _) = ASTAnalyzer::new()?
+        .analyze_file(complex_rust_code, &Language::Rust, Path::new("demo.rs"))?;
     println!("📝 Code analyzed in {:?}", start.elapsed());
 
-    // Simulated results that our analyzer would produce:
     println!("🔍 Analysis Results:");
-    println!("  • Functions found: 3");
-    println!("  • Cyclomatic Complexity: 23 (High - consider refactoring)");
-    println!("  • Max nesting depth: 8 (Very deep - extract nested logic)");
-    println!("  • Recursive functions: fibonacci (potential performance issue)");
-    println!("  • Async functions: fetch_and_process_data");
-    println!("  • Lines of code: ~95");
-    println!("  • Maintainability Index: 42/100 (Below average)");
+    println!("  • Functions found: {}", functions.len());
+    println!("  • Cyclomatic Complexity: {}", metrics.cyclomatic_complexity);
+    println!("  • Max nesting depth: {}", metrics.max_nesting_depth);
+    for function in functions.iter().filter(|function| function.is_recursive) {
+        println!("  • Recursive function: {}", function.name);
+    }
+    for function in functions.iter().filter(|function| function.is_async) {
+        println!("  • Async function: {}", function.name);
+    }
+    println!("  • Lines of code: {}", metrics.lines_of_code);
+    println!("  • Maintainability Index: {:.0}/100", metrics.maintainability_index);
     println!();
 
     Ok(())
@@ -288,21 +290,18 @@ async function fetchUserData(userId, options = {}) {
 }
 "#;
 
+    let (_, issues, functions, _) = ASTAnalyzer::new()?
+        .analyze_file(javascript_code, &Language::JavaScript, Path::new("demo.js"))?;
+
     println!("🔍 Security Analysis Results:");
-    println!("  🚨 HIGH RISK:");
-    println!("    • eval() usage detected (line 4) - Code injection risk");
-    println!("    • innerHTML with user data (line 7) - XSS vulnerability");
-    println!("    • Dynamic script in setTimeout (line 10) - Code injection risk");
-    println!();
-    println!("  ✅ GOOD PATTERNS FOUND:");
-    println!("    • Safe JSON parsing with error handling");
-    println!("    • Use of textContent instead of innerHTML");
-    println!("    • Proper timeout handling with AbortController");
+    for issue in &issues {
+        println!("  • {:?} line {}: {}", issue.severity, issue.line, issue.message);
+    }
     println!();
     println!("  📊 COMPLEXITY ANALYSIS:");
-    println!("    • processUserInput: Complexity 1 (Simple but dangerous)");
-    println!("    • betterProcessUserInput: Complexity 2 (Simple and safe)");
-    println!("    • fetchUserData: Complexity 8 (Moderate complexity)");
+    for function in &functions {
+        println!("    • {}: Complexity {}", function.name, function.cyclomatic_complexity);
+    }
     println!();
 
     Ok(())
@@ -379,147 +378,3 @@ fn demo_performance_analysis() -> Result<()> {
 
     Ok(())
 }
-
-// Mock types for the example
-#[derive(Debug)]
-struct User {
-    id: String,
-    is_active: bool,
-    account_type: AccountType,
-    subscription: Subscription,
-    features: Vec<Feature>,
-    last_active: std::time::SystemTime,
-    trial_expires_at: std::time::SystemTime,
-}
-
-#[derive(Debug)]
-enum AccountType {
-    Premium,
-    Basic,
-    Trial,
-}
-
-#[derive(Debug)]
-struct Subscription {
-    valid: bool,
-}
-
-impl Subscription {
-    fn is_valid(&self) -> bool { self.valid }
-}
-
-#[derive(Debug)]
-struct Feature {
-    category: FeatureCategory,
-    enabled: bool,
-}
-
-impl Feature {
-    fn is_enabled(&self) -> bool { self.enabled }
-    fn get_usage_stats(&self) -> Option<UsageStats> { None }
-}
-
-#[derive(Debug)]
-enum FeatureCategory {
-    Core,
-    Experimental,
-    Other,
-}
-
-#[derive(Debug)]
-struct ProcessingConfig {
-    threshold: u32,
-    include_experimental: bool,
-    basic_feature_limit: usize,
-    cleanup_threshold: std::time::SystemTime,
-}
-
-#[derive(Debug)]
-struct ProcessingStats {
-    core_features: u32,
-    experimental_features: u32,
-    other_features: u32,
-    basic_users: u32,
-    expired_trials: u32,
-    inactive_users: u32,
-}
-
-impl ProcessingStats {
-    fn new() -> Self {
-        Self {
-            core_features: 0,
-            experimental_features: 0,
-            other_features: 0,
-            basic_users: 0,
-            expired_trials: 0,
-            inactive_users: 0,
-        }
-    }
-}
-
-#[derive(Debug)]
-enum ProcessingResult {
-    HighUsage(UsageStats),
-    NormalUsage(UsageStats),
-    Experimental,
-    MarkedForCleanup,
-}
-
-#[derive(Debug)]
-struct UsageStats {
-    daily_active_users: u32,
-}
-
-#[derive(Debug)]
-struct ProcessedData {
-    results: std::collections::HashMap<String, ProcessingResult>,
-    stats: ProcessingStats,
-}
-
-#[derive(Debug)]
-enum Error {
-    InvalidSubscription(String),
-    FeatureLimitExceeded,
-    NoValidUsers,
-}
-
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Error::InvalidSubscription(id) => write!(f, "Invalid subscription for user {}", id),
-            Error::FeatureLimitExceeded => write!(f, "Feature limit exceeded"),
-            Error::NoValidUsers => write!(f, "No valid users found"),
-        }
-    }
-}
-
-impl std::error::Error for Error {}
-
-// Additional mock types
-#[derive(Debug)]
-struct RawData {
-    format: DataFormat,
-}
-
-#[derive(Debug)]
-enum DataFormat {
-    Json,
-    Xml,
-    Csv,
-    Unknown,
-}
-
-#[derive(Debug)]
-struct ProcessedItem;
-
-fn process_json_data(_data: RawData) -> Result<ProcessedItem> {
-    Ok(ProcessedItem)
-}
-
-fn process_xml_data(_data: RawData) -> Result<ProcessedItem> {
-    Ok(ProcessedItem)
-}
-
-fn process_csv_data(_data: RawData) -> Result<ProcessedItem> {
-    Ok(ProcessedItem)
-}
\ No newline at end of file
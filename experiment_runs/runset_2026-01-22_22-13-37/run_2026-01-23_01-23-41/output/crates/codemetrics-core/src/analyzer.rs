@@ -4,8 +4,8 @@ use std::collections::HashMap;
 use walkdir::WalkDir;
 use std::time::Instant;
 
-use crate::ast_analyzer::{ASTAnalyzer, FunctionAnalysis};
-use crate::core::{Language as LangType, CodeIssue as CoreCodeIssue, IssueSeverity as CoreIssueSeverity, IssueCategory};
+use crate::ast_analyzer::{ASTAnalyzer, RetainedParse};
+use crate::core::Language as LangType;
 
 /// Core analyzer that orchestrates the code analysis process
 pub struct CodeAnalyzer {
@@ -27,6 +27,7 @@ pub struct AnalysisResults {
 
 #[derive(Debug)]
 pub struct AnalysisIssue {
+    pub rule_id: &'static str,
     pub file_path: PathBuf,
     pub line: u32,
     pub severity: IssueSeverity,
@@ -58,7 +59,8 @@ impl CodeAnalyzer {
         }
 
         let ast_analyzer = ASTAnalyzer::new()
-            .context("Failed to initialize AST analyzer")?;
+            .context("Failed to initialize AST analyzer")?
+            .with_custom_rules(crate::custom_rules::discover(&root_path)?)?;
 
         Ok(Self { root_path, ast_analyzer })
     }
@@ -128,13 +130,34 @@ impl CodeAnalyzer {
         }
 
         // Sort complexity distribution by complexity (highest first)
-        results.complexity_distribution.sort_by(|a, b| b.1.cmp(&a.1));
+        results.complexity_distribution.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
 
         results.analysis_duration_ms = start_time.elapsed().as_millis() as u64;
 
         Ok(results)
     }
 
+    /// Parse every source file, yielding each syntax tree with its extracted entities
+    ///
+    /// Files are parsed one at a time as the iterator advances, so callers decide
+    /// which trees to keep in memory. Files that cannot be read or parsed yield
+    /// an error without ending the iteration.
+    pub fn parse_retained(&mut self) -> Result<impl Iterator<Item = Result<RetainedParse>> + '_> {
+        let source_files = self.discover_source_files()?;
+        let ast_analyzer = &mut self.ast_analyzer;
+
+        Ok(source_files.into_iter().map(move |file_path| {
+            let content = std::fs::read_to_string(&file_path)
+                .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
+            let language = file_path.extension()
+                .and_then(|ext| ext.to_str())
+                .map(LangType::from_extension)
+                .unwrap_or(LangType::Unknown);
+            ast_analyzer.analyze_file_retained(content, &language, &file_path)
+                .with_context(|| format!("Failed to parse {}", file_path.display()))
+        }))
+    }
+
     /// Discover all source files in the root path
     fn discover_source_files(&self) -> Result<Vec<PathBuf>> {
         let mut files = Vec::new();
@@ -156,10 +179,10 @@ impl CodeAnalyzer {
 
     /// Check if a file should be analyzed based on its extension
     fn is_source_file(&self, path: &Path) -> bool {
-        if let Some(ext) = path.extension() {
-            matches!(ext.to_str(), Some("rs" | "js" | "ts" | "py" | "go" | "jsx" | "tsx"))
-        } else {
-            false
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("rs" | "js" | "ts" | "py" | "go" | "jsx" | "tsx" | "rb") => true,
+            Some(ext) => crate::language_provider::for_extension(ext).is_some(),
+            None => false,
         }
     }
 
@@ -171,7 +194,10 @@ impl CodeAnalyzer {
             Some("ts" | "tsx") => "TypeScript".to_string(),
             Some("py") => "Python".to_string(),
             Some("go") => "Go".to_string(),
-            _ => "Unknown".to_string(),
+            Some("rb") => "Ruby".to_string(),
+            Some(ext) => crate::language_provider::for_extension(ext)
+                .map_or_else(|| "Unknown".to_string(), |provider| provider.name().to_string()),
+            None => "Unknown".to_string(),
         }
     }
 
@@ -190,6 +216,7 @@ impl CodeAnalyzer {
         // Check complexity threshold
         if complexity > config.complexity_threshold {
             issues.push(AnalysisIssue {
+                rule_id: crate::rules::HIGH_FILE_COMPLEXITY,
                 file_path: file_path.to_path_buf(),
                 line: 1, // Could be more specific with proper parsing
                 severity: IssueSeverity::Warning,
@@ -303,4 +330,21 @@ mod tests {
         assert!(!analyzer.is_source_file(Path::new("README.md")));
         assert!(!analyzer.is_source_file(Path::new("config.json")));
     }
+
+    #[test]
+    fn test_parse_retained_streams_each_file() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.js"), "function a() { return 1; }").unwrap();
+        fs::write(dir.path().join("b.py"), "def b():\n    return 2\n").unwrap();
+
+        let mut analyzer = CodeAnalyzer::new(dir.path().to_path_buf()).unwrap();
+        let mut parsed: Vec<RetainedParse> = analyzer.parse_retained().unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        parsed.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].language, LangType::JavaScript);
+        assert!(!parsed[1].root_node().has_error());
+    }
 }
\ No newline at end of file
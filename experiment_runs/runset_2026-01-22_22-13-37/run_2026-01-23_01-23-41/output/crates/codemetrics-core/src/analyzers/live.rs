@@ -0,0 +1,118 @@
+//! Results kept current while files change, for `watch` and `serve`
+//!
+//! Every file stays counted in a tally that is never finalized. Replacing a
+//! file takes its previous counts back out and counts the new parse, so a
+//! change re-counts only the files that changed. Ranking, clusters, clone
+//! classes and the health score depend on every file and are recomputed from
+//! the tally each time results are asked for.
+
+use anyhow::Result;
+use std::collections::{BTreeMap, HashSet};
+
+use super::{AnalysisResults, CodeAnalyzer};
+use crate::clone_detector::{CloneDetector, FileFingerprints};
+use crate::parsers::ParsedFile;
+use crate::rules;
+use crate::thresholds::SeverityBands;
+
+pub struct LiveResults<'a> {
+    analyzer: &'a CodeAnalyzer,
+    /// Counted but not finalized
+    tally: AnalysisResults,
+    files: BTreeMap<String, ParsedFile>,
+}
+
+impl<'a> LiveResults<'a> {
+    /// Count `files` as `CodeAnalyzer::aggregate_results` would
+    pub fn new(analyzer: &'a CodeAnalyzer, files: impl IntoIterator<Item = ParsedFile>) -> Self {
+        let files: BTreeMap<String, ParsedFile> = files.into_iter().map(|file| (file.path.clone(), file)).collect();
+        let mut live = Self { analyzer, tally: analyzer.empty_results(analyzer.config.thresholds.clone()), files: BTreeMap::new() };
+        live.recount(files);
+        live
+    }
+
+    /// The parse counted for `path`, if any
+    pub fn file(&self, path: &str) -> Option<&ParsedFile> {
+        self.files.get(path)
+    }
+
+    /// Count `file` in place of `path`'s previous parse; `None` drops the path
+    pub fn replace(&mut self, path: &str, file: Option<ParsedFile>) {
+        if let Some(previous) = self.files.remove(path) {
+            self.tally.remove_file(&previous, self.analyzer.target_of(path));
+        }
+        if let Some(file) = file {
+            // A path is only ever counted once here, so identities need not be compared
+            if self.analyzer.add_parsed(&mut self.tally, file.clone(), &mut HashSet::new()).is_some() {
+                self.files.insert(path.to_string(), file);
+            }
+        }
+    }
+
+    /// The finalized results for the files as they are now
+    pub fn results(&mut self) -> Result<AnalysisResults> {
+        // Percentile cutoffs move with every function, and every file's levels with them
+        if let SeverityBands::Percentile(bands) = &self.analyzer.config.severity_bands {
+            let complexities: Vec<u32> = self.files.values()
+                .flat_map(|file| file.functions.iter().map(|function| function.complexity))
+                .collect();
+            let cutoffs = bands.cutoffs(&complexities, &self.analyzer.config.thresholds);
+            if self.tally.severity_cutoffs != Some(cutoffs) {
+                let files = std::mem::take(&mut self.files);
+                self.recount(files);
+            }
+        }
+
+        let clones = self.analyzer.config.rules.is_enabled(rules::DUPLICATED_CODE).then(|| {
+            let mut detector = CloneDetector::default();
+            for file in self.files.values() {
+                if let Some(blocks) = &file.facts.clones {
+                    detector.add_fingerprints(&FileFingerprints {
+                        file_path: file.path.clone(),
+                        total_lines: file.line_count as usize,
+                        blocks: blocks.clone(),
+                    });
+                }
+            }
+            detector.finish()
+        });
+        let mut results = self.tally.clone();
+        self.analyzer.summarize(&mut results, clones)?;
+        Ok(results)
+    }
+
+    /// Count `files` from an empty tally
+    fn recount(&mut self, files: BTreeMap<String, ParsedFile>) {
+        let (tally, _) = self.analyzer.tally(files.values().cloned().map(Ok).collect());
+        self.tally = tally;
+        self.files = files;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::LanguageParser;
+
+    #[test]
+    fn test_replacing_a_file_matches_counting_from_scratch() -> Result<()> {
+        let analyzer = CodeAnalyzer::new();
+        let parser = LanguageParser::new();
+        let steady = parser.parse_file("src/steady.rs", "fn steady(x: i32) -> i32 { if x > 0 { 1 } else { 2 } }\n")?;
+        let before = parser.parse_file("src/lib.rs", "fn grows(x: i32) { if x > 0 {} }\nfn gone() {}\n")?;
+        let after = parser.parse_file("src/lib.rs", "fn grows(x: i32) {\n    if x > 0 {}\n    if x > 1 {}\n}\n")?;
+        let added = parser.parse_file("src/main.py", "def main(argv):\n    return len(argv) or 1\n")?;
+
+        let mut live = LiveResults::new(&analyzer, [steady.clone(), before]);
+        live.replace("src/lib.rs", Some(after.clone()));
+        live.replace("src/main.py", Some(added.clone()));
+        live.replace("src/main.py", None);
+        live.replace("src/main.py", Some(added.clone()));
+
+        let incremental = serde_json::to_value(live.results()?)?;
+        let from_scratch = serde_json::to_value(analyzer.aggregate_results(vec![Ok(steady), Ok(after), Ok(added)])?)?;
+        assert_eq!(incremental, from_scratch);
+        assert_eq!(incremental["files_analyzed"], 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,2368 @@
+//! Core analysis engine that orchestrates parsing and metric collection
+
+pub mod live;
+#[cfg(test)]
+pub(crate) mod test_util;
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tree_sitter::Node;
+use walkdir::WalkDir;
+use ignore::Walk;
+
+use crate::ast_analyzer::{self, SecurityQuery};
+use crate::build_graph::BuildGraph;
+use crate::cache::AnalysisCache;
+use crate::calibration::{calibrate, Standing};
+use crate::call_graph::{self, CallGraphNode};
+use crate::capabilities::{self, Capabilities};
+use crate::clone_detector::{CloneClass, CloneDetector, CloneReport, FileDuplication, FileFingerprints};
+use crate::clusters::{self, IssueCluster};
+use crate::core::CodeIssue;
+use crate::error_handling::ErrorHandling;
+use crate::facts::FileFacts;
+use crate::file_identity::{ContentKey, FileIdentity};
+use crate::function_shape;
+use crate::go_concurrency::{self, FileConcurrency};
+use crate::health::{HealthInputs, HealthModel, HealthScore};
+use crate::history::Snapshot;
+use crate::hotspots::Hotspot;
+use crate::language_provider;
+use crate::line_complexity::{self, FileLineComplexity};
+use crate::parsers::{LanguageParser, ParsedFile, FunctionInfo, RetainedTree, SupportedLanguage};
+use crate::progress::Progress;
+use crate::rules::{self, RuleSet};
+use crate::rust_api::{self, GenericSignature, ImplSurface};
+use crate::schema;
+use crate::shard::{Shard, ShardBy, ShardOutput};
+use crate::snippets::{self, Snippet, SnippetOptions};
+use crate::source_text::SourceText;
+use crate::stack::DetectedFramework;
+use crate::stability::Stability;
+use crate::suppressions::{self, DebtDue, SuppressedIssue, Suppression};
+use crate::thresholds::{self, BandCutoffs, DirectoryThresholds, SeverityBands, ThresholdLevel, Thresholds};
+use crate::type_metrics::{self, TypeMetrics};
+use crate::unsafe_usage::{self, CrateUnsafe};
+use crate::vocabulary::{self, ModuleVocabulary};
+
+/// Parsed files waiting for a slow consumer; reading stops once this many are queued
+const STREAM_BUFFER: usize = 64;
+
+/// A file counted into the results, with the facts only the passes comparing files still need
+struct CountedFile {
+    path: String,
+    lines: u32,
+    facts: FileFacts,
+}
+
+pub struct CodeAnalyzer {
+    parser: LanguageParser,
+    config: AnalysisConfig,
+    /// Parsing threads, looked up on first use and kept for every later parse
+    threads: OnceLock<Arc<rayon::ThreadPool>>,
+    /// Security pattern queries per language, compiled on first use
+    security: OnceLock<HashMap<SupportedLanguage, Vec<SecurityQuery>>>,
+    /// Told about each parsed file unless the config is quiet
+    progress: Option<Box<dyn Progress>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalysisConfig {
+    pub min_complexity_threshold: u32,
+    /// Functions with fewer statements than this are left out of average complexity
+    /// and the distribution, though still counted in totals; 0 keeps every function
+    pub min_function_statements: u32,
+    pub include_tests: bool,
+    pub max_file_size: usize,
+    pub excluded_paths: Vec<String>,
+    /// Only discover files in these languages (canonical names, e.g. "Rust")
+    pub focus_languages: Option<Vec<String>>,
+    /// Never discover files in these languages
+    pub excluded_languages: Vec<String>,
+    pub health_model: HealthModel,
+    pub thresholds: Thresholds,
+    pub severity_bands: SeverityBands,
+    pub rules: RuleSet,
+    /// Bazel/Buck targets to group metrics by, when requested
+    pub build_graph: Option<BuildGraph>,
+    /// Source context attached to each reported function
+    pub snippets: SnippetOptions,
+    /// Per-directory replacements for `thresholds`, e.g. for legacy code
+    pub directory_thresholds: Vec<DirectoryThresholds>,
+    /// Where parsed files are cached between runs; `None` parses everything
+    pub cache_dir: Option<PathBuf>,
+    /// Analyze every copy of byte-identical files instead of only the first
+    pub count_repeated_files: bool,
+    /// Map each file's decision points to lines, for editor heatmaps
+    pub line_complexity: bool,
+    /// How much `try`/`catch`, `?` and `if err != nil` add to complexity
+    pub error_handling: ErrorHandling,
+    /// Measure identifier vocabulary and entropy per module
+    pub vocabulary: bool,
+    /// Measure classes and structs: size, WMC, LCOM cohesion and inheritance depth
+    pub type_metrics: bool,
+    /// Count Rust impl blocks per type and trait bounds and lifetimes per signature
+    pub rust_api: bool,
+    /// Count Rust unsafe blocks per crate, with and without `// SAFETY:` comments
+    pub unsafe_density: bool,
+    /// Resolve calls between functions into fan-in and fan-out
+    pub call_graph: bool,
+    /// Keep one record per function, not only the high-complexity ones
+    pub function_records: bool,
+    /// Measure cognitive complexity and nesting of each function record
+    pub function_shape: bool,
+    /// Report no progress, for non-interactive runs
+    pub quiet: bool,
+    /// Threads that read and parse files; `None` uses one per CPU
+    pub jobs: Option<usize>,
+    /// Only analyze this part of the discovered files, for sharded CI runs
+    pub shard: Option<Shard>,
+    /// Which files `shard` keeps together
+    pub shard_by: ShardBy,
+    /// Accepted issues from the project config; suppression comments are read from the source
+    pub suppressions: Vec<Suppression>,
+    /// Findings of one rule in one directory that reports collapse into a cluster; 0 never clusters
+    pub cluster_threshold: usize,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            min_complexity_threshold: 5,
+            min_function_statements: 0,
+            include_tests: false,
+            max_file_size: 1024 * 1024, // 1MB
+            excluded_paths: vec![
+                "node_modules".to_string(),
+                "target".to_string(),
+                ".git".to_string(),
+                "dist".to_string(),
+                "build".to_string(),
+            ],
+            focus_languages: None,
+            excluded_languages: Vec::new(),
+            health_model: HealthModel::default(),
+            thresholds: Thresholds::default(),
+            severity_bands: SeverityBands::default(),
+            rules: RuleSet::default(),
+            build_graph: None,
+            snippets: SnippetOptions::default(),
+            directory_thresholds: Vec::new(),
+            cache_dir: None,
+            count_repeated_files: false,
+            line_complexity: false,
+            error_handling: ErrorHandling::default(),
+            vocabulary: false,
+            type_metrics: false,
+            rust_api: false,
+            unsafe_density: false,
+            call_graph: false,
+            function_records: false,
+            function_shape: false,
+            quiet: false,
+            jobs: None,
+            shard: None,
+            shard_by: ShardBy::File,
+            suppressions: Vec::new(),
+            cluster_threshold: clusters::DEFAULT_CLUSTER_THRESHOLD,
+        }
+    }
+}
+
+impl AnalysisConfig {
+    /// Start from the defaults and override only what differs
+    pub fn builder() -> AnalysisConfigBuilder {
+        AnalysisConfigBuilder::default()
+    }
+}
+
+/// Fluent construction of an `AnalysisConfig`, validated by `build`
+///
+/// Language names are accepted in any form `parse_language_list` understands
+/// (`rs`, `Rust`, `js`, ...).
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisConfigBuilder {
+    config: AnalysisConfig,
+    focus_languages: Option<Vec<String>>,
+    excluded_languages: Vec<String>,
+}
+
+impl AnalysisConfigBuilder {
+    pub fn thresholds(mut self, thresholds: Thresholds) -> Self {
+        self.config.thresholds = thresholds;
+        self
+    }
+
+    pub fn min_complexity(mut self, threshold: u32) -> Self {
+        self.config.min_complexity_threshold = threshold;
+        self
+    }
+
+    /// Leave one-line getters and the like out of averages and the distribution
+    pub fn min_function_statements(mut self, statements: u32) -> Self {
+        self.config.min_function_statements = statements;
+        self
+    }
+
+    /// Skip paths containing `path`, in addition to the defaults (`node_modules`, `target`, ...)
+    pub fn exclude_path(mut self, path: impl Into<String>) -> Self {
+        self.config.excluded_paths.push(path.into());
+        self
+    }
+
+    /// Replace the default excluded paths entirely
+    pub fn excluded_paths<I, S>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.excluded_paths = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only analyze these languages
+    pub fn focus_languages<I, S>(mut self, languages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.focus_languages = Some(languages.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn exclude_languages<I, S>(mut self, languages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.excluded_languages.extend(languages.into_iter().map(Into::into));
+        self
+    }
+
+    /// Largest file to analyze, in bytes
+    pub fn max_file_size(mut self, bytes: usize) -> Self {
+        self.config.max_file_size = bytes;
+        self
+    }
+
+    pub fn include_tests(mut self, include: bool) -> Self {
+        self.config.include_tests = include;
+        self
+    }
+
+    pub fn rules(mut self, rules: RuleSet) -> Self {
+        self.config.rules = rules;
+        self
+    }
+
+    pub fn severity_bands(mut self, bands: SeverityBands) -> Self {
+        self.config.severity_bands = bands;
+        self
+    }
+
+    pub fn health_model(mut self, model: HealthModel) -> Self {
+        self.config.health_model = model;
+        self
+    }
+
+    pub fn build_graph(mut self, graph: BuildGraph) -> Self {
+        self.config.build_graph = Some(graph);
+        self
+    }
+
+    pub fn snippets(mut self, options: SnippetOptions) -> Self {
+        self.config.snippets = options;
+        self
+    }
+
+    /// Judge files under `directory` by `thresholds` instead
+    pub fn directory_thresholds(mut self, directory: impl Into<PathBuf>, thresholds: Thresholds) -> Self {
+        self.config.directory_thresholds.push(DirectoryThresholds { directory: directory.into(), thresholds });
+        self
+    }
+
+    /// Reuse results for unchanged files across runs
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.cache_dir = Some(dir.into());
+        self
+    }
+
+    pub fn count_repeated_files(mut self, count: bool) -> Self {
+        self.config.count_repeated_files = count;
+        self
+    }
+
+    /// Attach a per-line complexity map for every analyzed file
+    pub fn line_complexity(mut self, enabled: bool) -> Self {
+        self.config.line_complexity = enabled;
+        self
+    }
+
+    /// Weigh error-handling constructs as a team's review norms do, see `crate::error_handling`
+    pub fn error_handling(mut self, error_handling: ErrorHandling) -> Self {
+        self.config.error_handling = error_handling;
+        self
+    }
+
+    pub fn vocabulary(mut self, enabled: bool) -> Self {
+        self.config.vocabulary = enabled;
+        self
+    }
+
+    pub fn type_metrics(mut self, enabled: bool) -> Self {
+        self.config.type_metrics = enabled;
+        self
+    }
+
+    pub fn rust_api(mut self, enabled: bool) -> Self {
+        self.config.rust_api = enabled;
+        self
+    }
+
+    pub fn unsafe_density(mut self, enabled: bool) -> Self {
+        self.config.unsafe_density = enabled;
+        self
+    }
+
+    pub fn call_graph(mut self, enabled: bool) -> Self {
+        self.config.call_graph = enabled;
+        self
+    }
+
+    /// Keep every function's metrics in `AnalysisResults::functions`, for row-per-function exports
+    pub fn function_records(mut self, enabled: bool) -> Self {
+        self.config.function_records = enabled;
+        self
+    }
+
+    /// Add cognitive complexity and nesting depth to the records `function_records` keeps
+    pub fn function_shape(mut self, enabled: bool) -> Self {
+        self.config.function_shape = enabled;
+        self
+    }
+
+    pub fn quiet(mut self, enabled: bool) -> Self {
+        self.config.quiet = enabled;
+        self
+    }
+
+    /// Read and parse files on `threads` threads instead of one per CPU
+    pub fn jobs(mut self, threads: usize) -> Self {
+        self.config.jobs = Some(threads);
+        self
+    }
+
+    /// Analyze one shard of the files, grouped by `by`; the results carry what `shard::merge` needs
+    pub fn shard(mut self, shard: Shard, by: ShardBy) -> Self {
+        self.config.shard = Some(shard);
+        self.config.shard_by = by;
+        self
+    }
+
+    pub fn suppression(mut self, suppression: Suppression) -> Self {
+        self.config.suppressions.push(suppression);
+        self
+    }
+
+    pub fn cluster_threshold(mut self, threshold: usize) -> Self {
+        self.config.cluster_threshold = threshold;
+        self
+    }
+
+    pub fn build(self) -> Result<AnalysisConfig> {
+        let mut config = self.config;
+        config.thresholds.validate()?;
+        for entry in &config.directory_thresholds {
+            entry.thresholds.validate()
+                .with_context(|| format!("Invalid thresholds for {}", entry.directory.display()))?;
+        }
+        if config.max_file_size == 0 {
+            anyhow::bail!("max file size must be greater than zero");
+        }
+        if config.jobs == Some(0) {
+            anyhow::bail!("jobs must be greater than zero");
+        }
+
+        config.excluded_languages = parse_language_list(&self.excluded_languages)?;
+        config.focus_languages = self.focus_languages
+            .map(|languages| parse_language_list(&languages))
+            .transpose()?;
+        if let Some(focus) = &config.focus_languages {
+            if focus.iter().all(|language| config.excluded_languages.contains(language)) {
+                anyhow::bail!("every focused language is also excluded, so nothing would be analyzed");
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+impl CodeAnalyzer {
+    pub fn new() -> Self {
+        Self::with_config(AnalysisConfig::default())
+    }
+
+    pub fn with_config(config: AnalysisConfig) -> Self {
+        Self {
+            parser: LanguageParser::with_error_handling(config.error_handling.clone()),
+            config,
+            threads: OnceLock::new(),
+            security: OnceLock::new(),
+            progress: None,
+        }
+    }
+
+    /// Report the progress of each parse to `progress`
+    pub fn with_progress(mut self, progress: impl Progress + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    pub fn analyze_path(&self, path: &Path) -> Result<AnalysisResults> {
+        let mut discovery = self.discover(path)?;
+        let parsed_files = self.parse_files(&discovery.files)?;
+        // A shard hands on every copy, since repeats are only known across all shards
+        let shard_files: Vec<ParsedFile> = parsed_files.iter()
+            .filter(|_| self.config.shard.is_some())
+            .filter_map(|parsed| parsed.as_ref().ok().cloned())
+            .collect();
+        let shard_skip_counts = discovery.skip_counts();
+        let parsed_files = discovery.drop_repeats(parsed_files, self.config.count_repeated_files);
+        // Test files are usually skipped, so count them from discovery rather than parsing
+        let analyzed_tests = discovery.files.iter().filter(|path| is_test_file(path));
+        let skipped_tests = discovery.skipped.iter()
+            .filter(|skipped| skipped.reason == SkipReason::TestFile)
+            .map(|skipped| &skipped.path);
+        let test_files: Vec<PathBuf> = analyzed_tests.clone().chain(skipped_tests).cloned().collect();
+        let analyzed_test_files = analyzed_tests.count();
+        let shard = self.config.shard.map(|shard| ShardOutput {
+            shard,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            groups: discovery.files.iter()
+                .filter(|_| self.config.shard_by.is_grouped())
+                .map(|file| self.config.shard_by.group(path, file))
+                .collect(),
+            files: shard_files,
+            errors: parsed_files.iter().filter_map(|parsed| parsed.as_ref().err().map(|e| e.to_string())).collect(),
+            skip_counts: shard_skip_counts,
+            test_files: test_files.clone(),
+            analyzed_test_files,
+        });
+        let (mut results, facts) = self.aggregate(parsed_files)?;
+        results.shard = shard;
+        results.record_test_files(test_files.iter(), analyzed_test_files);
+        self.score_health(&mut results);
+
+        results.skip_counts = discovery.skip_counts();
+        results.skipped = discovery.skipped;
+        results.repeated_files = discovery.repeated;
+        self.compare_files(path, &mut results, facts);
+        Ok(results)
+    }
+
+    /// Aggregate the parsed files of every shard of a run under `root`
+    ///
+    /// Copies are only found here, since each shard saw its own files alone.
+    pub(crate) fn aggregate_shards(&self, root: &Path, parsed_files: Vec<Result<ParsedFile>>) -> Result<AnalysisResults> {
+        let mut discovery = Discovery::default();
+        let parsed_files = discovery.drop_repeats(parsed_files, self.config.count_repeated_files);
+        let (mut results, facts) = self.aggregate(parsed_files)?;
+        results.skip_counts = discovery.skip_counts();
+        results.repeated_files = discovery.repeated;
+        self.compare_files(root, &mut results, facts);
+        Ok(results)
+    }
+
+    /// The passes that combine every file's facts: vocabulary per module, types, Rust API, unsafe density, call graph, Go concurrency
+    fn compare_files(&self, root: &Path, results: &mut AnalysisResults, files: Vec<CountedFile>) {
+        if self.config.vocabulary {
+            results.vocabulary = vocabulary::measure(root, files.iter()
+                .filter_map(|file| Some((file.path.as_str(), file.facts.terms.as_ref()?))));
+        }
+        if self.config.unsafe_density {
+            results.unsafe_density = unsafe_usage::measure(files.iter()
+                .filter_map(|file| Some((file.path.as_str(), file.lines, file.facts.unsafe_blocks.as_deref()?))));
+        }
+        if self.config.call_graph {
+            results.call_graph = call_graph::measure(files.iter()
+                .filter_map(|file| Some((file.path.as_str(), file.facts.calls.as_deref()?))));
+        }
+        let mut types = Vec::new();
+        let mut apis = Vec::new();
+        let mut concurrency = Vec::new();
+        for CountedFile { facts, .. } in files {
+            types.extend(facts.types);
+            apis.extend(facts.rust_api);
+            concurrency.extend(facts.concurrency);
+        }
+        if self.config.type_metrics {
+            results.types = type_metrics::measure(types);
+        }
+        if self.config.rust_api {
+            let api = rust_api::measure(apis);
+            results.impl_surface = api.types;
+            results.generic_signatures = api.signatures;
+        }
+        results.concurrency = go_concurrency::measure(concurrency);
+    }
+
+    /// Discover and parse every supported file under `path` without aggregating
+    pub fn parse_path(&self, path: &Path) -> Result<Vec<Result<ParsedFile>>> {
+        let mut discovery = self.discover(path)?;
+        let parsed_files = self.parse_files(&discovery.files)?;
+        Ok(discovery.drop_repeats(parsed_files, self.config.count_repeated_files))
+    }
+
+    fn parse_files(&self, files: &[PathBuf]) -> Result<Vec<Result<ParsedFile>>> {
+        let progress = self.progress.as_deref().filter(|_| !self.config.quiet);
+        if let Some(progress) = progress {
+            progress.start(files.len());
+        }
+
+        let cache = self.open_cache()?;
+
+        // Files finish out of order; slot each parse back at its file's position
+        let mut parsed_files: Vec<Option<Result<ParsedFile>>> = files.iter().map(|_| None).collect();
+        self.parse_bounded(files, cache.as_ref(), |index, parsed| {
+            if let Some(progress) = progress {
+                progress.parsed(&files[index]);
+            }
+            parsed_files[index] = Some(parsed);
+            Ok(())
+        })?;
+        let parsed_files = parsed_files.into_iter().flatten().collect();
+
+        if let Some(progress) = progress {
+            progress.finish(cache.as_ref().map(AnalysisCache::hits));
+        }
+
+        Ok(parsed_files)
+    }
+
+    /// Parse `files` on the configured number of threads, handing each result to `handle`
+    ///
+    /// Workers send into a bounded channel, so at most `STREAM_BUFFER` parsed
+    /// files wait for `handle` and reading pauses while they do. Memory stays
+    /// bounded only if `handle` lets go of each parse, as `analyze_streaming`
+    /// does; `parse_files` keeps them all. `handle` runs on the calling thread
+    /// with the file's index in `files`. If it fails, the workers stop at
+    /// their next send.
+    fn parse_bounded(
+        &self,
+        files: &[PathBuf],
+        cache: Option<&AnalysisCache>,
+        mut handle: impl FnMut(usize, Result<ParsedFile>) -> Result<()>,
+    ) -> Result<()> {
+        let pool = self.threads()?;
+        let (sender, receiver) = std::sync::mpsc::sync_channel(STREAM_BUFFER);
+        std::thread::scope(|scope| -> Result<()> {
+            scope.spawn(|| {
+                pool.install(|| {
+                    files.par_iter().enumerate().try_for_each_with(sender, |sender, (index, file_path)| {
+                        // The receiver is gone only when `handle` failed, so the remaining files are moot
+                        sender.send((index, self.parse_one(file_path, cache))).map_err(|_| ())
+                    })
+                })
+            });
+            for (index, parsed) in receiver {
+                handle(index, parsed)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn threads(&self) -> Result<&rayon::ThreadPool> {
+        if let Some(pool) = self.threads.get() {
+            return Ok(pool);
+        }
+        let pool = shared_threads(self.config.jobs.unwrap_or(0))?;
+        Ok(self.threads.get_or_init(|| pool))
+    }
+
+    fn security_queries(&self, language: SupportedLanguage) -> Result<&[SecurityQuery]> {
+        if self.security.get().is_none() {
+            let compiled = SupportedLanguage::ALL.iter()
+                .map(|language| Ok((*language, ast_analyzer::security_queries(language.grammar_name(), &language.get_language())?)))
+                .collect::<Result<HashMap<_, _>>>()?;
+            let _ = self.security.set(compiled);
+        }
+        Ok(self.security.get().and_then(|security| security.get(&language)).map(Vec::as_slice).unwrap_or_default())
+    }
+
+    /// The parse cache when one is configured, keyed by the options that change what a parse yields
+    pub fn open_cache(&self) -> Result<Option<AnalysisCache>> {
+        let config = &self.config;
+        let settings = format!(
+            "{:?} {:?} {:?} {:?} {:?}",
+            config.error_handling,
+            config.thresholds,
+            config.directory_thresholds,
+            config.rules,
+            [config.line_complexity, config.function_shape, config.vocabulary, config.type_metrics, config.rust_api, config.unsafe_density, config.call_graph],
+        );
+        config.cache_dir.as_deref().map(|dir| AnalysisCache::open(dir, &settings)).transpose()
+    }
+
+    /// Parse a single file, reusing a cached parse of identical content
+    pub fn parse_one(&self, file_path: &Path, cache: Option<&AnalysisCache>) -> Result<ParsedFile> {
+        let content = SourceText::read(file_path)
+            .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
+        let path = file_path.to_string_lossy();
+
+        if let Some(cached) = cache.and_then(|cache| cache.get(&path, &content)) {
+            return Ok(cached);
+        }
+
+        let parsed = self.parse_source(file_path, &content)?;
+        if let Some(cache) = cache {
+            // An unwritable cache only costs speed on the next run
+            let _ = cache.put(&content, &parsed);
+        }
+        Ok(parsed)
+    }
+
+    /// Parse text that may differ from what is on disk, e.g. an unsaved editor buffer
+    ///
+    /// The language is chosen from `file_path`'s extension; the file itself is never read.
+    pub fn parse_source(&self, file_path: &Path, content: &str) -> Result<ParsedFile> {
+        self.parser.parse_with(&file_path.to_string_lossy(), content, |root, language| self.file_facts(file_path, content, root, language))
+    }
+
+    /// Run the enabled per-file passes on the tree `parse_source` built
+    fn file_facts(&self, file_path: &Path, content: &str, root: Node, language: SupportedLanguage) -> Result<FileFacts> {
+        let path = file_path.to_string_lossy();
+        let thresholds = thresholds::thresholds_for(file_path, &self.config.thresholds, &self.config.directory_thresholds);
+        let weights = self.config.error_handling.for_language(&language.language());
+        let rust = language == SupportedLanguage::Rust;
+        let patterns = self.security_queries(language)?;
+        Ok(FileFacts {
+            line_complexity: self.config.line_complexity.then(|| line_complexity::for_tree(root, content, weights)),
+            shapes: self.config.function_shape.then(|| function_shape::for_tree(root, content)),
+            terms: self.config.vocabulary.then(|| vocabulary::terms(root, content)),
+            types: self.config.type_metrics.then(|| type_metrics::extract(&path, content, root, language, weights)),
+            rust_api: (self.config.rust_api && rust).then(|| {
+                let max_bounds = self.config.rules.is_enabled(rules::GENERIC_API_COMPLEXITY).then_some(thresholds.max_generic_bounds);
+                rust_api::extract(&path, content, root, max_bounds)
+            }),
+            unsafe_blocks: (self.config.unsafe_density && rust).then(|| unsafe_usage::blocks(root, content)),
+            concurrency: (language == SupportedLanguage::Go).then(|| {
+                let hotspot_complexity = self.config.rules.is_enabled(rules::CONCURRENCY_HOTSPOT).then_some(thresholds.function_complexity_warn);
+                go_concurrency::for_tree(&path, root, content, weights, hotspot_complexity)
+            }),
+            calls: self.config.call_graph.then(|| call_graph::extract(root, content)),
+            clones: self.config.rules.is_enabled(rules::DUPLICATED_CODE).then(|| CloneDetector::default().blocks(root)),
+            issues: ast_analyzer::findings(root, content, &language.language(), &self.config.rules, patterns),
+        })
+    }
+
+    /// Like `parse_source`, but keep the tree so the buffer's next version, passed with it as `previous`, re-parses incrementally
+    pub fn parse_retained(&self, file_path: &Path, content: String, previous: Option<RetainedTree>) -> Result<(ParsedFile, RetainedTree)> {
+        self.parser.parse_retained(&file_path.to_string_lossy(), content, previous)
+    }
+
+    /// Whether discovery would select `path`, for files that appear after it ran
+    pub fn accepts(&self, path: &Path) -> bool {
+        path.is_file() && self.skip_reason(path).is_none()
+    }
+
+    pub fn config(&self) -> &AnalysisConfig {
+        &self.config
+    }
+
+    pub fn discover_files(&self, root_path: &Path) -> Result<Vec<PathBuf>> {
+        Ok(self.discover(root_path)?.files)
+    }
+
+    /// Find files to analyze, recording why every other path was passed over
+    fn discover(&self, root_path: &Path) -> Result<Discovery> {
+        let mut discovery = Discovery::default();
+
+        // Use the `ignore` crate to respect .gitignore files
+        let mut visible = HashSet::new();
+        for result in Walk::new(root_path) {
+            let entry = result.context("Failed to read directory entry")?;
+            visible.insert(entry.path().to_path_buf());
+        }
+
+        // Walk everything so ignored paths can be reported; ignored directories
+        // are reported once rather than descended into
+        let mut ignored = Vec::new();
+        // Sorted so the same path of a file reachable several ways is kept on every run
+        let mut seen = HashSet::new();
+        let walker = WalkDir::new(root_path).sort_by_file_name().into_iter().filter_entry(|entry| {
+            let shown = visible.contains(entry.path());
+            if !shown {
+                ignored.push(entry.path().to_path_buf());
+            }
+            shown
+        });
+
+        for entry in walker {
+            let entry = entry.context("Failed to read directory entry")?;
+            let path = entry.path();
+
+            // Skip if it's not a file
+            if !path.is_file() {
+                continue;
+            }
+
+            match self.skip_reason(path) {
+                Some(reason) => discovery.skip(path, reason),
+                None if FileIdentity::of(path).is_some_and(|identity| !seen.insert(identity)) => {
+                    discovery.skip(path, SkipReason::Duplicate)
+                }
+                None => discovery.files.push(path.to_path_buf()),
+            }
+        }
+
+        for path in ignored {
+            discovery.skip(&path, SkipReason::Gitignored);
+        }
+        if let Some(shard) = self.config.shard {
+            discovery.files.retain(|file| shard.contains(root_path, file, &self.config.shard_by));
+        }
+
+        Ok(discovery)
+    }
+
+    /// Why a visible file is not analyzed, checked from cheapest to most expensive
+    fn skip_reason(&self, path: &Path) -> Option<SkipReason> {
+        // Skip excluded paths
+        if self.is_excluded_path(path) {
+            return Some(SkipReason::ExcludedPattern);
+        }
+
+        // Skip test files if not including tests
+        if !self.config.include_tests && is_test_file(path) {
+            return Some(SkipReason::TestFile);
+        }
+
+        // Check if we support this file type and its language is selected,
+        // so deselected languages are never read or parsed
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        match extension {
+            Some(ext) if self.is_supported_extension(ext) => {
+                if !self.is_selected_language(ext) {
+                    return Some(SkipReason::LanguageNotSelected);
+                }
+            }
+            _ => return Some(SkipReason::UnsupportedExtension),
+        }
+
+        // Skip if file is too large
+        if let Ok(metadata) = path.metadata() {
+            if metadata.len() > self.config.max_file_size as u64 {
+                return Some(SkipReason::TooLarge);
+            }
+        }
+
+        sniff(path)
+    }
+
+    fn is_excluded_path(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy();
+        self.config.excluded_paths.iter().any(|excluded| {
+            path_str.contains(excluded)
+        })
+    }
+
+    fn is_supported_extension(&self, extension: &str) -> bool {
+        matches!(extension, "rs" | "js" | "ts" | "py" | "go" | "jsx" | "tsx" | "rb" | "rake")
+            || language_provider::for_extension(extension).is_some()
+    }
+
+    fn is_selected_language(&self, extension: &str) -> bool {
+        let Some(language) = language_for_extension(extension) else {
+            return false;
+        };
+
+        if self.config.excluded_languages.iter().any(|excluded| excluded == language) {
+            return false;
+        }
+
+        match &self.config.focus_languages {
+            Some(focus) => focus.iter().any(|focused| focused == language),
+            None => true,
+        }
+    }
+
+    pub fn aggregate_results(&self, parsed_files: Vec<Result<ParsedFile>>) -> Result<AnalysisResults> {
+        Ok(self.aggregate(parsed_files)?.0)
+    }
+
+    /// `aggregate_results`, also handing back the files it counted
+    fn aggregate(&self, parsed_files: Vec<Result<ParsedFile>>) -> Result<(AnalysisResults, Vec<CountedFile>)> {
+        let (mut results, mut analyzed) = self.tally(parsed_files);
+        let clones = self.config.rules.is_enabled(rules::DUPLICATED_CODE).then(|| detect_clones(&mut analyzed));
+        self.summarize(&mut results, clones)?;
+        Ok((results, analyzed))
+    }
+
+    /// Count `parsed_files` into results that are not finalized yet
+    fn tally(&self, parsed_files: Vec<Result<ParsedFile>>) -> (AnalysisResults, Vec<CountedFile>) {
+        let cutoffs = match &self.config.severity_bands {
+            SeverityBands::Fixed => None,
+            SeverityBands::Percentile(bands) => {
+                let complexities: Vec<u32> = parsed_files.iter()
+                    .flatten()
+                    .flat_map(|parsed| parsed.functions.iter().map(|function| function.complexity))
+                    .collect();
+                Some(bands.cutoffs(&complexities, &self.config.thresholds))
+            }
+            SeverityBands::Pinned(cutoffs) => Some(*cutoffs),
+        };
+        let thresholds = match cutoffs {
+            Some(cutoffs) => self.config.thresholds.with_cutoffs(cutoffs),
+            None => self.config.thresholds.clone(),
+        };
+
+        let mut results = self.empty_results(thresholds);
+        results.severity_cutoffs = cutoffs;
+
+        // Callers may combine parses of overlapping roots; count each file once
+        let mut seen = HashSet::new();
+        let mut analyzed = Vec::new();
+        for parsed_result in parsed_files {
+            match parsed_result {
+                Ok(parsed_file) => analyzed.extend(self.add_parsed(&mut results, parsed_file, &mut seen)),
+                Err(e) => results.record_parse_error(e),
+            }
+        }
+        (results, analyzed)
+    }
+
+    /// Analyze `path` without holding every parsed file in memory
+    ///
+    /// `emit` receives each file's record as soon as that file is parsed, in
+    /// completion order; only the aggregates and each file's clone fingerprints
+    /// are kept for the returned results. Passes that need every file at once
+    /// (vocabulary, types, Rust API, unsafe density, Go concurrency) do not run,
+    /// and percentile severity bands, which need the whole distribution first,
+    /// are rejected. Of byte-identical files, the copy parsed first is counted.
+    pub fn analyze_streaming(&self, path: &Path, mut emit: impl FnMut(&FileRecord) -> Result<()>) -> Result<AnalysisResults> {
+        if matches!(self.config.severity_bands, SeverityBands::Percentile(_)) {
+            anyhow::bail!("Percentile severity bands need every file before the first is reported; pin the cutoffs to stream");
+        }
+        if self.config.shard.is_some() {
+            anyhow::bail!("Shards are merged from complete results and cannot be streamed");
+        }
+        let mut discovery = self.discover(path)?;
+        let cutoffs = match &self.config.severity_bands {
+            SeverityBands::Pinned(cutoffs) => Some(*cutoffs),
+            _ => None,
+        };
+        let thresholds = match cutoffs {
+            Some(cutoffs) => self.config.thresholds.with_cutoffs(cutoffs),
+            None => self.config.thresholds.clone(),
+        };
+        let mut results = self.empty_results(thresholds);
+        results.severity_cutoffs = cutoffs;
+
+        let cache = self.open_cache()?;
+        let mut seen = HashSet::new();
+        // Only the fingerprints taken at parse time are kept, not the files
+        let mut detector = self.config.rules.is_enabled(rules::DUPLICATED_CODE).then(CloneDetector::default);
+        let mut repeats = Repeats::default();
+        self.parse_bounded(&discovery.files, cache.as_ref(), |_, parsed_result| {
+            match parsed_result {
+                Ok(parsed_file) if !repeats.admit(&parsed_file, self.config.count_repeated_files) => {}
+                Ok(parsed_file) => {
+                    let record = results.file_record(&parsed_file);
+                    if let Some(mut counted) = self.add_parsed(&mut results, parsed_file, &mut seen) {
+                        emit(&record)?;
+                        if let Some(detector) = &mut detector {
+                            add_clone_fingerprints(detector, &mut counted);
+                        }
+                    }
+                }
+                Err(e) => results.record_parse_error(e),
+            }
+            Ok(())
+        })?;
+        discovery.add_repeats(repeats);
+
+        self.summarize(&mut results, detector.map(CloneDetector::finish))?;
+        let analyzed_tests = discovery.files.iter().filter(|path| is_test_file(path));
+        let skipped_tests = discovery.skipped.iter()
+            .filter(|skipped| skipped.reason == SkipReason::TestFile)
+            .map(|skipped| &skipped.path);
+        results.record_test_files(analyzed_tests.clone().chain(skipped_tests), analyzed_tests.count());
+        self.score_health(&mut results);
+        results.skip_counts = discovery.skip_counts();
+        results.skipped = discovery.skipped;
+        results.repeated_files = discovery.repeated;
+        Ok(results)
+    }
+
+    fn empty_results(&self, thresholds: Thresholds) -> AnalysisResults {
+        let mut results = AnalysisResults::new(thresholds);
+        results.min_function_statements = self.config.min_function_statements;
+        results.directory_thresholds = self.config.directory_thresholds.clone();
+        results
+    }
+
+    /// Count `parsed_file` into `results`; `None` when the same file was already counted
+    fn add_parsed(&self, results: &mut AnalysisResults, mut parsed_file: ParsedFile, seen: &mut HashSet<FileIdentity>) -> Option<CountedFile> {
+        if FileIdentity::of(Path::new(&parsed_file.path)).is_some_and(|identity| !seen.insert(identity)) {
+            return None;
+        }
+        let target = self.target_of(&parsed_file.path);
+        if self.config.function_records {
+            results.record_functions(&parsed_file);
+        }
+        let mut facts = std::mem::take(&mut parsed_file.facts);
+        facts.shapes = None;
+        if let Some(lines) = facts.line_complexity.take() {
+            results.line_complexity.push(FileLineComplexity { file_path: parsed_file.path.clone(), lines });
+        }
+        results.issues.extend(std::mem::take(&mut facts.issues).into_iter()
+            .map(|issue| FileIssue { file_path: parsed_file.path.clone(), issue }));
+        let counted = CountedFile { path: parsed_file.path.clone(), lines: parsed_file.line_count, facts };
+        results.add_file(parsed_file, target);
+        Some(counted)
+    }
+
+    /// The build target owning `path`, when a build graph was loaded
+    fn target_of(&self, path: &str) -> Option<String> {
+        self.config.build_graph.as_ref()
+            .map(|graph| match graph.target_for(Path::new(path)) {
+                Some(target) => target.label.clone(),
+                None => UNOWNED_TARGET.to_string(),
+            })
+    }
+
+    /// Rank, filter and score the counted files; `clones` is `None` when duplicated-code is disabled
+    fn summarize(&self, results: &mut AnalysisResults, clones: Option<CloneReport>) -> Result<()> {
+        results.finalize();
+        if !self.config.rules.is_enabled(rules::HIGH_CYCLOMATIC_COMPLEXITY) {
+            results.high_complexity_functions.clear();
+        }
+        if !self.config.rules.is_enabled(rules::HIGH_FILE_COMPLEXITY) {
+            results.high_complexity_files.clear();
+        }
+        // Severities the user changed with `--rule-severity` or `[rules] severity`
+        self.config.rules.apply_severities(results.issues.iter_mut().map(|found| &mut found.issue));
+        if let Some(severity) = self.config.rules.severity(rules::HIGH_FILE_COMPLEXITY) {
+            let level = ThresholdLevel::of_severity(severity);
+            results.high_complexity_files.iter_mut().for_each(|file| file.level = level);
+        }
+        suppressions::apply(results, &self.config.suppressions, chrono::Local::now().date_naive());
+        self.attach_snippets(&mut results.high_complexity_functions);
+        results.cluster_threshold = self.config.cluster_threshold;
+        clusters::apply(results, self.config.cluster_threshold);
+        if let Some(clones) = clones {
+            results.duplication_percent = Some(clones.duplication_percent);
+            results.clones = clones.classes;
+            results.file_duplication = clones.files;
+        }
+        results.calibration = calibrate(results)?;
+        results.record_capabilities();
+        self.score_health(results);
+        Ok(())
+    }
+
+    /// Score health from what is counted so far; scored again once test files are counted
+    pub(crate) fn score_health(&self, results: &mut AnalysisResults) {
+        let health = self.config.health_model.score(&HealthInputs::from_results(results));
+        for sub in &health.sub_scores {
+            match sub.score {
+                Some(_) => results.capabilities.measured(sub.dimension),
+                None => results.capabilities.unavailable(sub.dimension, format!("no {} data is collected by this analysis", sub.dimension)),
+            }
+        }
+        results.health = Some(health);
+    }
+
+    /// Excerpt the source around each reported function, reading each file once
+    fn attach_snippets(&self, functions: &mut [HighComplexityFunction]) {
+        if self.config.snippets.context_lines == 0 {
+            return;
+        }
+
+        let mut sources: HashMap<String, Option<String>> = HashMap::new();
+        for function in functions {
+            let source = sources.entry(function.file_path.clone())
+                .or_insert_with(|| std::fs::read_to_string(&function.file_path).ok());
+            function.snippet = source.as_deref()
+                .and_then(|content| snippets::extract(content, function.line_start, &self.config.snippets));
+        }
+    }
+}
+
+/// The process's parsing threads for a thread count (0 is one per CPU), started on first use
+///
+/// Every analyzer asking for the same count gets the same threads, so the
+/// parsers each thread keeps (see `parsers::pool`) stay warm from one
+/// analysis to the next, e.g. across the requests a daemon serves.
+fn shared_threads(count: usize) -> Result<Arc<rayon::ThreadPool>> {
+    static POOLS: OnceLock<Mutex<HashMap<usize, Arc<rayon::ThreadPool>>>> = OnceLock::new();
+    let mut pools = POOLS.get_or_init(Mutex::default).lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(pool) = pools.get(&count) {
+        return Ok(Arc::clone(pool));
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(count)
+        .build()
+        .context("Failed to start the parsing threads")?;
+    Ok(Arc::clone(pools.entry(count).or_insert(Arc::new(pool))))
+}
+
+/// Copy-paste across the counted files, from the fingerprints taken when each was parsed
+fn detect_clones(files: &mut [CountedFile]) -> CloneReport {
+    let mut detector = CloneDetector::default();
+    for file in files {
+        add_clone_fingerprints(&mut detector, file);
+    }
+    detector.finish()
+}
+
+fn add_clone_fingerprints(detector: &mut CloneDetector, file: &mut CountedFile) {
+    if let Some(blocks) = file.facts.clones.take() {
+        detector.add_fingerprints(&FileFingerprints { file_path: file.path.clone(), total_lines: file.lines as usize, blocks });
+    }
+}
+
+/// Why discovery passed over a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Matched by .gitignore (or similar ignore file) or hidden
+    Gitignored,
+    /// Path contains one of the configured excluded patterns
+    ExcludedPattern,
+    /// Looks like a test file and tests are not included
+    TestFile,
+    /// Larger than the configured maximum file size
+    TooLarge,
+    UnsupportedExtension,
+    /// Supported, but filtered out by `--languages` or `--exclude-languages`
+    LanguageNotSelected,
+    /// Has a source extension but contains binary data
+    Binary,
+    /// Minified bundle: a `.min.` name, or lines far too long for hand-written code
+    Minified,
+    /// Marked as generated (`Code generated ... DO NOT EDIT`, `@generated`, protoc output)
+    Generated,
+    /// Same file as one already selected, reached through a symlink or hard link
+    Duplicate,
+    /// Byte-identical to a file already selected, e.g. a vendored copy
+    Repeated,
+}
+
+impl SkipReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SkipReason::Gitignored => "gitignored or hidden",
+            SkipReason::ExcludedPattern => "excluded pattern",
+            SkipReason::TestFile => "test file",
+            SkipReason::TooLarge => "too large",
+            SkipReason::UnsupportedExtension => "unsupported extension",
+            SkipReason::LanguageNotSelected => "language not selected",
+            SkipReason::Binary => "binary",
+            SkipReason::Minified => "minified",
+            SkipReason::Generated => "generated",
+            SkipReason::Duplicate => "duplicate path",
+            SkipReason::Repeated => "identical to another file",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedPath {
+    pub path: PathBuf,
+    pub reason: SkipReason,
+}
+
+/// Byte-identical files found at several paths; the first is the one analyzed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepeatedFiles {
+    pub paths: Vec<PathBuf>,
+    /// Non-blank lines in each copy
+    pub lines: usize,
+}
+
+/// Files selected for analysis plus every path that was skipped
+#[derive(Debug, Default)]
+struct Discovery {
+    files: Vec<PathBuf>,
+    skipped: Vec<SkippedPath>,
+    repeated: Vec<RepeatedFiles>,
+}
+
+impl Discovery {
+    fn skip(&mut self, path: &Path, reason: SkipReason) {
+        self.skipped.push(SkippedPath { path: path.to_path_buf(), reason });
+    }
+
+    /// Drop parses of byte-identical copies of an earlier file, unless `count_all`
+    fn drop_repeats(&mut self, parsed_files: Vec<Result<ParsedFile>>, count_all: bool) -> Vec<Result<ParsedFile>> {
+        let mut repeats = Repeats::default();
+        let kept = parsed_files.into_iter()
+            .filter(|parsed| parsed.as_ref().map_or(true, |file| repeats.admit(file, count_all)))
+            .collect();
+        self.add_repeats(repeats);
+        kept
+    }
+
+    /// Move the copies `repeats` left out from the analyzed files to the skipped ones
+    fn add_repeats(&mut self, repeats: Repeats) {
+        let left_out: HashSet<&PathBuf> = repeats.left_out.iter().collect();
+        self.files.retain(|file| !left_out.contains(file));
+        for path in &repeats.left_out {
+            self.skip(path, SkipReason::Repeated);
+        }
+        self.repeated = repeats.groups.into_iter().filter(|group| group.paths.len() > 1).collect();
+    }
+
+    fn skip_counts(&self) -> BTreeMap<SkipReason, usize> {
+        let mut counts = BTreeMap::new();
+        for skipped in &self.skipped {
+            *counts.entry(skipped.reason).or_insert(0) += 1;
+        }
+        counts
+    }
+
+}
+
+/// Byte-identical files among the parses seen so far, by the content key taken while parsing
+#[derive(Default)]
+struct Repeats {
+    by_content: HashMap<ContentKey, usize>,
+    groups: Vec<RepeatedFiles>,
+    left_out: Vec<PathBuf>,
+}
+
+impl Repeats {
+    /// Whether to count `file`: not when an identical one came first, unless `count_all`
+    fn admit(&mut self, file: &ParsedFile, count_all: bool) -> bool {
+        let Some(key) = file.content_key else {
+            return true;
+        };
+        let groups = &mut self.groups;
+        let group = *self.by_content.entry(key).or_insert_with(|| {
+            groups.push(RepeatedFiles { paths: Vec::new(), lines: key.lines });
+            groups.len() - 1
+        });
+        let path = PathBuf::from(&file.path);
+        groups[group].paths.push(path.clone());
+        if groups[group].paths.len() == 1 || count_all {
+            return true;
+        }
+        self.left_out.push(path);
+        false
+    }
+}
+
+
+/// Bytes of each file read to decide whether it is source worth parsing
+const SNIFF_BYTES: usize = 8192;
+
+/// In a full sample, fewer line breaks than this many bytes' worth means minified code
+const MINIFIED_BYTES_PER_LINE: usize = 1000;
+
+/// Markers that tools put in the first lines of files they generate
+const GENERATED_MARKERS: [&str; 5] = [
+    "Code generated",
+    "DO NOT EDIT",
+    "@generated",
+    "Generated by the protocol buffer compiler",
+    "autogenerated by",
+];
+
+/// Why a file with a source extension is still not source to parse, from its name and first bytes
+///
+/// Checks only the first `SNIFF_BYTES`, so it costs one small read per file.
+/// Binary data is a NUL byte or mostly control characters; minified code is
+/// a `.min.` file name or a sample with almost no line breaks; generated code
+/// carries a marker in its first few lines or a protoc output name.
+fn sniff(path: &Path) -> Option<SkipReason> {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    if name.contains(".min.") {
+        return Some(SkipReason::Minified);
+    }
+    if name.ends_with(".pb.go") || name.ends_with("_pb2.py") || name.ends_with("_pb.js") || name.ends_with("_pb.rb") {
+        return Some(SkipReason::Generated);
+    }
+
+    let mut buffer = [0u8; SNIFF_BYTES];
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return None;
+    };
+    let read = file.read(&mut buffer).unwrap_or(0);
+    let sample = &buffer[..read];
+    let control = sample.iter().filter(|&&byte| byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\r' | 0x0c)).count();
+    if sample.contains(&0) || control * 10 > sample.len() {
+        return Some(SkipReason::Binary);
+    }
+    let line_breaks = sample.iter().filter(|&&byte| byte == b'\n').count();
+    if read == SNIFF_BYTES && line_breaks < SNIFF_BYTES / MINIFIED_BYTES_PER_LINE {
+        return Some(SkipReason::Minified);
+    }
+    let header = String::from_utf8_lossy(sample);
+    if header.lines().take(5).any(|line| GENERATED_MARKERS.iter().any(|marker| line.contains(marker))) {
+        return Some(SkipReason::Generated);
+    }
+    None
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisResults {
+    /// Version of the JSON layout, see `schema`
+    pub schema_version: &'static str,
+    pub files_analyzed: usize,
+    pub total_lines: u32,
+    pub total_functions: usize,
+    /// Over functions of at least `min_function_statements` statements
+    pub average_complexity: f64,
+    /// Over the same functions, each weighted by its length in lines, so the big ones count for more
+    pub weighted_average_complexity: f64,
+    /// Functions with fewer statements than this were left out of `average_complexity` and `complexity_distribution`
+    #[serde(skip_serializing_if = "is_zero")]
+    pub min_function_statements: u32,
+    /// How many functions that left out; they still count in `total_functions`
+    #[serde(skip_serializing_if = "is_zero")]
+    pub small_functions_excluded: usize,
+    pub high_complexity_functions: Vec<HighComplexityFunction>,
+    pub high_complexity_files: Vec<HighComplexityFile>,
+    /// Security, concurrency and resource findings, by file and line
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<FileIssue>,
+    pub language_breakdown: HashMap<String, LanguageStats>,
+    /// Languages compared side by side, least maintainable first
+    pub language_quality: Vec<LanguageQuality>,
+    /// Per build target (Bazel/Buck label), with the same counters as the language breakdown
+    pub target_breakdown: BTreeMap<String, LanguageStats>,
+    pub complexity_distribution: HashMap<u32, u32>,
+    /// Files and directories by complexity mass, heaviest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub complexity_mass: Vec<ComplexityMass>,
+    pub errors: Vec<String>,
+    pub health: Option<HealthScore>,
+    pub calibration: Vec<Standing>,
+    /// Frameworks detected in the project, when detection ran
+    pub stack: Vec<DetectedFramework>,
+    /// Which optional inputs were measured, and why the others were not
+    pub capabilities: Capabilities,
+    /// Pre-existing issues hidden because they are recorded in the baseline
+    pub baseline_suppressed: usize,
+    /// Issues hidden by a suppression comment or config entry that has not expired
+    pub suppressed: usize,
+    /// The issues counted in `suppressed`, so they stay visible in reports
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<SuppressedIssue>,
+    /// Suppressions that have expired or expire soon, earliest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub debt_due: Vec<DebtDue>,
+    /// Crowded (rule, directory) pairs that reports show collapsed, largest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issue_clusters: Vec<IssueCluster>,
+    /// Blocks repeated across the analyzed files, largest first
+    pub clones: Vec<CloneClass>,
+    /// Share of analyzed lines inside a repeated block, when clone detection ran
+    pub duplication_percent: Option<f64>,
+    /// Files containing repeated blocks, most duplicated first
+    pub file_duplication: Vec<FileDuplication>,
+    /// Byte-identical files; only the first of each is counted unless configured otherwise
+    pub repeated_files: Vec<RepeatedFiles>,
+    /// Complexity added by each line, per file, when requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub line_complexity: Vec<FileLineComplexity>,
+    /// Identifier vocabulary per module, largest first, when requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub vocabulary: Vec<ModuleVocabulary>,
+    /// Classes and structs, least cohesive first, when requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<TypeMetrics>,
+    /// Rust types by number of impl blocks, when requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub impl_surface: Vec<ImplSurface>,
+    /// Rust signatures with trait bounds or named lifetimes, most bounds first, when requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub generic_signatures: Vec<GenericSignature>,
+    /// Rust unsafe blocks per crate, most unjustified per line first, when requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unsafe_density: Vec<CrateUnsafe>,
+    /// Go files launching goroutines or using channels, busiest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub concurrency: Vec<FileConcurrency>,
+    /// Functions by how many others call them, most depended on first, when requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub call_graph: Vec<CallGraphNode>,
+    /// Every analyzed function, when requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<FunctionRecord>,
+    /// Files that are both complex and frequently changed, hottest first, when history was read
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hotspots: Vec<Hotspot>,
+    /// Earlier snapshots from the history store, oldest first, for trend charts
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<Snapshot>,
+    /// Parsed files of a `--shard` run, combined by `codemetrics merge`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard: Option<ShardOutput>,
+    /// Summed function complexity of every analyzed file, for combining with history
+    #[serde(skip)]
+    pub file_complexity: HashMap<String, u32>,
+    /// Totals of every analyzed file, those without functions too, for per-file exports
+    #[serde(skip)]
+    pub file_totals: BTreeMap<String, FileTotals>,
+    /// Thresholds the results were classified with, for reporters to reuse
+    pub thresholds: Thresholds,
+    /// Band cutoffs in effect; pass them back via `--pinned-bands` to keep severities stable
+    pub severity_cutoffs: Option<BandCutoffs>,
+    /// Number of paths discovery passed over, per reason
+    pub skip_counts: BTreeMap<SkipReason, usize>,
+    /// Every skipped path, listed by `--explain-skips`
+    #[serde(skip)]
+    pub skipped: Vec<SkippedPath>,
+    /// Test files that were analyzed as well as counted, with `--include-tests`; `None` until test files are counted
+    #[serde(skip)]
+    analyzed_test_files: Option<usize>,
+    /// Complexity mass and function lines per file, rolled up into `complexity_mass` by `finalize`
+    #[serde(skip)]
+    file_mass: HashMap<String, (u64, u64)>,
+    /// Complexity mass and lines of the functions averages are computed over
+    #[serde(skip)]
+    measured_mass: (u64, u64),
+    /// Kept so clusters can be recomputed when findings are dropped later, e.g. by a baseline
+    #[serde(skip)]
+    pub(crate) cluster_threshold: usize,
+    #[serde(skip)]
+    directory_thresholds: Vec<DirectoryThresholds>,
+}
+
+impl AnalysisResults {
+    fn new(thresholds: Thresholds) -> Self {
+        Self {
+            schema_version: schema::SCHEMA_VERSION,
+            files_analyzed: 0,
+            total_lines: 0,
+            total_functions: 0,
+            average_complexity: 0.0,
+            weighted_average_complexity: 0.0,
+            min_function_statements: 0,
+            small_functions_excluded: 0,
+            high_complexity_functions: Vec::new(),
+            high_complexity_files: Vec::new(),
+            issues: Vec::new(),
+            language_breakdown: HashMap::new(),
+            language_quality: Vec::new(),
+            target_breakdown: BTreeMap::new(),
+            complexity_distribution: HashMap::new(),
+            complexity_mass: Vec::new(),
+            errors: Vec::new(),
+            health: None,
+            calibration: Vec::new(),
+            stack: Vec::new(),
+            capabilities: Capabilities::default(),
+            baseline_suppressed: 0,
+            suppressed: 0,
+            suppressions: Vec::new(),
+            debt_due: Vec::new(),
+            issue_clusters: Vec::new(),
+            clones: Vec::new(),
+            duplication_percent: None,
+            file_duplication: Vec::new(),
+            repeated_files: Vec::new(),
+            line_complexity: Vec::new(),
+            vocabulary: Vec::new(),
+            types: Vec::new(),
+            impl_surface: Vec::new(),
+            generic_signatures: Vec::new(),
+            unsafe_density: Vec::new(),
+            concurrency: Vec::new(),
+            call_graph: Vec::new(),
+            functions: Vec::new(),
+            hotspots: Vec::new(),
+            history: Vec::new(),
+            shard: None,
+            file_complexity: HashMap::new(),
+            file_totals: BTreeMap::new(),
+            thresholds,
+            severity_cutoffs: None,
+            skip_counts: BTreeMap::new(),
+            skipped: Vec::new(),
+            analyzed_test_files: None,
+            file_mass: HashMap::new(),
+            measured_mass: (0, 0),
+            cluster_threshold: 0,
+            directory_thresholds: Vec::new(),
+        }
+    }
+
+    fn add_file(&mut self, parsed_file: ParsedFile, target: Option<String>) {
+        self.files_analyzed += 1;
+        self.total_lines += parsed_file.line_count;
+
+        // Determine language from file extension
+        let language = self.detect_language(&parsed_file.path);
+        let stats = self.language_breakdown.entry(language.clone()).or_default();
+        stats.files += 1;
+        stats.lines += parsed_file.line_count;
+        stats.comment_lines += parsed_file.comment_lines;
+        stats.imports += parsed_file.imports;
+        stats.inline_test_files += inline_test_file(&parsed_file);
+
+        let mut target_stats = target.map(|label| {
+            let stats = self.target_breakdown.entry(label).or_default();
+            stats.files += 1;
+            stats.lines += parsed_file.line_count;
+            stats.comment_lines += parsed_file.comment_lines;
+            stats.imports += parsed_file.imports;
+            stats.inline_test_files += inline_test_file(&parsed_file);
+            stats
+        });
+
+        let file_thresholds = thresholds::thresholds_for(
+            Path::new(&parsed_file.path),
+            &self.thresholds,
+            &self.directory_thresholds,
+        ).clone();
+
+        let mut file_complexity = 0;
+        let mut file_mass = (0, 0);
+        for function in &parsed_file.functions {
+            let lines = function_length(function);
+            let mass = function.complexity as u64 * lines as u64;
+            self.total_functions += 1;
+            stats.functions += 1;
+            stats.total_complexity += function.complexity;
+            stats.function_lines += lines;
+            file_complexity += function.complexity;
+            file_mass.0 += mass;
+            file_mass.1 += lines as u64;
+
+            let is_high = file_thresholds.function_level(function.complexity) != ThresholdLevel::Ok;
+            if let Some(target_stats) = target_stats.as_deref_mut() {
+                target_stats.functions += 1;
+                target_stats.total_complexity += function.complexity;
+                target_stats.function_lines += lines;
+                target_stats.high_complexity_functions += usize::from(is_high);
+            }
+
+            // Track complexity distribution, which averages are computed from
+            if function.statements < self.min_function_statements {
+                self.small_functions_excluded += 1;
+            } else {
+                *self.complexity_distribution.entry(function.complexity).or_insert(0) += 1;
+                self.measured_mass.0 += mass;
+                self.measured_mass.1 += lines as u64;
+            }
+
+            // Identify high complexity functions
+            if is_high {
+                stats.high_complexity_functions += 1;
+                self.high_complexity_functions.push(HighComplexityFunction {
+                    name: function.name.clone(),
+                    scope: function.scope.clone(),
+                    file_path: parsed_file.path.clone(),
+                    complexity: function.complexity,
+                    line_start: function.line_start,
+                    line_end: function.line_end,
+                    parameters: function.parameters,
+                    stability: None,
+                    snippet: None,
+                });
+            }
+        }
+
+        let level = file_thresholds.file_level(file_complexity);
+        if level != ThresholdLevel::Ok {
+            self.high_complexity_files.push(HighComplexityFile {
+                file_path: parsed_file.path.clone(),
+                total_complexity: file_complexity,
+                functions: parsed_file.functions.len(),
+                level,
+            });
+        }
+        self.file_complexity.insert(parsed_file.path.clone(), file_complexity);
+        self.file_mass.insert(parsed_file.path.clone(), file_mass);
+        self.file_totals.insert(parsed_file.path.clone(), FileTotals {
+            language,
+            lines: parsed_file.line_count,
+            functions: parsed_file.functions.len(),
+            function_lines: parsed_file.functions.iter().map(function_length).sum(),
+            total_complexity: file_complexity,
+            max_complexity: parsed_file.functions.iter().map(|function| function.complexity).max().unwrap_or(0),
+        });
+    }
+
+    /// Take back everything `add_parsed` counted for `parsed_file`, before the results are finalized
+    fn remove_file(&mut self, parsed_file: &ParsedFile, target: Option<String>) {
+        let path = &parsed_file.path;
+        let file_thresholds = thresholds::thresholds_for(
+            Path::new(path),
+            &self.thresholds,
+            &self.directory_thresholds,
+        ).clone();
+
+        let mut counted = LanguageStats {
+            files: 1,
+            lines: parsed_file.line_count,
+            comment_lines: parsed_file.comment_lines,
+            imports: parsed_file.imports,
+            inline_test_files: inline_test_file(parsed_file),
+            ..LanguageStats::default()
+        };
+        for function in &parsed_file.functions {
+            let lines = function_length(function);
+            counted.functions += 1;
+            counted.total_complexity += function.complexity;
+            counted.function_lines += lines;
+            counted.high_complexity_functions += usize::from(file_thresholds.function_level(function.complexity) != ThresholdLevel::Ok);
+
+            if function.statements < self.min_function_statements {
+                self.small_functions_excluded -= 1;
+            } else {
+                if let Entry::Occupied(mut count) = self.complexity_distribution.entry(function.complexity) {
+                    *count.get_mut() -= 1;
+                    if *count.get() == 0 {
+                        count.remove();
+                    }
+                }
+                self.measured_mass.0 -= function.complexity as u64 * lines as u64;
+                self.measured_mass.1 -= lines as u64;
+            }
+        }
+        self.files_analyzed -= 1;
+        self.total_lines -= parsed_file.line_count;
+        self.total_functions -= counted.functions;
+
+        let language = self.detect_language(path);
+        if self.language_breakdown.get_mut(&language).is_some_and(|stats| stats.subtract(&counted)) {
+            self.language_breakdown.remove(&language);
+        }
+        if let Some(label) = target {
+            if self.target_breakdown.get_mut(&label).is_some_and(|stats| stats.subtract(&counted)) {
+                self.target_breakdown.remove(&label);
+            }
+        }
+
+        self.high_complexity_functions.retain(|function| function.file_path != *path);
+        self.high_complexity_files.retain(|file| file.file_path != *path);
+        self.issues.retain(|found| found.file_path != *path);
+        self.line_complexity.retain(|file| file.file_path != *path);
+        self.functions.retain(|function| function.file_path != *path);
+        self.file_complexity.remove(path);
+        self.file_mass.remove(path);
+        self.file_totals.remove(path);
+    }
+
+    fn record_functions(&mut self, parsed_file: &ParsedFile) {
+        let records = self.function_records(parsed_file);
+        self.functions.extend(records);
+    }
+
+    fn function_records(&self, parsed_file: &ParsedFile) -> Vec<FunctionRecord> {
+        let language = self.detect_language(&parsed_file.path);
+        let file_thresholds = thresholds::thresholds_for(
+            Path::new(&parsed_file.path),
+            &self.thresholds,
+            &self.directory_thresholds,
+        );
+        let shape = |function: &FunctionInfo| parsed_file.facts.shapes.as_ref()
+            .and_then(|shapes| shapes.get(&function.line_start));
+        parsed_file.functions.iter().map(|function| FunctionRecord {
+            file_path: parsed_file.path.clone(),
+            language: language.clone(),
+            name: function.name.clone(),
+            scope: function.scope.clone(),
+            line_start: function.line_start,
+            line_end: function.line_end,
+            lines: function_length(function),
+            parameters: function.parameters,
+            complexity: function.complexity,
+            level: file_thresholds.function_level(function.complexity),
+            baselined: false,
+            cognitive_complexity: shape(function).map(|shape| shape.cognitive_complexity),
+            nesting_depth: shape(function).map(|shape| shape.nesting_depth),
+        }).collect()
+    }
+
+    /// What `CodeAnalyzer::analyze_streaming` emits for `parsed_file`
+    fn file_record(&self, parsed_file: &ParsedFile) -> FileRecord {
+        FileRecord {
+            file_path: parsed_file.path.clone(),
+            language: self.detect_language(&parsed_file.path),
+            lines: parsed_file.line_count,
+            complexity: parsed_file.functions.iter().map(|function| function.complexity).sum(),
+            functions: self.function_records(parsed_file),
+        }
+    }
+
+    fn record_parse_error(&mut self, error: anyhow::Error) {
+        eprintln!("Warning: Failed to parse file - {}", error);
+        self.errors.push(error.to_string());
+    }
+
+    fn detect_language(&self, file_path: &str) -> String {
+        file_path.split('.').next_back()
+            .and_then(language_for_extension)
+            .unwrap_or("Unknown")
+            .to_string()
+    }
+
+    /// High-complexity functions and files plus line findings, as gates and status files count issues
+    pub fn issue_count(&self) -> usize {
+        self.high_complexity_functions.len() + self.high_complexity_files.len() + self.issues.len()
+    }
+
+    /// Percentage of discovered files that failed to parse
+    pub fn parse_error_rate(&self) -> f64 {
+        let attempted = self.files_analyzed + self.errors.len();
+        if attempted == 0 {
+            return 0.0;
+        }
+        self.errors.len() as f64 / attempted as f64 * 100.0
+    }
+
+    fn finalize(&mut self) {
+        // Calculate average complexity over the functions in the distribution
+        let measured: u32 = self.complexity_distribution.values().sum();
+        if measured > 0 {
+            let total_complexity: u32 = self.complexity_distribution
+                .iter()
+                .map(|(complexity, count)| complexity * count)
+                .sum();
+            self.average_complexity = total_complexity as f64 / measured as f64;
+        }
+        let (mass, lines) = self.measured_mass;
+        if lines > 0 {
+            self.weighted_average_complexity = mass as f64 / lines as f64;
+        }
+        self.complexity_mass = complexity_mass(&self.file_mass);
+
+        // Most complex first; ties broken by location so output is identical across runs
+        self.high_complexity_functions.sort_by(|a, b| {
+            b.complexity.cmp(&a.complexity)
+                .then_with(|| a.file_path.cmp(&b.file_path))
+                .then_with(|| a.line_start.cmp(&b.line_start))
+        });
+        self.high_complexity_files.sort_by(|a, b| {
+            b.total_complexity.cmp(&a.total_complexity).then_with(|| a.file_path.cmp(&b.file_path))
+        });
+        self.issues.sort_by(|a, b| {
+            a.file_path.cmp(&b.file_path).then_with(|| (a.issue.line, a.issue.column).cmp(&(b.issue.line, b.issue.column)))
+        });
+        self.compare_languages();
+    }
+
+    fn record_capabilities(&mut self) {
+        self.capabilities.measured(capabilities::COMPLEXITY);
+        if self.calibration.is_empty() {
+            self.capabilities.unavailable(capabilities::CALIBRATION, "no reference projects match these languages and this size");
+        } else {
+            self.capabilities.measured(capabilities::CALIBRATION);
+        }
+        self.capabilities.unavailable(capabilities::COVERAGE, "coverage reports are not read by this version");
+        // Set by the caller once history and manifests have been looked at
+        self.capabilities.unavailable(capabilities::GIT_HISTORY, "not requested (use --stability or --profile deep)");
+        self.capabilities.unavailable(capabilities::MANIFESTS, "dependency manifests were not scanned");
+    }
+
+    /// Count test files per language; `analyzed` of them are also among the analyzed files
+    pub(crate) fn record_test_files<'a>(&mut self, paths: impl Iterator<Item = &'a PathBuf>, analyzed: usize) {
+        for path in paths {
+            let language = path.extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(language_for_extension);
+            if let Some(stats) = language.and_then(|language| self.language_breakdown.get_mut(language)) {
+                stats.test_files += 1;
+            }
+        }
+        self.analyzed_test_files = Some(analyzed);
+        self.compare_languages();
+    }
+
+    /// Test files and source files with inline tests per source file, once test files have been counted
+    pub(crate) fn test_file_ratio(&self) -> Option<f64> {
+        let tests: usize = self.language_breakdown.values().map(|stats| stats.test_files + stats.inline_test_files).sum();
+        let sources = self.files_analyzed.saturating_sub(self.analyzed_test_files?);
+        (sources > 0).then(|| tests as f64 / sources as f64)
+    }
+
+    fn compare_languages(&mut self) {
+        let mut quality: Vec<LanguageQuality> = self.language_breakdown.iter()
+            .map(|(language, stats)| LanguageQuality::from_stats(language, stats, self.analyzed_test_files.is_some_and(|analyzed| analyzed > 0)))
+            .collect();
+        quality.sort_by(|a, b| a.maintainability.total_cmp(&b.maintainability).then_with(|| a.language.cmp(&b.language)));
+        self.language_quality = quality;
+    }
+}
+
+/// Files and directories (each file's parent) with any mass, heaviest first
+fn complexity_mass(files: &HashMap<String, (u64, u64)>) -> Vec<ComplexityMass> {
+    let total: u64 = files.values().map(|(mass, _)| mass).sum();
+    if total == 0 {
+        return Vec::new();
+    }
+
+    let mut directories: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    for (path, (mass, lines)) in files {
+        let directory = directories.entry(clusters::directory_of(path)).or_default();
+        directory.0 += mass;
+        directory.1 += lines;
+    }
+
+    let entry = |path: String, scope: MassScope, (mass, lines): (u64, u64)| ComplexityMass {
+        path,
+        scope,
+        mass,
+        share_percent: mass as f64 / total as f64 * 100.0,
+        weighted_average_complexity: if lines > 0 { mass as f64 / lines as f64 } else { 0.0 },
+    };
+    let mut entries: Vec<ComplexityMass> = directories.into_iter()
+        .map(|(path, sums)| entry(path, MassScope::Directory, sums))
+        .chain(files.iter().map(|(path, &sums)| entry(path.clone(), MassScope::File, sums)))
+        .filter(|entry| entry.mass > 0)
+        .collect();
+    // On equal mass a directory comes before the file that makes it up
+    entries.sort_by(|a, b| b.mass.cmp(&a.mass).then_with(|| b.scope.cmp(&a.scope)).then_with(|| a.path.cmp(&b.path)));
+    entries
+}
+
+fn function_length(function: &FunctionInfo) -> u32 {
+    function.line_end.saturating_sub(function.line_start) + 1
+}
+
+fn is_zero<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// How much of the project's complexity sits in one file or directory
+///
+/// Mass is each function's complexity times its length in lines, summed, so a
+/// 200-line function of complexity 10 weighs as much as twenty 10-line ones.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComplexityMass {
+    pub path: String,
+    pub scope: MassScope,
+    pub mass: u64,
+    /// Share of the whole project's mass
+    pub share_percent: f64,
+    /// Mass per function line: the LOC-weighted average complexity within `path`
+    pub weighted_average_complexity: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MassScope {
+    File,
+    Directory,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HighComplexityFunction {
+    pub name: String,
+    /// Enclosing definitions, as in `FunctionInfo::scope`
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub scope: String,
+    pub file_path: String,
+    pub complexity: u32,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub parameters: u32,
+    /// Filled in by `StabilityClassifier` when history analysis is enabled
+    pub stability: Option<Stability>,
+    /// Source around `line_start`, with likely secrets redacted unless disabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<Snippet>,
+}
+
+/// Metrics of one function, kept for every function when `function_records` is set
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionRecord {
+    pub file_path: String,
+    pub language: String,
+    pub name: String,
+    /// Enclosing definitions, as in `FunctionInfo::scope`
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub scope: String,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub lines: u32,
+    pub parameters: u32,
+    pub complexity: u32,
+    pub level: ThresholdLevel,
+    /// Its complexity issue is recorded in the baseline, so gates and reports leave it out
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub baselined: bool,
+    /// Set when `function_shape` is enabled and the language has a grammar
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cognitive_complexity: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nesting_depth: Option<u32>,
+}
+
+/// One analyzed file with all its functions, as streamed by `CodeAnalyzer::analyze_streaming`
+#[derive(Debug, Clone, Serialize)]
+pub struct FileRecord {
+    pub file_path: String,
+    pub language: String,
+    pub lines: u32,
+    /// Summed complexity of its functions
+    pub complexity: u32,
+    pub functions: Vec<FunctionRecord>,
+}
+
+/// One analyzed file's totals
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileTotals {
+    pub language: String,
+    pub lines: u32,
+    pub functions: usize,
+    /// Lines spanned by its functions
+    pub function_lines: u32,
+    /// Summed complexity of its functions
+    pub total_complexity: u32,
+    pub max_complexity: u32,
+}
+
+/// A file whose summed function complexity crosses the file-level thresholds
+#[derive(Debug, Clone, Serialize)]
+pub struct HighComplexityFile {
+    pub file_path: String,
+    pub total_complexity: u32,
+    pub functions: usize,
+    pub level: ThresholdLevel,
+}
+
+/// A finding of one of the security, concurrency or resource rules in an analyzed file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileIssue {
+    pub file_path: String,
+    #[serde(flatten)]
+    pub issue: CodeIssue,
+}
+
+/// Target label for files no build rule lists in its `srcs`
+pub const UNOWNED_TARGET: &str = "(no target)";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LanguageStats {
+    pub files: usize,
+    pub lines: u32,
+    pub functions: usize,
+    /// Lines spanned by all functions, for average function length
+    pub function_lines: u32,
+    pub total_complexity: u32,
+    pub high_complexity_functions: usize,
+    /// Test files discovered, whether or not they were analyzed
+    pub test_files: usize,
+    /// Source files carrying their own tests, such as Rust files with a `#[cfg(test)]` module
+    #[serde(skip_serializing_if = "is_zero")]
+    pub inline_test_files: usize,
+    pub comment_lines: u32,
+    pub imports: u32,
+}
+
+impl LanguageStats {
+    /// Take one file's counts back out; true once nothing is left counted
+    fn subtract(&mut self, file: &LanguageStats) -> bool {
+        self.files -= file.files;
+        self.lines -= file.lines;
+        self.functions -= file.functions;
+        self.function_lines -= file.function_lines;
+        self.total_complexity -= file.total_complexity;
+        self.high_complexity_functions -= file.high_complexity_functions;
+        self.comment_lines -= file.comment_lines;
+        self.imports -= file.imports;
+        self.inline_test_files -= file.inline_test_files;
+        *self == LanguageStats::default()
+    }
+}
+
+/// How one language's code compares with the project's other languages
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageQuality {
+    pub language: String,
+    pub files: usize,
+    pub lines: u32,
+    pub functions: usize,
+    pub average_complexity: f64,
+    /// Simplified maintainability index, 0–100 (higher is better)
+    pub maintainability: f64,
+    /// High-complexity functions per 1000 lines
+    pub issue_density: f64,
+    /// Test files and source files with inline tests per source file
+    pub test_ratio: f64,
+}
+
+impl LanguageQuality {
+    /// `tests_analyzed` means test files are also counted in `stats.files`
+    fn from_stats(language: &str, stats: &LanguageStats, tests_analyzed: bool) -> Self {
+        let functions = stats.functions.max(1) as f64;
+        let average_complexity = if stats.functions > 0 {
+            stats.total_complexity as f64 / functions
+        } else {
+            0.0
+        };
+        let average_function_lines = stats.function_lines as f64 / functions;
+        let average_file_lines = stats.lines as f64 / stats.files.max(1) as f64;
+
+        // Same simplified formula as the per-file index, on per-language averages
+        let maintainability = (171.0
+            - 5.2 * average_complexity.max(1.0).ln()
+            - 0.23 * average_function_lines
+            - 16.2 * average_file_lines.max(1.0).ln())
+            .clamp(0.0, 100.0);
+
+        let source_files = if tests_analyzed {
+            stats.files.saturating_sub(stats.test_files)
+        } else {
+            stats.files
+        };
+
+        Self {
+            language: language.to_string(),
+            files: stats.files,
+            lines: stats.lines,
+            functions: stats.functions,
+            average_complexity,
+            maintainability,
+            issue_density: stats.high_complexity_functions as f64 * 1000.0 / stats.lines.max(1) as f64,
+            test_ratio: (stats.test_files + stats.inline_test_files) as f64 / source_files.max(1) as f64,
+        }
+    }
+}
+
+impl Default for CodeAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Canonical language name for a file extension, or the name of the plugin registered for it
+pub fn language_for_extension(extension: &str) -> Option<&'static str> {
+    match extension {
+        "rs" => Some("Rust"),
+        "js" | "jsx" => Some("JavaScript"),
+        "ts" | "tsx" => Some("TypeScript"),
+        "py" => Some("Python"),
+        "go" => Some("Go"),
+        "rb" | "rake" => Some("Ruby"),
+        _ => language_provider::for_extension(extension).map(|provider| provider.name()),
+    }
+}
+
+fn is_test_file(path: &Path) -> bool {
+    let path_str = path.to_string_lossy();
+    path_str.contains("test") ||
+    path_str.contains("spec") ||
+    path_str.ends_with("_test.rs") ||
+    path_str.ends_with(".test.js") ||
+    path_str.ends_with("_spec.rb") ||
+    is_ruby_test_support(path)
+}
+
+/// 1 for a source file with inline tests; test files already count as tests
+fn inline_test_file(parsed_file: &ParsedFile) -> usize {
+    usize::from(parsed_file.inline_tests > 0 && !is_test_file(Path::new(&parsed_file.path)))
+}
+
+/// Cucumber step definitions and test-only Ruby helpers whose paths do not say "test" or "spec"
+fn is_ruby_test_support(path: &Path) -> bool {
+    let is_ruby = path.extension().is_some_and(|extension| extension == "rb");
+    let under = |directory: &str| path.components().any(|component| component.as_os_str() == directory);
+    is_ruby && (under("features") || under("fixtures") || under("factories"))
+}
+
+/// Resolve user-supplied language names such as `rust`, `js` or `py`
+pub fn parse_language_list(names: &[String]) -> Result<Vec<String>> {
+    names.iter()
+        .map(|name| {
+            let canonical = match name.trim().to_lowercase().as_str() {
+                "rust" | "rs" => "Rust",
+                "javascript" | "js" | "jsx" => "JavaScript",
+                "typescript" | "ts" | "tsx" => "TypeScript",
+                "python" | "py" => "Python",
+                "go" | "golang" => "Go",
+                "ruby" | "rb" => "Ruby",
+                _ => anyhow::bail!("Unknown language '{}' (run `languages` to list supported ones)", name),
+            };
+            Ok(canonical.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::IssueSeverity;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_language_list() {
+        let names = vec!["rust".to_string(), "JS".to_string(), " py ".to_string()];
+        assert_eq!(parse_language_list(&names).unwrap(), vec!["Rust", "JavaScript", "Python"]);
+        assert!(parse_language_list(&["cobol".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_ruby_test_support_paths() {
+        assert!(is_ruby_test_support(Path::new("features/step_definitions/checkout_steps.rb")));
+        assert!(is_ruby_test_support(Path::new("spec/factories/users.rb")));
+        assert!(!is_ruby_test_support(Path::new("app/models/feature_flag.rb")));
+        assert!(!is_ruby_test_support(Path::new("features/readme.md")));
+    }
+
+    #[test]
+    fn test_discovery_honors_language_selection() {
+        let temp_dir = tempdir().unwrap();
+        for name in ["lib.rs", "app.js", "tool.py"] {
+            std::fs::write(temp_dir.path().join(name), "").unwrap();
+        }
+
+        let analyzer = CodeAnalyzer::with_config(AnalysisConfig {
+            focus_languages: Some(vec!["Rust".to_string(), "JavaScript".to_string()]),
+            excluded_languages: vec!["JavaScript".to_string()],
+            ..AnalysisConfig::default()
+        });
+
+        let files = analyzer.discover_files(temp_dir.path()).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("lib.rs"));
+    }
+
+    #[test]
+    fn test_plugin_language_files_are_discovered_and_analyzed() {
+        let _registration = language_provider::Scoped::register(language_provider::Scripted {
+            name: "Discovered",
+            extensions: &["discovered"],
+        }).unwrap();
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("main.discovered"), "fn pick(x: i32) { if x > 0 { let f = || 1; } }\n").unwrap();
+
+        let config = AnalysisConfig::builder().quiet(true).build().unwrap();
+        let results = CodeAnalyzer::with_config(config).analyze_path(temp_dir.path()).unwrap();
+        assert_eq!(results.files_analyzed, 1);
+        assert_eq!(results.language_breakdown["Discovered"].functions, 1);
+        assert_eq!(results.language_breakdown["Discovered"].total_complexity, 3);
+    }
+
+    #[test]
+    fn test_config_builder_validates() {
+        let config = AnalysisConfig::builder()
+            .focus_languages(["rs", "py"])
+            .exclude_path("vendor")
+            .max_file_size(4096)
+            .include_tests(true)
+            .build()
+            .unwrap();
+        assert_eq!(config.focus_languages, Some(vec!["Rust".to_string(), "Python".to_string()]));
+        assert!(config.excluded_paths.contains(&"node_modules".to_string()));
+        assert!(config.excluded_paths.contains(&"vendor".to_string()));
+        assert_eq!(config.min_complexity_threshold, AnalysisConfig::default().min_complexity_threshold);
+
+        assert!(AnalysisConfig::builder().focus_languages(["cobol"]).build().is_err());
+        assert!(AnalysisConfig::builder().max_file_size(0).build().is_err());
+        assert!(AnalysisConfig::builder().focus_languages(["js"]).exclude_languages(["javascript"]).build().is_err());
+        let inverted = Thresholds { function_complexity_warn: 20, function_complexity_error: 10, ..Thresholds::default() };
+        assert!(AnalysisConfig::builder().thresholds(inverted).build().is_err());
+    }
+
+    #[test]
+    fn test_parse_error_rate() {
+        let mut results = AnalysisResults::new(Thresholds::default());
+        assert_eq!(results.parse_error_rate(), 0.0);
+
+        results.files_analyzed = 3;
+        results.errors.push("Failed to parse file: broken.rs".to_string());
+        assert_eq!(results.parse_error_rate(), 25.0);
+    }
+
+    #[test]
+    fn test_language_quality_ranks_worst_first() {
+        let mut results = AnalysisResults::new(Thresholds::default());
+        results.language_breakdown.insert("Go".to_string(), LanguageStats {
+            files: 4, lines: 400, functions: 20, function_lines: 200, total_complexity: 40,
+            high_complexity_functions: 0, test_files: 2, ..LanguageStats::default()
+        });
+        results.language_breakdown.insert("Python".to_string(), LanguageStats {
+            files: 2, lines: 2000, functions: 10, function_lines: 1500, total_complexity: 180,
+            high_complexity_functions: 4, test_files: 0, ..LanguageStats::default()
+        });
+        results.compare_languages();
+
+        let python = &results.language_quality[0];
+        assert_eq!(python.language, "Python");
+        assert_eq!(python.average_complexity, 18.0);
+        assert_eq!(python.issue_density, 2.0);
+        assert_eq!(python.test_ratio, 0.0);
+
+        let go = &results.language_quality[1];
+        assert!(go.maintainability > python.maintainability);
+        assert_eq!(go.test_ratio, 0.5);
+    }
+
+    #[test]
+    fn test_identical_files_are_counted_once() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::create_dir(temp_dir.path().join("vendor_a")).unwrap();
+        std::fs::create_dir(temp_dir.path().join("vendor_b")).unwrap();
+        std::fs::write(temp_dir.path().join("vendor_a/util.py"), "def helper():\n    return 1\n").unwrap();
+        std::fs::write(temp_dir.path().join("vendor_b/util.py"), "def helper():\n    return 1\n").unwrap();
+        std::fs::write(temp_dir.path().join("vendor_a/__init__.py"), "").unwrap();
+        std::fs::write(temp_dir.path().join("vendor_b/__init__.py"), "").unwrap();
+
+        let results = CodeAnalyzer::new().analyze_path(temp_dir.path()).unwrap();
+        assert_eq!(results.files_analyzed, 3);
+        assert_eq!(results.skip_counts[&SkipReason::Repeated], 1);
+        assert_eq!(results.repeated_files.len(), 1, "blank files are not reported as repeated");
+        assert_eq!(results.repeated_files[0].lines, 2);
+        assert!(results.repeated_files[0].paths[0].ends_with("vendor_a/util.py"));
+        assert!(results.repeated_files[0].paths[1].ends_with("vendor_b/util.py"));
+
+        let counting_all = CodeAnalyzer::with_config(AnalysisConfig {
+            count_repeated_files: true,
+            ..AnalysisConfig::default()
+        });
+        let results = counting_all.analyze_path(temp_dir.path()).unwrap();
+        assert_eq!(results.files_analyzed, 4);
+        assert_eq!(results.repeated_files.len(), 1);
+
+        let streamed = CodeAnalyzer::new().analyze_streaming(temp_dir.path(), |_| Ok(())).unwrap();
+        assert_eq!(streamed.files_analyzed, 3);
+        assert_eq!(streamed.repeated_files.len(), 1);
+    }
+
+    #[test]
+    fn test_small_functions_leave_averages_but_not_totals() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("model.py"), r#"
+def name(self): return self._name
+
+def classify(value):
+    if value < 0:
+        return "negative"
+    elif value == 0:
+        return "zero"
+    return "positive"
+"#).unwrap();
+
+        let config = AnalysisConfig::builder().min_function_statements(3).quiet(true).build().unwrap();
+        let results = CodeAnalyzer::with_config(config).analyze_path(temp_dir.path()).unwrap();
+        assert_eq!(results.total_functions, 2);
+        assert_eq!(results.small_functions_excluded, 1);
+        assert_eq!(results.complexity_distribution.values().sum::<u32>(), 1);
+
+        let all = CodeAnalyzer::with_config(AnalysisConfig::builder().quiet(true).build().unwrap())
+            .analyze_path(temp_dir.path())
+            .unwrap();
+        assert!(results.average_complexity > all.average_complexity);
+    }
+
+    #[test]
+    fn test_streaming_emits_each_file_and_matches_the_batch_summary() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("a.py"), "def f(x):\n    if x:\n        return 1\n    return 0\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.py"), "def g():\n    return 2\n\ndef h():\n    return 3\n").unwrap();
+
+        let analyzer = CodeAnalyzer::with_config(AnalysisConfig::builder().quiet(true).build().unwrap());
+        let mut records = Vec::new();
+        let streamed = analyzer.analyze_streaming(temp_dir.path(), |record| {
+            records.push(record.clone());
+            Ok(())
+        }).unwrap();
+        records.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        assert_eq!(records.iter().map(|record| (record.functions.len(), record.complexity)).collect::<Vec<_>>(), vec![(1, 2), (2, 2)]);
+
+        let batch = analyzer.analyze_path(temp_dir.path()).unwrap();
+        assert_eq!((streamed.files_analyzed, streamed.total_functions), (batch.files_analyzed, batch.total_functions));
+        assert_eq!(streamed.average_complexity, batch.average_complexity);
+        assert!(streamed.duplication_percent.is_some());
+        assert_eq!(streamed.duplication_percent, batch.duplication_percent);
+
+        let failed = analyzer.analyze_streaming(temp_dir.path(), |_| anyhow::bail!("broken pipe"));
+        assert!(failed.is_err());
+    }
+
+    #[test]
+    fn test_per_file_passes_come_from_the_parse_and_the_cache() {
+        let temp_dir = tempdir().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join("cart.py"), "class Cart:\n    def total(self, items):\n        if items:\n            return sum(items)\n        return 0\n").unwrap();
+
+        let config = AnalysisConfig::builder()
+            .quiet(true)
+            .cache_dir(temp_dir.path().join("cache"))
+            .line_complexity(true)
+            .vocabulary(true)
+            .type_metrics(true)
+            .function_records(true)
+            .function_shape(true)
+            .build()
+            .unwrap();
+        let analyzer = CodeAnalyzer::with_config(config);
+        let fresh = analyzer.analyze_path(&project).unwrap();
+        let cached = analyzer.analyze_path(&project).unwrap();
+
+        for results in [&fresh, &cached] {
+            assert_eq!(results.line_complexity[0].lines[0].line, 3);
+            assert_eq!(results.vocabulary[0].vocabulary, 5);
+            assert_eq!((results.types[0].name.as_str(), results.types[0].wmc), ("Cart", 2));
+            assert_eq!(results.functions[0].cognitive_complexity, Some(1));
+        }
+        // Without the option, the cached parse is not reused and the pass does not run
+        let plain = CodeAnalyzer::with_config(AnalysisConfig::builder().quiet(true).cache_dir(temp_dir.path().join("cache")).build().unwrap());
+        assert!(plain.analyze_path(&project).unwrap().line_complexity.is_empty());
+    }
+
+    #[test]
+    fn test_clones_come_from_the_parsed_files() {
+        let block = "def total(items, limit, bonus):\n    result = 0\n    for item in items:\n        if item > limit and item % 2 == 0:\n            result += item * 2 + limit - bonus\n        elif item < 0:\n            result -= abs(item) + bonus\n        else:\n            result += item + 1\n    return result\n";
+        let analyzer = CodeAnalyzer::new();
+        // Neither path exists, so nothing can be read back from disk
+        let parsed = ["gone/cart.py", "gone/orders.py"].iter()
+            .map(|path| analyzer.parse_source(Path::new(path), block))
+            .collect();
+        let results = analyzer.aggregate_results(parsed).unwrap();
+
+        assert_eq!(results.duplication_percent, Some(100.0));
+        assert_eq!(results.clones[0].locations.len(), 2);
+    }
+
+    #[test]
+    fn test_security_findings_are_reported_with_their_origin() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("routes.js"), "\
+app.get('/user', (req, res) => {
+  const id = req.query.id;
+  db.query(`SELECT * FROM users WHERE id = ${id}`);
+  const token = Math.random().toString(36);
+});
+").unwrap();
+
+        let results = CodeAnalyzer::with_config(AnalysisConfig::builder().quiet(true).build().unwrap())
+            .analyze_path(temp_dir.path())
+            .unwrap();
+        let rule_ids: Vec<_> = results.issues.iter().map(|found| found.issue.rule_id.as_str()).collect();
+        assert!(rule_ids.contains(&rules::TAINTED_INPUT), "{:?}", rule_ids);
+        assert!(rule_ids.contains(&rules::INSECURE_RANDOMNESS), "{:?}", rule_ids);
+        let tainted = results.issues.iter().find(|found| found.issue.rule_id == rules::TAINTED_INPUT).unwrap();
+        assert_eq!(tainted.issue.line, 3);
+        assert_eq!(tainted.issue.origin.as_ref().map(|origin| origin.line), Some(2));
+
+        let without = AnalysisConfig::builder()
+            .quiet(true)
+            .rules(RuleSet::from_overrides(&[], &[rules::TAINTED_INPUT.to_string()]).unwrap())
+            .build()
+            .unwrap();
+        let results = CodeAnalyzer::with_config(without).analyze_path(temp_dir.path()).unwrap();
+        assert!(results.issues.iter().all(|found| found.issue.rule_id != rules::TAINTED_INPUT));
+    }
+
+    #[test]
+    fn test_configured_severities_apply_to_findings_and_files() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("routes.js"), "\
+app.get('/user', (req, res) => {
+  const id = req.query.id;
+  db.query(`SELECT * FROM users WHERE id = ${id}`);
+});
+").unwrap();
+
+        let mut rules = RuleSet::default();
+        rules.override_severities(&["tainted-input=warning".to_string(), "high-file-complexity=critical".to_string()]).unwrap();
+        let thresholds = Thresholds { file_complexity_warn: 1, ..Thresholds::default() };
+        let config = AnalysisConfig::builder().quiet(true).rules(rules).thresholds(thresholds).build().unwrap();
+        let results = CodeAnalyzer::with_config(config).analyze_path(temp_dir.path()).unwrap();
+
+        let tainted = results.issues.iter().find(|found| found.issue.rule_id == rules::TAINTED_INPUT).unwrap();
+        assert_eq!(tainted.issue.severity, IssueSeverity::Warning);
+        assert_eq!(results.high_complexity_files[0].level, ThresholdLevel::Critical);
+    }
+
+    #[test]
+    fn test_weighted_average_and_mass_favor_long_functions() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::create_dir(temp_dir.path().join("core")).unwrap();
+        let branches = "    if x > 0:\n        x -= 1\n".repeat(9);
+        std::fs::write(temp_dir.path().join("core/engine.py"), format!("def run(x):\n{}    return x\n", branches)).unwrap();
+        std::fs::write(temp_dir.path().join("util.py"), "def one(x):\n    return x\n").unwrap();
+
+        let config = AnalysisConfig::builder().quiet(true).build().unwrap();
+        let results = CodeAnalyzer::with_config(config).analyze_path(temp_dir.path()).unwrap();
+        // run: complexity 10 over 20 lines; one: complexity 1 over 2 lines
+        assert_eq!(results.average_complexity, 5.5);
+        assert_eq!(results.weighted_average_complexity, 202.0 / 22.0);
+
+        let heaviest = &results.complexity_mass[0];
+        assert!(heaviest.path.ends_with("core"));
+        assert_eq!((heaviest.scope, heaviest.mass), (MassScope::Directory, 200));
+        assert!(results.complexity_mass.iter().any(|entry| entry.path.ends_with("engine.py") && entry.scope == MassScope::File));
+        let shares: f64 = results.complexity_mass.iter()
+            .filter(|entry| entry.scope == MassScope::File)
+            .map(|entry| entry.share_percent)
+            .sum();
+        assert!((shares - 100.0).abs() < 1e-9);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_files_are_analyzed_once() {
+        let temp_dir = tempdir().unwrap();
+        let complex = "fn tangled(x: i32) -> i32 {\n".to_string()
+            + &"    if x > 0 { return 1; }\n".repeat(12)
+            + "    0\n}\n";
+        std::fs::write(temp_dir.path().join("real.rs"), complex).unwrap();
+        std::os::unix::fs::symlink("real.rs", temp_dir.path().join("alias.rs")).unwrap();
+
+        let analyzer = CodeAnalyzer::new();
+        let discovery = analyzer.discover(temp_dir.path()).unwrap();
+        assert_eq!(discovery.files.len(), 1);
+        assert!(discovery.files[0].ends_with("alias.rs"));
+        assert_eq!(discovery.skipped.iter().filter(|skipped| skipped.reason == SkipReason::Duplicate).count(), 1);
+
+        // Parses of the same file under both names, as from two overlapping passes
+        let twice = [temp_dir.path().join("real.rs"), temp_dir.path().join("alias.rs")]
+            .iter()
+            .map(|path| analyzer.parse_one(path, None))
+            .collect();
+        let results = analyzer.aggregate_results(twice).unwrap();
+        assert_eq!(results.files_analyzed, 1);
+        assert_eq!(results.high_complexity_functions.len(), 1);
+    }
+
+    #[test]
+    fn test_discovery_explains_skips() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::create_dir(temp_dir.path().join(".git")).unwrap();
+        std::fs::write(temp_dir.path().join(".gitignore"), "generated/\n").unwrap();
+        std::fs::create_dir(temp_dir.path().join("generated")).unwrap();
+        std::fs::write(temp_dir.path().join("generated/out.rs"), "fn main() {}").unwrap();
+        std::fs::write(temp_dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "hello").unwrap();
+        std::fs::write(temp_dir.path().join("blob.rs"), [0u8, 159, 146, 150]).unwrap();
+        std::fs::write(temp_dir.path().join("big.py"), "x = 1\n".repeat(100)).unwrap();
+        std::fs::write(temp_dir.path().join("app.min.js"), "var a=1;").unwrap();
+        std::fs::write(temp_dir.path().join("api.go"), "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n").unwrap();
+
+        let analyzer = CodeAnalyzer::with_config(AnalysisConfig {
+            max_file_size: 100,
+            ..AnalysisConfig::default()
+        });
+        let discovery = analyzer.discover(temp_dir.path()).unwrap();
+        assert_eq!(discovery.files.len(), 1);
+        assert!(discovery.files[0].ends_with("main.rs"));
+
+        let reason_of = |name: &str| discovery.skipped.iter()
+            .find(|skipped| skipped.path.ends_with(name))
+            .map(|skipped| skipped.reason);
+        assert_eq!(reason_of("generated"), Some(SkipReason::Gitignored));
+        assert_eq!(reason_of("notes.txt"), Some(SkipReason::UnsupportedExtension));
+        assert_eq!(reason_of("blob.rs"), Some(SkipReason::Binary));
+        assert_eq!(reason_of("big.py"), Some(SkipReason::TooLarge));
+        assert_eq!(reason_of("app.min.js"), Some(SkipReason::Minified));
+        assert_eq!(reason_of("api.go"), Some(SkipReason::Generated));
+        assert!(discovery.skip_counts()[&SkipReason::Gitignored] >= 1);
+    }
+}
@@ -0,0 +1,16 @@
+//! Helpers shared by the tests of the tree-sitter detectors
+
+use tree_sitter::Node;
+
+use crate::parsers::{pool, SupportedLanguage};
+
+/// Run a detector's `find` over `source` and keep `project` of each finding
+pub(crate) fn detect<T, R>(
+    language: SupportedLanguage,
+    source: &str,
+    find: fn(Node, &str, &str) -> Vec<T>,
+    project: fn(T) -> R,
+) -> Vec<R> {
+    let tree = pool::parse(language, source).unwrap();
+    find(tree.root_node(), source, language.grammar_name()).into_iter().map(project).collect()
+}
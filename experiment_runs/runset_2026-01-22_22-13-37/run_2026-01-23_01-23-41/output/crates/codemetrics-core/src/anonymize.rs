@@ -0,0 +1,288 @@
+//! Consistent pseudonyms for paths and identifiers, for sharing reports
+//!
+//! Every path segment and identifier is replaced by a keyed hash. The key is
+//! drawn fresh for each `Anonymizer`, so within one run the same name always
+//! maps to the same pseudonym (directories still group, clones still pair up)
+//! while nobody can confirm a guessed name against a shared report. File
+//! extensions are kept so language breakdowns stay meaningful.
+
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::hash::BuildHasher;
+use std::path::{Component, Path, PathBuf};
+
+use crate::analyzers::{AnalysisResults, FileRecord, FunctionRecord, MassScope};
+use crate::rules;
+
+pub struct Anonymizer {
+    key: RandomState,
+}
+
+impl Default for Anonymizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Anonymizer {
+    pub fn new() -> Self {
+        Self { key: RandomState::new() }
+    }
+
+    /// `src/billing/invoice.rs` becomes e.g. `d_1f0c2a9b7e44/d_88a1c3d0f2b5/f_c4e7a90b13d2.rs`
+    pub fn path(&self, path: &str) -> String {
+        let path = Path::new(path);
+        let last = path.components().count().saturating_sub(1);
+        let mut anonymized = PathBuf::new();
+        for (index, component) in path.components().enumerate() {
+            match component {
+                Component::Normal(name) if index == last => {
+                    let name = Path::new(name);
+                    let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+                    let mut file = format!("f_{}", self.digest(&stem));
+                    if let Some(extension) = name.extension() {
+                        file.push('.');
+                        file.push_str(&extension.to_string_lossy());
+                    }
+                    anonymized.push(file);
+                }
+                Component::Normal(name) => {
+                    anonymized.push(format!("d_{}", self.digest(&name.to_string_lossy())));
+                }
+                // Roots, `.` and `..` say nothing about the project
+                other => anonymized.push(other.as_os_str()),
+            }
+        }
+        anonymized.to_string_lossy().replace('\\', "/")
+    }
+
+    /// Function names, build target labels and other identifiers
+    pub fn identifier(&self, name: &str) -> String {
+        format!("id_{}", self.digest(name))
+    }
+
+    /// Replace the path and function names of a streamed file record
+    pub fn file_record(&self, record: &mut FileRecord) {
+        record.file_path = self.path(&record.file_path);
+        for function in &mut record.functions {
+            self.function_record(function);
+        }
+    }
+
+    /// Replace every path, identifier and source excerpt in `results`
+    ///
+    /// Error messages quote paths and source, so only their number is kept.
+    pub fn apply(&self, results: &mut AnalysisResults) {
+        for function in &mut results.high_complexity_functions {
+            function.name = self.identifier(&function.name);
+            function.scope = self.scope(&function.scope);
+            function.file_path = self.path(&function.file_path);
+            function.snippet = None;
+        }
+        for file in &mut results.high_complexity_files {
+            file.file_path = self.path(&file.file_path);
+        }
+        // Messages and origins quote the code, so only the rule's description is kept
+        for found in &mut results.issues {
+            found.file_path = self.path(&found.file_path);
+            found.issue.message = rules::find_rule(&found.issue.rule_id)
+                .map_or_else(|| found.issue.rule_id.clone(), |rule| rule.description.to_string());
+            if let Some(origin) = &mut found.issue.origin {
+                origin.excerpt.clear();
+            }
+        }
+        for function in &mut results.functions {
+            self.function_record(function);
+        }
+        results.target_breakdown = std::mem::take(&mut results.target_breakdown).into_iter()
+            .map(|(label, stats)| (self.identifier(&label), stats))
+            .collect();
+        for error in &mut results.errors {
+            *error = "details withheld (--anonymize)".to_string();
+        }
+        // Reasons can quote a git or manifest error, and with it the project's path
+        results.capabilities.withhold_reasons("details withheld (--anonymize)");
+        for framework in &mut results.stack {
+            framework.evidence = framework.evidence.iter().map(|file| self.path(file)).collect();
+        }
+        for location in results.clones.iter_mut().flat_map(|class| &mut class.locations) {
+            location.file_path = self.path(&location.file_path);
+        }
+        for file in &mut results.file_duplication {
+            file.file_path = self.path(&file.file_path);
+        }
+        for path in results.repeated_files.iter_mut().flat_map(|group| &mut group.paths) {
+            *path = PathBuf::from(self.path(&path.to_string_lossy()));
+        }
+        for suppressed in &mut results.suppressions {
+            suppressed.file_path = self.path(&suppressed.file_path);
+            suppressed.function = suppressed.function.as_deref().map(|name| self.identifier(name));
+            suppressed.reason = None;
+        }
+        for debt in &mut results.debt_due {
+            debt.file_path = self.path(&debt.file_path);
+            debt.function = debt.function.as_deref().map(|name| self.identifier(name));
+            debt.reason = None;
+        }
+        for cluster in &mut results.issue_clusters {
+            for member in &mut cluster.members {
+                member.name = member.name.as_deref().map(|name| self.identifier(name));
+                member.file_path = self.path(&member.file_path);
+            }
+            // The members' common parent, so the directory matches their pseudonymized paths
+            if let Some(parent) = cluster.members.first().and_then(|member| member.file_path.rsplit_once('/')) {
+                cluster.directory = parent.0.to_string();
+            }
+        }
+        for entry in &mut results.complexity_mass {
+            entry.path = match (entry.scope, entry.path.as_str()) {
+                (_, ".") => ".".to_string(),
+                // The parent of a pseudonymized file inside it, so directories match file paths
+                (MassScope::Directory, directory) => self.path(&format!("{}/file", directory))
+                    .rsplit_once('/')
+                    .map_or_else(String::new, |(parent, _)| parent.to_string()),
+                (MassScope::File, file) => self.path(file),
+            };
+        }
+        for hotspot in &mut results.hotspots {
+            hotspot.file_path = self.path(&hotspot.file_path);
+        }
+        for file in &mut results.line_complexity {
+            file.file_path = self.path(&file.file_path);
+        }
+        for module in &mut results.vocabulary {
+            module.module = match module.module.as_str() {
+                "." => ".".to_string(),
+                directory => self.directory(directory),
+            };
+            module.terms = std::mem::take(&mut module.terms).into_iter()
+                .map(|(term, count)| (self.identifier(&term), count))
+                .fold(BTreeMap::new(), |mut terms, (term, count)| {
+                    *terms.entry(term).or_insert(0) += count;
+                    terms
+                });
+        }
+        for file in &mut results.concurrency {
+            file.file_path = self.path(&file.file_path);
+            for function in &mut file.functions {
+                function.name = self.identifier(&function.name);
+            }
+        }
+        for type_ in &mut results.types {
+            type_.name = self.identifier(&type_.name);
+            type_.file_path = self.path(&type_.file_path);
+        }
+        for surface in &mut results.impl_surface {
+            surface.name = self.identifier(&surface.name);
+            surface.file_path = self.path(&surface.file_path);
+            surface.traits = surface.traits.iter().map(|name| self.identifier(name)).collect();
+        }
+        for signature in &mut results.generic_signatures {
+            signature.name = self.identifier(&signature.name);
+            signature.file_path = self.path(&signature.file_path);
+        }
+        for krate in &mut results.unsafe_density {
+            krate.name = self.identifier(&krate.name);
+            krate.root = self.path(&krate.root);
+        }
+        for node in &mut results.call_graph {
+            node.name = self.identifier(&node.name);
+            node.file_path = self.path(&node.file_path);
+        }
+        results.file_complexity = std::mem::take(&mut results.file_complexity).into_iter()
+            .map(|(path, complexity)| (self.path(&path), complexity))
+            .collect();
+        results.file_totals = std::mem::take(&mut results.file_totals).into_iter()
+            .map(|(path, totals)| (self.path(&path), totals))
+            .collect();
+        // A commit hash can be looked up in a public repository; a hex pseudonym still shortens like one
+        for snapshot in &mut results.history {
+            snapshot.commit = self.digest(&snapshot.commit);
+        }
+        for skipped in &mut results.skipped {
+            skipped.path = PathBuf::from(self.path(&skipped.path.to_string_lossy()));
+        }
+        // Raw parses for `merge`; pseudonyms differ per run, so anonymized shards could not be merged anyway
+        results.shard = None;
+    }
+
+    fn function_record(&self, function: &mut FunctionRecord) {
+        function.name = self.identifier(&function.name);
+        function.scope = self.scope(&function.scope);
+        function.file_path = self.path(&function.file_path);
+    }
+
+    /// `Cart::total`'s scope `Cart`, segment by segment so a type's methods still share it
+    fn scope(&self, scope: &str) -> String {
+        if scope.is_empty() {
+            return String::new();
+        }
+        scope.split("::").map(|segment| self.identifier(segment)).collect::<Vec<_>>().join("::")
+    }
+
+    /// A directory path, whose last segment is not a file
+    fn directory(&self, path: &str) -> String {
+        path.split('/')
+            .map(|segment| format!("d_{}", self.digest(segment)))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn digest(&self, text: &str) -> String {
+        format!("{:012x}", self.key.hash_one(text) >> 16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pseudonyms_are_consistent_within_a_run() {
+        let anonymizer = Anonymizer::new();
+        let invoice = anonymizer.path("src/billing/invoice.rs");
+        let refund = anonymizer.path("src/billing/refund.rs");
+
+        assert!(!invoice.contains("billing") && !invoice.contains("invoice"));
+        assert!(invoice.ends_with(".rs"));
+        assert_eq!(invoice, anonymizer.path("src/billing/invoice.rs"));
+        // Siblings still share their directory
+        assert_eq!(invoice.rsplit_once('/').unwrap().0, refund.rsplit_once('/').unwrap().0);
+        assert_ne!(invoice, refund);
+        assert_eq!(anonymizer.path("./a/b.py").split('/').next(), Some("."));
+
+        assert_eq!(anonymizer.identifier("total"), anonymizer.identifier("total"));
+        assert_ne!(anonymizer.identifier("total"), Anonymizer::new().identifier("total"));
+    }
+
+    #[test]
+    fn test_apply_withholds_capability_reasons_commits_and_scopes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("invoice.py"), "def total(items):\n    return sum(items)\n").unwrap();
+        let mut results = crate::analyzers::CodeAnalyzer::new().analyze_path(temp_dir.path()).unwrap();
+        results.capabilities.unavailable(crate::capabilities::GIT_HISTORY, format!("could not open {}", temp_dir.path().display()));
+        let commit = "3f9c2b7e1d4a6b8c0e2f4a6b8c0d2e4f6a8b0c1d";
+        results.history.push(crate::history::Snapshot::from_results(&results, commit, false));
+        results.high_complexity_functions.push(crate::analyzers::HighComplexityFunction {
+            name: "total".to_string(),
+            scope: "Invoice".to_string(),
+            file_path: "invoice.py".to_string(),
+            complexity: 12,
+            line_start: 1,
+            line_end: 2,
+            parameters: 1,
+            stability: None,
+            snippet: None,
+        });
+
+        let anonymizer = Anonymizer::new();
+        anonymizer.apply(&mut results);
+
+        assert_eq!(results.capabilities.reason(crate::capabilities::GIT_HISTORY), Some("details withheld (--anonymize)"));
+        let snapshot = &results.history[0];
+        assert_ne!(snapshot.commit, commit);
+        assert!(snapshot.short_commit().chars().all(|c| c.is_ascii_hexdigit()));
+        assert!(!results.high_complexity_functions[0].scope.contains("Invoice"));
+        assert!(results.file_totals.keys().chain(results.file_complexity.keys()).all(|path| !path.contains("invoice")));
+    }
+}
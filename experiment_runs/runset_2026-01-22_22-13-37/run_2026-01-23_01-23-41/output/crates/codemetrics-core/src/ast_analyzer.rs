@@ -0,0 +1,1612 @@
+use anyhow::{Context, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tree_sitter::{InputEdit, Language, Node, Parser, Point, Query, QueryCursor, Tree};
+use crate::clone_detector::CloneDetector;
+use crate::query_cache;
+use crate::core::{CodeAction, CodeIssue, CodeMetrics, TextEdit, IssueSeverity, IssueCategory, Language as LangType};
+use crate::custom_rules::{self, CompiledRule, CustomRule};
+use crate::error_handling::{ErrorHandling, ErrorHandlingWeights};
+use crate::imports;
+use crate::insecure_random;
+use crate::concurrency_smells;
+use crate::resource_leaks;
+use crate::language_provider::{self, LanguageProvider};
+use crate::rules::{self, RuleSet};
+use crate::security_patterns::{self, SecurityPattern};
+use crate::taint;
+use crate::unsafe_usage;
+use crate::thresholds::{ThresholdLevel, Thresholds};
+
+/// Advanced AST-based code analyzer using tree-sitter
+pub struct ASTAnalyzer {
+    parsers: HashMap<LangType, Parser>,
+    queries: HashMap<LangType, QuerySet>,
+    /// Extra decision-point kinds declared by plugin languages
+    decision_kinds: HashMap<LangType, Vec<&'static str>>,
+    thresholds: Thresholds,
+    rules: RuleSet,
+    /// Project-specific query rules, see `crate::custom_rules`
+    custom_rules: HashMap<LangType, Vec<CompiledRule>>,
+    /// How much `try`/`catch`, `?` and `if err != nil` add to complexity
+    error_handling: ErrorHandling,
+}
+
+/// Collection of tree-sitter queries for a specific language
+///
+/// Queries come from the process-wide cache, so constructing another analyzer
+/// does not recompile them.
+pub struct QuerySet {
+    pub functions: Arc<Query>,
+    pub complexity_nodes: Arc<Query>,
+    pub imports: Arc<Query>,
+    pub exports: Arc<Query>,
+    pub security_patterns: Vec<SecurityQuery>,
+}
+
+/// A row of the security pattern table, compiled for its language
+pub struct SecurityQuery {
+    pub pattern: &'static SecurityPattern,
+    pub query: Arc<Query>,
+}
+
+/// Detailed function analysis result
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FunctionAnalysis {
+    pub name: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub parameter_count: u32,
+    pub cyclomatic_complexity: u32,
+    pub nesting_depth: u32,
+    pub lines_of_code: u32,
+    pub is_async: bool,
+    pub is_recursive: bool,
+    pub calls: Vec<String>,
+    /// Imported names the function body actually references
+    pub used_imports: Vec<String>,
+    /// Names imported by the enclosing file, for comparison with `used_imports`;
+    /// `None` when a glob import leaves them uncounted
+    pub available_imports: Option<u32>,
+    /// Hash of the function's source text, name and enclosing definitions;
+    /// `reanalyze` carries the analysis forward instead of recomputing it
+    /// while this stays the same
+    pub reuse_key: u64,
+}
+
+/// Import/Export analysis for dependency tracking
+#[derive(Debug, Clone)]
+pub struct ImportExportAnalysis {
+    pub imports: Vec<ImportInfo>,
+    pub exports: Vec<ExportInfo>,
+}
+
+/// One imported module, as `crate::imports` extracts it
+#[derive(Debug, Clone, Default)]
+pub struct ImportInfo {
+    pub module_path: String,
+    /// Names taken from the module, `name as local` when renamed
+    pub imported_names: Vec<String>,
+    pub is_default: bool,
+    /// Local name the module itself is bound to, e.g. `np` for `import numpy as np`
+    pub binding: Option<String>,
+    pub line: u32,
+    /// Inline `mod name { ... }` blocks around a Rust import, outermost first;
+    /// its `self` and `super` start from the innermost
+    pub inline_modules: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportInfo {
+    pub name: String,
+    pub is_default: bool,
+    pub line: u32,
+}
+
+/// Everything `ASTAnalyzer::analyze_file` extracts from one file
+type FileAnalysis = (CodeMetrics, Vec<CodeIssue>, Vec<FunctionAnalysis>, ImportExportAnalysis);
+
+/// A file's syntax tree kept alongside everything extracted from it
+///
+/// Downstream tools (refactoring bots, custom visualizers) can walk the tree
+/// instead of parsing the file again. The value owns both the source text and
+/// the tree, so it can be stored, sent to another thread or dropped freely;
+/// nodes borrow from it and cannot outlive it. `Tree::clone` is a cheap
+/// reference-counted copy when a tree must be shared.
+#[derive(Debug, Clone)]
+pub struct RetainedParse {
+    pub file_path: PathBuf,
+    pub language: LangType,
+    source: String,
+    tree: Tree,
+    pub metrics: CodeMetrics,
+    pub issues: Vec<CodeIssue>,
+    pub functions: Vec<FunctionAnalysis>,
+    pub imports_exports: ImportExportAnalysis,
+    /// Functions `reanalyze` took unchanged from the previous version
+    pub reused_functions: usize,
+}
+
+impl RetainedParse {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    pub fn root_node(&self) -> Node<'_> {
+        self.tree.root_node()
+    }
+
+    /// Source text covered by `node`, which must come from this file's tree
+    pub fn node_text(&self, node: Node<'_>) -> &str {
+        &self.source[node.byte_range()]
+    }
+
+    /// Take ownership of the source and tree, e.g. to re-parse incrementally after an edit
+    pub fn into_source_and_tree(self) -> (String, Tree) {
+        (self.source, self.tree)
+    }
+}
+
+impl ASTAnalyzer {
+    pub fn new() -> Result<Self> {
+        let mut parsers = HashMap::new();
+        let mut queries = HashMap::new();
+
+        // Initialize parsers for supported languages
+        if let Ok(parser) = Self::create_parser(LangType::JavaScript) {
+            parsers.insert(LangType::JavaScript, parser);
+            queries.insert(LangType::JavaScript, Self::create_js_queries()?);
+        }
+
+        if let Ok(parser) = Self::create_parser(LangType::Rust) {
+            parsers.insert(LangType::Rust, parser);
+            queries.insert(LangType::Rust, Self::create_rust_queries()?);
+        }
+
+        if let Ok(parser) = Self::create_parser(LangType::Python) {
+            parsers.insert(LangType::Python, parser);
+            queries.insert(LangType::Python, Self::create_python_queries()?);
+        }
+
+        if let Ok(parser) = Self::create_parser(LangType::Ruby) {
+            parsers.insert(LangType::Ruby, parser);
+            queries.insert(LangType::Ruby, Self::create_ruby_queries()?);
+        }
+
+        let mut analyzer = Self {
+            parsers,
+            queries,
+            decision_kinds: HashMap::new(),
+            thresholds: Thresholds::default(),
+            rules: RuleSet::default(),
+            custom_rules: HashMap::new(),
+            error_handling: ErrorHandling::default(),
+        };
+        for provider in language_provider::registered() {
+            analyzer.register_language(provider.as_ref())?;
+        }
+        Ok(analyzer)
+    }
+
+    /// Support a plugin language in this analyzer only, as `LangType::Plugin(provider.name())`
+    ///
+    /// Languages registered process-wide with `language_provider::register` are
+    /// added by `new` already.
+    pub fn register_language(&mut self, provider: &dyn LanguageProvider) -> Result<()> {
+        let grammar = provider.grammar();
+        let sources = provider.queries();
+        let compile = |source: &str| query_cache::compiled(provider.name(), &grammar, source)
+            .with_context(|| format!("Invalid query for plugin language {}", provider.name()));
+        let queries = QuerySet {
+            functions: compile(sources.functions)?,
+            complexity_nodes: compile(sources.complexity_nodes)?,
+            imports: compile(sources.imports)?,
+            exports: compile(sources.exports)?,
+            security_patterns: vec![SecurityQuery {
+                pattern: &security_patterns::PLUGIN_PATTERN,
+                query: compile(sources.security_patterns)?,
+            }],
+        };
+
+        let language = LangType::Plugin(provider.name().to_string());
+        self.parsers.insert(language.clone(), provider.create_parser()?);
+        self.queries.insert(language.clone(), queries);
+        self.decision_kinds.insert(language, provider.decision_kinds().to_vec());
+        Ok(())
+    }
+
+    /// Languages this analyzer can parse, built-in and plugin
+    pub fn languages(&self) -> Vec<LangType> {
+        self.parsers.keys().cloned().collect()
+    }
+
+    /// Use custom thresholds for issue detection
+    pub fn with_thresholds(mut self, thresholds: Thresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Only report issues for the enabled rules
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Weigh error-handling constructs in cyclomatic complexity, see `crate::error_handling`
+    pub fn with_error_handling(mut self, error_handling: ErrorHandling) -> Self {
+        self.error_handling = error_handling;
+        self
+    }
+
+    /// Also report matches of these user-defined rules
+    ///
+    /// Fails when a rule names a language this analyzer cannot parse or its
+    /// query does not compile against that grammar.
+    pub fn with_custom_rules(mut self, rules: Vec<CustomRule>) -> Result<Self> {
+        for rule in rules {
+            let language = custom_rules::resolve_language(&rule.language)?;
+            let grammar = self.parsers.get(&language)
+                .and_then(Parser::language)
+                .ok_or_else(|| anyhow::anyhow!("Custom rule '{}': no parser for {:?}", rule.id, language))?;
+            let compiled = CompiledRule::compile(rule, &grammar)?;
+            self.custom_rules.entry(language).or_default().push(compiled);
+        }
+        Ok(self)
+    }
+
+    /// Parse source code and perform comprehensive analysis
+    pub fn analyze_file(&mut self, content: &str, language: &LangType, _file_path: &Path) -> Result<(CodeMetrics, Vec<CodeIssue>, Vec<FunctionAnalysis>, ImportExportAnalysis)> {
+        let (_, analysis, _) = self.parse_and_analyze(content, language, None)?;
+        Ok(analysis)
+    }
+
+    /// Like `analyze_file`, but keep the syntax tree and source for later use
+    pub fn analyze_file_retained(&mut self, content: String, language: &LangType, file_path: &Path) -> Result<RetainedParse> {
+        let (tree, (metrics, issues, functions, imports_exports), _) = self.parse_and_analyze(&content, language, None)?;
+        Ok(RetainedParse {
+            file_path: file_path.to_path_buf(),
+            language: language.clone(),
+            source: content,
+            tree,
+            metrics,
+            issues,
+            functions,
+            imports_exports,
+            reused_functions: 0,
+        })
+    }
+
+    /// Analyze a new version of a retained file, re-parsing only what changed
+    ///
+    /// The changed span between the old and new text is applied to the old
+    /// tree with `Tree::edit`, so tree-sitter reuses every subtree outside it.
+    /// Editor integrations call this on each keystroke; parsing a large file
+    /// from scratch each time would dominate their latency.
+    ///
+    /// Functions whose text is unchanged keep their previous analysis, with
+    /// only their line numbers updated, so a small edit to a giant generated
+    /// file does not re-measure every function in it.
+    pub fn reanalyze(&mut self, previous: RetainedParse, content: String) -> Result<RetainedParse> {
+        let RetainedParse { file_path, language, source, mut tree, functions, imports_exports, .. } = previous;
+        match input_edit(&source, &content) {
+            Some(edit) => tree.edit(&edit),
+            None => return self.analyze_file_retained(content, &language, &file_path),
+        }
+
+        let previous = Previous { tree: &tree, functions: &functions, bindings: import_bindings(&imports_exports.imports) };
+        let (tree, (metrics, issues, functions, imports_exports), reused_functions) = self.parse_and_analyze(&content, &language, Some(previous))?;
+        Ok(RetainedParse { file_path, language, source: content, tree, metrics, issues, functions, imports_exports, reused_functions })
+    }
+
+    /// Parse `content`, reusing the previous version's tree and function analyses where they still apply
+    ///
+    /// Also returns how many functions were carried forward.
+    fn parse_and_analyze(&mut self, content: &str, language: &LangType, previous: Option<Previous>) -> Result<(Tree, FileAnalysis, usize)> {
+        let parser = self.parsers.get_mut(language)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported language: {:?}", language))?;
+
+        let tree = parser.parse(content, previous.as_ref().map(|previous| previous.tree))
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse file"))?;
+
+        let root_node = tree.root_node();
+
+        // Perform different types of analysis; imports come first so functions can be resolved against them
+        let imports_exports = self.analyze_imports_exports(&tree, content, language)?;
+        let bindings = import_bindings(&imports_exports.imports);
+        // Which imports a function uses depends on the file's imports, not just its own text
+        let reusable: HashMap<u64, &FunctionAnalysis> = previous.iter()
+            .filter(|previous| previous.bindings == bindings)
+            .flat_map(|previous| previous.functions)
+            .map(|function| (function.reuse_key, function))
+            .collect();
+        let (functions, reused) = self.analyze_functions(&tree, content, language, &bindings, &reusable)?;
+        let metrics = self.calculate_metrics(&root_node, content, &functions)?;
+        let issues = self.detect_issues(&root_node, content, language, &functions)?;
+
+        Ok((tree, (metrics, issues, functions, imports_exports), reused))
+    }
+
+    /// Analyze all functions in the code, taking any in `reusable` (keyed by `reuse_key`) as they are
+    ///
+    /// Also returns how many were taken from `reusable`.
+    fn analyze_functions(&self, tree: &Tree, content: &str, language: &LangType, bindings: &HashSet<String>, reusable: &HashMap<u64, &FunctionAnalysis>) -> Result<(Vec<FunctionAnalysis>, usize)> {
+        let queries = self.queries.get(language)
+            .ok_or_else(|| anyhow::anyhow!("No queries for language: {:?}", language))?;
+
+        let mut cursor = QueryCursor::new();
+        let captures = cursor.matches(&queries.functions, tree.root_node(), content.as_bytes());
+
+        let mut functions = Vec::new();
+        let mut reused = 0;
+        let lines: Vec<&str> = content.lines().collect();
+
+        for match_ in captures {
+            if let Some(function_node) = match_.captures.first().map(|c| c.node) {
+                let analysis = match reusable.get(&reuse_key(function_node, content)) {
+                    Some(&previous) => {
+                        reused += 1;
+                        // Same text, but edits above it may have moved it
+                        FunctionAnalysis {
+                            start_line: function_node.start_position().row as u32 + 1,
+                            end_line: function_node.end_position().row as u32 + 1,
+                            ..previous.clone()
+                        }
+                    }
+                    None => self.analyze_single_function(function_node, &lines, content, language, bindings)?,
+                };
+                functions.push(analysis);
+            }
+        }
+
+        Ok((functions, reused))
+    }
+
+    /// Analyze a single function node in detail
+    fn analyze_single_function(&self, node: Node, lines: &[&str], content: &str, language: &LangType, bindings: &HashSet<String>) -> Result<FunctionAnalysis> {
+        let start_line = node.start_position().row as u32 + 1;
+        let end_line = node.end_position().row as u32 + 1;
+
+        // Extract function name
+        let name = function_name(node, content)
+            .unwrap_or_else(|| "anonymous".to_string());
+
+        // Count parameters
+        let parameter_count = parameter_count(node);
+
+        // Calculate cyclomatic complexity
+        let cyclomatic_complexity = self.calculate_cyclomatic_complexity(node, content, language);
+
+        // Calculate nesting depth
+        let nesting_depth = nesting_depth(node);
+
+        // Count lines of code (excluding blanks and comments)
+        let lines_of_code = self.count_function_loc(start_line, end_line, lines);
+
+        // Detect async functions
+        let is_async = self.is_async_function(node, content);
+
+        // Detect recursive calls
+        let is_recursive = self.is_recursive_function(node, content, &name);
+
+        // Extract function calls
+        let calls = self.extract_function_calls(node, content);
+
+        // Resolve identifiers against the file's imports
+        let used_imports = self.resolve_used_imports(node, content, bindings);
+
+        Ok(FunctionAnalysis {
+            name,
+            start_line,
+            end_line,
+            parameter_count,
+            cyclomatic_complexity,
+            nesting_depth,
+            lines_of_code,
+            is_async,
+            is_recursive,
+            calls,
+            used_imports,
+            available_imports: (!bindings.contains("*")).then_some(bindings.len() as u32),
+            reuse_key: reuse_key(node, content),
+        })
+    }
+
+    /// Calculate comprehensive code metrics
+    fn calculate_metrics(&self, root: &Node, content: &str, functions: &[FunctionAnalysis]) -> Result<CodeMetrics> {
+        let lines: Vec<&str> = content.lines().collect();
+        let total_lines = lines.len() as u32;
+
+        // Count lines of code (excluding comments and blank lines)
+        let lines_of_code = lines.iter()
+            .filter(|line| {
+                let trimmed = line.trim();
+                !trimmed.is_empty() &&
+                !trimmed.starts_with("//") &&
+                !trimmed.starts_with('#') &&
+                !trimmed.starts_with("/*")
+            })
+            .count() as u32;
+
+        let function_count = functions.len() as u32;
+        let parameter_count: u32 = functions.iter().map(|f| f.parameter_count).sum();
+        let cyclomatic_complexity: u32 = functions.iter().map(|f| f.cyclomatic_complexity).sum();
+        let max_nesting_depth = functions.iter().map(|f| f.nesting_depth).max().unwrap_or(0);
+
+        // Calculate maintainability index (simplified version)
+        let avg_complexity = if function_count > 0 {
+            cyclomatic_complexity as f64 / function_count as f64
+        } else {
+            1.0
+        };
+        let avg_loc = if function_count > 0 {
+            lines_of_code as f64 / function_count as f64
+        } else {
+            lines_of_code as f64
+        };
+
+        // Simplified maintainability index calculation
+        let maintainability_index = (171.0 - 5.2 * avg_complexity.ln() - 0.23 * avg_loc - 16.2 * (lines_of_code as f64).ln())
+            .clamp(0.0, 100.0);
+
+        Ok(CodeMetrics {
+            cyclomatic_complexity,
+            lines_of_code,
+            total_lines,
+            function_count,
+            parameter_count,
+            max_nesting_depth,
+            maintainability_index,
+            duplication_percent: duplication_percent(*root, content),
+        })
+    }
+
+    /// Detect various code quality issues
+    fn detect_issues(&self, root: &Node, content: &str, language: &LangType, functions: &[FunctionAnalysis]) -> Result<Vec<CodeIssue>> {
+        let mut issues = Vec::new();
+
+        // Check for overly complex functions
+        for func in functions {
+            let complexity_severity = match self.thresholds.function_level(func.cyclomatic_complexity) {
+                ThresholdLevel::Critical => Some(IssueSeverity::Critical),
+                ThresholdLevel::Error => Some(IssueSeverity::Error),
+                ThresholdLevel::Warn => Some(IssueSeverity::Warning),
+                ThresholdLevel::Ok => None,
+            };
+            if let Some(severity) = complexity_severity.filter(|_| self.rules.is_enabled(rules::HIGH_CYCLOMATIC_COMPLEXITY)) {
+                issues.push(CodeIssue {
+                    rule_id: rules::HIGH_CYCLOMATIC_COMPLEXITY.to_string(),
+                    severity,
+                    category: IssueCategory::Complexity,
+                    message: format!("Function '{}' has high cyclomatic complexity ({})", func.name, func.cyclomatic_complexity),
+                    line: func.start_line,
+                    column: 1,
+                    suggestion: Some(with_extraction_hint("Consider breaking this function into smaller, more focused functions", func)),
+                    action: None,
+                    origin: None,
+                });
+            }
+
+            if func.parameter_count > self.thresholds.max_parameters && self.rules.is_enabled(rules::TOO_MANY_PARAMETERS) {
+                issues.push(CodeIssue {
+                    rule_id: rules::TOO_MANY_PARAMETERS.to_string(),
+                    severity: IssueSeverity::Warning,
+                    category: IssueCategory::Maintainability,
+                    message: format!("Function '{}' has too many parameters ({})", func.name, func.parameter_count),
+                    line: func.start_line,
+                    column: 1,
+                    suggestion: Some("Consider using a parameter object or reducing the number of parameters".to_string()),
+                    action: None,
+                    origin: None,
+                });
+            }
+
+            if func.lines_of_code > self.thresholds.max_function_lines && self.rules.is_enabled(rules::LONG_FUNCTION) {
+                issues.push(CodeIssue {
+                    rule_id: rules::LONG_FUNCTION.to_string(),
+                    severity: IssueSeverity::Info,
+                    category: IssueCategory::Maintainability,
+                    message: format!("Function '{}' is quite long ({} lines)", func.name, func.lines_of_code),
+                    line: func.start_line,
+                    column: 1,
+                    suggestion: Some(with_extraction_hint("Consider breaking this function into smaller functions", func)),
+                    action: None,
+                    origin: None,
+                });
+            }
+
+            if func.nesting_depth > self.thresholds.max_nesting_depth && self.rules.is_enabled(rules::DEEP_NESTING) {
+                issues.push(CodeIssue {
+                    rule_id: rules::DEEP_NESTING.to_string(),
+                    severity: IssueSeverity::Warning,
+                    category: IssueCategory::Complexity,
+                    message: format!("Function '{}' has deep nesting (depth {})", func.name, func.nesting_depth),
+                    line: func.start_line,
+                    column: 1,
+                    suggestion: Some("Consider using early returns or extracting nested logic".to_string()),
+                    action: function_node_at(*root, func.start_line)
+                        .and_then(|node| early_return_action(node, content, language)),
+                    origin: None,
+                });
+            }
+        }
+
+        let patterns = self.queries.get(language)
+            .map(|queries| queries.security_patterns.as_slice())
+            .unwrap_or_default();
+        issues.extend(findings(*root, content, language, &self.rules, patterns));
+
+        // Project-specific rules from `.codemetrics/rules.toml`
+        for rule in self.custom_rules.get(language).into_iter().flatten() {
+            issues.extend(rule.issues(root, content));
+        }
+
+        // Severities the user changed with `--rule-severity` or `[rules] severity`
+        self.rules.apply_severities(&mut issues);
+
+        Ok(issues)
+    }
+
+    /// Analyze imports and exports for dependency tracking
+    fn analyze_imports_exports(&self, tree: &Tree, content: &str, language: &LangType) -> Result<ImportExportAnalysis> {
+        let queries = self.queries.get(language)
+            .ok_or_else(|| anyhow::anyhow!("No queries for language: {:?}", language))?;
+
+        let mut cursor = QueryCursor::new();
+        let mut imports = Vec::new();
+        let mut exports = Vec::new();
+
+        // Analyze imports
+        let import_matches = cursor.matches(&queries.imports, tree.root_node(), content.as_bytes());
+        for match_ in import_matches {
+            let statement = match_.captures.iter()
+                .find(|capture| queries.imports.capture_names()[capture.index as usize] == "import");
+            if let Some(statement) = statement {
+                imports.extend(imports::extract(statement.node, content, language));
+            }
+        }
+
+        // Analyze exports
+        let export_matches = cursor.matches(&queries.exports, tree.root_node(), content.as_bytes());
+        for match_ in export_matches {
+            if let Some(export_info) = self.extract_export_info(match_, content, &queries.exports) {
+                exports.push(export_info);
+            }
+        }
+
+        Ok(ImportExportAnalysis { imports, exports })
+    }
+
+    // Helper methods for tree-sitter operations
+    fn create_parser(language: LangType) -> Result<Parser> {
+        let mut parser = Parser::new();
+        let tree_sitter_lang = match language {
+            LangType::JavaScript => tree_sitter::Language::new(tree_sitter_javascript::LANGUAGE),
+            LangType::Rust => tree_sitter::Language::new(tree_sitter_rust::LANGUAGE),
+            LangType::Python => tree_sitter::Language::new(tree_sitter_python::LANGUAGE),
+            LangType::Ruby => tree_sitter::Language::new(tree_sitter_ruby::LANGUAGE),
+            _ => return Err(anyhow::anyhow!("Unsupported language for parser creation")),
+        };
+        parser.set_language(&tree_sitter_lang)
+            .map_err(|e| anyhow::anyhow!("Failed to set parser language: {}", e))?;
+        Ok(parser)
+    }
+
+    fn create_js_queries() -> Result<QuerySet> {
+        let functions = query_cache::compiled("javascript", &tree_sitter::Language::new(tree_sitter_javascript::LANGUAGE),
+            "(function_declaration name: (identifier) @func.name) @func.def
+             (method_definition name: (property_identifier) @func.name) @func.def
+             (arrow_function) @func.def")?;
+
+        let complexity_nodes = query_cache::compiled("javascript", &tree_sitter::Language::new(tree_sitter_javascript::LANGUAGE),
+            "(if_statement) @decision
+             (while_statement) @decision
+             (for_statement) @decision
+             (for_in_statement) @decision
+             (switch_statement) @decision
+             (try_statement) @decision")?;
+
+        let imports = query_cache::compiled("javascript", &tree_sitter::Language::new(tree_sitter_javascript::LANGUAGE),
+            "(import_statement) @import
+             (call_expression
+                function: (identifier) @require.function
+                (#eq? @require.function \"require\")) @import
+             (call_expression function: (import)) @import")?;
+
+        let exports = query_cache::compiled("javascript", &tree_sitter::Language::new(tree_sitter_javascript::LANGUAGE),
+            "(export_statement) @export")?;
+
+        let security_patterns = security_queries("javascript", &tree_sitter::Language::new(tree_sitter_javascript::LANGUAGE))?;
+
+        Ok(QuerySet {
+            functions,
+            complexity_nodes,
+            imports,
+            exports,
+            security_patterns,
+        })
+    }
+
+    fn create_rust_queries() -> Result<QuerySet> {
+        let functions = query_cache::compiled("rust", &tree_sitter::Language::new(tree_sitter_rust::LANGUAGE),
+            "(function_item name: (identifier) @func.name) @func.def")?;
+
+        let complexity_nodes = query_cache::compiled("rust", &tree_sitter::Language::new(tree_sitter_rust::LANGUAGE),
+            "(if_expression) @decision
+             (while_expression) @decision
+             (for_expression) @decision
+             (match_expression) @decision
+             (loop_expression) @decision")?;
+
+        let imports = query_cache::compiled("rust", &tree_sitter::Language::new(tree_sitter_rust::LANGUAGE),
+            "(use_declaration) @import")?;
+
+        let exports = query_cache::compiled("rust", &tree_sitter::Language::new(tree_sitter_rust::LANGUAGE),
+            "(visibility_modifier) @export")?;
+
+        let security_patterns = security_queries("rust", &tree_sitter::Language::new(tree_sitter_rust::LANGUAGE))?;
+
+        Ok(QuerySet {
+            functions,
+            complexity_nodes,
+            imports,
+            exports,
+            security_patterns,
+        })
+    }
+
+    fn create_python_queries() -> Result<QuerySet> {
+        let functions = query_cache::compiled("python", &tree_sitter::Language::new(tree_sitter_python::LANGUAGE),
+            "(function_definition name: (identifier) @func.name) @func.def")?;
+
+        let complexity_nodes = query_cache::compiled("python", &tree_sitter::Language::new(tree_sitter_python::LANGUAGE),
+            "(if_statement) @decision
+             (while_statement) @decision
+             (for_statement) @decision
+             (try_statement) @decision
+             (with_statement) @decision")?;
+
+        let imports = query_cache::compiled("python", &tree_sitter::Language::new(tree_sitter_python::LANGUAGE),
+            "(import_statement) @import
+             (import_from_statement) @import")?;
+
+        let exports = query_cache::compiled("python", &tree_sitter::Language::new(tree_sitter_python::LANGUAGE),
+            "(assignment left: (identifier) @export)")?;
+
+        let security_patterns = security_queries("python", &tree_sitter::Language::new(tree_sitter_python::LANGUAGE))?;
+
+        Ok(QuerySet {
+            functions,
+            complexity_nodes,
+            imports,
+            exports,
+            security_patterns,
+        })
+    }
+
+    fn create_ruby_queries() -> Result<QuerySet> {
+        // Blocks count as functions: their bodies carry much of the logic in idiomatic Ruby
+        let functions = query_cache::compiled("ruby", &tree_sitter::Language::new(tree_sitter_ruby::LANGUAGE),
+            "(method name: (_) @func.name) @func.def
+             (singleton_method name: (_) @func.name) @func.def
+             (block) @func.def
+             (do_block) @func.def")?;
+
+        let complexity_nodes = query_cache::compiled("ruby", &tree_sitter::Language::new(tree_sitter_ruby::LANGUAGE),
+            "(if) @decision
+             (unless) @decision
+             (while) @decision
+             (until) @decision
+             (for) @decision
+             (case) @decision
+             (rescue) @decision")?;
+
+        let imports = query_cache::compiled("ruby", &tree_sitter::Language::new(tree_sitter_ruby::LANGUAGE),
+            "(call
+                method: (identifier) @require.method
+                arguments: (argument_list (string (string_content) @require.path))
+                (#match? @require.method \"^(require|require_relative)$\")) @import")?;
+
+        let exports = query_cache::compiled("ruby", &tree_sitter::Language::new(tree_sitter_ruby::LANGUAGE),
+            "(class name: (_) @export.name) @export
+             (module name: (_) @export.name) @export")?;
+
+        let security_patterns = security_queries("ruby", &tree_sitter::Language::new(tree_sitter_ruby::LANGUAGE))?;
+
+        Ok(QuerySet {
+            functions,
+            complexity_nodes,
+            imports,
+            exports,
+            security_patterns,
+        })
+    }
+
+    // Helper method implementations for AST analysis
+    fn calculate_cyclomatic_complexity(&self, node: Node, content: &str, language: &LangType) -> u32 {
+        let mut complexity = 1; // Base complexity
+        let extra_kinds = self.decision_kinds.get(language).map(Vec::as_slice).unwrap_or_default();
+        let weights = self.error_handling.for_language(language);
+
+        walk_descendants(node, |node, _| {
+            complexity += match weights.weight(node, content) {
+                Some(weight) => weight,
+                None => u32::from(is_decision_point(node) || (node.is_named() && extra_kinds.contains(&node.kind()))),
+            };
+            true
+        });
+
+        complexity
+    }
+
+    fn count_function_loc(&self, start_line: u32, end_line: u32, lines: &[&str]) -> u32 {
+        let mut loc = 0;
+        let start_idx = (start_line as usize).saturating_sub(1);
+        let end_idx = ((end_line as usize).min(lines.len())).saturating_sub(1);
+
+        for line in lines.iter().take(end_idx + 1).skip(start_idx) {
+            let line = line.trim();
+            // Count non-empty lines that aren't just comments
+            if !line.is_empty() &&
+               !line.starts_with("//") &&
+               !line.starts_with('#') &&
+               !line.starts_with("/*") &&
+               !line.starts_with("*") &&
+               line != "{" && line != "}" {
+                loc += 1;
+            }
+        }
+
+        loc
+    }
+
+    fn is_async_function(&self, node: Node, content: &str) -> bool {
+        let mut cursor = node.walk();
+
+        // Check if any child or ancestor contains 'async' keyword
+        if cursor.goto_first_child() {
+            loop {
+                let child = cursor.node();
+                if child.kind() == "async" {
+                    return true;
+                }
+
+                // Check the text content for async keyword
+                let text = &content[child.start_byte()..child.end_byte()];
+                if text.contains("async") {
+                    return true;
+                }
+
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn is_recursive_function(&self, node: Node, content: &str, name: &str) -> bool {
+        if name.is_empty() || name == "anonymous" {
+            return false;
+        }
+
+        let mut found = false;
+
+        walk_descendants(node, |node, _| {
+            // Check if this is a call expression
+            if node.kind() == "call_expression" || node.kind() == "call" {
+                // Look for the function identifier in the call
+                let call_text = &content[node.start_byte()..node.end_byte()];
+                if call_text.starts_with(name) || call_text.contains(&format!("{}(", name)) {
+                    found = true;
+                }
+            }
+
+            // Check if this is an identifier that matches our function name
+            if node.kind() == "identifier" && &content[node.start_byte()..node.end_byte()] == name {
+                // Need to check if this is in a call context, not just a declaration
+                if let Some(parent) = node.parent() {
+                    if parent.kind() == "call_expression" || parent.kind() == "call" {
+                        found = true;
+                    }
+                }
+            }
+
+            !found
+        });
+
+        found
+    }
+
+    fn extract_function_calls(&self, node: Node, content: &str) -> Vec<String> {
+        let mut calls = Vec::new();
+
+        walk_descendants(node, |node, _| {
+            // Look for call expressions
+            if node.kind() == "call_expression" || node.kind() == "call" {
+                // Try to find the function name being called
+                if let Some(function_node) = node.child(0) {
+                    match function_node.kind() {
+                        "identifier" => {
+                            let call_name = &content[function_node.start_byte()..function_node.end_byte()];
+                            calls.push(call_name.to_string());
+                        }
+                        "member_expression" | "attribute" => {
+                            // For method calls like obj.method()
+                            let call_text = &content[function_node.start_byte()..function_node.end_byte()];
+                            calls.push(call_text.to_string());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            true
+        });
+
+        calls
+    }
+
+    fn resolve_used_imports(&self, node: Node, content: &str, bindings: &HashSet<String>) -> Vec<String> {
+        let mut used = BTreeSet::new();
+
+        walk_descendants(node, |node, _| {
+            if matches!(node.kind(), "identifier" | "type_identifier") {
+                let name = &content[node.start_byte()..node.end_byte()];
+                if bindings.contains(name) {
+                    used.insert(name.to_string());
+                }
+            }
+            true
+        });
+
+        used.into_iter().collect()
+    }
+
+    fn extract_export_info(&self, match_: tree_sitter::QueryMatch, content: &str, query: &Query) -> Option<ExportInfo> {
+        // Only Ruby classes and modules are extracted so far
+        let name = match_.captures.iter()
+            .find(|capture| query.capture_names()[capture.index as usize] == "export.name")?
+            .node;
+        Some(ExportInfo {
+            name: content[name.byte_range()].to_string(),
+            is_default: false,
+            line: name.start_position().row as u32 + 1,
+        })
+    }
+}
+
+/// Duplication within a single file, for per-file metrics
+fn duplication_percent(root: Node, content: &str) -> f64 {
+    let mut detector = CloneDetector::default();
+    detector.add_tree("", root, content);
+    detector.finish().duplication_percent
+}
+
+/// The version of a file `reanalyze` starts from
+struct Previous<'a> {
+    /// Already edited to match the new text
+    tree: &'a Tree,
+    functions: &'a [FunctionAnalysis],
+    bindings: HashSet<String>,
+}
+
+/// What a function's analysis depends on: its text, its name (a Ruby block's
+/// comes from the call it is passed to) and the names of the definitions around it
+fn reuse_key(node: Node, content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content[node.byte_range()].hash(&mut hasher);
+    function_name(node, content).hash(&mut hasher);
+    let mut ancestor = node.parent();
+    while let Some(scope) = ancestor {
+        if let Some(name) = ["name", "type", "method"].iter().find_map(|field| scope.child_by_field_name(field)) {
+            scope.kind().hash(&mut hasher);
+            content[name.byte_range()].hash(&mut hasher);
+        }
+        ancestor = scope.parent();
+    }
+    hasher.finish()
+}
+
+/// Local names bound by a file's imports
+///
+/// Named imports bind their (possibly aliased) names and whole-module imports
+/// their `binding`, e.g. `np` for `import numpy as np` or `React` for
+/// `import React from "react"`; side-effect imports and `as _` bind nothing.
+/// A glob import binds names that cannot be listed, so it is kept as `*`,
+/// which no identifier matches.
+fn import_bindings(imports: &[ImportInfo]) -> HashSet<String> {
+    imports.iter()
+        .flat_map(|import| {
+            let named = import.imported_names.iter().map(|name| name.rsplit(" as ").next().unwrap_or(name).trim());
+            import.binding.as_deref().into_iter().chain(named)
+        })
+        .filter(|local| !local.is_empty() && *local != "_")
+        .map(str::to_string)
+        .collect()
+}
+
+/// Append a note when a function depends on only a small share of its file's imports
+fn with_extraction_hint(suggestion: &str, func: &FunctionAnalysis) -> String {
+    let used = func.used_imports.len() as u32;
+    match func.available_imports {
+        Some(available) if available >= 4 && used * 2 <= available => format!(
+            "{}; it only needs {} of the file's {} imports, so extraction is cheap",
+            suggestion, used, available
+        ),
+        _ => suggestion.to_string(),
+    }
+}
+
+/// The single edit turning `old` into `new`: everything between their common prefix and suffix
+///
+/// `None` when the texts are identical, since there is nothing to tell the tree.
+pub(crate) fn input_edit(old: &str, new: &str) -> Option<InputEdit> {
+    let mut prefix = old.bytes().zip(new.bytes()).take_while(|(a, b)| a == b).count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    if prefix == old.len() && prefix == new.len() {
+        return None;
+    }
+
+    // The suffix may not reach back into the prefix of either text
+    let mut suffix = old[prefix..].bytes().rev().zip(new[prefix..].bytes().rev()).take_while(|(a, b)| a == b).count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+
+    let old_end_byte = old.len() - suffix;
+    let new_end_byte = new.len() - suffix;
+    Some(InputEdit {
+        start_byte: prefix,
+        old_end_byte,
+        new_end_byte,
+        start_position: point_at(old, prefix),
+        old_end_position: point_at(old, old_end_byte),
+        new_end_position: point_at(new, new_end_byte),
+    })
+}
+
+/// Row and byte column of `byte` in `text`, as tree-sitter counts them
+fn point_at(text: &str, byte: usize) -> Point {
+    let before = &text[..byte];
+    match before.rfind('\n') {
+        Some(newline) => Point::new(before.matches('\n').count(), byte - newline - 1),
+        None => Point::new(0, byte),
+    }
+}
+
+/// Security, concurrency and resource findings in one file, by every enabled rule
+///
+/// Both analyzers report these: `ASTAnalyzer` for single files and editors,
+/// `CodeAnalyzer` for `analyze`. `patterns` are the security pattern queries
+/// compiled for `language`'s grammar, see `security_queries`.
+pub(crate) fn findings(root: Node, content: &str, language: &LangType, rules: &RuleSet, patterns: &[SecurityQuery]) -> Vec<CodeIssue> {
+    let mut issues = security_pattern_issues(root, content, rules, patterns);
+
+    // User input followed into injection sinks, with where it came from
+    if let Some(grammar) = taint_grammar(language).filter(|_| rules.is_enabled(rules::TAINTED_INPUT)) {
+        issues.extend(taint::trace(root, content, grammar).into_iter().map(|flow| CodeIssue {
+            rule_id: rules::TAINTED_INPUT.to_string(),
+            severity: IssueSeverity::Error,
+            category: IssueCategory::Security,
+            message: format!(
+                "Input `{}` from line {} reaches {}: `{}`",
+                flow.source.excerpt, flow.source.line, flow.kind.describe(), flow.sink.excerpt
+            ),
+            line: flow.sink.line,
+            column: flow.sink.column,
+            suggestion: Some(flow.kind.suggestion().to_string()),
+            action: None,
+            origin: Some(flow.source),
+        }));
+    }
+
+    // Predictable generators feeding tokens, passwords and other secrets
+    if let Some(grammar) = taint_grammar(language).filter(|_| rules.is_enabled(rules::INSECURE_RANDOMNESS)) {
+        issues.extend(insecure_random::find(root, content, grammar).into_iter().map(|found| CodeIssue {
+            rule_id: rules::INSECURE_RANDOMNESS.to_string(),
+            severity: IssueSeverity::Error,
+            category: IssueCategory::Security,
+            message: format!("`{}` is not cryptographically secure but generates `{}`", found.call, found.target),
+            line: found.line,
+            column: found.column,
+            suggestion: Some(insecure_random::suggestion(grammar).to_string()),
+            action: None,
+            origin: None,
+        }));
+    }
+
+    // Races, stalls and serialized awaits
+    if let Some(grammar) = taint_grammar(language) {
+        issues.extend(concurrency_smells::find(root, content, grammar).into_iter()
+            .filter(|smell| rules.is_enabled(smell.rule_id))
+            .map(|smell| CodeIssue {
+                rule_id: smell.rule_id.to_string(),
+                severity: match smell.rule_id {
+                    rules::AWAIT_IN_LOOP => IssueSeverity::Warning,
+                    _ => IssueSeverity::Error,
+                },
+                category: IssueCategory::Concurrency,
+                message: smell.message,
+                line: smell.line,
+                column: smell.column,
+                suggestion: Some(concurrency_smells::suggestion(smell.rule_id, grammar).to_string()),
+                action: None,
+                origin: None,
+            }));
+    }
+
+    // Files, sockets and locks left open
+    if let Some(grammar) = taint_grammar(language).filter(|_| rules.is_enabled(rules::RESOURCE_LEAK)) {
+        issues.extend(resource_leaks::find(root, content, grammar).into_iter().map(|leak| CodeIssue {
+            rule_id: rules::RESOURCE_LEAK.to_string(),
+            severity: IssueSeverity::Warning,
+            category: IssueCategory::Maintainability,
+            message: leak.message(),
+            line: leak.line,
+            column: leak.column,
+            suggestion: Some(resource_leaks::suggestion(grammar).to_string()),
+            action: None,
+            origin: None,
+        }));
+    }
+
+    // Rust unsafe blocks that do not say why they are sound
+    if matches!(language, LangType::Rust) && rules.is_enabled(rules::UNJUSTIFIED_UNSAFE) {
+        issues.extend(unsafe_usage::blocks(root, content).into_iter().filter(|block| !block.justified).map(|block| CodeIssue {
+            rule_id: rules::UNJUSTIFIED_UNSAFE.to_string(),
+            severity: IssueSeverity::Warning,
+            category: IssueCategory::Security,
+            message: "unsafe block has no `// SAFETY:` comment".to_string(),
+            line: block.line,
+            column: block.column,
+            suggestion: Some("State the invariants the block relies on in a `// SAFETY:` comment directly above it".to_string()),
+            action: None,
+            origin: None,
+        }));
+    }
+
+    issues
+}
+
+/// One issue per `@security.risk` capture of each enabled pattern
+fn security_pattern_issues(root: Node, content: &str, rules: &RuleSet, patterns: &[SecurityQuery]) -> Vec<CodeIssue> {
+    let mut cursor = QueryCursor::new();
+    // Overlapping patterns within a row can capture the same node twice
+    let mut seen = HashSet::new();
+    let mut issues = Vec::new();
+
+    for SecurityQuery { pattern, query } in patterns.iter().filter(|security| rules.is_enabled(security.pattern.rule_id)) {
+        let Some(risk) = query.capture_index_for_name("security.risk") else {
+            continue;
+        };
+        for match_ in cursor.matches(query, root, content.as_bytes()) {
+            for node in match_.captures.iter().filter(|capture| capture.index == risk).map(|capture| capture.node) {
+                if !seen.insert((pattern.rule_id, node.start_byte())) {
+                    continue;
+                }
+                let position = node.start_position();
+                issues.push(CodeIssue {
+                    rule_id: pattern.rule_id.to_string(),
+                    severity: pattern.severity.clone(),
+                    category: IssueCategory::Security,
+                    message: format!("{}: `{}`", pattern.message, excerpt(&content[node.byte_range()])),
+                    line: position.row as u32 + 1,
+                    column: position.column as u32 + 1,
+                    suggestion: Some(pattern.suggestion.to_string()),
+                    action: None,
+                    origin: None,
+                });
+            }
+        }
+    }
+
+    issues.sort_by_key(|issue| (issue.line, issue.column));
+    issues
+}
+
+/// Compile the rows of the security pattern table for one built-in grammar
+pub(crate) fn security_queries(grammar: &'static str, language: &Language) -> Result<Vec<SecurityQuery>> {
+    security_patterns::for_grammar(grammar)
+        .map(|pattern| Ok(SecurityQuery { pattern, query: query_cache::compiled(grammar, language, pattern.query)? }))
+        .collect()
+}
+
+/// Grammar name of a built-in language, as `taint`, `insecure_random`, `concurrency_smells` and `resource_leaks` match on it
+fn taint_grammar(language: &LangType) -> Option<&'static str> {
+    match language {
+        LangType::JavaScript => Some("javascript"),
+        LangType::Python => Some("python"),
+        LangType::Ruby => Some("ruby"),
+        LangType::Rust => Some("rust"),
+        _ => None,
+    }
+}
+
+/// First line of a matched node, shortened to fit in an issue message
+pub(crate) fn excerpt(text: &str) -> String {
+    const MAX_CHARS: usize = 60;
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > MAX_CHARS || text.trim_end().contains('\n') {
+        format!("{}…", line.chars().take(MAX_CHARS).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+pub(crate) const FUNCTION_KINDS: [&str; 9] = [
+    "function_declaration",
+    "function",
+    "method_definition",
+    "arrow_function",
+    "function_item",
+    "function_definition",
+    // Go methods have a receiver
+    "method_declaration",
+    // Ruby `def name` and `def self.name`
+    "method",
+    "singleton_method",
+];
+
+/// The function node starting on 1-based `line`
+fn function_node_at<'tree>(root: Node<'tree>, line: u32) -> Option<Node<'tree>> {
+    let mut found = None;
+    walk_descendants(root, |node, _| {
+        if node.start_position().row + 1 == line as usize && FUNCTION_KINDS.contains(&node.kind()) {
+            found = Some(node);
+        }
+        found.is_none()
+    });
+    found
+}
+
+/// Turn a body that is a single `if` without `else` into a guard clause
+///
+/// The generated `return` has no value, so the action is a stub to review when
+/// the function returns something.
+fn early_return_action(function: Node, content: &str, language: &LangType) -> Option<CodeAction> {
+    let body = function.child_by_field_name("body")?;
+    let mut cursor = body.walk();
+    let mut statements = body.named_children(&mut cursor)
+        .filter(|node| !node.kind().contains("comment"));
+    let (statement, None) = (statements.next()?, statements.next()) else {
+        return None;
+    };
+
+    // Rust wraps the `if` in an expression statement unless it is the tail expression
+    let if_node = match statement.kind() {
+        "expression_statement" => statement.named_child(0)?,
+        _ => statement,
+    };
+    if !matches!(if_node.kind(), "if_statement" | "if_expression") || if_node.child_by_field_name("alternative").is_some() {
+        return None;
+    }
+
+    let condition = if_node.child_by_field_name("condition")?;
+    if condition.kind().contains("let") {
+        // `if let` bindings cannot simply be negated
+        return None;
+    }
+    let condition = &content[condition.byte_range()];
+    let consequence = if_node.child_by_field_name("consequence")?;
+    let inner: Vec<Node> = consequence.named_children(&mut consequence.walk()).collect();
+    let (first, last) = (inner.first()?, inner.last()?);
+
+    let line_start = |byte: usize| content[..byte].rfind('\n').map_or(0, |i| i + 1);
+    let indent_of = |byte: usize| {
+        let start = line_start(byte);
+        &content[start..start + content[start..].len() - content[start..].trim_start_matches([' ', '\t']).len()]
+    };
+    let outer_indent = indent_of(statement.start_byte());
+    let inner_indent = indent_of(first.start_byte());
+    let unit = inner_indent.strip_prefix(outer_indent).filter(|unit| !unit.is_empty())?;
+
+    let guard = match language {
+        LangType::Python => format!("if not ({}):\n{}{}return\n", condition.trim(), outer_indent, unit),
+        LangType::Rust => format!("if !({}) {{\n{}{}return;\n{}}}\n", condition.trim(), outer_indent, unit, outer_indent),
+        // JavaScript conditions keep their parentheses
+        _ => format!("if (!{}) {{\n{}{}return;\n{}}}\n", condition.trim(), outer_indent, unit, outer_indent),
+    };
+    let dedented: Vec<&str> = content[line_start(first.start_byte())..last.end_byte()]
+        .lines()
+        .map(|line| line.strip_prefix(unit).unwrap_or(line))
+        .collect();
+
+    let start = statement.start_position();
+    let end = statement.end_position();
+    Some(CodeAction {
+        title: "Replace the outer `if` with an early return".to_string(),
+        edits: vec![TextEdit {
+            start_line: start.row as u32 + 1,
+            start_column: start.column as u32 + 1,
+            end_line: end.row as u32 + 1,
+            end_column: end.column as u32 + 1,
+            new_text: format!("{}{}", guard, dedented.join("\n")),
+        }],
+    })
+}
+
+/// Name of a function node, `None` for anonymous functions
+pub(crate) fn function_name(node: Node, content: &str) -> Option<String> {
+    // Ruby blocks are named after the call they are passed to, e.g. `each block`
+    if matches!(node.kind(), "block" | "do_block") {
+        let call = node.parent().filter(|parent| parent.kind() == "call")?;
+        let method = call.child_by_field_name("method")?;
+        return Some(format!("{} block", &content[method.byte_range()]));
+    }
+
+    let mut cursor = node.walk();
+
+    // Look for function name in children
+    if cursor.goto_first_child() {
+        loop {
+            let child = cursor.node();
+            // Go method names are field identifiers
+            if matches!(child.kind(), "identifier" | "property_identifier" | "field_identifier") {
+                let name = &content[child.start_byte()..child.end_byte()];
+                return Some(name.to_string());
+            }
+            if !cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+
+    None
+}
+
+/// Parameters a function node declares
+pub(crate) fn parameter_count(node: Node) -> u32 {
+    let mut cursor = node.walk();
+    let mut param_count = 0;
+
+    if cursor.goto_first_child() {
+        loop {
+            let child = cursor.node();
+
+            // Look for parameter list nodes
+            if matches!(child.kind(), "parameters" | "formal_parameters" | "method_parameters" | "block_parameters") {
+                let mut param_cursor = child.walk();
+                if param_cursor.goto_first_child() {
+                    loop {
+                        let param_child = param_cursor.node();
+                        // Count parameter nodes (excluding commas and parentheses)
+                        if matches!(param_child.kind(), "identifier" | "parameter" | "typed_parameter" | "optional_parameter" | "keyword_parameter") {
+                            param_count += 1;
+                        }
+                        if !param_cursor.goto_next_sibling() {
+                            break;
+                        }
+                    }
+                }
+                break;
+            }
+
+            if !cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+
+    param_count
+}
+
+/// Deepest nesting of control-flow blocks under `node`, as `DEEP_NESTING` measures it
+pub(crate) fn nesting_depth(node: Node) -> u32 {
+    let mut max_depth = 0;
+    // Nesting depth of the most recently visited node at each tree depth
+    let mut nesting_at: Vec<u32> = Vec::new();
+
+    walk_descendants(node, |node, tree_depth| {
+        let parent_nesting = match tree_depth {
+            0 => 0,
+            _ => nesting_at[tree_depth - 1],
+        };
+        let mut nested_depth = parent_nesting;
+
+        // These node types increase nesting depth
+        match node.kind() {
+            "if_statement" | "if_expression" |
+            "while_statement" | "while_expression" |
+            "for_statement" | "for_expression" | "for_in_statement" |
+            "switch_statement" | "match_expression" |
+            "try_statement" | "catch_clause" |
+            "loop_expression" |
+            "block" | "compound_statement" => {
+                nested_depth += 1;
+                max_depth = max_depth.max(nested_depth);
+            }
+            _ => {}
+        }
+
+        nesting_at.truncate(tree_depth);
+        nesting_at.push(nested_depth);
+        true
+    });
+
+    max_depth
+}
+
+/// What this node adds to the complexity of its function
+///
+/// Error-handling constructs weigh what `weights` says; other decision points
+/// weigh one.
+pub(crate) fn decision_weight(node: Node, source: &str, weights: &ErrorHandlingWeights) -> u32 {
+    match weights.weight(node, source) {
+        Some(weight) => weight,
+        None => u32::from(is_decision_point(node)),
+    }
+}
+
+/// Whether this node adds a path through the code, error handling aside
+///
+/// Logical operators (`&&`, `||`) are not counted yet; `binary_expression`
+/// would need its operator inspected.
+pub(crate) fn is_decision_point(node: Node) -> bool {
+    // Ruby names its nodes after keywords, so only named nodes count, not the
+    // keyword tokens other grammars put inside `else_clause` or a ternary
+    if node.is_named() && matches!(node.kind(),
+        "if" | "unless" | "elsif" | "else" | "while" | "until" | "for" | "when" |
+        "conditional" | "if_modifier" | "unless_modifier" | "while_modifier" | "until_modifier")
+    {
+        return true;
+    }
+    matches!(node.kind(),
+        "if_statement" | "if_expression" |
+        "else_clause" |
+        "while_statement" | "while_expression" |
+        "for_statement" | "for_expression" | "for_in_statement" |
+        "switch_statement" | "match_expression" |
+        "case_clause" | "match_arm" |
+        "conditional_expression" | // Ternary operator
+        "loop_expression")
+}
+
+/// Pre-order walk over `root` and all of its descendants
+///
+/// Drives a single `TreeCursor` instead of recursing, so pathologically deep
+/// syntax trees (generated code, minified bundles) cannot overflow the stack.
+/// `visit` receives each node with its depth below `root` and returns `false`
+/// to stop the walk early.
+pub(crate) fn walk_descendants<'tree>(root: Node<'tree>, mut visit: impl FnMut(Node<'tree>, usize) -> bool) {
+    let mut cursor = root.walk();
+    let mut depth = 0;
+
+    loop {
+        if !visit(cursor.node(), depth) {
+            return;
+        }
+
+        if cursor.goto_first_child() {
+            depth += 1;
+            continue;
+        }
+
+        // Climb until a sibling is available or we are back at the root
+        loop {
+            if depth == 0 {
+                return;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            cursor.goto_parent();
+            depth -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ast_analyzer_creation() -> Result<()> {
+        let analyzer = ASTAnalyzer::new()?;
+        assert!(!analyzer.parsers.is_empty());
+        assert!(!analyzer.queries.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_javascript_analysis() -> Result<()> {
+        let mut analyzer = ASTAnalyzer::new()?;
+
+        let js_code = r#"
+            function calculateTotal(items, tax) {
+                let total = 0;
+                for (let item of items) {
+                    if (item.price > 0) {
+                        total += item.price;
+                    }
+                }
+                return total * (1 + tax);
+            }
+        "#;
+
+        let (metrics, _, _, _) = analyzer
+            .analyze_file(js_code, &LangType::JavaScript, Path::new("test.js"))?;
+
+        assert!(metrics.function_count > 0);
+        assert!(metrics.cyclomatic_complexity >= 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retained_parse_keeps_tree_and_source() -> Result<()> {
+        let mut analyzer = ASTAnalyzer::new()?;
+        let js_code = "function greet(name) {\n    return `hi ${name}`;\n}\n".to_string();
+
+        let parsed = analyzer.analyze_file_retained(js_code, &LangType::JavaScript, Path::new("greet.js"))?;
+
+        let function = parsed.root_node().named_child(0).unwrap();
+        assert_eq!(function.kind(), "function_declaration");
+        assert!(parsed.node_text(function).starts_with("function greet(name)"));
+        assert_eq!(parsed.functions.len(), 1);
+
+        let (source, tree) = parsed.into_source_and_tree();
+        assert_eq!(tree.root_node().end_byte(), source.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reanalyze_matches_a_fresh_parse() -> Result<()> {
+        let mut analyzer = ASTAnalyzer::new()?;
+        let before = "def total(items):\n    return sum(items)\n\ndef label(x):\n    return 'é'\n".to_string();
+        let after = "def total(items):\n    if not items:\n        return 0\n    return sum(items)\n\ndef label(x):\n    return 'é'\n".to_string();
+
+        let edit = input_edit(&before, &after).unwrap();
+        assert_eq!((edit.start_byte, edit.old_end_byte), (22, 22));
+        assert_eq!((edit.start_position, edit.new_end_position), (Point::new(1, 4), Point::new(3, 4)));
+        assert!(input_edit(&before, &before).is_none());
+
+        let retained = analyzer.analyze_file_retained(before, &LangType::Python, Path::new("cart.py"))?;
+        let updated = analyzer.reanalyze(retained, after.clone())?;
+        let fresh = analyzer.analyze_file_retained(after, &LangType::Python, Path::new("cart.py"))?;
+
+        assert_eq!(updated.root_node().to_sexp(), fresh.root_node().to_sexp());
+        let complexities = |parse: &RetainedParse| parse.functions.iter()
+            .map(|function| (function.name.clone(), function.start_line, function.cyclomatic_complexity))
+            .collect::<Vec<_>>();
+        assert_eq!(complexities(&updated), complexities(&fresh));
+        // `label` only moved down, so its analysis was carried forward
+        assert_eq!(updated.reused_functions, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reanalyze_renames_a_block_whose_call_changed() -> Result<()> {
+        let mut analyzer = ASTAnalyzer::new()?;
+        let before = "items.each do |item|\n  puts item\nend\n".to_string();
+        let after = before.replace("each", "map");
+
+        let retained = analyzer.analyze_file_retained(before, &LangType::Ruby, Path::new("list.rb"))?;
+        assert_eq!(retained.functions[0].name, "each block");
+        let updated = analyzer.reanalyze(retained, after)?;
+        assert_eq!(updated.functions[0].name, "map block");
+        assert_eq!(updated.reused_functions, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deep_nesting_offers_early_return_action() -> Result<()> {
+        let thresholds = Thresholds { max_nesting_depth: 1, ..Thresholds::default() };
+        let mut analyzer = ASTAnalyzer::new()?.with_thresholds(thresholds);
+        let js_code = "function process(items) {\n    if (items) {\n        for (const item of items) {\n            if (item) {\n                log(item);\n            }\n        }\n    }\n}\n";
+
+        let (_, issues, _, _) = analyzer.analyze_file(js_code, &LangType::JavaScript, Path::new("process.js"))?;
+        let action = issues.iter()
+            .find(|issue| issue.rule_id == rules::DEEP_NESTING)
+            .and_then(|issue| issue.action.as_ref())
+            .expect("deep nesting should carry an action");
+
+        assert_eq!(
+            action.apply(js_code)?,
+            "function process(items) {\n    if (!(items)) {\n        return;\n    }\n    for (const item of items) {\n        if (item) {\n            log(item);\n        }\n    }\n}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_security_patterns_report_under_their_own_rules() -> Result<()> {
+        let python_code = "import pickle\n\ndef load(cursor, blob, user_id):\n    cursor.execute(f\"SELECT * FROM users WHERE id = {user_id}\")\n    return pickle.loads(blob)\n";
+
+        let mut analyzer = ASTAnalyzer::new()?;
+        let (_, issues, _, _) = analyzer.analyze_file(python_code, &LangType::Python, Path::new("load.py"))?;
+        let found: Vec<_> = issues.iter()
+            .filter(|issue| issue.category == IssueCategory::Security)
+            .map(|issue| (issue.rule_id.as_str(), issue.line))
+            .collect();
+        assert_eq!(found, vec![(rules::SQL_STRING_INTERPOLATION, 4), (rules::UNSAFE_DESERIALIZATION, 5)]);
+
+        let without_sql = RuleSet::from_overrides(&[], &["sql-string-interpolation".to_string()])?;
+        let mut analyzer = ASTAnalyzer::new()?.with_rules(without_sql);
+        let (_, issues, _, _) = analyzer.analyze_file(python_code, &LangType::Python, Path::new("load.py"))?;
+        assert!(issues.iter().all(|issue| issue.rule_id != rules::SQL_STRING_INTERPOLATION));
+        assert!(issues.iter().any(|issue| issue.rule_id == rules::UNSAFE_DESERIALIZATION));
+        Ok(())
+    }
+
+    #[test]
+    fn test_very_deep_nesting_does_not_overflow() -> Result<()> {
+        let mut analyzer = ASTAnalyzer::new()?;
+        let depth = 10_000;
+
+        let js_code = format!(
+            "function deep(x) {{\n{}{}}}\n",
+            "if (x) {\n".repeat(depth),
+            "}\n".repeat(depth),
+        );
+
+        let (_, _, functions, _) = analyzer
+            .analyze_file(&js_code, &LangType::JavaScript, Path::new("deep.js"))?;
+
+        let deep = functions.iter().find(|f| f.name == "deep").unwrap();
+        assert_eq!(deep.nesting_depth, depth as u32);
+        assert_eq!(deep.cyclomatic_complexity, depth as u32 + 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_bindings_and_extraction_hint() {
+        let mut analyzer = ASTAnalyzer::new().unwrap();
+        let source = "import fs from \"fs-extra\";\nimport { a, b as c } from './util';\nimport './polyfill';\nfunction read() { return fs.readFileSync(c); }\n";
+        let (_, _, functions, imports_exports) = analyzer.analyze_file(source, &LangType::JavaScript, Path::new("read.js")).unwrap();
+        let mut names: Vec<String> = import_bindings(&imports_exports.imports).into_iter().collect();
+        names.sort();
+        assert_eq!(names, vec!["a", "c", "fs"]);
+        let read = functions.iter().find(|f| f.name == "read").unwrap();
+        assert_eq!((read.used_imports.clone(), read.available_imports), (vec!["c".to_string(), "fs".to_string()], Some(3)));
+
+        // Whatever `price` is, it may come from the glob
+        let (_, _, functions, _) = analyzer.analyze_file("use crate::model::*;\nfn total() -> u32 { price() }\n", &LangType::Rust, Path::new("total.rs")).unwrap();
+        assert_eq!(functions[0].available_imports, None);
+
+        let func = FunctionAnalysis {
+            name: "render".to_string(),
+            start_line: 1,
+            end_line: 80,
+            parameter_count: 1,
+            cyclomatic_complexity: 20,
+            nesting_depth: 2,
+            lines_of_code: 70,
+            is_async: false,
+            is_recursive: false,
+            calls: Vec::new(),
+            used_imports: vec!["fs".to_string()],
+            available_imports: Some(4),
+            reuse_key: 0,
+        };
+        assert!(with_extraction_hint("Split it", &func).contains("only needs 1 of the file's 4 imports"));
+
+        let coupled = FunctionAnalysis { used_imports: names, ..func };
+        assert_eq!(with_extraction_hint("Split it", &coupled), "Split it");
+    }
+    #[test]
+    fn test_ruby_methods_blocks_and_requires() -> Result<()> {
+        let mut analyzer = ASTAnalyzer::new()?;
+        let ruby_code = "require \"json\"\nrequire_relative \"pricing\"\n\nmodule Shop\n  class Cart\n    def total(items, discount)\n      items.each do |item|\n        next if item.nil?\n      end\n      items.empty? ? 0 : 1\n    end\n  end\nend\n";
+
+        let (_, _, functions, imports_exports) = analyzer.analyze_file(ruby_code, &LangType::Ruby, Path::new("lib/shop/cart.rb"))?;
+        let total = functions.iter().find(|f| f.name == "total").expect("method is detected");
+        assert_eq!((total.start_line, total.parameter_count, total.cyclomatic_complexity), (6, 2, 3));
+        let block = functions.iter().find(|f| f.name == "each block").expect("block is detected");
+        assert_eq!((block.parameter_count, block.cyclomatic_complexity), (1, 2));
+
+        let imports: Vec<&str> = imports_exports.imports.iter().map(|i| i.module_path.as_str()).collect();
+        assert_eq!(imports, vec!["json", "./pricing"]);
+        let exports: Vec<&str> = imports_exports.exports.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(exports, vec!["Shop", "Cart"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+//! Attribution of newly added complexity to workstreams
+//!
+//! Each function's complexity is split across the lines blamed to commits in
+//! the requested date range. By default the result is grouped by top-level
+//! directory; grouping by commit author is only done when explicitly opted in,
+//! and even then only aggregate shares are reported, never per-function blame.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::parsers::ParsedFile;
+use crate::vcs::Vcs;
+
+/// How attributed complexity is grouped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributionGrouping {
+    /// Top-level directory of the file (the default, privacy-preserving view)
+    Directory,
+    /// Commit author; requires explicit opt-in
+    Author,
+}
+
+/// Half-open date range `[since, until)` as unix timestamps
+#[derive(Debug, Clone, Copy)]
+pub struct DateRange {
+    pub since: i64,
+    pub until: i64,
+}
+
+impl DateRange {
+    /// Parse `YYYY-MM-DD` bounds; a missing `until` means "now"
+    pub fn parse(since: &str, until: Option<&str>) -> Result<Self> {
+        let since = parse_date(since)?;
+        let until = match until {
+            Some(date) => parse_date(date)?,
+            None => chrono::Utc::now().timestamp(),
+        };
+
+        if until <= since {
+            anyhow::bail!("--until must be after --since");
+        }
+
+        Ok(Self { since, until })
+    }
+
+    fn contains(&self, timestamp: i64) -> bool {
+        timestamp >= self.since && timestamp < self.until
+    }
+}
+
+fn parse_date(date: &str) -> Result<i64> {
+    let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD", date))?;
+    Ok(parsed.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp())
+}
+
+/// Aggregate share of newly added complexity for one group
+#[derive(Debug, Clone, Serialize)]
+pub struct AttributionEntry {
+    pub group: String,
+    pub added_complexity: f64,
+    pub share_percent: f64,
+    pub functions_touched: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttributionReport {
+    pub total_added_complexity: f64,
+    pub entries: Vec<AttributionEntry>,
+}
+
+/// Attribute complexity added within `range` across the parsed files
+pub fn attribute_complexity(
+    parsed_files: &[ParsedFile],
+    vcs: &dyn Vcs,
+    root: &Path,
+    range: DateRange,
+    grouping: AttributionGrouping,
+) -> AttributionReport {
+    let mut added: HashMap<String, f64> = HashMap::new();
+    let mut touched: HashMap<String, usize> = HashMap::new();
+
+    for parsed_file in parsed_files {
+        let Ok(blame) = vcs.blame(Path::new(&parsed_file.path)) else {
+            continue;
+        };
+
+        for function in &parsed_file.functions {
+            let start = function.line_start.saturating_sub(1) as usize;
+            let end = (function.line_end as usize).min(blame.len());
+            if start >= end {
+                continue;
+            }
+
+            let lines = &blame[start..end];
+            let mut lines_per_group: HashMap<String, usize> = HashMap::new();
+            for line in lines.iter().filter(|line| range.contains(line.author_time)) {
+                let group = match grouping {
+                    AttributionGrouping::Author => line.author.clone(),
+                    AttributionGrouping::Directory => top_level_directory(root, &parsed_file.path),
+                };
+                *lines_per_group.entry(group).or_insert(0) += 1;
+            }
+
+            for (group, count) in lines_per_group {
+                let share = function.complexity as f64 * count as f64 / lines.len() as f64;
+                *added.entry(group.clone()).or_insert(0.0) += share;
+                *touched.entry(group).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let total_added_complexity: f64 = added.values().sum();
+    let mut entries: Vec<AttributionEntry> = added
+        .into_iter()
+        .map(|(group, added_complexity)| AttributionEntry {
+            functions_touched: touched.get(&group).copied().unwrap_or(0),
+            share_percent: if total_added_complexity > 0.0 {
+                added_complexity / total_added_complexity * 100.0
+            } else {
+                0.0
+            },
+            group,
+            added_complexity,
+        })
+        .collect();
+
+    entries.sort_by(|a, b| b.added_complexity.total_cmp(&a.added_complexity));
+
+    AttributionReport { total_added_complexity, entries }
+}
+
+fn top_level_directory(root: &Path, file_path: &str) -> String {
+    let relative = Path::new(file_path).strip_prefix(root).unwrap_or(Path::new(file_path));
+    let mut components = relative.components();
+    match (components.next(), components.next()) {
+        (Some(first), Some(_)) => first.as_os_str().to_string_lossy().to_string(),
+        _ => "(root)".to_string(),
+    }
+}
+
+impl AttributionReport {
+    pub fn to_text(&self, grouping: AttributionGrouping) -> String {
+        let mut output = String::new();
+        let label = match grouping {
+            AttributionGrouping::Directory => "Workstream",
+            AttributionGrouping::Author => "Author",
+        };
+
+        output.push_str("\n📈 New Complexity Attribution\n");
+        output.push_str("==============================\n\n");
+        output.push_str(&format!("Total complexity added in range: {:.1}\n\n", self.total_added_complexity));
+        output.push_str(&format!("{:<30} {:>10} {:>8} {:>10}\n", label, "Added", "Share", "Functions"));
+
+        for entry in &self.entries {
+            output.push_str(&format!(
+                "{:<30} {:>10.1} {:>7.1}% {:>10}\n",
+                entry.group, entry.added_complexity, entry.share_percent, entry.functions_touched
+            ));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_range_parsing() {
+        let range = DateRange::parse("2024-01-01", Some("2024-02-01")).unwrap();
+        assert!(range.contains(range.since));
+        assert!(!range.contains(range.until));
+        assert!(DateRange::parse("2024-02-01", Some("2024-01-01")).is_err());
+        assert!(DateRange::parse("January", None).is_err());
+    }
+
+    #[test]
+    fn test_top_level_directory() {
+        let root = Path::new("project");
+        assert_eq!(top_level_directory(root, "project/billing/invoice.rs"), "billing");
+        assert_eq!(top_level_directory(root, "project/main.rs"), "(root)");
+    }
+}
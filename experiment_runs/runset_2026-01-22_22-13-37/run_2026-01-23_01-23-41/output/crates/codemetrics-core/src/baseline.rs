@@ -0,0 +1,216 @@
+//! Accepted-issue snapshots for adopting the tool in existing codebases
+//!
+//! `baseline generate` records a fingerprint for every current issue; later
+//! runs of `analyze` hide issues whose fingerprint is in the baseline, so only
+//! new problems are reported. Fingerprints combine the file path (relative to
+//! the analyzed root), the function name qualified by its enclosing impl,
+//! class or function, and the rule ID; security and other line findings have
+//! no function, so their message stands in for the name.
+//! Line numbers are left out so unrelated edits that move code do not
+//! resurface it.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::analyzers::{AnalysisResults, FileRecord, FunctionRecord};
+use crate::clusters;
+use crate::rules;
+use crate::thresholds::ThresholdLevel;
+
+/// Default location relative to the analyzed root, meant to be committed
+pub const BASELINE_FILE: &str = ".codemetrics/baseline.json";
+
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub generated_at: String,
+    pub fingerprints: BTreeSet<String>,
+}
+
+impl Baseline {
+    pub fn from_results(results: &AnalysisResults, root: &Path) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            fingerprints: issue_fingerprints(results, root).collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read baseline {}", path.display()))?;
+        let baseline: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid baseline {}", path.display()))?;
+        if baseline.version != FORMAT_VERSION {
+            anyhow::bail!(
+                "Baseline {} has format version {}, expected {}; regenerate it with `baseline generate`",
+                path.display(),
+                baseline.version,
+                FORMAT_VERSION
+            );
+        }
+        Ok(baseline)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write baseline {}", path.display()))
+    }
+
+    /// Drop every issue already in the baseline, returning how many were hidden
+    pub fn suppress(&self, results: &mut AnalysisResults, root: &Path) -> usize {
+        let before = results.issue_count();
+        results.high_complexity_functions.retain(|function| !self.accepts_function(root, &function.file_path, &function.scope, &function.name));
+        for function in &mut results.functions {
+            self.mark(function, root);
+        }
+        results.high_complexity_files.retain(|file| {
+            let key = fingerprint(root, &file.file_path, None, rules::HIGH_FILE_COMPLEXITY);
+            !self.fingerprints.contains(&key)
+        });
+        results.issues.retain(|found| {
+            let key = fingerprint(root, &found.file_path, Some(&finding_key(&found.issue.message)), &found.issue.rule_id);
+            !self.fingerprints.contains(&key)
+        });
+        let threshold = results.cluster_threshold;
+        clusters::apply(results, threshold);
+        before - results.issue_count()
+    }
+
+    /// Flag the functions of a streamed file record whose complexity issue is in the baseline
+    pub fn mark_record(&self, record: &mut FileRecord, root: &Path) {
+        for function in &mut record.functions {
+            self.mark(function, root);
+        }
+    }
+
+    fn mark(&self, function: &mut FunctionRecord, root: &Path) {
+        function.baselined = function.level != ThresholdLevel::Ok
+            && self.accepts_function(root, &function.file_path, &function.scope, &function.name);
+    }
+
+    fn accepts_function(&self, root: &Path, file_path: &str, scope: &str, name: &str) -> bool {
+        let key = fingerprint(root, file_path, Some(&qualified_name(scope, name)), rules::HIGH_CYCLOMATIC_COMPLEXITY);
+        self.fingerprints.contains(&key)
+    }
+}
+
+fn issue_fingerprints<'a>(results: &'a AnalysisResults, root: &'a Path) -> impl Iterator<Item = String> + 'a {
+    let functions = results.high_complexity_functions.iter()
+        .map(|function| fingerprint(root, &function.file_path, Some(&qualified_name(&function.scope, &function.name)), rules::HIGH_CYCLOMATIC_COMPLEXITY));
+    let files = results.high_complexity_files.iter()
+        .map(|file| fingerprint(root, &file.file_path, None, rules::HIGH_FILE_COMPLEXITY));
+    let issues = results.issues.iter()
+        .map(|found| fingerprint(root, &found.file_path, Some(&finding_key(&found.issue.message)), &found.issue.rule_id));
+    functions.chain(files).chain(issues)
+}
+
+/// `Cart::total` for a method, so same-named functions in one file get keys of their own
+///
+/// Top-level functions keep their bare name, and with it their keys in older baselines.
+fn qualified_name(scope: &str, name: &str) -> String {
+    match scope {
+        "" => name.to_string(),
+        scope => format!("{}::{}", scope, name),
+    }
+}
+
+/// A line finding's message without its digits, standing in for the function name
+///
+/// Messages quote the code they flag but also name lines, e.g. where tainted
+/// input came from; leaving digits out keeps the key when code moves.
+fn finding_key(message: &str) -> String {
+    message.chars().filter(|c| !c.is_ascii_digit()).collect()
+}
+
+/// Stable across platforms and toolchains, unlike `DefaultHasher`
+fn fingerprint(root: &Path, file_path: &str, function: Option<&str>, rule_id: &str) -> String {
+    let path = Path::new(file_path);
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let normalized = relative.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let key = format!("{}\0{}\0{}", normalized, function.unwrap_or(""), rule_id);
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::{AnalysisConfig, CodeAnalyzer};
+    use crate::gates::Gate;
+
+    #[test]
+    fn test_fingerprints_ignore_root_and_separators() {
+        let from_root = fingerprint(Path::new("proj"), "proj/src/lib.rs", Some("parse"), rules::HIGH_CYCLOMATIC_COMPLEXITY);
+        let from_inside = fingerprint(Path::new("."), "./src/lib.rs", Some("parse"), rules::HIGH_CYCLOMATIC_COMPLEXITY);
+        assert_eq!(from_root, from_inside);
+        assert_ne!(from_root, fingerprint(Path::new("."), "./src/lib.rs", Some("parse"), rules::HIGH_FILE_COMPLEXITY));
+        assert_ne!(from_root, fingerprint(Path::new("."), "./src/lib.rs", Some("render"), rules::HIGH_CYCLOMATIC_COMPLEXITY));
+        // Pinned so a toolchain upgrade can never silently invalidate committed baselines
+        assert_eq!(fingerprint(Path::new("."), "a.rs", None, "CM0102"), "b1e326a040f4b74c");
+    }
+
+    #[test]
+    fn test_findings_stay_accepted_when_code_moves() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = "app.get('/user', (req, res) => {\n  const id = req.query.id;\n  db.query(`SELECT * FROM users WHERE id = ${id}`);\n});\n";
+        std::fs::write(dir.path().join("routes.js"), source).unwrap();
+        let analyzer = CodeAnalyzer::with_config(AnalysisConfig::builder().quiet(true).build().unwrap());
+        let before = analyzer.analyze_path(dir.path()).unwrap();
+        assert!(Gate::parse("issues>0").unwrap().measure(&before).unwrap() > 0.0);
+        let baseline = Baseline::from_results(&before, dir.path());
+
+        std::fs::write(dir.path().join("routes.js"), format!("// routes\n\n{}", source)).unwrap();
+        let mut after = analyzer.analyze_path(dir.path()).unwrap();
+        assert_eq!(baseline.suppress(&mut after, dir.path()), before.issues.len());
+        assert!(after.issues.is_empty());
+    }
+
+    #[test]
+    fn test_same_named_methods_have_their_own_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let branches = |name: &str| {
+            let arms: String = (0..12).map(|arm| format!("            {} => {},\n", arm, arm)).collect();
+            format!("    fn {}(&self, n: u32) -> u32 {{\n        match n {{\n{}            _ => 0,\n        }}\n    }}\n", name, arms)
+        };
+        let source = format!("impl Cart {{\n{}}}\n\nimpl Order {{\n{}}}\n", branches("total"), branches("total"));
+        std::fs::write(dir.path().join("shop.rs"), &source).unwrap();
+        let analyzer = CodeAnalyzer::with_config(AnalysisConfig::builder().quiet(true).build().unwrap());
+        let before = analyzer.analyze_path(dir.path()).unwrap();
+        assert_eq!(before.high_complexity_functions.len(), 2);
+
+        // Only Cart's is accepted, so Order's stays reported
+        let mut baseline = Baseline::from_results(&before, dir.path());
+        let order = before.high_complexity_functions.iter().find(|function| function.scope == "Order").unwrap();
+        assert!(baseline.fingerprints.remove(&fingerprint(dir.path(), &order.file_path, Some("Order::total"), rules::HIGH_CYCLOMATIC_COMPLEXITY)));
+        let mut after = analyzer.analyze_path(dir.path()).unwrap();
+        baseline.suppress(&mut after, dir.path());
+        let remaining: Vec<_> = after.high_complexity_functions.iter()
+            .map(|function| qualified_name(&function.scope, &function.name))
+            .collect();
+        assert_eq!(remaining, vec!["Order::total"]);
+
+        // Streamed records are marked the same way before they are printed
+        let mut marked = Vec::new();
+        analyzer.analyze_streaming(dir.path(), |record| {
+            let mut record = record.clone();
+            baseline.mark_record(&mut record, dir.path());
+            marked.extend(record.functions.into_iter().map(|function| (function.scope, function.baselined)));
+            Ok(())
+        }).unwrap();
+        assert_eq!(marked, vec![("Cart".to_string(), true), ("Order".to_string(), false)]);
+    }
+}
@@ -0,0 +1,357 @@
+//! Bazel and Buck build targets as analysis units
+//!
+//! Monorepos built with Bazel or Buck already declare which files make up each
+//! target and which targets may depend on each other. Reading the `BUILD`,
+//! `BUILD.bazel` and `BUCK` files lets metrics be grouped per target and lets
+//! import-level dependencies be checked against the declared `deps`.
+//!
+//! Only the literal parts of rules are understood: `name`, string lists in
+//! `srcs` (including `glob([...])` patterns) and string lists in `deps`.
+//! Macros, `select()` and variables are ignored.
+
+use anyhow::{Context, Result};
+use ignore::overrides::{Override, OverrideBuilder};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::dependency_analyzer::DependencyAnalysisResult;
+
+const BUILD_FILES: [&str; 3] = ["BUILD.bazel", "BUILD", "BUCK"];
+
+/// A single rule from a build file
+#[derive(Debug, Clone)]
+pub struct BuildTarget {
+    /// Canonical label, e.g. `//services/billing:api`
+    pub label: String,
+    /// Package directory relative to the workspace root
+    pub package: PathBuf,
+    /// Source files listed explicitly, relative to the workspace root
+    pub srcs: BTreeSet<PathBuf>,
+    /// Compiled `glob()` patterns from `srcs`
+    globs: Option<Override>,
+    /// Canonical labels of declared dependencies
+    pub deps: BTreeSet<String>,
+}
+
+impl BuildTarget {
+    fn owns(&self, relative: &Path) -> bool {
+        if self.srcs.contains(relative) {
+            return true;
+        }
+        match (&self.globs, relative.strip_prefix(&self.package)) {
+            (Some(globs), Ok(within)) => globs.matched(within, false).is_whitelist(),
+            _ => false,
+        }
+    }
+}
+
+/// Every target declared under a workspace root
+#[derive(Debug, Clone)]
+pub struct BuildGraph {
+    root: PathBuf,
+    pub targets: Vec<BuildTarget>,
+    /// Package directory -> indices into `targets`
+    packages: HashMap<PathBuf, Vec<usize>>,
+}
+
+/// Imports from one target's files into another target's files
+#[derive(Debug, Serialize)]
+pub struct TargetDependency {
+    pub from: String,
+    pub to: String,
+    pub imports: u32,
+    /// Whether `to` appears in the `deps` of `from`
+    pub declared: bool,
+}
+
+impl BuildGraph {
+    /// Read every build file under `root`
+    pub fn load(root: &Path) -> Result<Self> {
+        let mut graph = Self {
+            root: root.to_path_buf(),
+            targets: Vec::new(),
+            packages: HashMap::new(),
+        };
+
+        for entry in ignore::Walk::new(root) {
+            let entry = entry.context("Failed to read directory entry")?;
+            let Some(priority) = entry.file_name().to_str()
+                .and_then(|name| BUILD_FILES.iter().position(|build_file| *build_file == name)) else {
+                continue;
+            };
+
+            // BUILD.bazel wins over BUILD when both exist, as in Bazel itself
+            let dir = entry.path().parent().unwrap_or(root);
+            if BUILD_FILES[..priority].iter().any(|preferred| dir.join(preferred).is_file()) {
+                continue;
+            }
+
+            let package = dir.strip_prefix(root).unwrap_or(Path::new("")).to_path_buf();
+
+            let content = std::fs::read_to_string(entry.path())
+                .with_context(|| format!("Failed to read {}", entry.path().display()))?;
+            graph.add_package(&package, &content)?;
+        }
+
+        if graph.targets.is_empty() {
+            anyhow::bail!("No BUILD, BUILD.bazel or BUCK files found under {}", root.display());
+        }
+
+        Ok(graph)
+    }
+
+    fn add_package(&mut self, package: &Path, content: &str) -> Result<()> {
+        let package_label = package.to_string_lossy().replace('\\', "/");
+        let mut indices = Vec::new();
+
+        for rule in parse_rules(content) {
+            let Some(name) = rule.name else {
+                continue;
+            };
+
+            let globs = if rule.glob_patterns.is_empty() {
+                None
+            } else {
+                let mut builder = OverrideBuilder::new("");
+                for pattern in &rule.glob_patterns {
+                    // Anchor to the package so `*.rs` does not match in subdirectories
+                    builder.add(&format!("/{}", pattern.trim_start_matches('/')))
+                        .with_context(|| format!("Invalid glob '{}' in //{}", pattern, package_label))?;
+                }
+                Some(builder.build()?)
+            };
+
+            indices.push(self.targets.len());
+            self.targets.push(BuildTarget {
+                label: format!("//{}:{}", package_label, name),
+                package: package.to_path_buf(),
+                srcs: rule.srcs.iter().map(|src| package.join(src)).collect(),
+                globs,
+                deps: rule.deps.iter().map(|dep| canonical_label(dep, &package_label)).collect(),
+            });
+        }
+
+        self.packages.insert(package.to_path_buf(), indices);
+        Ok(())
+    }
+
+    /// The target whose `srcs` include `file`, searched in the file's own package only
+    pub fn target_for(&self, file: &Path) -> Option<&BuildTarget> {
+        let relative = file.strip_prefix(&self.root).unwrap_or(file);
+        let package = relative.ancestors()
+            .skip(1)
+            .find(|dir| self.packages.contains_key(*dir))?;
+
+        self.packages[package].iter()
+            .map(|&index| &self.targets[index])
+            .find(|target| target.owns(relative))
+    }
+
+    /// Module-level imports rolled up to targets, flagging those missing from `deps`
+    pub fn target_dependencies(&self, analysis: &DependencyAnalysisResult) -> Vec<TargetDependency> {
+        let module_targets: HashMap<&str, &BuildTarget> = analysis.graph.nodes.iter()
+            .filter_map(|node| Some((node.id.as_str(), self.target_for(&node.file_path)?)))
+            .collect();
+
+        let mut imports: BTreeMap<(&str, &str), u32> = BTreeMap::new();
+        for edge in &analysis.graph.edges {
+            let (Some(from), Some(to)) = (module_targets.get(edge.from.as_str()), module_targets.get(edge.to.as_str())) else {
+                continue;
+            };
+            if from.label != to.label {
+                *imports.entry((from.label.as_str(), to.label.as_str())).or_insert(0) += 1;
+            }
+        }
+
+        let targets: HashMap<&str, &BuildTarget> = self.targets.iter()
+            .map(|target| (target.label.as_str(), target))
+            .collect();
+
+        imports.into_iter()
+            .map(|((from, to), count)| TargetDependency {
+                from: from.to_string(),
+                to: to.to_string(),
+                imports: count,
+                declared: targets.get(from).is_some_and(|target| target.deps.contains(to)),
+            })
+            .collect()
+    }
+}
+
+/// `:name` and `//pkg` shorthand expanded to `//pkg:name`
+fn canonical_label(label: &str, package_label: &str) -> String {
+    if let Some(name) = label.strip_prefix(':') {
+        return format!("//{}:{}", package_label, name);
+    }
+    if label.contains(':') {
+        return label.to_string();
+    }
+    match label.rsplit('/').next() {
+        Some(last) if !last.is_empty() => format!("{}:{}", label, last),
+        _ => label.to_string(),
+    }
+}
+
+/// The literal attributes of one rule call
+#[derive(Debug, Default)]
+struct RawRule {
+    name: Option<String>,
+    srcs: Vec<String>,
+    glob_patterns: Vec<String>,
+    deps: Vec<String>,
+}
+
+/// Extract top-level rule calls such as `rust_library(name = "x", srcs = [...])`
+fn parse_rules(content: &str) -> Vec<RawRule> {
+    let content = strip_comments(content);
+    let mut rules = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+    let mut in_string = false;
+
+    for (i, c) in content.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' if !in_string => {
+                if depth == 0 {
+                    start = Some(i + 1);
+                }
+                depth += 1;
+            }
+            ')' if !in_string => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    if let Some(begin) = start.take() {
+                        rules.push(parse_attributes(&content[begin..i]));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    rules
+}
+
+fn parse_attributes(body: &str) -> RawRule {
+    let mut rule = RawRule::default();
+
+    for (key, value) in split_top_level(body).into_iter().filter_map(|arg| arg.split_once('=')) {
+        let value = value.trim();
+        match key.trim() {
+            "name" => rule.name = string_literals(value).into_iter().next(),
+            "srcs" => {
+                // Only the first list of a glob() holds patterns; `exclude =` is ignored
+                if let Some(globbed) = value.strip_prefix("glob(") {
+                    let patterns = globbed.split(']').next().unwrap_or("");
+                    rule.glob_patterns.extend(string_literals(patterns));
+                } else {
+                    rule.srcs.extend(string_literals(value));
+                }
+            }
+            "deps" => rule.deps.extend(string_literals(value)),
+            _ => {}
+        }
+    }
+
+    rule
+}
+
+/// Split on commas that are not nested inside brackets, parentheses or strings
+fn split_top_level(body: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut start = 0;
+
+    for (i, c) in body.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '[' | '(' | '{' if !in_string => depth += 1,
+            ']' | ')' | '}' if !in_string => depth = depth.saturating_sub(1),
+            ',' if !in_string && depth == 0 => {
+                parts.push(&body[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&body[start..]);
+    parts
+}
+
+fn string_literals(value: &str) -> Vec<String> {
+    value.split('"').skip(1).step_by(2).map(str::to_string).collect()
+}
+
+fn strip_comments(content: &str) -> String {
+    content.lines()
+        .map(|line| {
+            let mut in_string = false;
+            for (i, c) in line.char_indices() {
+                match c {
+                    '"' => in_string = !in_string,
+                    '#' if !in_string => return &line[..i],
+                    _ => {}
+                }
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules_and_labels() {
+        let content = r#"
+            load("@rules_rust//rust:defs.bzl", "rust_library")
+
+            # The public API
+            rust_library(
+                name = "api",
+                srcs = ["lib.rs", "handlers.rs"],  # keep sorted
+                deps = [":model", "//common/log", "//third_party:serde"],
+            )
+
+            rust_library(
+                name = "model",
+                srcs = glob(["model/**/*.rs"], exclude = ["model/testdata/**"]),
+            )
+        "#;
+
+        let mut graph = BuildGraph { root: PathBuf::from("/repo"), targets: Vec::new(), packages: HashMap::new() };
+        graph.add_package(Path::new("services/billing"), content).unwrap();
+
+        assert_eq!(graph.targets.len(), 2);
+        let api = &graph.targets[0];
+        assert_eq!(api.label, "//services/billing:api");
+        assert!(api.srcs.contains(Path::new("services/billing/handlers.rs")));
+        let deps: Vec<&str> = api.deps.iter().map(String::as_str).collect();
+        assert_eq!(deps, vec!["//common/log:log", "//services/billing:model", "//third_party:serde"]);
+
+        let owner = |file: &str| graph.target_for(Path::new(file)).map(|target| target.label.as_str());
+        assert_eq!(owner("/repo/services/billing/lib.rs"), Some("//services/billing:api"));
+        assert_eq!(owner("/repo/services/billing/model/invoice/line.rs"), Some("//services/billing:model"));
+        assert_eq!(owner("/repo/services/billing/scratch.rs"), None);
+    }
+
+    #[test]
+    fn test_load_prefers_nearest_package() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("app/sub")).unwrap();
+        std::fs::write(root.join("app/BUILD"), "py_library(name = \"app\", srcs = glob([\"**/*.py\"]))").unwrap();
+        std::fs::write(root.join("app/sub/BUCK"), "python_library(name = \"sub\", srcs = [\"x.py\"])").unwrap();
+
+        let graph = BuildGraph::load(root).unwrap();
+        assert_eq!(graph.target_for(&root.join("app/main.py")).unwrap().label, "//app:app");
+        // Globs never reach into a subpackage
+        assert_eq!(graph.target_for(&root.join("app/sub/x.py")).unwrap().label, "//app/sub:sub");
+        assert!(graph.target_for(&root.join("app/sub/y.py")).is_none());
+    }
+}
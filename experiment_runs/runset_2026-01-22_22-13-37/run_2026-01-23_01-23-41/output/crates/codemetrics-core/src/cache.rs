@@ -0,0 +1,203 @@
+//! Persistent per-file analysis cache
+//!
+//! Re-parsing a large monorepo on every run is wasted work when most files
+//! have not changed. Each parsed file is stored under a key derived from its
+//! content, its extension (which picks the grammar) and the analyzer version,
+//! so an unchanged file skips tree-sitter entirely, and neither upgrading the
+//! tool nor changing an option that alters a parse, such as error-handling
+//! weights, reuses stale metrics. Entries are otherwise independent of the
+//! file's path: moving or copying a file still hits, so one cache in the
+//! user's cache directory serves every project.
+//!
+//! Entries made by another version or under other settings are never read
+//! again, so entries unused for `MAX_ENTRY_AGE` are deleted, at most once a
+//! day when a cache is opened.
+
+use anyhow::{Context, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+use crate::parsers::ParsedFile;
+
+/// Cache location relative to an output directory, e.g. `scan`'s
+pub const CACHE_DIR: &str = ".codemetrics/cache";
+
+/// Changes whenever parsing or metric extraction may produce different results
+const ANALYZER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Bumped whenever `ParsedFile` gains a field, since builds between releases share a version
+const ENTRY_FORMAT: u32 = 6;
+
+/// Entries not read or written for this long are deleted
+pub const MAX_ENTRY_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Touched whenever the cache is pruned
+const PRUNE_MARKER: &str = "last-pruned";
+
+/// `codemetrics` under the platform's per-user cache directory, if there is a home to put it in
+///
+/// `$XDG_CACHE_HOME` or `~/.cache` on Linux and other Unix systems,
+/// `~/Library/Caches` on macOS and `%LOCALAPPDATA%` on Windows.
+pub fn user_cache_dir() -> Option<PathBuf> {
+    let non_empty = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let base = if cfg!(windows) {
+        non_empty("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        non_empty("HOME").map(|home| home.join("Library/Caches"))
+    } else {
+        non_empty("XDG_CACHE_HOME").or_else(|| non_empty("HOME").map(|home| home.join(".cache")))
+    };
+    base.map(|base| base.join("codemetrics"))
+}
+
+pub struct AnalysisCache {
+    dir: PathBuf,
+    /// Options that change what a parse yields; entries made under others are misses
+    settings: String,
+    hits: AtomicUsize,
+}
+
+impl AnalysisCache {
+    pub fn open(dir: &Path, settings: &str) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+        if prune_due(dir) {
+            // A failed sweep only leaves stale entries for the next one
+            let _ = prune(dir, MAX_ENTRY_AGE);
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            settings: settings.to_string(),
+            hits: AtomicUsize::new(0),
+        })
+    }
+
+    /// The cached parse of `content`, reported under `path`
+    pub fn get(&self, path: &str, content: &str) -> Option<ParsedFile> {
+        let entry_path = self.entry_path(path, content);
+        let entry = std::fs::read_to_string(&entry_path).ok()?;
+        // A corrupt or outdated entry is simply a miss and gets overwritten
+        let mut parsed: ParsedFile = serde_json::from_str(&entry).ok()?;
+        parsed.path = path.to_string();
+        // Keep entries in use from being pruned; reading does not update the time everywhere
+        let _ = File::options().append(true).open(&entry_path).and_then(|file| file.set_modified(SystemTime::now()));
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(parsed)
+    }
+
+    pub fn put(&self, content: &str, parsed: &ParsedFile) -> Result<()> {
+        let entry_path = self.entry_path(&parsed.path, content);
+        if let Some(shard) = entry_path.parent() {
+            std::fs::create_dir_all(shard)?;
+        }
+
+        // Write then rename so a concurrent or interrupted run never reads a partial entry
+        let temporary = entry_path.with_extension(format!("tmp{}", std::process::id()));
+        std::fs::write(&temporary, serde_json::to_vec(parsed)?)?;
+        std::fs::rename(&temporary, &entry_path)?;
+        Ok(())
+    }
+
+    /// Files served from the cache since it was opened
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    fn entry_path(&self, path: &str, content: &str) -> PathBuf {
+        let extension = Path::new(path).extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let mut hasher = DefaultHasher::new();
+        ANALYZER_VERSION.hash(&mut hasher);
+        ENTRY_FORMAT.hash(&mut hasher);
+        self.settings.hash(&mut hasher);
+        extension.hash(&mut hasher);
+        content.hash(&mut hasher);
+        let key = format!("{:016x}{:08x}", hasher.finish(), content.len() as u32);
+        self.dir.join(&key[..2]).join(format!("{}.json", key))
+    }
+}
+
+/// Delete the entries under `dir` unused for longer than `max_age`; returns how many
+pub fn prune(dir: &Path, max_age: Duration) -> Result<usize> {
+    let mut removed = 0;
+    for shard in std::fs::read_dir(dir).with_context(|| format!("Failed to read cache directory {}", dir.display()))? {
+        let shard = shard?.path();
+        if !shard.is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(&shard)? {
+            let entry = entry?.path();
+            if age(&entry).is_some_and(|age| age > max_age) && std::fs::remove_file(&entry).is_ok() {
+                removed += 1;
+            }
+        }
+        // Only succeeds once the shard is empty
+        let _ = std::fs::remove_dir(&shard);
+    }
+    Ok(removed)
+}
+
+/// Whether a day has passed since the last sweep; claims the next one if so
+fn prune_due(dir: &Path) -> bool {
+    let marker = dir.join(PRUNE_MARKER);
+    let due = age(&marker).is_none_or(|age| age >= PRUNE_INTERVAL);
+    if due {
+        let _ = std::fs::write(&marker, "");
+    }
+    due
+}
+
+fn age(path: &Path) -> Option<Duration> {
+    std::fs::metadata(path).ok()?.modified().ok()?.elapsed().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::LanguageParser;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_cache_hits_on_identical_content_only() {
+        let temp_dir = tempdir().unwrap();
+        let cache = AnalysisCache::open(&temp_dir.path().join(CACHE_DIR), "").unwrap();
+        let content = "fn main() {\n    if true { println!(\"hi\"); }\n}\n";
+        let parsed = LanguageParser::new().parse_file("src/main.rs", content).unwrap();
+
+        assert!(cache.get("src/main.rs", content).is_none());
+        cache.put(content, &parsed).unwrap();
+
+        let cached = cache.get("src/moved.rs", content).unwrap();
+        assert_eq!(cached.path, "src/moved.rs");
+        assert_eq!(cached.functions.len(), parsed.functions.len());
+        assert!(cache.get("src/main.rs", "fn main() {}\n").is_none());
+        assert_eq!(cache.hits(), 1);
+        let reconfigured = AnalysisCache::open(&temp_dir.path().join(CACHE_DIR), "line_complexity").unwrap();
+        assert!(reconfigured.get("src/main.rs", content).is_none());
+        assert!(cache.get("scripts/main.py", content).is_none(), "another grammar parses the same text differently");
+    }
+
+    #[test]
+    fn test_prune_removes_entries_unused_for_too_long() {
+        let temp_dir = tempdir().unwrap();
+        let cache = AnalysisCache::open(temp_dir.path(), "").unwrap();
+        let parser = LanguageParser::new();
+        for content in ["fn old() {}\n", "fn fresh() {}\n"] {
+            cache.put(content, &parser.parse_file("lib.rs", content).unwrap()).unwrap();
+        }
+        let old = cache.entry_path("lib.rs", "fn old() {}\n");
+        let month_ago = SystemTime::now() - MAX_ENTRY_AGE - Duration::from_secs(60);
+        File::options().append(true).open(&old).unwrap().set_modified(month_ago).unwrap();
+
+        assert_eq!(prune(temp_dir.path(), MAX_ENTRY_AGE).unwrap(), 1);
+        assert!(!old.exists());
+        assert!(cache.get("lib.rs", "fn fresh() {}\n").is_some());
+    }
+}
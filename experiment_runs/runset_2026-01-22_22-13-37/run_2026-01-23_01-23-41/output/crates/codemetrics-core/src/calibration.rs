@@ -0,0 +1,188 @@
+//! Percentile standings against reference open-source corpora
+//!
+//! Raw metrics are hard to interpret on their own, so each language in the
+//! analysis is compared against the embedded distribution of similar-size
+//! reference projects. Only per-project deciles ship with the binary; the
+//! repositories and commits they were measured from are listed in the
+//! table's `sources`. `cargo xtask reference-corpora` writes the table from a
+//! manifest of repositories, using `project_metrics` and `deciles` below.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::analyzers::{AnalysisResults, LanguageStats};
+
+const REFERENCE_CORPORA: &str = include_str!("calibration/reference_corpora.json");
+
+#[derive(Debug, Deserialize)]
+struct ReferenceCorpora {
+    corpus_version: String,
+    distributions: Vec<ReferenceDistribution>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReferenceDistribution {
+    language: String,
+    size: String,
+    projects: u32,
+    /// Metric name -> deciles p0, p10, ..., p100
+    metrics: HashMap<String, Vec<f64>>,
+}
+
+/// Where one of the project's metrics falls within the reference distribution
+#[derive(Debug, Clone, Serialize)]
+pub struct Standing {
+    pub language: String,
+    pub metric: String,
+    pub value: f64,
+    pub percentile: f64,
+    pub size_bucket: String,
+    pub reference_projects: u32,
+    pub corpus_version: String,
+}
+
+impl Standing {
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {} is {:.2}, higher than {:.0}% of {} {} projects",
+            self.language,
+            self.metric.replace('_', " "),
+            self.value,
+            self.percentile,
+            self.size_bucket,
+            self.language,
+        )
+    }
+}
+
+/// Size bucket used to pick comparable reference projects
+pub fn size_bucket(total_lines: u32) -> &'static str {
+    match total_lines {
+        0..=9_999 => "small",
+        10_000..=99_999 => "medium",
+        _ => "large",
+    }
+}
+
+/// Compute percentile standings for every language with reference data
+pub fn calibrate(results: &AnalysisResults) -> Result<Vec<Standing>> {
+    let corpora: ReferenceCorpora = serde_json::from_str(REFERENCE_CORPORA)
+        .context("Embedded reference corpora are malformed")?;
+    let bucket = size_bucket(results.total_lines);
+
+    let mut standings = Vec::new();
+    let mut languages: Vec<_> = results.language_breakdown.iter().collect();
+    languages.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (language, stats) in languages {
+        if stats.functions == 0 {
+            continue;
+        }
+
+        let Some(reference) = corpora.distributions.iter()
+            .find(|d| &d.language == language && d.size == bucket) else {
+            continue;
+        };
+
+        for (metric, value) in observed(stats) {
+            if let Some(deciles) = reference.metrics.get(metric) {
+                standings.push(Standing {
+                    language: language.clone(),
+                    metric: metric.to_string(),
+                    value,
+                    percentile: percentile_from_deciles(deciles, value),
+                    size_bucket: bucket.to_string(),
+                    reference_projects: reference.projects,
+                    corpus_version: corpora.corpus_version.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(standings)
+}
+
+/// The metrics the tables hold for one language with functions
+fn observed(stats: &LanguageStats) -> [(&'static str, f64); 2] {
+    [
+        ("avg_function_complexity", stats.total_complexity as f64 / stats.functions as f64),
+        ("high_complexity_ratio", stats.high_complexity_functions as f64 / stats.functions as f64),
+    ]
+}
+
+/// One reference project's measurements: its size bucket, and each language's metrics
+pub fn project_metrics(results: &AnalysisResults) -> (&'static str, Vec<(String, &'static str, f64)>) {
+    let metrics = results.language_breakdown.iter()
+        .filter(|(_, stats)| stats.functions > 0)
+        .flat_map(|(language, stats)| observed(stats).map(|(metric, value)| (language.clone(), metric, value)))
+        .collect();
+    (size_bucket(results.total_lines), metrics)
+}
+
+/// Deciles p0, p10, ..., p100 of `values`, interpolated between neighbouring values
+pub fn deciles(values: &[f64]) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    if sorted.is_empty() {
+        return Vec::new();
+    }
+    (0..=10)
+        .map(|decile| {
+            let position = decile as f64 / 10.0 * (sorted.len() - 1) as f64;
+            let (low, high) = (sorted[position.floor() as usize], sorted[position.ceil() as usize]);
+            low + (high - low) * position.fract()
+        })
+        .collect()
+}
+
+/// Linear interpolation of `value` within deciles p0..p100
+fn percentile_from_deciles(deciles: &[f64], value: f64) -> f64 {
+    let Some((&first, &last)) = deciles.first().zip(deciles.last()) else {
+        return 0.0;
+    };
+    if value <= first {
+        return 0.0;
+    }
+    if value >= last {
+        return 100.0;
+    }
+
+    let step = 100.0 / (deciles.len() - 1) as f64;
+    for (i, window) in deciles.windows(2).enumerate() {
+        let (low, high) = (window[0], window[1]);
+        if value <= high {
+            let fraction = if high > low { (value - low) / (high - low) } else { 1.0 };
+            return (i as f64 + fraction) * step;
+        }
+    }
+
+    100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_corpora_parse() {
+        let corpora: ReferenceCorpora = serde_json::from_str(REFERENCE_CORPORA).unwrap();
+        assert!(!corpora.distributions.is_empty());
+        assert!(corpora.distributions.iter().all(|d| {
+            d.metrics.values().all(|deciles| deciles.len() == 11 && deciles.windows(2).all(|w| w[0] <= w[1]))
+        }));
+    }
+
+    #[test]
+    fn test_percentile_interpolation() {
+        let deciles: Vec<f64> = (0..=10).map(|i| i as f64).collect();
+        assert_eq!(percentile_from_deciles(&deciles, -1.0), 0.0);
+        assert_eq!(percentile_from_deciles(&deciles, 5.0), 50.0);
+        assert!((percentile_from_deciles(&deciles, 8.2) - 82.0).abs() < 1e-9);
+        assert_eq!(percentile_from_deciles(&deciles, 42.0), 100.0);
+
+        let measured: Vec<f64> = (0..=20).rev().map(|i| i as f64 / 2.0).collect();
+        assert_eq!(super::deciles(&measured), deciles);
+        assert_eq!(percentile_from_deciles(&super::deciles(&[1.0, 3.0]), 2.5), 75.0);
+    }
+}
@@ -0,0 +1,290 @@
+//! Which functions call which, across the whole project
+//!
+//! Each file records the names its functions call; once every file is parsed
+//! the calls are matched to functions by name, without knowing types:
+//!
+//! - `Order::save()`, `Self::save()`, `self.save()` and `this.save()` match
+//!   `save` methods of that type, and `orders::save()` a free `save` in a
+//!   file named `orders`
+//! - `save()` matches free functions named `save` and the caller's own
+//!   `save` method (an implicit `self` in Ruby and Java), only those in the
+//!   caller's file when it defines one
+//! - `Order.save()` and `orders.save()` match `save` methods of a type named
+//!   `Order` or `orders`, or a free `save` in a file named `Order` or
+//!   `orders`, as module calls in Python, JavaScript and Ruby are written.
+//!   A receiver that is a local value has a type only the compiler knows, so
+//!   `order.save()` usually resolves to nothing rather than to every `save`
+//!
+//! Calls into libraries match nothing. Fan-in is how many
+//! distinct functions call one, fan-out how many distinct project functions
+//! it calls; high fan-in marks code that many places depend on, high fan-out
+//! code that depends on many.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use tree_sitter::Node;
+
+use crate::ast_analyzer::{function_name, walk_descendants, FUNCTION_KINDS};
+
+/// Node kinds that call a function, across the supported grammars
+const CALL_KINDS: &[&str] = &["call_expression", "call", "method_invocation"];
+
+/// Node kinds whose functions are methods of a type
+const OWNER_KINDS: &[&str] = &["impl_item", "trait_item", "class_definition", "class_declaration", "class", "module"];
+
+/// Receivers that name the enclosing type
+const SELF_RECEIVERS: &[&str] = &["self", "Self", "this"];
+
+/// The names one function calls, nested functions' calls left to them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionCalls {
+    pub name: String,
+    /// Type or class the function is a method of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub line_start: u32,
+    /// Distinct callees: `Type::name` when called through a type, `receiver.name`
+    /// through a named receiver, and the bare name otherwise
+    pub calls: BTreeSet<String>,
+}
+
+/// A function's place in the project's call graph
+#[derive(Debug, Clone, Serialize)]
+pub struct CallGraphNode {
+    pub file_path: String,
+    pub name: String,
+    pub line_start: u32,
+    pub fan_in: usize,
+    pub fan_out: usize,
+}
+
+/// Every named function under `root` with the calls made directly in its body
+pub fn extract(root: Node, source: &str) -> Vec<FunctionCalls> {
+    let mut functions: Vec<FunctionCalls> = Vec::new();
+    // Tree depth and index in `functions` of each enclosing named function
+    let mut enclosing: Vec<(usize, usize)> = Vec::new();
+    walk_descendants(root, |node, depth| {
+        while enclosing.last().is_some_and(|(at, _)| *at >= depth) {
+            enclosing.pop();
+        }
+        if FUNCTION_KINDS.contains(&node.kind()) {
+            if let Some(name) = function_name(node, source) {
+                enclosing.push((depth, functions.len()));
+                let owner = owner(node, source);
+                functions.push(FunctionCalls { name, owner, line_start: node.start_position().row as u32 + 1, calls: BTreeSet::new() });
+            }
+        } else if let Some(((_, current), (qualifier, name))) = enclosing.last().zip(callee(node, source)) {
+            let function = &mut functions[*current];
+            let call = call_key(qualifier, name, function.owner.as_deref());
+            function.calls.insert(call);
+        }
+        true
+    });
+    functions
+}
+
+/// How `FunctionCalls::calls` records a call made from a method of `owner`
+fn call_key(qualifier: Option<Qualifier>, name: &str, owner: Option<&str>) -> String {
+    let qualifier = match qualifier {
+        Some(Qualifier::Type(receiver)) if SELF_RECEIVERS.contains(&receiver) => owner.map(Qualifier::Type),
+        qualifier => qualifier,
+    };
+    match qualifier {
+        Some(Qualifier::Type(owner)) => format!("{}::{}", owner, name),
+        Some(Qualifier::Receiver(receiver)) => format!("{}.{}", receiver, name),
+        None => name.to_string(),
+    }
+}
+
+/// What a call names before the function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Qualifier<'a> {
+    /// A type, or `self`/`this` until the caller's type replaces it
+    Type(&'a str),
+    /// A name that may be a type, a module or a local value
+    Receiver(&'a str),
+}
+
+/// The called name of a call node with what it is called through: `save()`,
+/// `Order::save()`, `self.save()` or `orders.save()`; `None` for other nodes and
+/// for calls through an expression such as `order.items.save()`, which cannot be resolved
+fn callee<'a>(node: Node, source: &'a str) -> Option<(Option<Qualifier<'a>>, &'a str)> {
+    if !CALL_KINDS.contains(&node.kind()) {
+        return None;
+    }
+    let target = ["function", "method", "name"].iter().find_map(|field| node.child_by_field_name(field))?;
+    let target = &source[target.byte_range()];
+    // `parse::<u32>` calls `parse`
+    let target = target.split_once("::<").map_or(target, |(target, _)| target);
+    let path = target.rsplit_once("::").filter(|(_, name)| !name.contains('.'));
+    let (qualifier, name) = match (path, target.rsplit_once('.')) {
+        (Some((path, name)), _) => (path.rsplit("::").next().map(Qualifier::Type), name),
+        (None, Some((receiver, name))) => (Some(receiver_qualifier(receiver)), name),
+        // Ruby's `order.save` and Java's `order.save()` keep the receiver in a field of its own
+        (None, None) => {
+            let receiver = node.child_by_field_name("receiver").or_else(|| node.child_by_field_name("object"));
+            (receiver.map(|receiver| receiver_qualifier(&source[receiver.byte_range()])), target)
+        }
+    };
+    let is_identifier = |text: &str| !text.is_empty() && text.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '?' || c == '!');
+    let resolvable = match qualifier {
+        Some(Qualifier::Type(qualifier) | Qualifier::Receiver(qualifier)) => is_identifier(qualifier),
+        None => true,
+    };
+    (is_identifier(name) && resolvable).then_some((qualifier, name))
+}
+
+fn receiver_qualifier(receiver: &str) -> Qualifier<'_> {
+    if SELF_RECEIVERS.contains(&receiver) { Qualifier::Type(receiver) } else { Qualifier::Receiver(receiver) }
+}
+
+/// The type or class `function` is a method of; generic parameters are dropped
+fn owner(function: Node, source: &str) -> Option<String> {
+    let container = function.parent()
+        .map(|parent| if parent.kind() == "decorated_definition" { parent.parent().unwrap_or(parent) } else { parent })?
+        .parent()
+        .filter(|container| OWNER_KINDS.contains(&container.kind()))?;
+    let name = container.child_by_field_name("type").or_else(|| container.child_by_field_name("name"))?;
+    let name = &source[name.byte_range()];
+    Some(name.split('<').next().unwrap_or(name).to_string())
+}
+
+/// Fan-in and fan-out of every function that calls or is called by another,
+/// most depended on first
+pub fn measure<'a>(files: impl IntoIterator<Item = (&'a str, &'a [FunctionCalls])>) -> Vec<CallGraphNode> {
+    let functions: Vec<(&str, &FunctionCalls)> = files.into_iter()
+        .flat_map(|(path, functions)| functions.iter().map(move |function| (path, function)))
+        .collect();
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, (_, function)) in functions.iter().enumerate() {
+        by_name.entry(function.name.as_str()).or_default().push(index);
+    }
+
+    let mut callers: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); functions.len()];
+    let mut fan_out = vec![0; functions.len()];
+    for (caller, (path, function)) in functions.iter().enumerate() {
+        let callees = function.calls.iter()
+            .flat_map(|call| resolve(call, (path, function), &by_name, &functions))
+            .filter(|callee| *callee != caller);
+        for callee in callees {
+            fan_out[caller] += 1;
+            callers[callee].insert(caller);
+        }
+    }
+
+    let mut nodes: Vec<CallGraphNode> = functions.iter().enumerate()
+        .map(|(index, (path, function))| CallGraphNode {
+            file_path: path.to_string(),
+            name: function.name.clone(),
+            line_start: function.line_start,
+            fan_in: callers[index].len(),
+            fan_out: fan_out[index],
+        })
+        .filter(|node| node.fan_in > 0 || node.fan_out > 0)
+        .collect();
+    nodes.sort_by(|a, b| b.fan_in.cmp(&a.fan_in)
+        .then(b.fan_out.cmp(&a.fan_out))
+        .then_with(|| a.file_path.cmp(&b.file_path))
+        .then(a.line_start.cmp(&b.line_start)));
+    nodes
+}
+
+/// The functions `call`, made by `caller`, may reach
+fn resolve(call: &str, caller: (&str, &FunctionCalls), by_name: &HashMap<&str, Vec<usize>>, functions: &[(&str, &FunctionCalls)]) -> Vec<usize> {
+    let (caller_path, caller) = caller;
+    let (qualifier, name) = match (call.split_once("::"), call.split_once('.')) {
+        (Some((owner, name)), _) => (Some(Qualifier::Type(owner)), name),
+        (None, Some((receiver, name))) => (Some(Qualifier::Receiver(receiver)), name),
+        (None, None) => (None, call),
+    };
+    let mut callees = by_name.get(name).into_iter().flatten().copied()
+        .filter(|callee| reaches(qualifier, functions[*callee], caller.owner.as_deref()))
+        .collect::<Vec<usize>>();
+    // A local definition shadows same-named ones elsewhere
+    if qualifier.is_none() && callees.iter().any(|callee| functions[*callee].0 == caller_path) {
+        callees.retain(|callee| functions[*callee].0 == caller_path);
+    }
+    callees
+}
+
+/// Whether a call through `qualifier`, made from a method of `caller_owner`, can reach `callee`
+fn reaches(qualifier: Option<Qualifier>, (path, callee): (&str, &FunctionCalls), caller_owner: Option<&str>) -> bool {
+    match (qualifier, callee.owner.as_deref()) {
+        (Some(Qualifier::Type(owner) | Qualifier::Receiver(owner)), Some(callee_owner)) => callee_owner == owner,
+        // A free function called through its module
+        (Some(Qualifier::Type(module) | Qualifier::Receiver(module)), None) => Path::new(path).file_stem().is_some_and(|stem| stem == module),
+        (None, callee_owner) => callee_owner.is_none() || callee_owner == caller_owner,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::parsers::{pool, SupportedLanguage};
+
+    #[test]
+    fn test_calls_resolve_by_name_and_type_across_files() -> Result<()> {
+        let rust = "\
+fn load(path: &str) -> Config {
+    let text = read(path);
+    let _version = text.parse::<u32>();
+    Config::parse(&text).validate()
+}
+
+impl Config {
+    fn parse(text: &str) -> Config {
+        fn inner() { helper(); }
+        Self::empty()
+    }
+
+    fn empty() -> Config { Config }
+
+    fn validate(self) -> Config { self }
+}
+
+impl<T> Manifest<T> {
+    fn parse(text: &str) -> Self { Manifest }
+}
+";
+        let python = "\
+def read(path):
+    return open(path).read()
+
+def main():
+    config = load('app.toml')
+    return read(util.slug(config))
+";
+        let util = "def slug(text):\n    return read(text).lower()\n\ndef read(text):\n    return text\n";
+        let parse = |language, source| -> Result<Vec<FunctionCalls>> { Ok(extract(pool::parse(language, source)?.root_node(), source)) };
+        let rust_calls = parse(SupportedLanguage::Rust, rust)?;
+        let python_calls = parse(SupportedLanguage::Python, python)?;
+        let util_calls = parse(SupportedLanguage::Python, util)?;
+        let load = &rust_calls[0];
+        // `.validate()` is called on an expression whose type is unknown
+        assert_eq!(load.calls.iter().map(String::as_str).collect::<Vec<_>>(), vec!["Config::parse", "read", "text.parse"]);
+        assert_eq!(rust_calls[1].owner.as_deref(), Some("Config"));
+        assert!(rust_calls[1].calls.contains("Config::empty") && !rust_calls[1].calls.contains("helper"));
+        assert_eq!(rust_calls.last().and_then(|parse| parse.owner.as_deref()), Some("Manifest"));
+
+        let nodes = measure([
+            ("src/config.rs", rust_calls.as_slice()),
+            ("app.py", python_calls.as_slice()),
+            ("util.py", util_calls.as_slice()),
+        ]);
+        let fan = |path: &str, name: &str| nodes.iter()
+            .find(|node| node.file_path == path && node.name == name)
+            .map(|node| (node.fan_in, node.fan_out));
+        assert_eq!(fan("app.py", "read"), Some((2, 0)), "called from load and main, not by `open(path).read()`");
+        assert_eq!(fan("util.py", "read"), Some((2, 0)), "main's call stays in app.py; load has no local `read`");
+        assert_eq!(fan("src/config.rs", "load"), Some((1, 3)));
+        assert_eq!(fan("app.py", "main"), Some((0, 3)));
+        assert_eq!(fan("util.py", "slug"), Some((1, 1)), "called through its module");
+        assert_eq!(fan("src/config.rs", "parse"), Some((1, 1)), "Manifest::parse is not called");
+        assert_eq!(nodes.iter().filter(|node| node.name == "parse").count(), 1);
+        assert_eq!(fan("src/config.rs", "inner"), None, "calls only helper, which is not a project function");
+        assert_eq!(fan("src/config.rs", "validate"), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! What an analysis run could and could not measure
+//!
+//! Optional inputs (git history, dependency manifests, coverage) are often
+//! missing. Rather than dropping the sections that depend on them, every
+//! report says which data was unavailable and why, and JSON consumers get the
+//! same information as a `capabilities` block.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub const COMPLEXITY: &str = "complexity";
+pub const GIT_HISTORY: &str = "git_history";
+pub const MANIFESTS: &str = "manifests";
+pub const COVERAGE: &str = "coverage";
+pub const CALIBRATION: &str = "calibration";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Capability {
+    pub measured: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct Capabilities(BTreeMap<&'static str, Capability>);
+
+impl Capabilities {
+    pub fn measured(&mut self, name: &'static str) {
+        self.0.insert(name, Capability { measured: true, reason: None });
+    }
+
+    pub fn unavailable(&mut self, name: &'static str, reason: impl Into<String>) {
+        self.0.insert(name, Capability { measured: false, reason: Some(reason.into()) });
+    }
+
+    /// Replace every reason with `placeholder`, keeping which inputs were measured
+    pub fn withhold_reasons(&mut self, placeholder: &str) {
+        for reason in self.0.values_mut().filter_map(|capability| capability.reason.as_mut()) {
+            *reason = placeholder.to_string();
+        }
+    }
+
+    /// Why `name` was not measured, if it was not
+    pub fn reason(&self, name: &str) -> Option<&str> {
+        self.0.get(name).and_then(|capability| capability.reason.as_deref())
+    }
+
+    /// Every unmeasured input with its reason, in name order
+    pub fn unavailable_entries(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.0.iter()
+            .filter(|(_, capability)| !capability.measured)
+            .map(|(name, capability)| (*name, capability.reason.as_deref().unwrap_or("unknown")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_serialize_as_block() {
+        let mut capabilities = Capabilities::default();
+        capabilities.measured(COMPLEXITY);
+        capabilities.unavailable(GIT_HISTORY, "not inside a git repository");
+
+        let json = serde_json::to_value(&capabilities).unwrap();
+        assert_eq!(json["complexity"]["measured"], true);
+        assert!(json["complexity"].get("reason").is_none());
+        assert_eq!(json["git_history"]["reason"], "not inside a git repository");
+        assert_eq!(capabilities.unavailable_entries().collect::<Vec<_>>(), vec![(GIT_HISTORY, "not inside a git repository")]);
+    }
+}
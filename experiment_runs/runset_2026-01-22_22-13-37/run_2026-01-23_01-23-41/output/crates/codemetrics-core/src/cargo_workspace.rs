@@ -0,0 +1,293 @@
+//! Rust crate boundaries and dependencies from `cargo metadata`
+//!
+//! Path heuristics cannot tell a workspace crate from a registry crate, or a
+//! renamed dependency from a local module. When a `Cargo.toml` is present and
+//! cargo is installed, the dependency analyzer uses this instead to resolve
+//! `use` paths and to report external crates under their real package names
+//! and versions.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Crates compiled into every Rust program; never reported as dependencies
+const STANDARD_CRATES: [&str; 5] = ["std", "core", "alloc", "proc_macro", "test"];
+
+/// A package that belongs to the workspace
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceCrate {
+    pub package: String,
+    /// Name used in `use` paths (the lib target name)
+    pub lib_name: String,
+    pub version: String,
+    /// Directory holding the crate root (`lib.rs` or `main.rs`)
+    pub src_root: PathBuf,
+    /// Declared features and what each enables
+    pub features: BTreeMap<String, Vec<String>>,
+    /// Features active in the resolved build, when the dependency graph was resolved
+    pub enabled_features: Vec<String>,
+}
+
+/// A dependency from outside the workspace
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalCrate {
+    pub package: String,
+    /// Exact resolved version, or the version requirement when resolution was unavailable
+    pub version: String,
+    pub optional: bool,
+    /// Features requested by the depending crates
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CargoWorkspace {
+    pub crates: Vec<WorkspaceCrate>,
+    /// Keyed by the name the dependency is imported under
+    pub dependencies: BTreeMap<String, ExternalCrate>,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+    workspace_members: Vec<String>,
+    resolve: Option<Resolve>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    id: String,
+    name: String,
+    version: String,
+    manifest_path: PathBuf,
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    dependencies: Vec<Dependency>,
+    #[serde(default)]
+    targets: Vec<Target>,
+}
+
+#[derive(Deserialize)]
+struct Dependency {
+    name: String,
+    req: String,
+    rename: Option<String>,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    features: Vec<String>,
+    /// Set for path dependencies, which may point into the workspace
+    path: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct Target {
+    name: String,
+    kind: Vec<String>,
+    src_path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Resolve {
+    nodes: Vec<ResolveNode>,
+}
+
+#[derive(Deserialize)]
+struct ResolveNode {
+    id: String,
+    #[serde(default)]
+    deps: Vec<ResolveDep>,
+    #[serde(default)]
+    features: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ResolveDep {
+    /// Extern crate name, already renamed and with dashes replaced
+    name: String,
+    pkg: String,
+}
+
+impl CargoWorkspace {
+    /// Run `cargo metadata` for the workspace at `root`
+    ///
+    /// Returns `None` when there is no `Cargo.toml` or cargo cannot be run. The
+    /// full dependency graph is resolved offline when possible so exact versions
+    /// are known; otherwise only the manifests are read.
+    pub fn load(root: &Path) -> Result<Option<Self>> {
+        let manifest = root.join("Cargo.toml");
+        if !manifest.is_file() {
+            return Ok(None);
+        }
+
+        let run = |extra: &[&str]| {
+            Command::new("cargo")
+                .args(["metadata", "--format-version", "1", "--offline"])
+                .args(extra)
+                .arg("--manifest-path").arg(&manifest)
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+        };
+
+        let Some(output) = run(&[]).or_else(|| run(&["--no-deps"])) else {
+            return Ok(None);
+        };
+
+        let json = String::from_utf8_lossy(&output.stdout);
+        Self::from_metadata_json(&json).map(Some)
+    }
+
+    pub fn from_metadata_json(json: &str) -> Result<Self> {
+        let metadata: Metadata = serde_json::from_str(json)
+            .context("Unexpected `cargo metadata` output")?;
+
+        let packages: HashMap<&str, &Package> = metadata.packages.iter()
+            .map(|package| (package.id.as_str(), package))
+            .collect();
+        let nodes: HashMap<&str, &ResolveNode> = metadata.resolve.iter()
+            .flat_map(|resolve| &resolve.nodes)
+            .map(|node| (node.id.as_str(), node))
+            .collect();
+
+        let members: Vec<&Package> = metadata.workspace_members.iter()
+            .filter_map(|id| packages.get(id.as_str()).copied())
+            .collect();
+        let member_dirs: Vec<PathBuf> = members.iter()
+            .filter_map(|package| package.manifest_path.parent().map(Path::to_path_buf))
+            .collect();
+
+        let mut crates = Vec::new();
+        let mut dependencies: BTreeMap<String, ExternalCrate> = BTreeMap::new();
+
+        for package in &members {
+            let node = nodes.get(package.id.as_str());
+            let lib = package.targets.iter()
+                .find(|target| target.kind.iter().any(|kind| kind.contains("lib") || kind == "proc-macro"))
+                .or_else(|| package.targets.first());
+
+            crates.push(WorkspaceCrate {
+                package: package.name.clone(),
+                lib_name: lib.map(|target| target.name.replace('-', "_"))
+                    .unwrap_or_else(|| package.name.replace('-', "_")),
+                version: package.version.clone(),
+                src_root: lib.and_then(|target| target.src_path.parent())
+                    .map(Path::to_path_buf)
+                    .unwrap_or_else(|| package.manifest_path.with_file_name("src")),
+                features: package.features.clone(),
+                enabled_features: node.map(|node| node.features.clone()).unwrap_or_default(),
+            });
+
+            for dependency in &package.dependencies {
+                let in_workspace = dependency.path.as_ref()
+                    .is_some_and(|path| member_dirs.iter().any(|dir| dir == path));
+                if in_workspace {
+                    continue;
+                }
+
+                let extern_name = dependency.rename.as_deref()
+                    .unwrap_or(&dependency.name)
+                    .replace('-', "_");
+                let resolved_version = node
+                    .and_then(|node| node.deps.iter().find(|dep| dep.name == extern_name))
+                    .and_then(|dep| packages.get(dep.pkg.as_str()))
+                    .map(|resolved| resolved.version.clone());
+
+                let entry = dependencies.entry(extern_name).or_insert_with(|| ExternalCrate {
+                    package: dependency.name.clone(),
+                    version: resolved_version.unwrap_or_else(|| dependency.req.clone()),
+                    optional: dependency.optional,
+                    features: Vec::new(),
+                });
+                entry.optional &= dependency.optional;
+                for feature in &dependency.features {
+                    if !entry.features.contains(feature) {
+                        entry.features.push(feature.clone());
+                    }
+                }
+            }
+        }
+
+        crates.sort_by(|a, b| a.package.cmp(&b.package));
+        Ok(Self { crates, dependencies })
+    }
+
+    /// Workspace crate imported as `name` in a `use` path
+    pub fn workspace_crate(&self, name: &str) -> Option<&WorkspaceCrate> {
+        self.crates.iter().find(|krate| krate.lib_name == name)
+    }
+
+    /// External crate imported as `name` in a `use` path
+    pub fn dependency(&self, name: &str) -> Option<&ExternalCrate> {
+        self.dependencies.get(name)
+    }
+
+    pub fn is_standard_crate(name: &str) -> bool {
+        STANDARD_CRATES.contains(&name)
+    }
+
+    /// The workspace crate whose source tree contains `file`
+    pub fn crate_for_file(&self, file: &Path) -> Option<&WorkspaceCrate> {
+        let file = std::fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        self.crates.iter()
+            .filter(|krate| file.starts_with(&krate.src_root))
+            .max_by_key(|krate| krate.src_root.components().count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_resolves_versions_renames_and_workspace_crates() {
+        let json = r#"{
+            "packages": [
+                {
+                    "id": "app 0.1.0 (path+file:///ws/app)", "name": "app", "version": "0.1.0",
+                    "manifest_path": "/ws/app/Cargo.toml",
+                    "features": {"default": ["fast"], "fast": []},
+                    "dependencies": [
+                        {"name": "serde", "req": "^1.0", "rename": null, "optional": false, "features": ["derive"]},
+                        {"name": "tokio-util", "req": "^0.7", "rename": "tu", "optional": true, "features": []},
+                        {"name": "model-core", "req": "*", "rename": null, "optional": false, "features": [], "path": "/ws/model"}
+                    ],
+                    "targets": [{"name": "app", "kind": ["bin"], "src_path": "/ws/app/src/main.rs"}]
+                },
+                {
+                    "id": "model-core 0.2.0 (path+file:///ws/model)", "name": "model-core", "version": "0.2.0",
+                    "manifest_path": "/ws/model/Cargo.toml",
+                    "targets": [{"name": "model-core", "kind": ["lib"], "src_path": "/ws/model/src/lib.rs"}]
+                },
+                {
+                    "id": "serde 1.0.193 (registry+https://github.com/rust-lang/crates.io-index)", "name": "serde", "version": "1.0.193",
+                    "manifest_path": "/registry/serde/Cargo.toml"
+                }
+            ],
+            "workspace_members": ["app 0.1.0 (path+file:///ws/app)", "model-core 0.2.0 (path+file:///ws/model)"],
+            "resolve": {"nodes": [
+                {"id": "app 0.1.0 (path+file:///ws/app)", "features": ["default", "fast"],
+                 "deps": [{"name": "serde", "pkg": "serde 1.0.193 (registry+https://github.com/rust-lang/crates.io-index)"}]}
+            ]}
+        }"#;
+
+        let workspace = CargoWorkspace::from_metadata_json(json).unwrap();
+
+        assert_eq!(workspace.crates.len(), 2);
+        assert_eq!(workspace.workspace_crate("model_core").unwrap().src_root, PathBuf::from("/ws/model/src"));
+        assert_eq!(workspace.crates[0].enabled_features, vec!["default", "fast"]);
+
+        let serde = workspace.dependency("serde").unwrap();
+        assert_eq!(serde.version, "1.0.193");
+        assert_eq!(serde.features, vec!["derive"]);
+
+        // Renamed, unresolved: falls back to the requirement
+        let renamed = workspace.dependency("tu").unwrap();
+        assert_eq!((renamed.package.as_str(), renamed.version.as_str(), renamed.optional), ("tokio-util", "^0.7", true));
+
+        assert!(workspace.dependency("model_core").is_none());
+        assert!(CargoWorkspace::is_standard_crate("alloc"));
+    }
+}
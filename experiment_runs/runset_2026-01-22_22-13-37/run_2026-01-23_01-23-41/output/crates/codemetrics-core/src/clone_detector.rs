@@ -0,0 +1,467 @@
+//! Copy-paste detection over normalized syntax trees
+//!
+//! Every named syntax node large enough to matter gets two fingerprints:
+//!
+//! - **token**: the leaf tokens in order, with identifiers and literals
+//!   replaced by placeholders, so renamed copies still match
+//! - **structural**: the shape of named nodes only, so copies whose operators
+//!   or keywords were also edited still match
+//!
+//! Nodes sharing a fingerprint form a clone class. Token classes are reported
+//! before structural ones, and larger classes before smaller, so the
+//! sub-blocks of an already reported clone are not reported again.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use tree_sitter::Node;
+
+use crate::parsers::{pool, SupportedLanguage};
+
+/// Shorter blocks are too common to be worth reporting
+pub const DEFAULT_MIN_LINES: u32 = 6;
+pub const DEFAULT_MIN_TOKENS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloneKind {
+    /// Same tokens up to renamed identifiers and changed literals
+    Token,
+    /// Same syntax tree shape with different tokens
+    Structural,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CloneLocation {
+    pub file_path: String,
+    pub line_start: u32,
+    pub line_end: u32,
+}
+
+/// One block of code and every place it was repeated
+#[derive(Debug, Clone, Serialize)]
+pub struct CloneClass {
+    pub kind: CloneKind,
+    pub tokens: usize,
+    pub locations: Vec<CloneLocation>,
+}
+
+/// How much of one file lies inside reported clones
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileDuplication {
+    pub file_path: String,
+    pub duplicated_lines: usize,
+    pub total_lines: usize,
+    pub duplication_percent: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CloneReport {
+    pub classes: Vec<CloneClass>,
+    /// Files with any duplicated lines, most duplicated first
+    pub files: Vec<FileDuplication>,
+    pub duplicated_lines: usize,
+    pub total_lines: usize,
+    pub duplication_percent: f64,
+}
+
+/// One file's fingerprints, enough to find its clones without its source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileFingerprints {
+    pub file_path: String,
+    pub total_lines: usize,
+    pub blocks: Vec<Fingerprint>,
+}
+
+/// A block large enough to report, under one of its two fingerprints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub kind: CloneKind,
+    pub hash: u64,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub tokens: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fragment {
+    file: usize,
+    line_start: u32,
+    line_end: u32,
+    tokens: usize,
+}
+
+/// The outermost reported fragments of each file, sorted by first line
+///
+/// None of them contains another, so their last lines are sorted too, and
+/// the only candidate to contain a span is the last one starting before it.
+#[derive(Default)]
+struct Outermost(HashMap<usize, Vec<(u32, u32)>>);
+
+impl Outermost {
+    fn contains(&self, fragment: &Fragment) -> bool {
+        let Some(spans) = self.0.get(&fragment.file) else {
+            return false;
+        };
+        let before = spans.partition_point(|&(start, _)| start <= fragment.line_start);
+        before > 0 && spans[before - 1].1 >= fragment.line_end
+    }
+
+    /// Add a fragment, dropping the spans it contains
+    fn insert(&mut self, fragment: &Fragment) {
+        if self.contains(fragment) {
+            return;
+        }
+        let spans = self.0.entry(fragment.file).or_default();
+        let from = spans.partition_point(|&(start, _)| start < fragment.line_start);
+        let to = from + spans[from..].partition_point(|&(_, end)| end <= fragment.line_end);
+        spans.splice(from..to, [(fragment.line_start, fragment.line_end)]);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Shape {
+    token_hash: u64,
+    structure_hash: u64,
+    tokens: usize,
+}
+
+pub struct CloneDetector {
+    min_lines: u32,
+    min_tokens: usize,
+    files: Vec<(String, usize)>,
+    fragments: HashMap<(CloneKind, u64), Vec<Fragment>>,
+}
+
+impl Default for CloneDetector {
+    fn default() -> Self {
+        Self::new(DEFAULT_MIN_LINES, DEFAULT_MIN_TOKENS)
+    }
+}
+
+impl CloneDetector {
+    pub fn new(min_lines: u32, min_tokens: usize) -> Self {
+        Self { min_lines, min_tokens, files: Vec::new(), fragments: HashMap::new() }
+    }
+
+    /// Parse and fingerprint a file; languages without a grammar are ignored
+    pub fn add_file(&mut self, path: &str, source: &str) -> Result<()> {
+        if let Some(fingerprints) = self.fingerprint_file(path, source)? {
+            self.add_fingerprints(&fingerprints);
+        }
+        Ok(())
+    }
+
+    /// Parse and fingerprint a file without adding it; `None` for languages without a grammar
+    pub fn fingerprint_file(&self, path: &str, source: &str) -> Result<Option<FileFingerprints>> {
+        let Some(language) = Path::new(path).extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(SupportedLanguage::from_extension)
+        else {
+            return Ok(None);
+        };
+
+        let tree = pool::parse(language, source)
+            .with_context(|| format!("Failed to parse {}", path))?;
+        Ok(Some(self.fingerprint(path, tree.root_node(), source)))
+    }
+
+    /// Fingerprint an already parsed file
+    pub fn add_tree(&mut self, path: &str, root: Node, source: &str) {
+        let fingerprints = self.fingerprint(path, root, source);
+        self.add_fingerprints(&fingerprints);
+    }
+
+    /// Fingerprint an already parsed file
+    pub fn fingerprint(&self, path: &str, root: Node, source: &str) -> FileFingerprints {
+        FileFingerprints { file_path: path.to_string(), total_lines: source.lines().count(), blocks: self.blocks(root) }
+    }
+
+    /// The blocks under `root` large enough to report, under both fingerprints
+    pub fn blocks(&self, root: Node) -> Vec<Fingerprint> {
+        let mut blocks = Vec::new();
+        // The root is the whole file rather than a block within it
+        for (node, shape) in shapes(root).into_iter().skip(1) {
+            let (start, end) = (node.start_position(), node.end_position());
+            let line_start = start.row as u32 + 1;
+            // A node ending at column 0 stops before that line
+            let end_row = if end.column == 0 && end.row > start.row { end.row - 1 } else { end.row };
+            let line_end = end_row as u32 + 1;
+            if !node.is_named() || shape.tokens < self.min_tokens || line_end + 1 - line_start < self.min_lines {
+                continue;
+            }
+
+            let block = |kind, hash| Fingerprint { kind, hash, line_start, line_end, tokens: shape.tokens };
+            blocks.push(block(CloneKind::Token, shape.token_hash));
+            blocks.push(block(CloneKind::Structural, shape.structure_hash));
+        }
+        blocks
+    }
+
+    /// Add a file fingerprinted by `fingerprint`, possibly in another process
+    pub fn add_fingerprints(&mut self, fingerprints: &FileFingerprints) {
+        let file = self.files.len();
+        self.files.push((fingerprints.file_path.clone(), fingerprints.total_lines));
+        for block in &fingerprints.blocks {
+            let fragment = Fragment { file, line_start: block.line_start, line_end: block.line_end, tokens: block.tokens };
+            self.fragments.entry((block.kind, block.hash)).or_default().push(fragment);
+        }
+    }
+
+    pub fn finish(self) -> CloneReport {
+        let mut groups: Vec<((CloneKind, u64), Vec<Fragment>)> = self.fragments.into_iter()
+            .filter(|(_, fragments)| fragments.len() > 1)
+            .collect();
+        groups.sort_by(|(a_key, a), (b_key, b)| {
+            a_key.0.cmp(&b_key.0)
+                .then_with(|| b[0].tokens.cmp(&a[0].tokens))
+                .then_with(|| (a[0].file, a[0].line_start).cmp(&(b[0].file, b[0].line_start)))
+                // A wrapper node before the node it wraps, so the reported span is the wider one
+                .then_with(|| b[0].line_end.cmp(&a[0].line_end))
+        });
+
+        let mut reported: Vec<Fragment> = Vec::new();
+        let mut outermost = Outermost::default();
+        let mut classes = Vec::new();
+        for ((kind, _), fragments) in groups {
+            let fresh: Vec<Fragment> = fragments.into_iter()
+                .filter(|fragment| !outermost.contains(fragment))
+                .collect();
+            if fresh.len() < 2 {
+                continue;
+            }
+            fresh.iter().for_each(|fragment| outermost.insert(fragment));
+
+            classes.push(CloneClass {
+                kind,
+                tokens: fresh[0].tokens,
+                locations: fresh.iter()
+                    .map(|fragment| CloneLocation {
+                        file_path: self.files[fragment.file].0.clone(),
+                        line_start: fragment.line_start,
+                        line_end: fragment.line_end,
+                    })
+                    .collect(),
+            });
+            reported.extend(fresh);
+        }
+
+        let mut files: Vec<FileDuplication> = self.files.iter()
+            .enumerate()
+            .filter_map(|(index, (file_path, total_lines))| {
+                let in_file: Vec<Fragment> = reported.iter().filter(|fragment| fragment.file == index).copied().collect();
+                let duplicated_lines = covered_lines(&in_file);
+                (duplicated_lines > 0).then(|| FileDuplication {
+                    file_path: file_path.clone(),
+                    duplicated_lines,
+                    total_lines: *total_lines,
+                    duplication_percent: percent(duplicated_lines, *total_lines),
+                })
+            })
+            .collect();
+        files.sort_by(|a, b| {
+            b.duplication_percent.total_cmp(&a.duplication_percent).then_with(|| a.file_path.cmp(&b.file_path))
+        });
+
+        let duplicated_lines = covered_lines(&reported);
+        let total_lines: usize = self.files.iter().map(|(_, lines)| lines).sum();
+        CloneReport {
+            classes,
+            files,
+            duplicated_lines,
+            total_lines,
+            duplication_percent: percent(duplicated_lines, total_lines),
+        }
+    }
+}
+
+/// Fingerprint every node under `root`, folding child hashes bottom-up
+///
+/// Nodes are listed in pre-order with a single cursor and then folded in
+/// reverse, so deeply nested generated code cannot overflow the stack.
+fn shapes(root: Node) -> Vec<(Node, Shape)> {
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut children: Vec<Vec<usize>> = Vec::new();
+    let mut parents: Vec<usize> = Vec::new();
+    let mut cursor = root.walk();
+
+    'walk: loop {
+        let index = nodes.len();
+        nodes.push(cursor.node());
+        children.push(Vec::new());
+        if let Some(&parent) = parents.last() {
+            children[parent].push(index);
+        }
+
+        if cursor.goto_first_child() {
+            parents.push(index);
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'walk;
+            }
+            parents.pop();
+        }
+    }
+
+    let mut shapes = vec![Shape::default(); nodes.len()];
+    for index in (0..nodes.len()).rev() {
+        let node = nodes[index];
+        let mut token_hasher = DefaultHasher::new();
+        let mut structure_hasher = DefaultHasher::new();
+        node.kind().hash(&mut structure_hasher);
+
+        let shape = if children[index].is_empty() {
+            match normalized_token(node) {
+                Some(token) => {
+                    token.hash(&mut token_hasher);
+                    Shape { token_hash: token_hasher.finish(), structure_hash: structure_hasher.finish(), tokens: 1 }
+                }
+                None => Shape::default(),
+            }
+        } else {
+            let mut tokens = 0;
+            for &child in &children[index] {
+                let child_shape = shapes[child];
+                if child_shape.tokens > 0 {
+                    child_shape.token_hash.hash(&mut token_hasher);
+                    tokens += child_shape.tokens;
+                }
+                if nodes[child].is_named() {
+                    child_shape.structure_hash.hash(&mut structure_hasher);
+                }
+            }
+            Shape { token_hash: token_hasher.finish(), structure_hash: structure_hasher.finish(), tokens }
+        };
+        shapes[index] = shape;
+    }
+
+    nodes.into_iter().zip(shapes).collect()
+}
+
+/// Leaf token with names and literal values abstracted away; comments are dropped
+fn normalized_token(node: Node) -> Option<&'static str> {
+    let kind = node.kind();
+    if kind.contains("comment") {
+        None
+    } else if kind.contains("identifier") {
+        Some("$id")
+    } else if ["string", "number", "integer", "float", "char"].iter().any(|literal| kind.contains(literal)) {
+        Some("$lit")
+    } else {
+        // Anonymous tokens (`if`, `+`, `{`) are named after their own text
+        Some(kind)
+    }
+}
+
+fn percent(part: usize, whole: usize) -> f64 {
+    if whole > 0 {
+        part as f64 / whole as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Lines inside at least one fragment, counting overlaps once
+fn covered_lines(fragments: &[Fragment]) -> usize {
+    let mut ranges: Vec<(usize, u32, u32)> = fragments.iter()
+        .map(|fragment| (fragment.file, fragment.line_start, fragment.line_end))
+        .collect();
+    ranges.sort_unstable();
+
+    let mut covered = 0;
+    let mut current: Option<(usize, u32, u32)> = None;
+    for (file, start, end) in ranges {
+        match current {
+            Some((open_file, open_start, open_end)) if open_file == file && start <= open_end + 1 => {
+                current = Some((open_file, open_start, open_end.max(end)));
+            }
+            _ => {
+                if let Some((_, open_start, open_end)) = current {
+                    covered += (open_end - open_start + 1) as usize;
+                }
+                current = Some((file, start, end));
+            }
+        }
+    }
+    if let Some((_, open_start, open_end)) = current {
+        covered += (open_end - open_start + 1) as usize;
+    }
+    covered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL: &str = r#"
+fn total_price(items: &[Item], discount: f64) -> f64 {
+    let mut total = 0.0;
+    for item in items {
+        if item.quantity > 0 {
+            total += item.price * item.quantity as f64;
+        }
+    }
+    if total > 100.0 {
+        total = total * (1.0 - discount);
+    }
+    total
+}
+"#;
+
+    #[test]
+    fn test_renamed_copy_forms_token_clone_class() {
+        let renamed = ORIGINAL
+            .replace("total_price", "order_sum")
+            .replace("total", "sum")
+            .replace("100.0", "250.0");
+        let unrelated = "fn main() {\n    println!(\"hello\");\n}\n";
+
+        let mut detector = CloneDetector::default();
+        detector.add_file("src/cart.rs", ORIGINAL).unwrap();
+        detector.add_file("src/orders.rs", &renamed).unwrap();
+        detector.add_file("src/main.rs", unrelated).unwrap();
+        detector.add_file("README.md", "# not code").unwrap();
+        let report = detector.finish();
+
+        assert_eq!(report.classes.len(), 1, "sub-blocks of the clone must not be reported again");
+        let class = &report.classes[0];
+        assert_eq!(class.kind, CloneKind::Token);
+        let files: Vec<&str> = class.locations.iter().map(|location| location.file_path.as_str()).collect();
+        assert_eq!(files, vec!["src/cart.rs", "src/orders.rs"]);
+        assert_eq!((class.locations[0].line_start, class.locations[0].line_end), (2, 13));
+
+        assert_eq!(report.total_lines, 13 + 13 + 3);
+        assert_eq!(report.duplicated_lines, 24);
+        assert!((report.duplication_percent - 24.0 / 29.0 * 100.0).abs() < 1e-9);
+
+        let files: Vec<(&str, usize)> = report.files.iter()
+            .map(|file| (file.file_path.as_str(), file.duplicated_lines))
+            .collect();
+        assert_eq!(files, vec![("src/cart.rs", 12), ("src/orders.rs", 12)]);
+    }
+
+    #[test]
+    fn test_covered_lines_merges_overlaps() {
+        let fragment = |file, line_start, line_end| Fragment { file, line_start, line_end, tokens: 0 };
+        assert_eq!(covered_lines(&[fragment(0, 1, 10), fragment(0, 5, 12), fragment(0, 13, 14), fragment(1, 1, 10)]), 24);
+    }
+
+    #[test]
+    fn test_outermost_keeps_only_enclosing_spans() {
+        let fragment = |file, line_start, line_end| Fragment { file, line_start, line_end, tokens: 0 };
+        let mut outermost = Outermost::default();
+        outermost.insert(&fragment(0, 10, 20));
+        outermost.insert(&fragment(0, 30, 40));
+        outermost.insert(&fragment(0, 12, 15));
+        outermost.insert(&fragment(0, 5, 45));
+        assert_eq!(outermost.0[&0], vec![(5, 45)]);
+        assert!(outermost.contains(&fragment(0, 20, 30)));
+        assert!(!outermost.contains(&fragment(0, 40, 50)));
+        assert!(!outermost.contains(&fragment(1, 20, 30)));
+    }
+}
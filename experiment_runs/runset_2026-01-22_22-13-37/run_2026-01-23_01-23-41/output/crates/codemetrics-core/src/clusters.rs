@@ -0,0 +1,132 @@
+//! Collapsing many findings of one rule in one directory
+//!
+//! A generated API layer or a vendored parser can trip the same rule hundreds
+//! of times, burying everything else in a flat list. When a (rule, directory)
+//! pair has at least the configured number of findings, reports show it as a
+//! single expandable cluster instead. The findings themselves stay in
+//! `high_complexity_functions` and `high_complexity_files`, so gates, SARIF and
+//! baselines still see each one.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::analyzers::AnalysisResults;
+use crate::rules;
+
+/// Findings of one rule in one directory before they are clustered
+pub const DEFAULT_CLUSTER_THRESHOLD: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssueCluster {
+    pub rule_id: &'static str,
+    /// Directory holding every member; `.` for files at the top
+    pub directory: String,
+    pub count: usize,
+    /// Worst first, as in the flat lists
+    pub members: Vec<ClusterMember>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterMember {
+    /// The function, for function-level rules
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub file_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
+impl IssueCluster {
+    /// Whether a finding of `rule_id` in `file_path` is shown as part of this cluster
+    pub fn covers(&self, rule_id: &str, file_path: &str) -> bool {
+        self.rule_id == rule_id && directory_of(file_path) == self.directory
+    }
+}
+
+/// Whether any of `clusters` covers the finding, so flat lists should leave it out
+pub fn is_clustered(clusters: &[IssueCluster], rule_id: &str, file_path: &str) -> bool {
+    clusters.iter().any(|cluster| cluster.covers(rule_id, file_path))
+}
+
+/// Recompute `results.issue_clusters` from the current findings; a threshold of 0 turns clustering off
+pub fn apply(results: &mut AnalysisResults, threshold: usize) {
+    results.issue_clusters.clear();
+    if threshold == 0 {
+        return;
+    }
+
+    let mut groups: BTreeMap<(&'static str, String), Vec<ClusterMember>> = BTreeMap::new();
+    for function in &results.high_complexity_functions {
+        groups.entry((rules::HIGH_CYCLOMATIC_COMPLEXITY, directory_of(&function.file_path)))
+            .or_default()
+            .push(ClusterMember {
+                name: Some(function.name.clone()),
+                file_path: function.file_path.clone(),
+                line: Some(function.line_start),
+            });
+    }
+    for file in &results.high_complexity_files {
+        groups.entry((rules::HIGH_FILE_COMPLEXITY, directory_of(&file.file_path)))
+            .or_default()
+            .push(ClusterMember { name: None, file_path: file.file_path.clone(), line: None });
+    }
+
+    results.issue_clusters = groups.into_iter()
+        .filter(|(_, members)| members.len() >= threshold)
+        .map(|((rule_id, directory), members)| IssueCluster { rule_id, directory, count: members.len(), members })
+        .collect();
+    results.issue_clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.count));
+}
+
+pub(crate) fn directory_of(file_path: &str) -> String {
+    match Path::new(file_path).parent().map(|parent| parent.to_string_lossy()) {
+        Some(parent) if !parent.is_empty() => parent.into_owned(),
+        _ => ".".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::{CodeAnalyzer, HighComplexityFunction};
+
+    #[test]
+    fn test_clusters_only_crowded_rule_and_directory_pairs() {
+        let mut results = CodeAnalyzer::new().aggregate_results(Vec::new()).unwrap();
+        let template = HighComplexityFunction {
+            name: String::new(),
+            scope: String::new(),
+            file_path: String::new(),
+            complexity: 20,
+            line_start: 1,
+            line_end: 30,
+            parameters: 12,
+            stability: None,
+            snippet: None,
+        };
+        for index in 0..6 {
+            results.high_complexity_functions.push(HighComplexityFunction {
+                name: format!("endpoint_{}", index),
+                file_path: format!("api/generated/endpoint_{}.py", index),
+                ..template.clone()
+            });
+        }
+        results.high_complexity_functions.push(HighComplexityFunction {
+            name: "checkout".to_string(),
+            file_path: "shop/cart.py".to_string(),
+            ..template.clone()
+        });
+
+        apply(&mut results, 5);
+        assert_eq!(results.issue_clusters.len(), 1);
+        let cluster = &results.issue_clusters[0];
+        assert_eq!((cluster.directory.as_str(), cluster.count), ("api/generated", 6));
+        assert!(is_clustered(&results.issue_clusters, rules::HIGH_CYCLOMATIC_COMPLEXITY, "api/generated/endpoint_0.py"));
+        assert!(!is_clustered(&results.issue_clusters, rules::HIGH_CYCLOMATIC_COMPLEXITY, "shop/cart.py"));
+        assert!(!is_clustered(&results.issue_clusters, rules::HIGH_FILE_COMPLEXITY, "api/generated/endpoint_0.py"));
+
+        apply(&mut results, 0);
+        assert!(results.issue_clusters.is_empty());
+    }
+}
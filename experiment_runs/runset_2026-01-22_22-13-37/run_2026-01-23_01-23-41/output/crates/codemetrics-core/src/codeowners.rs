@@ -0,0 +1,82 @@
+//! CODEOWNERS lookup, for grouping files by the team that owns them
+//!
+//! Follows GitHub's rules: the file is read from `.github/`, the root or
+//! `docs/` (first found wins), patterns use gitignore syntax, and the last
+//! matching line decides a file's owners. A matching line without owners
+//! leaves the file unowned.
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+/// Where CODEOWNERS is looked for, relative to the repository root
+pub const LOCATIONS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, Clone)]
+pub struct CodeOwners {
+    /// In file order; matched back to front
+    rules: Vec<(Gitignore, Vec<String>)>,
+}
+
+impl CodeOwners {
+    /// The repository's CODEOWNERS file; fails when there is none
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = LOCATIONS.iter()
+            .map(|location| root.join(location))
+            .find(|path| path.is_file())
+            .with_context(|| format!("No CODEOWNERS file in {} (looked in {})", root.display(), LOCATIONS.join(", ")))?;
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(root, &content).with_context(|| format!("Invalid CODEOWNERS {}", path.display()))
+    }
+
+    pub fn parse(root: &Path, content: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let owners: Vec<String> = fields.take_while(|field| !field.starts_with('#')).map(str::to_string).collect();
+
+            let mut builder = GitignoreBuilder::new(root);
+            builder.add_line(None, pattern)
+                .with_context(|| format!("Invalid pattern '{}'", pattern))?;
+            rules.push((builder.build()?, owners));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Owners of `path` (relative to the root), per the last matching rule
+    pub fn owners(&self, path: &Path) -> &[String] {
+        self.rules.iter()
+            .rev()
+            .find(|(matcher, _)| matcher.matched_path_or_any_parents(path, false).is_ignore())
+            .map(|(_, owners)| owners.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_matching_rule_wins() {
+        let root = Path::new("/repo");
+        let owners = CodeOwners::parse(root, "\
+# Default owners
+*               @org/core
+/billing/       @org/payments @alice
+*.md            # documentation has no owner
+").unwrap();
+
+        assert_eq!(owners.owners(Path::new("src/main.rs")), ["@org/core"]);
+        assert_eq!(owners.owners(Path::new("billing/invoice/pdf.rs")), ["@org/payments", "@alice"]);
+        assert!(owners.owners(Path::new("billing/README.md")).is_empty());
+    }
+}
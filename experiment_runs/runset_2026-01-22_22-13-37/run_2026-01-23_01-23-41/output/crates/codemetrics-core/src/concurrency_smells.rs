@@ -0,0 +1,397 @@
+//! Concurrency smells: code that passes its tests but races, stalls or
+//! serializes work under load
+//!
+//! - A Rust `static mut` accessed under a lock in one place and without one in
+//!   another (`rules::INCONSISTENT_STATIC_GUARD`). Whatever the lock was meant
+//!   to protect, the unguarded access races with it.
+//! - `await` inside a loop over a collection in JavaScript or Python
+//!   (`rules::AWAIT_IN_LOOP`). Loops over array literals, `for await` and
+//!   `async for` are left alone; `while` loops are usually polling or paging,
+//!   where one step depends on the last.
+//! - A blocking lock still held at an `await` (`rules::LOCK_ACROSS_AWAIT`): a
+//!   `std::sync` guard bound with `let` in Rust, or `with lock:` in Python.
+//!   The thread is parked on the lock while the task is suspended elsewhere.
+//!
+//! Everything is matched within one function; locks taken by callers are not
+//! seen.
+
+use std::collections::HashSet;
+use tree_sitter::Node;
+
+use crate::ast_analyzer::{excerpt, walk_descendants, FUNCTION_KINDS};
+use crate::rules;
+
+/// Calls that return a lock guard
+const GUARD_CALLS: &[&str] = &[".lock()", ".read()", ".write()", ".try_lock()"];
+
+/// Nodes whose body runs at another time than the code around them
+const SCOPE_KINDS: &[&str] = &[
+    "function_expression",
+    "generator_function_declaration",
+    "closure_expression",
+    "async_block",
+    "lambda",
+];
+
+/// One smell, reported under `rule_id`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcurrencySmell {
+    pub rule_id: &'static str,
+    /// 1-based
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+/// Every smell in a file parsed with `grammar`, in source order
+pub fn find(root: Node, content: &str, grammar: &str) -> Vec<ConcurrencySmell> {
+    let mut found = match grammar {
+        "rust" => {
+            let mut found = inconsistent_static_guards(root, content);
+            found.extend(rust_locks_across_await(root, content));
+            found
+        }
+        "javascript" => awaits_in_loops(root, content, grammar),
+        "python" => {
+            let mut found = awaits_in_loops(root, content, grammar);
+            found.extend(python_locks_across_await(root, content));
+            found
+        }
+        _ => Vec::new(),
+    };
+    found.sort_by_key(|smell| (smell.line, smell.column));
+    found
+}
+
+/// How to fix a smell reported under `rule_id` in `grammar`
+pub fn suggestion(rule_id: &str, grammar: &str) -> &'static str {
+    match (rule_id, grammar) {
+        (rules::INCONSISTENT_STATIC_GUARD, _) => "Take the same lock around every access, or replace the `static mut` with an atomic, a `Mutex` or a `OnceLock`",
+        (rules::AWAIT_IN_LOOP, "python") => "Start the coroutines first and await them together with asyncio.gather, bounded by an asyncio.Semaphore for large collections",
+        (rules::AWAIT_IN_LOOP, _) => "Start the calls first and await them together with Promise.all, in batches or through a pool for large collections",
+        (rules::LOCK_ACROSS_AWAIT, "python") => "Release the lock before awaiting, or use an asyncio.Lock with `async with`",
+        _ => "Drop the guard before awaiting (end its block or call `drop`), or use an async lock such as `tokio::sync::Mutex`",
+    }
+}
+
+fn smell(node: Node, rule_id: &'static str, message: String) -> ConcurrencySmell {
+    let position = node.start_position();
+    ConcurrencySmell {
+        rule_id,
+        line: position.row as u32 + 1,
+        column: position.column as u32 + 1,
+        message,
+    }
+}
+
+/// Unguarded accesses to `static mut` items that are guarded elsewhere
+fn inconsistent_static_guards(root: Node, content: &str) -> Vec<ConcurrencySmell> {
+    let mut statics = Vec::new();
+    walk_descendants(root, |node, _| {
+        if node.kind() == "static_item" && has_child(node, "mutable_specifier") {
+            if let Some(name) = node.child_by_field_name("name") {
+                statics.push(name);
+            }
+        }
+        true
+    });
+
+    let mut found = Vec::new();
+    for declaration in statics {
+        let name = &content[declaration.byte_range()];
+        let mut guarded_line = None;
+        let mut unguarded = Vec::new();
+        walk_descendants(root, |node, _| {
+            if node.kind() == "identifier" && node != declaration && &content[node.byte_range()] == name {
+                match is_guarded(node, content) {
+                    Some(true) => {
+                        guarded_line.get_or_insert(node.start_position().row + 1);
+                    }
+                    Some(false) => unguarded.push(node),
+                    None => {}
+                }
+            }
+            true
+        });
+        if let Some(line) = guarded_line {
+            found.extend(unguarded.into_iter().map(|node| smell(
+                node,
+                rules::INCONSISTENT_STATIC_GUARD,
+                format!("`static mut {}` is accessed here without a lock, but under one on line {}", name, line),
+            )));
+        }
+    }
+    found
+}
+
+/// Whether a lock guard bound earlier in an enclosing block is live at `access`,
+/// or `None` outside any function
+fn is_guarded(access: Node, content: &str) -> Option<bool> {
+    let mut node = access;
+    while let Some(parent) = node.parent() {
+        if parent.kind() == "block" {
+            let mut cursor = parent.walk();
+            let locked = parent.named_children(&mut cursor)
+                .take_while(|statement| statement.end_byte() <= access.start_byte())
+                .any(|statement| guard_binding(statement, content).is_some());
+            if locked {
+                return Some(true);
+            }
+        }
+        if parent.kind() == "function_item" {
+            return Some(false);
+        }
+        node = parent;
+    }
+    None
+}
+
+/// Name of the guard a `let` statement binds, and whether its lock blocks the thread
+fn guard_binding<'a>(statement: Node, content: &'a str) -> Option<(&'a str, bool)> {
+    if statement.kind() != "let_declaration" {
+        return None;
+    }
+    let name = &content[statement.child_by_field_name("pattern")?.byte_range()];
+    let value = &content[statement.child_by_field_name("value")?.byte_range()];
+    // `let _ = m.lock()` drops the guard on the spot
+    if name == "_" {
+        return None;
+    }
+
+    let mut text = value.trim();
+    let mut awaited = false;
+    loop {
+        if let Some(rest) = text.strip_suffix('?').or_else(|| text.strip_suffix(".unwrap()")) {
+            text = rest.trim_end();
+        } else if let Some(rest) = text.strip_suffix(".await") {
+            awaited = true;
+            text = rest;
+        } else if let Some(at) = text.rfind(".expect(").filter(|&at| text.ends_with(')') && !text[at..].contains(").")) {
+            text = &text[..at];
+        } else {
+            break;
+        }
+    }
+    GUARD_CALLS.iter().any(|call| text.ends_with(call)).then_some((name, !awaited))
+}
+
+/// Awaits after a blocking guard is bound and before it is dropped
+fn rust_locks_across_await(root: Node, content: &str) -> Vec<ConcurrencySmell> {
+    let mut found = Vec::new();
+    walk_descendants(root, |node, _| {
+        if let Some((name, true)) = guard_binding(node, content) {
+            let drop_call = format!("drop({})", name);
+            let mut next = node.next_named_sibling();
+            while let Some(statement) = next {
+                if content[statement.byte_range()].replace(' ', "").starts_with(&drop_call) {
+                    break;
+                }
+                if let Some(held) = first_await(statement) {
+                    found.push(smell(held, rules::LOCK_ACROSS_AWAIT, format!(
+                        "Blocking lock guard `{}` from line {} is still held at this `.await`",
+                        name, node.start_position().row + 1
+                    )));
+                    break;
+                }
+                next = statement.next_named_sibling();
+            }
+        }
+        true
+    });
+    found
+}
+
+/// Awaits in the body of a plain `with` over something named like a lock
+fn python_locks_across_await(root: Node, content: &str) -> Vec<ConcurrencySmell> {
+    let mut found = Vec::new();
+    walk_descendants(root, |node, _| {
+        if node.kind() == "with_statement" && !has_child(node, "async") {
+            if let Some(body) = node.child_by_field_name("body") {
+                let items = content[node.start_byte()..body.start_byte()].trim_start_matches("with").trim_end().trim_end_matches(':');
+                let lock = items.split(',')
+                    .map(|item| item.split(" as ").next().unwrap_or_default().trim())
+                    .find(|item| is_lock_name(item));
+                if let Some((lock, held)) = lock.zip(first_await(body)) {
+                    found.push(smell(held, rules::LOCK_ACROSS_AWAIT, format!("Blocking lock `{}` is held across this `await`", excerpt(lock))));
+                }
+            }
+        }
+        true
+    });
+    found
+}
+
+/// `self._lock`, `threading.Lock()` or `mutex`, but not `open(lockfile)`
+fn is_lock_name(expression: &str) -> bool {
+    let callee = expression.split('(').next().unwrap_or_default();
+    let last = callee.rsplit('.').next().unwrap_or_default().to_ascii_lowercase();
+    last.contains("lock") || last.contains("mutex")
+}
+
+/// The first await of each loop over a collection, awaited once per iteration
+fn awaits_in_loops(root: Node, content: &str, grammar: &str) -> Vec<ConcurrencySmell> {
+    let mut reported = HashSet::new();
+    let mut found = Vec::new();
+    walk_descendants(root, |node, _| {
+        if is_await(node) {
+            if let Some(looped) = enclosing_loop(node) {
+                let in_body = looped.child_by_field_name("body")
+                    .is_some_and(|body| body.byte_range().contains(&node.start_byte()));
+                if let Some(collection) = iterated_collection(looped, content, grammar).filter(|_| in_body) {
+                    if reported.insert(looped.id()) {
+                        found.push(smell(node, rules::AWAIT_IN_LOOP, format!(
+                            "`await` inside a loop over `{}` waits for each iteration before starting the next",
+                            excerpt(collection)
+                        )));
+                    }
+                }
+            }
+        }
+        true
+    });
+    found
+}
+
+/// The nearest loop around `node` in the same function
+fn enclosing_loop(node: Node) -> Option<Node> {
+    let mut node = node;
+    while let Some(parent) = node.parent() {
+        if is_scope(parent) {
+            return None;
+        }
+        if matches!(parent.kind(), "for_statement" | "for_in_statement" | "while_statement" | "do_statement") {
+            return Some(parent);
+        }
+        node = parent;
+    }
+    None
+}
+
+/// What a loop iterates over, when it walks a collection of unknown size
+fn iterated_collection<'a>(looped: Node, content: &'a str, grammar: &str) -> Option<&'a str> {
+    let text = move |node: Node| -> &'a str { &content[node.byte_range()] };
+    match (grammar, looped.kind()) {
+        // `for (const x of items)` and `for (const key in object)`, but not `for await`
+        ("javascript", "for_in_statement") if !has_child(looped, "await") => looped.child_by_field_name("right")
+            .filter(|right| right.kind() != "array")
+            .map(text),
+        // `for (let i = 0; i < items.length; i++)`
+        ("javascript", "for_statement") => looped.child_by_field_name("condition")
+            .map(|condition| text(condition).trim_end_matches(';').trim())
+            .filter(|condition| condition.contains(".length")),
+        ("python", "for_statement") if !has_child(looped, "async") => looped.child_by_field_name("right")
+            .filter(|right| !matches!(right.kind(), "list" | "tuple"))
+            .map(text),
+        _ => None,
+    }
+}
+
+/// The first await under `within` that runs as part of it, not in a nested function
+fn first_await(within: Node) -> Option<Node> {
+    let mut found = None;
+    walk_descendants(within, |node, _| {
+        if is_await(node) && !in_nested_scope(node, within) {
+            found = Some(node);
+        }
+        found.is_none()
+    });
+    found
+}
+
+fn in_nested_scope(node: Node, within: Node) -> bool {
+    let mut node = node;
+    while let Some(parent) = node.parent() {
+        if parent == within {
+            return false;
+        }
+        if is_scope(parent) {
+            return true;
+        }
+        node = parent;
+    }
+    false
+}
+
+/// JavaScript and Rust `await_expression`, Python `await` (the keyword token shares the kind but is unnamed)
+fn is_await(node: Node) -> bool {
+    node.is_named() && matches!(node.kind(), "await_expression" | "await")
+}
+
+fn is_scope(node: Node) -> bool {
+    FUNCTION_KINDS.contains(&node.kind()) || SCOPE_KINDS.contains(&node.kind())
+}
+
+fn has_child(node: Node, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(|child| child.kind() == kind);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::test_util::detect;
+    use crate::parsers::SupportedLanguage;
+
+    #[test]
+    fn test_rust_statics_and_guards_across_await() {
+        let source = "\
+static mut COUNT: u32 = 0;
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn increment() {
+    let _guard = LOCK.lock().unwrap();
+    unsafe { COUNT += 1 }
+}
+
+fn reset() {
+    unsafe { COUNT = 0 }
+}
+
+async fn refresh(cache: &Mutex<Cache>, client: &Client) {
+    let mut cache = cache.lock().unwrap();
+    cache.entries = client.fetch().await;
+}
+
+async fn refresh_async(cache: &tokio::sync::Mutex<Cache>, client: &Client) {
+    let mut cache = cache.lock().await;
+    cache.entries = client.fetch().await;
+}
+";
+        assert_eq!(detect(SupportedLanguage::Rust, source, find, |smell| (smell.line, smell.rule_id)), vec![
+            (10, rules::INCONSISTENT_STATIC_GUARD),
+            (15, rules::LOCK_ACROSS_AWAIT),
+        ]);
+    }
+
+    #[test]
+    fn test_awaits_in_loops_and_python_locks() {
+        let js = "\
+async function sync(users) {
+  for (const user of users) {
+    await save(user);
+    await audit(user);
+  }
+  for (const id of [1, 2]) {
+    await load(id);
+  }
+  await Promise.all(users.map(async user => save(user)));
+}
+";
+        assert_eq!(detect(SupportedLanguage::JavaScript, js, find, |smell| (smell.line, smell.rule_id)), vec![(3, rules::AWAIT_IN_LOOP)]);
+
+        let python = "\
+async def refresh(self, items):
+    for item in items:
+        await item.reload()
+    with self._lock:
+        await self.flush()
+    async with self._async_lock:
+        await self.flush()
+";
+        assert_eq!(detect(SupportedLanguage::Python, python, find, |smell| (smell.line, smell.rule_id)), vec![
+            (3, rules::AWAIT_IN_LOOP),
+            (5, rules::LOCK_ACROSS_AWAIT),
+        ]);
+    }
+}
@@ -0,0 +1,292 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Represents the programming language of a source file
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    Rust,
+    JavaScript,
+    TypeScript,
+    Python,
+    Go,
+    Ruby,
+    /// Added by a `LanguageProvider`, named by `LanguageProvider::name`
+    Plugin(String),
+    Unknown,
+}
+
+impl Language {
+    /// Detect language from file extension, including registered plugins
+    pub fn from_extension(ext: &str) -> Self {
+        Self::builtin_for_extension(ext)
+            .or_else(|| crate::language_provider::for_extension(ext)
+                .map(|provider| Language::Plugin(provider.name().to_string())))
+            .unwrap_or(Language::Unknown)
+    }
+
+    /// The built-in language for an extension, ignoring plugins
+    pub fn builtin_for_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "rs" => Some(Language::Rust),
+            "js" | "jsx" | "mjs" => Some(Language::JavaScript),
+            "ts" | "tsx" => Some(Language::TypeScript),
+            "py" | "pyi" => Some(Language::Python),
+            "go" => Some(Language::Go),
+            "rb" | "rake" | "gemspec" => Some(Language::Ruby),
+            _ => None,
+        }
+    }
+
+    /// Get file extensions for this language
+    pub fn extensions(&self) -> Vec<&'static str> {
+        match self {
+            Language::Rust => vec!["rs"],
+            Language::JavaScript => vec!["js", "jsx", "mjs"],
+            Language::TypeScript => vec!["ts", "tsx"],
+            Language::Python => vec!["py", "pyi"],
+            Language::Go => vec!["go"],
+            Language::Ruby => vec!["rb", "rake", "gemspec"],
+            Language::Plugin(name) => crate::language_provider::for_name(name)
+                .map(|provider| provider.extensions().to_vec())
+                .unwrap_or_default(),
+            Language::Unknown => vec![],
+        }
+    }
+}
+
+/// Core metrics for code analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeMetrics {
+    /// Cyclomatic complexity - number of linearly independent paths
+    pub cyclomatic_complexity: u32,
+
+    /// Lines of code (excluding comments and blank lines)
+    pub lines_of_code: u32,
+
+    /// Total lines including comments and whitespace
+    pub total_lines: u32,
+
+    /// Number of functions/methods
+    pub function_count: u32,
+
+    /// Number of parameters across all functions
+    pub parameter_count: u32,
+
+    /// Nesting depth (maximum)
+    pub max_nesting_depth: u32,
+
+    /// Maintainability index (0-100, higher is better)
+    pub maintainability_index: f64,
+
+    /// Share of lines inside blocks repeated elsewhere in the same file (0-100)
+    #[serde(default)]
+    pub duplication_percent: f64,
+}
+
+impl Default for CodeMetrics {
+    fn default() -> Self {
+        Self {
+            cyclomatic_complexity: 1,
+            lines_of_code: 0,
+            total_lines: 0,
+            function_count: 0,
+            parameter_count: 0,
+            max_nesting_depth: 0,
+            maintainability_index: 100.0,
+            duplication_percent: 0.0,
+        }
+    }
+}
+
+/// Analysis result for a single source file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileAnalysis {
+    pub file_path: PathBuf,
+    pub language: Language,
+    pub metrics: CodeMetrics,
+    pub issues: Vec<CodeIssue>,
+    pub analysis_time_ms: u64,
+}
+
+/// Represents a code quality issue or pattern
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CodeIssue {
+    /// Stable rule identifier such as `CM0101`, see `crate::rules`
+    pub rule_id: String,
+    pub severity: IssueSeverity,
+    pub category: IssueCategory,
+    pub message: String,
+    pub line: u32,
+    pub column: u32,
+    pub suggestion: Option<String>,
+    /// Machine-applicable version of `suggestion`, for rules that can compute one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<CodeAction>,
+    /// Where the input a security finding traced to this line came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<IssueLocation>,
+}
+
+/// A position in the analyzed file with the code found there
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueLocation {
+    pub line: u32,
+    pub column: u32,
+    pub excerpt: String,
+}
+
+/// Edits that carry out an issue's suggestion, for editor integrations and `--fix`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeAction {
+    pub title: String,
+    /// Non-overlapping edits, all against the original source
+    pub edits: Vec<TextEdit>,
+}
+
+/// Replace the text between two positions
+///
+/// Lines and columns are 1-based; columns count bytes, as tree-sitter does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+    pub new_text: String,
+}
+
+impl CodeAction {
+    /// `source` with every edit applied
+    pub fn apply(&self, source: &str) -> Result<String> {
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        let offset = |line: u32, column: u32| -> Result<usize> {
+            let start = line.checked_sub(1)
+                .and_then(|index| line_starts.get(index as usize))
+                .ok_or_else(|| anyhow::anyhow!("Edit line {} is outside the file", line))?;
+            let offset = start + column.saturating_sub(1) as usize;
+            if offset > source.len() || !source.is_char_boundary(offset) {
+                anyhow::bail!("Edit position {}:{} is not a valid position", line, column);
+            }
+            Ok(offset)
+        };
+
+        let mut ranges = self.edits.iter()
+            .map(|edit| Ok((offset(edit.start_line, edit.start_column)?, offset(edit.end_line, edit.end_column)?, edit)))
+            .collect::<Result<Vec<_>>>()?;
+        ranges.sort_by_key(|(start, _, _)| *start);
+
+        let mut result = String::with_capacity(source.len());
+        let mut copied = 0;
+        for (start, end, edit) in ranges {
+            if start < copied || end < start {
+                anyhow::bail!("Edits in '{}' overlap", self.title);
+            }
+            result.push_str(&source[copied..start]);
+            result.push_str(&edit.new_text);
+            copied = end;
+        }
+        result.push_str(&source[copied..]);
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IssueSeverity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl IssueSeverity {
+    /// Parse a user-supplied level such as `warning` or `error`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "info" => Some(IssueSeverity::Info),
+            "warning" | "warn" => Some(IssueSeverity::Warning),
+            "error" => Some(IssueSeverity::Error),
+            "critical" => Some(IssueSeverity::Critical),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IssueCategory {
+    Complexity,
+    Performance,
+    Maintainability,
+    Security,
+    Style,
+    Duplication,
+    Concurrency,
+}
+
+/// Aggregated analysis results for entire codebase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectAnalysis {
+    pub files: Vec<FileAnalysis>,
+    pub summary: ProjectSummary,
+    pub dependency_graph: Option<DependencyGraph>,
+    pub analysis_timestamp: String,
+    pub total_analysis_time_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSummary {
+    pub total_files: u32,
+    pub languages: HashMap<Language, u32>,
+    pub total_lines_of_code: u32,
+    pub total_complexity: u32,
+    pub average_maintainability: f64,
+    pub issue_count_by_severity: HashMap<IssueSeverity, u32>,
+    pub issue_count_by_category: HashMap<IssueCategory, u32>,
+}
+
+/// Represents dependencies between modules/files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<DependencyNode>,
+    pub edges: Vec<DependencyEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyNode {
+    pub id: String,
+    pub file_path: PathBuf,
+    pub module_name: String,
+    pub exports: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyEdge {
+    pub from: String,
+    pub to: String,
+    pub import_type: ImportType,
+    pub imported_symbols: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ImportType {
+    Named,
+    Default,
+    Namespace,
+    Side,
+}
+
+/// Configuration for analysis behavior
+#[derive(Debug, Clone)]
+pub struct AnalysisConfig {
+    pub complexity_threshold: u32,
+    pub maintainability_threshold: f64,
+    pub max_function_length: u32,
+    pub max_parameter_count: u32,
+    pub include_patterns: Vec<String>,
+    pub exclude_patterns: Vec<String>,
+    pub analyze_dependencies: bool,
+    pub parallel_analysis: bool,
+}
\ No newline at end of file
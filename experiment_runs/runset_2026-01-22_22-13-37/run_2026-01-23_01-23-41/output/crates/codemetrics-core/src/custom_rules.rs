@@ -0,0 +1,306 @@
+//! Project-specific lint rules written as tree-sitter queries
+//!
+//! Teams describe patterns they want flagged in `.codemetrics/rules.toml` (or
+//! `rules.yaml`) instead of writing Rust; `ASTAnalyzer::with_custom_rules`
+//! runs them in `detect_issues` next to the built-in rules:
+//!
+//! ```toml
+//! [[rule]]
+//! id = "no-console-log"
+//! language = "javascript"
+//! severity = "warning"
+//! query = '''
+//! (call_expression
+//!   function: (member_expression object: (identifier) @object property: (property_identifier) @method)
+//!   (#eq? @object "console")
+//!   (#eq? @method "log")) @match
+//! '''
+//! message = "Leftover console.{method} call"
+//! suggestion = "Use the project logger instead"
+//! ```
+//!
+//! The issue is reported at the `@match` capture, or at the first capture when
+//! the query has none. `{name}` in `message` is replaced by the text of the
+//! `@name` capture. IDs must not collide with built-in rule IDs or names.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tree_sitter::{Node, Query, QueryCursor};
+
+use crate::analyzers::parse_language_list;
+use crate::ast_analyzer::{excerpt, ASTAnalyzer};
+use crate::core::{CodeIssue, IssueCategory, IssueSeverity, Language};
+use crate::suppressions::{self, SuppressedIssue};
+use crate::{language_provider, rules};
+
+/// Where `discover` looks for rule files, relative to the project root
+pub const RULE_FILES: &[&str] = &[".codemetrics/rules.toml", ".codemetrics/rules.yaml", ".codemetrics/rules.yml"];
+
+/// One user-defined rule as written in a rule file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomRule {
+    pub id: String,
+    /// Language name such as `python` or `js`, or a plugin language's name
+    pub language: String,
+    pub query: String,
+    #[serde(default = "default_severity", deserialize_with = "severity_from_name")]
+    pub severity: IssueSeverity,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    #[serde(default, rename = "rule", alias = "rules")]
+    rules: Vec<CustomRule>,
+}
+
+fn default_severity() -> IssueSeverity {
+    IssueSeverity::Warning
+}
+
+fn severity_from_name<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<IssueSeverity, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    IssueSeverity::from_name(&name)
+        .ok_or_else(|| serde::de::Error::custom(format!("unknown severity '{}' (expected info, warning, error or critical)", name)))
+}
+
+/// Read rules from a TOML or YAML file, chosen by extension
+pub fn load(path: &Path) -> Result<Vec<CustomRule>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rule file: {}", path.display()))?;
+    let file: RuleFile = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml" | "yml") => serde_yaml::from_str(&text)
+            .with_context(|| format!("Invalid rule file: {}", path.display()))?,
+        _ => toml::from_str(&text)
+            .with_context(|| format!("Invalid rule file: {}", path.display()))?,
+    };
+
+    let mut ids = HashSet::new();
+    for rule in &file.rules {
+        if rules::find_rule(&rule.id).is_some() {
+            anyhow::bail!("Custom rule '{}' in {} reuses a built-in rule ID or name", rule.id, path.display());
+        }
+        if !ids.insert(rule.id.as_str()) {
+            anyhow::bail!("Custom rule '{}' is defined twice in {}", rule.id, path.display());
+        }
+    }
+    Ok(file.rules)
+}
+
+/// Rules from the first of `RULE_FILES` under `root`; none when no file exists
+pub fn discover(root: &Path) -> Result<Vec<CustomRule>> {
+    match RULE_FILES.iter().map(|name| root.join(name)).find(|path| path.is_file()) {
+        Some(path) => load(&path),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// The analyzer language a rule's `language` names
+pub fn resolve_language(name: &str) -> Result<Language> {
+    if let Ok(canonical) = parse_language_list(&[name.to_string()]) {
+        return Ok(match canonical[0].as_str() {
+            "Rust" => Language::Rust,
+            "JavaScript" => Language::JavaScript,
+            "TypeScript" => Language::TypeScript,
+            "Python" => Language::Python,
+            "Go" => Language::Go,
+            _ => Language::Ruby,
+        });
+    }
+    language_provider::for_name(name)
+        .map(|provider| Language::Plugin(provider.name().to_string()))
+        .ok_or_else(|| anyhow::anyhow!("Unknown language '{}' (run `languages` to list supported ones)", name))
+}
+
+/// A custom rule with its query compiled for the language's grammar
+pub struct CompiledRule {
+    pub rule: CustomRule,
+    query: Query,
+    location: u32,
+}
+
+impl CompiledRule {
+    pub fn compile(rule: CustomRule, grammar: &tree_sitter::Language) -> Result<Self> {
+        let query = Query::new(grammar, &rule.query)
+            .map_err(|error| anyhow::anyhow!("Invalid query for custom rule '{}': {}", rule.id, error))?;
+        let location = match query.capture_index_for_name("match") {
+            Some(index) => index,
+            None if query.capture_names().is_empty() => {
+                anyhow::bail!("Custom rule '{}' has no captures; add @match to the node to report", rule.id)
+            }
+            None => 0,
+        };
+        Ok(Self { rule, query, location })
+    }
+
+    /// One issue per distinct location the query matches
+    pub fn issues(&self, root: &Node, content: &str) -> Vec<CodeIssue> {
+        let mut cursor = QueryCursor::new();
+        let mut seen = HashSet::new();
+        let mut issues = Vec::new();
+
+        for match_ in cursor.matches(&self.query, *root, content.as_bytes()) {
+            let Some(node) = match_.captures.iter().find(|capture| capture.index == self.location).map(|capture| capture.node) else {
+                continue;
+            };
+            if !seen.insert(node.start_byte()) {
+                continue;
+            }
+
+            let mut message = self.rule.message.clone();
+            for capture in match_.captures {
+                let placeholder = format!("{{{}}}", self.query.capture_names()[capture.index as usize]);
+                message = message.replace(&placeholder, &excerpt(&content[capture.node.byte_range()]));
+            }
+            let position = node.start_position();
+            issues.push(CodeIssue {
+                rule_id: self.rule.id.clone(),
+                severity: self.rule.severity.clone(),
+                category: IssueCategory::Maintainability,
+                message,
+                line: position.row as u32 + 1,
+                column: position.column as u32 + 1,
+                suggestion: self.rule.suggestion.clone(),
+                action: None,
+                origin: None,
+            });
+        }
+
+        issues
+    }
+}
+
+/// Every issue found in a project, built-in and custom
+#[derive(Debug, Serialize)]
+pub struct LintReport {
+    pub files_scanned: usize,
+    pub findings: Vec<LintFinding>,
+    /// Findings hidden by `codemetrics-ignore` comments
+    pub suppressed: Vec<SuppressedIssue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LintFinding {
+    pub file_path: PathBuf,
+    #[serde(flatten)]
+    pub issue: CodeIssue,
+}
+
+/// Run `analyzer`'s rules over every supported file under `root`, respecting .gitignore and suppression comments
+pub fn lint(root: &Path, analyzer: &mut ASTAnalyzer) -> Result<LintReport> {
+    let supported = analyzer.languages();
+    let today = chrono::Local::now().date_naive();
+    let mut report = LintReport { files_scanned: 0, findings: Vec::new(), suppressed: Vec::new() };
+
+    for entry in ignore::Walk::new(root) {
+        let entry = entry.context("Failed to read directory entry")?;
+        let path = entry.path();
+        let language = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(Language::from_extension)
+            .unwrap_or(Language::Unknown);
+        if !path.is_file() || !supported.contains(&language) {
+            continue;
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?;
+        let (_, mut issues, functions, _) = analyzer.analyze_file(&content, &language, path)?;
+        report.suppressed.extend(suppressions::apply_comments(&path.to_string_lossy(), &content, &functions, &mut issues, today));
+        report.files_scanned += 1;
+        report.findings.extend(issues.into_iter().map(|issue| LintFinding { file_path: path.to_path_buf(), issue }));
+    }
+
+    report.findings.sort_by(|a, b| (&a.file_path, a.issue.line, a.issue.column).cmp(&(&b.file_path, b.issue.line, b.issue.column)));
+    Ok(report)
+}
+
+impl LintReport {
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Lint ({} findings in {} files scanned):\n", self.findings.len(), self.files_scanned);
+        for finding in &self.findings {
+            let _ = writeln!(
+                text,
+                "  {}:{}:{}  {:?} [{}] {}",
+                finding.file_path.display(),
+                finding.issue.line,
+                finding.issue.column,
+                finding.issue.severity,
+                finding.issue.rule_id,
+                finding.issue.message,
+            );
+        }
+        if !self.suppressed.is_empty() {
+            let _ = writeln!(text, "Suppressed ({} hidden by codemetrics-ignore comments):", self.suppressed.len());
+            for suppressed in &self.suppressed {
+                let _ = writeln!(
+                    text,
+                    "  {}:{}  [{}] {}",
+                    suppressed.file_path,
+                    suppressed.line,
+                    suppressed.rule,
+                    suppressed.reason.as_deref().unwrap_or("-"),
+                );
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const CONSOLE_LOG: &str = r#"
+[[rule]]
+id = "no-console-log"
+language = "js"
+query = '''
+(call_expression
+  function: (member_expression object: (identifier) @object property: (property_identifier) @method)
+  (#eq? @object "console")
+  (#eq? @method "log")) @match
+'''
+message = "Leftover {object}.{method} call"
+"#;
+
+    #[test]
+    fn test_custom_rule_reports_with_captures_in_message() -> Result<()> {
+        let dir = tempdir()?;
+        std::fs::create_dir(dir.path().join(".codemetrics"))?;
+        std::fs::write(dir.path().join(".codemetrics/rules.toml"), CONSOLE_LOG)?;
+        std::fs::write(dir.path().join("app.js"), "function run() {\n  console.log('hi');\n  console.error('no');\n}\n")?;
+
+        let custom = discover(dir.path())?;
+        assert_eq!(custom[0].severity, IssueSeverity::Warning);
+        let mut analyzer = ASTAnalyzer::new()?.with_custom_rules(custom)?;
+        let report = lint(dir.path(), &mut analyzer)?;
+
+        let findings: Vec<_> = report.findings.iter().filter(|finding| finding.issue.rule_id == "no-console-log").collect();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].issue.message, "Leftover console.log call");
+        assert_eq!((findings[0].issue.line, findings[0].issue.column), (2, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rule_files_are_validated() -> Result<()> {
+        let dir = tempdir()?;
+        let yaml = dir.path().join("rules.yaml");
+        std::fs::write(&yaml, "rules:\n  - id: CM0101\n    language: python\n    query: \"(call) @match\"\n    message: clash\n")?;
+        assert!(load(&yaml).is_err());
+
+        let bad_query = CONSOLE_LOG.replace("@match", "@match (oops");
+        let toml_path = dir.path().join("rules.toml");
+        std::fs::write(&toml_path, bad_query)?;
+        assert!(ASTAnalyzer::new()?.with_custom_rules(load(&toml_path)?).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,285 @@
+//! Enumeration of elementary cycles in a directed graph
+//!
+//! Uses Johnson's algorithm, so every distinct cycle is reported once, including
+//! cycles that share modules with each other. Strongly connected components are
+//! found with Tarjan's algorithm. Both are written with explicit stacks because
+//! import graphs can be deep enough to overflow the call stack.
+//!
+//! Densely connected graphs can contain exponentially many cycles, so the search
+//! stops after a configurable number of them and reports that it was truncated.
+
+use std::collections::{HashMap, HashSet};
+
+/// Cycles reported before the search gives up
+pub const DEFAULT_CYCLE_LIMIT: usize = 1000;
+
+/// Outcome of a cycle search
+#[derive(Debug, Default)]
+pub struct CycleSearch {
+    /// Each cycle starts and ends with the same node
+    pub cycles: Vec<Vec<String>>,
+    /// More cycles exist than `limit` allowed to be reported
+    pub truncated: bool,
+}
+
+/// Find up to `limit` distinct elementary cycles
+///
+/// Cycles are listed in the order of `node_ids`, each starting from its earliest
+/// node. Repeated edges between the same pair of nodes are collapsed first, so
+/// several imports between two modules never produce duplicate cycles.
+pub fn elementary_cycles(
+    node_ids: &[String],
+    adjacency: &HashMap<String, Vec<String>>,
+    limit: usize,
+) -> CycleSearch {
+    let mut ids: Vec<&str> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let nodes = node_ids.iter().map(String::as_str)
+        .chain(adjacency.keys().map(String::as_str))
+        .chain(adjacency.values().flatten().map(String::as_str));
+    for id in nodes {
+        index.entry(id).or_insert_with(|| {
+            ids.push(id);
+            ids.len() - 1
+        });
+    }
+
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); ids.len()];
+    for (from, targets) in adjacency {
+        let Some(&from) = index.get(from.as_str()) else {
+            continue;
+        };
+        successors[from].extend(targets.iter().map(|to| index[to.as_str()]));
+    }
+    for targets in &mut successors {
+        targets.sort_unstable();
+        targets.dedup();
+    }
+
+    let mut search = CycleSearch::default();
+    let all: Vec<usize> = (0..ids.len()).collect();
+    let mut pending = strongly_connected_components(&successors, &all);
+
+    while let Some(component) = pending.pop() {
+        if search.truncated {
+            break;
+        }
+
+        let start = component[0];
+        if component.len() == 1 {
+            if successors[start].contains(&start) {
+                record(&mut search, vec![start, start], &ids, limit);
+            }
+            continue;
+        }
+
+        let members: HashSet<usize> = component.iter().copied().collect();
+        for cycle in circuits_through(start, &successors, &members, limit.saturating_add(1).saturating_sub(search.cycles.len())) {
+            record(&mut search, cycle, &ids, limit);
+        }
+
+        // Every cycle through `start` is known; the rest of the component may hold more
+        let rest = &component[1..];
+        pending.extend(strongly_connected_components(&successors, rest));
+    }
+
+    search
+}
+
+fn record(search: &mut CycleSearch, cycle: Vec<usize>, ids: &[&str], limit: usize) {
+    if search.cycles.len() >= limit {
+        search.truncated = true;
+        return;
+    }
+    search.cycles.push(cycle.into_iter().map(|node| ids[node].to_string()).collect());
+}
+
+/// Johnson's circuit search: every elementary cycle through `start` inside `members`
+fn circuits_through(
+    start: usize,
+    successors: &[Vec<usize>],
+    members: &HashSet<usize>,
+    limit: usize,
+) -> Vec<Vec<usize>> {
+    let neighbors = |node: usize| successors[node].iter().copied().filter(|next| members.contains(next));
+
+    let mut cycles = Vec::new();
+    let mut blocked: HashSet<usize> = HashSet::from([start]);
+    let mut blocked_by: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut path = vec![start];
+    // (node, next successor index, whether a cycle was found below it)
+    let mut stack: Vec<(usize, usize, bool)> = vec![(start, 0, false)];
+
+    while let Some((node, next, found)) = stack.last_mut() {
+        let node = *node;
+
+        if let Some(successor) = neighbors(node).nth(*next) {
+            *next += 1;
+            if successor == start {
+                let mut cycle = path.clone();
+                cycle.push(start);
+                cycles.push(cycle);
+                *found = true;
+                if cycles.len() >= limit {
+                    break;
+                }
+            } else if blocked.insert(successor) {
+                path.push(successor);
+                stack.push((successor, 0, false));
+            }
+            continue;
+        }
+
+        let found = *found;
+        if found {
+            unblock(node, &mut blocked, &mut blocked_by);
+        } else {
+            for successor in neighbors(node) {
+                let waiting = blocked_by.entry(successor).or_default();
+                if !waiting.contains(&node) {
+                    waiting.push(node);
+                }
+            }
+        }
+
+        stack.pop();
+        path.pop();
+        if let Some(parent) = stack.last_mut() {
+            parent.2 |= found;
+        }
+    }
+
+    cycles
+}
+
+fn unblock(node: usize, blocked: &mut HashSet<usize>, blocked_by: &mut HashMap<usize, Vec<usize>>) {
+    let mut pending = vec![node];
+    while let Some(node) = pending.pop() {
+        if blocked.remove(&node) {
+            pending.extend(blocked_by.remove(&node).unwrap_or_default());
+        }
+    }
+}
+
+/// Tarjan's algorithm restricted to `members`; each component is sorted ascending
+fn strongly_connected_components(successors: &[Vec<usize>], members: &[usize]) -> Vec<Vec<usize>> {
+    let member_set: HashSet<usize> = members.iter().copied().collect();
+    let mut order: HashMap<usize, usize> = HashMap::new();
+    let mut low_link: HashMap<usize, usize> = HashMap::new();
+    let mut on_stack: HashSet<usize> = HashSet::new();
+    let mut component_stack: Vec<usize> = Vec::new();
+    let mut components = Vec::new();
+
+    for &root in members {
+        if order.contains_key(&root) {
+            continue;
+        }
+
+        let mut call_stack: Vec<(usize, usize)> = vec![(root, 0)];
+        order.insert(root, order.len());
+        low_link.insert(root, order[&root]);
+        component_stack.push(root);
+        on_stack.insert(root);
+
+        while let Some((node, next)) = call_stack.last_mut() {
+            let node = *node;
+            let successor = successors[node].iter()
+                .copied()
+                .filter(|successor| member_set.contains(successor))
+                .nth(*next);
+
+            if let Some(successor) = successor {
+                *next += 1;
+                if !order.contains_key(&successor) {
+                    order.insert(successor, order.len());
+                    low_link.insert(successor, order[&successor]);
+                    component_stack.push(successor);
+                    on_stack.insert(successor);
+                    call_stack.push((successor, 0));
+                } else if on_stack.contains(&successor) {
+                    let low = low_link[&node].min(order[&successor]);
+                    low_link.insert(node, low);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                let low = low_link[&parent].min(low_link[&node]);
+                low_link.insert(parent, low);
+            }
+
+            if low_link[&node] == order[&node] {
+                let mut component = Vec::new();
+                while let Some(member) = component_stack.pop() {
+                    on_stack.remove(&member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                components.push(component);
+            }
+        }
+    }
+
+    // Lowest-numbered components are searched first once popped from the work list
+    components.sort_by(|a, b| b[0].cmp(&a[0]));
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &str)]) -> (Vec<String>, HashMap<String, Vec<String>>) {
+        let mut node_ids: Vec<String> = Vec::new();
+        let mut adjacency: HashMap<String, Vec<String>> = HashMap::new();
+        for (from, to) in edges {
+            for id in [from, to] {
+                if !node_ids.iter().any(|node| node == id) {
+                    node_ids.push(id.to_string());
+                }
+            }
+            adjacency.entry(from.to_string()).or_default().push(to.to_string());
+        }
+        (node_ids, adjacency)
+    }
+
+    #[test]
+    fn test_enumerates_cycles_sharing_nodes() {
+        // a → b → a and a → c → a share `a`; b → c → a → b shares all three;
+        // the repeated a → b import must not produce duplicates
+        let (node_ids, adjacency) = graph(&[
+            ("a", "b"), ("a", "b"), ("b", "a"),
+            ("a", "c"), ("c", "a"),
+            ("b", "c"),
+            ("d", "d"),
+        ]);
+
+        let search = elementary_cycles(&node_ids, &adjacency, DEFAULT_CYCLE_LIMIT);
+        let mut cycles: Vec<String> = search.cycles.iter().map(|cycle| cycle.join(">")).collect();
+        cycles.sort();
+
+        assert_eq!(cycles, vec!["a>b>a", "a>b>c>a", "a>c>a", "d>d"]);
+        assert!(!search.truncated);
+    }
+
+    #[test]
+    fn test_cycle_limit_truncates() {
+        // A complete graph on five nodes has far more than three cycles
+        let names = ["a", "b", "c", "d", "e"];
+        let edges: Vec<(&str, &str)> = names.iter()
+            .flat_map(|from| names.iter().filter(move |to| *to != from).map(move |to| (*from, *to)))
+            .collect();
+        let (node_ids, adjacency) = graph(&edges);
+
+        let search = elementary_cycles(&node_ids, &adjacency, 3);
+        assert_eq!(search.cycles.len(), 3);
+        assert!(search.truncated);
+
+        let complete = elementary_cycles(&node_ids, &adjacency, usize::MAX);
+        assert_eq!(complete.cycles.len(), 84);
+    }
+}
@@ -0,0 +1,555 @@
+//! Functions, types and exports that nothing in the project references
+//!
+//! Uses are counted by name across the whole project: every identifier in
+//! every parsed file, minus the definitions themselves. Calls made by the
+//! definitions are then resolved through the call graph, so a definition is
+//! live only when something outside the definitions uses it or a live
+//! definition calls it. Private helpers called only from dead code, and
+//! private functions that only call each other, are dead too. Import
+//! statements are not uses: an unused import keeps nothing alive, and a
+//! renamed one (`parse as p`) counts as a use of `parse` once `p` is used.
+//! Names are not resolved to the definition they refer to, so two unrelated
+//! `render` methods keep each other alive; the report misses some dead code
+//! rather than flagging live code.
+//!
+//! Confidence says how likely a finding really is dead:
+//! - `high`: private and never named anywhere else
+//! - `medium`: exported, so code outside the project may still use it
+//! - `low`: decorated or attributed (often registered with a framework), or
+//!   named in a string literal or symbol, as in `getattr(obj, "name")` or
+//!   `send(:name)`, where it may be invoked dynamically
+//!
+//! Entry points are never reported: `main`, tests, dunder methods,
+//! constructors and trait impl methods, which are called through the trait.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tree_sitter::Node;
+
+use crate::ast_analyzer::{walk_descendants, ASTAnalyzer, RetainedParse};
+use crate::core::Language;
+
+/// Node kinds that define a function or method
+const FUNCTION_KINDS: &[&str] = &[
+    "function_item", "function_declaration", "method_definition", "function_definition",
+    "method", "singleton_method", "method_declaration",
+];
+
+/// Node kinds that define a type
+const TYPE_KINDS: &[&str] = &[
+    "struct_item", "enum_item", "trait_item", "type_item", "union_item",
+    "class_declaration", "interface_declaration", "type_alias_declaration",
+    "class_definition", "class", "module", "type_spec",
+];
+
+/// Names invoked by a runtime, framework or language protocol rather than by project code
+const ENTRY_POINTS: &[&str] = &["main", "init", "initialize", "constructor", "setup", "teardown", "setUp", "tearDown"];
+
+/// Statements that bring names into scope without using them; `pub use` re-exports are kept as uses
+const IMPORT_KINDS: &[&str] = &["import_statement", "import_from_statement", "use_declaration", "import_declaration"];
+
+/// Rust attributes that change how a function compiles, not who calls it
+const PLAIN_ATTRIBUTES: &[&str] = &["#[inline", "#[allow", "#[warn", "#[deny", "#[must_use", "#[cfg", "#[doc", "#[deprecated", "#[cold", "#[track_caller"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Function,
+    Type,
+    /// An exported constant, static or variable
+    Export,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    /// `high`, `medium` or `low`
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "high" => Ok(Confidence::High),
+            "medium" => Ok(Confidence::Medium),
+            "low" => Ok(Confidence::Low),
+            other => anyhow::bail!("Unknown confidence '{}' (expected high, medium or low)", other),
+        }
+    }
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Confidence::High => "high",
+            Confidence::Medium => "medium",
+            Confidence::Low => "low",
+        })
+    }
+}
+
+impl fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Type => "type",
+            SymbolKind::Export => "export",
+        })
+    }
+}
+
+/// A definition with no use anywhere in the project
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub file_path: PathBuf,
+    pub line: u32,
+    pub exported: bool,
+    pub confidence: Confidence,
+    /// Why the confidence is below `high`, if it is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caveat: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeadCodeReport {
+    pub files_scanned: usize,
+    /// Files that could not be read as UTF-8 text, left out of the scan
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unreadable: Vec<PathBuf>,
+    /// Most confident first, then by path and line
+    pub symbols: Vec<DeadSymbol>,
+}
+
+impl DeadCodeReport {
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Dead code ({} symbols in {} files scanned):\n", self.symbols.len(), self.files_scanned);
+        if !self.unreadable.is_empty() {
+            text.push_str(&format!("  ({} unreadable files skipped)\n", self.unreadable.len()));
+        }
+        for symbol in &self.symbols {
+            text.push_str(&format!(
+                "  {:<7} {:<9} {}:{}  {}{}\n",
+                symbol.confidence,
+                symbol.kind,
+                symbol.file_path.display(),
+                symbol.line,
+                symbol.name,
+                symbol.caveat.as_deref().map(|caveat| format!(" ({})", caveat)).unwrap_or_default(),
+            ));
+        }
+        text
+    }
+}
+
+/// A definition found while scanning, before uses are known
+struct Definition {
+    name: String,
+    kind: SymbolKind,
+    file_path: PathBuf,
+    line: u32,
+    exported: bool,
+    decorated: bool,
+    /// Calls the function makes to its own name, which do not keep it alive
+    self_calls: usize,
+    /// Names the function calls, other than its own
+    calls: Vec<String>,
+}
+
+/// Names defined, used and mentioned across every scanned file
+#[derive(Default)]
+struct Index {
+    definitions: Vec<Definition>,
+    identifiers: HashMap<String, usize>,
+    mentioned: HashSet<String>,
+    files: usize,
+    unreadable: Vec<PathBuf>,
+}
+
+/// Parse every supported non-test file under `root` and list unreferenced definitions
+pub fn find_dead_code(root: &Path) -> Result<DeadCodeReport> {
+    let mut analyzer = ASTAnalyzer::new()?;
+    let mut index = Index::default();
+
+    for entry in ignore::Walk::new(root) {
+        let entry = entry.context("Failed to read directory entry")?;
+        let path = entry.path();
+        let language = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(Language::from_extension)
+            .unwrap_or(Language::Unknown);
+        if !path.is_file() || language == Language::Unknown {
+            continue;
+        }
+
+        // One unreadable or non-UTF-8 file should not cost the whole report
+        let Ok(content) = std::fs::read_to_string(path) else {
+            index.unreadable.push(path.to_path_buf());
+            continue;
+        };
+        // Languages without a tree-sitter grammar are skipped rather than failing the run
+        if let Ok(parse) = analyzer.analyze_file_retained(content, &language, path) {
+            index.add(&parse);
+        }
+    }
+
+    Ok(index.finish())
+}
+
+impl Index {
+    fn add(&mut self, parse: &RetainedParse) {
+        self.files += 1;
+        // Tests use code but are not themselves dead when nothing calls them
+        let defines = !is_test_path(&parse.file_path);
+        let mut identifiers: HashMap<String, usize> = HashMap::new();
+
+        walk_descendants(parse.root_node(), |node, _| {
+            let kind = node.kind();
+            if node.child_count() == 0 && (kind.ends_with("identifier") || kind == "constant") && !is_impl_target(node) && !in_import(node) {
+                *identifiers.entry(parse.node_text(node).to_string()).or_insert(0) += 1;
+            }
+            if matches!(kind, "string_content" | "string_fragment" | "string" | "simple_symbol") {
+                self.mentioned.extend(words(parse.node_text(node)));
+            }
+            if defines {
+                if let Some(definition) = definition(parse, node) {
+                    self.definitions.push(definition);
+                }
+            }
+            true
+        });
+
+        // A renamed import is a use of the original name once the local one is used
+        for (original, local) in renamed_imports(parse) {
+            if identifiers.contains_key(&local) {
+                *self.identifiers.entry(original).or_insert(0) += 1;
+            }
+        }
+        for (name, count) in identifiers {
+            *self.identifiers.entry(name).or_insert(0) += count;
+        }
+    }
+
+    /// Which definitions are reachable from a use outside the definitions, or
+    /// from one that may be used where the project cannot see
+    fn live(&self) -> Vec<bool> {
+        let mut defined: HashMap<&str, usize> = HashMap::new();
+        let mut called: HashMap<&str, usize> = HashMap::new();
+        let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (position, definition) in self.definitions.iter().enumerate() {
+            *defined.entry(definition.name.as_str()).or_insert(0) += 1;
+            by_name.entry(definition.name.as_str()).or_default().push(position);
+            for call in &definition.calls {
+                *called.entry(call.as_str()).or_insert(0) += 1;
+            }
+        }
+
+        let mut live = vec![false; self.definitions.len()];
+        let mut pending: Vec<usize> = Vec::new();
+        for (position, definition) in self.definitions.iter().enumerate() {
+            let name = definition.name.as_str();
+            let occurrences = self.identifiers.get(name).copied().unwrap_or(0);
+            let uses = occurrences.saturating_sub(defined[name] + definition.self_calls);
+            live[position] = uses > called.get(name).copied().unwrap_or(0);
+            // Possibly used from outside: whatever it calls stays alive either way
+            if live[position] || self.may_be_used_elsewhere(definition) {
+                pending.push(position);
+            }
+        }
+        while let Some(position) = pending.pop() {
+            for call in &self.definitions[position].calls {
+                for &callee in by_name.get(call.as_str()).into_iter().flatten() {
+                    if !live[callee] {
+                        live[callee] = true;
+                        pending.push(callee);
+                    }
+                }
+            }
+        }
+        live
+    }
+
+    fn may_be_used_elsewhere(&self, definition: &Definition) -> bool {
+        definition.exported || definition.decorated || self.mentioned.contains(&definition.name)
+    }
+
+    fn finish(self) -> DeadCodeReport {
+        let live = self.live();
+        let mut symbols: Vec<DeadSymbol> = self.definitions.iter()
+            .zip(&live)
+            .filter(|(_, live)| !**live)
+            .map(|(definition, _)| {
+                let (confidence, caveat) = if self.mentioned.contains(&definition.name) {
+                    (Confidence::Low, Some("named in a string or symbol; may be invoked dynamically"))
+                } else if definition.decorated {
+                    (Confidence::Low, Some("decorated; may be registered with a framework"))
+                } else if definition.exported {
+                    (Confidence::Medium, Some("exported; may be used outside the project"))
+                } else {
+                    (Confidence::High, None)
+                };
+                DeadSymbol {
+                    name: definition.name.clone(),
+                    kind: definition.kind,
+                    file_path: definition.file_path.clone(),
+                    line: definition.line,
+                    exported: definition.exported,
+                    confidence,
+                    caveat: caveat.map(str::to_string),
+                }
+            })
+            .collect();
+        symbols.sort_by(|a, b| {
+            b.confidence.cmp(&a.confidence)
+                .then_with(|| a.file_path.cmp(&b.file_path))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+
+        DeadCodeReport { files_scanned: self.files, unreadable: self.unreadable, symbols }
+    }
+}
+
+/// The function, type or exported value `node` defines, unless it is an entry point
+fn definition(parse: &RetainedParse, node: Node<'_>) -> Option<Definition> {
+    let kind = if FUNCTION_KINDS.contains(&node.kind()) {
+        SymbolKind::Function
+    } else if TYPE_KINDS.contains(&node.kind()) {
+        SymbolKind::Type
+    } else if matches!(node.kind(), "const_item" | "static_item" | "variable_declarator") {
+        SymbolKind::Export
+    } else {
+        return None;
+    };
+
+    let name = parse.node_text(node.child_by_field_name("name")?).to_string();
+    let exported = is_exported(node, &name);
+    let line = node.start_position().row as u32 + 1;
+    let attributes = attributes(parse, node);
+
+    if kind == SymbolKind::Export && !exported {
+        // Private constants and locals are left to the compiler and linters
+        return None;
+    }
+    if kind == SymbolKind::Function && is_entry_point(node, &name, &attributes) {
+        return None;
+    }
+
+    let calls: Vec<&str> = parse.functions.iter()
+        .find(|function| function.start_line == line && function.name == name)
+        .map(|function| function.calls.iter().map(|call| last_segment(call)).collect())
+        .unwrap_or_default();
+    let self_calls = calls.iter().filter(|call| **call == name).count();
+    let calls = calls.into_iter().filter(|call| *call != name).map(str::to_string).collect();
+
+    Some(Definition {
+        name,
+        kind,
+        file_path: parse.file_path.clone(),
+        line,
+        exported,
+        decorated: kind == SymbolKind::Function
+            && attributes.iter().any(|attribute| !PLAIN_ATTRIBUTES.iter().any(|plain| attribute.starts_with(plain))),
+        self_calls,
+        calls,
+    })
+}
+
+/// Rust `pub`, JavaScript `export`, or a public top-level Python name
+fn is_exported(node: Node<'_>, name: &str) -> bool {
+    let mut cursor = node.walk();
+    if node.children(&mut cursor).any(|child| child.kind() == "visibility_modifier") {
+        return true;
+    }
+    let mut ancestor = node.parent();
+    for _ in 0..3 {
+        match ancestor {
+            Some(parent) if parent.kind() == "export_statement" => return true,
+            Some(parent) => ancestor = parent.parent(),
+            None => break,
+        }
+    }
+    let container = match node.parent() {
+        Some(parent) if parent.kind() == "decorated_definition" => parent.parent(),
+        parent => parent,
+    };
+    container.is_some_and(|parent| parent.kind() == "module") && node.kind() != "module" && !name.starts_with('_')
+}
+
+/// Rust attributes, Python decorators and JavaScript decorators on `node`
+fn attributes(parse: &RetainedParse, node: Node<'_>) -> Vec<String> {
+    let mut found = Vec::new();
+    if let Some(parent) = node.parent().filter(|parent| parent.kind() == "decorated_definition") {
+        let mut cursor = parent.walk();
+        found.extend(parent.children(&mut cursor)
+            .filter(|child| child.kind() == "decorator")
+            .map(|child| parse.node_text(child).to_string()));
+    }
+    let mut sibling = node.prev_named_sibling();
+    while let Some(previous) = sibling.filter(|previous| matches!(previous.kind(), "attribute_item" | "decorator")) {
+        found.push(parse.node_text(previous).to_string());
+        sibling = previous.prev_named_sibling();
+    }
+    found
+}
+
+fn is_entry_point(node: Node<'_>, name: &str, attributes: &[String]) -> bool {
+    ENTRY_POINTS.contains(&name)
+        || name.starts_with("test") || name.starts_with("Test")
+        || (name.starts_with("__") && name.ends_with("__"))
+        || attributes.iter().any(|attribute| attribute.contains("test") || attribute.contains("bench"))
+        || in_trait_impl(node)
+}
+
+/// Methods of `impl Trait for Type`, which are called through the trait
+fn in_trait_impl(node: Node<'_>) -> bool {
+    let mut ancestor = node.parent();
+    while let Some(parent) = ancestor {
+        if parent.kind() == "impl_item" {
+            return parent.child_by_field_name("trait").is_some();
+        }
+        ancestor = parent.parent();
+    }
+    false
+}
+
+/// Inside an import statement other than a `pub use` re-export
+fn in_import(node: Node<'_>) -> bool {
+    let mut ancestor = node.parent();
+    while let Some(parent) = ancestor {
+        if IMPORT_KINDS.contains(&parent.kind()) {
+            let mut cursor = parent.walk();
+            return !parent.children(&mut cursor).any(|child| child.kind() == "visibility_modifier");
+        }
+        ancestor = parent.parent();
+    }
+    false
+}
+
+/// `(original, local)` for each name the file imports under another name
+fn renamed_imports(parse: &RetainedParse) -> Vec<(String, String)> {
+    let mut renamed = Vec::new();
+    for import in &parse.imports_exports.imports {
+        renamed.extend(import.imported_names.iter()
+            .filter_map(|name| name.split_once(" as "))
+            .map(|(original, local)| (original.to_string(), local.to_string())));
+        // Rust binds each flattened path to a local name, `use a::b as c`
+        if let Some(binding) = import.binding.as_ref().filter(|_| parse.language == Language::Rust) {
+            renamed.push((last_segment(&import.module_path).to_string(), binding.clone()));
+        }
+    }
+    renamed.retain(|(original, local)| original != local);
+    renamed
+}
+
+/// The `Foo` of `impl Foo`, which does not use `Foo` so much as extend it
+fn is_impl_target(node: Node<'_>) -> bool {
+    node.parent()
+        .filter(|parent| parent.kind() == "impl_item")
+        .and_then(|parent| parent.child_by_field_name("type"))
+        .is_some_and(|target| target == node)
+}
+
+/// Whether `path` is in a test directory or named like a test file
+///
+/// Whole segments and name affixes only, so `latest.rs` or `inspector.py` are not tests.
+pub fn is_test_path(path: &Path) -> bool {
+    let file_name = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.components().any(|component| {
+        matches!(component.as_os_str().to_str(), Some("test" | "tests" | "spec" | "__tests__"))
+    }) || file_name.starts_with("test_")
+        || file_name.ends_with("_test")
+        || file_name.ends_with("_spec")
+        || file_name.ends_with(".test")
+        || file_name.ends_with(".spec")
+}
+
+/// `obj.method`, `Type::method` and `method` all name `method`
+fn last_segment(call: &str) -> &str {
+    call.rsplit(['.', ':']).next().unwrap_or(call)
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_unreferenced_definitions_with_confidence() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("app.py"), r#"
+import helpers
+from helpers import total as sum_all, stale
+
+def _unused(n):
+    return _unused(n - _only_from_dead())
+
+def _only_from_dead():
+    return 1
+
+def _ping():
+    return _pong()
+
+def _pong():
+    return _ping()
+
+def _used():
+    return 1
+
+class _Orphan:
+    pass
+
+def handler():
+    return getattr(helpers, "dispatch")()
+
+def public_api():
+    return _used() + sum_all()
+
+@app.route("/")
+def index():
+    return "ok"
+"#).unwrap();
+        std::fs::write(temp_dir.path().join("helpers.py"), "def dispatch():\n    return 2\n\ndef total():\n    return 3\n\ndef stale():\n    return 4\n").unwrap();
+        std::fs::write(temp_dir.path().join("latin1.py"), b"# caf\xe9\ndef _lost():\n    pass\n").unwrap();
+
+        let report = find_dead_code(temp_dir.path()).unwrap();
+        let found: Vec<(&str, Confidence)> = report.symbols.iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.confidence))
+            .collect();
+
+        assert_eq!(report.files_scanned, 2);
+        assert_eq!(report.unreadable, vec![temp_dir.path().join("latin1.py")]);
+        assert!(found.contains(&("_unused", Confidence::High)), "recursion is not a use: {:?}", found);
+        assert!(found.contains(&("_only_from_dead", Confidence::High)), "called only from dead code: {:?}", found);
+        assert!(found.contains(&("_ping", Confidence::High)) && found.contains(&("_pong", Confidence::High)));
+        assert!(found.contains(&("stale", Confidence::Medium)), "an unused import is not a use");
+        assert!(!found.iter().any(|(name, _)| *name == "total"), "used through its alias");
+        assert!(found.contains(&("_Orphan", Confidence::High)));
+        assert!(found.contains(&("public_api", Confidence::Medium)));
+        assert!(found.contains(&("dispatch", Confidence::Low)));
+        assert!(found.contains(&("index", Confidence::Low)));
+        assert!(!found.iter().any(|(name, _)| *name == "_used"));
+        assert_eq!(report.symbols[0].confidence, Confidence::High);
+    }
+
+    #[test]
+    fn test_test_paths_match_segments_and_affixes() {
+        for test in ["tests/cart.rs", "src/__tests__/cart.js", "cart_test.go", "cart.spec.ts", "test_cart.py", "spec/cart_spec.rb"] {
+            assert!(is_test_path(Path::new(test)), "{}", test);
+        }
+        for source in ["src/latest.rs", "src/inspector.py", "src/contest/entry.js", "specs.rs"] {
+            assert!(!is_test_path(Path::new(source)), "{}", source);
+        }
+    }
+}
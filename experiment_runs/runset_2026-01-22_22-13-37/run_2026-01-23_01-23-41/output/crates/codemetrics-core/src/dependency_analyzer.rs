@@ -0,0 +1,1551 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use crate::core::types::{DependencyGraph, DependencyNode, DependencyEdge, ImportType, Language};
+use crate::ast_analyzer::{ASTAnalyzer, ImportExportAnalysis, ImportInfo, ExportInfo};
+use crate::cargo_workspace::{CargoWorkspace, WorkspaceCrate};
+use crate::js_project::{normalize, JsProject};
+use crate::python_layout::PythonLayout;
+use crate::cycles::{self, DEFAULT_CYCLE_LIMIT};
+
+/// Analyzes dependencies between modules to build a comprehensive dependency graph
+pub struct DependencyAnalyzer {
+    root_path: PathBuf,
+    module_registry: HashMap<String, ModuleInfo>,
+    /// Most cycles reported per granularity
+    cycle_limit: usize,
+    /// Crate boundaries and dependencies for Rust, when `cargo metadata` was available
+    cargo: Option<CargoWorkspace>,
+    /// tsconfig `paths`/`baseUrl` and local package `exports` for JS/TS
+    js_project: Option<JsProject>,
+    /// Source roots and top-level packages, inferred from the analyzed Python files
+    python_layout: PythonLayout,
+}
+
+/// Internal representation of a module's metadata
+#[derive(Debug, Clone)]
+struct ModuleInfo {
+    file_path: PathBuf,
+    module_name: String,
+    language: Language,
+    imports: Vec<ImportInfo>,
+    exports: Vec<ExportInfo>,
+    external_dependencies: HashSet<String>,
+}
+
+/// Comprehensive dependency analysis result
+#[derive(Debug)]
+pub struct DependencyAnalysisResult {
+    pub root_path: PathBuf,
+    pub graph: DependencyGraph,
+    pub circular_dependencies: Vec<CircularDependency>,
+    /// Cycles between packages (directories), which file-level detection misses
+    /// when they are formed by many edges between different files
+    pub package_cycles: Vec<CircularDependency>,
+    /// Set when either cycle list was cut off at the cycle limit
+    pub cycles_truncated: bool,
+    pub unused_exports: Vec<UnusedExport>,
+    pub external_dependencies: HashMap<String, u32>, // dependency name -> usage count
+    pub external_imports: HashMap<String, Vec<String>>, // module name -> external packages it imports
+    pub dependency_depth: HashMap<String, u32>,
+    pub module_coupling: Vec<ModuleCoupling>,
+    /// Workspace crates, features and resolved dependency versions
+    pub cargo: Option<CargoWorkspace>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CircularDependency {
+    pub cycle: Vec<String>,
+    pub severity: CycleSeverity,
+    pub granularity: CycleGranularity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CycleGranularity {
+    Module,
+    Package,
+}
+
+#[derive(Debug, Serialize)]
+pub enum CycleSeverity {
+    Low,    // Self-reference or 2-node cycle
+    Medium, // 3-4 node cycle
+    High,   // 5+ node cycle
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnusedExport {
+    pub module_name: String,
+    pub export_name: String,
+    pub file_path: PathBuf,
+    pub line: u32,
+}
+
+/// Every internal import of one external package, for upgrade/removal planning
+#[derive(Debug, Serialize)]
+pub struct ExternalUsage {
+    pub package: String,
+    pub importers: Vec<ExternalImporter>,
+    /// Symbol -> number of modules importing it
+    pub symbols: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExternalImporter {
+    pub module_name: String,
+    pub file_path: PathBuf,
+    pub line: u32,
+    pub symbols: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ModuleCoupling {
+    pub module_name: String,
+    pub afferent_coupling: u32,  // Number of modules that depend on this module
+    pub efferent_coupling: u32,  // Number of modules this module depends on
+    pub instability: f64,        // Efferent / (Afferent + Efferent)
+    pub abstractness: f64,       // Abstract classes/interfaces / Total classes
+}
+
+impl DependencyAnalyzer {
+    pub fn new(root_path: PathBuf) -> Self {
+        Self {
+            root_path,
+            module_registry: HashMap::new(),
+            cycle_limit: DEFAULT_CYCLE_LIMIT,
+            cargo: None,
+            js_project: None,
+            python_layout: PythonLayout::default(),
+        }
+    }
+
+    /// Stop enumerating cycles after `limit` per granularity
+    pub fn with_cycle_limit(mut self, limit: usize) -> Self {
+        self.cycle_limit = limit;
+        self
+    }
+
+    /// Resolve Rust `use` paths against real crate boundaries instead of path prefixes
+    pub fn with_cargo_workspace(mut self, workspace: CargoWorkspace) -> Self {
+        self.cargo = Some(workspace);
+        self
+    }
+
+    /// Resolve JS/TS imports through tsconfig aliases and local package names
+    pub fn with_js_project(mut self, project: JsProject) -> Self {
+        self.js_project = Some(project);
+        self
+    }
+
+    /// Perform comprehensive dependency analysis
+    pub fn analyze(&mut self, import_export_data: HashMap<PathBuf, (Language, ImportExportAnalysis)>) -> Result<DependencyAnalysisResult> {
+        // Step 1: Build module registry
+        self.python_layout = PythonLayout::from_files(&self.root_path, import_export_data.keys());
+        self.build_module_registry(import_export_data)?;
+
+        // Step 2: Resolve module paths and build dependency graph
+        let graph = self.build_dependency_graph()?;
+
+        // Step 3: Detect circular dependencies, per module and per package
+        let (circular_dependencies, module_cycles_truncated) = self.detect_circular_dependencies(&graph)?;
+        let (package_cycles, package_cycles_truncated) = self.detect_package_cycles(&graph)?;
+
+        // Step 4: Find unused exports
+        let unused_exports = self.find_unused_exports(&graph)?;
+
+        // Step 5: Analyze external dependencies
+        let external_dependencies = self.analyze_external_dependencies()?;
+        let external_imports = self.module_registry.iter()
+            .filter(|(_, info)| !info.external_dependencies.is_empty())
+            .map(|(name, info)| {
+                let mut packages: Vec<String> = info.external_dependencies.iter().cloned().collect();
+                packages.sort();
+                (name.clone(), packages)
+            })
+            .collect();
+
+        // Step 6: Calculate dependency depths
+        let dependency_depth = self.calculate_dependency_depths(&graph)?;
+
+        // Step 7: Calculate module coupling metrics
+        let module_coupling = self.calculate_module_coupling(&graph)?;
+
+        Ok(DependencyAnalysisResult {
+            root_path: self.root_path.clone(),
+            graph,
+            circular_dependencies,
+            package_cycles,
+            cycles_truncated: module_cycles_truncated || package_cycles_truncated,
+            unused_exports,
+            external_dependencies,
+            external_imports,
+            dependency_depth,
+            module_coupling,
+            cargo: self.cargo.clone(),
+        })
+    }
+
+    /// Which modules import `package` and which of its symbols they use
+    ///
+    /// Must be called after `analyze`. Submodule imports such as `lodash/fp`
+    /// or `serde::de` count towards their top-level package.
+    pub fn external_usage(&self, package: &str) -> ExternalUsage {
+        let mut importers = Vec::new();
+        let mut symbols: BTreeMap<String, u32> = BTreeMap::new();
+
+        for (module_name, module_info) in &self.module_registry {
+            for import in &module_info.imports {
+                if !self.is_external_dependency(&import.module_path, &module_info.language)
+                    || self.external_package(&import.module_path, &module_info.language) != package
+                {
+                    continue;
+                }
+
+                let mut used = import.imported_names.clone();
+                if used.is_empty() {
+                    // `use serde::Deserialize` / `import numpy.linalg` name the symbol in the path
+                    let root = package_root(&import.module_path, &module_info.language);
+                    let remainder = import.module_path[root.len()..]
+                        .trim_start_matches([':', '.', '/']);
+                    used.push(if remainder.is_empty() { "*".to_string() } else { remainder.to_string() });
+                }
+                for symbol in &used {
+                    *symbols.entry(symbol.clone()).or_insert(0) += 1;
+                }
+
+                importers.push(ExternalImporter {
+                    module_name: module_name.clone(),
+                    file_path: module_info.file_path.clone(),
+                    line: import.line,
+                    symbols: used,
+                });
+            }
+        }
+
+        importers.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+        ExternalUsage { package: package.to_string(), importers, symbols }
+    }
+
+    /// Build a registry of all modules with their import/export information
+    fn build_module_registry(&mut self, data: HashMap<PathBuf, (Language, ImportExportAnalysis)>) -> Result<()> {
+        for (file_path, (language, import_export_analysis)) in data {
+            let module_name = self.path_to_module_name(&file_path, &language);
+
+            let mut external_dependencies = HashSet::new();
+
+            // Identify external vs internal dependencies
+            for import in &import_export_analysis.imports {
+                if self.is_external_dependency(&import.module_path, &language) {
+                    // With cargo metadata, Rust crates are counted under their package name
+                    external_dependencies.insert(match (&language, &self.cargo) {
+                        (Language::Rust, Some(_)) => self.external_package(&import.module_path, &language),
+                        _ => import.module_path.clone(),
+                    });
+                }
+            }
+
+            let module_info = ModuleInfo {
+                file_path: file_path.clone(),
+                module_name: module_name.clone(),
+                language,
+                imports: import_export_analysis.imports,
+                exports: import_export_analysis.exports,
+                external_dependencies,
+            };
+
+            self.module_registry.insert(module_name, module_info);
+        }
+
+        Ok(())
+    }
+
+    /// Build the dependency graph from the module registry
+    fn build_dependency_graph(&mut self) -> Result<DependencyGraph> {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+
+        // Create nodes
+        for (module_name, module_info) in &self.module_registry {
+            let node = DependencyNode {
+                id: module_name.clone(),
+                file_path: module_info.file_path.clone(),
+                module_name: module_name.clone(),
+                exports: module_info.exports.iter().map(|e| e.name.clone()).collect(),
+            };
+            nodes.push(node);
+        }
+
+        // Create edges
+        for (module_name, module_info) in &self.module_registry {
+            for import in &module_info.imports {
+                if let Some(target_module) = self.resolve_import_to_module(import, module_info) {
+                    let edge = DependencyEdge {
+                        from: module_name.clone(),
+                        to: target_module,
+                        import_type: if import.is_default {
+                            ImportType::Default
+                        } else if import.imported_names.is_empty() {
+                            ImportType::Namespace
+                        } else {
+                            ImportType::Named
+                        },
+                        imported_symbols: import.imported_names.clone(),
+                    };
+                    edges.push(edge);
+                }
+            }
+        }
+
+        Ok(DependencyGraph { nodes, edges })
+    }
+
+    /// Enumerate distinct circular dependencies, up to the cycle limit
+    ///
+    /// The flag is set when more cycles exist than were returned.
+    fn detect_circular_dependencies(&self, graph: &DependencyGraph) -> Result<(Vec<CircularDependency>, bool)> {
+        // Build adjacency list
+        let mut adjacency = HashMap::new();
+        for edge in &graph.edges {
+            adjacency.entry(edge.from.clone())
+                .or_insert_with(Vec::new)
+                .push(edge.to.clone());
+        }
+
+        let node_ids: Vec<String> = graph.nodes.iter().map(|node| node.id.clone()).collect();
+        let search = cycles::elementary_cycles(&node_ids, &adjacency, self.cycle_limit);
+        let circular_deps = search.cycles
+            .into_iter()
+            .map(|cycle| {
+                // The cycle path repeats its first module at the end
+                let severity = match cycle.len() - 1 {
+                    0..=2 => CycleSeverity::Low,
+                    3..=4 => CycleSeverity::Medium,
+                    _ => CycleSeverity::High,
+                };
+                CircularDependency { cycle, severity, granularity: CycleGranularity::Module }
+            })
+            .collect();
+
+        Ok((circular_deps, search.truncated))
+    }
+
+    /// Detect cycles between packages by collapsing modules to their directory
+    fn detect_package_cycles(&self, graph: &DependencyGraph) -> Result<(Vec<CircularDependency>, bool)> {
+        let packages = CollapsedGraph::from_graph(graph, &self.root_path, GraphView::Directory { depth: usize::MAX });
+
+        let mut adjacency = HashMap::new();
+        for edge in &packages.edges {
+            adjacency.entry(edge.from.clone())
+                .or_insert_with(Vec::new)
+                .push(edge.to.clone());
+        }
+
+        let package_ids: Vec<String> = packages.nodes.iter().map(|node| node.id.clone()).collect();
+        let search = cycles::elementary_cycles(&package_ids, &adjacency, self.cycle_limit);
+        let package_cycles = search.cycles
+            .into_iter()
+            .map(|cycle| {
+                // Package cycles are architectural problems, so they rank one step higher
+                let severity = match cycle.len() - 1 {
+                    0..=2 => CycleSeverity::Medium,
+                    _ => CycleSeverity::High,
+                };
+                CircularDependency { cycle, severity, granularity: CycleGranularity::Package }
+            })
+            .collect();
+
+        Ok((package_cycles, search.truncated))
+    }
+
+    /// Find exports that are never imported by any module
+    fn find_unused_exports(&self, graph: &DependencyGraph) -> Result<Vec<UnusedExport>> {
+        let mut used_symbols = HashSet::new();
+
+        // Collect all imported symbols
+        for edge in &graph.edges {
+            for symbol in &edge.imported_symbols {
+                used_symbols.insert(format!("{}::{}", edge.to, symbol));
+            }
+        }
+
+        let mut unused_exports = Vec::new();
+
+        // Find unused exports
+        for (module_name, module_info) in &self.module_registry {
+            for export in &module_info.exports {
+                let symbol_key = format!("{}::{}", module_name, export.name);
+                if !used_symbols.contains(&symbol_key) && !export.is_default {
+                    unused_exports.push(UnusedExport {
+                        module_name: module_name.clone(),
+                        export_name: export.name.clone(),
+                        file_path: module_info.file_path.clone(),
+                        line: export.line,
+                    });
+                }
+            }
+        }
+
+        Ok(unused_exports)
+    }
+
+    /// Analyze usage of external dependencies
+    fn analyze_external_dependencies(&self) -> Result<HashMap<String, u32>> {
+        let mut external_deps = HashMap::new();
+
+        for module_info in self.module_registry.values() {
+            for dep in &module_info.external_dependencies {
+                *external_deps.entry(dep.clone()).or_insert(0) += 1;
+            }
+        }
+
+        Ok(external_deps)
+    }
+
+    /// Calculate dependency depth for each module (longest distance to a leaf)
+    ///
+    /// Using the longest rather than the shortest distance guarantees every
+    /// acyclic edge points from a higher layer to a lower one. Edges that close
+    /// a cycle are ignored.
+    fn calculate_dependency_depths(&self, graph: &DependencyGraph) -> Result<HashMap<String, u32>> {
+        let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &graph.edges {
+            adjacency.entry(edge.from.as_str()).or_default().push(edge.to.as_str());
+        }
+
+        let mut longest: HashMap<&str, u32> = HashMap::new();
+        let mut in_progress: HashSet<&str> = HashSet::new();
+        let depths = graph.nodes.iter()
+            .map(|node| {
+                let chain = chain_length(&node.id, &adjacency, &mut longest, &mut in_progress);
+                (node.id.clone(), chain.saturating_sub(1))
+            })
+            .collect();
+
+        Ok(depths)
+    }
+
+    /// Calculate coupling metrics for each module
+    fn calculate_module_coupling(&self, graph: &DependencyGraph) -> Result<Vec<ModuleCoupling>> {
+        let mut coupling_metrics = Vec::new();
+        let mut afferent = HashMap::new();
+        let mut efferent = HashMap::new();
+
+        // Count incoming and outgoing dependencies
+        for edge in &graph.edges {
+            *efferent.entry(edge.from.clone()).or_insert(0) += 1;
+            *afferent.entry(edge.to.clone()).or_insert(0) += 1;
+        }
+
+        for node in &graph.nodes {
+            let afferent_coupling = *afferent.get(&node.id).unwrap_or(&0);
+            let efferent_coupling = *efferent.get(&node.id).unwrap_or(&0);
+
+            let instability = if afferent_coupling + efferent_coupling > 0 {
+                efferent_coupling as f64 / (afferent_coupling + efferent_coupling) as f64
+            } else {
+                0.0
+            };
+
+            // Simplified abstractness calculation - would need more sophisticated analysis
+            let abstractness = 0.0; // TODO: Implement proper abstractness calculation
+
+            coupling_metrics.push(ModuleCoupling {
+                module_name: node.id.clone(),
+                afferent_coupling,
+                efferent_coupling,
+                instability,
+                abstractness,
+            });
+        }
+
+        Ok(coupling_metrics)
+    }
+
+    // Helper methods
+    fn path_to_module_name(&self, file_path: &Path, language: &Language) -> String {
+        // Convert file path to module name based on language conventions
+        let relative_path = file_path.strip_prefix(&self.root_path).unwrap_or(file_path);
+        let path_str = relative_path.to_string_lossy();
+
+        match language {
+            Language::JavaScript | Language::TypeScript => {
+                path_str.trim_end_matches(".js")
+                    .trim_end_matches(".ts")
+                    .trim_end_matches(".jsx")
+                    .trim_end_matches(".tsx")
+                    .replace('/', "::")
+                    .to_string()
+            }
+            Language::Rust => {
+                path_str.trim_end_matches(".rs")
+                    .replace('/', "::")
+                    .to_string()
+            }
+            Language::Python => {
+                path_str.trim_end_matches(".py")
+                    .replace('/', ".")
+                    .to_string()
+            }
+            Language::Ruby => path_str.trim_end_matches(".rb").to_string(),
+            _ => path_str.to_string(),
+        }
+    }
+
+    fn is_external_dependency(&self, import_path: &str, language: &Language) -> bool {
+        match language {
+            Language::JavaScript | Language::TypeScript if self.js_project.is_some() => {
+                // Aliases and workspace packages look like npm packages but are internal
+                !self.js_project.as_ref().is_some_and(|project| project.is_internal(import_path))
+            }
+            Language::JavaScript | Language::TypeScript => {
+                // External if it doesn't start with ./ or ../
+                !import_path.starts_with("./") && !import_path.starts_with("../")
+            }
+            Language::Rust if self.cargo.is_some() => {
+                // External only if the first segment names a declared dependency
+                let root = package_root(import_path.trim_start_matches("::"), language);
+                self.cargo.as_ref().is_some_and(|cargo| cargo.dependency(&root).is_some())
+            }
+            Language::Rust => {
+                // External if it doesn't start with crate:: or super:: or self::
+                !import_path.starts_with("crate::")
+                    && !import_path.starts_with("super::")
+                    && !import_path.starts_with("self::")
+            }
+            Language::Python => {
+                // External unless relative or rooted at one of the project's own packages
+                !import_path.starts_with('.') && !self.python_layout.is_internal(import_path)
+            }
+            Language::Ruby => {
+                // `require_relative`, or a `require` of a file under the project's `lib/`
+                !import_path.starts_with('.') && self.ruby_load_path_file(import_path).is_none()
+            }
+            _ => true,
+        }
+    }
+
+    /// Package an external import is reported under
+    fn external_package(&self, import_path: &str, language: &Language) -> String {
+        let root = package_root(import_path.trim_start_matches("::"), language);
+        match (language, &self.cargo) {
+            (Language::Rust, Some(cargo)) => cargo.dependency(&root)
+                .map(|dependency| dependency.package.clone())
+                .unwrap_or(root),
+            _ => root,
+        }
+    }
+
+    fn resolve_import_to_module(&self, import: &ImportInfo, importer: &ModuleInfo) -> Option<String> {
+        let import_path = import.module_path.as_str();
+        if importer.language == Language::Python {
+            return self.python_layout.candidates(&importer.file_path, import_path, &import.imported_names)
+                .into_iter()
+                .map(|file| self.path_to_module_name(&file, &importer.language))
+                .find(|module| *module != importer.module_name && self.module_registry.contains_key(module));
+        }
+        if let (Language::Rust, Some(cargo)) = (&importer.language, &self.cargo) {
+            return self.resolve_rust_path(import, importer, cargo);
+        }
+        if importer.language == Language::Ruby {
+            let file = match import_path.starts_with('.') {
+                true => importer.file_path.parent()?.join(format!("{}.rb", import_path.trim_end_matches(".rb"))),
+                false => self.ruby_load_path_file(import_path)?,
+            };
+            let module = self.path_to_module_name(&normalize(&file), &importer.language);
+            return (module != importer.module_name && self.module_registry.contains_key(&module)).then_some(module);
+        }
+        if let (Language::JavaScript | Language::TypeScript, Some(project)) = (&importer.language, &self.js_project) {
+            let file = project.resolve(&importer.file_path, import_path)?;
+            let module = self.path_to_module_name(&file, &importer.language);
+            return (module != importer.module_name && self.module_registry.contains_key(&module)).then_some(module);
+        }
+
+        // This would implement sophisticated module resolution logic
+        // For now, return a simplified version
+        if self.is_external_dependency(import_path, &importer.language) {
+            None
+        } else {
+            Some(import_path.to_string())
+        }
+    }
+
+    /// Project file a plain `require` loads, looking in `lib/` as Bundler and Rails set up the load path
+    fn ruby_load_path_file(&self, import_path: &str) -> Option<PathBuf> {
+        ["lib", "app", ""].iter()
+            .map(|directory| self.root_path.join(directory).join(format!("{}.rb", import_path.trim_end_matches(".rb"))))
+            .find(|file| file.is_file())
+    }
+
+    /// Map a `use` path to the registered module that defines it
+    ///
+    /// `crate::`, `self::`, `super::`, other workspace crates and uniform paths
+    /// to child modules are followed; the longest prefix naming a module wins.
+    /// Inside an inline `mod tests { ... }`, `super` first climbs out of the
+    /// inline module, so `use super::*` stays in the file that holds it.
+    /// External and standard crates resolve to nothing.
+    fn resolve_rust_path(&self, import: &ImportInfo, importer: &ModuleInfo, cargo: &CargoWorkspace) -> Option<String> {
+        let segments: Vec<&str> = import.module_path.trim_start_matches("::").split("::").collect();
+        let first = *segments.first()?;
+
+        // The module a file defines: `foo/mod.rs`, `lib.rs` and `main.rs` stand for their directory
+        let mut current: Vec<String> = importer.module_name.split("::").map(str::to_string).collect();
+        if matches!(current.last().map(String::as_str), Some("mod" | "lib" | "main")) {
+            current.pop();
+        }
+        current.extend(import.inline_modules.iter().cloned());
+
+        let (mut base, rest) = match first {
+            "crate" => (self.crate_module_prefix(cargo.crate_for_file(&importer.file_path)?), &segments[1..]),
+            "self" => (current, &segments[1..]),
+            "super" => {
+                let supers = segments.iter().take_while(|segment| **segment == "super").count();
+                current.truncate(current.len().saturating_sub(supers));
+                (current, &segments[supers..])
+            }
+            name if cargo.dependency(name).is_some() || CargoWorkspace::is_standard_crate(name) => return None,
+            name => match cargo.workspace_crate(name) {
+                Some(krate) => (self.crate_module_prefix(krate), &segments[1..]),
+                None => (current, &segments[..]),
+            },
+        };
+
+        let base_len = base.len();
+        base.extend(rest.iter().map(|segment| segment.to_string()));
+        for end in (base_len..=base.len()).rev() {
+            let prefix = base[..end].join("::");
+            let mut candidates = vec![format!("{}::mod", prefix), prefix.clone()];
+            if end == base_len {
+                // Items defined directly in a crate root
+                candidates.extend([format!("{}::lib", prefix), format!("{}::main", prefix)]);
+            }
+            if let Some(found) = candidates.into_iter()
+                .map(|candidate| candidate.trim_start_matches("::").to_string())
+                .find(|candidate| self.module_registry.contains_key(candidate))
+            {
+                return (found != importer.module_name).then_some(found);
+            }
+        }
+
+        None
+    }
+
+    /// Module-name segments of a crate's source directory, relative to the analyzed root
+    fn crate_module_prefix(&self, krate: &WorkspaceCrate) -> Vec<String> {
+        let root = std::fs::canonicalize(&self.root_path).unwrap_or_else(|_| self.root_path.clone());
+        let relative = krate.src_root.strip_prefix(&root).unwrap_or(&krate.src_root);
+        relative.components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect()
+    }
+}
+
+impl DependencyAnalysisResult {
+    /// The module graph aggregated to the granularity selected by `view`
+    pub fn collapsed(&self, view: GraphView) -> CollapsedGraph {
+        CollapsedGraph::from_graph(&self.graph, &self.root_path, view)
+    }
+
+    /// Graphviz DOT of the module graph, nodes filled by instability and edges styled by import type
+    ///
+    /// Named imports are solid, default imports bold, namespace imports dashed
+    /// and side-effect imports dotted.
+    pub fn to_dot(&self) -> String {
+        let instability = self.instability_by_module();
+        let mut dot = String::from("digraph dependencies {\n    rankdir=LR;\n    node [shape=box, style=filled];\n");
+        for node in &self.graph.nodes {
+            let (band, color) = stability_band(instability.get(node.id.as_str()).copied());
+            dot.push_str(&format!(
+                "    \"{}\" [fillcolor=\"{}\", tooltip=\"{}\"];\n",
+                dot_escape(&node.id), color, band
+            ));
+        }
+        for edge in &self.graph.edges {
+            let style = match edge.import_type {
+                ImportType::Named => "solid",
+                ImportType::Default => "bold",
+                ImportType::Namespace => "dashed",
+                ImportType::Side => "dotted",
+            };
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\" [style={}];\n",
+                dot_escape(&edge.from), dot_escape(&edge.to), style
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Mermaid flowchart of the module graph, styled like `to_dot`
+    ///
+    /// Mermaid has one dotted line style, so namespace and side-effect imports
+    /// are told apart by a label.
+    pub fn to_mermaid(&self) -> String {
+        let instability = self.instability_by_module();
+        let ids: HashMap<&str, String> = self.graph.nodes.iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), format!("n{}", i)))
+            .collect();
+
+        let mut mermaid = String::from("graph LR\n");
+        let mut bands: BTreeMap<(&str, &str), Vec<&str>> = BTreeMap::new();
+        for node in &self.graph.nodes {
+            let id = &ids[node.id.as_str()];
+            mermaid.push_str(&format!("    {}[\"{}\"]\n", id, node.id.replace('"', "#quot;")));
+            bands.entry(stability_band(instability.get(node.id.as_str()).copied())).or_default().push(id);
+        }
+        for edge in &self.graph.edges {
+            let (Some(from), Some(to)) = (ids.get(edge.from.as_str()), ids.get(edge.to.as_str())) else {
+                continue;
+            };
+            let arrow = match edge.import_type {
+                ImportType::Named => "-->",
+                ImportType::Default => "==>",
+                ImportType::Namespace => "-. namespace .->",
+                ImportType::Side => "-. side effect .->",
+            };
+            mermaid.push_str(&format!("    {} {} {}\n", from, arrow, to));
+        }
+        for ((band, color), nodes) in &bands {
+            mermaid.push_str(&format!("    classDef {} fill:{}\n", band, color));
+            mermaid.push_str(&format!("    class {} {}\n", nodes.join(","), band));
+        }
+        mermaid
+    }
+
+    fn instability_by_module(&self) -> HashMap<&str, f64> {
+        self.module_coupling.iter()
+            .map(|coupling| (coupling.module_name.as_str(), coupling.instability))
+            .collect()
+    }
+
+    /// Modules grouped into layers by `dependency_depth`, leaves first
+    ///
+    /// Within each layer modules are ordered by the barycenter of the modules
+    /// they depend on in lower layers, which keeps edge crossings down in the
+    /// rendered view the same way a Sugiyama layout does.
+    pub fn layers(&self) -> LayeredView {
+        let mut layers: Vec<Vec<String>> = Vec::new();
+        for node in &self.graph.nodes {
+            let depth = *self.dependency_depth.get(&node.id).unwrap_or(&0) as usize;
+            if layers.len() <= depth {
+                layers.resize(depth + 1, Vec::new());
+            }
+            layers[depth].push(node.id.clone());
+        }
+
+        let mut dependencies: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.graph.edges {
+            dependencies.entry(edge.from.as_str()).or_default().push(edge.to.as_str());
+        }
+
+        let mut position: HashMap<String, f64> = HashMap::new();
+        for layer in &mut layers {
+            layer.sort();
+            let barycenter = |module: &String| {
+                let placed: Vec<f64> = dependencies.get(module.as_str())
+                    .map(|deps| deps.iter().filter_map(|d| position.get(*d).copied()).collect())
+                    .unwrap_or_default();
+                if placed.is_empty() {
+                    f64::MAX
+                } else {
+                    placed.iter().sum::<f64>() / placed.len() as f64
+                }
+            };
+            layer.sort_by(|a, b| barycenter(a).total_cmp(&barycenter(b)));
+            for (i, module) in layer.iter().enumerate() {
+                position.insert(module.clone(), i as f64);
+            }
+        }
+
+        LayeredView {
+            layers,
+            max_chain_length: self.max_chain_length(),
+        }
+    }
+
+    /// Number of modules on the longest import chain
+    pub fn max_chain_length(&self) -> u32 {
+        self.dependency_depth.values().max().map_or(0, |depth| depth + 1)
+    }
+}
+
+/// Modules on the longest chain starting at `start`, memoized in `longest`
+///
+/// Iterative post-order DFS; dependencies still in progress close a cycle and
+/// contribute nothing to the chain.
+fn chain_length<'a>(
+    start: &'a str,
+    adjacency: &HashMap<&'a str, Vec<&'a str>>,
+    longest: &mut HashMap<&'a str, u32>,
+    in_progress: &mut HashSet<&'a str>,
+) -> u32 {
+    if let Some(&length) = longest.get(start) {
+        return length;
+    }
+
+    in_progress.insert(start);
+    let mut stack: Vec<(&'a str, usize)> = vec![(start, 0)];
+
+    while let Some((node, next)) = stack.last_mut() {
+        let node = *node;
+        let dependencies = adjacency.get(node).map(Vec::as_slice).unwrap_or(&[]);
+
+        if *next < dependencies.len() {
+            let dependency = dependencies[*next];
+            *next += 1;
+            if !longest.contains_key(dependency) && in_progress.insert(dependency) {
+                stack.push((dependency, 0));
+            }
+        } else {
+            let below = dependencies.iter()
+                .map(|dependency| longest.get(dependency).copied().unwrap_or(0))
+                .max()
+                .unwrap_or(0);
+            in_progress.remove(node);
+            longest.insert(node, below + 1);
+            stack.pop();
+        }
+    }
+
+    longest[start]
+}
+
+/// Modules arranged in dependency layers, suitable for a layered drawing
+#[derive(Debug, Clone, Serialize)]
+pub struct LayeredView {
+    /// `layers[0]` holds modules with no internal dependencies
+    pub layers: Vec<Vec<String>>,
+    pub max_chain_length: u32,
+}
+
+impl LayeredView {
+    pub fn to_text(&self) -> String {
+        let mut output = String::new();
+        output.push_str("\n🏛️  Dependency Layers\n");
+        output.push_str("====================\n\n");
+        output.push_str(&format!(
+            "{} layers, longest import chain: {} modules\n\n",
+            self.layers.len(), self.max_chain_length
+        ));
+
+        for (depth, layer) in self.layers.iter().enumerate().rev() {
+            output.push_str(&format!("L{:<3} {}\n", depth, layer.join("  ")));
+        }
+
+        output
+    }
+}
+
+impl ExternalUsage {
+    pub fn to_text(&self) -> String {
+        let mut output = String::new();
+        let modules: HashSet<&str> = self.importers.iter().map(|i| i.module_name.as_str()).collect();
+
+        output.push_str(&format!("\n📦 External Dependency: {}\n", self.package));
+        output.push_str("==========================\n\n");
+        output.push_str(&format!(
+            "Imported {} times by {} modules, using {} distinct symbols\n\n",
+            self.importers.len(), modules.len(), self.symbols.len()
+        ));
+
+        output.push_str("Importers:\n");
+        for importer in &self.importers {
+            output.push_str(&format!(
+                "  {}:{}  {}\n",
+                importer.file_path.display(), importer.line, importer.symbols.join(", ")
+            ));
+        }
+
+        output.push_str("\nSymbols:\n");
+        let mut symbols: Vec<_> = self.symbols.iter().collect();
+        symbols.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (symbol, count) in symbols {
+            output.push_str(&format!("  {:<40} {}\n", symbol, count));
+        }
+
+        output
+    }
+}
+
+/// Top-level package an external import belongs to
+pub fn package_root(import_path: &str, language: &Language) -> String {
+    match language {
+        Language::JavaScript | Language::TypeScript => {
+            let mut segments = import_path.split('/');
+            match (segments.next(), segments.next()) {
+                (Some(scope), Some(name)) if scope.starts_with('@') => format!("{}/{}", scope, name),
+                (Some(name), _) => name.to_string(),
+                _ => import_path.to_string(),
+            }
+        }
+        Language::Rust => import_path.split("::").next().unwrap_or(import_path).to_string(),
+        Language::Python => import_path.split('.').next().unwrap_or(import_path).to_string(),
+        Language::Ruby => import_path.split('/').next().unwrap_or(import_path).to_string(),
+        _ => import_path.to_string(),
+    }
+}
+
+/// Parse every supported file under `root` and collect its imports and exports
+pub fn collect_import_data(root: &Path) -> Result<HashMap<PathBuf, (Language, ImportExportAnalysis)>> {
+    let mut ast_analyzer = ASTAnalyzer::new()?;
+    let mut data = HashMap::new();
+
+    for entry in ignore::Walk::new(root) {
+        let entry = entry.context("Failed to read directory entry")?;
+        let path = entry.path();
+        let language = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(Language::from_extension)
+            .unwrap_or(Language::Unknown);
+        if !path.is_file() || language == Language::Unknown {
+            continue;
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?;
+        // TypeScript's imports are JavaScript's, and the JavaScript grammar recovers around type annotations
+        let grammar = if language == Language::TypeScript { Language::JavaScript } else { language.clone() };
+        // Languages without a tree-sitter grammar are skipped rather than failing the run
+        if let Ok((_, _, _, imports_exports)) = ast_analyzer.analyze_file(&content, &grammar, path) {
+            data.insert(path.to_path_buf(), (language, imports_exports));
+        }
+    }
+
+    Ok(data)
+}
+
+/// Granularity at which the dependency graph is presented
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum GraphView {
+    /// One node per module
+    #[default]
+    Module,
+    /// One node per directory, truncated to `depth` path components
+    Directory { depth: usize },
+}
+
+/// Dependency graph with parallel edges merged into weighted ones
+#[derive(Debug, Clone, Serialize)]
+pub struct CollapsedGraph {
+    pub nodes: Vec<CollapsedNode>,
+    pub edges: Vec<CollapsedEdge>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CollapsedNode {
+    pub id: String,
+    /// Number of modules merged into this node
+    pub modules: usize,
+    /// Imports between modules that both live inside this node
+    pub internal_edges: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CollapsedEdge {
+    pub from: String,
+    pub to: String,
+    /// Number of module-level imports this edge stands for
+    pub weight: u32,
+}
+
+impl CollapsedGraph {
+    /// Aggregate `graph` to the granularity selected by `view`
+    pub fn from_graph(graph: &DependencyGraph, root: &Path, view: GraphView) -> Self {
+        let group_of: HashMap<&str, String> = graph.nodes.iter()
+            .map(|node| {
+                let group = match view {
+                    GraphView::Module => node.id.clone(),
+                    GraphView::Directory { depth } => directory_group(&node.file_path, root, depth),
+                };
+                (node.id.as_str(), group)
+            })
+            .collect();
+
+        let mut nodes: BTreeMap<String, CollapsedNode> = BTreeMap::new();
+        for group in group_of.values() {
+            nodes.entry(group.clone())
+                .or_insert_with(|| CollapsedNode { id: group.clone(), modules: 0, internal_edges: 0 })
+                .modules += 1;
+        }
+
+        let mut weights: BTreeMap<(String, String), u32> = BTreeMap::new();
+        for edge in &graph.edges {
+            let (Some(from), Some(to)) = (group_of.get(edge.from.as_str()), group_of.get(edge.to.as_str())) else {
+                continue;
+            };
+            if from == to && view != GraphView::Module {
+                if let Some(node) = nodes.get_mut(from) {
+                    node.internal_edges += 1;
+                }
+            } else {
+                *weights.entry((from.clone(), to.clone())).or_insert(0) += 1;
+            }
+        }
+
+        Self {
+            nodes: nodes.into_values().collect(),
+            edges: weights.into_iter()
+                .map(|((from, to), weight)| CollapsedEdge { from, to, weight })
+                .collect(),
+        }
+    }
+
+    /// Graphviz DOT with edge weights as labels and pen widths
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n    rankdir=LR;\n    node [shape=box];\n");
+        for node in &self.nodes {
+            dot.push_str(&format!("    \"{}\" [label=\"{} ({})\"];\n", node.id, node.id, node.modules));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\", penwidth={:.1}];\n",
+                edge.from, edge.to, edge.weight, 1.0 + (edge.weight as f64).ln()
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Mermaid flowchart with edge weights as labels
+    pub fn to_mermaid(&self) -> String {
+        let ids: HashMap<&str, String> = self.nodes.iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), format!("n{}", i)))
+            .collect();
+
+        let mut mermaid = String::from("graph LR\n");
+        for node in &self.nodes {
+            mermaid.push_str(&format!("    {}[\"{} ({})\"]\n", ids[node.id.as_str()], node.id, node.modules));
+        }
+        for edge in &self.edges {
+            mermaid.push_str(&format!(
+                "    {} -->|{}| {}\n",
+                ids[edge.from.as_str()], edge.weight, ids[edge.to.as_str()]
+            ));
+        }
+        mermaid
+    }
+}
+
+/// Fill colors from stable (depended upon, few dependencies) to unstable
+const STABILITY_BANDS: [(&str, &str); 3] = [("stable", "#c8e6c9"), ("balanced", "#fff9c4"), ("unstable", "#ffcdd2")];
+/// Modules without coupling data
+const UNMEASURED: (&str, &str) = ("unmeasured", "#eeeeee");
+
+/// Name and fill color of the instability band, by thirds of the 0..=1 range
+fn stability_band(instability: Option<f64>) -> (&'static str, &'static str) {
+    match instability {
+        Some(value) if value < 1.0 / 3.0 => STABILITY_BANDS[0],
+        Some(value) if value < 2.0 / 3.0 => STABILITY_BANDS[1],
+        Some(_) => STABILITY_BANDS[2],
+        None => UNMEASURED,
+    }
+}
+
+fn dot_escape(id: &str) -> String {
+    id.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Directory of `file_path` relative to `root`, keeping at most `depth` components
+fn directory_group(file_path: &Path, root: &Path, depth: usize) -> String {
+    let relative = file_path.strip_prefix(root).unwrap_or(file_path);
+    let components: Vec<String> = relative.parent()
+        .map(|parent| parent.components()
+            .take(depth.max(1))
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect())
+        .unwrap_or_default();
+
+    if components.is_empty() {
+        ".".to_string()
+    } else {
+        components.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependency_analyzer_creation() {
+        let analyzer = DependencyAnalyzer::new(PathBuf::from("."));
+        assert!(analyzer.module_registry.is_empty());
+    }
+
+    #[test]
+    fn test_external_dependency_detection() {
+        let analyzer = DependencyAnalyzer::new(PathBuf::from("."));
+
+        // JavaScript/TypeScript tests
+        assert!(analyzer.is_external_dependency("react", &Language::JavaScript));
+        assert!(!analyzer.is_external_dependency("./components", &Language::JavaScript));
+        assert!(!analyzer.is_external_dependency("../utils", &Language::JavaScript));
+
+        // Rust tests
+        assert!(analyzer.is_external_dependency("serde", &Language::Rust));
+        assert!(!analyzer.is_external_dependency("crate::utils", &Language::Rust));
+        assert!(!analyzer.is_external_dependency("super::types", &Language::Rust));
+
+        // Python tests
+        assert!(analyzer.is_external_dependency("numpy", &Language::Python));
+        assert!(!analyzer.is_external_dependency(".utils", &Language::Python));
+    }
+
+    #[test]
+    fn test_layers_and_max_chain_length() {
+        let analyzer = DependencyAnalyzer::new(PathBuf::from("."));
+        let graph = DependencyGraph {
+            nodes: ["app", "service", "db", "util"].iter().map(|id| DependencyNode {
+                id: id.to_string(),
+                file_path: PathBuf::from(format!("{}.rs", id)),
+                module_name: id.to_string(),
+                exports: Vec::new(),
+            }).collect(),
+            edges: [("app", "service"), ("service", "db"), ("app", "util")].iter().map(|(from, to)| DependencyEdge {
+                from: from.to_string(),
+                to: to.to_string(),
+                import_type: ImportType::Named,
+                imported_symbols: Vec::new(),
+            }).collect(),
+        };
+        let dependency_depth = analyzer.calculate_dependency_depths(&graph).unwrap();
+
+        let result = DependencyAnalysisResult {
+            root_path: PathBuf::from("."),
+            graph,
+            circular_dependencies: Vec::new(),
+            package_cycles: Vec::new(),
+            cycles_truncated: false,
+            unused_exports: Vec::new(),
+            external_dependencies: HashMap::new(),
+            external_imports: HashMap::new(),
+            dependency_depth,
+            module_coupling: Vec::new(),
+            cargo: None,
+        };
+
+        let view = result.layers();
+        assert_eq!(view.max_chain_length, 3);
+        assert_eq!(view.layers, vec![vec!["db", "util"], vec!["service"], vec!["app"]]);
+    }
+
+    #[test]
+    fn test_graph_exports_style_by_instability_and_import_type() {
+        let node = |id: &str| DependencyNode {
+            id: id.to_string(),
+            file_path: PathBuf::from(format!("{}.js", id)),
+            module_name: id.to_string(),
+            exports: Vec::new(),
+        };
+        let coupling = |id: &str, instability: f64| ModuleCoupling {
+            module_name: id.to_string(),
+            afferent_coupling: 1,
+            efferent_coupling: 1,
+            instability,
+            abstractness: 0.0,
+        };
+        let result = DependencyAnalysisResult {
+            root_path: PathBuf::from("."),
+            graph: DependencyGraph {
+                nodes: vec![node("app"), node("polyfill")],
+                edges: vec![DependencyEdge {
+                    from: "app".to_string(),
+                    to: "polyfill".to_string(),
+                    import_type: ImportType::Side,
+                    imported_symbols: Vec::new(),
+                }],
+            },
+            circular_dependencies: Vec::new(),
+            package_cycles: Vec::new(),
+            cycles_truncated: false,
+            unused_exports: Vec::new(),
+            external_dependencies: HashMap::new(),
+            external_imports: HashMap::new(),
+            dependency_depth: HashMap::new(),
+            module_coupling: vec![coupling("app", 1.0), coupling("polyfill", 0.0)],
+            cargo: None,
+        };
+
+        let dot = result.to_dot();
+        assert!(dot.contains("\"app\" [fillcolor=\"#ffcdd2\", tooltip=\"unstable\"]"));
+        assert!(dot.contains("\"app\" -> \"polyfill\" [style=dotted]"));
+
+        let mermaid = result.to_mermaid();
+        assert!(mermaid.contains("n0 -. side effect .-> n1"));
+        assert!(mermaid.contains("class n1 stable"));
+    }
+
+    #[test]
+    fn test_package_cycle_without_file_cycle() {
+        let analyzer = DependencyAnalyzer::new(PathBuf::from("src"));
+        let node = |id: &str, path: &str| DependencyNode {
+            id: id.to_string(),
+            file_path: PathBuf::from(path),
+            module_name: id.to_string(),
+            exports: Vec::new(),
+        };
+        let edge = |from: &str, to: &str| DependencyEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            import_type: ImportType::Named,
+            imported_symbols: Vec::new(),
+        };
+        // billing::invoice -> users::account and users::profile -> billing::plans:
+        // no file cycle, but billing <-> users at package level
+        let graph = DependencyGraph {
+            nodes: vec![
+                node("billing::invoice", "src/billing/invoice.rs"),
+                node("billing::plans", "src/billing/plans.rs"),
+                node("users::account", "src/users/account.rs"),
+                node("users::profile", "src/users/profile.rs"),
+            ],
+            edges: vec![
+                edge("billing::invoice", "users::account"),
+                edge("users::profile", "billing::plans"),
+            ],
+        };
+
+        assert!(analyzer.detect_circular_dependencies(&graph).unwrap().0.is_empty());
+        let (package_cycles, _) = analyzer.detect_package_cycles(&graph).unwrap();
+        assert_eq!(package_cycles.len(), 1);
+        assert_eq!(package_cycles[0].granularity, CycleGranularity::Package);
+        assert!(matches!(package_cycles[0].severity, CycleSeverity::Medium));
+    }
+
+    /// A single import chain far deeper than any recursive DFS could handle
+    fn deep_chain(length: usize) -> DependencyGraph {
+        DependencyGraph {
+            nodes: (0..length).map(|i| DependencyNode {
+                id: format!("m{}", i),
+                file_path: PathBuf::from(format!("m{}.rs", i)),
+                module_name: format!("m{}", i),
+                exports: Vec::new(),
+            }).collect(),
+            edges: (1..length).map(|i| DependencyEdge {
+                from: format!("m{}", i - 1),
+                to: format!("m{}", i),
+                import_type: ImportType::Named,
+                imported_symbols: Vec::new(),
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_very_deep_chain_cycle_detection() {
+        let analyzer = DependencyAnalyzer::new(PathBuf::from("."));
+        let mut graph = deep_chain(200_000);
+        assert!(analyzer.detect_circular_dependencies(&graph).unwrap().0.is_empty());
+
+        graph.edges.push(DependencyEdge {
+            from: "m199999".to_string(),
+            to: "m0".to_string(),
+            import_type: ImportType::Named,
+            imported_symbols: Vec::new(),
+        });
+        let (cycles, truncated) = analyzer.detect_circular_dependencies(&graph).unwrap();
+        assert!(!truncated);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].cycle.len(), 200_001);
+    }
+
+    #[test]
+    fn test_very_deep_chain_depths() {
+        let analyzer = DependencyAnalyzer::new(PathBuf::from("."));
+        let graph = deep_chain(200_000);
+        let depths = analyzer.calculate_dependency_depths(&graph).unwrap();
+        assert_eq!(depths["m0"], 199_999);
+        assert_eq!(depths["m199999"], 0);
+    }
+
+    #[test]
+    fn test_package_root() {
+        assert_eq!(package_root("lodash/fp", &Language::JavaScript), "lodash");
+        assert_eq!(package_root("@tanstack/query/core", &Language::TypeScript), "@tanstack/query");
+        assert_eq!(package_root("serde::de::Visitor", &Language::Rust), "serde");
+        assert_eq!(package_root("numpy.linalg", &Language::Python), "numpy");
+    }
+
+    #[test]
+    fn test_external_usage_lists_importers_and_symbols() {
+        let mut analyzer = DependencyAnalyzer::new(PathBuf::from("."));
+        let import = |module_path: &str, names: &[&str], line| ImportInfo {
+            module_path: module_path.to_string(),
+            imported_names: names.iter().map(|n| n.to_string()).collect(),
+            is_default: false,
+            binding: None,
+            line,
+            inline_modules: Vec::new(),
+        };
+
+        let mut data = HashMap::new();
+        data.insert(PathBuf::from("src/a.js"), (Language::JavaScript, ImportExportAnalysis {
+            imports: vec![import("lodash", &["map", "filter"], 1), import("./b", &["b"], 2)],
+            exports: Vec::new(),
+        }));
+        data.insert(PathBuf::from("src/b.js"), (Language::JavaScript, ImportExportAnalysis {
+            imports: vec![import("lodash/fp", &["map"], 3)],
+            exports: Vec::new(),
+        }));
+        analyzer.analyze(data).unwrap();
+
+        let usage = analyzer.external_usage("lodash");
+        assert_eq!(usage.importers.len(), 2);
+        assert_eq!(usage.symbols.get("map"), Some(&2));
+        assert_eq!(usage.symbols.get("filter"), Some(&1));
+        assert!(analyzer.external_usage("react").importers.is_empty());
+    }
+
+    #[test]
+    fn test_cargo_workspace_resolves_rust_paths() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(temp_dir.path()).unwrap();
+        for file in ["app/src/lib.rs", "app/src/net/mod.rs", "app/src/net/tcp.rs", "model/src/lib.rs"] {
+            std::fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let package = |name: &str, dir: &str, dependencies: serde_json::Value| serde_json::json!({
+            "id": format!("{} 0.1.0", name), "name": name, "version": "0.1.0",
+            "manifest_path": root.join(dir).join("Cargo.toml"),
+            "dependencies": dependencies,
+            "targets": [{"name": name, "kind": ["lib"], "src_path": root.join(dir).join("src/lib.rs")}],
+        });
+        let metadata = serde_json::json!({
+            "packages": [
+                package("app", "app", serde_json::json!([
+                    {"name": "serde", "req": "^1.0", "features": ["derive"]},
+                    {"name": "model-core", "req": "*", "path": root.join("model")},
+                ])),
+                package("model-core", "model", serde_json::json!([])),
+            ],
+            "workspace_members": ["app 0.1.0", "model-core 0.1.0"],
+        });
+        let workspace = CargoWorkspace::from_metadata_json(&metadata.to_string()).unwrap();
+
+        let import = |module_path: &str| ImportInfo {
+            module_path: module_path.to_string(),
+            imported_names: Vec::new(),
+            is_default: false,
+            binding: None,
+            line: 1,
+            inline_modules: Vec::new(),
+        };
+        let mut data = HashMap::new();
+        data.insert(root.join("app/src/lib.rs"), (Language::Rust, ImportExportAnalysis {
+            imports: vec![import("net::tcp::connect")],
+            exports: Vec::new(),
+        }));
+        data.insert(root.join("app/src/net/tcp.rs"), (Language::Rust, ImportExportAnalysis {
+            imports: vec![
+                import("super::Socket"),
+                import("model_core::Invoice"),
+                import("serde::Deserialize"),
+                import("std::io::Read"),
+            ],
+            exports: Vec::new(),
+        }));
+        for file in ["app/src/net/mod.rs", "model/src/lib.rs"] {
+            data.insert(root.join(file), (Language::Rust, ImportExportAnalysis { imports: Vec::new(), exports: Vec::new() }));
+        }
+
+        let mut analyzer = DependencyAnalyzer::new(root.clone()).with_cargo_workspace(workspace);
+        let result = analyzer.analyze(data).unwrap();
+
+        let mut edges: Vec<(&str, &str)> = result.graph.edges.iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect();
+        edges.sort();
+        assert_eq!(edges, vec![
+            ("app::src::lib", "app::src::net::tcp"),
+            ("app::src::net::tcp", "app::src::net::mod"),
+            ("app::src::net::tcp", "model::src::lib"),
+        ]);
+        assert_eq!(result.external_dependencies.keys().collect::<Vec<_>>(), vec!["serde"]);
+        assert_eq!(analyzer.external_usage("serde").symbols.get("Deserialize"), Some(&1));
+    }
+
+    #[test]
+    fn test_super_inside_an_inline_test_module_stays_in_its_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(temp_dir.path()).unwrap();
+        std::fs::create_dir_all(root.join("app/src")).unwrap();
+        std::fs::write(root.join("app/src/lib.rs"), "pub mod a;\npub mod b;\n").unwrap();
+        std::fs::write(root.join("app/src/a.rs"), "pub fn f() {}\n\n#[cfg(test)]\nmod tests {\n    use super::*;\n    use super::super::b::g;\n}\n").unwrap();
+        std::fs::write(root.join("app/src/b.rs"), "pub fn g() {}\n\nmod tests {\n    mod nested {\n        use super::super::g;\n    }\n}\n").unwrap();
+        let metadata = serde_json::json!({
+            "packages": [{
+                "id": "app 0.1.0", "name": "app", "version": "0.1.0",
+                "manifest_path": root.join("app/Cargo.toml"),
+                "dependencies": [],
+                "targets": [{"name": "app", "kind": ["lib"], "src_path": root.join("app/src/lib.rs")}],
+            }],
+            "workspace_members": ["app 0.1.0"],
+        });
+        let workspace = CargoWorkspace::from_metadata_json(&metadata.to_string()).unwrap();
+
+        let data = collect_import_data(&root).unwrap();
+        let result = DependencyAnalyzer::new(root.clone()).with_cargo_workspace(workspace).analyze(data).unwrap();
+        let edges: Vec<(&str, &str)> = result.graph.edges.iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect();
+        assert_eq!(edges, vec![("app::src::a", "app::src::b")]);
+        assert!(result.circular_dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_js_project_aliases_are_internal_edges() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/app")).unwrap();
+        std::fs::write(root.join("tsconfig.json"), r#"{ "compilerOptions": { "paths": { "@app/*": ["src/app/*"] } } }"#).unwrap();
+        for file in ["src/main.ts", "src/app/utils.ts"] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let import = |module_path: &str| ImportInfo {
+            module_path: module_path.to_string(),
+            imported_names: vec!["x".to_string()],
+            is_default: false,
+            binding: None,
+            line: 1,
+            inline_modules: Vec::new(),
+        };
+        let mut data = HashMap::new();
+        data.insert(root.join("src/main.ts"), (Language::TypeScript, ImportExportAnalysis {
+            imports: vec![import("@app/utils"), import("react")],
+            exports: Vec::new(),
+        }));
+        data.insert(root.join("src/app/utils.ts"), (Language::TypeScript, ImportExportAnalysis {
+            imports: Vec::new(),
+            exports: Vec::new(),
+        }));
+
+        let project = JsProject::load(root).unwrap().unwrap();
+        let mut analyzer = DependencyAnalyzer::new(root.to_path_buf()).with_js_project(project);
+        let result = analyzer.analyze(data).unwrap();
+
+        assert_eq!(result.graph.edges.len(), 1);
+        assert_eq!((result.graph.edges[0].from.as_str(), result.graph.edges[0].to.as_str()), ("src::main", "src::app::utils"));
+        assert_eq!(result.external_dependencies.keys().collect::<Vec<_>>(), vec!["react"]);
+    }
+
+    #[test]
+    fn test_python_src_layout_and_relative_imports() {
+        let import = |module_path: &str, names: &[&str]| ImportInfo {
+            module_path: module_path.to_string(),
+            imported_names: names.iter().map(|n| n.to_string()).collect(),
+            is_default: false,
+            binding: None,
+            line: 1,
+            inline_modules: Vec::new(),
+        };
+        let module = |imports| (Language::Python, ImportExportAnalysis { imports, exports: Vec::new() });
+
+        let mut data = HashMap::new();
+        data.insert(PathBuf::from("proj/src/shop/orders/service.py"), module(vec![
+            import("..util", &["format_price"]),
+            import("shop.orders", &["models"]),
+            import("requests", &[]),
+        ]));
+        data.insert(PathBuf::from("proj/src/shop/util.py"), module(Vec::new()));
+        data.insert(PathBuf::from("proj/src/shop/orders/models.py"), module(Vec::new()));
+
+        let mut analyzer = DependencyAnalyzer::new(PathBuf::from("proj"));
+        let result = analyzer.analyze(data).unwrap();
+
+        let mut targets: Vec<&str> = result.graph.edges.iter().map(|edge| edge.to.as_str()).collect();
+        targets.sort();
+        assert_eq!(targets, vec!["src.shop.orders.models", "src.shop.util"]);
+        assert_eq!(result.external_dependencies.keys().collect::<Vec<_>>(), vec!["requests"]);
+    }
+
+    #[test]
+    fn test_directory_collapse_merges_and_weights_edges() {
+        let node = |id: &str, path: &str| DependencyNode {
+            id: id.to_string(),
+            file_path: PathBuf::from(path),
+            module_name: id.to_string(),
+            exports: Vec::new(),
+        };
+        let edge = |from: &str, to: &str| DependencyEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            import_type: ImportType::Named,
+            imported_symbols: Vec::new(),
+        };
+        let graph = DependencyGraph {
+            nodes: vec![
+                node("api::users", "src/api/users.rs"),
+                node("api::orders", "src/api/orders.rs"),
+                node("db::pool", "src/db/pool.rs"),
+            ],
+            edges: vec![
+                edge("api::users", "db::pool"),
+                edge("api::orders", "db::pool"),
+                edge("api::orders", "api::users"),
+            ],
+        };
+
+        let collapsed = CollapsedGraph::from_graph(&graph, Path::new("src"), GraphView::Directory { depth: 1 });
+        assert_eq!(collapsed.nodes.len(), 2);
+        assert_eq!(collapsed.edges.len(), 1);
+        assert_eq!(collapsed.edges[0].weight, 2);
+        assert_eq!(collapsed.nodes.iter().find(|n| n.id == "api").unwrap().internal_edges, 1);
+        assert!(collapsed.to_dot().contains("\"api\" -> \"db\" [label=\"2\""));
+        assert!(collapsed.to_mermaid().contains("-->|2|"));
+    }
+
+    #[test]
+    fn test_imports_are_extracted_from_real_sources() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        for directory in ["web", "src/shop"] {
+            std::fs::create_dir_all(root.join(directory)).unwrap();
+        }
+        std::fs::write(root.join("package.json"), r#"{ "name": "web" }"#).unwrap();
+        std::fs::write(root.join("web/main.ts"), "import React from \"react\";\nimport { slug } from \"./util\";\nconst { join } = require(\"path\");\n").unwrap();
+        std::fs::write(root.join("web/util.js"), "export const slug = (title) => title.toLowerCase();\n").unwrap();
+        std::fs::write(root.join("src/shop/__init__.py"), "").unwrap();
+        std::fs::write(root.join("src/shop/service.py"), "import requests\nfrom .models import Order\n").unwrap();
+        std::fs::write(root.join("src/shop/models.py"), "class Order:\n    pass\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "use std::{fmt, io::Read};\n").unwrap();
+
+        let data = collect_import_data(root).unwrap();
+        let rust: Vec<&str> = data[&root.join("src/lib.rs")].1.imports.iter().map(|import| import.module_path.as_str()).collect();
+        assert_eq!(rust, vec!["std::fmt", "std::io::Read"]);
+
+        let project = JsProject::load(root).unwrap().unwrap();
+        let result = DependencyAnalyzer::new(root.to_path_buf()).with_js_project(project).analyze(data).unwrap();
+        let mut edges: Vec<(&str, &str)> = result.graph.edges.iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect();
+        edges.sort();
+        assert_eq!(edges, vec![("src.shop.service", "src.shop.models"), ("web::main", "web::util")]);
+        let mut external: Vec<&String> = result.external_dependencies.keys().collect();
+        external.sort();
+        assert_eq!(external, vec!["path", "react", "requests", "std::fmt", "std::io::Read"]);
+    }
+
+    #[test]
+    fn test_ruby_requires_resolve_against_lib() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("lib/shop")).unwrap();
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        std::fs::write(root.join("lib/shop/cart.rb"), "require \"json\"\nrequire_relative \"../pricing\"\nclass Cart\nend\n").unwrap();
+        std::fs::write(root.join("lib/pricing.rb"), "module Pricing\nend\n").unwrap();
+        std::fs::write(root.join("bin/run.rb"), "require \"shop/cart\"\n").unwrap();
+
+        let data = collect_import_data(root).unwrap();
+        let result = DependencyAnalyzer::new(root.to_path_buf()).analyze(data).unwrap();
+
+        let mut edges: Vec<(&str, &str)> = result.graph.edges.iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect();
+        edges.sort();
+        assert_eq!(edges, vec![("bin/run", "lib/shop/cart"), ("lib/shop/cart", "lib/pricing")]);
+        assert_eq!(result.external_dependencies.keys().collect::<Vec<_>>(), vec!["json"]);
+    }
+}
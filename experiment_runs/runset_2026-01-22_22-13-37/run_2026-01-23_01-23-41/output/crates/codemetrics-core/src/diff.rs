@@ -0,0 +1,329 @@
+//! Per-function comparison of two analyses, for PR comments and review
+//!
+//! Each side is a directory, a JSON results file from `analyze --format json`,
+//! or a git revision checked out into a temporary worktree. Functions are
+//! matched by path relative to the analyzed root plus name, so the two sides
+//! may live in different directories. JSON files do not record their root; the
+//! deepest directory shared by all their paths stands in for it.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::analyzers::{AnalysisResults, CodeAnalyzer};
+use crate::release_report::Worktree;
+use crate::vcs;
+
+/// Relative file path and function name
+type FunctionKey = (String, String);
+
+/// Metrics of one side of a diff
+#[derive(Debug)]
+pub struct DiffSide {
+    pub label: String,
+    files_analyzed: usize,
+    total_lines: u32,
+    total_functions: usize,
+    average_complexity: f64,
+    /// Functions reported as high complexity, with their complexity and first line
+    high: BTreeMap<FunctionKey, (u32, u32)>,
+    /// Every function whose complexity is known; complete only when per-function records were kept
+    all: BTreeMap<FunctionKey, u32>,
+}
+
+/// The fields of an `analyze --format json` file a diff needs
+#[derive(Deserialize)]
+struct JsonResults {
+    files_analyzed: usize,
+    total_lines: u32,
+    total_functions: usize,
+    average_complexity: f64,
+    high_complexity_functions: Vec<JsonFunction>,
+    #[serde(default)]
+    functions: Vec<JsonFunction>,
+}
+
+#[derive(Deserialize)]
+struct JsonFunction {
+    name: String,
+    file_path: String,
+    complexity: u32,
+    line_start: u32,
+}
+
+impl DiffSide {
+    /// A JSON results file, an existing directory, or otherwise a revision of the repository at `repo`
+    pub fn load(analyzer: &CodeAnalyzer, spec: &str, repo: &Path) -> Result<Self> {
+        let path = Path::new(spec);
+        if path.is_file() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            return Self::from_json(spec, &content).with_context(|| format!("Invalid results file {}", path.display()));
+        }
+        if path.is_dir() {
+            return Ok(Self::from_results(spec, &analyzer.analyze_path(path)?, path));
+        }
+
+        let vcs = vcs::detect(repo)
+            .with_context(|| format!("'{}' is neither a file, a directory nor a revision", spec))?;
+        let worktree = Worktree::checkout(vcs.as_ref(), spec)?;
+        let results = analyzer.analyze_path(&worktree.dir)?;
+        Ok(Self::from_results(spec, &results, &worktree.dir))
+    }
+
+    pub fn from_results(label: &str, results: &AnalysisResults, root: &Path) -> Self {
+        let relative = |file: &str| {
+            Path::new(file).strip_prefix(root).map(|path| path.to_string_lossy().into_owned()).unwrap_or_else(|_| file.to_string())
+        };
+        Self {
+            label: label.to_string(),
+            files_analyzed: results.files_analyzed,
+            total_lines: results.total_lines,
+            total_functions: results.total_functions,
+            average_complexity: results.average_complexity,
+            high: results.high_complexity_functions.iter()
+                .map(|func| ((relative(&func.file_path), func.name.clone()), (func.complexity, func.line_start)))
+                .collect(),
+            all: results.functions.iter()
+                .map(|func| ((relative(&func.file_path), func.name.clone()), func.complexity))
+                .chain(results.high_complexity_functions.iter()
+                    .map(|func| ((relative(&func.file_path), func.name.clone()), func.complexity)))
+                .collect(),
+        }
+    }
+
+    pub fn from_json(label: &str, json: &str) -> Result<Self> {
+        let results: JsonResults = serde_json::from_str(json)?;
+        let root = common_root(results.high_complexity_functions.iter()
+            .chain(&results.functions)
+            .map(|func| func.file_path.as_str()));
+        let relative = |file: &str| {
+            Path::new(file).strip_prefix(&root).map(|path| path.to_string_lossy().into_owned()).unwrap_or_else(|_| file.to_string())
+        };
+        Ok(Self {
+            label: label.to_string(),
+            files_analyzed: results.files_analyzed,
+            total_lines: results.total_lines,
+            total_functions: results.total_functions,
+            average_complexity: results.average_complexity,
+            high: results.high_complexity_functions.iter()
+                .map(|func| ((relative(&func.file_path), func.name.clone()), (func.complexity, func.line_start)))
+                .collect(),
+            all: results.functions.iter()
+                .chain(&results.high_complexity_functions)
+                .map(|func| ((relative(&func.file_path), func.name.clone()), func.complexity))
+                .collect(),
+        })
+    }
+}
+
+/// What changed between two analyses
+#[derive(Debug, Serialize)]
+pub struct ResultsDiff {
+    pub old: String,
+    pub new: String,
+    pub files_delta: i64,
+    pub lines_delta: i64,
+    pub functions_delta: i64,
+    pub average_complexity_delta: f64,
+    /// High complexity in `new` only: added functions, or ones that crossed the threshold
+    pub new_issues: Vec<FunctionChange>,
+    /// High complexity in `old` only: simplified or removed
+    pub resolved: Vec<FunctionChange>,
+    /// High complexity on both sides and more complex in `new`
+    pub regressions: Vec<FunctionChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionChange {
+    pub file_path: String,
+    pub name: String,
+    /// `None` when the function did not exist, or its complexity was not recorded
+    pub old_complexity: Option<u32>,
+    pub new_complexity: Option<u32>,
+    /// First line on the newer side, when it still exists there as an issue
+    pub line_start: Option<u32>,
+}
+
+impl ResultsDiff {
+    pub fn compare(old: &DiffSide, new: &DiffSide) -> Self {
+        let change = |(file_path, name): &FunctionKey, old_complexity, new_complexity, line_start| FunctionChange {
+            file_path: file_path.clone(),
+            name: name.clone(),
+            old_complexity,
+            new_complexity,
+            line_start,
+        };
+
+        let mut new_issues: Vec<FunctionChange> = new.high.iter()
+            .filter(|(key, _)| !old.high.contains_key(*key))
+            .map(|(key, &(complexity, line))| change(key, old.all.get(key).copied(), Some(complexity), Some(line)))
+            .collect();
+        let mut resolved: Vec<FunctionChange> = old.high.iter()
+            .filter(|(key, _)| !new.high.contains_key(*key))
+            .map(|(key, &(complexity, _))| change(key, Some(complexity), new.all.get(key).copied(), None))
+            .collect();
+        let mut regressions: Vec<FunctionChange> = new.high.iter()
+            .filter_map(|(key, &(complexity, line))| {
+                let &(before, _) = old.high.get(key)?;
+                (complexity > before).then(|| change(key, Some(before), Some(complexity), Some(line)))
+            })
+            .collect();
+
+        let by_new = |a: &FunctionChange, b: &FunctionChange| b.new_complexity.cmp(&a.new_complexity).then_with(|| a.file_path.cmp(&b.file_path));
+        new_issues.sort_by(by_new);
+        regressions.sort_by(|a, b| {
+            let growth = |change: &FunctionChange| change.new_complexity.unwrap_or(0) as i64 - change.old_complexity.unwrap_or(0) as i64;
+            growth(b).cmp(&growth(a)).then_with(|| a.file_path.cmp(&b.file_path))
+        });
+        resolved.sort_by(|a, b| b.old_complexity.cmp(&a.old_complexity).then_with(|| a.file_path.cmp(&b.file_path)));
+
+        Self {
+            old: old.label.clone(),
+            new: new.label.clone(),
+            files_delta: new.files_analyzed as i64 - old.files_analyzed as i64,
+            lines_delta: new.total_lines as i64 - old.total_lines as i64,
+            functions_delta: new.total_functions as i64 - old.total_functions as i64,
+            average_complexity_delta: new.average_complexity - old.average_complexity,
+            new_issues,
+            resolved,
+            regressions,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Diff {} → {}\n\n", self.old, self.new);
+        text.push_str(&format!("  Files:              {:+}\n", self.files_delta));
+        text.push_str(&format!("  Lines:              {:+}\n", self.lines_delta));
+        text.push_str(&format!("  Functions:          {:+}\n", self.functions_delta));
+        text.push_str(&format!("  Average complexity: {:+.2}\n", self.average_complexity_delta));
+
+        for (title, changes) in self.sections() {
+            if changes.is_empty() {
+                continue;
+            }
+            text.push_str(&format!("\n{} ({}):\n", title, changes.len()));
+            for change in changes {
+                text.push_str(&format!("  {:<12} {}  {}\n", change.complexity_change(), change.location(), change.name));
+            }
+        }
+        if self.is_unchanged() {
+            text.push_str("\nNo function crossed or moved above the complexity threshold.\n");
+        }
+        text
+    }
+
+    /// Markdown for a PR comment
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("## Complexity: {} → {}\n\n", self.old, self.new);
+        markdown.push_str("| Metric | Change |\n");
+        markdown.push_str("|--------|--------|\n");
+        markdown.push_str(&format!("| Files | {:+} |\n", self.files_delta));
+        markdown.push_str(&format!("| Lines of code | {:+} |\n", self.lines_delta));
+        markdown.push_str(&format!("| Functions | {:+} |\n", self.functions_delta));
+        markdown.push_str(&format!("| Average complexity | {:+.2} |\n\n", self.average_complexity_delta));
+
+        for (title, changes) in self.sections() {
+            if changes.is_empty() {
+                continue;
+            }
+            markdown.push_str(&format!("### {} ({})\n\n", title, changes.len()));
+            markdown.push_str("| Function | Complexity | Location |\n");
+            markdown.push_str("|----------|------------|----------|\n");
+            for change in changes {
+                markdown.push_str(&format!("| `{}` | {} | `{}` |\n", change.name, change.complexity_change(), change.location()));
+            }
+            markdown.push('\n');
+        }
+        if self.is_unchanged() {
+            markdown.push_str("No function crossed or moved above the complexity threshold. ✅\n");
+        }
+        markdown
+    }
+
+    fn sections(&self) -> [(&'static str, &[FunctionChange]); 3] {
+        [
+            ("New high-complexity functions", &self.new_issues),
+            ("Regressions", &self.regressions),
+            ("Resolved", &self.resolved),
+        ]
+    }
+
+    fn is_unchanged(&self) -> bool {
+        self.new_issues.is_empty() && self.regressions.is_empty() && self.resolved.is_empty()
+    }
+}
+
+impl FunctionChange {
+    /// e.g. `12 → 18`, `new 14`, or `9 → -` when the newer complexity is unknown
+    fn complexity_change(&self) -> String {
+        match (self.old_complexity, self.new_complexity) {
+            (Some(old), Some(new)) => format!("{} → {}", old, new),
+            (None, Some(new)) => format!("new {}", new),
+            (Some(old), None) => format!("{} → -", old),
+            (None, None) => "-".to_string(),
+        }
+    }
+
+    fn location(&self) -> String {
+        match self.line_start {
+            Some(line) => format!("{}:{}", self.file_path, line),
+            None => self.file_path.clone(),
+        }
+    }
+}
+
+/// Deepest directory containing every path
+fn common_root<'a>(paths: impl Iterator<Item = &'a str>) -> PathBuf {
+    let mut root: Option<PathBuf> = None;
+    for path in paths {
+        let parent = Path::new(path).parent().unwrap_or(Path::new(""));
+        root = Some(match root {
+            None => parent.to_path_buf(),
+            Some(root) => root.components().zip(parent.components())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+    root.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side(label: &str, root: &str, functions: &[(&str, &str, u32)]) -> DiffSide {
+        let functions: Vec<serde_json::Value> = functions.iter()
+            .map(|(file, name, complexity)| serde_json::json!({
+                "name": name,
+                "file_path": format!("{}/{}", root, file),
+                "complexity": complexity,
+                "line_start": 1,
+            }))
+            .collect();
+        let json = serde_json::json!({
+            "files_analyzed": 3,
+            "total_lines": 300,
+            "total_functions": 20,
+            "average_complexity": 4.0,
+            "high_complexity_functions": functions,
+        });
+        DiffSide::from_json(label, &json.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_functions_are_matched_across_roots() {
+        let old = side("old.json", "/tmp/a", &[("src/lib.rs", "parse", 12), ("src/lib.rs", "render", 15), ("src/io.rs", "read", 11)]);
+        let new = side("new.json", "/work/b", &[("src/lib.rs", "parse", 18), ("src/io.rs", "read", 11), ("src/cli.rs", "run", 13)]);
+
+        let diff = ResultsDiff::compare(&old, &new);
+        assert_eq!(diff.new_issues.len(), 1);
+        assert_eq!((diff.new_issues[0].file_path.as_str(), diff.new_issues[0].name.as_str()), ("cli.rs", "run"));
+        assert_eq!(diff.regressions[0].name, "parse");
+        assert_eq!((diff.regressions[0].old_complexity, diff.regressions[0].new_complexity), (Some(12), Some(18)));
+        assert_eq!(diff.resolved[0].name, "render");
+        assert!(diff.to_markdown().contains("| `parse` | 12 → 18 | `lib.rs:1` |"));
+    }
+}
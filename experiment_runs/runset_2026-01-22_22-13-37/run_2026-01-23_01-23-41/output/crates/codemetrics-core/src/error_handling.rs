@@ -0,0 +1,246 @@
+//! How much error-handling constructs add to cyclomatic complexity
+//!
+//! Whether `try`/`catch`, Rust's `?` and Go's `if err != nil` are decision
+//! points is contested, so each construct carries its own weight. Weights come
+//! from a preset and can be changed per language:
+//!
+//! | preset     | try | catch / except / rescue | finally / ensure | `?` | `if err != nil` |
+//! |------------|-----|-------------------------|------------------|-----|-----------------|
+//! | `standard` | 1   | 1                       | 0                | 0   | 1               |
+//! | `handlers` | 0   | 1                       | 0                | 0   | 1               |
+//! | `paths`    | 0   | 1                       | 0                | 1   | 1               |
+//! | `ignore`   | 0   | 0                       | 0                | 0   | 0               |
+//!
+//! `standard` is the default and what earlier releases counted, except that
+//! Python's `except` clauses were missed. `handlers`
+//! counts each handler once, as McCabe's definition and most linters do.
+//! `paths` additionally counts the early return hidden in every `?`. `ignore`
+//! leaves error plumbing out entirely, for teams that review it separately.
+//!
+//! In `codemetrics.toml`:
+//!
+//! ```toml
+//! [error_handling]
+//! preset = "handlers"
+//!
+//! [error_handling.languages.go]
+//! error_check = 0
+//! ```
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tree_sitter::Node;
+
+use crate::analyzers::parse_language_list;
+use crate::core::Language;
+
+pub const PRESETS: &[&str] = &["standard", "handlers", "paths", "ignore"];
+
+/// Complexity added by each kind of error-handling construct
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorHandlingWeights {
+    /// A `try` block itself (JavaScript, TypeScript, Python)
+    pub try_block: u32,
+    /// Each `catch`, `except` or `rescue` clause, and Ruby's `rescue` modifier
+    pub handler: u32,
+    /// `finally` and `ensure` clauses
+    pub finally: u32,
+    /// Rust's `?` operator
+    pub propagation: u32,
+    /// Go's `if err != nil`, checked on the name `err`
+    pub error_check: u32,
+}
+
+impl Default for ErrorHandlingWeights {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+impl ErrorHandlingWeights {
+    pub const STANDARD: Self = Self { try_block: 1, handler: 1, finally: 0, propagation: 0, error_check: 1 };
+
+    /// The weights of one of `PRESETS`
+    pub fn preset(name: &str) -> Result<Self> {
+        Ok(match name.trim().to_lowercase().as_str() {
+            "standard" => Self::STANDARD,
+            "handlers" => Self { try_block: 0, ..Self::STANDARD },
+            "paths" => Self { try_block: 0, propagation: 1, ..Self::STANDARD },
+            "ignore" => Self { try_block: 0, handler: 0, finally: 0, propagation: 0, error_check: 0 },
+            _ => anyhow::bail!("Unknown error handling preset '{}' (expected one of: {})", name, PRESETS.join(", ")),
+        })
+    }
+
+    /// Weight of `node` when it is an error-handling construct, `None` otherwise
+    pub fn weight(&self, node: Node, source: &str) -> Option<u32> {
+        match node.kind() {
+            "try_statement" => Some(self.try_block),
+            "catch_clause" | "except_clause" | "except_group_clause" => Some(self.handler),
+            // Ruby's keyword tokens share these names with the clauses
+            "rescue" | "rescue_modifier" if node.is_named() => Some(self.handler),
+            "finally_clause" => Some(self.finally),
+            "ensure" if node.is_named() => Some(self.finally),
+            "try_expression" => Some(self.propagation),
+            "if_statement" if is_go_error_check(node, source) => Some(self.error_check),
+            _ => None,
+        }
+    }
+}
+
+/// `if err != nil`, with or without an initializer such as `if err := f(); err != nil`
+fn is_go_error_check(node: Node, source: &str) -> bool {
+    let Some(condition) = node.child_by_field_name("condition") else {
+        return false;
+    };
+    let text = |field| condition.child_by_field_name(field).map(|child| &source[child.byte_range()]);
+    condition.kind() == "binary_expression"
+        && text("left") == Some("err")
+        && text("operator") == Some("!=")
+        && text("right") == Some("nil")
+}
+
+/// One table section: a preset and individual weights that replace its values
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeightOverrides {
+    pub preset: Option<String>,
+    pub try_block: Option<u32>,
+    pub handler: Option<u32>,
+    pub finally: Option<u32>,
+    pub propagation: Option<u32>,
+    pub error_check: Option<u32>,
+}
+
+impl WeightOverrides {
+    pub fn apply(&self, base: &ErrorHandlingWeights) -> Result<ErrorHandlingWeights> {
+        let base = match &self.preset {
+            Some(name) => ErrorHandlingWeights::preset(name)?,
+            None => *base,
+        };
+        Ok(ErrorHandlingWeights {
+            try_block: self.try_block.unwrap_or(base.try_block),
+            handler: self.handler.unwrap_or(base.handler),
+            finally: self.finally.unwrap_or(base.finally),
+            propagation: self.propagation.unwrap_or(base.propagation),
+            error_check: self.error_check.unwrap_or(base.error_check),
+        })
+    }
+}
+
+/// The `[error_handling]` table: defaults plus per-language sections
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ErrorHandlingConfig {
+    pub preset: Option<String>,
+    pub try_block: Option<u32>,
+    pub handler: Option<u32>,
+    pub finally: Option<u32>,
+    pub propagation: Option<u32>,
+    pub error_check: Option<u32>,
+    pub languages: BTreeMap<String, WeightOverrides>,
+}
+
+impl ErrorHandlingConfig {
+    /// The settings outside any language section
+    fn defaults(&self) -> WeightOverrides {
+        WeightOverrides {
+            preset: self.preset.clone(),
+            try_block: self.try_block,
+            handler: self.handler,
+            finally: self.finally,
+            propagation: self.propagation,
+            error_check: self.error_check,
+        }
+    }
+}
+
+/// Weights for every language, with per-language exceptions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorHandling {
+    default: ErrorHandlingWeights,
+    /// Keyed by canonical language name, e.g. `Rust`, or a plugin's name
+    languages: BTreeMap<String, ErrorHandlingWeights>,
+}
+
+impl ErrorHandling {
+    pub fn from_config(config: &ErrorHandlingConfig) -> Result<Self> {
+        let default = config.defaults().apply(&ErrorHandlingWeights::default())?;
+        let mut handling = Self { default, languages: BTreeMap::new() };
+        for (language, overrides) in &config.languages {
+            handling.languages.insert(canonical(language), overrides.apply(&default)?);
+        }
+        Ok(handling)
+    }
+
+    /// Apply `--error-handling` flags: `PRESET` for every language or `LANG=PRESET` for one
+    ///
+    /// A bare preset replaces the per-language sections of the config file;
+    /// `LANG=PRESET` flags win over it wherever they appear.
+    pub fn override_with(&mut self, specs: &[String]) -> Result<()> {
+        let (per_language, presets): (Vec<_>, Vec<_>) = specs.iter().partition(|spec| spec.contains('='));
+        for preset in presets {
+            self.default = ErrorHandlingWeights::preset(preset)?;
+            self.languages.clear();
+        }
+        for spec in per_language {
+            let (language, preset) = spec.split_once('=').expect("partitioned on '='");
+            self.languages.insert(canonical(language), ErrorHandlingWeights::preset(preset)?);
+        }
+        Ok(())
+    }
+
+    pub fn for_language(&self, language: &Language) -> &ErrorHandlingWeights {
+        let name = match language {
+            Language::Plugin(name) => name.clone(),
+            other => format!("{:?}", other),
+        };
+        self.languages.get(&name).unwrap_or(&self.default)
+    }
+}
+
+/// Built-in names and aliases (`rs`, `golang`) map to `Rust`, `Go`, ...; plugin names are kept
+fn canonical(name: &str) -> String {
+    parse_language_list(&[name.to_string()])
+        .map(|mut names| names.remove(0))
+        .unwrap_or_else(|_| name.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line_complexity;
+
+    #[test]
+    fn test_presets_and_language_sections() -> Result<()> {
+        let config: ErrorHandlingConfig = toml::from_str("preset = \"handlers\"\n[languages.rs]\npreset = \"paths\"\nhandler = 2\n")?;
+        let mut handling = ErrorHandling::from_config(&config)?;
+        assert_eq!(handling.for_language(&Language::Python).try_block, 0);
+        assert_eq!(*handling.for_language(&Language::Rust), ErrorHandlingWeights { handler: 2, ..ErrorHandlingWeights::preset("paths")? });
+
+        handling.override_with(&["golang=ignore".to_string()])?;
+        assert_eq!(handling.for_language(&Language::Go).error_check, 0);
+        handling.override_with(&["standard".to_string()])?;
+        assert_eq!(*handling.for_language(&Language::Rust), ErrorHandlingWeights::STANDARD);
+        assert!(handling.override_with(&["lenient".to_string()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_go_error_checks_and_rust_propagation_follow_weights() -> Result<()> {
+        let go = "package main\n\nfunc run() error {\n\tif err := step(); err != nil {\n\t\treturn err\n\t}\n\tif n > 0 {\n\t}\n\treturn nil\n}\n";
+        let rust = "fn run() -> Result<()> {\n    step()?;\n    Ok(())\n}\n";
+        let contributions = |path: &str, source: &str, preset: &str| -> Result<Vec<(u32, u32)>> {
+            let mut handling = ErrorHandling::default();
+            handling.override_with(&[preset.to_string()])?;
+            let map = line_complexity::for_file(path, source, &handling)?.expect("has a grammar");
+            Ok(map.lines.iter().map(|line| (line.line, line.contribution)).collect())
+        };
+
+        assert_eq!(contributions("main.go", go, "standard")?, vec![(4, 1), (7, 1)]);
+        assert_eq!(contributions("main.go", go, "ignore")?, vec![(7, 1)]);
+        assert_eq!(contributions("lib.rs", rust, "standard")?, vec![]);
+        assert_eq!(contributions("lib.rs", rust, "paths")?, vec![(2, 1)]);
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+//! What the optional per-file passes take from a file's syntax tree
+//!
+//! `CodeAnalyzer` runs every enabled pass on the tree it parsed a file into
+//! and keeps the results with the `ParsedFile`, so no pass reads or parses
+//! the file again and a cached parse carries them too. Passes that compare
+//! files (types, Rust API, vocabulary, unsafe density per crate, the call graph) combine
+//! these once every file is parsed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::call_graph::FunctionCalls;
+use crate::clone_detector::Fingerprint;
+use crate::core::CodeIssue;
+use crate::function_shape::FunctionShape;
+use crate::go_concurrency::FileConcurrency;
+use crate::line_complexity::LineComplexity;
+use crate::rust_api;
+use crate::type_metrics;
+use crate::unsafe_usage::UnsafeBlock;
+
+/// Each field is `None` when its pass is disabled or does not apply to the file's language
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileFacts {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_complexity: Option<Vec<LineComplexity>>,
+    /// Keyed by the line each function starts on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shapes: Option<BTreeMap<u32, FunctionShape>>,
+    /// Occurrences of each identifier, for `vocabulary`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terms: Option<BTreeMap<String, usize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub types: Option<type_metrics::Extracted>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust_api: Option<rust_api::Extracted>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsafe_blocks: Option<Vec<UnsafeBlock>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<FileConcurrency>,
+    /// What each function calls, for the call graph
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calls: Option<Vec<FunctionCalls>>,
+    /// Blocks large enough to be reported as clones, for duplicated-code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clones: Option<Vec<Fingerprint>>,
+    /// Security, concurrency and resource findings of the enabled rules
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<CodeIssue>,
+}
+
+impl FileFacts {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
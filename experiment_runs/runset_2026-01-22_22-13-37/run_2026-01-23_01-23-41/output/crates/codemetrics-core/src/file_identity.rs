@@ -0,0 +1,81 @@
+//! Canonical identity of a file on disk
+//!
+//! The same file can be reached through several paths: a symlink, a symlinked
+//! directory, or overlapping analysis roots. On Unix two paths are the same
+//! file when they share a device and inode; elsewhere their canonicalized paths
+//! are compared.
+//!
+//! Separate files can still hold the same bytes, e.g. vendored snapshots. Their
+//! `ContentKey` is taken from the text parsing reads anyway, so finding such
+//! copies costs no extra read.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FileIdentity {
+    Inode { device: u64, inode: u64 },
+    Canonical(PathBuf),
+}
+
+impl FileIdentity {
+    /// `None` when the file cannot be inspected, e.g. it was removed meanwhile
+    #[cfg(unix)]
+    pub fn of(path: &Path) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(FileIdentity::Inode { device: metadata.dev(), inode: metadata.ino() })
+    }
+
+    #[cfg(not(unix))]
+    pub fn of(path: &Path) -> Option<Self> {
+        path.canonicalize().ok().map(FileIdentity::Canonical)
+    }
+}
+
+/// Content hash and length, enough to tell identical files apart in practice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ContentKey {
+    hash: u64,
+    len: usize,
+    /// Non-blank lines, which follow from the content
+    pub lines: usize,
+}
+
+impl ContentKey {
+    /// `None` for blank files, which are identical everywhere (`__init__.py`) and not worth reporting
+    pub fn of(content: &str) -> Option<Self> {
+        let lines = content.lines().filter(|line| !line.trim().is_empty()).count();
+        if lines == 0 {
+            return None;
+        }
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        Some(Self { hash: hasher.finish(), len: content.len(), lines })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_symlinks_share_identity() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::create_dir(temp_dir.path().join("src")).unwrap();
+        std::fs::write(temp_dir.path().join("src/lib.rs"), "fn main() {}").unwrap();
+        std::fs::write(temp_dir.path().join("src/other.rs"), "fn main() {}").unwrap();
+        std::os::unix::fs::symlink("src/lib.rs", temp_dir.path().join("alias.rs")).unwrap();
+        std::os::unix::fs::symlink("src", temp_dir.path().join("linked")).unwrap();
+
+        let original = FileIdentity::of(&temp_dir.path().join("src/lib.rs")).unwrap();
+        assert_eq!(FileIdentity::of(&temp_dir.path().join("alias.rs")), Some(original.clone()));
+        assert_eq!(FileIdentity::of(&temp_dir.path().join("linked/lib.rs")), Some(original.clone()));
+        assert_ne!(FileIdentity::of(&temp_dir.path().join("src/other.rs")), Some(original));
+        assert_eq!(FileIdentity::of(&temp_dir.path().join("missing.rs")), None);
+    }
+}
@@ -0,0 +1,154 @@
+//! Cognitive complexity and nesting of each function, for row-per-function exports
+//!
+//! Cyclomatic complexity counts paths; cognitive complexity weighs how hard a
+//! function is to read. Following SonarSource's definition, each `if`, loop,
+//! `switch`/`match`, `catch` and ternary adds one plus the depth it is nested
+//! at, `else` and `else if` add one, and so does each run of `&&`/`||`.
+//! Nesting depth is the one `DEEP_NESTING` reports.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tree_sitter::Node;
+
+use crate::ast_analyzer::{nesting_depth, walk_descendants, FUNCTION_KINDS};
+
+/// Readability measures of the function starting on `line_start`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionShape {
+    pub line_start: u32,
+    pub cognitive_complexity: u32,
+    pub nesting_depth: u32,
+}
+
+/// Every function under `root`; of functions sharing a start line, the outermost
+pub fn for_tree(root: Node, source: &str) -> BTreeMap<u32, FunctionShape> {
+    let mut shapes = BTreeMap::new();
+    walk_descendants(root, |node, _| {
+        if FUNCTION_KINDS.contains(&node.kind()) {
+            let line_start = node.start_position().row as u32 + 1;
+            shapes.entry(line_start).or_insert_with(|| FunctionShape {
+                line_start,
+                cognitive_complexity: cognitive_complexity(node, source),
+                nesting_depth: nesting_depth(node),
+            });
+        }
+        true
+    });
+    shapes
+}
+
+/// Cognitive complexity of `function`, nested functions and closures included
+pub fn cognitive_complexity(function: Node, source: &str) -> u32 {
+    let mut complexity = 0;
+    // Nesting level the children of the most recently visited node at each tree depth start at
+    let mut nesting_at: Vec<u32> = Vec::new();
+
+    walk_descendants(function, |node, tree_depth| {
+        let nesting = match tree_depth {
+            0 => 0,
+            _ => nesting_at[tree_depth - 1],
+        };
+        let is_else_if = is_conditional(node) && node.parent().is_some_and(|parent| is_else(parent));
+
+        let nested = if tree_depth == 0 {
+            0
+        } else if is_else_if {
+            // Counted by its `else`, and its body sits level with the first branch
+            nesting
+        } else if is_else(node) {
+            complexity += 1;
+            nesting
+        } else if is_conditional(node) || is_loop_or_branch(node) {
+            complexity += 1 + nesting;
+            nesting + 1
+        } else if FUNCTION_KINDS.contains(&node.kind()) || matches!(node.kind(), "closure_expression" | "lambda") {
+            nesting + 1
+        } else {
+            if starts_operator_run(node, source) {
+                complexity += 1;
+            }
+            nesting
+        };
+
+        nesting_at.truncate(tree_depth);
+        nesting_at.push(nested);
+        true
+    });
+
+    complexity
+}
+
+fn is_conditional(node: Node) -> bool {
+    matches!(node.kind(), "if_statement" | "if_expression")
+        || (node.is_named() && matches!(node.kind(), "if" | "unless"))
+}
+
+fn is_else(node: Node) -> bool {
+    matches!(node.kind(), "else_clause" | "elif_clause")
+        || (node.is_named() && matches!(node.kind(), "else" | "elsif"))
+}
+
+fn is_loop_or_branch(node: Node) -> bool {
+    if node.is_named() && matches!(node.kind(), "while" | "until" | "for" | "case" | "conditional" | "rescue") {
+        return true;
+    }
+    matches!(node.kind(),
+        "while_statement" | "while_expression" | "do_statement" |
+        "for_statement" | "for_expression" | "for_in_statement" | "loop_expression" |
+        "switch_statement" | "match_expression" |
+        "catch_clause" | "except_clause" |
+        "conditional_expression" | "ternary_expression")
+}
+
+/// A `&&`/`||` (`and`/`or`) whose parent is not the same operator, so `a && b && c` counts once
+fn starts_operator_run(node: Node, source: &str) -> bool {
+    let Some(operator) = logical_operator(node, source) else {
+        return false;
+    };
+    node.parent().and_then(|parent| logical_operator(parent, source)) != Some(operator)
+}
+
+fn logical_operator<'a>(node: Node, source: &'a str) -> Option<&'a str> {
+    if !matches!(node.kind(), "binary_expression" | "boolean_operator" | "binary") {
+        return None;
+    }
+    let operator = source.get(node.child_by_field_name("operator")?.byte_range())?;
+    matches!(operator, "&&" | "||" | "and" | "or").then_some(operator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::parsers::{pool, SupportedLanguage};
+
+    #[test]
+    fn test_nested_branches_weigh_more() -> Result<()> {
+        let source = "\
+function route(request, user) {
+  if (user && user.active && request.ok) {
+    for (const item of request.items) {
+      if (item.hidden) {
+        continue;
+      }
+    }
+  } else if (request.retry) {
+    return retry(request);
+  } else {
+    return null;
+  }
+}
+
+function flat(a) {
+  return a ? 1 : 2;
+}
+";
+        let tree = pool::parse(SupportedLanguage::JavaScript, source)?;
+        let shapes = for_tree(tree.root_node(), source);
+        // if 1, && 1, for 2, nested if 3, else if 1, else 1
+        assert_eq!(shapes[&1].cognitive_complexity, 9);
+        assert!(shapes[&1].nesting_depth > shapes[&15].nesting_depth);
+        assert_eq!(shapes[&15].cognitive_complexity, 1);
+        Ok(())
+    }
+}
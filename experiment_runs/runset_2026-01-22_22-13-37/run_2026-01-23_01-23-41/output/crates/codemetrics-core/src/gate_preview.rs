@@ -0,0 +1,475 @@
+//! What other thresholds or gates would change, before they are enforced
+//!
+//! One result set (a fresh analysis or a saved `analyze --format json` file)
+//! is judged twice: under the policy in force and under a proposed one. The
+//! findings the proposal adds or makes more severe are listed, with every gate
+//! and its verdict under both, so a team sees the cost of tightening before
+//! switching it on. Both sides use project-wide thresholds; `[[overrides]]`
+//! directories are judged like the rest.
+//!
+//! A saved result set only holds the functions its own thresholds flagged,
+//! unless it was written with per-function records. Findings below those
+//! thresholds can only be previewed on a directory, which is analyzed afresh.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+use crate::analyzers::AnalysisResults;
+use crate::core::IssueSeverity;
+use crate::gates::{self, Gate, GateInputs};
+use crate::thresholds::{ThresholdLevel, Thresholds};
+
+/// Text output lists at most this many findings
+const MAX_LISTED: usize = 50;
+
+/// Thresholds and the gates checked against them
+#[derive(Debug, Clone)]
+pub struct Policy {
+    /// Shown in reports, e.g. `current` or `strict`
+    pub name: String,
+    pub thresholds: Thresholds,
+    pub gates: Vec<Gate>,
+}
+
+/// A function as far as thresholds are concerned
+#[derive(Debug, Clone, Deserialize)]
+struct FunctionFacts {
+    name: String,
+    file_path: String,
+    complexity: u32,
+    line_start: u32,
+    line_end: u32,
+    parameters: u32,
+}
+
+/// Results reduced to what thresholds and gates judge
+#[derive(Debug)]
+pub struct ResultSet {
+    functions: Vec<FunctionFacts>,
+    /// Summed function complexity per file
+    files: BTreeMap<String, u32>,
+    /// Thresholds the results were classified with
+    pub thresholds: Thresholds,
+    /// Whether every function is present, not only those `thresholds` flagged
+    pub complete: bool,
+    /// Gate metrics that do not depend on thresholds; the issue counts are redone per policy
+    gate_inputs: GateInputs,
+}
+
+/// The fields of an `analyze --format json` file a preview needs
+#[derive(Deserialize)]
+struct JsonResults {
+    thresholds: Thresholds,
+    files_analyzed: usize,
+    average_complexity: f64,
+    #[serde(default)]
+    weighted_average_complexity: f64,
+    complexity_distribution: HashMap<u32, u32>,
+    high_complexity_functions: Vec<FunctionFacts>,
+    #[serde(default)]
+    functions: Vec<FunctionFacts>,
+    high_complexity_files: Vec<JsonFile>,
+    #[serde(default)]
+    language_quality: Vec<JsonQuality>,
+    #[serde(default)]
+    health: Option<JsonHealth>,
+    #[serde(default)]
+    errors: Vec<String>,
+    #[serde(default)]
+    duplication_percent: Option<f64>,
+    #[serde(default)]
+    file_duplication: Vec<JsonDuplication>,
+    #[serde(default)]
+    issues: Vec<JsonIssue>,
+}
+
+#[derive(Deserialize)]
+struct JsonIssue {
+    severity: IssueSeverity,
+}
+
+#[derive(Deserialize)]
+struct JsonFile {
+    file_path: String,
+    total_complexity: u32,
+}
+
+#[derive(Deserialize)]
+struct JsonQuality {
+    maintainability: f64,
+}
+
+#[derive(Deserialize)]
+struct JsonHealth {
+    overall: f64,
+}
+
+#[derive(Deserialize)]
+struct JsonDuplication {
+    duplication_percent: f64,
+}
+
+impl ResultSet {
+    pub fn from_results(results: &AnalysisResults) -> Self {
+        let records = results.functions.iter().map(|func| FunctionFacts {
+            name: func.name.clone(),
+            file_path: func.file_path.clone(),
+            complexity: func.complexity,
+            line_start: func.line_start,
+            line_end: func.line_end,
+            parameters: func.parameters,
+        });
+        let flagged = results.high_complexity_functions.iter().map(|func| FunctionFacts {
+            name: func.name.clone(),
+            file_path: func.file_path.clone(),
+            complexity: func.complexity,
+            line_start: func.line_start,
+            line_end: func.line_end,
+            parameters: func.parameters,
+        });
+        let complete = !results.functions.is_empty();
+        // Every file's complexity is kept in memory, though not written to JSON
+        let files = if results.file_complexity.is_empty() {
+            results.high_complexity_files.iter().map(|file| (file.file_path.clone(), file.total_complexity)).collect()
+        } else {
+            results.file_complexity.iter().map(|(file, &complexity)| (file.clone(), complexity)).collect()
+        };
+        Self {
+            functions: if complete { records.collect() } else { flagged.collect() },
+            files,
+            thresholds: results.thresholds.clone(),
+            complete,
+            gate_inputs: GateInputs::of(results),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let results: JsonResults = serde_json::from_str(json)
+            .context("Not a JSON result set from `analyze --format json`")?;
+        let complete = !results.functions.is_empty();
+        let files = if complete {
+            results.functions.iter().fold(BTreeMap::new(), |mut files, func| {
+                *files.entry(func.file_path.clone()).or_insert(0) += func.complexity;
+                files
+            })
+        } else {
+            results.high_complexity_files.iter().map(|file| (file.file_path.clone(), file.total_complexity)).collect()
+        };
+        let attempted = results.files_analyzed + results.errors.len();
+        let gate_inputs = GateInputs {
+            max_complexity: results.complexity_distribution.keys().max().copied().unwrap_or(0),
+            average_complexity: results.average_complexity,
+            weighted_average_complexity: results.weighted_average_complexity,
+            function_complexities: Vec::new(),
+            file_levels: Vec::new(),
+            finding_levels: results.issues.iter().map(|issue| ThresholdLevel::of_severity(&issue.severity)).collect(),
+            thresholds: results.thresholds.clone(),
+            maintainability: results.language_quality.iter().map(|quality| quality.maintainability).reduce(f64::min),
+            health: results.health.map(|health| health.overall),
+            parse_errors: match attempted {
+                0 => 0.0,
+                attempted => results.errors.len() as f64 / attempted as f64 * 100.0,
+            },
+            duplication: results.duplication_percent,
+            duplication_file: results.duplication_percent
+                .map(|_| results.file_duplication.first().map_or(0.0, |file| file.duplication_percent)),
+        };
+        Ok(Self {
+            functions: if complete { results.functions } else { results.high_complexity_functions },
+            files,
+            thresholds: results.thresholds,
+            complete,
+            gate_inputs,
+        })
+    }
+
+    /// Every finding under `thresholds`, in file order
+    fn judge(&self, thresholds: &Thresholds) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for func in &self.functions {
+            let finding = |check, value, limit, level| Finding {
+                file_path: func.file_path.clone(),
+                function: Some(func.name.clone()),
+                line: Some(func.line_start),
+                check,
+                value,
+                limit,
+                level,
+            };
+            let level = thresholds.function_level(func.complexity);
+            let limit = match level {
+                ThresholdLevel::Ok => None,
+                ThresholdLevel::Warn => Some(thresholds.function_complexity_warn),
+                ThresholdLevel::Error => Some(thresholds.function_complexity_error),
+                ThresholdLevel::Critical => thresholds.function_complexity_critical,
+            };
+            if let Some(limit) = limit {
+                findings.push(finding("complexity", func.complexity, limit, level));
+            }
+            if func.parameters > thresholds.max_parameters {
+                findings.push(finding("parameters", func.parameters, thresholds.max_parameters, ThresholdLevel::Warn));
+            }
+            let lines = func.line_end.saturating_sub(func.line_start) + 1;
+            if lines > thresholds.max_function_lines {
+                findings.push(finding("length", lines, thresholds.max_function_lines, ThresholdLevel::Warn));
+            }
+        }
+        for (file, &complexity) in &self.files {
+            let level = thresholds.file_level(complexity);
+            let limit = match level {
+                ThresholdLevel::Ok => continue,
+                ThresholdLevel::Warn => thresholds.file_complexity_warn,
+                _ => thresholds.file_complexity_error,
+            };
+            findings.push(Finding {
+                file_path: file.clone(),
+                function: None,
+                line: None,
+                check: "file complexity",
+                value: complexity,
+                limit,
+                level,
+            });
+        }
+        findings
+    }
+
+    /// Gate metrics with high-complexity functions and files counted under `thresholds`
+    fn gate_inputs(&self, thresholds: &Thresholds) -> GateInputs {
+        GateInputs {
+            function_complexities: self.functions.iter()
+                .map(|func| func.complexity)
+                .filter(|&complexity| thresholds.function_level(complexity) != ThresholdLevel::Ok)
+                .collect(),
+            file_levels: self.files.values()
+                .map(|&complexity| thresholds.file_level(complexity))
+                .filter(|&level| level != ThresholdLevel::Ok)
+                .collect(),
+            thresholds: thresholds.clone(),
+            ..self.gate_inputs.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub file_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// `complexity`, `parameters`, `length` or `file complexity`
+    pub check: &'static str,
+    pub value: u32,
+    /// The threshold `value` reached
+    pub limit: u32,
+    pub level: ThresholdLevel,
+}
+
+impl Finding {
+    fn key(&self) -> (&str, Option<&str>, Option<u32>, &'static str) {
+        (&self.file_path, self.function.as_deref(), self.line, self.check)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FindingChange {
+    #[serde(flatten)]
+    pub finding: Finding,
+    /// Its level under the current policy; `None` for a new finding
+    pub was: Option<ThresholdLevel>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GateVerdict {
+    pub gate: String,
+    pub current_value: Option<f64>,
+    pub proposed_value: Option<f64>,
+    /// Whether the current policy has this gate and it fails
+    pub fails_now: bool,
+    /// Whether the proposed policy has this gate and it fails
+    pub fails_proposed: bool,
+}
+
+/// How the proposed policy differs from the current one on the same results
+#[derive(Debug, Serialize)]
+pub struct Preview {
+    pub current: String,
+    pub proposed: String,
+    /// Threshold names with their current and proposed values, where they differ
+    pub threshold_changes: Vec<(&'static str, u32, u32)>,
+    /// Findings the proposal adds or makes more severe, most severe first
+    pub added: Vec<FindingChange>,
+    /// Current findings the proposal would clear or make less severe
+    pub relaxed: usize,
+    pub gates: Vec<GateVerdict>,
+    /// False when the result set lacks functions below its recorded thresholds
+    pub complete: bool,
+}
+
+/// Judge `set` under both policies
+pub fn preview(set: &ResultSet, current: &Policy, proposed: &Policy) -> Preview {
+    let now = set.judge(&current.thresholds);
+    let then = set.judge(&proposed.thresholds);
+    let now_levels: HashMap<_, _> = now.iter().map(|finding| (finding.key(), finding.level)).collect();
+    let then_levels: HashMap<_, _> = then.iter().map(|finding| (finding.key(), finding.level)).collect();
+
+    let mut added: Vec<FindingChange> = then.iter()
+        .filter_map(|finding| {
+            let was = now_levels.get(&finding.key()).copied();
+            (was < Some(finding.level)).then(|| FindingChange { finding: finding.clone(), was })
+        })
+        .collect();
+    added.sort_by(|a, b| b.finding.level.cmp(&a.finding.level)
+        .then_with(|| (&a.finding.file_path, a.finding.line).cmp(&(&b.finding.file_path, b.finding.line))));
+    let relaxed = now.iter()
+        .filter(|finding| then_levels.get(&finding.key()).copied() < Some(finding.level))
+        .count();
+
+    let current_inputs = set.gate_inputs(&current.thresholds);
+    let proposed_inputs = set.gate_inputs(&proposed.thresholds);
+    let mut seen: Vec<Gate> = Vec::new();
+    let gates = current.gates.iter().chain(&proposed.gates)
+        .filter(|gate| {
+            let new = !seen.contains(*gate);
+            seen.push((*gate).clone());
+            new
+        })
+        .map(|gate| {
+            let fails = |policy: &Policy, inputs: &GateInputs| {
+                policy.gates.contains(gate) && !gates::evaluate_inputs(std::slice::from_ref(gate), inputs).is_empty()
+            };
+            GateVerdict {
+                gate: gate.to_string(),
+                current_value: gate.measure_inputs(&current_inputs),
+                proposed_value: gate.measure_inputs(&proposed_inputs),
+                fails_now: fails(current, &current_inputs),
+                fails_proposed: fails(proposed, &proposed_inputs),
+            }
+        })
+        .collect();
+
+    Preview {
+        current: current.name.clone(),
+        proposed: proposed.name.clone(),
+        threshold_changes: threshold_changes(&current.thresholds, &proposed.thresholds),
+        added,
+        relaxed,
+        gates,
+        complete: set.complete,
+    }
+}
+
+impl Preview {
+    /// Gates that pass now but would fail under the proposal
+    pub fn newly_failing(&self) -> impl Iterator<Item = &GateVerdict> {
+        self.gates.iter().filter(|verdict| verdict.fails_proposed && !verdict.fails_now)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{} -> {}", self.current, self.proposed);
+        for (name, now, then) in &self.threshold_changes {
+            let _ = writeln!(out, "  {:<26} {} -> {}", name, now, then);
+        }
+
+        let new = self.added.iter().filter(|change| change.was.is_none()).count();
+        let _ = writeln!(out, "\nFindings: {} new, {} more severe, {} cleared or less severe", new, self.added.len() - new, self.relaxed);
+        for change in self.added.iter().take(MAX_LISTED) {
+            let finding = &change.finding;
+            let location = match (&finding.function, finding.line) {
+                (Some(function), Some(line)) => format!("{}:{} {}", finding.file_path, line, function),
+                _ => finding.file_path.clone(),
+            };
+            let level = match change.was {
+                None => format!("{:?}", finding.level).to_lowercase(),
+                Some(was) => format!("{:?} -> {:?}", was, finding.level).to_lowercase(),
+            };
+            let _ = writeln!(out, "  {:<14} {}  {} {} (limit {})", level, location, finding.check, finding.value, finding.limit);
+        }
+        if self.added.len() > MAX_LISTED {
+            let _ = writeln!(out, "  ... and {} more", self.added.len() - MAX_LISTED);
+        }
+
+        if !self.gates.is_empty() {
+            let _ = writeln!(out, "\nGates:");
+            let verdict = |fails: bool| if fails { "FAIL" } else { "pass" };
+            let value = |value: Option<f64>| value.map_or_else(|| "n/a".to_string(), |value| format!("{:.2}", value));
+            for gate in &self.gates {
+                let marker = if gate.fails_proposed && !gate.fails_now { "  <- newly failing" } else { "" };
+                let _ = writeln!(
+                    out, "  {:<24} {} -> {}  ({} -> {}){}",
+                    gate.gate, verdict(gate.fails_now), verdict(gate.fails_proposed),
+                    value(gate.current_value), value(gate.proposed_value), marker
+                );
+            }
+        }
+
+        if !self.complete {
+            let _ = writeln!(out, "\nThis result set only lists functions its own thresholds flagged; preview a directory to see findings below them.");
+        }
+        out
+    }
+}
+
+fn threshold_changes(now: &Thresholds, then: &Thresholds) -> Vec<(&'static str, u32, u32)> {
+    [
+        ("function_complexity_warn", now.function_complexity_warn, then.function_complexity_warn),
+        ("function_complexity_error", now.function_complexity_error, then.function_complexity_error),
+        ("file_complexity_warn", now.file_complexity_warn, then.file_complexity_warn),
+        ("file_complexity_error", now.file_complexity_error, then.file_complexity_error),
+        ("max_nesting_depth", now.max_nesting_depth, then.max_nesting_depth),
+        ("max_parameters", now.max_parameters, then.max_parameters),
+        ("max_function_lines", now.max_function_lines, then.max_function_lines),
+        ("max_generic_bounds", now.max_generic_bounds, then.max_generic_bounds),
+    ]
+    .into_iter()
+    .filter(|(_, now, then)| now != then)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stricter_thresholds_add_findings_and_flip_gates() {
+        let json = serde_json::json!({
+            "thresholds": Thresholds::default(),
+            "files_analyzed": 2,
+            "average_complexity": 6.0,
+            "complexity_distribution": { "3": 1, "9": 1, "13": 1 },
+            "high_complexity_functions": [
+                { "name": "route", "file_path": "src/a.rs", "complexity": 13, "line_start": 1, "line_end": 20, "parameters": 6 }
+            ],
+            "functions": [
+                { "name": "route", "file_path": "src/a.rs", "complexity": 13, "line_start": 1, "line_end": 20, "parameters": 6 },
+                { "name": "parse", "file_path": "src/a.rs", "complexity": 9, "line_start": 30, "line_end": 40, "parameters": 2 },
+                { "name": "tiny", "file_path": "src/b.rs", "complexity": 3, "line_start": 1, "line_end": 3, "parameters": 0 }
+            ],
+            "high_complexity_files": []
+        });
+        let set = ResultSet::from_json(&json.to_string()).unwrap();
+        assert!(set.complete);
+
+        let gates = vec![Gate::parse("issues.error>0").unwrap()];
+        let current = Policy { name: "current".to_string(), thresholds: set.thresholds.clone(), gates: gates.clone() };
+        let strict = Policy { name: "strict".to_string(), thresholds: Thresholds::preset("strict").unwrap(), gates };
+        let preview = preview(&set, &current, &strict);
+
+        // route: warn -> error and too many parameters; parse: newly at warn
+        let summary: Vec<(&str, &str, Option<ThresholdLevel>)> = preview.added.iter()
+            .map(|change| (change.finding.function.as_deref().unwrap(), change.finding.check, change.was))
+            .collect();
+        assert_eq!(summary, vec![
+            ("route", "complexity", Some(ThresholdLevel::Warn)),
+            ("route", "parameters", None),
+            ("parse", "complexity", None),
+        ]);
+        assert_eq!(preview.relaxed, 0);
+        assert_eq!(preview.newly_failing().map(|verdict| verdict.gate.as_str()).collect::<Vec<_>>(), vec!["issues.error>0"]);
+        assert!(preview.to_text().contains("newly failing"));
+    }
+}
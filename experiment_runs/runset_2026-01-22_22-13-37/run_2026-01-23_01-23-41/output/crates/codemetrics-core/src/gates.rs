@@ -0,0 +1,241 @@
+//! Quality gates that fail a CI run
+//!
+//! A gate is `<metric><op><limit>`, e.g. `complexity>15`, and is violated when
+//! the metric satisfies the comparison. Metrics:
+//!
+//! | Metric                | Value                                            |
+//! |-----------------------|--------------------------------------------------|
+//! | `complexity`          | highest function complexity                      |
+//! | `complexity.avg`      | average function complexity                      |
+//! | `complexity.weighted` | average function complexity weighted by length   |
+//! | `issues`              | high-complexity functions and files, findings    |
+//! | `issues.<level>`      | issues at `warn`, `error` or `critical` severity |
+//! | `maintainability`     | lowest per-language maintainability index        |
+//! | `health`              | overall health score                             |
+//! | `parse_errors`        | percentage of files that failed to parse         |
+//! | `duplication`         | percentage of lines inside repeated blocks       |
+//! | `duplication.file`    | the same percentage for the most duplicated file |
+
+use anyhow::{Context, Result};
+use std::fmt;
+
+use crate::analyzers::AnalysisResults;
+use crate::thresholds::{ThresholdLevel, Thresholds};
+
+const METRICS: &[&str] = &[
+    "complexity",
+    "complexity.avg",
+    "complexity.weighted",
+    "issues",
+    "issues.warn",
+    "issues.error",
+    "issues.critical",
+    "maintainability",
+    "health",
+    "parse_errors",
+    "duplication",
+    "duplication.file",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl Comparison {
+    fn holds(self, value: f64, limit: f64) -> bool {
+        match self {
+            Comparison::Greater => value > limit,
+            Comparison::GreaterOrEqual => value >= limit,
+            Comparison::Less => value < limit,
+            Comparison::LessOrEqual => value <= limit,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gate {
+    pub metric: String,
+    pub comparison: Comparison,
+    pub limit: f64,
+}
+
+impl Gate {
+    pub fn parse(spec: &str) -> Result<Self> {
+        // Two-character operators first so `>=` is not read as `>` followed by `=15`
+        let (index, comparison) = [
+            (">=", Comparison::GreaterOrEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">", Comparison::Greater),
+            ("<", Comparison::Less),
+        ]
+        .into_iter()
+        .find_map(|(symbol, comparison)| spec.find(symbol).map(|index| (index, comparison)))
+        .with_context(|| format!("Gate '{}' needs a comparison such as complexity>15", spec))?;
+
+        let metric = spec[..index].trim();
+        if !METRICS.contains(&metric) {
+            anyhow::bail!("Unknown gate metric '{}', expected one of: {}", metric, METRICS.join(", "));
+        }
+        let limit = spec[index + comparison.symbol().len()..].trim();
+        let limit = limit.parse()
+            .with_context(|| format!("Gate '{}' has a non-numeric limit '{}'", spec, limit))?;
+
+        Ok(Self { metric: metric.to_string(), comparison, limit })
+    }
+
+    /// The metric's value, or `None` when the run did not measure it
+    pub fn measure(&self, results: &AnalysisResults) -> Option<f64> {
+        self.measure_inputs(&GateInputs::of(results))
+    }
+
+    pub fn measure_inputs(&self, inputs: &GateInputs) -> Option<f64> {
+        let issues_at = |level: ThresholdLevel| {
+            let functions = inputs.function_complexities.iter()
+                .filter(|&&complexity| inputs.thresholds.function_level(complexity) == level)
+                .count();
+            let files = inputs.file_levels.iter().filter(|&&file| file == level).count();
+            let findings = inputs.finding_levels.iter().filter(|&&finding| finding == level).count();
+            (functions + files + findings) as f64
+        };
+
+        match self.metric.as_str() {
+            "complexity" => Some(inputs.max_complexity as f64),
+            "complexity.avg" => Some(inputs.average_complexity),
+            "complexity.weighted" => Some(inputs.weighted_average_complexity),
+            "issues" => Some((inputs.function_complexities.len() + inputs.file_levels.len() + inputs.finding_levels.len()) as f64),
+            "issues.warn" => Some(issues_at(ThresholdLevel::Warn)),
+            "issues.error" => Some(issues_at(ThresholdLevel::Error)),
+            "issues.critical" => Some(issues_at(ThresholdLevel::Critical)),
+            "maintainability" => inputs.maintainability,
+            "health" => inputs.health,
+            "parse_errors" => Some(inputs.parse_errors),
+            "duplication" => inputs.duplication,
+            "duplication.file" => inputs.duplication_file,
+            _ => None,
+        }
+    }
+}
+
+/// What gates read from a run
+///
+/// Kept apart from `AnalysisResults` so saved results can be gated again,
+/// e.g. under other thresholds by `gate_preview`.
+#[derive(Debug, Clone, Default)]
+pub struct GateInputs {
+    pub max_complexity: u32,
+    pub average_complexity: f64,
+    pub weighted_average_complexity: f64,
+    /// Complexity of each high-complexity function
+    pub function_complexities: Vec<u32>,
+    /// Severity of each high-complexity file
+    pub file_levels: Vec<ThresholdLevel>,
+    /// Severity of each security, concurrency or resource finding
+    pub finding_levels: Vec<ThresholdLevel>,
+    /// Thresholds that decide each function's severity
+    pub thresholds: Thresholds,
+    /// Lowest per-language maintainability index
+    pub maintainability: Option<f64>,
+    pub health: Option<f64>,
+    /// Percentage of files that failed to parse
+    pub parse_errors: f64,
+    pub duplication: Option<f64>,
+    /// Duplication of the most duplicated file, when clone detection ran
+    pub duplication_file: Option<f64>,
+}
+
+impl GateInputs {
+    pub fn of(results: &AnalysisResults) -> Self {
+        Self {
+            max_complexity: results.complexity_distribution.keys().max().copied().unwrap_or(0),
+            average_complexity: results.average_complexity,
+            weighted_average_complexity: results.weighted_average_complexity,
+            function_complexities: results.high_complexity_functions.iter().map(|func| func.complexity).collect(),
+            file_levels: results.high_complexity_files.iter().map(|file| file.level).collect(),
+            finding_levels: results.issues.iter().map(|found| ThresholdLevel::of_severity(&found.issue.severity)).collect(),
+            thresholds: results.thresholds.clone(),
+            maintainability: results.language_quality.iter()
+                .map(|quality| quality.maintainability)
+                .reduce(f64::min),
+            health: results.health.as_ref().map(|health| health.overall),
+            parse_errors: results.parse_error_rate(),
+            duplication: results.duplication_percent,
+            duplication_file: results.duplication_percent.map(|_| {
+                results.file_duplication.first().map_or(0.0, |file| file.duplication_percent)
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Gate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.metric, self.comparison.symbol(), self.limit)
+    }
+}
+
+#[derive(Debug)]
+pub struct GateFailure {
+    pub gate: Gate,
+    pub actual: f64,
+}
+
+/// Every gate whose condition holds; unmeasured metrics never fail a gate
+pub fn evaluate(gates: &[Gate], results: &AnalysisResults) -> Vec<GateFailure> {
+    evaluate_inputs(gates, &GateInputs::of(results))
+}
+
+pub fn evaluate_inputs(gates: &[Gate], inputs: &GateInputs) -> Vec<GateFailure> {
+    gates.iter()
+        .filter_map(|gate| {
+            let actual = gate.measure_inputs(inputs)?;
+            gate.comparison.holds(actual, gate.limit).then(|| GateFailure { gate: gate.clone(), actual })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::CodeAnalyzer;
+
+    #[test]
+    fn test_gates_parse_and_evaluate() {
+        let gate = Gate::parse("complexity >= 15").unwrap();
+        assert_eq!(gate.comparison, Comparison::GreaterOrEqual);
+        assert_eq!(gate.limit, 15.0);
+        assert!(Gate::parse("lines>10").is_err());
+        assert!(Gate::parse("complexity").is_err());
+        assert!(Gate::parse("complexity>high").is_err());
+
+        let mut results = CodeAnalyzer::new().aggregate_results(Vec::new()).unwrap();
+        results.complexity_distribution.insert(22, 1);
+        results.complexity_distribution.insert(3, 4);
+        let gates = [
+            Gate::parse("complexity>15").unwrap(),
+            Gate::parse("issues.critical>0").unwrap(),
+            Gate::parse("maintainability<50").unwrap(),
+            Gate::parse("duplication>5").unwrap(),
+        ];
+        let failures = evaluate(&gates, &results);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].gate.to_string(), "complexity>15");
+        assert_eq!(failures[0].actual, 22.0);
+
+        results.duplication_percent = Some(7.5);
+        let failures = evaluate(&gates, &results);
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[1].actual, 7.5);
+    }
+}
@@ -0,0 +1,182 @@
+//! Goroutine and channel usage in Go code
+//!
+//! Counts, per function and file, the goroutines launched (`go` statements),
+//! channel operations (sends and `<-` receives, including those in `select`
+//! cases) and `select` statements. Work inside a function literal counts
+//! toward the named function containing it, so `go func() { ... }()` bodies
+//! stay with their launcher.
+//!
+//! A function with at least `HEAVY_CONCURRENCY` such constructs whose
+//! cyclomatic complexity also reaches the warning threshold is a concurrency
+//! hotspot (`rules::CONCURRENCY_HOTSPOT`): its branches multiply with its
+//! interleavings, which is where deadlocks, leaks and races hide.
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::ast_analyzer::{decision_weight, walk_descendants};
+use crate::error_handling::ErrorHandlingWeights;
+
+/// Goroutines, channel operations and selects that make a function concurrency-heavy
+pub const HEAVY_CONCURRENCY: u32 = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionConcurrency {
+    /// `Type.Method` for methods
+    pub name: String,
+    pub line: u32,
+    pub goroutines: u32,
+    /// Sends and receives, including those in `select` cases
+    pub channel_operations: u32,
+    pub selects: u32,
+    pub complexity: u32,
+    /// Heavy concurrency in a complex function, when the rule is enabled
+    pub hotspot: bool,
+}
+
+impl FunctionConcurrency {
+    pub fn operations(&self) -> u32 {
+        self.goroutines + self.channel_operations + self.selects
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileConcurrency {
+    pub file_path: String,
+    pub goroutines: u32,
+    pub channel_operations: u32,
+    pub selects: u32,
+    /// Functions that launch goroutines or use channels, busiest first
+    pub functions: Vec<FunctionConcurrency>,
+}
+
+/// The files among `files` that use any concurrency, busiest first
+pub fn measure(files: impl IntoIterator<Item = FileConcurrency>) -> Vec<FileConcurrency> {
+    let mut files: Vec<FileConcurrency> = files.into_iter()
+        .filter(|file| !file.functions.is_empty())
+        .collect();
+    files.sort_by_key(|file| std::cmp::Reverse(file.goroutines + file.channel_operations + file.selects));
+    files
+}
+
+/// Concurrency of one Go source file under `root`
+///
+/// `hotspot_complexity` is the complexity from which a concurrency-heavy
+/// function is a hotspot, or `None` when hotspots are not reported.
+pub fn for_tree(path: &str, root: Node, source: &str, weights: &ErrorHandlingWeights, hotspot_complexity: Option<u32>) -> FileConcurrency {
+    let mut functions = Vec::new();
+    let mut cursor = root.walk();
+    for node in root.named_children(&mut cursor) {
+        let name = match node.kind() {
+            "function_declaration" => text(node.child_by_field_name("name"), source),
+            "method_declaration" => match receiver_type(node, source) {
+                Some(receiver) => format!("{}.{}", receiver, text(node.child_by_field_name("name"), source)),
+                None => text(node.child_by_field_name("name"), source),
+            },
+            _ => continue,
+        };
+        let mut function = measure_function(node, name, source, weights);
+        if function.operations() == 0 {
+            continue;
+        }
+        function.hotspot = hotspot_complexity.is_some_and(|limit| function.operations() >= HEAVY_CONCURRENCY && function.complexity >= limit);
+        functions.push(function);
+    }
+    functions.sort_by_key(|function| std::cmp::Reverse(function.operations()));
+
+    FileConcurrency {
+        file_path: path.to_string(),
+        goroutines: functions.iter().map(|function| function.goroutines).sum(),
+        channel_operations: functions.iter().map(|function| function.channel_operations).sum(),
+        selects: functions.iter().map(|function| function.selects).sum(),
+        functions,
+    }
+}
+
+fn measure_function(node: Node, name: String, source: &str, weights: &ErrorHandlingWeights) -> FunctionConcurrency {
+    let mut function = FunctionConcurrency {
+        name,
+        line: node.start_position().row as u32 + 1,
+        goroutines: 0,
+        channel_operations: 0,
+        selects: 0,
+        complexity: 1,
+        hotspot: false,
+    };
+    walk_descendants(node, |node, _| {
+        match node.kind() {
+            "go_statement" => function.goroutines += 1,
+            "send_statement" => function.channel_operations += 1,
+            "unary_expression" if node.child_by_field_name("operator").is_some_and(|operator| operator.kind() == "<-") => {
+                function.channel_operations += 1;
+            }
+            "select_statement" => function.selects += 1,
+            _ => {}
+        }
+        function.complexity += decision_weight(node, source, weights);
+        true
+    });
+    function
+}
+
+/// `Server` from a `(s *Server)` or `(s Server[T])` receiver
+fn receiver_type(node: Node, source: &str) -> Option<String> {
+    let receivers = node.child_by_field_name("receiver")?;
+    let mut cursor = receivers.walk();
+    let receiver = receivers.named_children(&mut cursor).find(|child| child.kind() == "parameter_declaration")?;
+    let type_text = &source[receiver.child_by_field_name("type")?.byte_range()];
+    Some(type_text.trim_start_matches('*').split('[').next().unwrap_or(type_text).to_string())
+}
+
+fn text(node: Option<Node>, source: &str) -> String {
+    node.map_or_else(|| "anonymous".to_string(), |node| source[node.byte_range()].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::parsers::{pool, SupportedLanguage};
+
+    #[test]
+    fn test_counts_and_hotspots_per_function() -> Result<()> {
+        let source = "\
+package main
+
+func (p *Pool) Run(jobs <-chan Job, results chan<- Result, done chan struct{}) {
+\tfor i := 0; i < p.size; i++ {
+\t\tgo func() {
+\t\t\tfor job := range jobs {
+\t\t\t\tif job.Skip {
+\t\t\t\t\tcontinue
+\t\t\t\t}
+\t\t\t\tresults <- job.Do()
+\t\t\t}
+\t\t}()
+\t}
+\tselect {
+\tcase <-done:
+\t\treturn
+\tcase results <- Result{}:
+\t}
+}
+
+func plain(x int) int {
+\treturn x
+}
+";
+        let tree = pool::parse(SupportedLanguage::Go, source)?;
+        let file = for_tree("pool.go", tree.root_node(), source, &ErrorHandlingWeights::default(), Some(4));
+        assert_eq!(file.functions.len(), 1);
+        let run = &file.functions[0];
+        assert_eq!(run.name, "Pool.Run");
+        assert_eq!((run.goroutines, run.channel_operations, run.selects), (1, 3, 1));
+        // Two loops and an `if`; select cases are not counted as branches
+        assert_eq!(run.complexity, 4);
+        assert!(run.hotspot);
+
+        let relaxed = for_tree("pool.go", tree.root_node(), source, &ErrorHandlingWeights::default(), None);
+        assert!(!relaxed.functions[0].hotspot);
+        Ok(())
+    }
+}
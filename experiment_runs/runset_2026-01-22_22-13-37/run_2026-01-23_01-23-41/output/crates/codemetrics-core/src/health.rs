@@ -0,0 +1,252 @@
+//! Weighted project health score
+//!
+//! The overall score is a weighted mean of per-dimension sub-scores, each in
+//! the range 0–100 (higher is healthier):
+//!
+//! | Dimension   | Input                                   | 100 when          | 0 when            |
+//! |-------------|-----------------------------------------|-------------------|-------------------|
+//! | complexity  | average complexity, high-complexity share | avg ≤ 1, none high | avg ≥ 15, all high |
+//! | duplication | duplicated lines percentage             | 0%                | ≥ 50%             |
+//! | docs        | comment-to-code ratio                   | ≥ 20%             | 0%                |
+//! | tests       | test files and files with inline tests per source file | ≥ 0.5 | 0          |
+//! | deps        | average imports per file                | ≤ 5               | ≥ 30              |
+//!
+//! Dimensions without data are reported as unavailable and their weight is
+//! redistributed over the remaining ones, so a missing input never drags the
+//! score down. Every reporter and badge reads the same `HealthScore`.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::analyzers::AnalysisResults;
+
+/// Relative weight of each dimension; they need not sum to one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthModel {
+    pub complexity: f64,
+    pub duplication: f64,
+    pub docs: f64,
+    pub tests: f64,
+    pub deps: f64,
+}
+
+impl Default for HealthModel {
+    fn default() -> Self {
+        Self {
+            complexity: 0.35,
+            duplication: 0.2,
+            docs: 0.15,
+            tests: 0.2,
+            deps: 0.1,
+        }
+    }
+}
+
+/// Raw measurements feeding the model; `None` marks a dimension as unmeasured
+#[derive(Debug, Clone, Default)]
+pub struct HealthInputs {
+    pub average_complexity: Option<f64>,
+    pub high_complexity_ratio: Option<f64>,
+    pub duplication_percent: Option<f64>,
+    pub comment_ratio: Option<f64>,
+    pub test_file_ratio: Option<f64>,
+    pub average_imports_per_file: Option<f64>,
+}
+
+impl HealthInputs {
+    pub fn from_results(results: &AnalysisResults) -> Self {
+        let high_complexity_ratio = if results.total_functions > 0 {
+            Some(results.high_complexity_functions.len() as f64 / results.total_functions as f64)
+        } else {
+            None
+        };
+
+        let files = results.files_analyzed;
+        let (comment_lines, imports) = results.language_breakdown.values()
+            .fold((0, 0), |(comments, imports), stats| (comments + stats.comment_lines, imports + stats.imports));
+        let code_lines = results.total_lines.saturating_sub(comment_lines);
+
+        Self {
+            average_complexity: (results.total_functions > 0).then_some(results.average_complexity),
+            high_complexity_ratio,
+            duplication_percent: results.duplication_percent,
+            comment_ratio: (code_lines > 0).then(|| comment_lines as f64 / code_lines as f64),
+            test_file_ratio: results.test_file_ratio(),
+            average_imports_per_file: (files > 0).then(|| imports as f64 / files as f64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubScore {
+    pub dimension: &'static str,
+    pub score: Option<f64>,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthScore {
+    pub overall: f64,
+    pub grade: char,
+    pub sub_scores: Vec<SubScore>,
+}
+
+impl HealthModel {
+    /// Weights must be finite and non-negative, and not all zero
+    pub fn validate(&self) -> Result<()> {
+        let weights = [self.complexity, self.duplication, self.docs, self.tests, self.deps];
+        if weights.iter().any(|weight| !weight.is_finite() || *weight < 0.0) {
+            bail!("health weights must be non-negative numbers");
+        }
+        if weights.iter().all(|weight| *weight == 0.0) {
+            bail!("at least one health weight must be positive");
+        }
+        Ok(())
+    }
+
+    pub fn score(&self, inputs: &HealthInputs) -> HealthScore {
+        let complexity = inputs.average_complexity.map(|avg| {
+            let average_part = 1.0 - ((avg - 1.0) / 14.0).clamp(0.0, 1.0);
+            let high_part = 1.0 - inputs.high_complexity_ratio.unwrap_or(0.0).clamp(0.0, 1.0);
+            (average_part * 0.6 + high_part * 0.4) * 100.0
+        });
+        let duplication = inputs.duplication_percent
+            .map(|pct| (1.0 - pct / 50.0).clamp(0.0, 1.0) * 100.0);
+        let docs = inputs.comment_ratio
+            .map(|ratio| (ratio / 0.2).clamp(0.0, 1.0) * 100.0);
+        let tests = inputs.test_file_ratio
+            .map(|ratio| (ratio / 0.5).clamp(0.0, 1.0) * 100.0);
+        let deps = inputs.average_imports_per_file
+            .map(|imports| (1.0 - (imports - 5.0) / 25.0).clamp(0.0, 1.0) * 100.0);
+
+        let sub_scores = vec![
+            SubScore { dimension: "complexity", score: complexity, weight: self.complexity },
+            SubScore { dimension: "duplication", score: duplication, weight: self.duplication },
+            SubScore { dimension: "docs", score: docs, weight: self.docs },
+            SubScore { dimension: "tests", score: tests, weight: self.tests },
+            SubScore { dimension: "deps", score: deps, weight: self.deps },
+        ];
+
+        let (weighted_sum, total_weight) = sub_scores.iter()
+            .filter_map(|sub| sub.score.map(|score| (score * sub.weight, sub.weight)))
+            .fold((0.0, 0.0), |(sum, weight), (s, w)| (sum + s, weight + w));
+
+        let overall = if total_weight > 0.0 {
+            (weighted_sum / total_weight).round()
+        } else {
+            100.0
+        };
+
+        HealthScore {
+            overall,
+            grade: grade_for(overall),
+            sub_scores,
+        }
+    }
+}
+
+fn grade_for(score: f64) -> char {
+    match score {
+        s if s >= 90.0 => 'A',
+        s if s >= 75.0 => 'B',
+        s if s >= 60.0 => 'C',
+        s if s >= 40.0 => 'D',
+        _ => 'F',
+    }
+}
+
+impl HealthScore {
+    /// Badge color shared by every output that renders the score
+    pub fn badge_color(&self) -> &'static str {
+        match self.grade {
+            'A' => "brightgreen",
+            'B' => "green",
+            'C' => "yellow",
+            'D' => "orange",
+            _ => "red",
+        }
+    }
+
+    /// Shields.io endpoint JSON for a README badge
+    pub fn shields_endpoint(&self) -> serde_json::Value {
+        serde_json::json!({
+            "schemaVersion": 1,
+            "label": "code health",
+            "message": format!("{:.0} ({})", self.overall, self.grade),
+            "color": self.badge_color(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::{AnalysisConfig, CodeAnalyzer};
+
+    #[test]
+    fn test_missing_dimensions_are_redistributed() {
+        let model = HealthModel::default();
+        let inputs = HealthInputs {
+            average_complexity: Some(1.0),
+            high_complexity_ratio: Some(0.0),
+            ..Default::default()
+        };
+
+        let score = model.score(&inputs);
+        assert_eq!(score.overall, 100.0);
+        assert_eq!(score.grade, 'A');
+        assert!(score.sub_scores.iter().filter(|s| s.score.is_none()).count() == 4);
+    }
+
+    #[test]
+    fn test_weights_shift_overall_score() {
+        let inputs = HealthInputs {
+            average_complexity: Some(15.0),
+            high_complexity_ratio: Some(1.0),
+            comment_ratio: Some(0.2),
+            ..Default::default()
+        };
+
+        let docs_heavy = HealthModel { docs: 10.0, ..HealthModel::default() };
+        let complexity_heavy = HealthModel { complexity: 10.0, ..HealthModel::default() };
+
+        assert!(docs_heavy.score(&inputs).overall > complexity_heavy.score(&inputs).overall);
+    }
+
+    #[test]
+    fn test_docs_tests_and_deps_are_measured() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::create_dir_all(dir.path().join("tests")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "use std::fmt;\nuse std::io;\n\n/// Adds one\nfn add(x: i32) -> i32 {\n    x + 1\n}\n").unwrap();
+        std::fs::write(dir.path().join("src/app.py"), "import os\nfrom sys import argv\n# entry point\ndef main():\n    return os.getcwd()\n").unwrap();
+        std::fs::write(dir.path().join("tests/lib_test.rs"), "#[test]\nfn adds() {}\n").unwrap();
+
+        let analyzer = CodeAnalyzer::with_config(AnalysisConfig::builder().quiet(true).build().unwrap());
+        let results = analyzer.analyze_path(dir.path()).unwrap();
+        let inputs = HealthInputs::from_results(&results);
+        assert_eq!(inputs.average_imports_per_file, Some(2.0));
+        assert_eq!(inputs.test_file_ratio, Some(0.5));
+        assert_eq!(inputs.comment_ratio, Some(2.0 / 10.0));
+        let health = results.health.unwrap();
+        assert!(health.sub_scores.iter().all(|sub| sub.dimension == "duplication" || sub.score.is_some()));
+    }
+
+    #[test]
+    fn test_inline_rust_tests_count_towards_the_tests_dimension() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "fn add(x: i32) -> i32 {\n    x + 1\n}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn adds() {}\n}\n",
+        ).unwrap();
+        std::fs::write(dir.path().join("src/util.rs"), "pub fn id(x: u8) -> u8 {\n    x\n}\n").unwrap();
+
+        let analyzer = CodeAnalyzer::with_config(AnalysisConfig::builder().quiet(true).build().unwrap());
+        let results = analyzer.analyze_path(dir.path()).unwrap();
+        assert_eq!(HealthInputs::from_results(&results).test_file_ratio, Some(0.5));
+        let tests = results.health.unwrap().sub_scores.into_iter().find(|sub| sub.dimension == "tests").unwrap();
+        assert_eq!(tests.score, Some(100.0));
+    }
+}
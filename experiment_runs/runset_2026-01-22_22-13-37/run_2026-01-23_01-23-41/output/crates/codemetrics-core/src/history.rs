@@ -0,0 +1,187 @@
+//! Analysis snapshots over time, to see whether debt is growing or shrinking
+//!
+//! `history record` appends one summary line per commit to a JSON-lines file
+//! (re-recording a commit replaces its line), and `history show` or the HTML
+//! report's trend chart read it back. Each line keeps the headline metrics,
+//! per-language counts and the health dimensions, but no per-file or
+//! per-function detail, so the store stays small enough to commit or cache
+//! between CI runs. A snapshot of a working tree with uncommitted changes is
+//! marked dirty: its numbers do not belong to the commit it is filed under.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+use crate::analyzers::AnalysisResults;
+
+/// Default location relative to the analyzed root
+pub const HISTORY_FILE: &str = ".codemetrics/history.jsonl";
+
+/// Headline metrics of one analysis, keyed by the commit it ran on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub commit: String,
+    /// Recorded from a working tree with uncommitted changes to tracked files
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dirty: bool,
+    pub recorded_at: String,
+    pub files_analyzed: usize,
+    pub total_lines: u32,
+    pub total_functions: usize,
+    pub average_complexity: f64,
+    /// Average complexity weighted by function length; absent in snapshots recorded before it was tracked
+    pub weighted_average_complexity: Option<f64>,
+    /// High-complexity functions plus files, as counted by the `issues` gate
+    pub issues: usize,
+    /// Lowest per-language maintainability index
+    pub maintainability: Option<f64>,
+    pub health: Option<f64>,
+    pub duplication_percent: Option<f64>,
+    /// Health sub-scores that could be measured, by dimension
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub health_dimensions: BTreeMap<String, f64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub languages: BTreeMap<String, LanguageSnapshot>,
+}
+
+/// One language's share of a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageSnapshot {
+    pub files: usize,
+    pub lines: u32,
+    pub functions: usize,
+    pub average_complexity: f64,
+    pub high_complexity_functions: usize,
+}
+
+impl Snapshot {
+    pub fn from_results(results: &AnalysisResults, commit: &str, dirty: bool) -> Self {
+        Self {
+            commit: commit.to_string(),
+            dirty,
+            recorded_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            files_analyzed: results.files_analyzed,
+            total_lines: results.total_lines,
+            total_functions: results.total_functions,
+            average_complexity: results.average_complexity,
+            weighted_average_complexity: Some(results.weighted_average_complexity),
+            issues: results.issue_count(),
+            maintainability: results.language_quality.iter()
+                .map(|quality| quality.maintainability)
+                .reduce(f64::min),
+            health: results.health.as_ref().map(|health| health.overall),
+            duplication_percent: results.duplication_percent,
+            health_dimensions: results.health.iter()
+                .flat_map(|health| &health.sub_scores)
+                .filter_map(|sub| Some((sub.dimension.to_string(), sub.score?)))
+                .collect(),
+            languages: results.language_breakdown.iter()
+                .map(|(language, stats)| (language.clone(), LanguageSnapshot {
+                    files: stats.files,
+                    lines: stats.lines,
+                    functions: stats.functions,
+                    average_complexity: if stats.functions == 0 { 0.0 } else { stats.total_complexity as f64 / stats.functions as f64 },
+                    high_complexity_functions: stats.high_complexity_functions,
+                }))
+                .collect(),
+        }
+    }
+
+    /// Abbreviated commit, for tables and chart labels; dirty snapshots end in `+`
+    pub fn short_commit(&self) -> String {
+        let short = &self.commit[..self.commit.len().min(8)];
+        if self.dirty { format!("{}+", short) } else { short.to_string() }
+    }
+}
+
+/// Every snapshot in `path`, oldest first; a missing file is an empty history
+pub fn load(path: &Path) -> Result<Vec<Snapshot>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read history {}", path.display()))?;
+    content.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Invalid snapshot on line {} of {}", index + 1, path.display()))
+        })
+        .collect()
+}
+
+/// Add `snapshot` to the end of the history, or replace an earlier one for the
+/// same commit and the same dirtiness in place, so the history stays in commit
+/// order and a dirty run never overwrites a clean one
+pub fn record(path: &Path, snapshot: Snapshot) -> Result<()> {
+    let mut snapshots = load(path)?;
+    let existing = snapshots.iter()
+        .position(|existing| existing.commit == snapshot.commit && existing.dirty == snapshot.dirty);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match existing {
+        Some(index) => snapshots[index] = snapshot,
+        None => {
+            // Appending keeps concurrent readers (and diffs of a committed store) simple
+            let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)
+                .with_context(|| format!("Failed to open history {}", path.display()))?;
+            let line = serde_json::to_string(&snapshot)?;
+            return writeln!(file, "{}", line).with_context(|| format!("Failed to write history {}", path.display()));
+        }
+    }
+
+    let mut content = String::new();
+    for snapshot in &snapshots {
+        content.push_str(&serde_json::to_string(snapshot)?);
+        content.push('\n');
+    }
+    std::fs::write(path, content).with_context(|| format!("Failed to write history {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn snapshot(commit: &str, average_complexity: f64) -> Snapshot {
+        Snapshot {
+            commit: commit.to_string(),
+            dirty: false,
+            recorded_at: "2024-01-01T00:00:00Z".to_string(),
+            files_analyzed: 10,
+            total_lines: 1000,
+            total_functions: 50,
+            average_complexity,
+            weighted_average_complexity: None,
+            issues: 3,
+            maintainability: Some(70.0),
+            health: None,
+            duplication_percent: None,
+            health_dimensions: BTreeMap::new(),
+            languages: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_appends_and_replaces_by_commit() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join(HISTORY_FILE);
+        assert!(load(&path).unwrap().is_empty());
+
+        record(&path, snapshot("aaa", 4.0)).unwrap();
+        record(&path, snapshot("bbb", 5.0)).unwrap();
+        record(&path, snapshot("aaa", 3.5)).unwrap();
+        record(&path, Snapshot { dirty: true, ..snapshot("bbb", 9.0) }).unwrap();
+
+        let snapshots = load(&path).unwrap();
+        let commits: Vec<String> = snapshots.iter().map(Snapshot::short_commit).collect();
+        assert_eq!(commits, vec!["aaa", "bbb", "bbb+"]);
+        assert_eq!(snapshots[0].average_complexity, 3.5);
+        assert_eq!(snapshots[1].average_complexity, 5.0);
+    }
+}
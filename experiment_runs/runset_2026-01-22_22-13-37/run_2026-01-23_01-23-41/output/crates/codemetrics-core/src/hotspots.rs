@@ -0,0 +1,200 @@
+//! Change-frequency hotspots: complexity weighted by how often code changes
+//!
+//! A complex file nobody has touched in a year costs little; one edited every
+//! week is where defects and slow reviews pile up. Each analyzed file's total
+//! complexity is combined with its commit count over a window of history, in
+//! the spirit of CodeScene's hotspot map, to rank what to refactor first.
+//! Files made mostly of blocks repeated elsewhere are listed too: every fix in
+//! one of them has to be made again in each copy.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::analyzers::AnalysisResults;
+use crate::clone_detector::FileDuplication;
+use crate::js_project::normalize;
+use crate::vcs::{FileChurn, Vcs};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Share of a file's lines inside repeated blocks above which it is a hotspot
+const DUPLICATION_THRESHOLD: f64 = 10.0;
+
+/// Why a file is a hotspot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotspotKind {
+    /// Complex and changed often during the window
+    Churn,
+    /// Largely made of blocks repeated in other files
+    DuplicatedCode,
+}
+
+/// A file that changed during the window or is largely duplicated, with its complexity
+#[derive(Debug, Clone, Serialize)]
+pub struct Hotspot {
+    pub kind: HotspotKind,
+    pub file_path: String,
+    /// Summed complexity of the file's functions
+    pub complexity: u32,
+    pub commits: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub authors: usize,
+    /// Share of its lines inside repeated blocks, for `DuplicatedCode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplication_percent: Option<f64>,
+    /// 0–100: change frequency and complexity, each relative to the busiest and most
+    /// complex file; for `DuplicatedCode`, the duplicated share
+    pub score: f64,
+}
+
+/// Ranks analyzed files by complexity and recent change frequency
+pub struct HotspotAnalyzer {
+    /// How far back history is read, in days
+    window_days: i64,
+    now: i64,
+}
+
+impl HotspotAnalyzer {
+    pub fn new() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        Self { window_days: 90, now }
+    }
+
+    pub fn with_window_days(mut self, days: i64) -> Self {
+        self.window_days = days;
+        self
+    }
+
+    /// Fill `results.hotspots` from the history of the repository behind `vcs`
+    pub fn annotate(&self, results: &mut AnalysisResults, vcs: &dyn Vcs) -> Result<()> {
+        let history = vcs.churn(self.now - self.window_days * SECONDS_PER_DAY)?;
+        let current_dir = std::env::current_dir()?;
+        let base = std::fs::canonicalize(&current_dir).unwrap_or(current_dir);
+        let churned = rank(&results.file_complexity, &history, &base);
+        add(results, churned);
+        Ok(())
+    }
+
+    /// Add the files clone detection found largely duplicated; needs no history
+    pub fn annotate_duplication(&self, results: &mut AnalysisResults) {
+        let duplicated = duplicated(&results.file_duplication, &results.file_complexity);
+        add(results, duplicated);
+    }
+}
+
+/// Merge `hotspots` into `results`, hottest first
+fn add(results: &mut AnalysisResults, hotspots: Vec<Hotspot>) {
+    results.hotspots.extend(hotspots);
+    results.hotspots.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.file_path.cmp(&b.file_path)));
+}
+
+impl Default for HotspotAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Files that are both complex and changed, hottest first; untouched files are left out
+///
+/// History paths are absolute, analyzed paths are as given on the command
+/// line; relative ones are joined onto `base`, the canonical working directory.
+pub fn rank(file_complexity: &HashMap<String, u32>, history: &[FileChurn], base: &Path) -> Vec<Hotspot> {
+    let by_path: HashMap<&Path, &FileChurn> = history.iter().map(|churn| (churn.path.as_path(), churn)).collect();
+    let changed: Vec<(&String, u32, &FileChurn)> = file_complexity.iter()
+        .filter(|(_, &complexity)| complexity > 0)
+        .filter_map(|(file, &complexity)| {
+            let absolute = normalize(&base.join(file));
+            by_path.get(absolute.as_path()).map(|churn| (file, complexity, *churn))
+        })
+        .collect();
+
+    let max_commits = changed.iter().map(|(_, _, churn)| churn.commits).max().unwrap_or(0).max(1) as f64;
+    let max_complexity = changed.iter().map(|(_, complexity, _)| *complexity).max().unwrap_or(0).max(1) as f64;
+
+    let mut hotspots: Vec<Hotspot> = changed.into_iter()
+        .map(|(file, complexity, churn)| Hotspot {
+            kind: HotspotKind::Churn,
+            file_path: file.clone(),
+            complexity,
+            commits: churn.commits,
+            lines_added: churn.lines_added,
+            lines_removed: churn.lines_removed,
+            authors: churn.authors,
+            duplication_percent: None,
+            score: churn.commits as f64 / max_commits * complexity as f64 / max_complexity * 100.0,
+        })
+        .collect();
+    hotspots.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.file_path.cmp(&b.file_path)));
+    hotspots
+}
+
+/// Files with more than `DUPLICATION_THRESHOLD` percent of their lines in repeated blocks
+pub fn duplicated(file_duplication: &[FileDuplication], file_complexity: &HashMap<String, u32>) -> Vec<Hotspot> {
+    file_duplication.iter()
+        .filter(|file| file.duplication_percent > DUPLICATION_THRESHOLD)
+        .map(|file| Hotspot {
+            kind: HotspotKind::DuplicatedCode,
+            file_path: file.file_path.clone(),
+            complexity: file_complexity.get(&file.file_path).copied().unwrap_or(0),
+            commits: 0,
+            lines_added: 0,
+            lines_removed: 0,
+            authors: 0,
+            duplication_percent: Some(file.duplication_percent),
+            score: file.duplication_percent,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn churn(path: &str, commits: usize) -> FileChurn {
+        FileChurn { path: PathBuf::from(path), commits, lines_added: commits * 10, lines_removed: commits, authors: 1 }
+    }
+
+    #[test]
+    fn test_frequently_changed_complex_files_rank_first() {
+        let file_complexity = HashMap::from([
+            ("/repo/legacy.rs".to_string(), 80),
+            ("/repo/checkout.rs".to_string(), 40),
+            ("../config.rs".to_string(), 5),
+            ("/repo/untouched.rs".to_string(), 200),
+        ]);
+        let history = vec![churn("/repo/legacy.rs", 1), churn("/repo/checkout.rs", 20), churn("/repo/config.rs", 20)];
+
+        let hotspots = rank(&file_complexity, &history, Path::new("/repo/src"));
+        let ranked: Vec<&str> = hotspots.iter().map(|hotspot| hotspot.file_path.as_str()).collect();
+        assert_eq!(ranked, vec!["/repo/checkout.rs", "../config.rs", "/repo/legacy.rs"]);
+        assert!((hotspots[0].score - 50.0).abs() < 1e-9);
+        assert_eq!(hotspots[0].lines_added, 200);
+    }
+
+    #[test]
+    fn test_duplicated_files_become_hotspots() {
+        let file = |file_path: &str, duplication_percent| FileDuplication {
+            file_path: file_path.to_string(),
+            duplicated_lines: 0,
+            total_lines: 100,
+            duplication_percent,
+        };
+        let file_complexity = HashMap::from([("cart.rs".to_string(), 30)]);
+
+        let hotspots = duplicated(&[file("cart.rs", 60.0), file("main.rs", 5.0)], &file_complexity);
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!((hotspots[0].kind, hotspots[0].complexity), (HotspotKind::DuplicatedCode, 30));
+        assert_eq!(hotspots[0].duplication_percent, Some(60.0));
+        assert_eq!(hotspots[0].score, 60.0);
+    }
+}
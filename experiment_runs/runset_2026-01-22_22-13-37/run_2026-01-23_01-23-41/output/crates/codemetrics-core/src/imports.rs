@@ -0,0 +1,286 @@
+//! What each import statement brings into a file
+//!
+//! One `ImportInfo` is produced per imported module, with the module path as
+//! written (quotes stripped) and the names it binds locally:
+//!
+//! | Source                                   | `module_path` | names / binding          |
+//! |------------------------------------------|---------------|--------------------------|
+//! | `import React, { useState } from "react"` | `react`       | default `React`; `useState` |
+//! | `const { join: j } = require("path")`    | `path`        | `join as j`              |
+//! | `import numpy.linalg as la`              | `numpy.linalg`| binding `la`             |
+//! | `from ..util import fmt, parse as p`     | `..util`      | `fmt`, `parse as p`      |
+//! | `use std::{fmt, io::Read as _}`          | `std::fmt`, `std::io::Read` | bindings `fmt`, `_` |
+//! | `require_relative "pricing"`             | `./pricing`   | none                     |
+//!
+//! Rust use trees are flattened so every path resolves on its own. A `mod`
+//! declaration is not an import: the parent owns the child rather than
+//! depending on it, and counting it would close a cycle with every child that
+//! uses its parent. Relative Ruby requires are marked `./` like relative
+//! imports elsewhere, so they resolve as internal.
+
+use tree_sitter::Node;
+
+use crate::ast_analyzer::ImportInfo;
+use crate::core::Language;
+
+/// Imports made by `node`, an `@import` capture of the language's import query
+///
+/// Plugin languages declare where their imports are but not their shape, so
+/// nothing is extracted for them.
+pub fn extract(node: Node, content: &str, language: &Language) -> Vec<ImportInfo> {
+    let line = node.start_position().row as u32 + 1;
+    match (language, node.kind()) {
+        (Language::JavaScript | Language::TypeScript, "import_statement") => javascript_import(node, content, line),
+        (Language::JavaScript | Language::TypeScript, "call_expression") => javascript_require(node, content, line).into_iter().collect(),
+        (Language::Python, "import_statement") => python_import(node, content, line),
+        (Language::Python, "import_from_statement") => python_from_import(node, content, line).into_iter().collect(),
+        (Language::Rust, _) => rust_use(node, content, line),
+        (Language::Ruby, "call") => ruby_require(node, content, line).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `import d, * as ns, { a, b as c } from "m"` and the side-effect `import "m"`
+fn javascript_import(node: Node, content: &str, line: u32) -> Vec<ImportInfo> {
+    let Some(source) = node.child_by_field_name("source") else {
+        return Vec::new();
+    };
+    let module_path = unquoted(source, content);
+    let import = |imported_names, is_default, binding| ImportInfo { module_path: module_path.clone(), imported_names, is_default, binding, line, ..Default::default() };
+
+    let Some(clause) = named_children(node).into_iter().find(|child| child.kind() == "import_clause") else {
+        return vec![import(Vec::new(), false, None)];
+    };
+    let mut imports = Vec::new();
+    for part in named_children(clause) {
+        match part.kind() {
+            "identifier" => imports.push(import(Vec::new(), true, Some(text(part, content).to_string()))),
+            "namespace_import" => {
+                let binding = named_children(part).first().map(|name| text(*name, content).to_string());
+                imports.push(import(Vec::new(), false, binding));
+            }
+            "named_imports" => imports.push(import(import_specifiers(part, content), false, None)),
+            _ => {}
+        }
+    }
+    imports
+}
+
+/// `a` and `b as c` from `{ a, b as c }`
+fn import_specifiers(named_imports: Node, content: &str) -> Vec<String> {
+    named_children(named_imports).into_iter()
+        .filter(|specifier| specifier.kind() == "import_specifier")
+        .filter_map(|specifier| {
+            let name = unquoted(specifier.child_by_field_name("name")?, content);
+            Some(match specifier.child_by_field_name("alias") {
+                Some(alias) => format!("{} as {}", name, text(alias, content)),
+                None => name,
+            })
+        })
+        .collect()
+}
+
+/// `require("m")` and `import("m")`, with the names a declaration destructures from the module
+fn javascript_require(node: Node, content: &str, line: u32) -> Option<ImportInfo> {
+    let source = named_children(node.child_by_field_name("arguments")?).into_iter().next()?;
+    if source.kind() != "string" {
+        return None;
+    }
+    let mut import = ImportInfo { module_path: unquoted(source, content), line, ..Default::default() };
+
+    let declared = node.parent()
+        .filter(|parent| parent.kind() == "variable_declarator")
+        .and_then(|declarator| declarator.child_by_field_name("name"));
+    match declared {
+        Some(name) if name.kind() == "identifier" => import.binding = Some(text(name, content).to_string()),
+        Some(pattern) if pattern.kind() == "object_pattern" => import.imported_names = destructured_names(pattern, content),
+        _ => {}
+    }
+    Some(import)
+}
+
+/// `a` and `b as c` from `{ a, b: c }`
+fn destructured_names(pattern: Node, content: &str) -> Vec<String> {
+    named_children(pattern).into_iter()
+        .filter_map(|property| match property.kind() {
+            "shorthand_property_identifier_pattern" => Some(text(property, content).to_string()),
+            "pair_pattern" => Some(format!(
+                "{} as {}",
+                text(property.child_by_field_name("key")?, content),
+                text(property.child_by_field_name("value")?, content),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `import a.b, c as d`
+fn python_import(node: Node, content: &str, line: u32) -> Vec<ImportInfo> {
+    let mut cursor = node.walk();
+    node.children_by_field_name("name", &mut cursor)
+        .map(|name| {
+            let (module_path, binding) = match name.child_by_field_name("alias") {
+                Some(alias) => (text(name.child_by_field_name("name").unwrap_or(name), content), text(alias, content)),
+                // `import os.path` binds `os`
+                None => (text(name, content), text(name, content).split('.').next().unwrap_or_default()),
+            };
+            ImportInfo { module_path: module_path.to_string(), binding: Some(binding.to_string()), line, ..Default::default() }
+        })
+        .collect()
+}
+
+/// `from m import a, b as c` and `from m import *`
+fn python_from_import(node: Node, content: &str, line: u32) -> Option<ImportInfo> {
+    let module_path = text(node.child_by_field_name("module_name")?, content).to_string();
+    let mut cursor = node.walk();
+    let mut imported_names: Vec<String> = node.children_by_field_name("name", &mut cursor)
+        .map(|name| match (name.child_by_field_name("name"), name.child_by_field_name("alias")) {
+            (Some(imported), Some(alias)) => format!("{} as {}", text(imported, content), text(alias, content)),
+            _ => text(name, content).to_string(),
+        })
+        .collect();
+    if named_children(node).iter().any(|child| child.kind() == "wildcard_import") {
+        imported_names.push("*".to_string());
+    }
+    Some(ImportInfo { module_path, imported_names, line, ..Default::default() })
+}
+
+/// A `use` declaration's paths
+fn rust_use(node: Node, content: &str, line: u32) -> Vec<ImportInfo> {
+    let mut imports = Vec::new();
+    if let Some(tree) = node.child_by_field_name("argument") {
+        rust_use_tree(tree, "", content, line, &mut imports);
+    }
+    let inline_modules = inline_modules(node, content);
+    if !inline_modules.is_empty() {
+        imports.iter_mut().for_each(|import| import.inline_modules = inline_modules.clone());
+    }
+    imports
+}
+
+/// Names of the `mod name { ... }` blocks around `node`, outermost first
+fn inline_modules(node: Node, content: &str) -> Vec<String> {
+    let mut modules = Vec::new();
+    let mut parent = node.parent();
+    while let Some(module) = parent {
+        if let Some(name) = module.child_by_field_name("name").filter(|_| module.kind() == "mod_item") {
+            modules.push(text(name, content).to_string());
+        }
+        parent = module.parent();
+    }
+    modules.reverse();
+    modules
+}
+
+/// One import per path in a use tree, each prefixed by the paths of the lists around it
+fn rust_use_tree(tree: Node, prefix: &str, content: &str, line: u32, imports: &mut Vec<ImportInfo>) {
+    // `use a::{self, b}` imports `a` itself
+    let join = |path: &str| [prefix, path].iter().filter(|part| !part.is_empty() && **part != "self").copied().collect::<Vec<_>>().join("::");
+    let last_segment = |path: &str| path.rsplit("::").next().map(str::to_string);
+
+    match tree.kind() {
+        "use_list" => {
+            for item in named_children(tree).into_iter().filter(|item| !item.is_extra()) {
+                rust_use_tree(item, prefix, content, line, imports);
+            }
+        }
+        "scoped_use_list" => {
+            let path = tree.child_by_field_name("path").map(|path| text(path, content)).unwrap_or_default();
+            if let Some(list) = tree.child_by_field_name("list") {
+                rust_use_tree(list, &join(path), content, line, imports);
+            }
+        }
+        "use_as_clause" => {
+            let path = tree.child_by_field_name("path").map(|path| text(path, content)).unwrap_or_default();
+            let binding = tree.child_by_field_name("alias").map(|alias| text(alias, content).to_string());
+            imports.push(ImportInfo { module_path: join(path), binding, line, ..Default::default() });
+        }
+        "use_wildcard" => {
+            let path = named_children(tree).first().map(|path| text(*path, content)).unwrap_or_default();
+            imports.push(ImportInfo { module_path: join(path), imported_names: vec!["*".to_string()], line, ..Default::default() });
+        }
+        _ => {
+            let module_path = join(text(tree, content));
+            imports.push(ImportInfo { binding: last_segment(&module_path), module_path, line, ..Default::default() });
+        }
+    }
+}
+
+/// `require "m"` and `require_relative "m"`
+fn ruby_require(node: Node, content: &str, line: u32) -> Option<ImportInfo> {
+    let method = text(node.child_by_field_name("method")?, content);
+    let argument = named_children(node.child_by_field_name("arguments")?).into_iter().next()?;
+    if argument.kind() != "string" {
+        return None;
+    }
+    let path = unquoted(argument, content);
+    let module_path = if method == "require_relative" && !path.starts_with('.') {
+        format!("./{}", path)
+    } else {
+        path
+    };
+    Some(ImportInfo { module_path, line, ..Default::default() })
+}
+
+fn named_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).collect()
+}
+
+fn text<'a>(node: Node, content: &'a str) -> &'a str {
+    &content[node.byte_range()]
+}
+
+/// A string literal's text without its quotes
+fn unquoted(node: Node, content: &str) -> String {
+    text(node, content).trim_matches(['"', '\'', '`']).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast_analyzer::ASTAnalyzer;
+    use std::path::Path;
+
+    fn imports_of(source: &str, language: Language) -> Vec<(String, Vec<String>, bool, Option<String>)> {
+        let mut analyzer = ASTAnalyzer::new().unwrap();
+        let (_, _, _, imports_exports) = analyzer.analyze_file(source, &language, Path::new("source")).unwrap();
+        imports_exports.imports.into_iter()
+            .map(|import| (import.module_path, import.imported_names, import.is_default, import.binding))
+            .collect()
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_javascript_python_and_rust_imports_bind_their_local_names() {
+        let javascript = "import React, { useState as useLocal } from \"react\";\nimport * as fs from 'fs';\nimport './polyfill';\nconst { join, resolve: r } = require('path');\nconst lodash = require('lodash');\n";
+        assert_eq!(imports_of(javascript, Language::JavaScript), vec![
+            ("react".to_string(), Vec::new(), true, Some("React".to_string())),
+            ("react".to_string(), names(&["useState as useLocal"]), false, None),
+            ("fs".to_string(), Vec::new(), false, Some("fs".to_string())),
+            ("./polyfill".to_string(), Vec::new(), false, None),
+            ("path".to_string(), names(&["join", "resolve as r"]), false, None),
+            ("lodash".to_string(), Vec::new(), false, Some("lodash".to_string())),
+        ]);
+
+        let python = "import os.path, numpy as np\nfrom ..util import fmt, parse as p\nfrom shop import *\n";
+        assert_eq!(imports_of(python, Language::Python), vec![
+            ("os.path".to_string(), Vec::new(), false, Some("os".to_string())),
+            ("numpy".to_string(), Vec::new(), false, Some("np".to_string())),
+            ("..util".to_string(), names(&["fmt", "parse as p"]), false, None),
+            ("shop".to_string(), names(&["*"]), false, None),
+        ]);
+
+        let rust = "mod net;\nmod inline {}\nuse std::{fmt, io::{self, Read as _}};\nuse crate::model::*;\nuse serde::Deserialize;\n";
+        assert_eq!(imports_of(rust, Language::Rust), vec![
+            ("std::fmt".to_string(), Vec::new(), false, Some("fmt".to_string())),
+            ("std::io".to_string(), Vec::new(), false, Some("io".to_string())),
+            ("std::io::Read".to_string(), Vec::new(), false, Some("_".to_string())),
+            ("crate::model".to_string(), names(&["*"]), false, None),
+            ("serde::Deserialize".to_string(), Vec::new(), false, Some("Deserialize".to_string())),
+        ]);
+    }
+}
@@ -0,0 +1,153 @@
+//! Non-cryptographic random numbers used for secrets
+//!
+//! `Math.random()`, Python's `random` module, Ruby's `rand` and fast seeded
+//! generators are predictable enough for an attacker to recover their state
+//! from a few outputs. They are fine for sampling and jitter, so a call is only
+//! reported when the value it feeds is named like a secret: the variable,
+//! field, keyword argument or function it ends up in mentions a token,
+//! password, nonce, salt and the like. The other cryptography rules are plain
+//! tree-sitter queries in `security_patterns`.
+
+use tree_sitter::Node;
+
+use crate::ast_analyzer::{excerpt, walk_descendants, FUNCTION_KINDS};
+
+/// Words that mark a name as holding a secret, matched against the name lowercased with `_` removed
+const SECRET_WORDS: &[&str] = &[
+    "token", "secret", "password", "passwd", "nonce", "salt", "otp", "apikey", "session", "csrf", "resetcode",
+    "verificationcode",
+];
+
+/// Nodes that bind or name a value, with the field holding the name
+const NAMING_NODES: &[(&str, &str)] = &[
+    ("variable_declarator", "name"),
+    ("assignment_expression", "left"),
+    ("assignment", "left"),
+    ("augmented_assignment", "left"),
+    ("operator_assignment", "left"),
+    ("let_declaration", "pattern"),
+    ("pair", "key"),
+    ("keyword_argument", "name"),
+    ("field_initializer", "field"),
+    ("public_field_definition", "property"),
+];
+
+/// A predictable generator whose output is named like a secret
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsecureRandom {
+    /// 1-based
+    pub line: u32,
+    pub column: u32,
+    /// The generator call, shortened
+    pub call: String,
+    /// The secret-looking name it feeds
+    pub target: String,
+}
+
+/// Every such call in a file parsed with `grammar`, in source order
+pub fn find(root: Node, content: &str, grammar: &str) -> Vec<InsecureRandom> {
+    let mut found = Vec::new();
+    walk_descendants(root, |node, _| {
+        if is_weak_generator(node, content, grammar) {
+            if let Some(target) = secret_target(node, content) {
+                let position = node.start_position();
+                found.push(InsecureRandom {
+                    line: position.row as u32 + 1,
+                    column: position.column as u32 + 1,
+                    call: excerpt(&content[node.byte_range()]),
+                    target,
+                });
+            }
+        }
+        true
+    });
+    found
+}
+
+/// What to generate secrets with instead, per grammar
+pub fn suggestion(grammar: &str) -> &'static str {
+    match grammar {
+        "javascript" => "Use crypto.randomBytes, crypto.randomUUID or crypto.getRandomValues for secrets",
+        "python" => "Use the secrets module, e.g. secrets.token_urlsafe() or secrets.token_hex()",
+        "ruby" => "Use SecureRandom, e.g. SecureRandom.hex or SecureRandom.urlsafe_base64",
+        "rust" => "Use rand::rngs::OsRng, rand::thread_rng() or the getrandom crate for secrets",
+        _ => "Use the platform's cryptographically secure random number generator for secrets",
+    }
+}
+
+fn is_weak_generator(node: Node, content: &str, grammar: &str) -> bool {
+    let text = |node: Node| &content[node.byte_range()];
+    match (grammar, node.kind()) {
+        ("javascript", "call_expression") => node.child_by_field_name("function")
+            .is_some_and(|function| text(function) == "Math.random"),
+        ("python", "call") => node.child_by_field_name("function").is_some_and(|function| matches!(text(function),
+            "random.random" | "random.randint" | "random.randrange" | "random.choice" | "random.choices" |
+            "random.sample" | "random.getrandbits" | "random.uniform" | "random.randbytes")),
+        ("ruby", "call") => {
+            let method = node.child_by_field_name("method").map(text);
+            let receiver = node.child_by_field_name("receiver").map(text);
+            matches!((receiver, method), (None | Some("Random" | "Kernel"), Some("rand")) | (Some("Random"), Some("bytes")))
+        }
+        ("rust", "call_expression") => node.child_by_field_name("function").is_some_and(|function| {
+            let path = text(function);
+            path.starts_with("fastrand::") || path.starts_with("SmallRng::") || path.starts_with("oorandom::")
+        }),
+        _ => false,
+    }
+}
+
+/// The nearest secret-looking name the value flows into, up to and including its function
+fn secret_target(call: Node, content: &str) -> Option<String> {
+    let mut node = call;
+    while let Some(parent) = node.parent() {
+        node = parent;
+        let name = if FUNCTION_KINDS.contains(&node.kind()) {
+            node.child_by_field_name("name")
+        } else {
+            NAMING_NODES.iter()
+                .find(|(kind, _)| *kind == node.kind())
+                .and_then(|(_, field)| node.child_by_field_name(field))
+        };
+        if let Some(name) = name.map(|name| &content[name.byte_range()]).filter(|name| is_secret_name(name)) {
+            return Some(excerpt(name));
+        }
+        if FUNCTION_KINDS.contains(&node.kind()) {
+            return None;
+        }
+    }
+    None
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let normalized = name.to_ascii_lowercase().replace('_', "");
+    SECRET_WORDS.iter().any(|word| normalized.contains(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::test_util::detect;
+    use crate::parsers::SupportedLanguage;
+
+    #[test]
+    fn test_only_secret_looking_targets_are_reported() {
+        let js = "\
+const resetToken = Math.random().toString(36).slice(2);
+const jitter = Math.random() * 100;
+function makeSessionId() {
+  return Math.floor(Math.random() * 1e9);
+}
+";
+        assert_eq!(detect(SupportedLanguage::JavaScript, js, find, |found| (found.line, found.target)), vec![
+            (1, "resetToken".to_string()),
+            (4, "makeSessionId".to_string()),
+        ]);
+
+        let python = "\
+import random
+user.api_key = ''.join(random.choice(ALPHABET) for _ in range(32))
+delay = random.uniform(0, 1)
+";
+        assert_eq!(detect(SupportedLanguage::Python, python, find, |found| (found.line, found.target)), vec![(2, "user.api_key".to_string())]);
+    }
+}
@@ -0,0 +1,405 @@
+//! TypeScript/JavaScript module resolution settings
+//!
+//! Aliased imports such as `@app/utils` look like npm packages unless the
+//! project's `tsconfig.json`/`jsconfig.json` (`baseUrl`, `paths`) and the
+//! `package.json` files of local packages (`name`, `exports`) are consulted.
+//! This follows the parts of TypeScript's resolver that decide which file an
+//! import names; type-only conditions and `node_modules` lookups are ignored.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+
+const CONFIG_FILES: [&str; 2] = ["tsconfig.json", "jsconfig.json"];
+
+/// Tried in order when a specifier has no extension or names a directory
+const EXTENSIONS: [&str; 6] = ["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// Conditions whose targets are tried, most source-like first
+const CONDITIONS: [&str; 5] = ["source", "types", "import", "default", "require"];
+
+/// A package in the repository that can be imported by name
+#[derive(Debug, Clone)]
+struct LocalPackage {
+    name: String,
+    dir: PathBuf,
+    /// Subpath pattern (`.`, `./utils`, `./utils/*`) -> targets, in declaration order
+    exports: Vec<(String, Vec<String>)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JsProject {
+    base_url: Option<PathBuf>,
+    /// Directory `paths` substitutions are relative to
+    paths_base: PathBuf,
+    /// `paths` patterns in declaration order, each with its substitutions
+    paths: Vec<(String, Vec<String>)>,
+    packages: Vec<LocalPackage>,
+}
+
+impl JsProject {
+    /// Read the tsconfig/jsconfig and every `package.json` under `root`
+    ///
+    /// Returns `None` when none of them exist.
+    pub fn load(root: &Path) -> Result<Option<Self>> {
+        let mut project = Self { paths_base: root.to_path_buf(), ..Self::default() };
+        let mut found = false;
+
+        if let Some(config) = CONFIG_FILES.iter().map(|name| root.join(name)).find(|path| path.is_file()) {
+            project.apply_config(&config)?;
+            found = true;
+        }
+
+        let walker = ignore::WalkBuilder::new(root)
+            .filter_entry(|entry| entry.file_name() != "node_modules")
+            .build();
+        for entry in walker {
+            let entry = entry.context("Failed to read directory entry")?;
+            if entry.file_name() != "package.json" {
+                continue;
+            }
+            found = true;
+            // One broken manifest, say a fixture, should not cost every other package's resolution
+            let manifest = match read_jsonc(entry.path()) {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    eprintln!("Warning: package.json skipped - {:#}", e);
+                    continue;
+                }
+            };
+            if let Some(package) = LocalPackage::from_manifest(&manifest, entry.path().parent().unwrap_or(root)) {
+                project.packages.push(package);
+            }
+        }
+
+        Ok(found.then_some(project))
+    }
+
+    /// Apply a config file after the configs it `extends`, which may be overridden
+    fn apply_config(&mut self, config: &Path) -> Result<()> {
+        let mut chain = vec![config.to_path_buf()];
+        loop {
+            let current = chain.last().unwrap();
+            let json = read_jsonc(current)?;
+            // Only relative `extends` can be followed without node_modules
+            let Some(extends) = json.get("extends").and_then(Value::as_str).filter(|extends| extends.starts_with('.')) else {
+                break;
+            };
+
+            let parent = current.parent().unwrap_or(Path::new("")).join(extends);
+            let parent = if parent.is_file() { parent } else { PathBuf::from(format!("{}.json", parent.display())) };
+            if chain.contains(&parent) || !parent.is_file() {
+                break;
+            }
+            chain.push(parent);
+        }
+
+        for file in chain.iter().rev() {
+            let dir = file.parent().unwrap_or(Path::new(""));
+            let json = read_jsonc(file)?;
+            let Some(options) = json.get("compilerOptions") else {
+                continue;
+            };
+
+            if let Some(base_url) = options.get("baseUrl").and_then(Value::as_str) {
+                let base_url = normalize(&dir.join(base_url));
+                self.paths_base = base_url.clone();
+                self.base_url = Some(base_url);
+            }
+            if let Some(paths) = options.get("paths").and_then(Value::as_object) {
+                if self.base_url.is_none() {
+                    self.paths_base = dir.to_path_buf();
+                }
+                self.paths = paths.iter()
+                    .map(|(pattern, targets)| (pattern.clone(), string_list(targets)))
+                    .collect();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether `specifier` names a file of this project rather than an npm package
+    pub fn is_internal(&self, specifier: &str) -> bool {
+        is_relative(specifier)
+            || self.paths.iter().any(|(pattern, _)| match_pattern(pattern, specifier).is_some())
+            || self.resolve_bare(specifier).is_some()
+    }
+
+    /// The file `specifier` refers to when imported from `importer`
+    pub fn resolve(&self, importer: &Path, specifier: &str) -> Option<PathBuf> {
+        if is_relative(specifier) {
+            let dir = importer.parent().unwrap_or(Path::new(""));
+            return resolve_file(&dir.join(specifier));
+        }
+        self.resolve_bare(specifier)
+    }
+
+    fn resolve_bare(&self, specifier: &str) -> Option<PathBuf> {
+        // The most specific `paths` pattern wins, as in TypeScript
+        let best_pattern = self.paths.iter()
+            .filter_map(|(pattern, targets)| Some((match_pattern(pattern, specifier)?, pattern, targets)))
+            .max_by_key(|(_, pattern, _)| pattern.find('*').unwrap_or(pattern.len()));
+        if let Some((captured, _, targets)) = best_pattern {
+            if let Some(file) = targets.iter()
+                .find_map(|target| resolve_file(&self.paths_base.join(target.replacen('*', captured, 1))))
+            {
+                return Some(file);
+            }
+        }
+
+        if let Some(file) = self.base_url.as_ref().and_then(|base_url| resolve_file(&base_url.join(specifier))) {
+            return Some(file);
+        }
+
+        self.packages.iter()
+            .filter_map(|package| Some((package, package.subpath(specifier)?)))
+            .max_by_key(|(package, _)| package.name.len())
+            .and_then(|(package, subpath)| package.resolve(&subpath))
+    }
+}
+
+impl LocalPackage {
+    fn from_manifest(manifest: &Value, dir: &Path) -> Option<Self> {
+        let name = manifest.get("name")?.as_str()?.to_string();
+        let exports = match manifest.get("exports") {
+            None => Vec::new(),
+            // A bare target or a conditions object applies to the package root
+            Some(target @ (Value::String(_) | Value::Array(_))) => vec![(".".to_string(), condition_targets(target))],
+            Some(Value::Object(map)) if !map.keys().any(|key| key.starts_with('.')) => {
+                vec![(".".to_string(), condition_targets(&Value::Object(map.clone())))]
+            }
+            Some(Value::Object(map)) => map.iter()
+                .map(|(subpath, target)| (subpath.clone(), condition_targets(target)))
+                .collect(),
+            Some(_) => Vec::new(),
+        };
+
+        Some(Self { name, dir: dir.to_path_buf(), exports })
+    }
+
+    /// `./sub/path` for `name/sub/path`, `.` for `name` itself
+    fn subpath(&self, specifier: &str) -> Option<String> {
+        let rest = specifier.strip_prefix(&self.name)?;
+        if rest.is_empty() {
+            Some(".".to_string())
+        } else {
+            rest.strip_prefix('/').map(|rest| format!("./{}", rest))
+        }
+    }
+
+    fn resolve(&self, subpath: &str) -> Option<PathBuf> {
+        if self.exports.is_empty() {
+            // Without an exports map any file of the package can be imported
+            return resolve_file(&self.dir.join(subpath));
+        }
+
+        let (captured, targets) = self.exports.iter()
+            .filter_map(|(pattern, targets)| Some((match_pattern(pattern, subpath)?, pattern, targets)))
+            .max_by_key(|(_, pattern, _)| pattern.find('*').unwrap_or(pattern.len()))
+            .map(|(captured, _, targets)| (captured, targets))?;
+
+        targets.iter().find_map(|target| resolve_file(&self.dir.join(target.replacen('*', captured, 1))))
+    }
+}
+
+fn is_relative(specifier: &str) -> bool {
+    specifier == "." || specifier == ".." || specifier.starts_with("./") || specifier.starts_with("../")
+}
+
+/// Match `specifier` against a pattern with at most one `*`, returning what `*` matched
+fn match_pattern<'a>(pattern: &str, specifier: &'a str) -> Option<&'a str> {
+    match pattern.split_once('*') {
+        None => (pattern == specifier).then_some(""),
+        Some((prefix, suffix)) => specifier.strip_prefix(prefix)?
+            .strip_suffix(suffix)
+            .filter(|_| specifier.len() >= prefix.len() + suffix.len()),
+    }
+}
+
+/// Try `path` as written, with each extension, as a `.js` specifier for a TS file, and as a directory index
+fn resolve_file(path: &Path) -> Option<PathBuf> {
+    let path = normalize(path);
+    if path.is_file() {
+        return Some(path);
+    }
+
+    let with_extension = |base: &Path, extension: &str| {
+        let mut file = base.as_os_str().to_os_string();
+        file.push(".");
+        file.push(extension);
+        PathBuf::from(file)
+    };
+
+    let source_of_output = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("js" | "jsx" | "mjs" | "cjs") => Some(path.with_extension("")),
+        _ => None,
+    };
+    let candidates = EXTENSIONS.iter().map(|extension| with_extension(&path, extension))
+        .chain(source_of_output.iter().flat_map(|base| ["ts", "tsx"].map(|extension| with_extension(base, extension))))
+        .chain(EXTENSIONS.iter().map(|extension| path.join(format!("index.{}", extension))));
+
+    candidates.into_iter().find(|candidate| candidate.is_file())
+}
+
+/// Resolve `.` and `..` lexically, keeping a leading `.` so paths still match the analyzed root
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir if normalized.as_os_str().is_empty() => normalized.push("."),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                let can_pop = matches!(normalized.components().next_back(), Some(Component::Normal(_)));
+                if !can_pop || !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Targets of an exports entry, flattening condition objects and fallback arrays
+fn condition_targets(target: &Value) -> Vec<String> {
+    match target {
+        Value::String(path) => vec![path.clone()],
+        Value::Array(fallbacks) => fallbacks.iter().flat_map(condition_targets).collect(),
+        Value::Object(conditions) => {
+            let preferred = CONDITIONS.iter().filter_map(|condition| conditions.get(*condition));
+            let others = conditions.iter()
+                .filter(|(condition, _)| !CONDITIONS.contains(&condition.as_str()))
+                .map(|(_, target)| target);
+            let mut targets = Vec::new();
+            for target in preferred.chain(others).flat_map(condition_targets) {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+            targets
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value.as_array()
+        .map(|items| items.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn read_jsonc(path: &Path) -> Result<Value> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&strip_jsonc(&content))
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// tsconfig files allow comments and trailing commas; plain JSON does not
+fn strip_jsonc(content: &str) -> String {
+    let mut stripped = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            stripped.push(c);
+            match c {
+                '\\' => stripped.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                stripped.push(c);
+            }
+            ('/', Some('/')) => {
+                while chars.next_if(|&next| next != '\n').is_some() {}
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            (']' | '}', _) => {
+                let trimmed = stripped.trim_end().len();
+                if stripped[..trimmed].ends_with(',') {
+                    stripped.truncate(trimmed - 1);
+                }
+                stripped.push(c);
+            }
+            _ => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_jsonc() {
+        let content = r#"{
+            // Path aliases
+            "compilerOptions": { "baseUrl": "./src", /* inline */ "paths": { "@app/*": ["app/*",], }, },
+            "url": "http://example.com/a//b"
+        }"#;
+        let json: Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+        assert_eq!(json["compilerOptions"]["paths"]["@app/*"][0], "app/*");
+        assert_eq!(json["url"], "http://example.com/a//b");
+    }
+
+    #[test]
+    fn test_resolves_paths_base_url_and_package_exports() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let write = |file: &str, content: &str| {
+            std::fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            std::fs::write(root.join(file), content).unwrap();
+        };
+        write("tsconfig.base.json", r#"{ "compilerOptions": { "baseUrl": "src" } }"#);
+        write("tsconfig.json", r#"{
+            "extends": "./tsconfig.base",
+            "compilerOptions": { "paths": { "@app/*": ["app/*"], "@app/config": ["config/index.ts"] } }
+        }"#);
+        write("package.json", r#"{ "name": "root", "private": true }"#);
+        write("packages/ui/package.json", r#"{
+            "name": "@acme/ui",
+            "exports": { ".": { "types": "./src/index.ts", "import": "./dist/index.js" }, "./button": "./src/button.tsx" }
+        }"#);
+        write("fixtures/broken/package.json", r#"{ "name": "broken", "#);
+        for file in ["src/app/utils.ts", "src/config/index.ts", "src/lib/format.js", "src/app/view.ts",
+                     "packages/ui/src/index.ts", "packages/ui/src/button.tsx"] {
+            write(file, "");
+        }
+
+        let project = JsProject::load(root).unwrap().unwrap();
+        let importer = root.join("src/app/view.ts");
+        let resolve = |specifier: &str| project.resolve(&importer, specifier)
+            .map(|path| path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"));
+
+        assert_eq!(resolve("@app/utils").as_deref(), Some("src/app/utils.ts"));
+        assert_eq!(resolve("@app/config").as_deref(), Some("src/config/index.ts"));
+        assert_eq!(resolve("lib/format").as_deref(), Some("src/lib/format.js"));
+        assert_eq!(resolve("./utils.js").as_deref(), Some("src/app/utils.ts"));
+        assert_eq!(resolve("../config").as_deref(), Some("src/config/index.ts"));
+        assert_eq!(resolve("@acme/ui").as_deref(), Some("packages/ui/src/index.ts"));
+        assert_eq!(resolve("@acme/ui/button").as_deref(), Some("packages/ui/src/button.tsx"));
+        assert_eq!(resolve("@acme/ui/internal"), None);
+
+        assert!(project.is_internal("@app/missing"));
+        assert!(!project.is_internal("react"));
+    }
+}
@@ -0,0 +1,202 @@
+//! Languages added by downstream crates without forking the analyzer
+//!
+//! A `LanguageProvider` bundles a tree-sitter grammar, the queries that find
+//! functions, imports and risky calls in it, and the file extensions it
+//! handles. Providers registered with `register` are picked up by every
+//! `ASTAnalyzer` constructed afterwards, detected by `Language::from_extension`
+//! as `Language::Plugin`, and listed next to the built-in languages.
+//!
+//! ```ignore
+//! struct Zig;
+//!
+//! impl LanguageProvider for Zig {
+//!     fn name(&self) -> &'static str { "Zig" }
+//!     fn extensions(&self) -> &[&'static str] { &["zig"] }
+//!     fn grammar(&self) -> tree_sitter::Language { tree_sitter_zig::LANGUAGE.into() }
+//!     fn queries(&self) -> QuerySources {
+//!         QuerySources {
+//!             functions: "(function_declaration name: (identifier) @func.name) @func.def",
+//!             ..QuerySources::default()
+//!         }
+//!     }
+//! }
+//!
+//! codemetrics_core::language_provider::register(Zig)?;
+//! ```
+
+use anyhow::{Context, Result};
+use std::sync::{Arc, OnceLock, RwLock};
+use tree_sitter::Parser;
+
+/// Everything the analyzer needs to support one more language
+pub trait LanguageProvider: Send + Sync {
+    /// Display name, e.g. `"Zig"`; also keys the compiled-query cache
+    fn name(&self) -> &'static str;
+
+    /// Extensions without the dot, e.g. `["ex", "exs"]`
+    fn extensions(&self) -> &[&'static str];
+
+    fn grammar(&self) -> tree_sitter::Language;
+
+    fn queries(&self) -> QuerySources;
+
+    /// Node kinds that add a decision point, beyond the kinds shared by most grammars
+    /// (`if_statement`, `for_statement`, `catch_clause`, ...)
+    fn decision_kinds(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// A parser set to this grammar
+    fn create_parser(&self) -> Result<Parser> {
+        let mut parser = Parser::new();
+        parser.set_language(&self.grammar())
+            .with_context(|| format!("Failed to set parser language to {}", self.name()))?;
+        Ok(parser)
+    }
+}
+
+/// Tree-sitter query sources, using the capture names of the built-in languages
+///
+/// Only `functions` is required; an empty query matches nothing.
+#[derive(Debug, Clone, Default)]
+pub struct QuerySources {
+    /// `@func.def` on the function node, `@func.name` on its name
+    pub functions: &'static str,
+    /// `@decision` on branching nodes
+    pub complexity_nodes: &'static str,
+    /// `@import` on import statements
+    pub imports: &'static str,
+    /// `@export` on exported declarations
+    pub exports: &'static str,
+    /// `@security.risk` on calls worth reviewing
+    pub security_patterns: &'static str,
+}
+
+static PROVIDERS: OnceLock<RwLock<Vec<Arc<dyn LanguageProvider>>>> = OnceLock::new();
+
+fn providers() -> &'static RwLock<Vec<Arc<dyn LanguageProvider>>> {
+    PROVIDERS.get_or_init(Default::default)
+}
+
+/// Add a language for the rest of the process
+///
+/// Fails if the name or an extension is already taken by another plugin, or
+/// an extension belongs to a built-in language.
+pub fn register(provider: impl LanguageProvider + 'static) -> Result<()> {
+    let mut providers = providers().write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    for extension in provider.extensions() {
+        if crate::core::Language::builtin_for_extension(extension).is_some() {
+            anyhow::bail!("Extension '{}' already belongs to a built-in language", extension);
+        }
+    }
+    if let Some(existing) = providers.iter().find(|existing| {
+        existing.name() == provider.name()
+            || existing.extensions().iter().any(|extension| provider.extensions().contains(extension))
+    }) {
+        anyhow::bail!("Language '{}' overlaps the registered plugin '{}'", provider.name(), existing.name());
+    }
+    providers.push(Arc::new(provider));
+    Ok(())
+}
+
+/// Every registered plugin, in registration order
+pub fn registered() -> Vec<Arc<dyn LanguageProvider>> {
+    providers().read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// The plugin handling `extension` (compared case-insensitively), if any
+pub fn for_extension(extension: &str) -> Option<Arc<dyn LanguageProvider>> {
+    let extension = extension.to_lowercase();
+    registered().into_iter().find(|provider| provider.extensions().contains(&extension.as_str()))
+}
+
+pub fn for_name(name: &str) -> Option<Arc<dyn LanguageProvider>> {
+    registered().into_iter().find(|provider| provider.name() == name)
+}
+
+/// Registers a plugin for one test and removes it again when dropped
+///
+/// Tests run in parallel in one process, so each scoped plugin needs a name and
+/// extensions of its own.
+#[cfg(test)]
+pub(crate) struct Scoped(&'static str);
+
+#[cfg(test)]
+impl Scoped {
+    pub(crate) fn register(provider: impl LanguageProvider + 'static) -> Result<Self> {
+        let name = provider.name();
+        register(provider)?;
+        Ok(Self(name))
+    }
+}
+
+#[cfg(test)]
+impl Drop for Scoped {
+    fn drop(&mut self) {
+        providers().write().unwrap_or_else(|poisoned| poisoned.into_inner())
+            .retain(|provider| provider.name() != self.0);
+    }
+}
+
+/// Rust's grammar under another name, with closures counted as branches
+#[cfg(test)]
+pub(crate) struct Scripted {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+}
+
+#[cfg(test)]
+impl LanguageProvider for Scripted {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn extensions(&self) -> &[&'static str] {
+        self.extensions
+    }
+
+    fn grammar(&self) -> tree_sitter::Language {
+        tree_sitter::Language::new(tree_sitter_rust::LANGUAGE)
+    }
+
+    fn queries(&self) -> QuerySources {
+        QuerySources {
+            functions: "(function_item name: (identifier) @func.name) @func.def",
+            ..QuerySources::default()
+        }
+    }
+
+    fn decision_kinds(&self) -> &[&'static str] {
+        &["closure_expression"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast_analyzer::ASTAnalyzer;
+    use crate::core::Language;
+    use std::path::Path;
+
+    #[test]
+    fn test_registered_language_is_detected_and_analyzed() -> Result<()> {
+        let scripted = || Scripted { name: "Scripted", extensions: &["scripted"] };
+        let registration = Scoped::register(scripted())?;
+        assert!(register(scripted()).is_err());
+
+        let language = Language::from_extension("SCRIPTED");
+        assert_eq!(language, Language::Plugin("Scripted".to_string()));
+        assert_eq!(language.extensions(), vec!["scripted"]);
+
+        let mut analyzer = ASTAnalyzer::new()?;
+        assert!(analyzer.languages().contains(&language));
+        let source = "fn pick(x: i32) { if x > 0 { let f = || 1; } }";
+        let (_, _, functions, _) = analyzer.analyze_file(source, &language, Path::new("a.scripted"))?;
+        assert_eq!(functions[0].name, "pick");
+        assert_eq!(functions[0].cyclomatic_complexity, 3);
+
+        drop(registration);
+        assert_eq!(Language::from_extension("scripted"), Language::Unknown);
+        Ok(())
+    }
+}
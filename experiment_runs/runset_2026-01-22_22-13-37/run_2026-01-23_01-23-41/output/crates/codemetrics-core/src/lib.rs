@@ -0,0 +1,73 @@
+//! CodeMetrics - A fast, extensible code metrics analyzer
+//!
+//! This crate is the analysis engine: parsing with tree-sitter, complexity and
+//! issue detection, dependency graphs and version-control history. It has no
+//! terminal or templating dependencies; rendering lives in `codemetrics-report`
+//! and the command-line front end in `codemetrics-cli`.
+
+pub mod analyzer;
+pub mod analyzers;
+pub mod anonymize;
+pub mod ast_analyzer;
+pub mod attribution;
+pub mod baseline;
+pub mod build_graph;
+pub mod cache;
+pub mod calibration;
+pub mod call_graph;
+pub mod capabilities;
+pub mod clone_detector;
+pub mod clusters;
+pub mod codeowners;
+pub mod concurrency_smells;
+pub mod cargo_workspace;
+pub mod core;
+pub mod custom_rules;
+pub mod cycles;
+pub mod dead_code;
+pub mod dependency_analyzer;
+pub mod diff;
+pub mod error_handling;
+pub mod facts;
+pub mod file_identity;
+pub mod function_shape;
+pub mod gate_preview;
+pub mod gates;
+pub mod go_concurrency;
+pub mod health;
+pub mod history;
+pub mod hotspots;
+pub mod imports;
+pub mod insecure_random;
+pub mod js_project;
+pub mod language_provider;
+pub mod line_complexity;
+pub mod parsers;
+pub mod progress;
+pub mod python_layout;
+pub mod query_cache;
+pub mod release_report;
+pub mod resource_leaks;
+pub mod rules;
+pub mod rust_api;
+pub mod schema;
+pub mod security_patterns;
+pub mod shard;
+pub mod snippets;
+pub mod source_text;
+pub mod stability;
+pub mod stack;
+pub mod suppressions;
+pub mod taint;
+pub mod thresholds;
+pub mod type_metrics;
+pub mod unsafe_usage;
+pub mod vcs;
+pub mod vocabulary;
+
+pub use analyzer::{CodeAnalyzer, AnalysisResults};
+pub use ast_analyzer::{ASTAnalyzer, FunctionAnalysis, RetainedParse};
+pub use core::{Language, CodeMetrics, CodeIssue};
+
+/// Re-export commonly used types
+pub type Result<T> = anyhow::Result<T>;
@@ -0,0 +1,96 @@
+//! Per-line complexity contributions, for editor gutter heatmaps
+//!
+//! Each decision point (`if`, loop, match arm, ternary, ...) adds one to the
+//! cyclomatic complexity of its function, and error-handling constructs add
+//! their configured weight. This module reports the line each of them starts
+//! on, so a plugin can shade lines without re-implementing the analysis.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tree_sitter::Node;
+
+use crate::ast_analyzer::{decision_weight, walk_descendants};
+use crate::core::Language;
+use crate::error_handling::{ErrorHandling, ErrorHandlingWeights};
+use crate::parsers::{pool, SupportedLanguage};
+
+/// What one line adds to the complexity of the function containing it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineComplexity {
+    /// 1-based line number
+    pub line: u32,
+    pub contribution: u32,
+    /// Node kinds of the decision points starting on this line
+    pub decisions: Vec<String>,
+}
+
+/// Lines of one file that contribute complexity; lines that add nothing are omitted
+#[derive(Debug, Clone, Serialize)]
+pub struct FileLineComplexity {
+    pub file_path: String,
+    pub lines: Vec<LineComplexity>,
+}
+
+/// Parse a file and map its decision points to lines; `None` for languages without a grammar
+pub fn for_file(path: &str, source: &str, error_handling: &ErrorHandling) -> Result<Option<FileLineComplexity>> {
+    let extension = Path::new(path).extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+    let Some(language) = SupportedLanguage::from_extension(extension) else {
+        return Ok(None);
+    };
+    let weights = error_handling.for_language(&Language::from_extension(extension));
+
+    let tree = pool::parse(language, source)
+        .with_context(|| format!("Failed to parse {}", path))?;
+    Ok(Some(FileLineComplexity { file_path: path.to_string(), lines: for_tree(tree.root_node(), source, weights) }))
+}
+
+/// Decision points under `root`, grouped by the line they start on
+///
+/// Error-handling constructs weighted zero are left out.
+pub fn for_tree(root: Node, source: &str, weights: &ErrorHandlingWeights) -> Vec<LineComplexity> {
+    let mut by_line: BTreeMap<u32, LineComplexity> = BTreeMap::new();
+    walk_descendants(root, |node, _| {
+        let weight = decision_weight(node, source, weights);
+        if weight > 0 {
+            let line = node.start_position().row as u32 + 1;
+            let entry = by_line.entry(line).or_insert_with(|| LineComplexity { line, contribution: 0, decisions: Vec::new() });
+            entry.contribution += weight;
+            entry.decisions.push(node.kind().to_string());
+        }
+        true
+    });
+
+    by_line.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decisions_are_attributed_to_their_lines() -> Result<()> {
+        let source = "\
+fn classify(n: i32) -> &'static str {
+    if n < 0 {
+        return \"negative\";
+    }
+    for _ in 0..n {}
+    match n {
+        0 => \"zero\",
+        _ => \"positive\",
+    }
+}
+";
+        let map = for_file("src/classify.rs", source, &ErrorHandling::default())?.expect("rust has a grammar");
+        let lines: Vec<(u32, u32)> = map.lines.iter().map(|line| (line.line, line.contribution)).collect();
+        assert_eq!(lines, vec![(2, 1), (5, 1), (6, 1), (7, 1), (8, 1)]);
+        assert_eq!(map.lines[2].decisions, vec!["match_expression"]);
+
+        assert!(for_file("README.md", "# title", &ErrorHandling::default())?.is_none());
+        Ok(())
+    }
+}
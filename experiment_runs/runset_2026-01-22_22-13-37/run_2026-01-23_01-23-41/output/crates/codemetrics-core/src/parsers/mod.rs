@@ -0,0 +1,497 @@
+//! Parsing source files into the per-function measurements `CodeAnalyzer` aggregates
+//!
+//! Trees come from the per-thread parsers in `pool`, and functions are found
+//! and measured with the same helpers `ASTAnalyzer` uses, so a function has the
+//! same name, parameters and complexity whichever analyzer reports it.
+
+pub mod pool;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use tree_sitter::{Language, Node, QueryCursor, Tree};
+
+use crate::ast_analyzer::{function_name, input_edit, is_decision_point, parameter_count, walk_descendants, FUNCTION_KINDS};
+use crate::core::Language as LangType;
+use crate::error_handling::{ErrorHandling, ErrorHandlingWeights};
+use crate::facts::FileFacts;
+use crate::file_identity::ContentKey;
+use crate::language_provider::{self, LanguageProvider};
+use crate::query_cache;
+
+/// Languages with a built-in tree-sitter grammar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SupportedLanguage {
+    Rust,
+    JavaScript,
+    Python,
+    Go,
+    Ruby,
+}
+
+impl SupportedLanguage {
+    pub const ALL: [SupportedLanguage; 5] = [Self::Rust, Self::JavaScript, Self::Python, Self::Go, Self::Ruby];
+
+    /// Get the tree-sitter language parser for this language
+    pub fn get_language(&self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+            Self::Ruby => tree_sitter_ruby::LANGUAGE.into(),
+        }
+    }
+
+    /// Grammar name used to key the shared query cache
+    pub fn grammar_name(&self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::JavaScript => "javascript",
+            Self::Python => "python",
+            Self::Go => "go",
+            Self::Ruby => "ruby",
+        }
+    }
+
+    /// Detect language from file extension
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "rs" => Some(Self::Rust),
+            "js" | "jsx" | "mjs" | "ts" | "tsx" => Some(Self::JavaScript),
+            "py" | "pyx" | "pyi" => Some(Self::Python),
+            "go" => Some(Self::Go),
+            "rb" | "rake" | "gemspec" => Some(Self::Ruby),
+            _ => None,
+        }
+    }
+
+    /// The language `ErrorHandling` and the rest of the analyzers know it as
+    pub fn language(&self) -> LangType {
+        match self {
+            Self::Rust => LangType::Rust,
+            Self::JavaScript => LangType::JavaScript,
+            Self::Python => LangType::Python,
+            Self::Go => LangType::Go,
+            Self::Ruby => LangType::Ruby,
+        }
+    }
+}
+
+/// One parsed source file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedFile {
+    pub path: String,
+    pub line_count: u32,
+    pub functions: Vec<FunctionInfo>,
+    /// Lines holding a comment, for the health score's docs input
+    #[serde(default)]
+    pub comment_lines: u32,
+    /// `use`, `import`, `require` and similar declarations, for its deps input
+    #[serde(default)]
+    pub imports: u32,
+    /// Rust `#[cfg(test)]` modules and `#[test]` functions, which make the file
+    /// count towards the tests input like a test file
+    #[serde(default)]
+    pub inline_tests: u32,
+    /// The parsed text's key, so byte-identical copies are counted once; `None` for blank files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_key: Option<ContentKey>,
+    /// What the enabled per-file passes measured from the same tree
+    #[serde(default, skip_serializing_if = "FileFacts::is_empty")]
+    pub facts: FileFacts,
+}
+
+/// One function, method or closure-like definition in a `ParsedFile`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub parameters: u32,
+    /// Cyclomatic complexity, nested functions included
+    pub complexity: u32,
+    /// Statements in the body, nested functions included; an expression body is one
+    #[serde(default)]
+    pub statements: u32,
+    /// Definitions around the function, outermost first, e.g. `Cart` for a method in `impl Cart`; empty at the top level
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub scope: String,
+}
+
+/// A buffer's text and syntax tree, kept so its next version parses incrementally
+pub struct RetainedTree {
+    language: SupportedLanguage,
+    source: String,
+    tree: Tree,
+}
+
+impl RetainedTree {
+    pub fn language(&self) -> SupportedLanguage {
+        self.language
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn root_node(&self) -> Node<'_> {
+        self.tree.root_node()
+    }
+}
+
+/// Turns source text into `ParsedFile`s
+#[derive(Debug, Clone, Default)]
+pub struct LanguageParser {
+    error_handling: ErrorHandling,
+}
+
+impl LanguageParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Weigh error-handling constructs in function complexity as `analyze` was configured to
+    pub fn with_error_handling(error_handling: ErrorHandling) -> Self {
+        Self { error_handling }
+    }
+
+    /// Names of the languages `parse_file` understands, for `codemetrics languages`
+    pub fn supported_languages() -> Vec<&'static str> {
+        SupportedLanguage::ALL.iter().map(SupportedLanguage::grammar_name).collect()
+    }
+
+    /// Whether `path` has a built-in grammar, rather than a plugin's or none
+    pub fn is_builtin(path: &str) -> bool {
+        SupportedLanguage::from_extension(extension_of(path)).is_some()
+    }
+
+    /// Parse `content`, choosing the grammar from `path`'s extension
+    pub fn parse_file(&self, path: &str, content: &str) -> Result<ParsedFile> {
+        self.parse_with(path, content, |_, _| Ok(FileFacts::default()))
+    }
+
+    /// Like `parse_file`, handing the tree to `passes` for the file's `facts`
+    ///
+    /// Files of a registered plugin language are measured with the plugin's
+    /// `functions` query; the passes only run on built-in languages.
+    pub fn parse_with(&self, path: &str, content: &str, passes: impl FnOnce(Node, SupportedLanguage) -> Result<FileFacts>) -> Result<ParsedFile> {
+        if let Some(provider) = plugin_of(path) {
+            return self.parse_plugin(path, content, provider.as_ref());
+        }
+        let language = language_of(path)?;
+        let tree = pool::parse(language, content)
+            .with_context(|| format!("Failed to parse {}", path))?;
+        let mut parsed = self.measure(path, content, &tree, language);
+        parsed.facts = passes(tree.root_node(), language)?;
+        Ok(parsed)
+    }
+
+    /// Like `parse_file`, but keep the tree, and start from `previous`'s when it is the same language
+    ///
+    /// The change between `previous`'s text and `content` is applied to its
+    /// tree, so tree-sitter re-parses only around the edit. The measurements
+    /// are the same as `parse_file`'s for the same text.
+    pub fn parse_retained(&self, path: &str, content: String, previous: Option<RetainedTree>) -> Result<(ParsedFile, RetainedTree)> {
+        let language = language_of(path)?;
+        let old_tree = previous.filter(|previous| previous.language == language)
+            .map(|RetainedTree { source, mut tree, .. }| {
+                if let Some(edit) = input_edit(&source, &content) {
+                    tree.edit(&edit);
+                }
+                tree
+            });
+        let tree = pool::reparse(language, &content, old_tree.as_ref())
+            .with_context(|| format!("Failed to parse {}", path))?;
+        let parsed = self.measure(path, &content, &tree, language);
+        Ok((parsed, RetainedTree { language, source: content, tree }))
+    }
+
+    /// The pool keeps built-in grammars only, so a plugin file gets a parser of its own
+    fn parse_plugin(&self, path: &str, content: &str, provider: &dyn LanguageProvider) -> Result<ParsedFile> {
+        let tree = provider.create_parser()?.parse(content, None)
+            .with_context(|| format!("Failed to parse {}", path))?;
+        let query = query_cache::compiled(provider.name(), &provider.grammar(), provider.queries().functions)
+            .with_context(|| format!("Invalid query for plugin language {}", provider.name()))?;
+        let weights = self.error_handling.for_language(&LangType::Plugin(provider.name().to_string()));
+
+        let mut functions = Vec::new();
+        let mut cursor = QueryCursor::new();
+        for found in cursor.matches(&query, tree.root_node(), content.as_bytes()) {
+            if let Some(capture) = found.captures.first() {
+                functions.push(function_info(capture.node, content, weights, provider.decision_kinds()));
+            }
+        }
+        let mut comment_rows = HashSet::new();
+        walk_descendants(tree.root_node(), |node, _| {
+            add_comment_rows(node, &mut comment_rows);
+            true
+        });
+
+        // A plugin grammar's import kinds are unknown, so none are counted
+        Ok(ParsedFile {
+            path: path.to_string(),
+            line_count: content.lines().count() as u32,
+            functions,
+            comment_lines: comment_rows.len() as u32,
+            imports: 0,
+            inline_tests: 0,
+            content_key: ContentKey::of(content),
+            facts: FileFacts::default(),
+        })
+    }
+
+    fn measure(&self, path: &str, content: &str, tree: &Tree, language: SupportedLanguage) -> ParsedFile {
+        let weights = self.error_handling.for_language(&language.language());
+        let mut functions = Vec::new();
+        let mut comment_rows = HashSet::new();
+        let mut imports = 0;
+        let mut inline_tests = 0;
+        walk_descendants(tree.root_node(), |node, _| {
+            if FUNCTION_KINDS.contains(&node.kind()) {
+                functions.push(function_info(node, content, weights, &[]));
+            }
+            add_comment_rows(node, &mut comment_rows);
+            imports += u32::from(is_import(node, content));
+            inline_tests += u32::from(is_test_attribute(node, content));
+            true
+        });
+
+        ParsedFile {
+            path: path.to_string(),
+            line_count: content.lines().count() as u32,
+            functions,
+            comment_lines: comment_rows.len() as u32,
+            imports,
+            inline_tests,
+            content_key: ContentKey::of(content),
+            facts: FileFacts::default(),
+        }
+    }
+}
+
+fn extension_of(path: &str) -> &str {
+    Path::new(path).extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+}
+
+fn language_of(path: &str) -> Result<SupportedLanguage> {
+    SupportedLanguage::from_extension(extension_of(path))
+        .with_context(|| format!("Unsupported file type: {}", path))
+}
+
+/// The registered plugin for `path`, when no built-in grammar claims its extension
+fn plugin_of(path: &str) -> Option<Arc<dyn LanguageProvider>> {
+    if LanguageParser::is_builtin(path) {
+        return None;
+    }
+    language_provider::for_extension(extension_of(path))
+}
+
+/// Every grammar names its comment nodes `comment`, `line_comment`, `block_comment` or similar
+///
+/// Rust's line comments take their newline with them, so a comment ending at
+/// the start of a row does not count that row.
+fn add_comment_rows(node: Node, rows: &mut HashSet<usize>) {
+    if node.kind().contains("comment") {
+        let (start, end) = (node.start_position(), node.end_position());
+        let last = if end.column == 0 && end.row > start.row { end.row - 1 } else { end.row };
+        rows.extend(start.row..=last);
+    }
+}
+
+/// One imported module or crate path; a grouped Go import counts each spec
+fn is_import(node: Node, content: &str) -> bool {
+    let callee = |field: &str| node.child_by_field_name(field).map(|name| &content[name.byte_range()]);
+    match node.kind() {
+        "use_declaration" | "import_statement" | "import_from_statement" | "import_spec" => true,
+        "call_expression" => callee("function") == Some("require"),
+        "call" => node.child_by_field_name("receiver").is_none()
+            && matches!(callee("method"), Some("require" | "require_relative")),
+        _ => false,
+    }
+}
+
+/// `#[cfg(test)]`, `#[test]` or a runner's own test attribute such as `#[tokio::test]`
+fn is_test_attribute(node: Node, content: &str) -> bool {
+    if node.kind() != "attribute_item" {
+        return false;
+    }
+    let attribute: String = content[node.byte_range()].chars().filter(|c| !c.is_whitespace()).collect();
+    attribute == "#[cfg(test)]" || attribute == "#[test]" || attribute.ends_with("::test]")
+}
+
+/// `extra_kinds` are a plugin's own decision node kinds, counted like `ASTAnalyzer` counts them
+fn function_info(node: Node, content: &str, weights: &ErrorHandlingWeights, extra_kinds: &[&str]) -> FunctionInfo {
+    let mut complexity = 1;
+    walk_descendants(node, |node, _| {
+        complexity += match weights.weight(node, content) {
+            Some(weight) => weight,
+            None => u32::from(is_decision_point(node) || (node.is_named() && extra_kinds.contains(&node.kind()))),
+        };
+        true
+    });
+
+    FunctionInfo {
+        name: function_name(node, content).unwrap_or_else(|| "anonymous".to_string()),
+        line_start: node.start_position().row as u32 + 1,
+        line_end: node.end_position().row as u32 + 1,
+        parameters: parameter_count(node),
+        complexity,
+        statements: statement_count(node),
+        scope: enclosing_scope(node, content),
+    }
+}
+
+/// Nodes whose named children are statements: blocks in every grammar, and
+/// Ruby's bodies and `then`/`else` branches
+const STATEMENT_LIST_KINDS: [&str; 6] = ["block", "statement_block", "block_body", "body_statement", "then", "else"];
+
+/// Statements anywhere in `function`, or one for an arrow function or method
+/// whose body is a bare expression
+fn statement_count(function: Node) -> u32 {
+    let mut statements = 0;
+    walk_descendants(function, |node, _| {
+        if STATEMENT_LIST_KINDS.contains(&node.kind()) {
+            let mut cursor = node.walk();
+            statements += node.named_children(&mut cursor)
+                .filter(|child| !child.kind().contains("comment") && !matches!(child.kind(), "label" | "empty_statement"))
+                .count() as u32;
+        }
+        true
+    });
+    let expression_body = function.child_by_field_name("body")
+        .is_some_and(|body| !STATEMENT_LIST_KINDS.contains(&body.kind()));
+    statements + u32::from(expression_body)
+}
+
+/// Definitions other than functions that qualify the functions inside them
+const SCOPE_KINDS: [&str; 9] = [
+    "impl_item",
+    "trait_item",
+    "mod_item",
+    "class_definition",
+    "class_declaration",
+    "abstract_class_declaration",
+    "interface_declaration",
+    "class",
+    "module",
+];
+
+/// Names of the definitions around a function, outermost first and `::`-separated
+///
+/// Trait impls read `<Type as Trait>` so `fmt` in `Display` and `Debug` impls
+/// differ. Go methods are declared outside their type, so the receiver's type
+/// stands in for it.
+fn enclosing_scope(node: Node, content: &str) -> String {
+    let mut names = Vec::new();
+    let mut ancestor = node.parent();
+    while let Some(scope) = ancestor {
+        if FUNCTION_KINDS.contains(&scope.kind()) {
+            names.push(function_name(scope, content).unwrap_or_else(|| "anonymous".to_string()));
+        } else if SCOPE_KINDS.contains(&scope.kind()) {
+            if let Some(name) = scope.child_by_field_name("name").or_else(|| scope.child_by_field_name("type")) {
+                let name = &content[name.byte_range()];
+                names.push(match scope.child_by_field_name("trait") {
+                    Some(implemented) => format!("<{} as {}>", name, &content[implemented.byte_range()]),
+                    None => name.to_string(),
+                });
+            }
+        }
+        ancestor = scope.parent();
+    }
+    if let Some(receiver) = node.child_by_field_name("receiver").filter(|_| node.kind() == "method_declaration") {
+        let mut receiver_type = None;
+        walk_descendants(receiver, |descendant, _| {
+            if descendant.kind() == "type_identifier" {
+                receiver_type = Some(content[descendant.byte_range()].to_string());
+            }
+            receiver_type.is_none()
+        });
+        names.extend(receiver_type);
+    }
+    names.reverse();
+    names.join("::")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_functions_and_their_complexity() -> Result<()> {
+        let source = "\
+fn classify(n: i32, strict: bool) -> &'static str {
+    if n < 0 {
+        \"negative\"
+    } else if n == 0 {
+        \"zero\"
+    } else {
+        \"positive\"
+    }
+}
+
+fn id(x: u8) -> u8 { x }
+";
+        let parsed = LanguageParser::new().parse_file("src/lib.rs", source)?;
+
+        assert_eq!(parsed.line_count, 11);
+        let names: Vec<_> = parsed.functions.iter().map(|function| function.name.as_str()).collect();
+        assert_eq!(names, ["classify", "id"]);
+        assert_eq!(parsed.functions[0].parameters, 2);
+        assert_eq!((parsed.functions[0].line_start, parsed.functions[0].line_end), (1, 9));
+        assert!(parsed.functions[0].complexity > parsed.functions[1].complexity);
+        assert_eq!(parsed.functions[1].complexity, 1);
+        assert!(LanguageParser::new().parse_file("notes.txt", "").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_go_and_ruby_methods_are_functions() -> Result<()> {
+        let go = "package cart\n\nfunc (c *Cart) Total(tax int) int {\n\tif tax > 0 {\n\t\treturn tax\n\t}\n\treturn 0\n}\n";
+        let ruby = "class Cart\n  def total(tax)\n    tax > 0 ? tax : 0\n  end\n\n  def self.empty\n    new\n  end\nend\n";
+
+        let parsed = LanguageParser::new().parse_file("cart.go", go)?;
+        assert_eq!(parsed.functions.len(), 1);
+        assert_eq!((parsed.functions[0].name.as_str(), parsed.functions[0].complexity), ("Total", 2));
+        assert_eq!(parsed.functions[0].scope, "Cart");
+
+        let parsed = LanguageParser::new().parse_file("cart.rb", ruby)?;
+        let names: Vec<_> = parsed.functions.iter().map(|function| function.name.as_str()).collect();
+        assert_eq!(names, ["total", "empty"]);
+        assert!(parsed.functions.iter().all(|function| function.scope == "Cart"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_handling_weights_reach_function_complexity() -> Result<()> {
+        let source = "fn run() -> Result<()> {\n    step()?;\n    next()?;\n    Ok(())\n}\n";
+        let mut paths = ErrorHandling::default();
+        paths.override_with(&["rust=paths".to_string()])?;
+
+        let standard = LanguageParser::new().parse_file("lib.rs", source)?;
+        let weighted = LanguageParser::with_error_handling(paths).parse_file("lib.rs", source)?;
+        assert_eq!(standard.functions[0].complexity, 1);
+        assert_eq!(weighted.functions[0].complexity, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_statements_are_counted_per_grammar() -> Result<()> {
+        let statements = |path: &str, source: &str| -> Result<Vec<u32>> {
+            let parsed = LanguageParser::new().parse_file(path, source)?;
+            Ok(parsed.functions.iter().map(|function| function.statements).collect())
+        };
+
+        let rust = "fn run() -> Result<()> {\n    // two steps\n    step()?;\n    next()?;\n    Ok(())\n}\n\nfn id(x: u8) -> u8 { x }\n";
+        assert_eq!(statements("lib.rs", rust)?, [3, 1]);
+        let python = "def classify(value):\n    if value < 0:\n        return 'negative'\n    return 'positive'\n";
+        assert_eq!(statements("app.py", python)?, [3]);
+        assert_eq!(statements("app.js", "const double = x => x * 2;\n")?, [1]);
+        assert_eq!(statements("cart.rb", "def total(tax)\n  sum = tax * 2\n  sum\nend\n")?, [2]);
+        Ok(())
+    }
+}
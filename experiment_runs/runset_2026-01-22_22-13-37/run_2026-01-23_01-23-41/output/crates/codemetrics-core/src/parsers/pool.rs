@@ -0,0 +1,65 @@
+//! Reusable tree-sitter parsers, one per language per thread
+//!
+//! Creating a `Parser` and loading its grammar costs about as much as parsing
+//! a small file, and the passes that reparse files (line maps, clones, types,
+//! vocabulary, ...) used to pay it for every file. A `Parser` needs `&mut` to
+//! parse and cannot be shared between threads, so each worker thread keeps
+//! its own, configured on first use and reused for every later file.
+
+use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use tree_sitter::{Parser, Tree};
+
+use super::SupportedLanguage;
+
+thread_local! {
+    static PARSERS: RefCell<HashMap<SupportedLanguage, Parser>> = RefCell::new(HashMap::new());
+}
+
+/// Parse `source` as `language` with this thread's parser for it
+pub fn parse(language: SupportedLanguage, source: &str) -> Result<Tree> {
+    reparse(language, source, None)
+}
+
+/// Like `parse`, reusing the subtrees of `old` that its recorded edits left untouched
+pub fn reparse(language: SupportedLanguage, source: &str, old: Option<&Tree>) -> Result<Tree> {
+    PARSERS.with(|parsers| {
+        let mut parsers = parsers.borrow_mut();
+        let parser = match parsers.entry(language) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut parser = Parser::new();
+                parser.set_language(&language.get_language())
+                    .with_context(|| format!("Failed to set language for {:?}", language))?;
+                entry.insert(parser)
+            }
+        };
+        match parser.parse(source, old) {
+            Some(tree) => Ok(tree),
+            None => {
+                // A failed parse can leave partial state behind for the next file
+                parser.reset();
+                anyhow::bail!("Failed to parse {:?} source", language)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsers_are_reused_across_languages_and_files() {
+        let rust = parse(SupportedLanguage::Rust, "fn main() {}").unwrap();
+        let python = parse(SupportedLanguage::Python, "def main():\n    pass\n").unwrap();
+        let again = parse(SupportedLanguage::Rust, "struct Point { x: i32 }").unwrap();
+
+        assert_eq!(rust.root_node().kind(), "source_file");
+        assert_eq!(python.root_node().kind(), "module");
+        assert_eq!(again.root_node().child(0).unwrap().kind(), "struct_item");
+        PARSERS.with(|parsers| assert_eq!(parsers.borrow().len(), 2));
+    }
+}
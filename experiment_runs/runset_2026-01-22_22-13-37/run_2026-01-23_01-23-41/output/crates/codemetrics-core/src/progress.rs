@@ -0,0 +1,18 @@
+//! How far a long analysis has got, for front ends to show
+//!
+//! `CodeAnalyzer` reports each step through a `Progress` it was given and
+//! draws nothing itself; the command line renders these as a progress bar.
+
+use std::path::Path;
+
+/// Every method defaults to doing nothing, so an implementation picks what it shows
+pub trait Progress: Send + Sync {
+    /// `files` were discovered and are about to be parsed
+    fn start(&self, _files: usize) {}
+
+    /// `path` was parsed, or failed to parse
+    fn parsed(&self, _path: &Path) {}
+
+    /// Every file was parsed; `cached` of them were unchanged and read from the cache
+    fn finish(&self, _cached: Option<usize>) {}
+}
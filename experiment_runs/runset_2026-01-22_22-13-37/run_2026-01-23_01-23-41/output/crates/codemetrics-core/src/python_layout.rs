@@ -0,0 +1,143 @@
+//! Where Python imports are rooted
+//!
+//! `import mypkg.core` names a file relative to a source root, not to the
+//! analyzed directory: with a `src/` layout the file is `src/mypkg/core.py`.
+//! Directories without `__init__.py` are namespace packages (PEP 420) and are
+//! importable all the same. The layout is inferred from the analyzed files, so
+//! no packaging metadata is required.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Directory names treated as source roots in addition to the analyzed root
+const SOURCE_DIRS: [&str; 1] = ["src"];
+
+#[derive(Debug, Clone, Default)]
+pub struct PythonLayout {
+    /// The analyzed root first, then every `src/` directory holding Python files
+    source_roots: Vec<PathBuf>,
+    /// Top-level packages and modules importable from any source root
+    top_level: HashSet<String>,
+}
+
+impl PythonLayout {
+    pub fn from_files<'a>(root: &Path, files: impl IntoIterator<Item = &'a PathBuf>) -> Self {
+        let files: Vec<&PathBuf> = files.into_iter()
+            .filter(|file| file.extension().is_some_and(|ext| ext == "py" || ext == "pyi"))
+            .collect();
+
+        let mut source_roots = vec![root.to_path_buf()];
+        for file in &files {
+            for dir in file.ancestors().skip(1).take_while(|dir| dir.starts_with(root) && *dir != root) {
+                let is_source_dir = dir.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| SOURCE_DIRS.contains(&name));
+                if is_source_dir && !source_roots.iter().any(|known| known == dir) {
+                    source_roots.push(dir.to_path_buf());
+                }
+            }
+        }
+
+        let mut top_level = HashSet::new();
+        for file in &files {
+            for source_root in &source_roots {
+                let Ok(relative) = file.strip_prefix(source_root) else {
+                    continue;
+                };
+                let mut components = relative.components();
+                let (Some(first), rest) = (components.next(), components.next()) else {
+                    continue;
+                };
+                let first = first.as_os_str().to_string_lossy();
+                // A lone file is a module named by its stem; otherwise the directory is a package
+                let name = match rest {
+                    Some(_) => first.into_owned(),
+                    None => first.trim_end_matches(".pyi").trim_end_matches(".py").to_string(),
+                };
+                top_level.insert(name);
+            }
+        }
+
+        Self { source_roots, top_level }
+    }
+
+    /// Whether an absolute import names a package or module of this project
+    pub fn is_internal(&self, module_path: &str) -> bool {
+        let first = module_path.split('.').next().unwrap_or(module_path);
+        self.top_level.contains(first)
+    }
+
+    /// Files an import may refer to, most specific first
+    ///
+    /// `from pkg import name` may import the submodule `pkg.name`, so that is
+    /// tried before `pkg` itself. Relative imports (`..pkg`) climb one package
+    /// per dot beyond the first, starting from the importer's own package.
+    pub fn candidates(&self, importer: &Path, module_path: &str, imported_names: &[String]) -> Vec<PathBuf> {
+        let level = module_path.chars().take_while(|c| *c == '.').count();
+        let parts: Vec<&str> = module_path[level..].split('.').filter(|part| !part.is_empty()).collect();
+
+        let bases: Vec<PathBuf> = if level > 0 {
+            let mut package = importer.parent().unwrap_or(Path::new("")).to_path_buf();
+            for _ in 1..level {
+                if !package.pop() {
+                    return Vec::new();
+                }
+            }
+            vec![package]
+        } else {
+            self.source_roots.clone()
+        };
+
+        let mut candidates = Vec::new();
+        for base in bases {
+            let module = parts.iter().fold(base, |path, part| path.join(part));
+            if let [name] = imported_names {
+                candidates.extend(module_files(&module.join(name)));
+            }
+            if !parts.is_empty() || level > 0 {
+                candidates.extend(module_files(&module));
+            }
+        }
+        candidates
+    }
+}
+
+/// `pkg/mod.py`, its stub, or `pkg/mod/__init__.py`
+fn module_files(module: &Path) -> [PathBuf; 3] {
+    [
+        module.with_extension("py"),
+        module.with_extension("pyi"),
+        module.join("__init__.py"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_src_layout_namespace_packages_and_relative_levels() {
+        let root = PathBuf::from("proj");
+        let files: Vec<PathBuf> = [
+            "proj/src/shop/__init__.py",
+            "proj/src/shop/orders/service.py",
+            "proj/src/shop/util.py",
+            // Namespace package: no __init__.py
+            "proj/src/plugins/stripe.py",
+            "proj/manage.py",
+        ].iter().map(PathBuf::from).collect();
+        let layout = PythonLayout::from_files(&root, &files);
+
+        assert!(layout.is_internal("shop.orders.service"));
+        assert!(layout.is_internal("plugins.stripe"));
+        assert!(layout.is_internal("manage"));
+        assert!(!layout.is_internal("requests"));
+
+        let service = Path::new("proj/src/shop/orders/service.py");
+        let candidates = layout.candidates(service, "..util", &["format_price".to_string()]);
+        assert!(candidates.contains(&PathBuf::from("proj/src/shop/util.py")));
+        assert!(layout.candidates(service, "plugins", &["stripe".to_string()])
+            .contains(&PathBuf::from("proj/src/plugins/stripe.py")));
+        assert!(layout.candidates(service, "......too.far", &[]).is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+//! Process-wide cache of compiled tree-sitter queries
+//!
+//! Compiling a query is far more expensive than running it, and analyzers are
+//! constructed repeatedly by long-lived hosts (editor integrations, server
+//! mode). Queries are immutable once compiled and safe to share between
+//! threads, so each distinct query is compiled once per process.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use tree_sitter::{Language, Query};
+
+/// A query is only reusable with the exact grammar it was compiled against
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct QueryKey {
+    grammar: &'static str,
+    abi_version: usize,
+    /// Distinguishes builds of the same grammar with different node sets
+    node_kinds: usize,
+    source: String,
+}
+
+static QUERIES: OnceLock<Mutex<HashMap<QueryKey, Arc<Query>>>> = OnceLock::new();
+
+/// The compiled form of `source` for `language`, compiling it on first use
+///
+/// `grammar` names the language, e.g. `"rust"`. Compilation errors are returned
+/// every time rather than cached.
+pub fn compiled(grammar: &'static str, language: &Language, source: &str) -> Result<Arc<Query>> {
+    let key = QueryKey {
+        grammar,
+        abi_version: language.version(),
+        node_kinds: language.node_kind_count(),
+        source: source.to_string(),
+    };
+    let cache = QUERIES.get_or_init(Default::default);
+
+    if let Some(query) = cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get(&key) {
+        return Ok(Arc::clone(query));
+    }
+
+    // Compile without holding the lock; a concurrent duplicate is simply discarded
+    let query = Arc::new(Query::new(language, source)?);
+    let mut cache = cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    Ok(Arc::clone(cache.entry(key).or_insert(query)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_queries_are_compiled_once() {
+        let language = tree_sitter::Language::new(tree_sitter_rust::LANGUAGE);
+        let source = "(struct_item name: (type_identifier) @name) @query_cache_test";
+
+        let first = compiled("rust", &language, source).unwrap();
+        let second = compiled("rust", &language, source).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let other = compiled("rust", &language, "(enum_item) @query_cache_test").unwrap();
+        assert!(!Arc::ptr_eq(&first, &other));
+
+        assert!(compiled("rust", &language, "(not_a_node_kind) @x").is_err());
+    }
+}
@@ -0,0 +1,270 @@
+//! Release health comparison between two tags
+//!
+//! Checks out both revisions into temporary directories, analyzes each one and
+//! summarizes what changed in a form suitable for release notes appendices.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::analyzers::{AnalysisResults, CodeAnalyzer, HighComplexityFunction};
+use crate::parsers::ParsedFile;
+use crate::stack;
+use crate::vcs::{self, Vcs};
+
+/// Summary of the changes between two releases
+#[derive(Debug, Serialize)]
+pub struct ReleaseReport {
+    pub from: String,
+    pub to: String,
+    pub loc_delta: i64,
+    pub function_delta: i64,
+    pub total_complexity_delta: i64,
+    pub average_complexity_delta: f64,
+    pub new_public_apis: Vec<PublicApi>,
+    pub new_hotspots: Vec<HighComplexityFunction>,
+    pub added_dependencies: Vec<String>,
+    pub removed_dependencies: Vec<String>,
+}
+
+/// A public function that exists in the newer release only
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicApi {
+    pub name: String,
+    pub file_path: String,
+    pub line_start: u32,
+}
+
+/// Metrics and inventory collected for a single revision
+struct ReleaseSnapshot {
+    results: AnalysisResults,
+    public_apis: Vec<PublicApi>,
+    dependencies: BTreeSet<String>,
+}
+
+impl ReleaseReport {
+    /// Analyze `from` and `to` in the repository at `repo` and compare them
+    pub fn generate(analyzer: &CodeAnalyzer, repo: &Path, from: &str, to: &str) -> Result<Self> {
+        let vcs = vcs::detect(repo)?;
+        let old = ReleaseSnapshot::capture(analyzer, vcs.as_ref(), from)?;
+        let new = ReleaseSnapshot::capture(analyzer, vcs.as_ref(), to)?;
+
+        Ok(Self::compare(from, to, &old, &new))
+    }
+
+    fn compare(from: &str, to: &str, old: &ReleaseSnapshot, new: &ReleaseSnapshot) -> Self {
+        let old_apis: BTreeSet<(&str, &str)> = old.public_apis.iter()
+            .map(|api| (api.file_path.as_str(), api.name.as_str()))
+            .collect();
+        let new_public_apis = new.public_apis.iter()
+            .filter(|api| !old_apis.contains(&(api.file_path.as_str(), api.name.as_str())))
+            .cloned()
+            .collect();
+
+        let old_hotspots: BTreeSet<(&str, &str)> = old.results.high_complexity_functions.iter()
+            .map(|f| (f.file_path.as_str(), f.name.as_str()))
+            .collect();
+        let new_hotspots = new.results.high_complexity_functions.iter()
+            .filter(|f| !old_hotspots.contains(&(f.file_path.as_str(), f.name.as_str())))
+            .cloned()
+            .collect();
+
+        Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            loc_delta: new.results.total_lines as i64 - old.results.total_lines as i64,
+            function_delta: new.results.total_functions as i64 - old.results.total_functions as i64,
+            total_complexity_delta: total_complexity(&new.results) as i64 - total_complexity(&old.results) as i64,
+            average_complexity_delta: new.results.average_complexity - old.results.average_complexity,
+            new_public_apis,
+            new_hotspots,
+            added_dependencies: new.dependencies.difference(&old.dependencies).cloned().collect(),
+            removed_dependencies: old.dependencies.difference(&new.dependencies).cloned().collect(),
+        }
+    }
+
+    /// Render the report as a Markdown appendix for release notes
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+
+        markdown.push_str(&format!("## Code Health: {} → {}\n\n", self.from, self.to));
+        markdown.push_str("| Metric | Change |\n");
+        markdown.push_str("|--------|--------|\n");
+        markdown.push_str(&format!("| Lines of code | {:+} |\n", self.loc_delta));
+        markdown.push_str(&format!("| Functions | {:+} |\n", self.function_delta));
+        markdown.push_str(&format!("| Total complexity | {:+} |\n", self.total_complexity_delta));
+        markdown.push_str(&format!("| Average complexity | {:+.2} |\n\n", self.average_complexity_delta));
+
+        if !self.new_public_apis.is_empty() {
+            markdown.push_str(&format!("### New Public APIs ({})\n\n", self.new_public_apis.len()));
+            for api in &self.new_public_apis {
+                markdown.push_str(&format!("- `{}` ({}:{})\n", api.name, api.file_path, api.line_start));
+            }
+            markdown.push('\n');
+        }
+
+        if !self.new_hotspots.is_empty() {
+            markdown.push_str(&format!("### New Complexity Hotspots ({})\n\n", self.new_hotspots.len()));
+            markdown.push_str("| Function | Complexity | Location |\n");
+            markdown.push_str("|----------|------------|----------|\n");
+            for func in &self.new_hotspots {
+                markdown.push_str(&format!(
+                    "| `{}` | {} | `{}:{}` |\n",
+                    func.name, func.complexity, func.file_path, func.line_start
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !self.added_dependencies.is_empty() || !self.removed_dependencies.is_empty() {
+            markdown.push_str("### Dependency Changes\n\n");
+            for dep in &self.added_dependencies {
+                markdown.push_str(&format!("- ➕ `{}`\n", dep));
+            }
+            for dep in &self.removed_dependencies {
+                markdown.push_str(&format!("- ➖ `{}`\n", dep));
+            }
+            markdown.push('\n');
+        }
+
+        markdown
+    }
+}
+
+impl ReleaseSnapshot {
+    fn capture(analyzer: &CodeAnalyzer, vcs: &dyn Vcs, rev: &str) -> Result<Self> {
+        let worktree = Worktree::checkout(vcs, rev)?;
+
+        let parsed_files: Vec<ParsedFile> = analyzer.parse_path(&worktree.dir)?
+            .into_iter()
+            .filter_map(|parsed| parsed.ok())
+            .map(|mut parsed| {
+                parsed.path = relative_path(&worktree.dir, &parsed.path);
+                parsed
+            })
+            .collect();
+
+        let public_apis = collect_public_apis(&worktree.dir, &parsed_files);
+        let dependencies = manifest_dependencies(&worktree.dir);
+        let results = analyzer.aggregate_results(parsed_files.into_iter().map(Ok).collect())?;
+
+        Ok(Self { results, public_apis, dependencies })
+    }
+}
+
+/// Temporary checkout of one revision, removed again on drop
+pub(crate) struct Worktree<'a> {
+    vcs: &'a dyn Vcs,
+    pub(crate) dir: PathBuf,
+}
+
+impl<'a> Worktree<'a> {
+    pub(crate) fn checkout(vcs: &'a dyn Vcs, rev: &str) -> Result<Self> {
+        let sanitized: String = rev.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .collect();
+        let dir = std::env::temp_dir()
+            .join(format!("codemetrics-release-{}-{}", sanitized, std::process::id()));
+
+        vcs.checkout(rev, &dir)?;
+        Ok(Self { vcs, dir })
+    }
+}
+
+impl Drop for Worktree<'_> {
+    fn drop(&mut self) {
+        let _ = self.vcs.remove_checkout(&self.dir);
+    }
+}
+
+fn total_complexity(results: &AnalysisResults) -> u32 {
+    results.complexity_distribution
+        .iter()
+        .map(|(complexity, count)| complexity * count)
+        .sum()
+}
+
+fn relative_path(root: &Path, path: &str) -> String {
+    Path::new(path)
+        .strip_prefix(root)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// Find functions whose declaration line marks them as part of the public surface
+fn collect_public_apis(root: &Path, parsed_files: &[ParsedFile]) -> Vec<PublicApi> {
+    let mut apis = Vec::new();
+
+    for parsed_file in parsed_files {
+        let Ok(content) = std::fs::read_to_string(root.join(&parsed_file.path)) else {
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        let extension = parsed_file.path.rsplit('.').next().unwrap_or("");
+
+        for function in &parsed_file.functions {
+            let line = lines.get(function.line_start.saturating_sub(1) as usize).copied().unwrap_or("");
+            if is_public_declaration(extension, line.trim_start(), &function.name) {
+                apis.push(PublicApi {
+                    name: function.name.clone(),
+                    file_path: parsed_file.path.clone(),
+                    line_start: function.line_start,
+                });
+            }
+        }
+    }
+
+    apis
+}
+
+fn is_public_declaration(extension: &str, line: &str, name: &str) -> bool {
+    match extension {
+        "rs" => line.starts_with("pub "),
+        "js" | "jsx" | "ts" | "tsx" => line.starts_with("export "),
+        "py" => !name.starts_with('_'),
+        "go" => name.chars().next().is_some_and(|c| c.is_uppercase()),
+        _ => false,
+    }
+}
+
+/// Collect declared dependency names from the manifests found at the project root
+fn manifest_dependencies(root: &Path) -> BTreeSet<String> {
+    [("Cargo.toml", "cargo"), ("package.json", "npm"), ("requirements.txt", "pypi")].into_iter()
+        .filter_map(|(manifest, registry)| Some((registry, stack::manifest_dependencies(&root.join(manifest))?)))
+        .flat_map(|(registry, names)| names.into_iter().map(move |name| format!("{}:{}", registry, name)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_public_declaration_detection() {
+        assert!(is_public_declaration("rs", "pub fn analyze() {", "analyze"));
+        assert!(!is_public_declaration("rs", "fn helper() {", "helper"));
+        assert!(is_public_declaration("ts", "export function render() {", "render"));
+        assert!(!is_public_declaration("py", "def _private():", "_private"));
+        assert!(is_public_declaration("go", "func Serve() {", "Serve"));
+    }
+
+    #[test]
+    fn test_manifest_dependencies() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n\n[dependencies]\nserde = \"1.0\"\n\n[dependencies.tokio]\nversion = \"1\"\n\n[dev-dependencies]\ntempfile = \"3\"\n",
+        ).unwrap();
+        std::fs::write(temp_dir.path().join("requirements.txt"), "requests>=2.0\n# comment\n").unwrap();
+
+        let deps = manifest_dependencies(temp_dir.path());
+        assert!(deps.contains("cargo:serde"));
+        assert!(deps.contains("cargo:tokio"));
+        assert!(deps.contains("cargo:tempfile"));
+        assert!(deps.contains("pypi:requests"));
+        assert!(!deps.contains("cargo:name"));
+        assert!(!deps.contains("cargo:version"));
+    }
+}
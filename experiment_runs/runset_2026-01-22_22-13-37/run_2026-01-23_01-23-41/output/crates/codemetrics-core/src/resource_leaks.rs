@@ -0,0 +1,302 @@
+//! Files, sockets and locks opened without being closed
+//!
+//! Best effort, within one function: a resource counts as closed when the
+//! language closes it for you (Python `with`, Ruby blocks), when the function
+//! calls `close`, `release` or `unlock` on what it was bound to, or when the
+//! function hands it to its caller or stores it on an object. Everything else
+//! is reported under `rules::RESOURCE_LEAK`. Rust is left out, since its
+//! handles and guards close when dropped.
+
+use tree_sitter::Node;
+
+use crate::ast_analyzer::{excerpt, walk_descendants, FUNCTION_KINDS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    File,
+    Socket,
+    Lock,
+}
+
+/// Calls that open a resource, per grammar
+const OPENERS: &[(&str, &str, Resource)] = &[
+    ("python", "open", Resource::File),
+    ("python", "io.open", Resource::File),
+    ("python", "os.fdopen", Resource::File),
+    ("python", "tempfile.TemporaryFile", Resource::File),
+    ("python", "tempfile.NamedTemporaryFile", Resource::File),
+    ("python", "socket.socket", Resource::Socket),
+    ("python", "socket.create_connection", Resource::Socket),
+    ("javascript", "fs.open", Resource::File),
+    ("javascript", "fs.openSync", Resource::File),
+    ("javascript", "openSync", Resource::File),
+    ("javascript", "fs.promises.open", Resource::File),
+    ("javascript", "fsPromises.open", Resource::File),
+    ("javascript", "net.connect", Resource::Socket),
+    ("javascript", "net.createConnection", Resource::Socket),
+    ("javascript", "tls.connect", Resource::Socket),
+    ("ruby", "File.open", Resource::File),
+    ("ruby", "File.new", Resource::File),
+    ("ruby", "Tempfile.new", Resource::File),
+    ("ruby", "TCPSocket.new", Resource::Socket),
+    ("ruby", "TCPSocket.open", Resource::Socket),
+    ("ruby", "UNIXSocket.new", Resource::Socket),
+];
+
+/// Functions in grammars whose kinds `FUNCTION_KINDS` does not list
+const MORE_FUNCTION_KINDS: &[&str] = &["function_expression", "generator_function_declaration"];
+
+/// A resource opened in a function that does not close it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceLeak {
+    /// 1-based
+    pub line: u32,
+    pub column: u32,
+    pub resource: Resource,
+    /// The call that opened it, shortened
+    pub call: String,
+}
+
+impl ResourceLeak {
+    pub fn message(&self) -> String {
+        match self.resource {
+            Resource::File => format!("`{}` opens a file that this function never closes", self.call),
+            Resource::Socket => format!("`{}` opens a socket that this function never closes", self.call),
+            Resource::Lock => format!("`{}` takes a lock that this function never releases", self.call),
+        }
+    }
+}
+
+/// Every such resource in a file parsed with `grammar`, in source order
+pub fn find(root: Node, content: &str, grammar: &str) -> Vec<ResourceLeak> {
+    let mut found = Vec::new();
+    walk_descendants(root, |node, _| {
+        if let Some((resource, callee)) = opened(node, content, grammar) {
+            // Scripts end right after their top-level code, closing everything
+            if let Some(function) = enclosing_function(node) {
+                if is_leaked(node, &callee, resource, content, grammar, function) {
+                    let position = node.start_position();
+                    found.push(ResourceLeak {
+                        line: position.row as u32 + 1,
+                        column: position.column as u32 + 1,
+                        resource,
+                        call: excerpt(&content[node.byte_range()]),
+                    });
+                }
+            }
+        }
+        true
+    });
+    found
+}
+
+/// How to make sure the resource is closed, per grammar
+pub fn suggestion(grammar: &str) -> &'static str {
+    match grammar {
+        "python" => "Use a `with` statement (`with open(path) as f:`, `with lock:`), which closes or releases even when an exception is raised",
+        "javascript" => "Close it in a `finally` block (`fs.closeSync(fd)`, `await handle.close()`, `socket.end()`), or use `fs.readFile` and streams, which close themselves",
+        "ruby" => "Pass a block (`File.open(path) { |f| ... }`, `mutex.synchronize { ... }`), or close it in an `ensure` clause",
+        _ => "Close the resource on every path out of the function",
+    }
+}
+
+/// What `call` opens, and the callee as written
+fn opened(call: Node, content: &str, grammar: &str) -> Option<(Resource, String)> {
+    let callee = callee(call, content)?;
+    if let Some(&(_, _, resource)) = OPENERS.iter().find(|(opener_grammar, name, _)| *opener_grammar == grammar && *name == callee) {
+        return Some((resource, callee));
+    }
+    let take_lock = match grammar {
+        "python" => ".acquire",
+        "ruby" => ".lock",
+        _ => return None,
+    };
+    callee.ends_with(take_lock).then_some((Resource::Lock, callee))
+}
+
+/// `fs.open` for JavaScript and Python calls, `File.open` for Ruby's receiver and method
+fn callee(call: Node, content: &str) -> Option<String> {
+    let text = |node: Node| content[node.byte_range()].to_string();
+    match call.kind() {
+        "call_expression" => call.child_by_field_name("function").map(text),
+        "call" => match call.child_by_field_name("function") {
+            Some(function) => Some(text(function)),
+            None => {
+                let method = text(call.child_by_field_name("method")?);
+                Some(match call.child_by_field_name("receiver") {
+                    Some(receiver) => format!("{}.{}", text(receiver), method),
+                    None => method,
+                })
+            }
+        },
+        _ => None,
+    }
+}
+
+fn is_leaked(call: Node, opener: &str, resource: Resource, content: &str, grammar: &str, function: Node) -> bool {
+    let body = &content[function.byte_range()];
+    if resource == Resource::Lock {
+        let receiver = opener.rsplit_once('.').map_or("", |(receiver, _)| receiver);
+        let release = if grammar == "python" { ".release(" } else { ".unlock" };
+        return !mentions(body, "", receiver, release);
+    }
+    // The block closes the file when it returns
+    if grammar == "ruby" && call.child_by_field_name("block").is_some() {
+        return false;
+    }
+
+    let mut value = call;
+    while let Some(parent) = value.parent().filter(|parent| matches!(parent.kind(), "await_expression" | "await" | "parenthesized_expression")) {
+        value = parent;
+    }
+    let Some(user) = value.parent() else {
+        return false;
+    };
+    match user.kind() {
+        // `with open(path) as f:`, or handed back to the caller
+        "with_item" | "as_pattern" | "return_statement" | "return" => false,
+        "variable_declarator" | "assignment" | "assignment_expression" => {
+            match user.child_by_field_name("name").or_else(|| user.child_by_field_name("left")) {
+                Some(target) if target.kind() == "identifier" => !is_closed(&content[target.byte_range()], body),
+                // Stored on an object (`self.file = ...`, `@socket = ...`), which owns it from then on
+                _ => false,
+            }
+        }
+        "argument_list" | "arguments" => {
+            let outer = user.parent();
+            if outer.is_some_and(|outer| outer.kind() == "return") {
+                return false;
+            }
+            let wrapper = outer.and_then(|outer| callee(outer, content)).unwrap_or_default();
+            !(wrapper.ends_with("closing") || wrapper.ends_with("enter_context"))
+        }
+        // Ruby returns a method's last expression
+        "body_statement" | "method" if grammar == "ruby" && value.next_named_sibling().is_none() => false,
+        // `fs.open(path, (err, fd) => ...)` gets its descriptor in the callback
+        _ if grammar == "javascript" => !body.contains("close("),
+        _ => true,
+    }
+}
+
+/// Whether `body` closes, returns or otherwise hands off the resource bound to `name`
+///
+/// `name` has to appear as a whole identifier: `self.close()` or `f2.close()`
+/// say nothing about `f`.
+fn is_closed(name: &str, body: &str) -> bool {
+    [".close", ".destroy(", ".end("].iter().any(|method| mentions(body, "", name, method))
+        || ["close(", "closeSync(", "closing(", "enter_context("].iter().any(|call| mentions(body, call, name, ""))
+        || ["return ", "with "].iter().any(|keyword| mentions(body, keyword, name, ""))
+}
+
+/// Whether `body` contains `before`, `name` and `after` in a row, with `name`
+/// standing alone rather than being part of a longer name, a field or a call,
+/// and a trailing method name in `after` not being the start of a longer one
+fn mentions(body: &str, before: &str, name: &str, after: &str) -> bool {
+    let is_identifier = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    body.match_indices(&format!("{}{}{}", before, name, after)).any(|(start, _)| {
+        let name_start = start + before.len();
+        let name_end = name_start + name.len();
+        let attached_before = body[..name_start].chars().next_back()
+            .is_some_and(|c| is_identifier(c) || matches!(c, '.' | '@'));
+        let next = body[name_end + after.len()..].chars().next();
+        let attached_after = if after.is_empty() {
+            // `return f.read()` hands back what was read, not `f`
+            next.is_some_and(|c| is_identifier(c) || matches!(c, '.' | '(' | '['))
+        } else {
+            // `f.closed` only asks whether `f.close` has happened
+            after.ends_with(is_identifier) && next.is_some_and(is_identifier)
+        };
+        !attached_before && !attached_after
+    })
+}
+
+fn enclosing_function(node: Node) -> Option<Node> {
+    let mut node = node;
+    while let Some(parent) = node.parent() {
+        if FUNCTION_KINDS.contains(&parent.kind()) || MORE_FUNCTION_KINDS.contains(&parent.kind()) {
+            return Some(parent);
+        }
+        node = parent;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::test_util::detect;
+    use crate::parsers::SupportedLanguage;
+
+    #[test]
+    fn test_python_files_and_locks() {
+        let source = "\
+def load(path):
+    f = open(path)
+    return f.read()
+
+def load_safely(path):
+    with open(path) as f:
+        return f.read()
+
+def first_line(path):
+    return open(path).readline()
+
+def opener(path):
+    return open(path)
+
+def locked(lock):
+    lock.acquire()
+    work()
+
+def reopen(self, path):
+    f = open(path)
+    self.close()
+    return f.read()
+
+def peek(path):
+    f = open(path)
+    if not f.closed:
+        return f.read(1)
+";
+        assert_eq!(detect(SupportedLanguage::Python, source, find, |leak| (leak.line, leak.resource)), vec![
+            (2, Resource::File),
+            (10, Resource::File),
+            (16, Resource::Lock),
+            (20, Resource::File),
+            (25, Resource::File),
+        ]);
+    }
+
+    #[test]
+    fn test_javascript_and_ruby_closes() {
+        let js = "\
+function readHeader(path) {
+  const fd = fs.openSync(path, 'r');
+  fs.readSync(fd, buffer, 0, 16, 0);
+  return buffer;
+}
+
+function readHeaderSafely(path) {
+  const fd = fs.openSync(path, 'r');
+  try {
+    return read(fd);
+  } finally {
+    fs.closeSync(fd);
+  }
+}
+";
+        assert_eq!(detect(SupportedLanguage::JavaScript, js, find, |leak| (leak.line, leak.resource)), vec![(2, Resource::File)]);
+
+        let ruby = "\
+def read_config(path)
+  file = File.open(path)
+  file.read
+end
+
+def read_config_safely(path)
+  File.open(path) { |f| f.read }
+end
+";
+        assert_eq!(detect(SupportedLanguage::Ruby, ruby, find, |leak| (leak.line, leak.resource)), vec![(2, Resource::File)]);
+    }
+}
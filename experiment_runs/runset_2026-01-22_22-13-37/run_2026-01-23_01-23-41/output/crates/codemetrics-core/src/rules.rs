@@ -0,0 +1,515 @@
+//! Catalog of issue rules with stable identifiers
+//!
+//! Rule IDs never change meaning once published, so they can be referenced
+//! from config files, suppressions and CI scripts. IDs are grouped by
+//! category: `CM01xx` complexity, `CM02xx` maintainability, `CM03xx` security,
+//! `CM04xx` concurrency.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::clone_detector;
+use crate::go_concurrency;
+use crate::core::{CodeIssue, IssueSeverity};
+use crate::stack::DetectedFramework;
+use crate::thresholds::Thresholds;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Rule {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub category: &'static str,
+    pub description: &'static str,
+    pub enabled_by_default: bool,
+    pub docs: RuleDocs,
+}
+
+/// The longer explanation behind a rule, for `rules explain` and report appendices
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleDocs {
+    pub why: &'static str,
+    pub fix: &'static str,
+    pub references: &'static [&'static str],
+}
+
+pub const HIGH_CYCLOMATIC_COMPLEXITY: &str = "CM0101";
+pub const HIGH_FILE_COMPLEXITY: &str = "CM0102";
+pub const DEEP_NESTING: &str = "CM0103";
+pub const CONCURRENCY_HOTSPOT: &str = "CM0104";
+pub const TOO_MANY_PARAMETERS: &str = "CM0201";
+pub const LONG_FUNCTION: &str = "CM0202";
+pub const DUPLICATED_CODE: &str = "CM0203";
+pub const GENERIC_API_COMPLEXITY: &str = "CM0204";
+pub const RESOURCE_LEAK: &str = "CM0205";
+pub const SECURITY_PATTERN: &str = "CM0301";
+pub const HTML_INJECTION: &str = "CM0302";
+pub const SQL_STRING_INTERPOLATION: &str = "CM0303";
+pub const UNSAFE_BLOCK: &str = "CM0304";
+pub const UNSAFE_DESERIALIZATION: &str = "CM0305";
+pub const TAINTED_INPUT: &str = "CM0306";
+pub const UNJUSTIFIED_UNSAFE: &str = "CM0307";
+pub const WEAK_HASH: &str = "CM0308";
+pub const ECB_MODE: &str = "CM0309";
+pub const INSECURE_RANDOMNESS: &str = "CM0310";
+pub const HARDCODED_IV: &str = "CM0311";
+pub const INCONSISTENT_STATIC_GUARD: &str = "CM0401";
+pub const AWAIT_IN_LOOP: &str = "CM0402";
+pub const LOCK_ACROSS_AWAIT: &str = "CM0403";
+
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: HIGH_CYCLOMATIC_COMPLEXITY,
+        name: "high-cyclomatic-complexity",
+        category: "complexity",
+        description: "Function has more independent paths than the function complexity threshold",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "Every independent path through a function is a case to understand, review and test. Past a dozen or so, readers stop holding the whole function in their head and defects concentrate there.",
+            fix: "Extract cohesive branches into named helper functions, replace condition chains with lookup tables or polymorphism, and return early instead of nesting else-branches.",
+            references: &["McCabe, \"A Complexity Measure\", IEEE Transactions on Software Engineering, 1976", "https://refactoring.com/catalog/extractFunction.html"],
+        },
+    },
+    Rule {
+        id: HIGH_FILE_COMPLEXITY,
+        name: "high-file-complexity",
+        category: "complexity",
+        description: "Summed complexity of a file's functions exceeds the file complexity threshold",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "A file carrying a large share of the codebase's decision logic usually mixes several responsibilities, so unrelated changes collide in it and its tests grow slow and brittle.",
+            fix: "Split the file along its responsibilities, moving groups of functions that change together into their own module.",
+            references: &["https://refactoring.com/catalog/moveFunction.html", "https://refactoring.guru/smells/large-class"],
+        },
+    },
+    Rule {
+        id: DEEP_NESTING,
+        name: "deep-nesting",
+        category: "complexity",
+        description: "Function nests blocks deeper than the nesting threshold",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "Each level of nesting adds a condition the reader must keep in mind to know when a line runs. Deeply nested code hides the main path and makes missing cases hard to spot.",
+            fix: "Use guard clauses to handle edge cases first and return, extract inner loops or blocks into functions, and flatten conditions with early `continue`.",
+            references: &["https://refactoring.com/catalog/replaceNestedConditionalWithGuardClauses.html"],
+        },
+    },
+    Rule {
+        id: CONCURRENCY_HOTSPOT,
+        name: "concurrency-hotspot",
+        category: "complexity",
+        description: "Go function launches goroutines or uses channels heavily and is also highly complex",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "Every goroutine and channel operation adds interleavings on top of the function's own branches. Deadlocks, goroutine leaks and races hide where both are high, and tests rarely reach those paths.",
+            fix: "Keep the coordination (goroutines, channels, select) in a small function and move the branching logic into plain functions it calls; reach for errgroup or a worker pool instead of hand-rolled plumbing.",
+            references: &["https://go.dev/doc/effective_go#concurrency", "https://go.dev/blog/pipelines"],
+        },
+    },
+    Rule {
+        id: TOO_MANY_PARAMETERS,
+        name: "too-many-parameters",
+        category: "maintainability",
+        description: "Function takes more parameters than the parameter threshold",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "Long parameter lists are easy to call with arguments in the wrong order and usually signal that a function does several jobs or that related values travel without a type.",
+            fix: "Group values that travel together into a struct or parameter object, pass the object the values come from, or split the function.",
+            references: &["https://refactoring.com/catalog/introduceParameterObject.html", "https://refactoring.guru/smells/long-parameter-list"],
+        },
+    },
+    Rule {
+        id: LONG_FUNCTION,
+        name: "long-function",
+        category: "maintainability",
+        description: "Function body is longer than the function length threshold",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "Long functions mix levels of abstraction, so a reader has to work out which part matters for the change at hand, and their pieces cannot be tested or reused on their own.",
+            fix: "Extract steps into well-named functions until the body reads as a summary of what it does.",
+            references: &["https://refactoring.guru/smells/long-method", "https://refactoring.com/catalog/extractFunction.html"],
+        },
+    },
+    Rule {
+        id: DUPLICATED_CODE,
+        name: "duplicated-code",
+        category: "maintainability",
+        description: "Block of code is repeated elsewhere, possibly with renamed identifiers or changed literals",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "A fix applied to one copy of repeated code is easily missed in the others, so copies drift apart and bugs come back.",
+            fix: "Extract the shared block into one function or module and call it from every copy, parameterizing what differs.",
+            references: &["https://refactoring.guru/smells/duplicate-code"],
+        },
+    },
+    Rule {
+        id: GENERIC_API_COMPLEXITY,
+        name: "generic-api-complexity",
+        category: "maintainability",
+        description: "Rust signature carries more trait bounds than the generic bounds threshold",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "Every bound is a constraint callers must satisfy and decode from rustdoc, and bounds spread to every generic caller. Heavily bounded signatures make a library hard to adopt and hard to change without breaking users.",
+            fix: "Bundle bounds that always travel together into a trait with a blanket impl, take `impl Trait` or `&dyn Trait` where monomorphization is not needed, or move bounds from the signature onto the few methods that need them.",
+            references: &["https://rust-lang.github.io/api-guidelines/flexibility.html", "https://doc.rust-lang.org/reference/trait-bounds.html"],
+        },
+    },
+    Rule {
+        id: RESOURCE_LEAK,
+        name: "resource-leak",
+        category: "maintainability",
+        description: "File, socket or lock is opened in a function that never closes or releases it",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "A handle left for the garbage collector stays open for as long as it lives, and not at all if an exception skips the cleanup. Long-running processes run out of file descriptors, writes are lost unflushed, and a lock that is never released deadlocks the next caller.",
+            fix: "Tie the resource to a scope that closes it on every path: Python `with`, a Ruby block, or `try`/`finally` in JavaScript. Hand it to the caller explicitly when it has to outlive the function.",
+            references: &["https://docs.python.org/3/reference/compound_stmts.html#the-with-statement", "https://nodejs.org/api/fs.html#fsclosefd-callback"],
+        },
+    },
+    Rule {
+        id: SECURITY_PATTERN,
+        name: "security-pattern",
+        category: "security",
+        description: "Code matches a known insecure pattern for its language",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "The call is a common source of vulnerabilities such as injection or unsafe deserialization when its input can be influenced by a user.",
+            fix: "Check where the input comes from; prefer parameterized APIs, avoid evaluating strings as code, and validate or escape untrusted data.",
+            references: &["https://owasp.org/www-project-top-ten/", "https://cwe.mitre.org/"],
+        },
+    },
+    Rule {
+        id: HTML_INJECTION,
+        name: "html-injection",
+        category: "security",
+        description: "Markup is inserted into the page through innerHTML, outerHTML, document.write or insertAdjacentHTML",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "Anything in the inserted string is parsed as HTML, so a value that carries user input can run script in the page (cross-site scripting).",
+            fix: "Set textContent or build elements with the DOM API; when markup is really needed, sanitize it first.",
+            references: &["https://cwe.mitre.org/data/definitions/79.html", "https://cheatsheetseries.owasp.org/cheatsheets/DOM_based_XSS_Prevention_Cheat_Sheet.html"],
+        },
+    },
+    Rule {
+        id: SQL_STRING_INTERPOLATION,
+        name: "sql-string-interpolation",
+        category: "security",
+        description: "Query text passed to a database call is built by interpolation, concatenation or formatting",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "A value spliced into SQL can change the statement itself, letting whoever controls it read or modify data they should not reach (SQL injection).",
+            fix: "Keep the SQL constant and pass values as bound parameters through the driver's placeholders.",
+            references: &["https://cwe.mitre.org/data/definitions/89.html", "https://cheatsheetseries.owasp.org/cheatsheets/Query_Parameterization_Cheat_Sheet.html"],
+        },
+    },
+    Rule {
+        id: UNSAFE_BLOCK,
+        name: "unsafe-block",
+        category: "security",
+        description: "Rust `unsafe` block",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "Inside an unsafe block the compiler no longer checks memory safety, so a wrong assumption becomes undefined behavior rather than a compile error.",
+            fix: "Prefer a safe API; otherwise keep the block minimal, wrap it in a safe function and document its invariants in a `// SAFETY:` comment.",
+            references: &["https://doc.rust-lang.org/nomicon/", "https://rust-lang.github.io/rust-clippy/master/index.html#undocumented_unsafe_blocks"],
+        },
+    },
+    Rule {
+        id: UNSAFE_DESERIALIZATION,
+        name: "unsafe-deserialization",
+        category: "security",
+        description: "Data is loaded with a deserializer that can construct arbitrary objects (pickle, marshal, Marshal.load, YAML.unsafe_load)",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "These formats can instantiate any class and run code while loading, so deserializing attacker-controlled bytes amounts to running the attacker's code.",
+            fix: "Use a data-only format such as JSON or safe YAML loading for untrusted input, or authenticate the payload before loading it.",
+            references: &["https://cwe.mitre.org/data/definitions/502.html", "https://docs.python.org/3/library/pickle.html"],
+        },
+    },
+    Rule {
+        id: TAINTED_INPUT,
+        name: "tainted-input",
+        category: "security",
+        description: "A value that looks like user input reaches an SQL query, HTML insertion or shell command in the same function",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "Request parameters, cookies, command-line arguments and similar input are controlled by whoever sends them. Reaching a query, the page or a shell unescaped, they can change what runs (SQL injection, cross-site scripting, command injection).",
+            fix: "Bind the value as a query parameter, escape it for HTML, or pass it as a separate argument without a shell. Converting it first (to a number, through an allow-list) also ends the flow. The finding names the line the input came from.",
+            references: &["https://cwe.mitre.org/data/definitions/20.html", "https://cwe.mitre.org/data/definitions/78.html", "https://owasp.org/Top10/A03_2021-Injection/"],
+        },
+    },
+    Rule {
+        id: UNJUSTIFIED_UNSAFE,
+        name: "unjustified-unsafe",
+        category: "security",
+        description: "Rust `unsafe` block without a `// SAFETY:` comment above it or its statement",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "The soundness of an unsafe block rests on invariants the compiler cannot see. Unwritten, they are lost on the next refactor, and reviewers cannot check the block against them.",
+            fix: "Add a `// SAFETY:` comment directly above the block, or above the statement containing it, naming each invariant and why it holds here. Clippy's `undocumented_unsafe_blocks` lint enforces the same convention at compile time.",
+            references: &["https://rust-lang.github.io/rust-clippy/master/index.html#undocumented_unsafe_blocks", "https://std-dev-guide.rust-lang.org/policy/safety-comments.html"],
+        },
+    },
+    Rule {
+        id: WEAK_HASH,
+        name: "weak-hash",
+        category: "security",
+        description: "MD5 or SHA-1, which are broken for passwords, signatures and other security uses",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "MD5 and SHA-1 collisions can be produced on commodity hardware, and both are fast enough to test billions of password guesses per second. Checksums are a fine use; anything an attacker could forge or brute-force is not.",
+            fix: "Hash passwords with a slow, salted function (argon2, scrypt or bcrypt) and use SHA-256 or better (or HMAC) for integrity and signatures. Suppress the finding where the hash is a plain checksum.",
+            references: &["https://cwe.mitre.org/data/definitions/328.html", "https://cwe.mitre.org/data/definitions/916.html", "https://cheatsheetseries.owasp.org/cheatsheets/Password_Storage_Cheat_Sheet.html"],
+        },
+    },
+    Rule {
+        id: ECB_MODE,
+        name: "ecb-mode",
+        category: "security",
+        description: "Block cipher in ECB mode",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "ECB encrypts equal plaintext blocks to equal ciphertext blocks, so the structure of the data shows through and blocks can be reordered or replayed without the key.",
+            fix: "Use an authenticated mode such as AES-GCM or ChaCha20-Poly1305 with a fresh nonce per message, ideally through a high-level library (libsodium, Fernet, `aes-gcm`).",
+            references: &["https://cwe.mitre.org/data/definitions/327.html", "https://cheatsheetseries.owasp.org/cheatsheets/Cryptographic_Storage_Cheat_Sheet.html"],
+        },
+    },
+    Rule {
+        id: INSECURE_RANDOMNESS,
+        name: "insecure-randomness",
+        category: "security",
+        description: "Non-cryptographic random generator feeds a token, password, nonce or other secret",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "General-purpose generators (`Math.random`, Python's `random`, Ruby's `rand`) are seeded predictably or have state that a few outputs reveal, so tokens made with them can be guessed.",
+            fix: "Generate secrets with the platform's secure generator: `crypto.randomBytes` or `crypto.getRandomValues`, Python's `secrets`, Ruby's `SecureRandom`, Rust's `OsRng`.",
+            references: &["https://cwe.mitre.org/data/definitions/338.html", "https://docs.python.org/3/library/secrets.html", "https://developer.mozilla.org/en-US/docs/Web/API/Crypto/getRandomValues"],
+        },
+    },
+    Rule {
+        id: HARDCODED_IV,
+        name: "hardcoded-iv",
+        category: "security",
+        description: "Cipher initialized with a constant IV or nonce",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "An IV or nonce reused across messages makes CBC leak which messages share a prefix, and lets CTR and GCM ciphertexts be XORed together or forged.",
+            fix: "Generate a random IV or nonce for every message with a secure generator and store it alongside the ciphertext; it does not need to be secret.",
+            references: &["https://cwe.mitre.org/data/definitions/329.html", "https://cwe.mitre.org/data/definitions/1204.html"],
+        },
+    },
+    Rule {
+        id: INCONSISTENT_STATIC_GUARD,
+        name: "inconsistently-guarded-static",
+        category: "concurrency",
+        description: "Rust `static mut` is accessed under a lock in one place and without one in another",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "A lock only protects data when every access takes it. One unguarded read or write of a `static mut` races with the guarded ones, which is undefined behavior and shows up as torn or lost updates under load.",
+            fix: "Take the same lock around every access, or better, make the static itself safe to share: an atomic, a `Mutex<T>` or `RwLock<T>` holding the value, or a `OnceLock` for one-time initialization.",
+            references: &["https://doc.rust-lang.org/reference/items/static-items.html#mutable-statics", "https://doc.rust-lang.org/edition-guide/rust-2024/static-mut-references.html"],
+        },
+    },
+    Rule {
+        id: AWAIT_IN_LOOP,
+        name: "await-in-loop",
+        category: "concurrency",
+        description: "`await` inside a loop over a collection runs the iterations one after another",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "Each iteration waits for the previous one's I/O to finish, so the loop takes the sum of all the latencies instead of roughly the longest. Over a large collection that is the difference between milliseconds and minutes.",
+            fix: "Start the operations first and await them together with `Promise.all` or `asyncio.gather`. For large collections, bound the concurrency with batches, a pool or a semaphore. Keep the loop when each step really depends on the last.",
+            references: &["https://eslint.org/docs/latest/rules/no-await-in-loop", "https://docs.python.org/3/library/asyncio-task.html#asyncio.gather"],
+        },
+    },
+    Rule {
+        id: LOCK_ACROSS_AWAIT,
+        name: "lock-held-across-await",
+        category: "concurrency",
+        description: "Blocking mutex or lock guard is still held at an `await`",
+        enabled_by_default: true,
+        docs: RuleDocs {
+            why: "While the task is suspended, the lock stays taken. Any other task that needs it blocks its whole executor thread, which can stall the runtime or deadlock when the awaited work needs that thread.",
+            fix: "Copy what you need out of the guarded data and drop the guard before awaiting, or switch to an async-aware lock (`tokio::sync::Mutex`, `asyncio.Lock`) when it really must be held across the await.",
+            references: &["https://rust-lang.github.io/rust-clippy/master/index.html#await_holding_lock", "https://tokio.rs/tokio/tutorial/shared-state#holding-a-mutexguard-across-an-await"],
+        },
+    },
+];
+
+/// Rules switched on automatically when a framework is detected
+pub struct RulePack {
+    pub frameworks: &'static [&'static str],
+    pub rules: &'static [&'static str],
+}
+
+pub const RULE_PACKS: &[RulePack] = &[
+    // Request handlers are where injection-prone patterns matter most
+    RulePack {
+        frameworks: &["Django", "Flask", "FastAPI", "Express", "Spring", "actix-web", "Axum", "Rocket", "Gin", "Echo"],
+        rules: &[SECURITY_PATTERN, HTML_INJECTION, SQL_STRING_INTERPOLATION, UNSAFE_DESERIALIZATION, TAINTED_INPUT],
+    },
+];
+
+impl Rule {
+    /// Human-readable default threshold, if the rule has one
+    pub fn default_setting(&self, thresholds: &Thresholds) -> Option<String> {
+        match self.id {
+            HIGH_CYCLOMATIC_COMPLEXITY => Some(format!(
+                "warn ≥{}, error ≥{}",
+                thresholds.function_complexity_warn, thresholds.function_complexity_error
+            )),
+            HIGH_FILE_COMPLEXITY => Some(format!(
+                "warn ≥{}, error ≥{}",
+                thresholds.file_complexity_warn, thresholds.file_complexity_error
+            )),
+            DEEP_NESTING => Some(format!("depth >{}", thresholds.max_nesting_depth)),
+            CONCURRENCY_HOTSPOT => Some(format!(
+                "≥{} goroutines/channel operations/selects and complexity ≥{}",
+                go_concurrency::HEAVY_CONCURRENCY, thresholds.function_complexity_warn
+            )),
+            TOO_MANY_PARAMETERS => Some(format!(">{} parameters", thresholds.max_parameters)),
+            LONG_FUNCTION => Some(format!(">{} lines", thresholds.max_function_lines)),
+            GENERIC_API_COMPLEXITY => Some(format!(">{} trait bounds per signature", thresholds.max_generic_bounds)),
+            DUPLICATED_CODE => Some(format!(
+                "≥{} lines and ≥{} tokens",
+                clone_detector::DEFAULT_MIN_LINES, clone_detector::DEFAULT_MIN_TOKENS
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Look a rule up by ID (`CM0101`) or name (`high-cyclomatic-complexity`)
+pub fn find_rule(key: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.id.eq_ignore_ascii_case(key) || rule.name == key)
+}
+
+/// Which rules are active for an analysis run, and any changed severities
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    enabled: BTreeSet<&'static str>,
+    severities: BTreeMap<&'static str, IssueSeverity>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self {
+            enabled: RULES.iter().filter(|rule| rule.enabled_by_default).map(|rule| rule.id).collect(),
+            severities: BTreeMap::new(),
+        }
+    }
+}
+
+impl RuleSet {
+    /// Start from the defaults and apply explicit enables and disables by ID or name
+    pub fn from_overrides(enable: &[String], disable: &[String]) -> Result<Self> {
+        Self::for_stack(&[], enable, disable)
+    }
+
+    fn apply_overrides(&mut self, enable: &[String], disable: &[String]) -> Result<()> {
+        for key in enable {
+            self.enabled.insert(lookup(key)?.id);
+        }
+        for key in disable {
+            self.enabled.remove(lookup(key)?.id);
+        }
+        Ok(())
+    }
+
+    /// Like `from_overrides`, after enabling the packs of every detected framework
+    ///
+    /// Explicit overrides are applied last, so `--disable-rules` still wins.
+    pub fn for_stack(stack: &[DetectedFramework], enable: &[String], disable: &[String]) -> Result<Self> {
+        let mut rules = Self::default();
+        for pack in RULE_PACKS {
+            if stack.iter().any(|framework| pack.frameworks.contains(&framework.name)) {
+                rules.enabled.extend(pack.rules);
+            }
+        }
+        rules.apply_overrides(enable, disable)?;
+        Ok(rules)
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        self.enabled.contains(id)
+    }
+
+    /// Report findings of some rules at another severity, from `rule=level` entries such as `long-function=warning`
+    ///
+    /// Later entries for the same rule win, so command-line flags can follow config file values.
+    pub fn override_severities(&mut self, entries: &[String]) -> Result<()> {
+        for entry in entries {
+            let (key, level) = entry.split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid rule severity '{}', expected RULE=LEVEL", entry))?;
+            let severity = IssueSeverity::from_name(level)
+                .ok_or_else(|| anyhow::anyhow!("Unknown severity '{}' (expected info, warning, error or critical)", level))?;
+            self.severities.insert(lookup(key.trim())?.id, severity);
+        }
+        Ok(())
+    }
+
+    /// The severity configured for `id`, if it was changed from the rule's own
+    pub fn severity(&self, id: &str) -> Option<&IssueSeverity> {
+        self.severities.get(id)
+    }
+
+    /// Give each issue the severity configured for its rule, where one was
+    pub fn apply_severities<'a>(&self, issues: impl IntoIterator<Item = &'a mut CodeIssue>) {
+        for issue in issues {
+            if let Some(severity) = self.severity(&issue.rule_id) {
+                issue.severity = severity.clone();
+            }
+        }
+    }
+}
+
+fn lookup(key: &str) -> Result<&'static Rule> {
+    find_rule(key).ok_or_else(|| anyhow::anyhow!("Unknown rule '{}' (run `rules` to list them)", key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_overrides_by_id_or_name() {
+        let mut rules = RuleSet::default();
+        rules.override_severities(&["long-function=warning".to_string(), "CM0201=info".to_string(), "CM0201=error".to_string()]).unwrap();
+        assert_eq!(rules.severity(LONG_FUNCTION), Some(&IssueSeverity::Warning));
+        assert_eq!(rules.severity(TOO_MANY_PARAMETERS), Some(&IssueSeverity::Error));
+        assert_eq!(rules.severity(DEEP_NESTING), None);
+
+        assert!(rules.override_severities(&["long-function".to_string()]).is_err());
+        assert!(rules.override_severities(&["long-function=loud".to_string()]).is_err());
+        assert!(rules.override_severities(&["no-such-rule=info".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_rule_ids_are_unique_and_well_formed() {
+        let ids: BTreeSet<_> = RULES.iter().map(|rule| rule.id).collect();
+        assert_eq!(ids.len(), RULES.len());
+        assert!(RULES.iter().all(|rule| rule.id.len() == 6 && rule.id.starts_with("CM")));
+    }
+
+    #[test]
+    fn test_every_rule_is_documented() {
+        for rule in RULES {
+            assert!(!rule.docs.why.is_empty() && !rule.docs.fix.is_empty(), "{} lacks docs", rule.id);
+            assert!(!rule.docs.references.is_empty(), "{} lacks references", rule.id);
+        }
+    }
+
+    #[test]
+    fn test_overrides_by_id_and_name() {
+        let rules = RuleSet::from_overrides(&[], &["deep-nesting".to_string(), "cm0201".to_string()]).unwrap();
+        assert!(!rules.is_enabled(DEEP_NESTING));
+        assert!(!rules.is_enabled(TOO_MANY_PARAMETERS));
+        assert!(rules.is_enabled(HIGH_CYCLOMATIC_COMPLEXITY));
+        assert!(RuleSet::from_overrides(&["CM9999".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn test_rule_packs_reference_known_rules() {
+        assert!(RULE_PACKS.iter().flat_map(|pack| pack.rules).all(|id| find_rule(id).is_some()));
+    }
+}
@@ -0,0 +1,324 @@
+//! Trait-impl surface and generic complexity of Rust code
+//!
+//! For each type this counts its `impl` blocks and the traits it implements:
+//! the surface a reader assembles from across the crate to learn what the type
+//! can do. For each function signature it counts type parameters, trait bounds
+//! (in `<T: A + B>`, `where` clauses and `impl A + B` argument and return
+//! types) and the distinct named lifetimes it mentions; `'_` and `'static` are
+//! not counted.
+//!
+//! A signature with more trait bounds than `max_generic_bounds` is reported
+//! under `rules::GENERIC_API_COMPLEXITY`: each bound is a constraint callers
+//! must satisfy and one more line of rustdoc they must decode.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tree_sitter::Node;
+
+use crate::ast_analyzer::walk_descendants;
+use crate::type_metrics::shared_components;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImplSurface {
+    pub name: String,
+    /// File declaring the type, or holding its first `impl` block when it is declared elsewhere
+    pub file_path: String,
+    pub line: u32,
+    pub impl_blocks: u32,
+    /// `impl Type` blocks, as opposed to `impl Trait for Type`
+    pub inherent_impls: u32,
+    /// Traits implemented, by last path segment
+    pub traits: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenericSignature {
+    /// `Type::method` inside `impl` blocks, `Trait::method` inside traits
+    pub name: String,
+    pub file_path: String,
+    pub line: u32,
+    pub type_parameters: u32,
+    pub trait_bounds: u32,
+    pub lifetimes: u32,
+    /// More trait bounds than the limit, when the rule is enabled
+    pub too_generic: bool,
+}
+
+/// Impl surface and generic signatures across the analyzed Rust files
+#[derive(Debug, Default)]
+pub struct RustApi {
+    /// Types with at least one `impl` block, most blocks first
+    pub types: Vec<ImplSurface>,
+    /// Signatures with a trait bound or a named lifetime, most bounds first
+    pub signatures: Vec<GenericSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Declaration {
+    name: String,
+    file_path: String,
+    line: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Impl {
+    type_name: String,
+    trait_name: Option<String>,
+    file_path: String,
+    line: u32,
+}
+
+/// The types, `impl` blocks and generic signatures one Rust file declares, see `extract`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Extracted {
+    declarations: Vec<Declaration>,
+    impls: Vec<Impl>,
+    signatures: Vec<GenericSignature>,
+}
+
+/// Impl surface and generic signatures across every file's `extract`
+pub fn measure(files: impl IntoIterator<Item = Extracted>) -> RustApi {
+    let mut declarations = Vec::new();
+    let mut impls = Vec::new();
+    let mut signatures = Vec::new();
+    for file in files {
+        declarations.extend(file.declarations);
+        impls.extend(file.impls);
+        signatures.extend(file.signatures);
+    }
+
+    // Keyed by name and declaring file, so same-named types in different modules stay apart
+    let mut surfaces: BTreeMap<(String, Option<String>), ImplSurface> = BTreeMap::new();
+    for implementation in impls {
+        let owner = declarations.iter()
+            .filter(|declaration| declaration.name == implementation.type_name)
+            .max_by_key(|declaration| shared_components(&declaration.file_path, &implementation.file_path));
+        let key = (implementation.type_name.clone(), owner.map(|declaration| declaration.file_path.clone()));
+        let surface = surfaces.entry(key).or_insert_with(|| ImplSurface {
+            name: implementation.type_name.clone(),
+            file_path: owner.map_or_else(|| implementation.file_path.clone(), |declaration| declaration.file_path.clone()),
+            line: owner.map_or(implementation.line, |declaration| declaration.line),
+            impl_blocks: 0,
+            inherent_impls: 0,
+            traits: Vec::new(),
+        });
+        surface.impl_blocks += 1;
+        match implementation.trait_name {
+            Some(trait_name) => surface.traits.push(trait_name),
+            None => surface.inherent_impls += 1,
+        }
+    }
+
+    let mut types: Vec<ImplSurface> = surfaces.into_values()
+        .map(|mut surface| {
+            surface.traits.sort();
+            surface.traits.dedup();
+            surface
+        })
+        .collect();
+    types.sort_by(|a, b| b.impl_blocks.cmp(&a.impl_blocks)
+        .then(b.traits.len().cmp(&a.traits.len()))
+        .then_with(|| (&a.file_path, a.line).cmp(&(&b.file_path, b.line))));
+    signatures.sort_by(|a, b| (b.trait_bounds, b.lifetimes).cmp(&(a.trait_bounds, a.lifetimes))
+        .then_with(|| (&a.file_path, a.line).cmp(&(&b.file_path, b.line))));
+    RustApi { types, signatures }
+}
+
+/// What one Rust file under `root` declares
+///
+/// `max_bounds` is the trait bound limit for its signatures, or `None` when
+/// over-generic signatures are not reported.
+pub fn extract(path: &str, source: &str, root: Node, max_bounds: Option<u32>) -> Extracted {
+    let mut out = Extracted::default();
+    let line = |node: Node| node.start_position().row as u32 + 1;
+    walk_descendants(root, |node, _| {
+        match node.kind() {
+            "struct_item" | "enum_item" | "union_item" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    out.declarations.push(Declaration {
+                        name: source[name.byte_range()].to_string(),
+                        file_path: path.to_string(),
+                        line: line(node),
+                    });
+                }
+            }
+            "impl_item" => {
+                if let Some(self_type) = node.child_by_field_name("type").map(|type_node| type_name(type_node, source)) {
+                    out.impls.push(Impl {
+                        type_name: self_type,
+                        trait_name: node.child_by_field_name("trait").map(|trait_node| type_name(trait_node, source)),
+                        file_path: path.to_string(),
+                        line: line(node),
+                    });
+                }
+            }
+            "function_item" | "function_signature_item" => {
+                if let Some(mut signature) = signature(node, source) {
+                    signature.too_generic = max_bounds.is_some_and(|limit| signature.trait_bounds > limit);
+                    signature.file_path = path.to_string();
+                    out.signatures.push(signature);
+                }
+            }
+            _ => {}
+        }
+        true
+    });
+    out
+}
+
+/// Generics of the signature of `node`, if it has any trait bound or named lifetime
+fn signature(node: Node, source: &str) -> Option<GenericSignature> {
+    let mut type_parameters = 0;
+    let mut trait_bounds = 0;
+    let mut lifetimes = BTreeSet::new();
+
+    let body = node.child_by_field_name("body");
+    let mut cursor = node.walk();
+    for part in node.children(&mut cursor).filter(|part| Some(*part) != body) {
+        walk_descendants(part, |node, _| {
+            match node.kind() {
+                "type_parameters" => {
+                    let mut cursor = node.walk();
+                    type_parameters += node.named_children(&mut cursor)
+                        .filter(|parameter| match parameter.kind() {
+                            "type_parameter" | "type_identifier" | "optional_type_parameter" => true,
+                            // `'a: 'b` constrains a lifetime, not a type
+                            "constrained_type_parameter" => parameter.child_by_field_name("left").is_some_and(|left| left.kind() != "lifetime"),
+                            _ => false,
+                        })
+                        .count() as u32;
+                }
+                "trait_bounds" => trait_bounds += bound_count(node),
+                "abstract_type" => trait_bounds += node.child_by_field_name("trait").map_or(0, bound_count),
+                // `impl A + B` may parse as `(impl A) + B`; `impl A` is counted above
+                "bounded_type" if starts_with_impl_trait(node) && node.parent().is_some_and(|parent| parent.kind() != "bounded_type") => {
+                    trait_bounds += bound_count(node) - 1;
+                }
+                "lifetime" => {
+                    let lifetime = &source[node.byte_range()];
+                    if lifetime != "'_" && lifetime != "'static" {
+                        lifetimes.insert(lifetime);
+                    }
+                }
+                _ => {}
+            }
+            true
+        });
+    }
+    if trait_bounds == 0 && lifetimes.is_empty() {
+        return None;
+    }
+
+    let name = &source[node.child_by_field_name("name")?.byte_range()];
+    let container = node.parent()
+        .filter(|parent| parent.kind() == "declaration_list")
+        .and_then(|list| list.parent());
+    let name = match container {
+        Some(container) if container.kind() == "impl_item" => match container.child_by_field_name("type") {
+            Some(type_node) => format!("{}::{}", type_name(type_node, source), name),
+            None => name.to_string(),
+        },
+        Some(container) if container.kind() == "trait_item" => match container.child_by_field_name("name") {
+            Some(trait_name) => format!("{}::{}", &source[trait_name.byte_range()], name),
+            None => name.to_string(),
+        },
+        _ => name.to_string(),
+    };
+    Some(GenericSignature {
+        name,
+        file_path: String::new(),
+        line: node.start_position().row as u32 + 1,
+        type_parameters,
+        trait_bounds,
+        lifetimes: lifetimes.len() as u32,
+        too_generic: false,
+    })
+}
+
+/// Traits in a `: A + B + 'a` list or an `A + B` bounded type, lifetimes aside
+fn bound_count(node: Node) -> u32 {
+    match node.kind() {
+        "trait_bounds" | "bounded_type" => {
+            (0..node.named_child_count())
+                .filter_map(|index| node.named_child(index))
+                .filter(|bound| bound.kind() != "lifetime")
+                .map(bound_count)
+                .sum()
+        }
+        _ => 1,
+    }
+}
+
+fn starts_with_impl_trait(node: Node) -> bool {
+    match node.named_child(0) {
+        Some(first) if first.kind() == "bounded_type" => starts_with_impl_trait(first),
+        Some(first) => first.kind() == "abstract_type",
+        None => false,
+    }
+}
+
+/// `Parser` for `crate::parse::Parser<'a, T>`
+fn type_name(node: Node, source: &str) -> String {
+    let node = match node.kind() {
+        "generic_type" => node.child_by_field_name("type").unwrap_or(node),
+        _ => node,
+    };
+    let text = &source[node.byte_range()];
+    text.rsplit("::").next().unwrap_or(text).trim_start_matches('&').trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::parsers::{pool, SupportedLanguage};
+
+    #[test]
+    fn test_impl_surface_and_signature_generics() -> Result<()> {
+        let model = "\
+pub struct Store<T> { items: Vec<T> }
+
+impl<T> Store<T> {
+    pub fn get<'a>(&'a self, index: usize) -> Option<&'a T> { self.items.get(index) }
+}
+
+impl<T: Clone> Clone for Store<T> {
+    fn clone(&self) -> Self { Store { items: self.items.clone() } }
+}
+";
+        let sync = "\
+impl<T> std::fmt::Debug for crate::model::Store<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { Ok(()) }
+}
+
+pub fn sync<S, F>(store: S, on_done: F) -> impl Iterator<Item = u8> + Send
+where
+    S: Clone + Send + Sync + 'static,
+    F: FnOnce() + Send,
+{
+    std::iter::empty()
+}
+";
+        let extract_file = |path, source| -> Result<Extracted> {
+            Ok(extract(path, source, pool::parse(SupportedLanguage::Rust, source)?.root_node(), Some(4)))
+        };
+        let api = measure([extract_file("src/model.rs", model)?, extract_file("src/sync.rs", sync)?]);
+
+        assert_eq!(api.types.len(), 1);
+        let store = &api.types[0];
+        assert_eq!((store.file_path.as_str(), store.line), ("src/model.rs", 1));
+        assert_eq!((store.impl_blocks, store.inherent_impls), (3, 1));
+        assert_eq!(store.traits, vec!["Clone", "Debug"]);
+
+        // Three bounds on S, two on F, two in the return type
+        let sync = &api.signatures[0];
+        assert_eq!(sync.name, "sync");
+        assert_eq!((sync.type_parameters, sync.trait_bounds, sync.lifetimes), (2, 7, 0));
+        assert!(sync.too_generic);
+        let get = api.signatures.iter().find(|signature| signature.name == "Store::get").unwrap();
+        assert_eq!((get.trait_bounds, get.lifetimes, get.too_generic), (0, 1, false));
+        // `Formatter<'_>` is elided, so `fmt` has nothing to report
+        assert_eq!(api.signatures.len(), 2);
+        Ok(())
+    }
+}
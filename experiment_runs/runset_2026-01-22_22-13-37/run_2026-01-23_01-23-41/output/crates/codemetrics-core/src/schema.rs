@@ -0,0 +1,60 @@
+//! The versioned contract of `analyze --format json`
+//!
+//! Every results file carries `schema_version`, MAJOR.MINOR. A minor bump only
+//! adds optional fields, so readers built for 1.0 keep working on 1.3; fields
+//! are removed, renamed or retyped only with a major bump. The JSON Schema for
+//! the current version is embedded here and printed by `codemetrics schema`.
+
+use anyhow::Result;
+use serde_json::Value;
+
+/// Version of the results layout this build writes
+pub const SCHEMA_VERSION: &str = "1.2";
+
+/// JSON Schema (draft 2020-12) of the results this build writes
+pub const RESULTS_SCHEMA: &str = include_str!("../schema/results.schema.json");
+
+/// Fail unless `results` has the major version this build reads
+pub fn check_version(results: &Value) -> Result<()> {
+    // Files written before the field existed follow 1.0
+    let found = results.get("schema_version").and_then(Value::as_str).unwrap_or("1.0");
+    let (major, _) = parse(found)?;
+    let (current_major, _) = parse(SCHEMA_VERSION)?;
+    if major != current_major {
+        anyhow::bail!("Results use schema version {}, this build reads {}.x", found, current_major);
+    }
+    Ok(())
+}
+
+fn parse(version: &str) -> Result<(u32, u32)> {
+    let parsed = version.split_once('.')
+        .and_then(|(major, minor)| Some((major.parse().ok()?, minor.parse().ok()?)));
+    parsed.ok_or_else(|| anyhow::anyhow!("Invalid schema version '{}': expected MAJOR.MINOR", version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::CodeAnalyzer;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_describes_serialized_results() {
+        let schema: Value = serde_json::from_str(RESULTS_SCHEMA).unwrap();
+        assert_eq!(schema["properties"]["schema_version"]["const"], SCHEMA_VERSION);
+
+        let results = serde_json::to_value(CodeAnalyzer::new().aggregate_results(Vec::new()).unwrap()).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for key in results.as_object().unwrap().keys() {
+            assert!(properties.contains_key(key), "`{}` is missing from the schema", key);
+        }
+        for required in schema["required"].as_array().unwrap() {
+            assert!(results.get(required.as_str().unwrap()).is_some(), "{} is not serialized", required);
+        }
+
+        assert!(check_version(&results).is_ok());
+        assert!(check_version(&json!({})).is_ok());
+        assert!(check_version(&json!({ "schema_version": "1.9" })).is_ok());
+        assert!(check_version(&json!({ "schema_version": "2.0" })).is_err());
+    }
+}
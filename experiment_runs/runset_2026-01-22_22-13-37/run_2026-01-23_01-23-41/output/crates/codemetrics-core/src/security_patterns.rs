@@ -0,0 +1,382 @@
+//! Per-language table of insecure code patterns
+//!
+//! Each row is a tree-sitter query that captures `@security.risk` on the
+//! offending node and reports under its own rule ID, so a pattern that is noise
+//! for a project can be switched off with `--disable-rules` without losing the
+//! rest. Plugin languages supply a single query through
+//! `QuerySources::security_patterns`; its matches report under `CM0301`.
+
+use crate::core::IssueSeverity;
+use crate::rules::{
+    ECB_MODE, HARDCODED_IV, HTML_INJECTION, SECURITY_PATTERN, SQL_STRING_INTERPOLATION, UNSAFE_BLOCK, UNSAFE_DESERIALIZATION,
+    WEAK_HASH,
+};
+
+#[derive(Debug)]
+pub struct SecurityPattern {
+    pub rule_id: &'static str,
+    /// Grammar the query is written against, as named in the query cache
+    pub grammar: &'static str,
+    pub query: &'static str,
+    pub severity: IssueSeverity,
+    pub message: &'static str,
+    pub suggestion: &'static str,
+}
+
+/// How matches of a plugin language's own query are reported
+pub const PLUGIN_PATTERN: SecurityPattern = SecurityPattern {
+    rule_id: SECURITY_PATTERN,
+    grammar: "",
+    query: "",
+    severity: IssueSeverity::Warning,
+    message: "Code matches an insecure pattern for this language",
+    suggestion: "Check where the input comes from and validate or escape untrusted data",
+};
+
+pub const SECURITY_PATTERNS: &[SecurityPattern] = &[
+    // JavaScript
+    SecurityPattern {
+        rule_id: SECURITY_PATTERN,
+        grammar: "javascript",
+        query: "(call_expression function: (identifier) @func (#eq? @func \"eval\")) @security.risk
+                (new_expression constructor: (identifier) @ctor (#eq? @ctor \"Function\")) @security.risk
+                (call_expression
+                    function: (identifier) @func
+                    arguments: (arguments . [(string) (template_string)])
+                    (#match? @func \"^(setTimeout|setInterval)$\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "String evaluated as code",
+        suggestion: "Pass a function instead of a string, and parse data with JSON.parse rather than evaluating it",
+    },
+    SecurityPattern {
+        rule_id: HTML_INJECTION,
+        grammar: "javascript",
+        query: "(assignment_expression
+                    left: (member_expression property: (property_identifier) @prop)
+                    (#match? @prop \"^(innerHTML|outerHTML)$\")) @security.risk
+                (augmented_assignment_expression
+                    left: (member_expression property: (property_identifier) @prop)
+                    (#match? @prop \"^(innerHTML|outerHTML)$\")) @security.risk
+                (call_expression
+                    function: (member_expression object: (identifier) @object property: (property_identifier) @method)
+                    (#eq? @object \"document\")
+                    (#match? @method \"^(write|writeln)$\")) @security.risk
+                (call_expression
+                    function: (member_expression property: (property_identifier) @method)
+                    (#eq? @method \"insertAdjacentHTML\")) @security.risk",
+        severity: IssueSeverity::Warning,
+        message: "Markup inserted into the page as HTML",
+        suggestion: "Set textContent for plain text, or sanitize the markup (e.g. with DOMPurify) before inserting it",
+    },
+    SecurityPattern {
+        rule_id: SQL_STRING_INTERPOLATION,
+        grammar: "javascript",
+        query: "(call_expression
+                    function: (member_expression property: (property_identifier) @method)
+                    arguments: (arguments . (template_string (template_substitution)))
+                    (#match? @method \"^(query|execute|raw|exec)$\")) @security.risk
+                (call_expression
+                    function: (member_expression property: (property_identifier) @method)
+                    arguments: (arguments . (binary_expression left: (string)))
+                    (#match? @method \"^(query|execute|raw|exec)$\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "SQL built by string interpolation",
+        suggestion: "Pass values as query parameters (placeholders) instead of splicing them into the SQL",
+    },
+    SecurityPattern {
+        rule_id: WEAK_HASH,
+        grammar: "javascript",
+        query: "(call_expression
+                    function: (member_expression property: (property_identifier) @method)
+                    arguments: (arguments . (string) @algorithm)
+                    (#eq? @method \"createHash\")
+                    (#match? @algorithm \"(?i)^.(md5|sha1).$\")) @security.risk",
+        severity: IssueSeverity::Warning,
+        message: "Weak hash function",
+        suggestion: "Hash passwords with bcrypt, scrypt or argon2; use createHash('sha256') or better for integrity checks",
+    },
+    SecurityPattern {
+        rule_id: ECB_MODE,
+        grammar: "javascript",
+        query: "((string) @security.risk (#match? @security.risk \"(?i)(^|[^a-z])ecb([^a-z]|$)\"))",
+        severity: IssueSeverity::Error,
+        message: "Block cipher in ECB mode",
+        suggestion: "Use an authenticated mode such as aes-256-gcm with a random 12-byte IV per message",
+    },
+    SecurityPattern {
+        rule_id: HARDCODED_IV,
+        grammar: "javascript",
+        query: "(call_expression
+                    function: (member_expression property: (property_identifier) @method)
+                    arguments: (arguments . (_) . (_) . (string))
+                    (#match? @method \"^(createCipheriv|createDecipheriv)$\")) @security.risk
+                (call_expression
+                    function: (member_expression property: (property_identifier) @method)
+                    arguments: (arguments . (_) . (_) . (call_expression
+                        function: (member_expression object: (identifier) @buffer property: (property_identifier) @from)
+                        arguments: (arguments . [(string) (array) (number)])))
+                    (#match? @method \"^(createCipheriv|createDecipheriv)$\")
+                    (#eq? @buffer \"Buffer\")
+                    (#match? @from \"^(from|alloc)$\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "Cipher initialized with a constant IV",
+        suggestion: "Generate the IV per message with crypto.randomBytes and store it with the ciphertext",
+    },
+    // Python
+    SecurityPattern {
+        rule_id: SECURITY_PATTERN,
+        grammar: "python",
+        query: "(call function: (identifier) @func (#match? @func \"^(eval|exec|compile)$\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "String evaluated as code",
+        suggestion: "Parse data with json or ast.literal_eval instead of evaluating it",
+    },
+    SecurityPattern {
+        rule_id: SQL_STRING_INTERPOLATION,
+        grammar: "python",
+        query: "(call
+                    function: (attribute attribute: (identifier) @method)
+                    arguments: (argument_list . (string (interpolation)))
+                    (#match? @method \"^(execute|executemany|executescript|raw)$\")) @security.risk
+                (call
+                    function: (attribute attribute: (identifier) @method)
+                    arguments: (argument_list . (binary_operator left: (string)))
+                    (#match? @method \"^(execute|executemany|executescript|raw)$\")) @security.risk
+                (call
+                    function: (attribute attribute: (identifier) @method)
+                    arguments: (argument_list . (call function: (attribute object: (string) attribute: (identifier) @format)))
+                    (#match? @method \"^(execute|executemany|executescript|raw)$\")
+                    (#eq? @format \"format\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "SQL built by string interpolation",
+        suggestion: "Pass values as query parameters, e.g. cursor.execute(\"... WHERE id = %s\", (user_id,))",
+    },
+    SecurityPattern {
+        rule_id: UNSAFE_DESERIALIZATION,
+        grammar: "python",
+        query: "(call
+                    function: (attribute object: (identifier) @module attribute: (identifier) @method)
+                    (#match? @module \"^(pickle|cPickle|dill|marshal)$\")
+                    (#match? @method \"^(load|loads)$\")) @security.risk
+                (call
+                    function: (attribute object: (identifier) @module attribute: (identifier) @method)
+                    (#eq? @module \"shelve\")
+                    (#eq? @method \"open\")) @security.risk
+                (call
+                    function: (attribute object: (identifier) @module attribute: (identifier) @method)
+                    (#eq? @module \"yaml\")
+                    (#eq? @method \"unsafe_load\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "Data deserialized into arbitrary objects",
+        suggestion: "Read untrusted input with a data-only format such as json or yaml.safe_load, or sign and verify the payload",
+    },
+    SecurityPattern {
+        rule_id: WEAK_HASH,
+        grammar: "python",
+        query: "(call
+                    function: (attribute object: (identifier) @module attribute: (identifier) @method)
+                    (#eq? @module \"hashlib\")
+                    (#match? @method \"^(md5|sha1)$\")) @security.risk
+                (call
+                    function: (attribute object: (identifier) @module attribute: (identifier) @method)
+                    arguments: (argument_list . (string) @algorithm)
+                    (#eq? @module \"hashlib\")
+                    (#eq? @method \"new\")
+                    (#match? @algorithm \"(?i)^.(md5|sha1).$\")) @security.risk",
+        severity: IssueSeverity::Warning,
+        message: "Weak hash function",
+        suggestion: "Hash passwords with argon2-cffi, bcrypt or hashlib.scrypt; use hashlib.sha256 or better for integrity checks",
+    },
+    SecurityPattern {
+        rule_id: ECB_MODE,
+        grammar: "python",
+        query: "((string) @security.risk (#match? @security.risk \"(?i)(^|[^a-z])ecb([^a-z]|$)\"))
+                ((attribute attribute: (identifier) @mode) @security.risk (#eq? @mode \"MODE_ECB\"))
+                ((call function: (attribute object: (identifier) @module attribute: (identifier) @mode)) @security.risk
+                    (#eq? @module \"modes\")
+                    (#eq? @mode \"ECB\"))",
+        severity: IssueSeverity::Error,
+        message: "Block cipher in ECB mode",
+        suggestion: "Use an authenticated mode, e.g. AESGCM from cryptography or Fernet, with a fresh nonce per message",
+    },
+    SecurityPattern {
+        rule_id: HARDCODED_IV,
+        grammar: "python",
+        query: "(call
+                    function: (attribute attribute: (identifier) @method)
+                    arguments: (argument_list (keyword_argument name: (identifier) @param value: (string)))
+                    (#eq? @method \"new\")
+                    (#match? @param \"^(iv|IV|nonce)$\")) @security.risk
+                (call
+                    function: (attribute object: (identifier) @module attribute: (identifier) @mode)
+                    arguments: (argument_list . (string))
+                    (#eq? @module \"modes\")
+                    (#match? @mode \"^(CBC|CFB|OFB|CTR|GCM)$\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "Cipher initialized with a constant IV or nonce",
+        suggestion: "Generate the IV per message with os.urandom or secrets.token_bytes and store it with the ciphertext",
+    },
+    // Ruby
+    SecurityPattern {
+        rule_id: SECURITY_PATTERN,
+        grammar: "ruby",
+        query: "(call method: (identifier) @func (#match? @func \"^(eval|instance_eval|class_eval)$\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "String evaluated as code",
+        suggestion: "Use define_method, public_send or a lookup table instead of evaluating strings",
+    },
+    SecurityPattern {
+        rule_id: SECURITY_PATTERN,
+        grammar: "ruby",
+        query: "(call method: (identifier) @func (#match? @func \"^(system|exec)$\")) @security.risk",
+        severity: IssueSeverity::Warning,
+        message: "Shell command run from Ruby",
+        suggestion: "Pass the program and its arguments separately, e.g. system(\"ls\", dir), so no shell parses them",
+    },
+    SecurityPattern {
+        rule_id: SQL_STRING_INTERPOLATION,
+        grammar: "ruby",
+        query: "(call
+                    method: (identifier) @method
+                    arguments: (argument_list . (string (interpolation)))
+                    (#match? @method \"^(where|having|order|find_by_sql|execute|exec_query|select_all|select_value)$\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "SQL built by string interpolation",
+        suggestion: "Use placeholders, e.g. where(\"name = ?\", name), or the hash form where(name: name)",
+    },
+    SecurityPattern {
+        rule_id: UNSAFE_DESERIALIZATION,
+        grammar: "ruby",
+        query: "(call
+                    receiver: (constant) @module
+                    method: (identifier) @method
+                    (#eq? @module \"Marshal\")
+                    (#match? @method \"^(load|restore)$\")) @security.risk
+                (call
+                    receiver: (constant) @module
+                    method: (identifier) @method
+                    (#match? @module \"^(YAML|Psych)$\")
+                    (#eq? @method \"unsafe_load\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "Data deserialized into arbitrary objects",
+        suggestion: "Read untrusted input with JSON or YAML.safe_load, or sign and verify the payload",
+    },
+    SecurityPattern {
+        rule_id: WEAK_HASH,
+        grammar: "ruby",
+        query: "(call
+                    receiver: (scope_resolution scope: (constant) @module name: (constant) @algorithm)
+                    (#eq? @module \"Digest\")
+                    (#match? @algorithm \"^(MD5|SHA1)$\")) @security.risk",
+        severity: IssueSeverity::Warning,
+        message: "Weak hash function",
+        suggestion: "Hash passwords with bcrypt (has_secure_password) or argon2; use Digest::SHA256 or better for integrity checks",
+    },
+    SecurityPattern {
+        rule_id: ECB_MODE,
+        grammar: "ruby",
+        query: "((string) @security.risk (#match? @security.risk \"(?i)(^|[^a-z])ecb([^a-z]|$)\"))",
+        severity: IssueSeverity::Error,
+        message: "Block cipher in ECB mode",
+        suggestion: "Use an authenticated mode such as OpenSSL::Cipher.new('aes-256-gcm') with a random IV per message",
+    },
+    SecurityPattern {
+        rule_id: HARDCODED_IV,
+        grammar: "ruby",
+        query: "(assignment
+                    left: (call method: (identifier) @attribute)
+                    right: (string)
+                    (#eq? @attribute \"iv\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "Cipher initialized with a constant IV",
+        suggestion: "Assign cipher.random_iv instead and store the IV with the ciphertext",
+    },
+    // Rust
+    SecurityPattern {
+        rule_id: UNSAFE_BLOCK,
+        grammar: "rust",
+        query: "(unsafe_block) @security.risk",
+        severity: IssueSeverity::Info,
+        message: "unsafe block opts out of the compiler's memory-safety checks",
+        suggestion: "Keep the block as small as possible and state the invariants it relies on in a `// SAFETY:` comment",
+    },
+    SecurityPattern {
+        rule_id: SQL_STRING_INTERPOLATION,
+        grammar: "rust",
+        query: "(call_expression
+                    function: [(field_expression field: (field_identifier) @method) (scoped_identifier name: (identifier) @method) (identifier) @method]
+                    arguments: (arguments . (macro_invocation macro: (identifier) @macro))
+                    (#match? @method \"^(query|query_as|query_scalar|execute|prepare)$\")
+                    (#eq? @macro \"format\")) @security.risk
+                (call_expression
+                    function: [(field_expression field: (field_identifier) @method) (scoped_identifier name: (identifier) @method) (identifier) @method]
+                    arguments: (arguments . (reference_expression value: (macro_invocation macro: (identifier) @macro)))
+                    (#match? @method \"^(query|query_as|query_scalar|execute|prepare)$\")
+                    (#eq? @macro \"format\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "SQL built by string interpolation",
+        suggestion: "Bind values as query parameters, e.g. sqlx::query(\"... WHERE id = $1\").bind(id)",
+    },
+    SecurityPattern {
+        rule_id: WEAK_HASH,
+        grammar: "rust",
+        query: "(call_expression
+                    function: (scoped_identifier path: (identifier) @type name: (identifier) @method)
+                    (#match? @type \"^(Md5|Sha1|md5|sha1)$\")
+                    (#match? @method \"^(new|digest|compute)$\")) @security.risk",
+        severity: IssueSeverity::Warning,
+        message: "Weak hash function",
+        suggestion: "Hash passwords with the argon2 or scrypt crates; use sha2::Sha256 or better for integrity checks",
+    },
+    SecurityPattern {
+        rule_id: ECB_MODE,
+        grammar: "rust",
+        query: "((string_literal) @security.risk (#match? @security.risk \"(?i)(^|[^a-z])ecb([^a-z]|$)\"))
+                ((scoped_identifier path: (identifier) @module) @security.risk (#eq? @module \"ecb\"))
+                ((scoped_type_identifier path: (identifier) @module) @security.risk (#eq? @module \"ecb\"))",
+        severity: IssueSeverity::Error,
+        message: "Block cipher in ECB mode",
+        suggestion: "Use an AEAD such as aes_gcm::Aes256Gcm or chacha20poly1305 with a fresh nonce per message",
+    },
+    SecurityPattern {
+        rule_id: HARDCODED_IV,
+        grammar: "rust",
+        query: "(call_expression
+                    function: (scoped_identifier name: (identifier) @method)
+                    arguments: (arguments . (_) . [(string_literal) (reference_expression value: (array_expression))])
+                    (#eq? @method \"new_from_slices\")) @security.risk",
+        severity: IssueSeverity::Error,
+        message: "Cipher initialized with a constant IV",
+        suggestion: "Generate the IV per message, e.g. with Aes128CbcEnc::generate_iv(&mut OsRng), and store it with the ciphertext",
+    },
+];
+
+/// The table rows written against `grammar`
+pub fn for_grammar(grammar: &str) -> impl Iterator<Item = &'static SecurityPattern> + '_ {
+    SECURITY_PATTERNS.iter().filter(move |pattern| pattern.grammar == grammar)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules;
+
+    #[test]
+    fn test_patterns_compile_and_report_security_rules() {
+        for pattern in SECURITY_PATTERNS {
+            let language = match pattern.grammar {
+                "javascript" => tree_sitter::Language::new(tree_sitter_javascript::LANGUAGE),
+                "python" => tree_sitter::Language::new(tree_sitter_python::LANGUAGE),
+                "ruby" => tree_sitter::Language::new(tree_sitter_ruby::LANGUAGE),
+                "rust" => tree_sitter::Language::new(tree_sitter_rust::LANGUAGE),
+                other => panic!("no grammar {}", other),
+            };
+            let query = tree_sitter::Query::new(&language, pattern.query)
+                .unwrap_or_else(|error| panic!("{} query for {}: {}", pattern.rule_id, pattern.grammar, error));
+            assert!(query.capture_index_for_name("security.risk").is_some());
+
+            let rule = rules::find_rule(pattern.rule_id).expect("pattern references a known rule");
+            assert_eq!(rule.category, "security");
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Splitting one analysis across CI jobs and merging the parts back
+//!
+//! `--shard 2/8` keeps the discovered files whose group hashes to the second
+//! of eight buckets. By default each file is its own group, which balances
+//! load best; grouping by top-level directory or CODEOWNERS team instead keeps
+//! everything a team owns on one shard, so that shard's report is meaningful
+//! on its own. The hash is FNV-1a over the group key, so every job (on any
+//! platform or toolchain) agrees on the split without coordinating. A shard's JSON output carries its parsed files, and
+//! `merge` aggregates the files of all shards in one pass: averages, percentile
+//! bands, health and calibration come out exactly as for an unsharded run.
+//! Parsed files carry their clone fingerprints and content keys, and shards
+//! what discovery found (test files), so the merge job needs no checkout.
+//! Repeated files are found in the merge, as copies may sit in different shards.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::analyzers::{AnalysisResults, CodeAnalyzer, SkipReason};
+use crate::codeowners::CodeOwners;
+use crate::parsers::{LanguageParser, ParsedFile};
+use crate::rules;
+use crate::schema;
+
+/// One of `count` equal parts, numbered from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl Shard {
+    /// Parse `INDEX/COUNT`, e.g. `2/8`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (index, count) = spec.split_once('/')
+            .with_context(|| format!("Invalid shard '{}', expected INDEX/COUNT such as 2/8", spec))?;
+        let index: usize = index.trim().parse().with_context(|| format!("Invalid shard index in '{}'", spec))?;
+        let count: usize = count.trim().parse().with_context(|| format!("Invalid shard count in '{}'", spec))?;
+        if count == 0 || index == 0 || index > count {
+            anyhow::bail!("Invalid shard '{}': the index must be between 1 and the count", spec);
+        }
+        Ok(Self { index, count })
+    }
+
+    /// Whether `file`, discovered under `root`, belongs to this shard
+    pub fn contains(&self, root: &Path, file: &Path, by: &ShardBy) -> bool {
+        let key = by.group(root, file);
+        (fnv1a(key.as_bytes()) % self.count as u64) as usize == self.index - 1
+    }
+}
+
+/// What is kept together on one shard
+#[derive(Debug, Clone, Default)]
+pub enum ShardBy {
+    /// Every file on its own: the most even split
+    #[default]
+    File,
+    /// Top-level directory under the root; files directly in the root form one group
+    Directory,
+    /// CODEOWNERS owners; unowned files form one group
+    Owner(CodeOwners),
+}
+
+/// Group of files at the root, or without an owner
+const REMAINDER_GROUP: &str = "(none)";
+
+impl ShardBy {
+    /// `file`, `directory` or `owner`; `owner` reads CODEOWNERS under `root`
+    pub fn parse(name: &str, root: &Path) -> Result<Self> {
+        match name {
+            "file" => Ok(ShardBy::File),
+            "directory" => Ok(ShardBy::Directory),
+            "owner" => Ok(ShardBy::Owner(CodeOwners::load(root)?)),
+            other => anyhow::bail!("Unknown shard grouping '{}' (expected file, directory or owner)", other),
+        }
+    }
+
+    /// The key `file` is sharded by: its path, top-level directory, or owners
+    pub fn group(&self, root: &Path, file: &Path) -> String {
+        let relative = file.strip_prefix(root).unwrap_or(file);
+        let relative = relative.strip_prefix(".").unwrap_or(relative);
+        match self {
+            ShardBy::File => slash_path(relative),
+            ShardBy::Directory => match relative.parent().and_then(|parent| parent.components().next()) {
+                Some(top) => top.as_os_str().to_string_lossy().into_owned(),
+                None => REMAINDER_GROUP.to_string(),
+            },
+            ShardBy::Owner(codeowners) => match codeowners.owners(relative) {
+                [] => REMAINDER_GROUP.to_string(),
+                owners => owners.join(" "),
+            },
+        }
+    }
+
+    /// Whether groups are worth listing in reports; single files are not
+    pub fn is_grouped(&self) -> bool {
+        !matches!(self, ShardBy::File)
+    }
+}
+
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// What a shard contributes to the merged results, embedded in its JSON output as `shard`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardOutput {
+    pub shard: Shard,
+    /// Clone fingerprints in `files` are only comparable between runs of the same version
+    #[serde(default)]
+    pub tool_version: String,
+    /// Directories or owners this shard covers, when sharding by either
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub groups: BTreeSet<String>,
+    pub files: Vec<ParsedFile>,
+    pub errors: Vec<String>,
+    /// Discovery runs over the whole tree in every shard, so these and the
+    /// fields below are already global
+    pub skip_counts: BTreeMap<SkipReason, usize>,
+    /// Test files discovered, analyzed or not
+    #[serde(default)]
+    pub test_files: Vec<PathBuf>,
+    /// How many of `test_files` were analyzed
+    #[serde(default)]
+    pub analyzed_test_files: usize,
+}
+
+/// The `shard` section of every results file; fails unless they are exactly shards 1..=N of one split
+pub fn read_outputs(paths: &[PathBuf]) -> Result<Vec<ShardOutput>> {
+    let mut outputs: Vec<ShardOutput> = Vec::new();
+    for path in paths {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut results: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid results file {}", path.display()))?;
+        schema::check_version(&results).with_context(|| format!("Cannot merge {}", path.display()))?;
+        let section = results.get_mut("shard").map(serde_json::Value::take)
+            .with_context(|| format!("{} is not the output of an `analyze --shard` run", path.display()))?;
+        let output: ShardOutput = serde_json::from_value(section)
+            .with_context(|| format!("Invalid shard section in {}", path.display()))?;
+        outputs.push(output);
+    }
+
+    let count = outputs.first().map(|output| output.shard.count).context("No shard results to merge")?;
+    if let Some(other) = outputs.iter().find(|output| output.shard.count != count) {
+        anyhow::bail!("Shard {} comes from a different split than shard {}/{}", other.shard, outputs[0].shard.index, count);
+    }
+    outputs.sort_by_key(|output| output.shard.index);
+    for (expected, output) in (1..=count).zip(&outputs) {
+        if output.shard.index != expected {
+            anyhow::bail!("Shard {}/{} is missing or given twice", expected, count);
+        }
+    }
+    if outputs.len() != count {
+        anyhow::bail!("Expected {} shard results, got {}", count, outputs.len());
+    }
+    if let Some(other) = outputs.iter().find(|output| output.tool_version != outputs[0].tool_version) {
+        anyhow::bail!("Shard {} was analyzed by codemetrics {:?} but shard {} by {:?}; re-run the shards with one version",
+            other.shard, other.tool_version, outputs[0].shard, outputs[0].tool_version);
+    }
+    Ok(outputs)
+}
+
+/// Aggregate the files of every shard as if they had been analyzed in one run under `root`
+///
+/// Clones come from the fingerprints in the parsed files. The merge fails
+/// when duplicated-code is enabled but a shard ran without it, rather than
+/// reporting less duplication than there is.
+pub fn merge(analyzer: &CodeAnalyzer, root: &Path, mut outputs: Vec<ShardOutput>) -> Result<AnalysisResults> {
+    let first = outputs.first_mut().context("No shard results to merge")?;
+    let skip_counts = std::mem::take(&mut first.skip_counts);
+    let test_files = std::mem::take(&mut first.test_files);
+    let analyzed_test_files = first.analyzed_test_files;
+
+    if analyzer.config().rules.is_enabled(rules::DUPLICATED_CODE) {
+        // Plugin languages are never fingerprinted
+        let unfingerprinted = outputs.iter()
+            .flat_map(|output| &output.files)
+            .find(|file| file.facts.clones.is_none() && LanguageParser::is_builtin(&file.path));
+        if let Some(file) = unfingerprinted {
+            anyhow::bail!("{} has no clone fingerprints: its shard ran with duplicated-code disabled. \
+                Re-run the shards with it enabled, or disable it for the merge", file.path);
+        }
+    }
+
+    let parsed: Vec<Result<ParsedFile>> = outputs.into_iter()
+        .flat_map(|output| {
+            let errors = output.errors.into_iter().map(|error| Err(anyhow::anyhow!(error)));
+            output.files.into_iter().map(Ok).chain(errors)
+        })
+        .collect();
+
+    let mut results = analyzer.aggregate_shards(root, parsed)?;
+    results.record_test_files(test_files.iter(), analyzed_test_files);
+    analyzer.score_health(&mut results);
+    results.skip_counts.extend(skip_counts);
+    Ok(results)
+}
+
+/// 64-bit FNV-1a, stable across platforms and Rust versions unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_file_lands_in_exactly_one_shard() {
+        let root = Path::new("/repo");
+        let shards: Vec<Shard> = (1..=4).map(|index| Shard::parse(&format!("{}/4", index)).unwrap()).collect();
+        for n in 0..200 {
+            let file = root.join(format!("pkg{}/src/file{}.rs", n % 7, n));
+            assert_eq!(shards.iter().filter(|shard| shard.contains(root, &file, &ShardBy::File)).count(), 1);
+        }
+        assert!(Shard::parse("0/4").is_err());
+        assert!(Shard::parse("5/4").is_err());
+        assert_eq!(Shard::parse("2/8").unwrap().to_string(), "2/8");
+    }
+
+    #[test]
+    fn test_directory_and_owner_groups_stay_together() {
+        let root = Path::new("/repo");
+        let owners = CodeOwners::parse(root, "/billing/ @org/payments\n/search/ @org/search\n").unwrap();
+        let by_owner = ShardBy::Owner(owners);
+        assert_eq!(by_owner.group(root, &root.join("billing/api/refund.rs")), "@org/payments");
+        assert_eq!(by_owner.group(root, &root.join("tools/gen.py")), "(none)");
+        assert_eq!(ShardBy::Directory.group(root, &root.join("search/index/mod.rs")), "search");
+        assert_eq!(ShardBy::Directory.group(Path::new("."), Path::new("./setup.py")), "(none)");
+
+        let shard = Shard::parse("3/5").unwrap();
+        let billing: Vec<bool> = ["billing/a.rs", "billing/deep/b.rs", "billing/c.py"].iter()
+            .map(|file| shard.contains(root, &root.join(file), &by_owner))
+            .collect();
+        assert!(billing.iter().all(|&inside| inside == billing[0]));
+    }
+
+    #[test]
+    fn test_merge_needs_only_the_shard_outputs() -> Result<()> {
+        let block = "fn total(items: &[u32], limit: u32) -> u32 {\n    let mut sum = 0;\n    for item in items {\n        if *item > limit {\n            sum += item * 2 + limit;\n        } else {\n            sum += item + 1;\n        }\n    }\n    sum\n}\n";
+        let temp_dir = tempfile::tempdir()?;
+        std::fs::create_dir(temp_dir.path().join("tests"))?;
+        std::fs::write(temp_dir.path().join("cart.rs"), block)?;
+        std::fs::write(temp_dir.path().join("orders.rs"), block.replace("total", "sum_orders"))?;
+        std::fs::write(temp_dir.path().join("tests/cart_test.rs"), "fn check() {}\n")?;
+        let analyze = |shard: Option<Shard>| {
+            let mut builder = crate::analyzers::AnalysisConfig::builder().quiet(true);
+            if let Some(shard) = shard {
+                builder = builder.shard(shard, ShardBy::File);
+            }
+            CodeAnalyzer::with_config(builder.build()?).analyze_path(temp_dir.path())
+        };
+
+        let whole = analyze(None)?;
+        let outputs = (1..=2)
+            .map(|index| {
+                let output = analyze(Some(Shard { index, count: 2 }))?.shard.context("shard output")?;
+                Ok(serde_json::from_value(serde_json::to_value(output)?)?)
+            })
+            .collect::<Result<Vec<ShardOutput>>>()?;
+        let root = temp_dir.path().to_path_buf();
+        temp_dir.close()?;
+
+        let merged = merge(&CodeAnalyzer::new(), &root, outputs)?;
+        assert!(whole.duplication_percent.unwrap() > 0.0);
+        assert_eq!(merged.duplication_percent, whole.duplication_percent);
+        assert_eq!(merged.clones.len(), whole.clones.len());
+        assert_eq!(merged.language_breakdown["Rust"].test_files, 1);
+        assert_eq!(merged.skip_counts, whole.skip_counts);
+        Ok(())
+    }
+}
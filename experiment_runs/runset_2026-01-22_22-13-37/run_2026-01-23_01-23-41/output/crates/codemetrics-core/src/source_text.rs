@@ -0,0 +1,91 @@
+//! Reading source files, memory-mapped when they are large
+//!
+//! Small files are read into a `String`. From `MAP_THRESHOLD` on, the file is
+//! mapped instead: the parser and the cache's content hash read the page
+//! cache directly, so a large file is never copied onto the heap. Every page
+//! is still read once, by the UTF-8 check when the file is opened, so mapping
+//! saves the copy and the allocation, not the I/O.
+
+use anyhow::{Context, Result};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::Read;
+use std::ops::Deref;
+use std::path::Path;
+
+/// Files at least this large are mapped rather than read
+pub const MAP_THRESHOLD: u64 = 256 * 1024;
+
+/// A file's contents, known to be valid UTF-8
+pub enum SourceText {
+    Read(String),
+    Mapped(Mmap),
+}
+
+impl SourceText {
+    pub fn read(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let len = file.metadata()
+            .with_context(|| format!("Failed to stat {}", path.display()))?
+            .len();
+        if len < MAP_THRESHOLD {
+            let mut content = String::with_capacity(len as usize);
+            file.read_to_string(&mut content)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            return Ok(Self::Read(content));
+        }
+
+        // SAFETY: the map is read-only and lives only while the file is parsed.
+        // Source files are not expected to be rewritten during a run; if one is
+        // truncated meanwhile, reading past its new end faults, as it would for
+        // any tool that maps its inputs.
+        let map = unsafe { Mmap::map(&file) }
+            .with_context(|| format!("Failed to map {}", path.display()))?;
+        std::str::from_utf8(&map)
+            .with_context(|| format!("{} is not valid UTF-8", path.display()))?;
+        Ok(Self::Mapped(map))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Read(content) => content,
+            // SAFETY: validated as UTF-8 in `read`, and the map is read-only
+            Self::Mapped(map) => unsafe { std::str::from_utf8_unchecked(map) },
+        }
+    }
+}
+
+impl Deref for SourceText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_large_files_are_mapped_and_read_the_same() {
+        let temp_dir = tempdir().unwrap();
+        let small = temp_dir.path().join("small.rs");
+        let large = temp_dir.path().join("large.rs");
+        std::fs::write(&small, "fn main() {}\n").unwrap();
+        let content = "fn f() {}\n".repeat(MAP_THRESHOLD as usize / 10 + 1);
+        std::fs::write(&large, &content).unwrap();
+
+        assert!(matches!(SourceText::read(&small).unwrap(), SourceText::Read(_)));
+        let mapped = SourceText::read(&large).unwrap();
+        assert!(matches!(mapped, SourceText::Mapped(_)));
+        assert_eq!(mapped.as_str(), content);
+
+        let mut invalid = vec![b'a'; MAP_THRESHOLD as usize];
+        invalid.push(0xff);
+        std::fs::write(&large, invalid).unwrap();
+        assert!(SourceText::read(&large).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Function stability classification from git history
+//!
+//! Uses blame from the repository's version control system to find when each line of a function was last touched,
+//! then combines the function's age with how much of it changed recently.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::analyzers::AnalysisResults;
+use crate::vcs::Vcs;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// How settled a function is, based on its age and recent churn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Stability {
+    /// Untouched during the recent window
+    Stable,
+    /// Recently introduced, or lightly modified during the window
+    Active,
+    /// Older code that saw substantial rewrites during the window
+    Volatile,
+}
+
+impl Stability {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Stability::Stable => "stable",
+            Stability::Active => "active",
+            Stability::Volatile => "volatile",
+        }
+    }
+}
+
+/// Classifies functions using per-line blame timestamps
+pub struct StabilityClassifier {
+    /// Size of the "recent" window in days
+    window_days: i64,
+    /// Share of lines changed within the window that makes older code volatile
+    volatile_ratio: f64,
+    now: i64,
+}
+
+impl StabilityClassifier {
+    pub fn new() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        Self {
+            window_days: 90,
+            volatile_ratio: 0.3,
+            now,
+        }
+    }
+
+    pub fn with_window_days(mut self, days: i64) -> Self {
+        self.window_days = days;
+        self
+    }
+
+    /// Annotate every high-complexity function with its stability class
+    pub fn annotate(&self, results: &mut AnalysisResults, vcs: &dyn Vcs) -> Result<()> {
+        let mut blame_cache: HashMap<String, Vec<i64>> = HashMap::new();
+
+        for func in &mut results.high_complexity_functions {
+            if !blame_cache.contains_key(&func.file_path) {
+                let timestamps = self.blame_timestamps(vcs, Path::new(&func.file_path))
+                    .unwrap_or_default();
+                blame_cache.insert(func.file_path.clone(), timestamps);
+            }
+
+            let timestamps = &blame_cache[&func.file_path];
+            let start = func.line_start.saturating_sub(1) as usize;
+            let end = (func.line_end as usize).min(timestamps.len());
+            if start < end {
+                func.stability = Some(self.classify(&timestamps[start..end]));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Classify a function from the last-modified timestamps of its lines
+    pub fn classify(&self, line_timestamps: &[i64]) -> Stability {
+        let window_start = self.now - self.window_days * SECONDS_PER_DAY;
+        let first_introduced = line_timestamps.iter().copied().min().unwrap_or(self.now);
+        let recent_lines = line_timestamps.iter().filter(|&&t| t >= window_start).count();
+
+        if recent_lines == 0 {
+            return Stability::Stable;
+        }
+
+        let recent_ratio = recent_lines as f64 / line_timestamps.len() as f64;
+        if first_introduced < window_start && recent_ratio >= self.volatile_ratio {
+            Stability::Volatile
+        } else {
+            Stability::Active
+        }
+    }
+
+    /// Author timestamp of the commit that last touched each line of `file`
+    fn blame_timestamps(&self, vcs: &dyn Vcs, file: &Path) -> Result<Vec<i64>> {
+        Ok(vcs.blame(file)?.into_iter().map(|line| line.author_time).collect())
+    }
+}
+
+impl Default for StabilityClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classifier_at(now: i64) -> StabilityClassifier {
+        StabilityClassifier {
+            window_days: 90,
+            volatile_ratio: 0.3,
+            now,
+        }
+    }
+
+    #[test]
+    fn test_classification() {
+        let now = 1_000 * SECONDS_PER_DAY;
+        let classifier = classifier_at(now);
+        let old = now - 400 * SECONDS_PER_DAY;
+        let recent = now - 10 * SECONDS_PER_DAY;
+
+        assert_eq!(classifier.classify(&[old, old, old]), Stability::Stable);
+        assert_eq!(classifier.classify(&[recent, recent]), Stability::Active);
+        assert_eq!(classifier.classify(&[old, old, old, old, recent]), Stability::Active);
+        assert_eq!(classifier.classify(&[old, recent, recent]), Stability::Volatile);
+    }
+}
@@ -0,0 +1,291 @@
+//! Heuristic detection of the frameworks a project is built on
+//!
+//! Evidence comes from dependency manifests (`package.json`, `Cargo.toml`,
+//! `pyproject.toml`, `requirements*.txt`, `go.mod`, `pom.xml`, Gradle builds)
+//! and from import statements near the top of source files. Detection is
+//! deliberately cheap: manifests are parsed, sources are only skimmed.
+
+use anyhow::Result;
+use ignore::Walk;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Import statements are expected within this many lines of the top of a file
+const IMPORT_SCAN_LINES: usize = 80;
+
+/// Evidence listed per framework, so a monorepo does not flood the report
+const MAX_EVIDENCE: usize = 3;
+
+struct Signature {
+    name: &'static str,
+    language: &'static str,
+    /// Package names as they appear in manifests; a trailing `*` matches a prefix
+    dependencies: &'static [&'static str],
+    /// Lowercase import fragments, with quotes normalized to `'`
+    imports: &'static [&'static str],
+}
+
+const SIGNATURES: &[Signature] = &[
+    Signature {
+        name: "React",
+        language: "JavaScript",
+        dependencies: &["react"],
+        imports: &["from 'react'", "require('react')"],
+    },
+    Signature { name: "Next.js", language: "JavaScript", dependencies: &["next"], imports: &["from 'next/"] },
+    Signature { name: "Vue", language: "JavaScript", dependencies: &["vue"], imports: &["from 'vue'"] },
+    Signature { name: "Angular", language: "TypeScript", dependencies: &["@angular/core"], imports: &["from '@angular/core'"] },
+    Signature {
+        name: "Express",
+        language: "JavaScript",
+        dependencies: &["express"],
+        imports: &["from 'express'", "require('express')"],
+    },
+    Signature {
+        name: "Django",
+        language: "Python",
+        dependencies: &["django"],
+        imports: &["from django", "import django"],
+    },
+    Signature { name: "Flask", language: "Python", dependencies: &["flask"], imports: &["from flask import"] },
+    Signature { name: "FastAPI", language: "Python", dependencies: &["fastapi"], imports: &["from fastapi import"] },
+    Signature { name: "actix-web", language: "Rust", dependencies: &["actix-web"], imports: &["use actix_web"] },
+    Signature { name: "Axum", language: "Rust", dependencies: &["axum"], imports: &["use axum"] },
+    Signature { name: "Rocket", language: "Rust", dependencies: &["rocket"], imports: &["use rocket"] },
+    Signature {
+        name: "Spring",
+        language: "Java",
+        dependencies: &["spring-boot*", "spring-web*", "spring-context"],
+        imports: &["import org.springframework"],
+    },
+    Signature { name: "Gin", language: "Go", dependencies: &["github.com/gin-gonic/gin"], imports: &["'github.com/gin-gonic/gin'"] },
+    Signature { name: "Echo", language: "Go", dependencies: &["github.com/labstack/echo*"], imports: &["'github.com/labstack/echo"] },
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectedFramework {
+    pub name: &'static str,
+    pub language: &'static str,
+    /// Files that revealed the framework, manifests first
+    pub evidence: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StackReport {
+    /// Frameworks in a stable order
+    pub frameworks: Vec<DetectedFramework>,
+    /// Dependency manifests read, whether or not they named a known framework
+    pub manifests: usize,
+}
+
+/// Frameworks used under `root`
+pub fn detect(root: &Path) -> Result<StackReport> {
+    let mut manifests = 0;
+    let mut found: BTreeMap<&'static str, DetectedFramework> = BTreeMap::new();
+    let mut record = |signature: &Signature, evidence: String| {
+        let framework = found.entry(signature.name).or_insert_with(|| DetectedFramework {
+            name: signature.name,
+            language: signature.language,
+            evidence: Vec::new(),
+        });
+        if framework.evidence.len() < MAX_EVIDENCE && !framework.evidence.contains(&evidence) {
+            framework.evidence.push(evidence);
+        }
+    };
+
+    let mut sources = Vec::new();
+    for entry in Walk::new(root).filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if !path.is_file() || path.components().any(|part| part.as_os_str() == "node_modules") {
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(path).display().to_string();
+
+        if let Some(dependencies) = manifest_dependencies(path) {
+            manifests += 1;
+            for signature in SIGNATURES {
+                if dependencies.iter().any(|dependency| signature.matches_dependency(dependency)) {
+                    record(signature, relative.clone());
+                }
+            }
+        } else if let Some(language) = import_language(path) {
+            sources.push((path.to_path_buf(), relative, language));
+        }
+    }
+
+    for (path, relative, language) in sources {
+        let Ok(file) = std::fs::File::open(&path) else {
+            continue;
+        };
+        // A Rust string mentioning `require('express')` is not an Express import
+        let signatures: Vec<&Signature> = SIGNATURES.iter()
+            .filter(|signature| signature.imported_from(language))
+            .collect();
+        for line in BufReader::new(file).lines().take(IMPORT_SCAN_LINES).map_while(|line| line.ok()) {
+            let line = line.trim().replace('"', "'").to_lowercase();
+            for signature in &signatures {
+                if signature.matches_import(&line) {
+                    record(signature, relative.clone());
+                }
+            }
+        }
+    }
+
+    Ok(StackReport {
+        frameworks: found.into_values().collect(),
+        manifests,
+    })
+}
+
+impl Signature {
+    fn matches_dependency(&self, dependency: &str) -> bool {
+        self.dependencies.iter().any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => dependency.starts_with(prefix),
+            None => dependency == *pattern,
+        })
+    }
+
+    /// Whether sources in `language`, as given by `import_language`, can import the framework
+    fn imported_from(&self, language: &str) -> bool {
+        match self.language {
+            "TypeScript" => language == "JavaScript",
+            own => own == language,
+        }
+    }
+
+    /// `line` is trimmed, lowercased and uses `'` quotes
+    fn matches_import(&self, line: &str) -> bool {
+        let is_import = line.starts_with("import ") || line.contains("require(");
+        self.imports.iter().any(|fragment| line.starts_with(fragment) || (is_import && line.contains(fragment)))
+    }
+}
+
+/// Language whose import syntax a source file uses; TypeScript and Kotlin share
+/// JavaScript's and Java's, or `None` when the file is not skimmed for imports
+fn import_language(path: &Path) -> Option<&'static str> {
+    match path.extension()?.to_str()? {
+        "rs" => Some("Rust"),
+        "js" | "jsx" | "ts" | "tsx" => Some("JavaScript"),
+        "py" => Some("Python"),
+        "go" => Some("Go"),
+        "java" | "kt" => Some("Java"),
+        _ => None,
+    }
+}
+
+/// Dependency names declared by `path`, or `None` when it is not a manifest
+pub(crate) fn manifest_dependencies(path: &Path) -> Option<Vec<String>> {
+    let name = path.file_name()?.to_str()?;
+    let is_manifest = matches!(name, "package.json" | "Cargo.toml" | "pyproject.toml" | "go.mod" | "pom.xml")
+        || name.starts_with("build.gradle")
+        || (name.starts_with("requirements") && name.ends_with(".txt"));
+    if !is_manifest {
+        return None;
+    }
+    let content = std::fs::read_to_string(path).ok()?;
+
+    let dependencies = match name {
+        "package.json" => {
+            let manifest: serde_json::Value = serde_json::from_str(&content).ok()?;
+            ["dependencies", "devDependencies", "peerDependencies"].iter()
+                .filter_map(|section| manifest.get(section)?.as_object())
+                .flat_map(|section| section.keys().cloned())
+                .collect()
+        }
+        "Cargo.toml" => {
+            let manifest: toml::Table = content.parse().ok()?;
+            let workspace = manifest.get("workspace").and_then(|workspace| workspace.get("dependencies"));
+            ["dependencies", "dev-dependencies"].iter()
+                .filter_map(|section| manifest.get(*section))
+                .chain(workspace)
+                .filter_map(|section| section.as_table())
+                .flat_map(|section| section.keys().cloned())
+                .collect()
+        }
+        "pyproject.toml" => {
+            let manifest: toml::Table = content.parse().ok()?;
+            let pep621 = manifest.get("project")
+                .and_then(|project| project.get("dependencies"))
+                .and_then(|dependencies| dependencies.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|requirement| requirement.as_str())
+                .map(requirement_name);
+            let poetry = manifest.get("tool")
+                .and_then(|tool| tool.get("poetry"))
+                .and_then(|poetry| poetry.get("dependencies"))
+                .and_then(|dependencies| dependencies.as_table())
+                .into_iter()
+                .flat_map(|dependencies| dependencies.keys().map(|key| key.to_lowercase()));
+            pep621.chain(poetry).collect()
+        }
+        "go.mod" => content.lines()
+            .map(|line| line.trim().trim_start_matches("require").trim())
+            .filter_map(|line| line.split_whitespace().next())
+            .filter(|module| module.contains('/'))
+            .map(str::to_string)
+            .collect(),
+        "pom.xml" => content.split("<artifactId>")
+            .skip(1)
+            .filter_map(|rest| rest.split("</artifactId>").next())
+            .map(|artifact| artifact.trim().to_string())
+            .collect(),
+        // Gradle coordinates: 'group:artifact:version'
+        _ if name.starts_with("build.gradle") => content.split(['\'', '"'])
+            .filter_map(|literal| literal.split(':').nth(1))
+            .map(str::to_string)
+            .collect(),
+        _ => content.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('-'))
+            .map(requirement_name)
+            .collect(),
+    };
+    Some(dependencies)
+}
+
+/// `Django>=4.2; python_version > "3.8"` → `django`
+fn requirement_name(requirement: &str) -> String {
+    requirement.split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == '.'))
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detects_frameworks_from_manifests_and_imports() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("package.json"), r#"{"dependencies": {"react": "^18.2.0", "lodash": "4"}}"#).unwrap();
+        std::fs::write(root.join("requirements.txt"), "Django>=4.2\nrequests==2.31\n").unwrap();
+        std::fs::create_dir(root.join("api")).unwrap();
+        std::fs::write(root.join("api/main.rs"), "use actix_web::{web, App};\n\nfn main() {}\n").unwrap();
+        std::fs::write(root.join("api/util.py"), "import os\nprint('from flask import nothing')\n").unwrap();
+
+        let report = detect(root).unwrap();
+        assert_eq!(report.manifests, 2);
+        let stack = report.frameworks;
+        let names: Vec<&str> = stack.iter().map(|framework| framework.name).collect();
+        assert_eq!(names, vec!["Django", "React", "actix-web"]);
+        assert_eq!(stack[2].evidence, vec![Path::new("api").join("main.rs").display().to_string()]);
+        assert_eq!(requirement_name("Flask[async]>=2; python_version > '3.8'"), "flask");
+    }
+
+    #[test]
+    fn test_imports_only_count_in_their_own_language() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("signatures.rs"), "const EXPRESS: &str = \"require('express')\";\n").unwrap();
+        std::fs::write(temp_dir.path().join("app.ts"), "import { Component } from '@angular/core';\n").unwrap();
+
+        let report = detect(temp_dir.path()).unwrap();
+        let names: Vec<&str> = report.frameworks.iter().map(|framework| framework.name).collect();
+        assert_eq!(names, vec!["Angular"]);
+    }
+}
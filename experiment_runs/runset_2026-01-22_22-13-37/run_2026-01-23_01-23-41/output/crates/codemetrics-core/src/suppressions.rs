@@ -0,0 +1,405 @@
+//! Accepted issues with an optional expiry date
+//!
+//! A suppression comes either from a comment on or just above a function,
+//!
+//! ```text
+//! // codemetrics-ignore complexity until=2025-06-01 waiting on the parser rewrite
+//! # codemetrics:ignore complexity,maintainability generated code
+//! ```
+//!
+//! or from a `[[suppress]]` entry in codemetrics.toml. It names rules by ID
+//! (`CM0101`), name (`high-cyclomatic-complexity`) or category (`complexity`),
+//! separated by commas. A comment covers the function starting just below it;
+//! for issues reported at a single line (`codemetrics lint`, and the security,
+//! concurrency and resource findings of `analyze`), a comment at the end of
+//! that line or on the line above covers it too.
+//!
+//! Once `until` has passed the suppression no longer hides anything, so the
+//! issue is reported again; expired suppressions and those expiring within
+//! `DUE_SOON_DAYS` are listed as debt coming due. Every issue a suppression
+//! hides is listed in `AnalysisResults::suppressions`, so nothing disappears
+//! from reports without a trace.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::analyzers::AnalysisResults;
+use crate::ast_analyzer::FunctionAnalysis;
+use crate::core::CodeIssue;
+use crate::rules;
+
+/// Comment spellings that start a suppression
+pub const COMMENT_MARKERS: &[&str] = &["codemetrics-ignore", "codemetrics:ignore"];
+
+/// Suppressions expiring within this many days are listed before they lapse
+pub const DUE_SOON_DAYS: i64 = 30;
+
+/// How far above a function's first line a comment still applies, to step over attributes and decorators
+const COMMENT_REACH: u32 = 3;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suppression {
+    /// Rule ID, rule name or category
+    pub rule: String,
+    /// File or directory the entry covers; every file when unset
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Only this function; every function (and the file itself) when unset
+    #[serde(default)]
+    pub function: Option<String>,
+    /// Last day the suppression applies
+    #[serde(default)]
+    pub until: Option<NaiveDate>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// A suppression that has lapsed or is about to
+#[derive(Debug, Clone, Serialize)]
+pub struct DebtDue {
+    pub rule: String,
+    pub file_path: String,
+    pub function: Option<String>,
+    pub until: NaiveDate,
+    /// The issue is reported again
+    pub expired: bool,
+    pub reason: Option<String>,
+    /// `comment` or `config`
+    pub source: &'static str,
+}
+
+/// An issue a suppression hid, so reports can list what is not shown
+#[derive(Debug, Clone, Serialize)]
+pub struct SuppressedIssue {
+    pub rule: String,
+    pub file_path: String,
+    pub function: Option<String>,
+    pub line: u32,
+    pub until: Option<NaiveDate>,
+    pub reason: Option<String>,
+    /// `comment` or `config`
+    pub source: &'static str,
+}
+
+impl Suppression {
+    /// Parse the text following `codemetrics-ignore` (or `codemetrics:ignore`) on a comment line
+    pub fn parse_comment(line: &str) -> Option<Result<Self>> {
+        let (start, marker) = COMMENT_MARKERS.iter()
+            .filter_map(|marker| line.find(marker).map(|start| (start, marker)))
+            .min()?;
+        let rest = &line[start + marker.len()..];
+        let mut words = rest.split_whitespace()
+            .map(|word| word.trim_end_matches("*/").trim_end_matches("-->"))
+            .filter(|word| !word.is_empty());
+        let rule = words.next()?.to_string();
+
+        let mut until = None;
+        let mut reason = Vec::new();
+        for word in words {
+            match word.strip_prefix("until=") {
+                Some(date) => match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                    Ok(date) => until = Some(date),
+                    Err(e) => return Some(Err(e).with_context(|| format!("Invalid suppression date '{}'", date))),
+                },
+                None => reason.push(word),
+            }
+        }
+        Some(Ok(Self {
+            rule,
+            path: None,
+            function: None,
+            until,
+            reason: (!reason.is_empty()).then(|| reason.join(" ")),
+        }))
+    }
+
+    fn covers_rule(&self, rule_id: &str) -> bool {
+        self.rule.split(',').map(str::trim).any(|key| {
+            // Custom rules from `.codemetrics/rules.toml` are only known by their own ID
+            key == rule_id
+                || rules::find_rule(key).is_some_and(|rule| rule.id == rule_id)
+                || rules::RULES.iter().any(|rule| rule.id == rule_id && rule.category == key)
+        })
+    }
+
+    fn covers_path(&self, file_path: &str) -> bool {
+        self.path.as_ref().is_none_or(|path| normalized(Path::new(file_path)).starts_with(normalized(path)))
+    }
+
+    fn covers_function(&self, name: Option<&str>) -> bool {
+        match (&self.function, name) {
+            (Some(function), Some(name)) => function == name,
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+/// Where an issue was found and the suppressions that might cover it
+struct Candidate<'a> {
+    file_path: &'a str,
+    function: Option<&'a str>,
+    line: u32,
+    rule_id: &'a str,
+    from_comments: Vec<Suppression>,
+}
+
+/// Decides issues one at a time, collecting what was hidden and what is coming due
+struct Decider<'a> {
+    configured: &'a [Suppression],
+    today: NaiveDate,
+    suppressed: Vec<SuppressedIssue>,
+    due: Vec<DebtDue>,
+}
+
+impl Decider<'_> {
+    /// True keeps the issue; every dated suppression that matched is checked for expiry
+    fn keep(&mut self, candidate: Candidate) -> bool {
+        let Candidate { file_path, function, line, rule_id, from_comments } = candidate;
+        let configured = self.configured.iter()
+            .filter(|entry| entry.covers_rule(rule_id) && entry.covers_path(file_path) && entry.covers_function(function))
+            .map(|entry| (entry.clone(), "config"));
+        let commented = from_comments.into_iter()
+            .filter(|entry| entry.covers_rule(rule_id))
+            .map(|entry| (entry, "comment"));
+
+        let mut hidden_by = None;
+        for (entry, source) in configured.chain(commented) {
+            let Some(until) = entry.until else {
+                hidden_by.get_or_insert((entry, source));
+                continue;
+            };
+            let expired = until < self.today;
+            if (until - self.today).num_days() <= DUE_SOON_DAYS {
+                self.due.push(DebtDue {
+                    rule: rule_id.to_string(),
+                    file_path: file_path.to_string(),
+                    function: function.map(str::to_string),
+                    until,
+                    expired,
+                    reason: entry.reason.clone(),
+                    source,
+                });
+            }
+            if !expired {
+                hidden_by.get_or_insert((entry, source));
+            }
+        }
+
+        let Some((entry, source)) = hidden_by else {
+            return true;
+        };
+        self.suppressed.push(SuppressedIssue {
+            rule: rule_id.to_string(),
+            file_path: file_path.to_string(),
+            function: function.map(str::to_string),
+            line,
+            until: entry.until,
+            reason: entry.reason,
+            source,
+        });
+        false
+    }
+}
+
+/// Hide suppressed issues in `results`, listing them in `suppressions` and lapsed and soon-to-lapse suppressions in `debt_due`
+pub fn apply(results: &mut AnalysisResults, configured: &[Suppression], today: NaiveDate) {
+    let mut comments = CommentCache::default();
+    let mut decider = Decider { configured, today, suppressed: Vec::new(), due: Vec::new() };
+
+    results.high_complexity_functions.retain(|function| decider.keep(Candidate {
+        file_path: &function.file_path,
+        function: Some(&function.name),
+        line: function.line_start,
+        rule_id: rules::HIGH_CYCLOMATIC_COMPLEXITY,
+        from_comments: above(comments.of(&function.file_path), function.line_start),
+    }));
+    results.high_complexity_files.retain(|file| decider.keep(Candidate {
+        file_path: &file.file_path,
+        function: None,
+        line: 1,
+        rule_id: rules::HIGH_FILE_COMPLEXITY,
+        from_comments: Vec::new(),
+    }));
+    results.issues.retain(|found| decider.keep(Candidate {
+        file_path: &found.file_path,
+        function: None,
+        line: found.issue.line,
+        rule_id: &found.issue.rule_id,
+        from_comments: at_line(comments.of(&found.file_path), found.issue.line),
+    }));
+
+    decider.due.sort_by(|a, b| a.until.cmp(&b.until).then_with(|| a.file_path.cmp(&b.file_path)));
+    results.suppressed = decider.suppressed.len();
+    results.suppressions = decider.suppressed;
+    results.debt_due = decider.due;
+}
+
+/// Drop `issues` covered by a suppression comment in `content`, returning what was dropped
+///
+/// An issue reported at a function's first line is covered like that function;
+/// any other issue by a comment on its own line or the line above.
+pub fn apply_comments(file_path: &str, content: &str, functions: &[FunctionAnalysis], issues: &mut Vec<CodeIssue>, today: NaiveDate) -> Vec<SuppressedIssue> {
+    let comments = parse_comments(file_path, content);
+    let mut decider = Decider { configured: &[], today, suppressed: Vec::new(), due: Vec::new() };
+
+    issues.retain(|issue| {
+        let function = functions.iter().find(|function| function.start_line == issue.line);
+        decider.keep(Candidate {
+            file_path,
+            function: function.map(|function| function.name.as_str()),
+            line: issue.line,
+            rule_id: &issue.rule_id,
+            from_comments: match function {
+                Some(_) => above(&comments, issue.line),
+                None => at_line(&comments, issue.line),
+            },
+        })
+    });
+    decider.suppressed
+}
+
+/// Suppression comments of each file, read once
+#[derive(Default)]
+struct CommentCache {
+    files: HashMap<String, Vec<(u32, Suppression)>>,
+}
+
+impl CommentCache {
+    fn of(&mut self, file_path: &str) -> &[(u32, Suppression)] {
+        self.files.entry(file_path.to_string()).or_insert_with(|| {
+            std::fs::read_to_string(file_path)
+                .map(|content| parse_comments(file_path, &content))
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// Every suppression comment in `content` with its line; malformed ones are reported and skipped
+fn parse_comments(file_path: &str, content: &str) -> Vec<(u32, Suppression)> {
+    content.lines()
+        .enumerate()
+        .filter_map(|(index, text)| match Suppression::parse_comment(text)? {
+            Ok(suppression) => Some((index as u32 + 1, suppression)),
+            Err(e) => {
+                eprintln!("Warning: {}:{} - {:#}", file_path, index + 1, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Suppressions commented on `line` or up to `COMMENT_REACH` lines above it
+fn above(comments: &[(u32, Suppression)], line: u32) -> Vec<Suppression> {
+    comments.iter()
+        .filter(|(comment_line, _)| *comment_line <= line && line - comment_line <= COMMENT_REACH)
+        .map(|(_, suppression)| suppression.clone())
+        .collect()
+}
+
+/// Suppressions commented at the end of `line` or on the line above it
+fn at_line(comments: &[(u32, Suppression)], line: u32) -> Vec<Suppression> {
+    comments.iter()
+        .filter(|(comment_line, _)| *comment_line == line || *comment_line + 1 == line)
+        .map(|(_, suppression)| suppression.clone())
+        .collect()
+}
+
+fn normalized(path: &Path) -> PathBuf {
+    path.components().filter(|component| *component != Component::CurDir).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::{CodeAnalyzer, HighComplexityFunction};
+
+    fn function(file_path: &str, name: &str, line_start: u32) -> HighComplexityFunction {
+        HighComplexityFunction {
+            name: name.to_string(),
+            scope: String::new(),
+            file_path: file_path.to_string(),
+            complexity: 20,
+            line_start,
+            line_end: line_start + 30,
+            parameters: 1,
+            stability: None,
+            snippet: None,
+        }
+    }
+
+    #[test]
+    fn test_expired_suppressions_resurface_as_debt() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file = temp_dir.path().join("lib.rs");
+        std::fs::write(&file, "\
+// codemetrics-ignore complexity until=2025-06-01 parser rewrite
+fn parse() {}
+
+// codemetrics-ignore CM0101 until=2024-01-01
+fn render() {}
+
+// codemetrics-ignore high-cyclomatic-complexity
+fn layout() {}
+").unwrap();
+        let file = file.to_string_lossy().into_owned();
+
+        let mut results = CodeAnalyzer::new().aggregate_results(Vec::new()).unwrap();
+        results.high_complexity_functions = vec![
+            function(&file, "parse", 2),
+            function(&file, "render", 5),
+            function(&file, "layout", 8),
+            function("./src/legacy.rs", "convert", 1),
+        ];
+        let configured = [Suppression {
+            rule: "complexity".to_string(),
+            path: Some(PathBuf::from("src")),
+            function: None,
+            until: Some(NaiveDate::from_ymd_opt(2030, 1, 1).unwrap()),
+            reason: None,
+        }];
+
+        apply(&mut results, &configured, NaiveDate::from_ymd_opt(2025, 5, 20).unwrap());
+
+        let remaining: Vec<&str> = results.high_complexity_functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(remaining, vec!["render"]);
+        assert_eq!(results.suppressed, 3);
+        let due: Vec<(&str, bool)> = results.debt_due.iter()
+            .map(|debt| (debt.function.as_deref().unwrap(), debt.expired))
+            .collect();
+        assert_eq!(due, vec![("render", true), ("parse", false)]);
+        assert_eq!(results.debt_due[1].reason.as_deref(), Some("parser rewrite"));
+        let listed: Vec<(&str, &str)> = results.suppressions.iter()
+            .map(|suppressed| (suppressed.function.as_deref().unwrap(), suppressed.source))
+            .collect();
+        assert_eq!(listed, vec![("parse", "comment"), ("layout", "comment"), ("convert", "config")]);
+    }
+
+    #[test]
+    fn test_line_and_function_comments_in_python() -> Result<()> {
+        let content = "\
+# codemetrics:ignore complexity,maintainability generated
+def convert(a, b, c, d, e, f):
+    return eval(a)  # codemetrics:ignore CM0301 trusted input
+";
+        let mut analyzer = crate::ASTAnalyzer::new()?.with_thresholds(crate::thresholds::Thresholds {
+            max_parameters: 2,
+            ..Default::default()
+        });
+        let (_, mut issues, functions, _) = analyzer.analyze_file(content, &crate::Language::Python, Path::new("gen.py"))?;
+        assert!(issues.iter().any(|issue| issue.rule_id == rules::TOO_MANY_PARAMETERS));
+
+        let today = NaiveDate::from_ymd_opt(2025, 5, 20).unwrap();
+        let suppressed = apply_comments("gen.py", content, &functions, &mut issues, today);
+
+        assert!(issues.is_empty(), "{:?}", issues);
+        let hidden: Vec<(&str, u32)> = suppressed.iter().map(|issue| (issue.rule.as_str(), issue.line)).collect();
+        assert_eq!(hidden, vec![(rules::TOO_MANY_PARAMETERS, 2), (rules::SECURITY_PATTERN, 3)]);
+        assert_eq!(suppressed[1].reason.as_deref(), Some("trusted input"));
+        Ok(())
+    }
+}
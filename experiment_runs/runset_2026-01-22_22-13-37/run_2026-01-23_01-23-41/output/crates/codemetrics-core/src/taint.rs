@@ -0,0 +1,450 @@
+//! Heuristic tracking of user input into injection sinks
+//!
+//! Identifiers that look like request data (`req`, `params`, `user_input`) and
+//! well-known input APIs (`location.search`, `sys.argv`, `env::args()`) are
+//! sources. Taint follows plain assignments in source order, into closures
+//! defined later in the same function, and stops at calls that look like a
+//! sanitizer or conversion (`escape`, `int`, `.parse()`). A flow is a tainted
+//! value reaching a query builder, an HTML insertion or a shell command.
+//!
+//! Nothing crosses function boundaries, fields or containers, so this finds the
+//! obvious cases and misses the rest. It points reviewers at code worth
+//! reading; it does not prove any code safe.
+
+use std::collections::HashMap;
+use tree_sitter::Node;
+
+use crate::ast_analyzer::{excerpt, walk_descendants};
+use crate::core::IssueLocation;
+
+/// Names that look like user input, lowercased with `_` removed
+const SOURCE_NAMES: &[&str] = &[
+    "req", "request", "params", "form", "formdata", "input", "userinput", "rawinput", "payload", "cookies",
+    "argv", "stdin", "gets", "searchparams", "queryparams", "querystring",
+];
+
+/// Input APIs, matched against the end of a member path
+const SOURCE_APIS: &[&str] = &[
+    "location.search", "location.hash", "location.href", "document.cookie", "document.URL", "document.referrer",
+    "window.name", "sys.argv", "sys.stdin", "os.environ", "env::args", "env::var", "env::var_os", "io::stdin",
+];
+
+/// Callee names (lowercased, `_` removed) whose result no longer carries the input as-is
+const CONVERSIONS: &[&str] = &[
+    "int", "float", "bool", "len", "parseint", "parsefloat", "number", "parse", "toi", "tof", "length", "count",
+];
+const SANITIZER_WORDS: &[&str] = &["escape", "sanitize", "quote", "purify", "encode", "clean"];
+
+const FUNCTION_KINDS: &[&str] = &[
+    "function_declaration", "function_expression", "function", "arrow_function", "method_definition",
+    "generator_function_declaration", "function_definition", "lambda", "method", "singleton_method", "block",
+    "do_block", "function_item", "closure_expression",
+];
+const MEMBER_KINDS: &[&str] = &["member_expression", "attribute", "field_expression", "scoped_identifier"];
+/// Nodes whose first child is the value they are taken from, for showing whole paths like `req.query.id`
+const CHAIN_KINDS: &[&str] = &[
+    "member_expression", "attribute", "field_expression", "scoped_identifier", "subscript_expression", "subscript",
+    "element_reference", "index_expression", "call_expression", "call",
+];
+const SQL_METHODS: &[&str] = &[
+    "query", "execute", "raw", "executemany", "executescript", "queryRawUnsafe", "$queryRawUnsafe",
+    "executeRawUnsafe", "$executeRawUnsafe", "query_as", "query_scalar", "prepare", "sql_query",
+];
+const RUBY_SQL_METHODS: &[&str] = &[
+    "where", "having", "order", "find_by_sql", "execute", "exec_query", "select_all", "select_value",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkKind {
+    Sql,
+    Html,
+    Command,
+}
+
+impl SinkKind {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            SinkKind::Sql => "an SQL query",
+            SinkKind::Html => "HTML inserted into the page",
+            SinkKind::Command => "a shell command",
+        }
+    }
+
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            SinkKind::Sql => "Keep the SQL constant and pass the value as a bound query parameter",
+            SinkKind::Html => "Escape the value, or set textContent instead of inserting markup",
+            SinkKind::Command => "Run the program directly with separate arguments, not through a shell, and check the value against an allow-list",
+        }
+    }
+}
+
+/// User input reaching a sink within one function
+#[derive(Debug, Clone)]
+pub struct TaintFlow {
+    pub kind: SinkKind,
+    pub source: IssueLocation,
+    pub sink: IssueLocation,
+}
+
+/// Every source-to-sink flow in a file parsed with `grammar`, in source order
+pub fn trace(root: Node, content: &str, grammar: &str) -> Vec<TaintFlow> {
+    let tracer = Tracer { content, grammar };
+    // Innermost function last; each scope starts from what its enclosing one had
+    let mut scopes: Vec<(usize, HashMap<String, IssueLocation>)> = vec![(usize::MAX, HashMap::new())];
+    let mut flows = Vec::new();
+
+    walk_descendants(root, |node, _| {
+        while scopes.last().is_some_and(|(end, _)| *end <= node.start_byte()) {
+            scopes.pop();
+        }
+        if FUNCTION_KINDS.contains(&node.kind()) {
+            let inherited = scopes.last().map(|(_, scope)| scope.clone()).unwrap_or_default();
+            scopes.push((node.end_byte(), inherited));
+        }
+        let Some((_, scope)) = scopes.last_mut() else {
+            return true;
+        };
+
+        if let Some((targets, value, augmented)) = assignment(node) {
+            let taint = tracer.taint_in(value, scope);
+            for target in targets {
+                let name = tracer.text(target).to_string();
+                match &taint {
+                    Some(source) => {
+                        scope.insert(name, source.clone());
+                    }
+                    None if !augmented => {
+                        scope.remove(&name);
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        if let Some((kind, inputs)) = tracer.sink(node) {
+            if let Some(source) = inputs.into_iter().find_map(|input| tracer.taint_in(input, scope)) {
+                flows.push(TaintFlow { kind, source, sink: tracer.location(node) });
+            }
+        }
+        true
+    });
+
+    flows
+}
+
+/// The names an assignment binds, the value it binds them to, and whether it only adds to them (`+=`)
+fn assignment(node: Node) -> Option<(Vec<Node>, Node, bool)> {
+    let (target, value, augmented) = match node.kind() {
+        "variable_declarator" => (node.child_by_field_name("name")?, node.child_by_field_name("value")?, false),
+        "let_declaration" => (node.child_by_field_name("pattern")?, node.child_by_field_name("value")?, false),
+        "assignment_expression" | "assignment" => (node.child_by_field_name("left")?, node.child_by_field_name("right")?, false),
+        "augmented_assignment_expression" | "augmented_assignment" | "operator_assignment" | "compound_assignment_expr" => {
+            (node.child_by_field_name("left")?, node.child_by_field_name("right")?, true)
+        }
+        _ => return None,
+    };
+    // Fields and elements are not tracked
+    if CHAIN_KINDS.contains(&target.kind()) {
+        return None;
+    }
+    let mut names = Vec::new();
+    walk_descendants(target, |node, _| {
+        if matches!(node.kind(), "identifier" | "shorthand_property_identifier_pattern") {
+            names.push(node);
+        }
+        true
+    });
+    Some((names, value, augmented))
+}
+
+struct Tracer<'a> {
+    content: &'a str,
+    grammar: &'a str,
+}
+
+impl<'a> Tracer<'a> {
+    fn text(&self, node: Node) -> &'a str {
+        &self.content[node.byte_range()]
+    }
+
+    fn location(&self, node: Node) -> IssueLocation {
+        let position = node.start_position();
+        IssueLocation {
+            line: position.row as u32 + 1,
+            column: position.column as u32 + 1,
+            excerpt: excerpt(self.text(node)),
+        }
+    }
+
+    /// Where the first input inside `node` comes from, skipping sanitized subexpressions
+    fn taint_in(&self, node: Node, scope: &HashMap<String, IssueLocation>) -> Option<IssueLocation> {
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            if self.is_sanitizer(node) {
+                continue;
+            }
+            if let Some(source) = self.source(node, scope) {
+                return Some(source);
+            }
+            let mut cursor = node.walk();
+            let children: Vec<Node> = node.named_children(&mut cursor).collect();
+            stack.extend(children.into_iter().rev());
+        }
+        None
+    }
+
+    /// `node` itself as a source of input, directly or through a tainted variable
+    fn source(&self, node: Node, scope: &HashMap<String, IssueLocation>) -> Option<IssueLocation> {
+        let kind = node.kind();
+        if MEMBER_KINDS.contains(&kind) {
+            let text = self.text(node);
+            let api = SOURCE_APIS.iter()
+                .any(|api| text == *api || text.ends_with(&format!(".{}", api)) || text.ends_with(&format!("::{}", api)));
+            // `self.request` in a Django view, `this.req` in a class
+            let own_field = node.child(0).is_some_and(|object| matches!(self.text(object), "self" | "this"))
+                && node.named_child(node.named_child_count().saturating_sub(1))
+                    .is_some_and(|field| looks_like_input(self.text(field)));
+            return (api || own_field).then(|| self.location(self.chain(node)));
+        }
+        if !matches!(kind, "identifier" | "constant") || self.is_field_name(node) {
+            return None;
+        }
+        let name = self.text(node);
+        if looks_like_input(name) {
+            return Some(self.location(self.chain(node)));
+        }
+        scope.get(name).cloned()
+    }
+
+    /// The whole access path starting at `node`, e.g. `req.query.id` from `req`
+    fn chain<'t>(&self, mut node: Node<'t>) -> Node<'t> {
+        while let Some(parent) = node.parent() {
+            if !CHAIN_KINDS.contains(&parent.kind()) || parent.named_child(0) != Some(node) {
+                break;
+            }
+            node = parent;
+        }
+        node
+    }
+
+    /// A property or method name rather than a variable, e.g. `input` in `form.input`
+    fn is_field_name(&self, node: Node) -> bool {
+        let Some(parent) = node.parent() else {
+            return false;
+        };
+        ["attribute", "method", "field", "property", "name"].iter()
+            .any(|field| parent.child_by_field_name(field) == Some(node))
+    }
+
+    fn is_sanitizer(&self, node: Node) -> bool {
+        let Some((_, name)) = self.callee(node) else {
+            return false;
+        };
+        let name = normalize(name);
+        CONVERSIONS.contains(&name.as_str()) || SANITIZER_WORDS.iter().any(|word| name.contains(word))
+    }
+
+    /// Full text and final name of what `node` calls, when it is a call
+    fn callee(&self, node: Node) -> Option<(&'a str, &'a str)> {
+        let function = match node.kind() {
+            "call_expression" => node.child_by_field_name("function")?,
+            "call" if self.grammar == "ruby" => {
+                let method = node.child_by_field_name("method")?;
+                let full = &self.content[node.start_byte()..method.end_byte()];
+                return Some((full, self.text(method)));
+            }
+            "call" => node.child_by_field_name("function")?,
+            _ => return None,
+        };
+        let full = self.text(function);
+        let name = match function.kind() {
+            "member_expression" => function.child_by_field_name("property").map(|property| self.text(property)),
+            "attribute" => function.child_by_field_name("attribute").map(|attribute| self.text(attribute)),
+            "field_expression" => function.child_by_field_name("field").map(|field| self.text(field)),
+            "scoped_identifier" => function.child_by_field_name("name").map(|name| self.text(name)),
+            "generic_function" => function.child_by_field_name("function").map(|inner| self.text(inner).rsplit(['.', ':']).next().unwrap_or_default()),
+            _ => Some(full),
+        };
+        Some((full, name.unwrap_or(full)))
+    }
+
+    /// The kind of sink `node` is, with the expressions that must not carry input
+    fn sink<'t>(&self, node: Node<'t>) -> Option<(SinkKind, Vec<Node<'t>>)> {
+        // `el.innerHTML = ...`
+        if matches!(node.kind(), "assignment_expression" | "augmented_assignment_expression") && self.grammar == "javascript" {
+            let left = node.child_by_field_name("left")?;
+            let property = left.child_by_field_name("property")?;
+            return matches!(self.text(property), "innerHTML" | "outerHTML")
+                .then(|| (SinkKind::Html, node.child_by_field_name("right").into_iter().collect()));
+        }
+        // Ruby backticks
+        if node.kind() == "subshell" {
+            return Some((SinkKind::Command, vec![node]));
+        }
+
+        let (full, name) = self.callee(node)?;
+        let arguments = arguments(node);
+        let first = || arguments.first().copied().into_iter().collect::<Vec<_>>();
+        match self.grammar {
+            "javascript" => {
+                let object = full.rsplit_once('.').map(|(object, _)| object);
+                if SQL_METHODS.contains(&name) && object.is_some() {
+                    Some((SinkKind::Sql, first()))
+                } else if matches!(name, "exec" | "execSync" | "spawn" | "spawnSync")
+                    && matches!(object, None | Some("child_process" | "childProcess" | "cp"))
+                {
+                    Some((SinkKind::Command, first()))
+                } else if matches!(name, "write" | "writeln") && object == Some("document") {
+                    Some((SinkKind::Html, arguments))
+                } else if name == "insertAdjacentHTML" {
+                    Some((SinkKind::Html, arguments.get(1).copied().into_iter().collect()))
+                } else {
+                    None
+                }
+            }
+            "python" => {
+                if SQL_METHODS.contains(&name) && full.contains('.') {
+                    Some((SinkKind::Sql, first()))
+                } else if matches!(full, "os.system" | "os.popen")
+                    || (full.starts_with("subprocess.") && arguments_text(node, self.content).contains("shell=True"))
+                {
+                    Some((SinkKind::Command, first()))
+                } else if matches!(name, "Markup" | "mark_safe" | "render_template_string") {
+                    Some((SinkKind::Html, first()))
+                } else {
+                    None
+                }
+            }
+            "ruby" => {
+                let receiver = node.child_by_field_name("receiver");
+                if RUBY_SQL_METHODS.contains(&name) {
+                    // `where(name: value)` binds the value
+                    Some((SinkKind::Sql, first().into_iter().filter(|argument| argument.kind() != "pair").collect()))
+                } else if matches!(name, "system" | "exec" | "spawn")
+                    && matches!(receiver.map(|receiver| self.text(receiver)), None | Some("Kernel" | "Process"))
+                {
+                    Some((SinkKind::Command, first()))
+                } else if name == "html_safe" {
+                    Some((SinkKind::Html, receiver.into_iter().collect()))
+                } else if name == "raw" && receiver.is_none() {
+                    Some((SinkKind::Html, first()))
+                } else {
+                    None
+                }
+            }
+            "rust" => {
+                if SQL_METHODS.contains(&name) && full != name {
+                    Some((SinkKind::Sql, first()))
+                } else if full.ends_with("Command::new") {
+                    Some((SinkKind::Command, first()))
+                } else if matches!(name, "arg" | "args") && first().iter().any(|argument| self.has_format(*argument)) {
+                    // Only a command line built with `format!` is likely to reach a shell as one string
+                    Some((SinkKind::Command, first()))
+                } else if matches!(name, "Html" | "PreEscaped") {
+                    Some((SinkKind::Html, first()))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn has_format(&self, node: Node) -> bool {
+        let mut found = false;
+        walk_descendants(node, |node, _| {
+            if node.kind() == "macro_invocation"
+                && node.child_by_field_name("macro").is_some_and(|name| self.text(name) == "format")
+            {
+                found = true;
+            }
+            !found
+        });
+        found
+    }
+}
+
+fn looks_like_input(name: &str) -> bool {
+    SOURCE_NAMES.contains(&normalize(name).as_str())
+}
+
+fn normalize(name: &str) -> String {
+    name.trim_start_matches('$').replace('_', "").to_lowercase()
+}
+
+/// A call's arguments, without keyword arguments and comments
+fn arguments(call: Node) -> Vec<Node> {
+    let Some(list) = call.child_by_field_name("arguments") else {
+        return Vec::new();
+    };
+    let mut cursor = list.walk();
+    let arguments = list.named_children(&mut cursor)
+        .filter(|argument| !matches!(argument.kind(), "keyword_argument" | "comment"))
+        .collect();
+    arguments
+}
+
+fn arguments_text<'a>(call: Node, content: &'a str) -> &'a str {
+    call.child_by_field_name("arguments").map_or("", |list| &content[list.byte_range()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree_sitter::Parser;
+
+    fn flows(language: tree_sitter::Language, grammar: &str, source: &str) -> Vec<(SinkKind, u32, u32, String)> {
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        trace(tree.root_node(), source, grammar).into_iter()
+            .map(|flow| (flow.kind, flow.source.line, flow.sink.line, flow.source.excerpt))
+            .collect()
+    }
+
+    #[test]
+    fn test_input_is_followed_through_assignments_to_sinks() {
+        let js = "\
+app.get('/user', (req, res) => {
+  const id = req.query.id;
+  const sql = `SELECT * FROM users WHERE id = ${id}`;
+  db.query(sql);
+  db.query('SELECT * FROM users WHERE id = ?', [id]);
+  const safe = escapeHtml(req.query.name);
+  el.innerHTML = safe;
+  el.innerHTML = req.query.name;
+});
+";
+        assert_eq!(flows(tree_sitter::Language::new(tree_sitter_javascript::LANGUAGE), "javascript", js), vec![
+            (SinkKind::Sql, 2, 4, "req.query.id".to_string()),
+            (SinkKind::Html, 8, 8, "req.query.name".to_string()),
+        ]);
+
+        let python = "\
+def search(request):
+    term = request.args.get('q')
+    cursor.execute(\"SELECT * FROM t WHERE name = '%s'\" % term)
+    cursor.execute(\"SELECT * FROM t WHERE name = %s\", (term,))
+    limit = int(term)
+    cursor.execute(\"SELECT * FROM t LIMIT \" + str(limit))
+    os.system('grep ' + term)
+";
+        assert_eq!(flows(tree_sitter::Language::new(tree_sitter_python::LANGUAGE), "python", python), vec![
+            (SinkKind::Sql, 2, 3, "request.args.get('q')".to_string()),
+            (SinkKind::Command, 2, 7, "request.args.get('q')".to_string()),
+        ]);
+
+        let rust = "\
+fn run() {
+    let name = std::env::args().nth(1).unwrap();
+    let cmd = Command::new(\"sh\").arg(\"-c\").arg(format!(\"ls {}\", name)).output();
+    let n: u32 = name.parse().unwrap();
+    Command::new(\"ls\").arg(format!(\"-{}\", n));
+}
+";
+        assert_eq!(flows(tree_sitter::Language::new(tree_sitter_rust::LANGUAGE), "rust", rust), vec![
+            (SinkKind::Command, 2, 3, "std::env::args().nth(1).unwrap()".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,276 @@
+//! Named quality thresholds shared by analysis, issue detection and reporting
+//!
+//! Function-level and file-level complexity are judged separately: a file made
+//! of many small functions can be too complex as a whole even though none of
+//! its functions crosses the per-function limit.
+//!
+//! Function severity can also come from the project's own distribution
+//! (`SeverityBands::Percentile`), so a large legacy codebase only flags its
+//! worst outliers instead of everything above a fixed number.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::core::IssueSeverity;
+
+/// How far a measurement is past its thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum ThresholdLevel {
+    Ok,
+    Warn,
+    Error,
+    /// Only reachable when percentile or pinned severity bands are in use
+    Critical,
+}
+
+impl ThresholdLevel {
+    /// The level an issue of `severity` is reported at, e.g. after `--rule-severity`
+    pub fn of_severity(severity: &IssueSeverity) -> Self {
+        match severity {
+            IssueSeverity::Critical => Self::Critical,
+            IssueSeverity::Error => Self::Error,
+            IssueSeverity::Warning => Self::Warn,
+            IssueSeverity::Info => Self::Ok,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thresholds {
+    /// Cyclomatic complexity at which a function becomes a hotspot
+    pub function_complexity_warn: u32,
+    pub function_complexity_error: u32,
+    /// Summed complexity of all functions in a single file
+    pub file_complexity_warn: u32,
+    pub file_complexity_error: u32,
+    /// Deepest acceptable block nesting inside a function
+    pub max_nesting_depth: u32,
+    pub max_parameters: u32,
+    /// Longest acceptable function body, in lines of code
+    pub max_function_lines: u32,
+    /// Most trait bounds acceptable in one Rust signature
+    #[serde(default = "default_max_generic_bounds")]
+    pub max_generic_bounds: u32,
+    /// Set when severity comes from percentile or pinned bands
+    #[serde(default)]
+    pub function_complexity_critical: Option<u32>,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            function_complexity_warn: 10,
+            function_complexity_error: 15,
+            file_complexity_warn: 50,
+            file_complexity_error: 100,
+            max_nesting_depth: 5,
+            max_parameters: 7,
+            max_function_lines: 50,
+            max_generic_bounds: default_max_generic_bounds(),
+            function_complexity_critical: None,
+        }
+    }
+}
+
+fn default_max_generic_bounds() -> u32 {
+    5
+}
+
+/// Names accepted by `Thresholds::preset`
+pub const PRESETS: [&str; 3] = ["strict", "default", "relaxed"];
+
+impl Thresholds {
+    /// Built-in threshold sets: `strict` for new code, `default`, and `relaxed` for legacy code
+    pub fn preset(name: &str) -> Result<Self> {
+        match name {
+            "strict" => Ok(Self {
+                function_complexity_warn: 8,
+                function_complexity_error: 12,
+                file_complexity_warn: 40,
+                file_complexity_error: 80,
+                max_nesting_depth: 4,
+                max_parameters: 5,
+                max_function_lines: 40,
+                max_generic_bounds: 4,
+                function_complexity_critical: None,
+            }),
+            "default" => Ok(Self::default()),
+            "relaxed" => Ok(Self {
+                function_complexity_warn: 15,
+                function_complexity_error: 25,
+                file_complexity_warn: 80,
+                file_complexity_error: 150,
+                max_nesting_depth: 6,
+                max_parameters: 8,
+                max_function_lines: 80,
+                max_generic_bounds: 6,
+                function_complexity_critical: None,
+            }),
+            other => anyhow::bail!("Unknown threshold preset '{}', expected one of: {}", other, PRESETS.join(", ")),
+        }
+    }
+
+    /// Reject warn/error pairs that are in the wrong order
+    pub fn validate(&self) -> Result<()> {
+        if self.function_complexity_warn > self.function_complexity_error {
+            anyhow::bail!("function complexity warn threshold must not exceed the error threshold");
+        }
+        if self.file_complexity_warn > self.file_complexity_error {
+            anyhow::bail!("file complexity warn threshold must not exceed the error threshold");
+        }
+        Ok(())
+    }
+
+    pub fn function_level(&self, complexity: u32) -> ThresholdLevel {
+        match self.function_complexity_critical {
+            Some(critical) if complexity >= critical => ThresholdLevel::Critical,
+            _ => level(complexity, self.function_complexity_warn, self.function_complexity_error),
+        }
+    }
+
+    pub fn file_level(&self, total_complexity: u32) -> ThresholdLevel {
+        level(total_complexity, self.file_complexity_warn, self.file_complexity_error)
+    }
+
+    /// Copy of these thresholds with function bands replaced by `cutoffs`
+    pub fn with_cutoffs(&self, cutoffs: BandCutoffs) -> Self {
+        Self {
+            function_complexity_warn: cutoffs.warn,
+            function_complexity_error: cutoffs.error,
+            function_complexity_critical: Some(cutoffs.critical),
+            ..self.clone()
+        }
+    }
+}
+
+/// Thresholds that replace the project-wide ones for files under `directory`
+///
+/// These keep their fixed values even when percentile or pinned bands are in use.
+#[derive(Debug, Clone)]
+pub struct DirectoryThresholds {
+    pub directory: PathBuf,
+    pub thresholds: Thresholds,
+}
+
+/// Thresholds for `path`: the most specific matching directory's, else `base`
+pub fn thresholds_for<'a>(path: &Path, base: &'a Thresholds, directories: &'a [DirectoryThresholds]) -> &'a Thresholds {
+    directories.iter()
+        .filter(|entry| path.starts_with(&entry.directory))
+        .max_by_key(|entry| entry.directory.components().count())
+        .map_or(base, |entry| &entry.thresholds)
+}
+
+/// Where function severity cutoffs come from
+#[derive(Debug, Clone, Default)]
+pub enum SeverityBands {
+    /// Use the configured `Thresholds` as-is
+    #[default]
+    Fixed,
+    /// Derive cutoffs from this run's complexity distribution
+    Percentile(PercentileBands),
+    /// Reuse cutoffs recorded by an earlier run
+    Pinned(BandCutoffs),
+}
+
+/// Percentiles of the function complexity distribution at which each band starts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PercentileBands {
+    pub warn: f64,
+    pub error: f64,
+    pub critical: f64,
+}
+
+impl Default for PercentileBands {
+    fn default() -> Self {
+        Self {
+            warn: 90.0,
+            error: 97.0,
+            critical: 99.0,
+        }
+    }
+}
+
+/// Concrete complexity cutoffs, as produced by percentile bands or pinned from a previous run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandCutoffs {
+    pub warn: u32,
+    pub error: u32,
+    pub critical: u32,
+}
+
+impl PercentileBands {
+    /// Cutoffs for `complexities`, never stricter than the fixed `floor` thresholds
+    pub fn cutoffs(&self, complexities: &[u32], floor: &Thresholds) -> BandCutoffs {
+        let mut sorted = complexities.to_vec();
+        sorted.sort_unstable();
+
+        let warn = percentile(&sorted, self.warn).max(floor.function_complexity_warn);
+        let error = percentile(&sorted, self.error).max(floor.function_complexity_error).max(warn);
+        let critical = percentile(&sorted, self.critical).max(error + 1);
+
+        BandCutoffs { warn, error, critical }
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice
+fn percentile(sorted: &[u32], pct: f64) -> u32 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn level(value: u32, warn: u32, error: u32) -> ThresholdLevel {
+    if value >= error {
+        ThresholdLevel::Error
+    } else if value >= warn {
+        ThresholdLevel::Warn
+    } else {
+        ThresholdLevel::Ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_and_file_levels_are_independent() {
+        let thresholds = Thresholds::default();
+        assert_eq!(thresholds.function_level(9), ThresholdLevel::Ok);
+        assert_eq!(thresholds.function_level(10), ThresholdLevel::Warn);
+        assert_eq!(thresholds.function_level(15), ThresholdLevel::Error);
+        assert_eq!(thresholds.file_level(15), ThresholdLevel::Ok);
+        assert_eq!(thresholds.file_level(100), ThresholdLevel::Error);
+    }
+
+    #[test]
+    fn test_validate_rejects_inverted_pairs() {
+        assert!(Thresholds::default().validate().is_ok());
+        let inverted = Thresholds { file_complexity_warn: 200, ..Thresholds::default() };
+        assert!(inverted.validate().is_err());
+    }
+
+    #[test]
+    fn test_percentile_bands_only_flag_outliers() {
+        let floor = Thresholds::default();
+        // A legacy codebase where a tenth of all functions sit at complexity 40
+        let mut complexities = vec![40; 100];
+        complexities.extend(vec![3; 900]);
+        complexities.push(90);
+
+        let cutoffs = PercentileBands::default().cutoffs(&complexities, &floor);
+        assert_eq!(cutoffs.warn, 40);
+        assert!(cutoffs.critical > cutoffs.error);
+
+        let banded = floor.with_cutoffs(cutoffs);
+        assert_eq!(banded.function_level(20), ThresholdLevel::Ok);
+        assert_eq!(banded.function_level(90), ThresholdLevel::Critical);
+
+        // A tidy codebase keeps the fixed thresholds as a floor
+        let tidy = PercentileBands::default().cutoffs(&[1, 2, 3], &floor);
+        assert_eq!((tidy.warn, tidy.error), (10, 15));
+    }
+}
@@ -0,0 +1,580 @@
+//! Class- and struct-level metrics: size, cohesion and inheritance
+//!
+//! For every class (Python, JavaScript, Ruby) and struct or enum (Rust, Go)
+//! this records its method and field counts, the weighted methods per class
+//! (WMC, the summed cyclomatic complexity of its methods), its depth of
+//! inheritance (DIT) where the language has class inheritance, and LCOM4: the
+//! number of groups of instance methods that share no field and call no method
+//! of another group. 1 is cohesive; 3 suggests a type doing three jobs.
+//!
+//! Rust `impl` blocks and Go methods are declared apart from their type, often
+//! in another file. They are matched to the declaration of that name in the
+//! same language whose path shares the most directories with theirs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use tree_sitter::Node;
+
+use crate::ast_analyzer::{decision_weight, walk_descendants};
+use crate::error_handling::ErrorHandlingWeights;
+use crate::parsers::SupportedLanguage;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeMetrics {
+    pub name: String,
+    /// File declaring the type
+    pub file_path: String,
+    pub line: u32,
+    pub methods: usize,
+    pub fields: usize,
+    /// Weighted methods per class: the summed cyclomatic complexity of its methods
+    pub wmc: u32,
+    /// Groups of instance methods sharing no field or call (LCOM4); 0 without instance methods
+    pub lcom: usize,
+    /// Classes above it in the analyzed code, plus one for an external base; `None` without inheritance
+    pub depth_of_inheritance: Option<u32>,
+}
+
+/// A type as declared in one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Declaration {
+    language: SupportedLanguage,
+    name: String,
+    file_path: String,
+    line: u32,
+    fields: BTreeSet<String>,
+    /// Names of the classes it extends; `None` for languages without class inheritance
+    bases: Option<Vec<String>>,
+    methods: Vec<Method>,
+}
+
+/// Methods declared apart from their type: a Rust `impl` block or a Go method
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Detached {
+    language: SupportedLanguage,
+    type_name: String,
+    file_path: String,
+    methods: Vec<Method>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Method {
+    name: String,
+    complexity: u32,
+    /// Works on an instance (`self`, `this`, a receiver), so it counts toward cohesion
+    instance: bool,
+    /// Fields and methods reached through the instance
+    members: BTreeSet<String>,
+}
+
+/// The types and detached methods one file declares, see `extract`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Extracted {
+    declarations: Vec<Declaration>,
+    detached: Vec<Detached>,
+}
+
+/// Metrics of every type declared in `files`, least cohesive first
+pub fn measure(files: impl IntoIterator<Item = Extracted>) -> Vec<TypeMetrics> {
+    let mut declarations = Vec::new();
+    let mut detached = Vec::new();
+    for file in files {
+        declarations.extend(file.declarations);
+        detached.extend(file.detached);
+    }
+
+    // Methods of types declared outside the analyzed files are dropped
+    for group in detached {
+        if let Some(index) = owner(&declarations, &group) {
+            declarations[index].methods.extend(group.methods);
+        }
+    }
+
+    let depths = inheritance_depths(&declarations);
+    let mut types: Vec<TypeMetrics> = declarations.into_iter().zip(depths)
+        .map(|(declaration, depth_of_inheritance)| TypeMetrics {
+            methods: declaration.methods.len(),
+            fields: declaration.fields.len(),
+            wmc: declaration.methods.iter().map(|method| method.complexity).sum(),
+            lcom: lcom4(&declaration.methods, &declaration.fields),
+            depth_of_inheritance,
+            name: declaration.name,
+            file_path: declaration.file_path,
+            line: declaration.line,
+        })
+        .collect();
+    types.sort_by(|a, b| b.lcom.cmp(&a.lcom)
+        .then(b.wmc.cmp(&a.wmc))
+        .then_with(|| (&a.file_path, a.line).cmp(&(&b.file_path, b.line))));
+    types
+}
+
+/// The declaration `group`'s methods belong to: same name and language, nearest path
+fn owner(declarations: &[Declaration], group: &Detached) -> Option<usize> {
+    declarations.iter()
+        .enumerate()
+        .filter(|(_, declaration)| declaration.language == group.language && declaration.name == group.type_name)
+        .max_by_key(|(_, declaration)| shared_components(&declaration.file_path, &group.file_path))
+        .map(|(index, _)| index)
+}
+
+/// Leading path components `a` and `b` have in common
+pub(crate) fn shared_components(a: &str, b: &str) -> usize {
+    Path::new(a).components()
+        .zip(Path::new(b).components())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+fn inheritance_depths(declarations: &[Declaration]) -> Vec<Option<u32>> {
+    let mut by_name: HashMap<(SupportedLanguage, &str), usize> = HashMap::new();
+    for (index, declaration) in declarations.iter().enumerate() {
+        by_name.entry((declaration.language, declaration.name.as_str())).or_insert(index);
+    }
+
+    fn depth(index: usize, declarations: &[Declaration], by_name: &HashMap<(SupportedLanguage, &str), usize>, visiting: &mut Vec<usize>) -> u32 {
+        let bases = declarations[index].bases.as_deref().unwrap_or_default();
+        // A cycle can only come from two classes sharing a name
+        if bases.is_empty() || visiting.contains(&index) {
+            return 0;
+        }
+        visiting.push(index);
+        let deepest = bases.iter()
+            .map(|base| match by_name.get(&(declarations[index].language, base.as_str())) {
+                Some(&parent) => depth(parent, declarations, by_name, visiting),
+                None => 0,
+            })
+            .max()
+            .unwrap_or(0);
+        visiting.pop();
+        deepest + 1
+    }
+
+    declarations.iter()
+        .enumerate()
+        .map(|(index, declaration)| declaration.bases.as_ref().map(|_| depth(index, declarations, &by_name, &mut Vec::new())))
+        .collect()
+}
+
+/// Connected components of instance methods, linked by a shared field or a call
+fn lcom4(methods: &[Method], fields: &BTreeSet<String>) -> usize {
+    let instance: Vec<&Method> = methods.iter().filter(|method| method.instance).collect();
+    let mut parent: Vec<usize> = (0..instance.len()).collect();
+    fn root(parent: &[usize], mut index: usize) -> usize {
+        while parent[index] != index {
+            index = parent[index];
+        }
+        index
+    }
+
+    for (i, a) in instance.iter().enumerate() {
+        for (j, b) in instance.iter().enumerate().skip(i + 1) {
+            let linked = a.members.contains(&b.name)
+                || b.members.contains(&a.name)
+                || a.members.iter().any(|member| fields.contains(member) && b.members.contains(member));
+            if linked {
+                let (root_a, root_b) = (root(&parent, i), root(&parent, j));
+                parent[root_a] = root_b;
+            }
+        }
+    }
+    (0..instance.len()).filter(|&index| root(&parent, index) == index).count()
+}
+
+/// Types and detached methods declared under `root`, one file's share of `measure`
+pub fn extract(path: &str, source: &str, root: Node, language: SupportedLanguage, weights: &ErrorHandlingWeights) -> Extracted {
+    let file = SourceFile { path, source, language, weights };
+    let mut out = Extracted::default();
+    walk_descendants(root, |node, _| {
+        file.visit(node, &mut out);
+        true
+    });
+    out
+}
+
+struct SourceFile<'a> {
+    path: &'a str,
+    source: &'a str,
+    language: SupportedLanguage,
+    weights: &'a ErrorHandlingWeights,
+}
+
+impl SourceFile<'_> {
+    fn visit(&self, node: Node, out: &mut Extracted) {
+        let declaration = match (self.language, node.kind()) {
+            (SupportedLanguage::Rust, "struct_item" | "enum_item") => self.rust_type(node),
+            (SupportedLanguage::Rust, "impl_item") => {
+                out.detached.extend(self.rust_impl(node));
+                None
+            }
+            (SupportedLanguage::Go, "type_spec") => self.go_struct(node),
+            (SupportedLanguage::Go, "method_declaration") => {
+                out.detached.extend(self.go_method(node));
+                None
+            }
+            (SupportedLanguage::Python, "class_definition") => self.python_class(node),
+            // The `class` keyword token shares the kind of class expressions
+            (SupportedLanguage::JavaScript, "class_declaration" | "class") if node.is_named() => self.javascript_class(node),
+            (SupportedLanguage::Ruby, "class") if node.is_named() => self.ruby_class(node),
+            _ => None,
+        };
+        out.declarations.extend(declaration);
+    }
+
+    fn text(&self, node: Node) -> &str {
+        &self.source[node.byte_range()]
+    }
+
+    fn field_text(&self, node: Node, field: &str) -> Option<String> {
+        node.child_by_field_name(field).map(|child| self.text(child).to_string())
+    }
+
+    fn declaration(&self, node: Node, name: String, fields: BTreeSet<String>, bases: Option<Vec<String>>, methods: Vec<Method>) -> Declaration {
+        Declaration {
+            language: self.language,
+            name,
+            file_path: self.path.to_string(),
+            line: node.start_position().row as u32 + 1,
+            fields,
+            bases,
+            methods,
+        }
+    }
+
+    fn method(&self, node: Node, name: String, instance: bool, members: BTreeSet<String>) -> Method {
+        let mut complexity = 1;
+        walk_descendants(node, |node, _| {
+            complexity += decision_weight(node, self.source, self.weights);
+            true
+        });
+        Method { name, complexity, instance, members }
+    }
+
+    /// Members reached as `receiver.member` under `node`, and those among them assigned to
+    fn accesses(&self, node: Node, access: &str, object: &str, property: &str, receiver: &str) -> (BTreeSet<String>, BTreeSet<String>) {
+        let mut used = BTreeSet::new();
+        let mut assigned = BTreeSet::new();
+        walk_descendants(node, |node, _| {
+            if node.kind() == access && node.child_by_field_name(object).is_some_and(|object| self.text(object) == receiver) {
+                if let Some(member) = node.child_by_field_name(property) {
+                    let is_target = node.parent().is_some_and(|parent| {
+                        parent.kind().contains("assignment") && parent.child_by_field_name("left") == Some(node)
+                    });
+                    if is_target {
+                        assigned.insert(self.text(member).to_string());
+                    }
+                    used.insert(self.text(member).to_string());
+                }
+            }
+            true
+        });
+        (used, assigned)
+    }
+
+    fn rust_type(&self, node: Node) -> Option<Declaration> {
+        let name = self.field_text(node, "name")?;
+        let mut fields = BTreeSet::new();
+        if let Some(body) = node.child_by_field_name("body").filter(|_| node.kind() == "struct_item") {
+            let mut cursor = body.walk();
+            let members = body.named_children(&mut cursor)
+                .filter(|member| !matches!(member.kind(), "visibility_modifier" | "attribute_item" | "line_comment" | "block_comment"));
+            for (position, member) in members.enumerate() {
+                // Tuple struct fields are named by position
+                fields.insert(self.field_text(member, "name").unwrap_or_else(|| position.to_string()));
+            }
+        }
+        Some(self.declaration(node, name, fields, None, Vec::new()))
+    }
+
+    fn rust_impl(&self, node: Node) -> Option<Detached> {
+        let mut type_node = node.child_by_field_name("type")?;
+        if type_node.kind() == "generic_type" {
+            type_node = type_node.child_by_field_name("type")?;
+        }
+        let body = node.child_by_field_name("body")?;
+        let mut cursor = body.walk();
+        let methods = body.named_children(&mut cursor)
+            .filter(|item| item.kind() == "function_item")
+            .filter_map(|item| {
+                let parameters = item.child_by_field_name("parameters")?;
+                let mut cursor = parameters.walk();
+                let instance = parameters.named_children(&mut cursor).any(|parameter| parameter.kind() == "self_parameter");
+                let (members, _) = self.accesses(item, "field_expression", "value", "field", "self");
+                Some(self.method(item, self.field_text(item, "name")?, instance, members))
+            })
+            .collect();
+        Some(Detached {
+            language: self.language,
+            type_name: last_segment(self.text(type_node)),
+            file_path: self.path.to_string(),
+            methods,
+        })
+    }
+
+    fn go_struct(&self, node: Node) -> Option<Declaration> {
+        let name = self.field_text(node, "name")?;
+        let struct_type = node.child_by_field_name("type").filter(|type_node| type_node.kind() == "struct_type")?;
+        let mut fields = BTreeSet::new();
+        let mut cursor = struct_type.walk();
+        for list in struct_type.named_children(&mut cursor).filter(|child| child.kind() == "field_declaration_list") {
+            let mut cursor = list.walk();
+            for declaration in list.named_children(&mut cursor).filter(|child| child.kind() == "field_declaration") {
+                let mut names_cursor = declaration.walk();
+                let names: Vec<String> = declaration.children_by_field_name("name", &mut names_cursor)
+                    .map(|name| self.text(name).to_string())
+                    .collect();
+                if names.is_empty() {
+                    // An embedded type is a field named after the type
+                    fields.extend(declaration.child_by_field_name("type").map(|embedded| last_segment(self.text(embedded))));
+                }
+                fields.extend(names);
+            }
+        }
+        // Embedding is composition, not inheritance
+        Some(self.declaration(node, name, fields, None, Vec::new()))
+    }
+
+    fn go_method(&self, node: Node) -> Option<Detached> {
+        let receiver_list = node.child_by_field_name("receiver")?;
+        let mut cursor = receiver_list.walk();
+        let receiver = receiver_list.named_children(&mut cursor).find(|child| child.kind() == "parameter_declaration")?;
+        let type_text = self.text(receiver.child_by_field_name("type")?);
+        let type_name = type_text.split('[').next().unwrap_or(type_text);
+        let members = match self.field_text(receiver, "name") {
+            Some(receiver_name) => self.accesses(node, "selector_expression", "operand", "field", &receiver_name).0,
+            None => BTreeSet::new(),
+        };
+        Some(Detached {
+            language: self.language,
+            type_name: last_segment(type_name),
+            file_path: self.path.to_string(),
+            methods: vec![self.method(node, self.field_text(node, "name")?, true, members)],
+        })
+    }
+
+    fn python_class(&self, node: Node) -> Option<Declaration> {
+        let name = self.field_text(node, "name")?;
+        let bases = node.child_by_field_name("superclasses").map_or_else(Vec::new, |superclasses| {
+            let mut cursor = superclasses.walk();
+            superclasses.named_children(&mut cursor)
+                .filter(|base| base.kind() != "keyword_argument")
+                .map(|base| last_segment(self.text(base)))
+                .filter(|base| base != "object")
+                .collect()
+        });
+
+        let mut fields = BTreeSet::new();
+        let mut methods = Vec::new();
+        let body = node.child_by_field_name("body")?;
+        let mut cursor = body.walk();
+        for statement in body.named_children(&mut cursor) {
+            match statement.kind() {
+                "function_definition" => methods.extend(self.python_method(statement, false, &mut fields)),
+                "decorated_definition" => {
+                    let text = self.text(statement);
+                    let class_level = text.starts_with("@staticmethod") || text.starts_with("@classmethod");
+                    if let Some(definition) = statement.child_by_field_name("definition").filter(|definition| definition.kind() == "function_definition") {
+                        methods.extend(self.python_method(definition, class_level, &mut fields));
+                    }
+                }
+                "expression_statement" => {
+                    let mut cursor = statement.walk();
+                    let targets = statement.named_children(&mut cursor)
+                        .filter(|child| child.kind() == "assignment")
+                        .filter_map(|assignment| assignment.child_by_field_name("left"))
+                        .filter(|target| target.kind() == "identifier");
+                    fields.extend(targets.map(|target| self.text(target).to_string()));
+                }
+                _ => {}
+            }
+        }
+        Some(self.declaration(node, name, fields, Some(bases), methods))
+    }
+
+    /// A method, adding the attributes it assigns through its first parameter to `fields`
+    fn python_method(&self, node: Node, class_level: bool, fields: &mut BTreeSet<String>) -> Option<Method> {
+        let name = self.field_text(node, "name")?;
+        let receiver = node.child_by_field_name("parameters").and_then(|parameters| {
+            let mut cursor = parameters.walk();
+            let first = parameters.named_children(&mut cursor).next();
+            first.filter(|parameter| parameter.kind() == "identifier").map(|parameter| self.text(parameter).to_string())
+        });
+        let (members, assigned) = match (&receiver, class_level) {
+            (Some(receiver), false) => self.accesses(node, "attribute", "object", "attribute", receiver),
+            _ => Default::default(),
+        };
+        fields.extend(assigned);
+        Some(self.method(node, name, receiver.is_some() && !class_level, members))
+    }
+
+    fn javascript_class(&self, node: Node) -> Option<Declaration> {
+        let name = self.field_text(node, "name")?;
+        let mut cursor = node.walk();
+        let bases: Vec<String> = node.named_children(&mut cursor)
+            .filter(|child| child.kind() == "class_heritage")
+            .filter_map(|heritage| heritage.named_child(heritage.named_child_count().checked_sub(1)?))
+            .map(|base| last_segment(self.text(base)))
+            .collect();
+
+        let mut fields = BTreeSet::new();
+        let mut methods = Vec::new();
+        let body = node.child_by_field_name("body")?;
+        let mut cursor = body.walk();
+        for member in body.named_children(&mut cursor) {
+            match member.kind() {
+                "method_definition" => {
+                    let Some(method_name) = self.field_text(member, "name") else {
+                        continue;
+                    };
+                    let mut cursor = member.walk();
+                    let is_static = member.children(&mut cursor).any(|child| child.kind() == "static");
+                    let (members, assigned) = self.accesses(member, "member_expression", "object", "property", "this");
+                    fields.extend(assigned);
+                    methods.push(self.method(member, method_name, !is_static, members));
+                }
+                "field_definition" | "public_field_definition" => {
+                    fields.extend(self.field_text(member, "property").or_else(|| self.field_text(member, "name")));
+                }
+                _ => {}
+            }
+        }
+        Some(self.declaration(node, name, fields, Some(bases), methods))
+    }
+
+    fn ruby_class(&self, node: Node) -> Option<Declaration> {
+        let name = last_segment(&self.field_text(node, "name")?);
+        let bases = node.child_by_field_name("superclass")
+            .and_then(|superclass| superclass.named_child(0))
+            .map(|base| vec![last_segment(self.text(base))])
+            .unwrap_or_default();
+
+        let mut fields = BTreeSet::new();
+        walk_descendants(node, |node, _| {
+            if node.kind() == "instance_variable" {
+                fields.insert(self.text(node).to_string());
+            }
+            true
+        });
+
+        let mut methods = Vec::new();
+        let mut cursor = node.walk();
+        let mut statements: Vec<Node> = Vec::new();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "body_statement" => {
+                    let mut cursor = child.walk();
+                    statements.extend(child.named_children(&mut cursor));
+                }
+                _ => statements.push(child),
+            }
+        }
+        for statement in statements {
+            let instance = match statement.kind() {
+                "method" => true,
+                "singleton_method" => false,
+                _ => continue,
+            };
+            let Some(method_name) = self.field_text(statement, "name") else {
+                continue;
+            };
+            let mut members = BTreeSet::new();
+            walk_descendants(statement, |node, _| {
+                match node.kind() {
+                    "instance_variable" => {
+                        members.insert(self.text(node).to_string());
+                    }
+                    "call" if node.child_by_field_name("receiver").is_some_and(|receiver| receiver.kind() == "self") => {
+                        members.extend(self.field_text(node, "method"));
+                    }
+                    _ => {}
+                }
+                true
+            });
+            methods.push(self.method(statement, method_name, instance, members));
+        }
+        Some(self.declaration(node, name, fields, Some(bases), methods))
+    }
+}
+
+/// `Widget` from `ui.Widget`, `ui::Widget`, `*Widget` or `Ui::Widget`
+fn last_segment(path: &str) -> String {
+    path.rsplit(['.', ':', '*']).next().unwrap_or(path).trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::pool;
+
+    fn extract_file(path: &str, language: SupportedLanguage, source: &str) -> Extracted {
+        let tree = pool::parse(language, source).unwrap();
+        extract(path, source, tree.root_node(), language, &ErrorHandlingWeights::default())
+    }
+
+    #[test]
+    fn test_class_cohesion_size_and_inheritance() {
+        let shapes = extract_file("shapes.py", SupportedLanguage::Python, "\
+class Base:
+    pass
+
+class Report(Base):
+    title = 'report'
+
+    def __init__(self, rows):
+        self.rows = rows
+        self.printer = None
+
+    def total(self):
+        return sum(self.rows)
+
+    def average(self):
+        return self.total() / len(self.rows) if self.rows else 0
+
+    def send(self):
+        self.printer.print()
+
+    @staticmethod
+    def helper():
+        pass
+");
+
+        let types = measure([shapes]);
+        let report = types.iter().find(|type_| type_.name == "Report").unwrap();
+        assert_eq!((report.methods, report.fields), (5, 3));
+        // `send` only touches the printer, which `__init__` also sets: still one group
+        assert_eq!(report.lcom, 1);
+        // average: 1 + conditional expression
+        assert_eq!(report.wmc, 6);
+        assert_eq!(report.depth_of_inheritance, Some(1));
+        assert_eq!(types.iter().find(|type_| type_.name == "Base").unwrap().depth_of_inheritance, Some(0));
+    }
+
+    #[test]
+    fn test_rust_impls_in_other_files_join_their_struct() {
+        let declared = extract_file("cache.rs", SupportedLanguage::Rust, "pub struct Cache {\n    hits: u64,\n    misses: u64,\n    path: String,\n}\n");
+        let implemented = extract_file("cache_impl.rs", SupportedLanguage::Rust, "\
+impl Cache {
+    pub fn new(path: String) -> Self {
+        Cache { hits: 0, misses: 0, path }
+    }
+
+    fn record(&mut self, hit: bool) {
+        if hit { self.hits += 1 } else { self.misses += 1 }
+    }
+
+    fn location(&self) -> &str {
+        &self.path
+    }
+}
+");
+
+        let types = measure([declared, implemented]);
+        assert_eq!(types.len(), 1);
+        let cache = &types[0];
+        assert_eq!((cache.methods, cache.fields, cache.line), (3, 3, 1));
+        // `record` and `location` share nothing; `new` takes no self
+        assert_eq!(cache.lcom, 2);
+        assert_eq!(cache.depth_of_inheritance, None);
+    }
+}
@@ -0,0 +1,197 @@
+//! Rust `unsafe` blocks and whether each states why it is sound
+//!
+//! The convention (enforced by clippy's `undocumented_unsafe_blocks`) is a
+//! `// SAFETY:` comment directly above the block, or above the statement that
+//! contains it, naming the invariants the block relies on. Blocks without one
+//! are reported under `rules::UNJUSTIFIED_UNSAFE`; per crate, this module
+//! measures how many blocks there are per thousand lines, justified or not.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tree_sitter::Node;
+
+use crate::ast_analyzer::walk_descendants;
+
+/// Name reported for Rust files outside any Cargo package
+pub const NO_CRATE: &str = "(no crate)";
+
+/// One `unsafe { ... }` block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsafeBlock {
+    /// 1-based
+    pub line: u32,
+    pub column: u32,
+    /// A `SAFETY:` comment precedes the block or its statement
+    pub justified: bool,
+}
+
+/// Unsafe blocks of one crate, relative to its size
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateUnsafe {
+    /// Package name from the nearest `Cargo.toml`, or `NO_CRATE`
+    pub name: String,
+    /// Directory holding that `Cargo.toml`
+    pub root: String,
+    pub files: u32,
+    pub lines: u32,
+    pub unsafe_blocks: u32,
+    pub justified: u32,
+    pub unjustified: u32,
+    /// Unsafe blocks per 1000 lines
+    pub density: f64,
+    /// Unjustified unsafe blocks per 1000 lines
+    pub unjustified_density: f64,
+}
+
+/// Every unsafe block under `root`, in source order
+pub fn blocks(root: Node, source: &str) -> Vec<UnsafeBlock> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut found = Vec::new();
+    walk_descendants(root, |node, _| {
+        if node.kind() == "unsafe_block" {
+            let position = node.start_position();
+            found.push(UnsafeBlock {
+                line: position.row as u32 + 1,
+                column: position.column as u32 + 1,
+                justified: is_justified(node, &lines),
+            });
+        }
+        true
+    });
+    found
+}
+
+/// Whether a `SAFETY:` comment sits before `block` on its line, or in the
+/// comments and attributes directly above it or above its statement
+fn is_justified(block: Node, lines: &[&str]) -> bool {
+    let position = block.start_position();
+    if lines.get(position.row).is_some_and(|line| mentions_safety(&line[..position.column.min(line.len())])) {
+        return true;
+    }
+
+    // `let value = unsafe { ... };` is commented above the `let`
+    let mut statement = block;
+    while let Some(parent) = statement.parent() {
+        if matches!(parent.kind(), "block" | "source_file" | "declaration_list") {
+            break;
+        }
+        statement = parent;
+    }
+    [position.row, statement.start_position().row].into_iter().any(|row| {
+        lines[..row].iter().rev()
+            .map(|line| line.trim())
+            .take_while(|line| line.starts_with("//") || line.starts_with("/*") || line.starts_with('*') || line.ends_with("*/") || line.starts_with("#["))
+            .any(mentions_safety)
+    })
+}
+
+fn mentions_safety(text: &str) -> bool {
+    text.to_ascii_uppercase().contains("SAFETY:")
+}
+
+/// Unsafe density of each crate, from the `blocks` and line count of each of its Rust files
+pub fn measure<'a>(files: impl IntoIterator<Item = (&'a str, u32, &'a [UnsafeBlock])>) -> Vec<CrateUnsafe> {
+    let mut manifests: HashMap<PathBuf, Option<(String, PathBuf)>> = HashMap::new();
+    let mut crates: BTreeMap<(String, PathBuf), CrateUnsafe> = BTreeMap::new();
+    for (path, lines, found) in files {
+        let (name, root) = owning_crate(Path::new(path), &mut manifests)
+            .unwrap_or_else(|| (NO_CRATE.to_string(), PathBuf::new()));
+        let entry = crates.entry((name.clone(), root.clone())).or_insert_with(|| CrateUnsafe {
+            name,
+            root: root.to_string_lossy().into_owned(),
+            files: 0,
+            lines: 0,
+            unsafe_blocks: 0,
+            justified: 0,
+            unjustified: 0,
+            density: 0.0,
+            unjustified_density: 0.0,
+        });
+        entry.files += 1;
+        entry.lines += lines;
+        entry.unsafe_blocks += found.len() as u32;
+        entry.justified += found.iter().filter(|block| block.justified).count() as u32;
+    }
+
+    let mut crates: Vec<CrateUnsafe> = crates.into_values()
+        .map(|mut krate| {
+            krate.unjustified = krate.unsafe_blocks - krate.justified;
+            let per_kloc = |count: u32| match krate.lines {
+                0 => 0.0,
+                lines => count as f64 * 1000.0 / lines as f64,
+            };
+            krate.density = per_kloc(krate.unsafe_blocks);
+            krate.unjustified_density = per_kloc(krate.unjustified);
+            krate
+        })
+        .collect();
+    crates.sort_by(|a, b| b.unjustified_density.total_cmp(&a.unjustified_density)
+        .then(b.density.total_cmp(&a.density))
+        .then_with(|| a.name.cmp(&b.name)));
+    crates
+}
+
+/// Package name and directory of the nearest `Cargo.toml` with a `[package]` above `file`
+fn owning_crate(file: &Path, manifests: &mut HashMap<PathBuf, Option<(String, PathBuf)>>) -> Option<(String, PathBuf)> {
+    for dir in file.ancestors().skip(1) {
+        let package = manifests.entry(dir.to_path_buf()).or_insert_with(|| {
+            let manifest = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+            let manifest: toml::Value = toml::from_str(&manifest).ok()?;
+            let name = manifest.get("package")?.get("name")?.as_str()?;
+            Some((name.to_string(), dir.to_path_buf()))
+        });
+        if package.is_some() {
+            return package.clone();
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::parsers::{pool, SupportedLanguage};
+
+    #[test]
+    fn test_safety_comments_justify_blocks_and_count_per_crate() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"ffi\"\nversion = \"0.1.0\"\n")?;
+        std::fs::create_dir(dir.path().join("src"))?;
+        let source = "\
+fn read(ptr: *const u8) -> u8 {
+    // SAFETY: callers pass a pointer from `Buffer::as_ptr`, valid for one byte
+    unsafe { *ptr }
+}
+
+fn len(ptr: *const u8) -> usize {
+    // SAFETY: `ptr` is NUL-terminated, see `Buffer::new`
+    #[allow(clippy::cast_possible_truncation)]
+    let len =
+        unsafe { strlen(ptr) };
+    len
+}
+
+fn write(ptr: *mut u8) {
+    // the pointer is fine
+
+    unsafe { *ptr = 0 }
+}
+";
+        let path = dir.path().join("src/lib.rs");
+        std::fs::write(&path, source)?;
+        let path = path.to_string_lossy().into_owned();
+
+        let found = blocks(pool::parse(SupportedLanguage::Rust, source)?.root_node(), source);
+        let justified: Vec<(u32, bool)> = found.iter().map(|block| (block.line, block.justified)).collect();
+        assert_eq!(justified, vec![(3, true), (10, true), (17, false)]);
+
+        let crates = measure([(path.as_str(), source.lines().count() as u32, found.as_slice())]);
+        assert_eq!(crates.len(), 1);
+        assert_eq!(crates[0].name, "ffi");
+        assert_eq!((crates[0].unsafe_blocks, crates[0].justified, crates[0].unjustified), (3, 2, 1));
+        assert!((crates[0].unjustified_density - 1000.0 / 18.0).abs() < 1e-9);
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+//! Git backend, shelling out to the `git` binary
+//!
+//! libgit2 (through `git2`) would save a process per call, but churn is one
+//! `git log` per run and blame one call per file. The binary also honours
+//! the user's own git setup that libgit2 reads differently or not at all:
+//! `safe.directory`, replace refs, worktrees, partial clones and
+//! `blame.ignoreRevsFile`. And no C library has to be built for every target.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{BlameLine, FileChurn, Vcs};
+
+pub struct Git {
+    root: PathBuf,
+}
+
+impl Git {
+    pub fn new(root: &Path) -> Self {
+        Self { root: root.to_path_buf() }
+    }
+}
+
+impl Vcs for Git {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+
+    /// Thin wrapper around `git blame --line-porcelain`
+    fn blame(&self, file: &Path) -> Result<Vec<BlameLine>> {
+        // Run from the file's own directory so git discovers the right repository
+        let dir = file.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let name = file.file_name().context("Blame target has no file name")?;
+
+        let output = Command::new("git")
+            .arg("-C").arg(dir)
+            .args(["blame", "--line-porcelain", "--"])
+            .arg(name)
+            .output()
+            .context("Failed to run git blame")?;
+
+        if !output.status.success() {
+            anyhow::bail!("git blame failed for {}", file.display());
+        }
+
+        Ok(parse_porcelain(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn head(&self) -> Result<String> {
+        let output = Command::new("git")
+            .arg("-C").arg(&self.root)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .context("Failed to run git rev-parse")?;
+
+        if !output.status.success() {
+            anyhow::bail!("git rev-parse failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Untracked files are left out: they are often build output the analysis skips anyway
+    fn is_dirty(&self) -> Result<bool> {
+        let output = Command::new("git")
+            .arg("-C").arg(&self.root)
+            .args(["status", "--porcelain", "--untracked-files=no"])
+            .output()
+            .context("Failed to run git status")?;
+
+        if !output.status.success() {
+            anyhow::bail!("git status failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        Ok(!output.stdout.is_empty())
+    }
+
+    /// One pass over `git log --numstat`; renames count as a delete and an add
+    fn churn(&self, since: i64) -> Result<Vec<FileChurn>> {
+        let since = chrono::DateTime::from_timestamp(since, 0)
+            .context("History window starts at an invalid time")?
+            .to_rfc3339();
+        let output = Command::new("git")
+            .arg("-C").arg(&self.root)
+            .args(["log", "--no-renames", "--numstat", "--format=%x00%an"])
+            .arg(format!("--since={}", since))
+            .output()
+            .context("Failed to run git log")?;
+
+        if !output.status.success() {
+            anyhow::bail!("git log failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        Ok(parse_numstat_log(&String::from_utf8_lossy(&output.stdout), &self.root))
+    }
+
+    fn show(&self, rev: &str, file: &Path) -> Result<Option<String>> {
+        let dir = file.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let name = file.file_name().context("File has no file name")?;
+
+        // `./name` is resolved against the `-C` directory rather than the repository root
+        let output = Command::new("git")
+            .arg("-C").arg(dir)
+            .arg("show")
+            .arg(format!("{}:./{}", rev, name.to_string_lossy()))
+            .output()
+            .context("Failed to run git show")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("does not exist in") || stderr.contains("exists on disk, but not in") {
+                return Ok(None);
+            }
+            anyhow::bail!("Failed to read {} at revision '{}': {}", file.display(), rev, stderr.trim());
+        }
+
+        Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    /// Detached worktree, so the user's checkout and index are left alone
+    fn checkout(&self, rev: &str, dest: &Path) -> Result<()> {
+        let status = Command::new("git")
+            .arg("-C").arg(&self.root)
+            .args(["worktree", "add", "--detach", "--quiet"])
+            .arg(dest)
+            .arg(rev)
+            .status()
+            .context("Failed to run git")?;
+
+        if !status.success() {
+            anyhow::bail!("Failed to check out revision '{}'", rev);
+        }
+
+        Ok(())
+    }
+
+    fn remove_checkout(&self, dest: &Path) -> Result<()> {
+        let status = Command::new("git")
+            .arg("-C").arg(&self.root)
+            .args(["worktree", "remove", "--force"])
+            .arg(dest)
+            .status()
+            .context("Failed to run git")?;
+
+        if !status.success() {
+            anyhow::bail!("Failed to remove worktree {}", dest.display());
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse line-porcelain output; every line entry ends with its tab-prefixed content
+pub fn parse_porcelain(porcelain: &str) -> Vec<BlameLine> {
+    let mut lines = Vec::new();
+    let mut author = String::new();
+    let mut author_time = 0;
+
+    for line in porcelain.lines() {
+        if let Some(name) = line.strip_prefix("author ") {
+            author = name.to_string();
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            author_time = time.trim().parse().unwrap_or(0);
+        } else if line.starts_with('\t') {
+            lines.push(BlameLine { author: author.clone(), author_time });
+        }
+    }
+
+    lines
+}
+
+/// Parse `git log --numstat --format=%x00%an`: a NUL-prefixed author line per
+/// commit, then one `added<TAB>removed<TAB>path` line per file (`-` for binary)
+pub fn parse_numstat_log(log: &str, root: &Path) -> Vec<FileChurn> {
+    let mut files: BTreeMap<&str, (FileChurn, HashSet<&str>)> = BTreeMap::new();
+    let mut author = "";
+
+    for line in log.lines() {
+        if let Some(name) = line.strip_prefix('\0') {
+            author = name;
+            continue;
+        }
+        let mut fields = line.splitn(3, '\t');
+        let (Some(added), Some(removed), Some(path)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+
+        let (churn, authors) = files.entry(path).or_insert_with(|| (
+            FileChurn { path: root.join(path), commits: 0, lines_added: 0, lines_removed: 0, authors: 0 },
+            HashSet::new(),
+        ));
+        churn.commits += 1;
+        churn.lines_added += added.parse().unwrap_or(0);
+        churn.lines_removed += removed.parse().unwrap_or(0);
+        authors.insert(author);
+    }
+
+    files.into_values()
+        .map(|(churn, authors)| FileChurn { authors: authors.len(), ..churn })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_porcelain_parsing() {
+        let porcelain = "abc123 1 1 2\nauthor Alice\nauthor-time 1700000000\n\tfn main() {\n\
+                         def456 2 2\nauthor Bob\nauthor-time 1700000500\n\t}\n";
+        let lines = parse_porcelain(porcelain);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], BlameLine { author: "Alice".to_string(), author_time: 1_700_000_000 });
+        assert_eq!(lines[1].author, "Bob");
+    }
+
+    #[test]
+    fn test_numstat_log_parsing() {
+        let log = "\0Alice\n\n3\t1\tsrc/cart.rs\n-\t-\tlogo.png\n\0Bob\n\n10\t0\tsrc/cart.rs\n\0Alice\n\n1\t1\tsrc/cart.rs\n";
+        let files = parse_numstat_log(log, Path::new("/repo"));
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, Path::new("/repo/logo.png"));
+        assert_eq!(files[0].commits, 1);
+        assert_eq!(files[1], FileChurn {
+            path: PathBuf::from("/repo/src/cart.rs"),
+            commits: 3,
+            lines_added: 14,
+            lines_removed: 2,
+            authors: 2,
+        });
+    }
+}
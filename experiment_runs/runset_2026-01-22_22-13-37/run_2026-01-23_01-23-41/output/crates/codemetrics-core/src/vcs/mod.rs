@@ -0,0 +1,130 @@
+//! Version control backends behind a common interface
+//!
+//! Blame-based features (stability, attribution) and revision checkouts
+//! (release reports) go through the `Vcs` trait rather than shelling out to
+//! git directly. Git is the only backend today; Mercurial and Jujutsu
+//! repositories are recognized so users get a clear error instead of a
+//! confusing git failure, and can be added as further implementations.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+mod git;
+
+pub use git::Git;
+
+/// Authorship of a single line as reported by blame
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlameLine {
+    pub author: String,
+    pub author_time: i64,
+}
+
+/// How often one file changed during a window of history
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChurn {
+    /// Absolute path, joined onto the repository root
+    pub path: PathBuf,
+    pub commits: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    /// Distinct commit authors
+    pub authors: usize,
+}
+
+/// Operations the history-aware features need from a version control system
+pub trait Vcs {
+    /// Short backend name for messages, e.g. "git"
+    fn name(&self) -> &'static str;
+
+    /// Full ID of the checked-out revision
+    fn head(&self) -> Result<String>;
+
+    /// Whether tracked files differ from the checked-out revision
+    fn is_dirty(&self) -> Result<bool>;
+
+    /// Blame every line of `file`, in line order
+    fn blame(&self, file: &Path) -> Result<Vec<BlameLine>>;
+
+    /// Per-file commit counts and line churn for commits authored at or after `since` (unix time)
+    fn churn(&self, since: i64) -> Result<Vec<FileChurn>>;
+
+    /// Contents of `file` as of `rev`; `None` when the file did not exist there
+    fn show(&self, rev: &str, file: &Path) -> Result<Option<String>>;
+
+    /// Materialize `rev` in the new directory `dest`, leaving the working copy untouched
+    fn checkout(&self, rev: &str, dest: &Path) -> Result<()>;
+
+    /// Remove a directory created by `checkout`
+    fn remove_checkout(&self, dest: &Path) -> Result<()>;
+}
+
+/// Repository kinds recognized by their metadata directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcsKind {
+    Git,
+    Mercurial,
+    Jujutsu,
+}
+
+impl VcsKind {
+    /// Checked in order, so colocated Jujutsu repositories are driven through git
+    const MARKERS: [(&'static str, VcsKind); 3] = [
+        (".git", VcsKind::Git),
+        (".jj", VcsKind::Jujutsu),
+        (".hg", VcsKind::Mercurial),
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            VcsKind::Git => "Git",
+            VcsKind::Mercurial => "Mercurial",
+            VcsKind::Jujutsu => "Jujutsu",
+        }
+    }
+}
+
+/// Find the repository containing `path` and its kind
+pub fn find_repository(path: &Path) -> Option<(VcsKind, &Path)> {
+    path.ancestors().find_map(|dir| {
+        VcsKind::MARKERS.iter()
+            .find(|(marker, _)| dir.join(marker).exists())
+            .map(|(_, kind)| (*kind, dir))
+    })
+}
+
+/// Backend for the repository containing `path`
+pub fn detect(path: &Path) -> Result<Box<dyn Vcs>> {
+    let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    match find_repository(&absolute) {
+        Some((VcsKind::Git, root)) => Ok(Box::new(Git::new(root))),
+        Some((kind, root)) => anyhow::bail!(
+            "{} repositories are not supported yet ({}); only git history can be read",
+            kind.label(),
+            root.display()
+        ),
+        None => anyhow::bail!("{} is not inside a version-controlled repository", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_by_marker_directory() {
+        let temp_dir = tempdir().unwrap();
+        let nested = temp_dir.path().join("src/deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert!(detect(&nested).is_err());
+
+        std::fs::create_dir(temp_dir.path().join(".hg")).unwrap();
+        let error = detect(&nested).err().unwrap().to_string();
+        assert!(error.contains("Mercurial"));
+
+        std::fs::create_dir(temp_dir.path().join(".git")).unwrap();
+        assert_eq!(detect(&nested).unwrap().name(), "git");
+    }
+}
@@ -0,0 +1,158 @@
+//! Identifier vocabulary per module, a proxy for conceptual surface area
+//!
+//! A module (directory) that uses many distinct names, each about equally
+//! often, asks more of a reader than one built from a few recurring concepts.
+//! For every module this records how many identifiers occur, how many are
+//! distinct, and the Shannon entropy of their frequency distribution in bits.
+//!
+//! The raw term frequencies can be exported as JSON, so the numbers can be
+//! recomputed or compared across revisions outside this tool.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tree_sitter::Node;
+
+use crate::ast_analyzer::walk_descendants;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleVocabulary {
+    /// Directory relative to the analyzed root; `.` for files at the root
+    pub module: String,
+    pub files: usize,
+    /// Identifier occurrences
+    pub identifiers: usize,
+    /// Distinct identifiers
+    pub vocabulary: usize,
+    /// Shannon entropy of identifier frequencies, in bits
+    pub entropy: f64,
+    /// Occurrences of each identifier, written only by `write_raw`
+    #[serde(skip)]
+    pub terms: BTreeMap<String, usize>,
+}
+
+impl ModuleVocabulary {
+    fn new(module: String) -> Self {
+        Self { module, files: 0, identifiers: 0, vocabulary: 0, entropy: 0.0, terms: BTreeMap::new() }
+    }
+
+    fn finish(&mut self) {
+        self.identifiers = self.terms.values().sum();
+        self.vocabulary = self.terms.len();
+        self.entropy = entropy(&self.terms, self.identifiers);
+    }
+}
+
+/// Vocabulary of every module under `root`, from each file's `terms`, largest vocabulary first
+pub fn measure<'a>(root: &Path, files: impl IntoIterator<Item = (&'a str, &'a BTreeMap<String, usize>)>) -> Vec<ModuleVocabulary> {
+    let mut modules: BTreeMap<String, ModuleVocabulary> = BTreeMap::new();
+    for (path, terms) in files {
+        let name = module_name(root, Path::new(path));
+        let module = modules.entry(name.clone()).or_insert_with(|| ModuleVocabulary::new(name));
+        module.files += 1;
+        for (term, count) in terms {
+            *module.terms.entry(term.clone()).or_insert(0) += count;
+        }
+    }
+
+    let mut modules: Vec<ModuleVocabulary> = modules.into_values()
+        .map(|mut module| {
+            module.finish();
+            module
+        })
+        .collect();
+    modules.sort_by(|a, b| b.vocabulary.cmp(&a.vocabulary).then_with(|| a.module.cmp(&b.module)));
+    modules
+}
+
+/// Write every module's term frequencies, for study outside this tool
+pub fn write_raw(modules: &[ModuleVocabulary], path: &Path) -> Result<()> {
+    #[derive(Serialize)]
+    struct RawModule<'a> {
+        module: &'a str,
+        files: usize,
+        terms: &'a BTreeMap<String, usize>,
+    }
+
+    let raw = serde_json::json!({
+        "generated_at": Utc::now().to_rfc3339(),
+        "modules": modules.iter()
+            .map(|module| RawModule { module: &module.module, files: module.files, terms: &module.terms })
+            .collect::<Vec<_>>(),
+    });
+    let json = serde_json::to_string_pretty(&raw).context("Failed to serialize vocabulary")?;
+    std::fs::write(path, json)
+        .with_context(|| format!("Failed to write vocabulary to {}", path.display()))
+}
+
+/// Occurrences of each identifier under `root`
+pub fn terms(root: Node, source: &str) -> BTreeMap<String, usize> {
+    let mut terms = BTreeMap::new();
+    walk_descendants(root, |node, _| {
+        if is_identifier(node) {
+            *terms.entry(source[node.byte_range()].to_string()).or_insert(0) += 1;
+        }
+        true
+    });
+    terms
+}
+
+/// Identifier leaves in any grammar: `identifier`, `type_identifier`, `field_identifier`, Ruby `constant`, ...
+fn is_identifier(node: Node) -> bool {
+    node.is_named() && node.child_count() == 0 && (node.kind().ends_with("identifier") || node.kind() == "constant")
+}
+
+fn module_name(root: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(root).unwrap_or(file);
+    match relative.parent().map(|parent| parent.to_string_lossy().replace('\\', "/")) {
+        Some(parent) if !parent.is_empty() => parent,
+        _ => ".".to_string(),
+    }
+}
+
+fn entropy(terms: &BTreeMap<String, usize>, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    terms.values()
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{pool, SupportedLanguage};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_vocabulary_and_entropy_per_module() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let python = |source: &str| terms(pool::parse(SupportedLanguage::Python, source).unwrap().root_node(), source);
+        // Four occurrences of two names, evenly split: exactly one bit
+        let billing = python("total = price\nprice = total\n");
+        let main = python("run()\n");
+
+        let paths = ["billing/a.py", "main.py"].map(|file| root.join(file).to_string_lossy().into_owned());
+        let modules = measure(root, [(paths[0].as_str(), &billing), (paths[1].as_str(), &main)]);
+
+        assert_eq!(modules.len(), 2);
+        let billing = &modules[0];
+        assert_eq!(billing.module, "billing");
+        assert_eq!((billing.files, billing.identifiers, billing.vocabulary), (1, 4, 2));
+        assert!((billing.entropy - 1.0).abs() < 1e-9);
+        assert_eq!(modules[1].module, ".");
+        assert_eq!(modules[1].entropy, 0.0);
+
+        let raw_path = root.join("vocabulary.json");
+        write_raw(&modules, &raw_path).unwrap();
+        let raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&raw_path).unwrap()).unwrap();
+        assert_eq!(raw["modules"][0]["terms"]["price"], 2);
+    }
+}
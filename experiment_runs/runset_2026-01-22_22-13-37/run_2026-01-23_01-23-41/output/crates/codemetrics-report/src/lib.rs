@@ -0,0 +1,12 @@
+//! Report rendering for CodeMetrics results
+//!
+//! Turns `codemetrics-core` analysis results into terminal tables, HTML,
+//! Markdown, JSON and SARIF, and delivers them through an `OutputSink`.
+//! Kept apart from the core so embedders that only analyze do not pull in
+//! templating and terminal dependencies.
+
+pub mod output;
+pub mod reporters;
+pub mod visualization;
+
+pub use reporters::{sections, sink, Reporter};
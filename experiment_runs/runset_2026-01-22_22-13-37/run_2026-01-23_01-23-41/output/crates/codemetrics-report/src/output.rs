@@ -1,5 +1,4 @@
-use crate::analyzer::{AnalysisResults, AnalysisIssue, IssueSeverity};
-use crate::cli::OutputFormat;
+use codemetrics_core::analyzer::{AnalysisResults, IssueSeverity};
 use anyhow::{Context, Result};
 use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Table};
 use serde_json;
@@ -7,6 +6,19 @@ use std::path::Path;
 use std::fs::File;
 use std::io::Write;
 
+/// What `OutputFormatter` renders; the command line chooses one from its own arguments
+#[derive(Debug, Clone)]
+pub enum OutputFormat {
+    /// Human-readable terminal output with tables
+    Terminal,
+    /// JSON format for CI/CD integration
+    Json,
+    /// HTML report with interactive visualizations
+    Html,
+    /// Markdown format for documentation
+    Markdown,
+}
+
 /// Handles formatting and outputting analysis results
 pub struct OutputFormatter {
     format: OutputFormat,
@@ -46,8 +58,8 @@ impl OutputFormatter {
         let mut output = String::new();
 
         // Header
-        output.push_str(&format!("🎯 CodeMetrics Analysis Results\n"));
-        output.push_str(&format!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n"));
+        output.push_str("🎯 CodeMetrics Analysis Results\n");
+        output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
 
         // Summary statistics
         let mut summary_table = Table::new();
@@ -133,7 +145,7 @@ impl OutputFormatter {
             issues_table
                 .load_preset(UTF8_FULL)
                 .apply_modifier(UTF8_ROUND_CORNERS)
-                .set_header(vec!["Severity", "File", "Line", "Message"]);
+                .set_header(vec!["Severity", "Rule", "File", "Line", "Message"]);
 
             for issue in &results.issues {
                 let severity_icon = match issue.severity {
@@ -144,6 +156,7 @@ impl OutputFormatter {
 
                 issues_table.add_row(vec![
                     severity_icon,
+                    issue.rule_id,
                     &issue.file_path.file_name()
                         .unwrap_or_else(|| issue.file_path.as_os_str())
                         .to_string_lossy(),
@@ -153,7 +166,7 @@ impl OutputFormatter {
             }
 
             output.push_str(&issues_table.to_string());
-            output.push_str("\n");
+            output.push('\n');
         }
 
         Ok(output)
@@ -181,6 +194,7 @@ impl OutputFormatter {
 
         #[derive(serde::Serialize)]
         struct JsonIssue {
+            rule_id: String,
             file: String,
             line: u32,
             severity: String,
@@ -202,6 +216,7 @@ impl OutputFormatter {
             }).collect(),
             issues: results.issues.iter().map(|issue| {
                 JsonIssue {
+                    rule_id: issue.rule_id.to_string(),
                     file: issue.file_path.to_string_lossy().to_string(),
                     line: issue.line,
                     severity: match issue.severity {
@@ -221,8 +236,6 @@ impl OutputFormatter {
 
     /// Format results as HTML report
     fn format_html(&self, results: &AnalysisResults) -> Result<String> {
-        let html_template = include_str!("templates/report.html");
-
         // For now, return a simple HTML structure
         // TODO: Implement proper templating with handlebars
         Ok(format!(
@@ -307,7 +320,7 @@ impl OutputFormatter {
                 let percentage = (*count as f32 / results.files_analyzed as f32) * 100.0;
                 output.push_str(&format!("| {} | {} | {:.1}% |\n", language, count, percentage));
             }
-            output.push_str("\n");
+            output.push('\n');
         }
 
         // Complex files
@@ -340,7 +353,7 @@ impl OutputFormatter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analyzer::{AnalysisResults, AnalysisIssue, IssueSeverity};
+    use codemetrics_core::analyzer::{AnalysisResults, AnalysisIssue, IssueSeverity};
     use std::collections::HashMap;
     use std::path::PathBuf;
 
@@ -362,6 +375,7 @@ mod tests {
             analysis_duration_ms: 150,
             issues: vec![
                 AnalysisIssue {
+                    rule_id: "CM0102",
                     file_path: PathBuf::from("complex_file.rs"),
                     line: 42,
                     severity: IssueSeverity::Warning,
@@ -0,0 +1,163 @@
+//! Inline review annotations for GitHub Actions and GitLab merge requests
+//!
+//! GitHub reads workflow commands (`::warning file=...::message`) from a step's
+//! stdout and pins them to the named lines; GitLab reads a Code Quality JSON
+//! artifact and shows entries on changed lines of the merge request diff. Both
+//! want paths relative to the repository root, so analyze from the checkout root.
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use codemetrics_core::analyzers::AnalysisResults;
+use codemetrics_core::rules;
+use codemetrics_core::thresholds::ThresholdLevel;
+
+/// One finding at a file and line range
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub rule_id: &'static str,
+    pub level: ThresholdLevel,
+    pub message: String,
+    pub file_path: String,
+    /// `None` for file-level findings
+    pub lines: Option<(u32, u32)>,
+    /// Identifies the finding across runs: rule, path and function name (clones and line findings, having no name, add their lines)
+    pub key: String,
+}
+
+/// Every reported function, file, clone location and line finding as an annotation
+pub fn collect(results: &AnalysisResults) -> Vec<Annotation> {
+    let functions = results.high_complexity_functions.iter().map(|func| Annotation {
+        rule_id: rules::HIGH_CYCLOMATIC_COMPLEXITY,
+        level: results.thresholds.function_level(func.complexity),
+        message: format!("`{}` has cyclomatic complexity {}", func.name, func.complexity),
+        file_path: repository_path(&func.file_path),
+        lines: Some((func.line_start, func.line_end)),
+        key: format!("{}:{}:{}", rules::HIGH_CYCLOMATIC_COMPLEXITY, repository_path(&func.file_path), func.name),
+    });
+    let files = results.high_complexity_files.iter().map(|file| Annotation {
+        rule_id: rules::HIGH_FILE_COMPLEXITY,
+        level: file.level,
+        message: format!("File has total complexity {} across {} functions", file.total_complexity, file.functions),
+        file_path: repository_path(&file.file_path),
+        lines: None,
+        key: format!("{}:{}", rules::HIGH_FILE_COMPLEXITY, repository_path(&file.file_path)),
+    });
+    let clones = results.clones.iter().flat_map(|class| {
+        class.locations.iter().map(move |location| Annotation {
+            rule_id: rules::DUPLICATED_CODE,
+            level: ThresholdLevel::Warn,
+            message: format!("Lines {}-{} are repeated in {} other places", location.line_start, location.line_end, class.locations.len() - 1),
+            file_path: repository_path(&location.file_path),
+            lines: Some((location.line_start, location.line_end)),
+            key: format!("{}:{}:{}-{}", rules::DUPLICATED_CODE, repository_path(&location.file_path), location.line_start, location.line_end),
+        })
+    });
+    let issues = results.issues.iter().filter_map(|found| {
+        let rule = rules::find_rule(&found.issue.rule_id)?;
+        Some(Annotation {
+            rule_id: rule.id,
+            level: ThresholdLevel::of_severity(&found.issue.severity),
+            message: found.issue.message.clone(),
+            file_path: repository_path(&found.file_path),
+            lines: Some((found.issue.line, found.issue.line)),
+            key: format!("{}:{}:{}", rule.id, repository_path(&found.file_path), found.issue.line),
+        })
+    });
+    functions.chain(files).chain(clones).chain(issues).collect()
+}
+
+/// GitHub Actions workflow commands, one per line
+pub fn github(annotations: &[Annotation]) -> String {
+    let mut out = String::new();
+    for annotation in annotations {
+        let command = match annotation.level {
+            ThresholdLevel::Critical | ThresholdLevel::Error => "error",
+            ThresholdLevel::Warn => "warning",
+            ThresholdLevel::Ok => "notice",
+        };
+        let title = rules::find_rule(annotation.rule_id)
+            .map(|rule| format!("{} {}", rule.id, rule.name))
+            .unwrap_or_else(|| annotation.rule_id.to_string());
+        let mut properties = format!("file={}", escape_property(&annotation.file_path));
+        if let Some((start, end)) = annotation.lines {
+            properties.push_str(&format!(",line={},endLine={}", start, end));
+        }
+        properties.push_str(&format!(",title={}", escape_property(&title)));
+        out.push_str(&format!("::{} {}::{}\n", command, properties, escape_data(&annotation.message)));
+    }
+    out
+}
+
+/// GitLab Code Quality report (a subset of the Code Climate issue format)
+pub fn gitlab(annotations: &[Annotation]) -> Result<String> {
+    let issues: Vec<_> = annotations.iter()
+        .map(|annotation| {
+            let severity = match annotation.level {
+                ThresholdLevel::Critical => "critical",
+                ThresholdLevel::Error => "major",
+                ThresholdLevel::Warn => "minor",
+                ThresholdLevel::Ok => "info",
+            };
+            let (begin, end) = annotation.lines.unwrap_or((1, 1));
+            json!({
+                "description": annotation.message,
+                "check_name": annotation.rule_id,
+                "fingerprint": annotation.key,
+                "severity": severity,
+                "location": {
+                    "path": annotation.file_path,
+                    "lines": { "begin": begin, "end": end },
+                },
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&issues).context("Failed to serialize Code Quality report")
+}
+
+/// Forward slashes without a leading `./`, as both platforms match against repository paths
+fn repository_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.strip_prefix("./").map(str::to_string).unwrap_or(path)
+}
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotation() -> Annotation {
+        Annotation {
+            rule_id: rules::HIGH_CYCLOMATIC_COMPLEXITY,
+            level: ThresholdLevel::Error,
+            message: "`parse` has cyclomatic complexity 22".to_string(),
+            file_path: repository_path("./src/a,b.rs"),
+            lines: Some((10, 40)),
+            key: "CM0101:src/a,b.rs:parse".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_github_workflow_command() {
+        assert_eq!(
+            github(&[annotation()]),
+            "::error file=src/a%2Cb.rs,line=10,endLine=40,title=CM0101 high-cyclomatic-complexity::`parse` has cyclomatic complexity 22\n"
+        );
+    }
+
+    #[test]
+    fn test_gitlab_code_quality_issue() {
+        let issues: serde_json::Value = serde_json::from_str(&gitlab(&[annotation()]).unwrap()).unwrap();
+        assert_eq!(issues[0]["severity"], "major");
+        assert_eq!(issues[0]["location"]["path"], "src/a,b.rs");
+        assert_eq!(issues[0]["location"]["lines"]["end"], 40);
+        assert_eq!(issues[0]["fingerprint"], "CM0101:src/a,b.rs:parse");
+    }
+}
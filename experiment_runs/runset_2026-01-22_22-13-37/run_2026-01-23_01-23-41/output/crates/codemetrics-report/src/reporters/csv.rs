@@ -0,0 +1,80 @@
+//! One row per function as CSV, for spreadsheets and `pandas.read_csv`
+//!
+//! Cognitive complexity and nesting depth are left empty for functions in
+//! languages without a grammar, or when the analysis did not measure them.
+
+use std::fmt::Write as _;
+
+use codemetrics_core::analyzers::FunctionRecord;
+
+const HEADER: [&str; 11] = [
+    "file", "language", "name", "line_start", "line_end", "complexity",
+    "cognitive_complexity", "loc", "parameters", "nesting_depth", "level",
+];
+
+/// Header row then every function, RFC 4180 quoted
+pub fn functions(functions: &[FunctionRecord]) -> String {
+    let mut out = String::new();
+    write_row(&mut out, HEADER.iter().map(|column| column.to_string()));
+    let optional = |value: Option<u32>| value.map(|value| value.to_string()).unwrap_or_default();
+    for function in functions {
+        write_row(&mut out, [
+            function.file_path.clone(),
+            function.language.clone(),
+            function.name.clone(),
+            function.line_start.to_string(),
+            function.line_end.to_string(),
+            function.complexity.to_string(),
+            optional(function.cognitive_complexity),
+            function.lines.to_string(),
+            function.parameters.to_string(),
+            optional(function.nesting_depth),
+            format!("{:?}", function.level),
+        ]);
+    }
+    out
+}
+
+fn write_row(out: &mut String, fields: impl IntoIterator<Item = String>) {
+    for (index, field) in fields.into_iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            let _ = write!(out, "\"{}\"", field.replace('"', "\"\""));
+        } else {
+            out.push_str(&field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codemetrics_core::thresholds::ThresholdLevel;
+
+    #[test]
+    fn test_one_quoted_row_per_function() {
+        let record = FunctionRecord {
+            file_path: "src/cart, v2.js".to_string(),
+            language: "JavaScript".to_string(),
+            name: "\"total\"".to_string(),
+            scope: String::new(),
+            line_start: 3,
+            line_end: 9,
+            lines: 7,
+            parameters: 2,
+            complexity: 12,
+            level: ThresholdLevel::Warn,
+            baselined: false,
+            cognitive_complexity: Some(15),
+            nesting_depth: None,
+        };
+        let csv = functions(&[record]);
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(rows[0], "file,language,name,line_start,line_end,complexity,cognitive_complexity,loc,parameters,nesting_depth,level");
+        assert_eq!(rows[1], r#""src/cart, v2.js",JavaScript,"""total""",3,9,12,15,7,2,,Warn"#);
+        assert_eq!(rows[2], "");
+    }
+}
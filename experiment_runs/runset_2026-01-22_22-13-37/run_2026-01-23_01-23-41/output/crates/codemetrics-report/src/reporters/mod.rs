@@ -0,0 +1,1696 @@
+//! Output formatters and report generators
+//!
+//! `Reporter::render*` build a report as a `String` without side effects, so
+//! embedders can serve or upload it; `output_results` and `generate_report`
+//! are the CLI wrappers that deliver the rendered report to a `sink`.
+
+pub mod annotations;
+pub mod csv;
+pub mod ndjson;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod prometheus;
+pub mod sections;
+pub mod sink;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use comfy_table::{Table, Cell, Color, Attribute, ContentArrangement};
+use handlebars::Handlebars;
+
+use codemetrics_core::analyzers::{AnalysisResults, ComplexityMass, FileIssue, HighComplexityFile, HighComplexityFunction, MassScope, RepeatedFiles};
+use codemetrics_core::calibration::Standing;
+use codemetrics_core::capabilities::Capabilities;
+use codemetrics_core::clusters::{self, ClusterMember, IssueCluster};
+use codemetrics_core::go_concurrency::FunctionConcurrency;
+use codemetrics_core::health::HealthScore;
+use codemetrics_core::hotspots::{Hotspot, HotspotKind};
+use codemetrics_core::rules;
+use codemetrics_core::shard::ShardOutput;
+use codemetrics_core::stack::DetectedFramework;
+use codemetrics_core::suppressions::SuppressedIssue;
+use codemetrics_core::stability::Stability;
+use codemetrics_core::thresholds::{ThresholdLevel, Thresholds};
+use sections::{RenderedSection, ReportSection, Sections};
+use sink::{FileSink, OutputSink, StdoutSink};
+
+pub struct Reporter {
+    format: String,
+    handlebars: Handlebars<'static>,
+    sections: Sections,
+}
+
+impl Reporter {
+    pub fn new(format: &str) -> Self {
+        let mut handlebars = Handlebars::new();
+
+        // Register HTML report template
+        handlebars.register_template_string("html_report", include_str!("templates/report.html"))
+            .expect("Failed to register HTML template");
+
+        Self {
+            format: format.to_string(),
+            handlebars,
+            sections: Sections::default(),
+        }
+    }
+
+    /// Append `section` to HTML reports after the built-in sections, in the order added
+    pub fn add_section(&mut self, section: impl ReportSection + 'static) -> Result<()> {
+        self.sections.add(&mut self.handlebars, Box::new(section))
+    }
+
+    /// Print `analyze` output for the configured format
+    pub fn output_results(&self, results: &AnalysisResults) -> Result<()> {
+        self.write_report(results, &mut StdoutSink)
+    }
+
+    /// Write a report for the configured format to `output_path`, or print it
+    pub fn generate_report(&self, results: &AnalysisResults, output_path: Option<&Path>) -> Result<()> {
+        match output_path {
+            Some(path) => {
+                self.write_report(results, &mut FileSink::new(path))?;
+                println!("Report written to: {}", path.display());
+                Ok(())
+            }
+            None => self.write_report(results, &mut StdoutSink),
+        }
+    }
+
+    /// Render the configured format and hand it to `sink` as one artifact
+    pub fn write_report(&self, results: &AnalysisResults, sink: &mut dyn OutputSink) -> Result<()> {
+        let content = match self.format.as_str() {
+            "parquet" => self.render_parquet(results)?,
+            "xlsx" => self.render_xlsx(results)?,
+            _ => self.render(results)?.into_bytes(),
+        };
+        sink.write(&self.artifact_name(), &content)
+    }
+
+    /// File name the configured format is delivered under, e.g. `report.html`
+    pub fn artifact_name(&self) -> String {
+        let extension = match self.format.as_str() {
+            // The name GitLab's documentation and templates use for `artifacts:reports:codequality`
+            "gitlab" => return "gl-code-quality-report.json".to_string(),
+            // Served as a Shields.io endpoint, next to the JSON report
+            "badge" => return "badge.json".to_string(),
+            "github" => "github.txt",
+            "html" => "html",
+            "markdown" => "md",
+            "json" => "json",
+            "sarif" => "sarif",
+            "parquet" => "parquet",
+            "xlsx" => "xlsx",
+            "csv" => "csv",
+            "ndjson" => "ndjson",
+            "prometheus" => "prom",
+            _ => "txt",
+        };
+        format!("report.{}", extension)
+    }
+
+    /// Render the configured format without printing anything
+    pub fn render(&self, results: &AnalysisResults) -> Result<String> {
+        match self.format.as_str() {
+            "json" => self.render_json(results),
+            "html" => self.render_html(results),
+            "markdown" => self.render_markdown(results),
+            "sarif" => self.render_sarif(results),
+            "badge" => self.render_badge(results),
+            // File records are streamed during analysis; only the summary is left to render
+            "ndjson" => ndjson::summary(results),
+            "github" => Ok(annotations::github(&annotations::collect(results))),
+            "gitlab" => annotations::gitlab(&annotations::collect(results)),
+            "csv" => Ok(csv::functions(&results.functions)),
+            "prometheus" => Ok(prometheus::metrics(results)),
+            "parquet" => anyhow::bail!("Parquet is a binary format; deliver it with `write_report`"),
+            "xlsx" => anyhow::bail!("xlsx is a binary format; deliver it with `write_report`"),
+            _ => self.render_text(results),
+        }
+    }
+
+    /// Tables and histogram as printed to a terminal
+    pub fn render_text(&self, results: &AnalysisResults) -> Result<String> {
+        let mut out = String::new();
+        writeln!(out, "\n📊 Code Analysis Results")?;
+        writeln!(out, "========================\n")?;
+
+        // Overview section
+        let mut overview_table = Table::new();
+        overview_table
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec!["Metric", "Value"]);
+
+        overview_table.add_row(vec![
+            Cell::new("Files Analyzed").add_attribute(Attribute::Bold),
+            Cell::new(results.files_analyzed.to_string()).fg(Color::Green),
+        ]);
+
+        overview_table.add_row(vec![
+            Cell::new("Total Functions").add_attribute(Attribute::Bold),
+            Cell::new(results.total_functions.to_string()).fg(Color::Blue),
+        ]);
+
+        overview_table.add_row(vec![
+            Cell::new("Average Complexity").add_attribute(Attribute::Bold),
+            Cell::new(format!("{:.2}", results.average_complexity))
+                .fg(if results.average_complexity >= results.thresholds.function_complexity_warn as f64 {
+                    Color::Red
+                } else {
+                    Color::Green
+                }),
+        ]);
+
+        overview_table.add_row(vec![
+            Cell::new("Weighted Complexity (by LOC)").add_attribute(Attribute::Bold),
+            Cell::new(format!("{:.2}", results.weighted_average_complexity))
+                .fg(if results.weighted_average_complexity >= results.thresholds.function_complexity_warn as f64 {
+                    Color::Red
+                } else {
+                    Color::Green
+                }),
+        ]);
+
+        overview_table.add_row(vec![
+            Cell::new("High Complexity Functions").add_attribute(Attribute::Bold),
+            Cell::new(results.high_complexity_functions.len().to_string())
+                .fg(if results.high_complexity_functions.is_empty() { Color::Green } else { Color::Yellow }),
+        ]);
+
+        if let Some(health) = &results.health {
+            let color = match health.grade {
+                'A' | 'B' => Color::Green,
+                'C' => Color::Yellow,
+                _ => Color::Red,
+            };
+            overview_table.add_row(vec![
+                Cell::new("Health Score").add_attribute(Attribute::Bold),
+                Cell::new(format!("{:.0}/100 ({})", health.overall, health.grade)).fg(color),
+            ]);
+        }
+
+        if let Some(duplication) = results.duplication_percent {
+            overview_table.add_row(vec![
+                Cell::new("Duplicated Lines").add_attribute(Attribute::Bold),
+                Cell::new(format!("{:.1}%", duplication)).fg(duplication_color(duplication)),
+            ]);
+        }
+
+        writeln!(out, "{}", overview_table)?;
+
+        if let Some(shard) = &results.shard {
+            writeln!(out, "\nShard: {}", shard_summary(shard))?;
+        }
+        if !results.skip_counts.is_empty() {
+            writeln!(out, "\nSkipped: {} (use --explain-skips to list paths)", skip_summary(results))?;
+        }
+        if results.baseline_suppressed > 0 {
+            writeln!(out, "Baseline: {} pre-existing issues hidden (use --no-baseline to show them)", results.baseline_suppressed)?;
+        }
+        if results.suppressed > 0 {
+            writeln!(out, "Suppressed: {} issues hidden by codemetrics-ignore comments or [[suppress]] entries (listed below)", results.suppressed)?;
+        }
+        if results.small_functions_excluded > 0 {
+            writeln!(out, "Methodology: {}", small_function_note(results))?;
+        }
+
+        if let Some(health) = &results.health {
+            self.write_health_breakdown(&mut out, health, &results.capabilities)?;
+        }
+
+        if !results.stack.is_empty() {
+            writeln!(out, "\n🧱 Stack")?;
+            writeln!(out, "========\n")?;
+            for framework in &results.stack {
+                writeln!(out, "  {:<12} {:<12} {}", framework.name, framework.language, framework.evidence.join(", "))?;
+            }
+        }
+
+        // Language breakdown
+        if !results.language_breakdown.is_empty() {
+            writeln!(out, "\n🔤 Language Breakdown")?;
+            writeln!(out, "====================\n")?;
+
+            let mut lang_table = Table::new();
+            lang_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec![
+                    "Language", "Files", "Functions", "Percentage",
+                    "Avg Complexity", "Maintainability", "Issues/KLOC", "Tests/File",
+                ]);
+
+            // Least maintainable first, answering "which language is in worst shape?"
+            for quality in &results.language_quality {
+                let percentage = (quality.files as f64 / results.files_analyzed as f64) * 100.0;
+                lang_table.add_row(vec![
+                    Cell::new(&quality.language).add_attribute(Attribute::Bold),
+                    Cell::new(quality.files.to_string()),
+                    Cell::new(quality.functions.to_string()),
+                    Cell::new(format!("{:.1}%", percentage)).fg(Color::Cyan),
+                    Cell::new(format!("{:.1}", quality.average_complexity)),
+                    Cell::new(format!("{:.0}", quality.maintainability)).fg(maintainability_color(quality.maintainability)),
+                    Cell::new(format!("{:.1}", quality.issue_density)),
+                    Cell::new(format!("{:.2}", quality.test_ratio)),
+                ]);
+            }
+
+            writeln!(out, "{}", lang_table)?;
+        }
+
+        // Build target breakdown
+        if !results.target_breakdown.is_empty() {
+            writeln!(out, "\n🎯 Build Target Breakdown")?;
+            writeln!(out, "========================\n")?;
+
+            let mut target_table = Table::new();
+            target_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Target", "Files", "Functions", "Avg Complexity", "High Complexity"]);
+
+            for (target, stats) in &results.target_breakdown {
+                let average = if stats.functions > 0 {
+                    stats.total_complexity as f64 / stats.functions as f64
+                } else {
+                    0.0
+                };
+                target_table.add_row(vec![
+                    Cell::new(target).add_attribute(Attribute::Bold),
+                    Cell::new(stats.files.to_string()),
+                    Cell::new(stats.functions.to_string()),
+                    Cell::new(format!("{:.2}", average)),
+                    Cell::new(stats.high_complexity_functions.to_string())
+                        .fg(if stats.high_complexity_functions == 0 { Color::Green } else { Color::Yellow }),
+                ]);
+            }
+
+            writeln!(out, "{}", target_table)?;
+        }
+
+        // High complexity functions
+        if !results.high_complexity_functions.is_empty() {
+            writeln!(out, "\n⚠️  High Complexity Functions (≥{})", results.thresholds.function_complexity_warn)?;
+            writeln!(out, "===================================\n")?;
+
+            if let Some(cutoffs) = results.severity_cutoffs {
+                writeln!(out, 
+                    "Severity bands: warn ≥{}, error ≥{}, critical ≥{}\n",
+                    cutoffs.warn, cutoffs.error, cutoffs.critical
+                )?;
+            }
+
+            let mut complexity_table = Table::new();
+            complexity_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Function", "Complexity", "Parameters", "Stability", "Location"]);
+
+            let functions: Vec<&HighComplexityFunction> = unclustered_functions(results).collect();
+            for func in functions.iter().take(10) { // Show top 10
+                let complexity_color = level_color(results.thresholds.function_level(func.complexity));
+
+                complexity_table.add_row(vec![
+                    Cell::new(&func.name).add_attribute(Attribute::Bold),
+                    Cell::new(func.complexity.to_string()).fg(complexity_color),
+                    Cell::new(func.parameters.to_string()),
+                    stability_cell(func.stability),
+                    Cell::new(format!("{}:{}", func.file_path, func.line_start)).fg(Color::Cyan),
+                ]);
+            }
+
+            writeln!(out, "{}", complexity_table)?;
+
+            if functions.len() > 10 {
+                writeln!(out, "... and {} more", functions.len() - 10)?;
+            }
+        }
+
+        if !results.high_complexity_files.is_empty() {
+            writeln!(out, "\n📁 High Complexity Files (total ≥{})", results.thresholds.file_complexity_warn)?;
+            writeln!(out, "=====================================\n")?;
+
+            let mut file_table = Table::new();
+            file_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["File", "Total Complexity", "Functions"]);
+
+            for file in unclustered_files(results).take(10) {
+                file_table.add_row(vec![
+                    Cell::new(&file.file_path).fg(Color::Cyan),
+                    Cell::new(file.total_complexity.to_string()).fg(level_color(file.level)),
+                    Cell::new(file.functions.to_string()),
+                ]);
+            }
+
+            writeln!(out, "{}", file_table)?;
+        }
+
+        if !results.issue_clusters.is_empty() {
+            writeln!(out, "\n📦 Clustered Findings")?;
+            writeln!(out, "===================\n")?;
+            for cluster in &results.issue_clusters {
+                writeln!(out, "  {}", cluster_summary(cluster))?;
+            }
+            writeln!(out, "\nUse --format json or html to list every member, or --cluster-threshold 0 for flat lists")?;
+        }
+
+        if !results.complexity_mass.is_empty() {
+            writeln!(out, "\n🏋️  Complexity Mass (complexity × lines)")?;
+            writeln!(out, "=======================================\n")?;
+
+            let mut mass_table = Table::new();
+            mass_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Path", "Scope", "Share", "Weighted Complexity"]);
+            for entry in heaviest(results, MassScope::Directory).chain(heaviest(results, MassScope::File)) {
+                mass_table.add_row(vec![
+                    Cell::new(&entry.path).fg(Color::Cyan),
+                    Cell::new(mass_scope_label(entry.scope)),
+                    Cell::new(format!("{:.1}%", entry.share_percent)),
+                    Cell::new(format!("{:.1}", entry.weighted_average_complexity)),
+                ]);
+            }
+            writeln!(out, "{}", mass_table)?;
+        }
+
+        // Complexity distribution
+        self.write_complexity_histogram(&mut out, &results.complexity_distribution, &results.thresholds)?;
+
+        if !results.calibration.is_empty() {
+            writeln!(out, "\n🎯 Compared to Reference Projects")?;
+            writeln!(out, "================================\n")?;
+            for standing in &results.calibration {
+                writeln!(out, "• {}", standing.describe())?;
+            }
+        }
+
+        if !results.file_duplication.is_empty() {
+            writeln!(out, "\n🧬 Duplication ({} repeated blocks)", results.clones.len())?;
+            writeln!(out, "================================\n")?;
+
+            let mut duplication_table = Table::new();
+            duplication_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["File", "Duplicated Lines", "Duplicated"]);
+
+            for file in results.file_duplication.iter().take(10) {
+                duplication_table.add_row(vec![
+                    Cell::new(&file.file_path).fg(Color::Cyan),
+                    Cell::new(format!("{}/{}", file.duplicated_lines, file.total_lines)),
+                    Cell::new(format!("{:.1}%", file.duplication_percent)).fg(duplication_color(file.duplication_percent)),
+                ]);
+            }
+
+            writeln!(out, "{}", duplication_table)?;
+
+            if results.file_duplication.len() > 10 {
+                writeln!(out, "... and {} more", results.file_duplication.len() - 10)?;
+            }
+        }
+
+        if !results.issues.is_empty() {
+            writeln!(out, "\n🔒 Findings ({})", results.issues.len())?;
+            writeln!(out, "============\n")?;
+
+            let mut issues_table = Table::new();
+            issues_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Rule", "Location", "Finding"]);
+
+            for found in &results.issues {
+                issues_table.add_row(vec![
+                    Cell::new(&found.issue.rule_id).fg(level_color(ThresholdLevel::of_severity(&found.issue.severity))),
+                    Cell::new(issue_location(found)).fg(Color::Cyan),
+                    Cell::new(&found.issue.message),
+                ]);
+            }
+
+            writeln!(out, "{}", issues_table)?;
+        }
+
+        if !results.suppressions.is_empty() {
+            writeln!(out, "\n🙈 Suppressed Issues")?;
+            writeln!(out, "===================\n")?;
+
+            let mut suppressed_table = Table::new();
+            suppressed_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Rule", "Location", "Until", "Reason"]);
+
+            for suppressed in &results.suppressions {
+                suppressed_table.add_row(vec![
+                    Cell::new(&suppressed.rule),
+                    Cell::new(suppressed_location(suppressed)).fg(Color::Cyan),
+                    Cell::new(suppressed.until.map_or_else(|| "-".to_string(), |until| until.to_string())),
+                    Cell::new(format!("{} ({})", suppressed.reason.as_deref().unwrap_or("-"), suppressed.source)),
+                ]);
+            }
+
+            writeln!(out, "{}", suppressed_table)?;
+        }
+
+        if !results.debt_due.is_empty() {
+            writeln!(out, "\n⏳ Debt Coming Due (suppressions expired or expiring)")?;
+            writeln!(out, "===================================================\n")?;
+
+            let mut debt_table = Table::new();
+            debt_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Until", "Rule", "Location", "Reason"]);
+
+            for debt in &results.debt_due {
+                let location = match &debt.function {
+                    Some(function) => format!("{} ({})", debt.file_path, function),
+                    None => debt.file_path.clone(),
+                };
+                debt_table.add_row(vec![
+                    Cell::new(format!("{}{}", debt.until, if debt.expired { " (expired)" } else { "" }))
+                        .fg(if debt.expired { Color::Red } else { Color::Yellow }),
+                    Cell::new(&debt.rule),
+                    Cell::new(&location).fg(Color::Cyan),
+                    Cell::new(debt.reason.as_deref().unwrap_or("-")),
+                ]);
+            }
+
+            writeln!(out, "{}", debt_table)?;
+        }
+
+        if !results.hotspots.is_empty() {
+            writeln!(out, "\n🔥 Hotspots (complex and frequently changed, or duplicated)")?;
+            writeln!(out, "=========================================================\n")?;
+
+            let mut hotspot_table = Table::new();
+            hotspot_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["File", "Why", "Score", "Complexity", "Commits", "Churn (+/-)", "Authors"]);
+
+            for hotspot in results.hotspots.iter().take(10) {
+                hotspot_table.add_row(vec![
+                    Cell::new(&hotspot.file_path).fg(Color::Cyan),
+                    Cell::new(hotspot_reason(hotspot)),
+                    Cell::new(format!("{:.0}", hotspot.score)).fg(if hotspot.score >= 50.0 { Color::Red } else { Color::Yellow }),
+                    Cell::new(hotspot.complexity.to_string()),
+                    Cell::new(hotspot.commits.to_string()),
+                    Cell::new(format!("+{}/-{}", hotspot.lines_added, hotspot.lines_removed)),
+                    Cell::new(hotspot.authors.to_string()),
+                ]);
+            }
+
+            writeln!(out, "{}", hotspot_table)?;
+
+            if results.hotspots.len() > 10 {
+                writeln!(out, "... and {} more", results.hotspots.len() - 10)?;
+            }
+        }
+
+        if !results.vocabulary.is_empty() {
+            writeln!(out, "\n🔤 Vocabulary (distinct identifiers per module)")?;
+            writeln!(out, "=============================================\n")?;
+
+            let mut vocabulary_table = Table::new();
+            vocabulary_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Module", "Files", "Identifiers", "Vocabulary", "Entropy (bits)"]);
+
+            for module in results.vocabulary.iter().take(10) {
+                vocabulary_table.add_row(vec![
+                    Cell::new(&module.module).fg(Color::Cyan),
+                    Cell::new(module.files.to_string()),
+                    Cell::new(module.identifiers.to_string()),
+                    Cell::new(module.vocabulary.to_string()),
+                    Cell::new(format!("{:.2}", module.entropy)),
+                ]);
+            }
+
+            writeln!(out, "{}", vocabulary_table)?;
+
+            if results.vocabulary.len() > 10 {
+                writeln!(out, "... and {} more", results.vocabulary.len() - 10)?;
+            }
+        }
+
+        if !results.concurrency.is_empty() {
+            writeln!(out, "\n🔀 Go Concurrency (goroutines, channel operations, selects)")?;
+            writeln!(out, "=========================================================\n")?;
+
+            let mut concurrency_table = Table::new();
+            concurrency_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Function", "File", "Goroutines", "Channel Ops", "Selects", "Complexity"]);
+
+            let functions = concurrency_functions(results);
+            for (file, function) in functions.iter().take(10) {
+                let name = if function.hotspot { format!("{} 🔥", function.name) } else { function.name.clone() };
+                concurrency_table.add_row(vec![
+                    Cell::new(&name).fg(if function.hotspot { Color::Red } else { Color::Cyan }),
+                    Cell::new(format!("{}:{}", file, function.line)),
+                    Cell::new(function.goroutines.to_string()),
+                    Cell::new(function.channel_operations.to_string()),
+                    Cell::new(function.selects.to_string()),
+                    Cell::new(function.complexity.to_string()),
+                ]);
+            }
+
+            writeln!(out, "{}", concurrency_table)?;
+
+            if functions.len() > 10 {
+                writeln!(out, "... and {} more", functions.len() - 10)?;
+            }
+            let hotspots = functions.iter().filter(|(_, function)| function.hotspot).count();
+            if hotspots > 0 {
+                writeln!(out, "🔥 {} concurrency hotspot(s): heavy concurrency in a complex function ({})", hotspots, rules::CONCURRENCY_HOTSPOT)?;
+            }
+        }
+
+        if !results.types.is_empty() {
+            writeln!(out, "\n🧩 Types (least cohesive first)")?;
+            writeln!(out, "==============================\n")?;
+
+            let mut types_table = Table::new();
+            types_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Type", "File", "Methods", "Fields", "WMC", "LCOM", "DIT"]);
+
+            for type_ in results.types.iter().take(10) {
+                types_table.add_row(vec![
+                    Cell::new(&type_.name).fg(Color::Cyan),
+                    Cell::new(format!("{}:{}", type_.file_path, type_.line)),
+                    Cell::new(type_.methods.to_string()),
+                    Cell::new(type_.fields.to_string()),
+                    Cell::new(type_.wmc.to_string()),
+                    Cell::new(type_.lcom.to_string()).fg(if type_.lcom > 1 { Color::Yellow } else { Color::Green }),
+                    Cell::new(type_.depth_of_inheritance.map_or_else(|| "-".to_string(), |depth| depth.to_string())),
+                ]);
+            }
+
+            writeln!(out, "{}", types_table)?;
+
+            if results.types.len() > 10 {
+                writeln!(out, "... and {} more", results.types.len() - 10)?;
+            }
+        }
+
+        if !results.impl_surface.is_empty() {
+            writeln!(out, "\n🦀 Rust Impl Surface (most impl blocks first)")?;
+            writeln!(out, "============================================\n")?;
+
+            let mut surface_table = Table::new();
+            surface_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Type", "File", "Impl Blocks", "Inherent", "Traits"]);
+
+            for surface in results.impl_surface.iter().take(10) {
+                surface_table.add_row(vec![
+                    Cell::new(&surface.name).fg(Color::Cyan),
+                    Cell::new(format!("{}:{}", surface.file_path, surface.line)),
+                    Cell::new(surface.impl_blocks.to_string()),
+                    Cell::new(surface.inherent_impls.to_string()),
+                    Cell::new(surface.traits.join(", ")),
+                ]);
+            }
+
+            writeln!(out, "{}", surface_table)?;
+
+            if results.impl_surface.len() > 10 {
+                writeln!(out, "... and {} more", results.impl_surface.len() - 10)?;
+            }
+        }
+
+        if !results.generic_signatures.is_empty() {
+            writeln!(out, "\n🦀 Rust Generic Signatures (most trait bounds first)")?;
+            writeln!(out, "===================================================\n")?;
+
+            let mut signatures_table = Table::new();
+            signatures_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Function", "File", "Type Params", "Trait Bounds", "Lifetimes"]);
+
+            for signature in results.generic_signatures.iter().take(10) {
+                signatures_table.add_row(vec![
+                    Cell::new(&signature.name).fg(Color::Cyan),
+                    Cell::new(format!("{}:{}", signature.file_path, signature.line)),
+                    Cell::new(signature.type_parameters.to_string()),
+                    Cell::new(signature.trait_bounds.to_string()).fg(if signature.too_generic { Color::Red } else { Color::Green }),
+                    Cell::new(signature.lifetimes.to_string()),
+                ]);
+            }
+
+            writeln!(out, "{}", signatures_table)?;
+
+            if results.generic_signatures.len() > 10 {
+                writeln!(out, "... and {} more", results.generic_signatures.len() - 10)?;
+            }
+            let too_generic = results.generic_signatures.iter().filter(|signature| signature.too_generic).count();
+            if too_generic > 0 {
+                writeln!(out, "⚠️  {} signature(s) over the trait bound limit ({})", too_generic, rules::GENERIC_API_COMPLEXITY)?;
+            }
+        }
+
+        if results.unsafe_density.iter().any(|krate| krate.unsafe_blocks > 0) {
+            writeln!(out, "\n🦀 Rust Unsafe Density (most unjustified blocks per 1000 lines first)")?;
+            writeln!(out, "=====================================================================\n")?;
+
+            let mut unsafe_table = Table::new();
+            unsafe_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Crate", "Lines", "Unsafe Blocks", "With SAFETY", "Without", "Per 1k Lines", "Without per 1k"]);
+
+            for krate in &results.unsafe_density {
+                unsafe_table.add_row(vec![
+                    Cell::new(&krate.name).fg(Color::Cyan),
+                    Cell::new(krate.lines.to_string()),
+                    Cell::new(krate.unsafe_blocks.to_string()),
+                    Cell::new(krate.justified.to_string()),
+                    Cell::new(krate.unjustified.to_string()).fg(if krate.unjustified > 0 { Color::Red } else { Color::Green }),
+                    Cell::new(format!("{:.2}", krate.density)),
+                    Cell::new(format!("{:.2}", krate.unjustified_density)),
+                ]);
+            }
+
+            writeln!(out, "{}", unsafe_table)?;
+        }
+
+        if !results.call_graph.is_empty() {
+            writeln!(out, "\n🕸  Call Graph (most called first)")?;
+            writeln!(out, "================================\n")?;
+
+            let mut calls_table = Table::new();
+            calls_table
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_header(vec!["Function", "File", "Fan-in", "Fan-out"]);
+
+            for node in results.call_graph.iter().take(10) {
+                calls_table.add_row(vec![
+                    Cell::new(&node.name).fg(Color::Cyan),
+                    Cell::new(format!("{}:{}", node.file_path, node.line_start)),
+                    Cell::new(node.fan_in.to_string()),
+                    Cell::new(node.fan_out.to_string()),
+                ]);
+            }
+
+            writeln!(out, "{}", calls_table)?;
+
+            if results.call_graph.len() > 10 {
+                writeln!(out, "... and {} more", results.call_graph.len() - 10)?;
+            }
+        }
+
+        if !results.repeated_files.is_empty() {
+            writeln!(out, "\n🗂  Repeated Files (identical content, counted once)")?;
+            writeln!(out, "==================================================\n")?;
+            for group in &results.repeated_files {
+                writeln!(out, "• {} lines × {}: {}", group.lines, group.paths.len(), repeated_paths(group))?;
+            }
+        }
+
+        if results.capabilities.unavailable_entries().next().is_some() {
+            writeln!(out, "\n⚪ Data Unavailable")?;
+            writeln!(out, "===================\n")?;
+            for (name, reason) in results.capabilities.unavailable_entries() {
+                writeln!(out, "• {}: {}", name, reason)?;
+            }
+        }
+
+        if !results.errors.is_empty() {
+            writeln!(out, "\n❌ Parsing Errors")?;
+            writeln!(out, "=================\n")?;
+            for error in &results.errors {
+                writeln!(out, "• {}", error)?;
+            }
+        }
+
+        Ok(out)
+    }
+
+    fn write_health_breakdown(&self, out: &mut String, health: &HealthScore, capabilities: &Capabilities) -> Result<()> {
+        writeln!(out, "\n🩺 Health Sub-scores")?;
+        writeln!(out, "===================\n")?;
+
+        for sub in &health.sub_scores {
+            match sub.score {
+                Some(score) => writeln!(out, "{:12} {:>5.0}  (weight {:.2})", sub.dimension, score, sub.weight)?,
+                None => writeln!(
+                    out,
+                    "{:12}   n/a  (data unavailable: {})",
+                    sub.dimension,
+                    capabilities.reason(sub.dimension).unwrap_or("no data")
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// shields.io endpoint JSON for the health score
+    pub fn render_badge(&self, results: &AnalysisResults) -> Result<String> {
+        let health = results.health.as_ref()
+            .context("Health score was not computed")?;
+        Ok(serde_json::to_string_pretty(&health.shields_endpoint())?)
+    }
+
+    /// One row per function in `results.functions`, as a Parquet file
+    #[cfg(feature = "parquet")]
+    pub fn render_parquet(&self, results: &AnalysisResults) -> Result<Vec<u8>> {
+        parquet::write_functions(&results.functions)
+    }
+
+    #[cfg(not(feature = "parquet"))]
+    pub fn render_parquet(&self, _results: &AnalysisResults) -> Result<Vec<u8>> {
+        anyhow::bail!("This build has no Parquet support; rebuild with `--features parquet`")
+    }
+
+    /// Files, Functions and Issues sheets as an Excel workbook
+    #[cfg(feature = "xlsx")]
+    pub fn render_xlsx(&self, results: &AnalysisResults) -> Result<Vec<u8>> {
+        xlsx::write_workbook(results)
+    }
+
+    #[cfg(not(feature = "xlsx"))]
+    pub fn render_xlsx(&self, _results: &AnalysisResults) -> Result<Vec<u8>> {
+        anyhow::bail!("This build has no xlsx support; rebuild with `--features xlsx`")
+    }
+
+    fn write_complexity_histogram(&self, out: &mut String, distribution: &HashMap<u32, u32>, thresholds: &Thresholds) -> Result<()> {
+        writeln!(out, "\n📈 Complexity Distribution")?;
+        writeln!(out, "==========================\n")?;
+
+        let mut sorted_complexities: Vec<(&u32, &u32)> = distribution.iter().collect();
+        sorted_complexities.sort_by_key(|(complexity, _)| *complexity);
+
+        let max_count = *distribution.values().max().unwrap_or(&0);
+
+        let mut table = Table::new();
+        table.load_preset(comfy_table::presets::NOTHING);
+        for (complexity, count) in sorted_complexities.iter().take(20) { // Show up to complexity 20
+            let bar_length = if max_count > 0 {
+                ((**count as f64 / max_count as f64) * 40.0) as usize
+            } else {
+                0
+            };
+
+            let bar = "█".repeat(bar_length);
+            let color = match thresholds.function_level(**complexity) {
+                ThresholdLevel::Ok if **complexity >= thresholds.function_complexity_warn / 2 => Color::Blue,
+                level => level_color(level),
+            };
+
+            table.add_row(vec![
+                Cell::new(format!("{:2}:", complexity)),
+                Cell::new(&bar).fg(color),
+                Cell::new(format!("({})", count)),
+            ]);
+        }
+        writeln!(out, "{}", table)?;
+
+        Ok(())
+    }
+
+    pub fn render_json(&self, results: &AnalysisResults) -> Result<String> {
+        serde_json::to_string_pretty(results)
+            .context("Failed to serialize results to JSON")
+    }
+
+    pub fn render_html(&self, results: &AnalysisResults) -> Result<String> {
+        let mut report_data = ReportData::from(results);
+        report_data.sections = self.sections.render(&self.handlebars, results)?;
+        self.handlebars.render("html_report", &report_data)
+            .context("Failed to render HTML template")
+    }
+
+    /// SARIF 2.1.0 log, for code scanning UIs that annotate issues inline
+    pub fn render_sarif(&self, results: &AnalysisResults) -> Result<String> {
+        let functions = results.high_complexity_functions.iter().map(|func| {
+            sarif_result(
+                rules::HIGH_CYCLOMATIC_COMPLEXITY,
+                results.thresholds.function_level(func.complexity),
+                format!("`{}` has cyclomatic complexity {}", func.name, func.complexity),
+                &func.file_path,
+                Some(func.line_start),
+            )
+        });
+        let files = results.high_complexity_files.iter().map(|file| {
+            sarif_result(
+                rules::HIGH_FILE_COMPLEXITY,
+                file.level,
+                format!("File has total complexity {} across {} functions", file.total_complexity, file.functions),
+                &file.file_path,
+                None,
+            )
+        });
+        let clones = results.clones.iter().flat_map(|class| {
+            class.locations.iter().map(move |location| {
+                sarif_result(
+                    rules::DUPLICATED_CODE,
+                    ThresholdLevel::Warn,
+                    format!(
+                        "Lines {}-{} are repeated in {} other places",
+                        location.line_start,
+                        location.line_end,
+                        class.locations.len() - 1
+                    ),
+                    &location.file_path,
+                    Some(location.line_start),
+                )
+            })
+        });
+
+        let issues = results.issues.iter().map(|found| {
+            let mut result = sarif_result(
+                &found.issue.rule_id,
+                ThresholdLevel::of_severity(&found.issue.severity),
+                found.issue.message.clone(),
+                &found.file_path,
+                Some(found.issue.line),
+            );
+            result["locations"][0]["physicalLocation"]["region"]["startColumn"] = json!(found.issue.column);
+            if let Some(origin) = &found.issue.origin {
+                result["relatedLocations"] = json!([{
+                    "id": 1,
+                    "message": { "text": format!("Input `{}`", origin.excerpt) },
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": found.file_path.replace('\\', "/") },
+                        "region": { "startLine": origin.line, "startColumn": origin.column },
+                    },
+                }]);
+            }
+            result
+        });
+
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "codemetrics",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules::RULES.iter().map(|rule| json!({
+                            "id": rule.id,
+                            "name": rule.name,
+                            "shortDescription": { "text": rule.description },
+                            "help": { "text": format!("{}\n\nHow to fix: {}", rule.docs.why, rule.docs.fix) },
+                        })).collect::<Vec<_>>(),
+                    }
+                },
+                "results": functions.chain(files).chain(clones).chain(issues).collect::<Vec<_>>(),
+            }]
+        });
+        serde_json::to_string_pretty(&log).context("Failed to serialize SARIF log")
+    }
+
+    pub fn render_markdown(&self, results: &AnalysisResults) -> Result<String> {
+        let mut markdown = String::new();
+
+        markdown.push_str("# Code Analysis Report\n\n");
+
+        markdown.push_str("## Overview\n\n");
+        markdown.push_str(&format!("- **Files Analyzed:** {}\n", results.files_analyzed));
+        markdown.push_str(&format!("- **Total Functions:** {}\n", results.total_functions));
+        markdown.push_str(&format!("- **Average Complexity:** {:.2}\n", results.average_complexity));
+        markdown.push_str(&format!("- **Weighted Complexity (by LOC):** {:.2}\n", results.weighted_average_complexity));
+        markdown.push_str(&format!("- **High Complexity Functions:** {}\n", results.high_complexity_functions.len()));
+        if let Some(shard) = &results.shard {
+            markdown.push_str(&format!("- **Shard:** {}\n", shard_summary(shard)));
+        }
+        if let Some(health) = &results.health {
+            markdown.push_str(&format!("- **Health Score:** {:.0}/100 ({})\n", health.overall, health.grade));
+        }
+        if !results.skip_counts.is_empty() {
+            markdown.push_str(&format!("- **Skipped:** {}\n", skip_summary(results)));
+        }
+        if let Some(duplication) = results.duplication_percent {
+            markdown.push_str(&format!("- **Duplicated Lines:** {:.1}%\n", duplication));
+        }
+        if results.baseline_suppressed > 0 {
+            markdown.push_str(&format!("- **Hidden by baseline:** {} pre-existing issues\n", results.baseline_suppressed));
+        }
+        if results.suppressed > 0 {
+            markdown.push_str(&format!("- **Suppressed:** {} issues\n", results.suppressed));
+        }
+        markdown.push('\n');
+
+        if let Some(health) = &results.health {
+            markdown.push_str("## Health Breakdown\n\n");
+            markdown.push_str("| Dimension | Score | Weight |\n");
+            markdown.push_str("|-----------|-------|--------|\n");
+            for sub in &health.sub_scores {
+                let score = sub.score.map(|s| format!("{:.0}", s)).unwrap_or_else(|| "n/a".to_string());
+                markdown.push_str(&format!("| {} | {} | {:.2} |\n", sub.dimension, score, sub.weight));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.stack.is_empty() {
+            markdown.push_str("## Stack\n\n");
+            markdown.push_str("| Framework | Language | Evidence |\n");
+            markdown.push_str("|-----------|----------|----------|\n");
+            for framework in &results.stack {
+                markdown.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    framework.name, framework.language, framework.evidence.join(", ")
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.language_breakdown.is_empty() {
+            markdown.push_str("## Language Breakdown\n\n");
+            markdown.push_str("| Language | Files | Functions | Percentage | Avg Complexity | Maintainability | Issues/KLOC | Tests/File |\n");
+            markdown.push_str("|----------|-------|-----------|------------|----------------|-----------------|-------------|------------|\n");
+
+            for quality in &results.language_quality {
+                let percentage = (quality.files as f64 / results.files_analyzed as f64) * 100.0;
+                markdown.push_str(&format!(
+                    "| {} | {} | {} | {:.1}% | {:.1} | {:.0} | {:.1} | {:.2} |\n",
+                    quality.language, quality.files, quality.functions, percentage,
+                    quality.average_complexity, quality.maintainability, quality.issue_density, quality.test_ratio
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.calibration.is_empty() {
+            markdown.push_str("## Compared to Reference Projects\n\n");
+            markdown.push_str("| Language | Metric | Value | Percentile | Reference Set |\n");
+            markdown.push_str("|----------|--------|-------|------------|---------------|\n");
+            for standing in &results.calibration {
+                markdown.push_str(&format!(
+                    "| {} | {} | {:.2} | {:.0} | {} projects ({}) |\n",
+                    standing.language,
+                    standing.metric,
+                    standing.value,
+                    standing.percentile,
+                    standing.reference_projects,
+                    standing.size_bucket
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.high_complexity_functions.is_empty() {
+            markdown.push_str("## High Complexity Functions\n\n");
+            markdown.push_str("| Function | Complexity | Parameters | Stability | Location |\n");
+            markdown.push_str("|----------|------------|------------|-----------|----------|\n");
+
+            for func in unclustered_functions(results).take(20) {
+                markdown.push_str(&format!(
+                    "| `{}` | {} | {} | {} | `{}:{}` |\n",
+                    func.name,
+                    func.complexity,
+                    func.parameters,
+                    func.stability.map(|s| s.label()).unwrap_or("-"),
+                    func.file_path,
+                    func.line_start
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.high_complexity_files.is_empty() {
+            markdown.push_str("## High Complexity Files\n\n");
+            markdown.push_str("| File | Total Complexity | Functions |\n");
+            markdown.push_str("|------|------------------|-----------|\n");
+
+            for file in unclustered_files(results).take(20) {
+                markdown.push_str(&format!(
+                    "| `{}` | {} | {} |\n",
+                    file.file_path, file.total_complexity, file.functions
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.complexity_mass.is_empty() {
+            markdown.push_str("## Complexity Mass\n\n");
+            markdown.push_str("Where complexity concentrates: each function's complexity times its lines, summed.\n\n");
+            markdown.push_str("| Path | Scope | Share | Weighted Complexity |\n");
+            markdown.push_str("|------|-------|-------|---------------------|\n");
+            for entry in heaviest(results, MassScope::Directory).chain(heaviest(results, MassScope::File)) {
+                markdown.push_str(&format!(
+                    "| `{}` | {} | {:.1}% | {:.1} |\n",
+                    entry.path, mass_scope_label(entry.scope), entry.share_percent, entry.weighted_average_complexity
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.issue_clusters.is_empty() {
+            markdown.push_str("## Clustered Findings\n\n");
+            for cluster in &results.issue_clusters {
+                markdown.push_str(&format!("<details><summary>{}</summary>\n\n", cluster_summary(cluster)));
+                for member in &cluster.members {
+                    markdown.push_str(&format!("- {}\n", member_label(member)));
+                }
+                markdown.push_str("\n</details>\n\n");
+            }
+        }
+
+        if !results.file_duplication.is_empty() {
+            markdown.push_str("## Duplication\n\n");
+            markdown.push_str(&format!("{} blocks are repeated across the project.\n\n", results.clones.len()));
+            markdown.push_str("| File | Duplicated Lines | Duplicated |\n");
+            markdown.push_str("|------|------------------|------------|\n");
+            for file in results.file_duplication.iter().take(20) {
+                markdown.push_str(&format!(
+                    "| `{}` | {}/{} | {:.1}% |\n",
+                    file.file_path, file.duplicated_lines, file.total_lines, file.duplication_percent
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.issues.is_empty() {
+            markdown.push_str("## Findings\n\n");
+            markdown.push_str("| Rule | Location | Finding |\n");
+            markdown.push_str("|------|----------|---------|\n");
+            for found in &results.issues {
+                markdown.push_str(&format!(
+                    "| {} | `{}` | {} |\n",
+                    found.issue.rule_id,
+                    issue_location(found).replace('\n', "`<br>`"),
+                    found.issue.message.replace('|', "\\|"),
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.suppressions.is_empty() {
+            markdown.push_str("## Suppressed Issues\n\n");
+            markdown.push_str("Hidden by `codemetrics-ignore` comments or `[[suppress]]` entries in codemetrics.toml.\n\n");
+            markdown.push_str("| Rule | Location | Until | Reason |\n");
+            markdown.push_str("|------|----------|-------|--------|\n");
+            for suppressed in &results.suppressions {
+                markdown.push_str(&format!(
+                    "| {} | `{}` | {} | {} ({}) |\n",
+                    suppressed.rule,
+                    suppressed_location(suppressed),
+                    suppressed.until.map_or_else(|| "-".to_string(), |until| until.to_string()),
+                    suppressed.reason.as_deref().unwrap_or("-"),
+                    suppressed.source,
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.debt_due.is_empty() {
+            markdown.push_str("## Debt Coming Due\n\n");
+            markdown.push_str("Expired suppressions report their issue again; the others expire soon.\n\n");
+            markdown.push_str("| Until | Rule | Location | Reason |\n");
+            markdown.push_str("|-------|------|----------|--------|\n");
+            for debt in &results.debt_due {
+                let location = match &debt.function {
+                    Some(function) => format!("`{}` in `{}`", function, debt.file_path),
+                    None => format!("`{}`", debt.file_path),
+                };
+                markdown.push_str(&format!(
+                    "| {}{} | {} | {} | {} |\n",
+                    debt.until, if debt.expired { " ⚠️ expired" } else { "" },
+                    debt.rule, location, debt.reason.as_deref().unwrap_or("-")
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.hotspots.is_empty() {
+            markdown.push_str("## Hotspots\n\n");
+            markdown.push_str("Complexity weighted by how often each file changed in recent history, and files mostly made of repeated blocks.\n\n");
+            markdown.push_str("| File | Why | Score | Complexity | Commits | Churn | Authors |\n");
+            markdown.push_str("|------|-----|-------|------------|---------|-------|---------|\n");
+            for hotspot in results.hotspots.iter().take(20) {
+                markdown.push_str(&format!(
+                    "| `{}` | {} | {:.0} | {} | {} | +{}/-{} | {} |\n",
+                    hotspot.file_path, hotspot_reason(hotspot), hotspot.score, hotspot.complexity, hotspot.commits,
+                    hotspot.lines_added, hotspot.lines_removed, hotspot.authors
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.vocabulary.is_empty() {
+            markdown.push_str("## Vocabulary\n\n");
+            markdown.push_str("| Module | Files | Identifiers | Vocabulary | Entropy (bits) |\n");
+            markdown.push_str("|--------|-------|-------------|------------|----------------|\n");
+            for module in results.vocabulary.iter().take(20) {
+                markdown.push_str(&format!(
+                    "| `{}` | {} | {} | {} | {:.2} |\n",
+                    module.module, module.files, module.identifiers, module.vocabulary, module.entropy
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.concurrency.is_empty() {
+            markdown.push_str("## Go Concurrency\n\n");
+            markdown.push_str("🔥 marks concurrency hotspots: heavy goroutine and channel use in a complex function.\n\n");
+            markdown.push_str("| Function | File | Goroutines | Channel Ops | Selects | Complexity |\n");
+            markdown.push_str("|----------|------|------------|-------------|---------|------------|\n");
+            for (file, function) in concurrency_functions(results).iter().take(20) {
+                markdown.push_str(&format!(
+                    "| `{}`{} | `{}:{}` | {} | {} | {} | {} |\n",
+                    function.name, if function.hotspot { " 🔥" } else { "" }, file, function.line,
+                    function.goroutines, function.channel_operations, function.selects, function.complexity
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.types.is_empty() {
+            markdown.push_str("## Types\n\n");
+            markdown.push_str("LCOM counts groups of methods sharing no field or call; above 1 the type may be doing several jobs.\n\n");
+            markdown.push_str("| Type | File | Methods | Fields | WMC | LCOM | DIT |\n");
+            markdown.push_str("|------|------|---------|--------|-----|------|-----|\n");
+            for type_ in results.types.iter().take(20) {
+                markdown.push_str(&format!(
+                    "| `{}` | `{}:{}` | {} | {} | {} | {} | {} |\n",
+                    type_.name, type_.file_path, type_.line, type_.methods, type_.fields, type_.wmc, type_.lcom,
+                    type_.depth_of_inheritance.map_or_else(|| "-".to_string(), |depth| depth.to_string())
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.impl_surface.is_empty() {
+            markdown.push_str("## Rust Impl Surface\n\n");
+            markdown.push_str("| Type | File | Impl Blocks | Inherent | Traits |\n");
+            markdown.push_str("|------|------|-------------|----------|--------|\n");
+            for surface in results.impl_surface.iter().take(20) {
+                markdown.push_str(&format!(
+                    "| `{}` | `{}:{}` | {} | {} | {} |\n",
+                    surface.name, surface.file_path, surface.line, surface.impl_blocks, surface.inherent_impls, surface.traits.join(", ")
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.generic_signatures.is_empty() {
+            markdown.push_str("## Rust Generic Signatures\n\n");
+            markdown.push_str("⚠️ marks signatures with more trait bounds than the limit.\n\n");
+            markdown.push_str("| Function | File | Type Params | Trait Bounds | Lifetimes |\n");
+            markdown.push_str("|----------|------|-------------|--------------|-----------|\n");
+            for signature in results.generic_signatures.iter().take(20) {
+                markdown.push_str(&format!(
+                    "| `{}`{} | `{}:{}` | {} | {} | {} |\n",
+                    signature.name, if signature.too_generic { " ⚠️" } else { "" }, signature.file_path, signature.line,
+                    signature.type_parameters, signature.trait_bounds, signature.lifetimes
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if results.unsafe_density.iter().any(|krate| krate.unsafe_blocks > 0) {
+            markdown.push_str("## Rust Unsafe Density\n\n");
+            markdown.push_str(&format!("Blocks without a `// SAFETY:` comment are reported under {}.\n\n", rules::UNJUSTIFIED_UNSAFE));
+            markdown.push_str("| Crate | Lines | Unsafe Blocks | With SAFETY | Without | Per 1k Lines | Without per 1k |\n");
+            markdown.push_str("|-------|-------|---------------|-------------|---------|--------------|----------------|\n");
+            for krate in &results.unsafe_density {
+                markdown.push_str(&format!(
+                    "| `{}` | {} | {} | {} | {} | {:.2} | {:.2} |\n",
+                    krate.name, krate.lines, krate.unsafe_blocks, krate.justified, krate.unjustified, krate.density, krate.unjustified_density
+                ));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.call_graph.is_empty() {
+            markdown.push_str("## Call Graph\n\n");
+            markdown.push_str("Calls are matched to functions by name, so functions sharing a name share their callers.\n\n");
+            markdown.push_str("| Function | File | Fan-in | Fan-out |\n");
+            markdown.push_str("|----------|------|--------|---------|\n");
+            for node in results.call_graph.iter().take(20) {
+                markdown.push_str(&format!("| `{}` | `{}:{}` | {} | {} |\n", node.name, node.file_path, node.line_start, node.fan_in, node.fan_out));
+            }
+            markdown.push('\n');
+        }
+
+        if !results.repeated_files.is_empty() {
+            markdown.push_str("## Repeated Files\n\n");
+            markdown.push_str("Identical content found at several paths; only the first copy is counted.\n\n");
+            markdown.push_str("| Lines | Copies | Paths |\n");
+            markdown.push_str("|-------|--------|-------|\n");
+            for group in &results.repeated_files {
+                markdown.push_str(&format!("| {} | {} | {} |\n", group.lines, group.paths.len(), repeated_paths(group)));
+            }
+            markdown.push('\n');
+        }
+
+        if results.capabilities.unavailable_entries().next().is_some() {
+            markdown.push_str("## Data Unavailable\n\n");
+            for (name, reason) in results.capabilities.unavailable_entries() {
+                markdown.push_str(&format!("- **{}:** data unavailable: {}\n", name, reason));
+            }
+            markdown.push('\n');
+        }
+
+        markdown.push_str("## Methodology\n\n");
+        for note in methodology(results) {
+            markdown.push_str(&format!("- {}\n", note));
+        }
+        markdown.push('\n');
+
+        Ok(markdown)
+    }
+}
+
+fn sarif_result(rule_id: &str, level: ThresholdLevel, message: String, file_path: &str, line: Option<u32>) -> Value {
+    let level = match level {
+        ThresholdLevel::Critical | ThresholdLevel::Error => "error",
+        ThresholdLevel::Warn => "warning",
+        ThresholdLevel::Ok => "note",
+    };
+    let mut location = json!({ "artifactLocation": { "uri": file_path.replace('\\', "/") } });
+    if let Some(line) = line {
+        location["region"] = json!({ "startLine": line });
+    }
+    json!({
+        "ruleId": rule_id,
+        "level": level,
+        "message": { "text": message },
+        "locations": [{ "physicalLocation": location }],
+    })
+}
+
+/// "2/8" or "2/8 covering @org/payments, @org/search"
+fn shard_summary(shard: &ShardOutput) -> String {
+    if shard.groups.is_empty() {
+        return shard.shard.to_string();
+    }
+    let groups: Vec<&str> = shard.groups.iter().map(String::as_str).collect();
+    format!("{} covering {}", shard.shard, groups.join(", "))
+}
+
+/// High complexity functions not shown as part of a cluster, worst first
+fn unclustered_functions(results: &AnalysisResults) -> impl Iterator<Item = &HighComplexityFunction> {
+    results.high_complexity_functions.iter()
+        .filter(|func| !clusters::is_clustered(&results.issue_clusters, rules::HIGH_CYCLOMATIC_COMPLEXITY, &func.file_path))
+}
+
+fn unclustered_files(results: &AnalysisResults) -> impl Iterator<Item = &HighComplexityFile> {
+    results.high_complexity_files.iter()
+        .filter(|file| !clusters::is_clustered(&results.issue_clusters, rules::HIGH_FILE_COMPLEXITY, &file.file_path))
+}
+
+/// "CM0101 high-cyclomatic-complexity in api/generated: 143 findings"
+fn cluster_summary(cluster: &IssueCluster) -> String {
+    let name = rules::find_rule(cluster.rule_id).map(|rule| rule.name).unwrap_or_default();
+    format!("{} {} in {}: {} findings", cluster.rule_id, name, cluster.directory, cluster.count)
+}
+
+/// `name` at `file:line`, or just the file for file-level rules
+fn member_label(member: &ClusterMember) -> String {
+    match (&member.name, member.line) {
+        (Some(name), Some(line)) => format!("`{}` at `{}:{}`", name, member.file_path, line),
+        _ => format!("`{}`", member.file_path),
+    }
+}
+
+/// How the headline numbers were computed, for the methodology section
+fn methodology(results: &AnalysisResults) -> Vec<String> {
+    let thresholds = &results.thresholds;
+    let mut notes = vec![
+        "Complexity is cyclomatic: 1 plus one per branch, loop, case or exception handler.".to_string(),
+        format!(
+            "Functions are flagged at complexity ≥{} (warning) and ≥{} (error); files when their functions sum to ≥{}.",
+            thresholds.function_complexity_warn, thresholds.function_complexity_error, thresholds.file_complexity_warn
+        ),
+    ];
+    notes.push(
+        "Weighted complexity weights each function by its length in lines; complexity mass is complexity times lines, summed per file and directory.".to_string(),
+    );
+    if results.severity_cutoffs.is_some() {
+        notes.push("Severity bands come from this project's own complexity distribution.".to_string());
+    }
+    if results.min_function_statements > 0 {
+        notes.push(small_function_note(results));
+    }
+    notes
+}
+
+/// The five heaviest entries of one scope
+fn heaviest(results: &AnalysisResults, scope: MassScope) -> impl Iterator<Item = &ComplexityMass> {
+    results.complexity_mass.iter().filter(move |entry| entry.scope == scope).take(5)
+}
+
+fn mass_scope_label(scope: MassScope) -> &'static str {
+    match scope {
+        MassScope::File => "file",
+        MassScope::Directory => "directory",
+    }
+}
+
+/// "src/a.rs:12 (parse)" for a suppressed issue
+/// `path:line:column`, then where the input came from on a second line when it was traced
+fn issue_location(found: &FileIssue) -> String {
+    let location = format!("{}:{}:{}", found.file_path, found.issue.line, found.issue.column);
+    match &found.issue.origin {
+        Some(origin) => format!("{}\ninput at {}:{}", location, origin.line, origin.column),
+        None => location,
+    }
+}
+
+fn suppressed_location(suppressed: &SuppressedIssue) -> String {
+    match &suppressed.function {
+        Some(function) => format!("{}:{} ({})", suppressed.file_path, suppressed.line, function),
+        None => format!("{}:{}", suppressed.file_path, suppressed.line),
+    }
+}
+
+fn small_function_note(results: &AnalysisResults) -> String {
+    format!(
+        "Average complexity and the distribution leave out {} functions with fewer than {} statements; they still count in totals, issues and file complexity.",
+        results.small_functions_excluded, results.min_function_statements
+    )
+}
+
+/// Skip-reason counts as "3 gitignored or hidden, 1 binary"
+fn skip_summary(results: &AnalysisResults) -> String {
+    results.skip_counts.iter()
+        .map(|(reason, count)| format!("{} {}", count, reason.label()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// What put a file on the hotspot list
+fn hotspot_reason(hotspot: &Hotspot) -> String {
+    match hotspot.kind {
+        HotspotKind::Churn => "changes often".to_string(),
+        HotspotKind::DuplicatedCode => format!("{:.0}% duplicated", hotspot.duplication_percent.unwrap_or(hotspot.score)),
+    }
+}
+
+/// Functions using goroutines or channels across all files, hotspots first, then busiest
+fn concurrency_functions(results: &AnalysisResults) -> Vec<(&str, &FunctionConcurrency)> {
+    let mut functions: Vec<(&str, &FunctionConcurrency)> = results.concurrency.iter()
+        .flat_map(|file| file.functions.iter().map(move |function| (file.file_path.as_str(), function)))
+        .collect();
+    functions.sort_by_key(|(_, function)| std::cmp::Reverse((function.hotspot, function.operations())));
+    functions
+}
+
+fn repeated_paths(group: &RepeatedFiles) -> String {
+    group.paths.iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn level_color(level: ThresholdLevel) -> Color {
+    match level {
+        ThresholdLevel::Critical => Color::Magenta,
+        ThresholdLevel::Error => Color::Red,
+        ThresholdLevel::Warn => Color::Yellow,
+        ThresholdLevel::Ok => Color::Green,
+    }
+}
+
+/// Up to 5% duplication is typical; past 15% copies are likely to drift apart
+fn duplication_color(percent: f64) -> Color {
+    match percent {
+        p if p <= 5.0 => Color::Green,
+        p if p <= 15.0 => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+/// Conventional maintainability index bands: 20 and below is hard to maintain
+fn maintainability_color(index: f64) -> Color {
+    match index {
+        i if i >= 65.0 => Color::Green,
+        i if i > 20.0 => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+fn stability_cell(stability: Option<Stability>) -> Cell {
+    match stability {
+        Some(Stability::Volatile) => Cell::new("volatile").fg(Color::Red),
+        Some(Stability::Active) => Cell::new("active").fg(Color::Yellow),
+        Some(Stability::Stable) => Cell::new("stable").fg(Color::Green),
+        None => Cell::new("-"),
+    }
+}
+
+// Data structure for template rendering
+#[derive(Serialize)]
+struct ReportData {
+    files_analyzed: usize,
+    total_functions: usize,
+    average_complexity: f64,
+    weighted_average_complexity: String,
+    high_complexity_count: usize,
+    languages: Vec<LanguageData>,
+    high_complexity_functions: Vec<HighComplexityFunction>,
+    high_complexity_files: Vec<HighComplexityFile>,
+    issue_clusters: Vec<ClusterData>,
+    complexity_mass: Vec<MassData>,
+    complexity_distribution: Vec<ComplexityPoint>,
+    health: Option<HealthScore>,
+    calibration: Vec<Standing>,
+    stack: Vec<DetectedFramework>,
+    duplication_percent: Option<String>,
+    clone_count: usize,
+    file_duplication: Vec<DuplicationData>,
+    repeated_files: Vec<RepeatedFiles>,
+    unavailable: Vec<UnavailableData>,
+    trend: Vec<TrendPoint>,
+    /// Explanations of the rules behind the report's findings
+    rule_docs: Vec<&'static rules::Rule>,
+    methodology: Vec<String>,
+    thresholds: Thresholds,
+    /// Sections added with `Reporter::add_section`
+    sections: Vec<RenderedSection>,
+}
+
+/// A collapsed (rule, directory) pair, expandable in the HTML report
+#[derive(Serialize)]
+struct ClusterData {
+    summary: String,
+    members: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct MassData {
+    path: String,
+    scope: &'static str,
+    share: String,
+    weighted_average_complexity: String,
+}
+
+/// One recorded snapshot on the trend chart
+#[derive(Serialize)]
+struct TrendPoint {
+    commit: String,
+    average_complexity: String,
+    weighted_average_complexity: Option<String>,
+    issues: usize,
+    maintainability: Option<String>,
+}
+
+#[derive(Serialize)]
+struct DuplicationData {
+    file_path: String,
+    duplicated_lines: usize,
+    total_lines: usize,
+    percent: String,
+}
+
+#[derive(Serialize)]
+struct UnavailableData {
+    name: &'static str,
+    reason: String,
+}
+
+#[derive(Serialize)]
+struct LanguageData {
+    name: String,
+    files: usize,
+    functions: usize,
+    percentage: f64,
+    average_complexity: String,
+    maintainability: String,
+    issue_density: String,
+    test_ratio: String,
+}
+
+#[derive(Serialize)]
+struct ComplexityPoint {
+    complexity: u32,
+    count: u32,
+}
+
+impl From<&AnalysisResults> for ReportData {
+    fn from(results: &AnalysisResults) -> Self {
+        let languages: Vec<LanguageData> = results.language_quality
+            .iter()
+            .map(|quality| {
+                let percentage = (quality.files as f64 / results.files_analyzed as f64) * 100.0;
+                LanguageData {
+                    name: quality.language.clone(),
+                    files: quality.files,
+                    functions: quality.functions,
+                    percentage,
+                    average_complexity: format!("{:.1}", quality.average_complexity),
+                    maintainability: format!("{:.0}", quality.maintainability),
+                    issue_density: format!("{:.1}", quality.issue_density),
+                    test_ratio: format!("{:.2}", quality.test_ratio),
+                }
+            })
+            .collect();
+
+        let complexity_distribution: Vec<ComplexityPoint> = results.complexity_distribution
+            .iter()
+            .map(|(&complexity, &count)| ComplexityPoint { complexity, count })
+            .collect();
+
+        ReportData {
+            files_analyzed: results.files_analyzed,
+            total_functions: results.total_functions,
+            average_complexity: results.average_complexity,
+            weighted_average_complexity: format!("{:.2}", results.weighted_average_complexity),
+            high_complexity_count: results.high_complexity_functions.len(),
+            languages,
+            high_complexity_functions: unclustered_functions(results).cloned().collect(),
+            high_complexity_files: unclustered_files(results).cloned().collect(),
+            issue_clusters: results.issue_clusters.iter()
+                .map(|cluster| ClusterData {
+                    summary: cluster_summary(cluster),
+                    members: cluster.members.iter()
+                        .map(|member| match (&member.name, member.line) {
+                            (Some(name), Some(line)) => format!("{} at {}:{}", name, member.file_path, line),
+                            _ => member.file_path.clone(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            complexity_mass: heaviest(results, MassScope::Directory).chain(heaviest(results, MassScope::File))
+                .map(|entry| MassData {
+                    path: entry.path.clone(),
+                    scope: mass_scope_label(entry.scope),
+                    share: format!("{:.1}", entry.share_percent),
+                    weighted_average_complexity: format!("{:.1}", entry.weighted_average_complexity),
+                })
+                .collect(),
+            complexity_distribution,
+            health: results.health.clone(),
+            calibration: results.calibration.clone(),
+            stack: results.stack.clone(),
+            duplication_percent: results.duplication_percent.map(|percent| format!("{:.1}", percent)),
+            clone_count: results.clones.len(),
+            file_duplication: results.file_duplication.iter()
+                .take(20)
+                .map(|file| DuplicationData {
+                    file_path: file.file_path.clone(),
+                    duplicated_lines: file.duplicated_lines,
+                    total_lines: file.total_lines,
+                    percent: format!("{:.1}", file.duplication_percent),
+                })
+                .collect(),
+            repeated_files: results.repeated_files.clone(),
+            unavailable: results.capabilities.unavailable_entries()
+                .map(|(name, reason)| UnavailableData { name, reason: reason.to_string() })
+                .collect(),
+            trend: results.history.iter()
+                .map(|snapshot| TrendPoint {
+                    commit: snapshot.short_commit(),
+                    average_complexity: format!("{:.2}", snapshot.average_complexity),
+                    weighted_average_complexity: snapshot.weighted_average_complexity.map(|average| format!("{:.2}", average)),
+                    issues: snapshot.issues,
+                    maintainability: snapshot.maintainability.map(|index| format!("{:.1}", index)),
+                })
+                .collect(),
+            rule_docs: reported_rules(results),
+            methodology: methodology(results),
+            thresholds: results.thresholds.clone(),
+            sections: Vec::new(),
+        }
+    }
+}
+
+/// The rules with at least one finding in `results`, in catalog order
+fn reported_rules(results: &AnalysisResults) -> Vec<&'static rules::Rule> {
+    let reported = [
+        (rules::HIGH_CYCLOMATIC_COMPLEXITY, !results.high_complexity_functions.is_empty()),
+        (rules::HIGH_FILE_COMPLEXITY, !results.high_complexity_files.is_empty()),
+        (rules::DUPLICATED_CODE, !results.clones.is_empty()),
+    ];
+    rules::RULES.iter()
+        .filter(|rule| {
+            reported.iter().any(|&(id, found)| found && id == rule.id)
+                || results.issues.iter().any(|found| found.issue.rule_id == rule.id)
+        })
+        .collect()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codemetrics_core::analyzers::{AnalysisConfig, CodeAnalyzer};
+    use codemetrics_core::parsers::LanguageParser;
+
+    #[test]
+    fn test_render_sarif_locates_hotspots() {
+        let mut results = CodeAnalyzer::new().aggregate_results(Vec::new()).unwrap();
+        results.high_complexity_functions.push(HighComplexityFunction {
+            name: "dispatch".to_string(),
+            scope: String::new(),
+            file_path: "src\\router.rs".to_string(),
+            complexity: 40,
+            line_start: 12,
+            line_end: 90,
+            parameters: 2,
+            stability: None,
+            snippet: None,
+        });
+
+        let sarif: Value = serde_json::from_str(&Reporter::new("sarif").render(&results).unwrap()).unwrap();
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], rules::HIGH_CYCLOMATIC_COMPLEXITY);
+        assert_eq!(result["level"], "error");
+        assert_eq!(result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "src/router.rs");
+        assert_eq!(result["locations"][0]["physicalLocation"]["region"]["startLine"], 12);
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap().len(), rules::RULES.len());
+    }
+
+    #[test]
+    fn test_findings_show_where_their_input_came_from() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("routes.js"), "\
+app.get('/user', (req, res) => {
+  const id = req.query.id;
+  db.query(`SELECT * FROM users WHERE id = ${id}`);
+});
+").unwrap();
+        let config = AnalysisConfig::builder().quiet(true).build().unwrap();
+        let results = CodeAnalyzer::with_config(config).analyze_path(dir.path()).unwrap();
+
+        let sarif: Value = serde_json::from_str(&Reporter::new("sarif").render(&results).unwrap()).unwrap();
+        let result = sarif["runs"][0]["results"].as_array().unwrap().iter()
+            .find(|result| result["ruleId"] == rules::TAINTED_INPUT)
+            .unwrap();
+        assert_eq!(result["locations"][0]["physicalLocation"]["region"]["startLine"], 3);
+        assert_eq!(result["relatedLocations"][0]["physicalLocation"]["region"]["startLine"], 2);
+        let text = Reporter::new("text").render(&results).unwrap();
+        assert!(text.contains("Findings (2)") && text.contains("input at 2:14"), "{}", text);
+    }
+
+    #[test]
+    fn test_write_report_to_memory_sink() {
+        let results = CodeAnalyzer::new().aggregate_results(Vec::new()).unwrap();
+        let mut sink = sink::MemorySink::default();
+        Reporter::new("markdown").write_report(&results, &mut sink).unwrap();
+
+        let markdown = String::from_utf8(sink.get("report.md").unwrap().to_vec()).unwrap();
+        assert!(markdown.starts_with("# Code Analysis Report"));
+    }
+
+    #[test]
+    fn test_html_colors_the_complexity_distribution() {
+        let parsed = LanguageParser::new().parse_file("lib.rs", "fn id(x: u8) -> u8 { x }\n");
+        let results = CodeAnalyzer::new().aggregate_results(vec![parsed]).unwrap();
+
+        let html = Reporter::new("html").render(&results).unwrap();
+        assert!(html.contains("'#2ecc71'"));
+    }
+}
@@ -0,0 +1,91 @@
+//! Newline-delimited JSON, written while the analysis runs
+//!
+//! One `{"type": "file", ...}` record per analyzed file as soon as it is
+//! parsed, in completion order, then one `{"type": "summary", ...}` record
+//! holding the aggregate results in the shape of `--format json`. Consumers
+//! can start on the first files right away, and neither side holds the whole
+//! project in memory.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+
+use codemetrics_core::analyzers::{AnalysisResults, FileRecord};
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Record<'a> {
+    File(&'a FileRecord),
+    Summary(&'a AnalysisResults),
+}
+
+/// Writes file records to `out` one line at a time
+pub struct NdjsonWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    /// Write and flush one file record, so readers see it before the next file is done
+    pub fn file(&mut self, record: &FileRecord) -> Result<()> {
+        serde_json::to_writer(&mut self.out, &Record::File(record))?;
+        self.out.write_all(b"\n")?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// The closing summary record, without a trailing newline
+pub fn summary(results: &AnalysisResults) -> Result<String> {
+    Ok(serde_json::to_string(&Record::Summary(results))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codemetrics_core::analyzers::{AnalysisConfig, CodeAnalyzer, FunctionRecord};
+    use codemetrics_core::thresholds::ThresholdLevel;
+
+    #[test]
+    fn test_one_tagged_record_per_line() -> Result<()> {
+        let record = FileRecord {
+            file_path: "src/lib.rs".to_string(),
+            language: "Rust".to_string(),
+            lines: 12,
+            complexity: 3,
+            functions: vec![FunctionRecord {
+                file_path: "src/lib.rs".to_string(),
+                language: "Rust".to_string(),
+                name: "parse".to_string(),
+                scope: String::new(),
+                line_start: 1,
+                line_end: 12,
+                lines: 12,
+                parameters: 1,
+                complexity: 3,
+                level: ThresholdLevel::Ok,
+                baselined: false,
+                cognitive_complexity: None,
+                nesting_depth: None,
+            }],
+        };
+        let mut out = Vec::new();
+        let mut writer = NdjsonWriter::new(&mut out);
+        writer.file(&record)?;
+        writer.file(&record)?;
+        let empty = tempfile::tempdir()?;
+        let config = AnalysisConfig::builder().quiet(true).build()?;
+        out.extend(summary(&CodeAnalyzer::with_config(config).analyze_path(empty.path())?)?.bytes());
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)?.lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        assert_eq!(lines.len(), 3);
+        assert_eq!((lines[0]["type"].as_str(), lines[0]["functions"][0]["name"].as_str()), (Some("file"), Some("parse")));
+        assert_eq!((lines[2]["type"].as_str(), lines[2]["files_analyzed"].as_u64()), (Some("summary"), Some(0)));
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+//! One row per function as a Parquet file
+//!
+//! Defect-prediction and other ML pipelines load this straight into a
+//! dataframe instead of flattening the nested JSON report. Only built with the
+//! `parquet` feature, which pulls in `arrow2`.
+
+use anyhow::{Context, Result};
+use arrow2::array::{Array, UInt32Array, Utf8Array};
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::{DataType, Field, Schema};
+use arrow2::io::parquet::write::{
+    transverse, CompressionOptions, Encoding, FileWriter, RowGroupIterator, Version, WriteOptions,
+};
+
+use codemetrics_core::analyzers::FunctionRecord;
+
+/// Write `functions` as a single-row-group Parquet file
+pub fn write_functions(functions: &[FunctionRecord]) -> Result<Vec<u8>> {
+    let schema = Schema::from(vec![
+        Field::new("file_path", DataType::Utf8, false),
+        Field::new("language", DataType::Utf8, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("line_start", DataType::UInt32, false),
+        Field::new("line_end", DataType::UInt32, false),
+        Field::new("lines", DataType::UInt32, false),
+        Field::new("parameters", DataType::UInt32, false),
+        Field::new("complexity", DataType::UInt32, false),
+        Field::new("level", DataType::Utf8, false),
+        // Empty for languages without a built-in grammar
+        Field::new("cognitive_complexity", DataType::UInt32, true),
+        Field::new("nesting_depth", DataType::UInt32, true),
+    ]);
+
+    let text = |field: fn(&FunctionRecord) -> &str| {
+        Utf8Array::<i32>::from_iter_values(functions.iter().map(field)).boxed()
+    };
+    let number = |field: fn(&FunctionRecord) -> u32| {
+        UInt32Array::from_iter_values(functions.iter().map(field)).boxed()
+    };
+    let optional = |field: fn(&FunctionRecord) -> Option<u32>| {
+        UInt32Array::from_iter(functions.iter().map(field)).boxed()
+    };
+    let levels: Vec<String> = functions.iter().map(|function| format!("{:?}", function.level)).collect();
+    let columns: Vec<Box<dyn Array>> = vec![
+        text(|function| function.file_path.as_str()),
+        text(|function| function.language.as_str()),
+        text(|function| function.name.as_str()),
+        number(|function| function.line_start),
+        number(|function| function.line_end),
+        number(|function| function.lines),
+        number(|function| function.parameters),
+        number(|function| function.complexity),
+        Utf8Array::<i32>::from_iter_values(levels.iter()).boxed(),
+        optional(|function| function.cognitive_complexity),
+        optional(|function| function.nesting_depth),
+    ];
+    let chunk = Chunk::try_new(columns).context("Failed to assemble function columns")?;
+
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: CompressionOptions::Snappy,
+        version: Version::V2,
+        data_pagesize_limit: None,
+    };
+    let encodings: Vec<Vec<Encoding>> = schema.fields.iter()
+        .map(|field| transverse(&field.data_type, |_| Encoding::Plain))
+        .collect();
+    let row_groups = RowGroupIterator::try_new(std::iter::once(Ok(chunk)), &schema, options, encodings)
+        .context("Failed to prepare Parquet row group")?;
+
+    let mut buffer = Vec::new();
+    let mut writer = FileWriter::try_new(&mut buffer, schema, options)
+        .context("Failed to start Parquet file")?;
+    for group in row_groups {
+        writer.write(group.context("Failed to encode Parquet row group")?)
+            .context("Failed to write Parquet row group")?;
+    }
+    writer.end(None).context("Failed to finish Parquet file")?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow2::io::parquet::read;
+    use codemetrics_core::thresholds::ThresholdLevel;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_functions_round_trip_through_parquet() {
+        let functions = vec![FunctionRecord {
+            file_path: "src/cart.rs".to_string(),
+            language: "Rust".to_string(),
+            name: "total".to_string(),
+            scope: String::new(),
+            line_start: 3,
+            line_end: 9,
+            lines: 7,
+            parameters: 2,
+            complexity: 12,
+            level: ThresholdLevel::Warn,
+            baselined: false,
+            cognitive_complexity: Some(15),
+            nesting_depth: None,
+        }];
+        let bytes = write_functions(&functions).unwrap();
+        assert!(bytes.starts_with(b"PAR1") && bytes.ends_with(b"PAR1"));
+
+        let metadata = read::read_metadata(&mut Cursor::new(&bytes)).unwrap();
+        let schema = read::infer_schema(&metadata).unwrap();
+        assert_eq!(metadata.num_rows, 1);
+        assert_eq!(schema.fields.len(), 11);
+        assert_eq!(schema.fields[7].name, "complexity");
+        assert_eq!(schema.fields[9].name, "cognitive_complexity");
+        assert!(schema.fields[10].is_nullable);
+    }
+}
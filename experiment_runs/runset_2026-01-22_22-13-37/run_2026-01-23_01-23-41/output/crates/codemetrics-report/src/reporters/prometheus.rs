@@ -0,0 +1,104 @@
+//! Prometheus text exposition format, for scraping and alerting
+//!
+//! Every metric is a gauge describing the latest analysis, so a node exporter
+//! textfile collector can pick up `--format prometheus` output from CI, and
+//! `codemetrics serve` answers `GET /metrics` with the same text. Issues are
+//! the findings the annotation formats report, counted per severity; every
+//! severity is always present so alerts see zero rather than a missing series.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use codemetrics_core::analyzers::{AnalysisResults, LanguageStats};
+use codemetrics_core::thresholds::ThresholdLevel;
+
+use super::annotations;
+
+/// Severity label values, matching the GitHub annotation levels
+const SEVERITIES: [(ThresholdLevel, &str); 4] = [
+    (ThresholdLevel::Critical, "critical"),
+    (ThresholdLevel::Error, "error"),
+    (ThresholdLevel::Warn, "warning"),
+    (ThresholdLevel::Ok, "notice"),
+];
+
+/// The exposition text, ending in a newline
+pub fn metrics(results: &AnalysisResults) -> String {
+    let mut out = String::new();
+    gauge(&mut out, "codemetrics_files_analyzed", "Files analyzed", &[(String::new(), results.files_analyzed as f64)]);
+    gauge(&mut out, "codemetrics_lines", "Lines in analyzed files", &[(String::new(), results.total_lines as f64)]);
+    gauge(&mut out, "codemetrics_functions", "Functions found", &[(String::new(), results.total_functions as f64)]);
+    gauge(&mut out, "codemetrics_avg_complexity", "Average cyclomatic complexity per function", &[(String::new(), results.average_complexity)]);
+    gauge(&mut out, "codemetrics_weighted_avg_complexity", "Average cyclomatic complexity weighted by function length", &[(String::new(), results.weighted_average_complexity)]);
+    gauge(&mut out, "codemetrics_high_complexity_functions", "Functions over the complexity threshold", &[(String::new(), results.high_complexity_functions.len() as f64)]);
+    if let Some(health) = &results.health {
+        gauge(&mut out, "codemetrics_health_score", "Overall health score, 0 to 100", &[(String::new(), health.overall)]);
+    }
+    if let Some(duplication) = results.duplication_percent {
+        gauge(&mut out, "codemetrics_duplication_percent", "Share of analyzed lines inside a repeated block", &[(String::new(), duplication)]);
+    }
+
+    let issues = annotations::collect(results);
+    let by_severity: Vec<_> = SEVERITIES.iter()
+        .map(|(level, name)| (label("severity", name), issues.iter().filter(|issue| issue.level == *level).count() as f64))
+        .collect();
+    gauge(&mut out, "codemetrics_issues_total", "Reported issues by severity", &by_severity);
+    gauge(&mut out, "codemetrics_suppressed_issues", "Issues hidden by suppressions or the baseline", &[(String::new(), (results.suppressed + results.baseline_suppressed) as f64)]);
+    gauge(&mut out, "codemetrics_analysis_errors", "Files that could not be analyzed", &[(String::new(), results.errors.len() as f64)]);
+
+    let languages: BTreeMap<_, _> = results.language_breakdown.iter().collect();
+    let per_language = |value: fn(&LanguageStats) -> f64| -> Vec<(String, f64)> {
+        languages.iter().map(|(language, stats)| (label("language", language), value(stats))).collect()
+    };
+    gauge(&mut out, "codemetrics_language_files", "Files analyzed per language", &per_language(|stats| stats.files as f64));
+    gauge(&mut out, "codemetrics_language_lines", "Lines per language", &per_language(|stats| stats.lines as f64));
+    gauge(&mut out, "codemetrics_language_functions", "Functions per language", &per_language(|stats| stats.functions as f64));
+    gauge(
+        &mut out,
+        "codemetrics_language_high_complexity_functions",
+        "Functions over the complexity threshold per language",
+        &per_language(|stats| stats.high_complexity_functions as f64),
+    );
+    out
+}
+
+/// `# HELP`, `# TYPE` and one sample per label set; skipped when there are no samples
+fn gauge(out: &mut String, name: &str, help: &str, samples: &[(String, f64)]) {
+    if samples.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    for (labels, value) in samples {
+        let _ = writeln!(out, "{}{} {}", name, labels, value);
+    }
+}
+
+/// `{name="value"}`, with the value escaped as the exposition format requires
+fn label(name: &str, value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+    format!("{{{}=\"{}\"}}", name, escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use codemetrics_core::analyzers::{AnalysisConfig, CodeAnalyzer};
+
+    #[test]
+    fn test_gauges_per_severity_and_language() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("lib.rs"), "fn add(a: u32, b: u32) -> u32 {\n    a + b\n}\n")?;
+        let config = AnalysisConfig::builder().quiet(true).build()?;
+        let text = metrics(&CodeAnalyzer::with_config(config).analyze_path(dir.path())?);
+
+        assert!(text.contains("# TYPE codemetrics_avg_complexity gauge\ncodemetrics_avg_complexity 1\n"));
+        assert!(text.contains("codemetrics_files_analyzed 1\n"));
+        assert!(text.contains("codemetrics_issues_total{severity=\"critical\"} 0\n"));
+        assert!(text.contains("codemetrics_issues_total{severity=\"notice\"} 0\n"));
+        assert!(text.contains("codemetrics_language_files{language=\"Rust\"} 1\n"));
+        assert_eq!(label("path", "a\"b\\c"), r#"{path="a\"b\\c"}"#);
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+//! Extra sections for the HTML report
+//!
+//! Teams that want their own content in the report (service catalog links,
+//! SLO dashboards, ownership) implement `ReportSection` and add it with
+//! `Reporter::add_section` instead of forking `report.html`. Each section
+//! supplies JSON data and a Handlebars partial; the partial is rendered with
+//! that data as its context and placed after the built-in sections.
+
+use anyhow::{Context, Result};
+use handlebars::Handlebars;
+use serde::Serialize;
+use serde_json::Value;
+
+use codemetrics_core::analyzers::AnalysisResults;
+
+pub trait ReportSection: Send + Sync {
+    /// Identifies the section: its template name and the anchor in the report,
+    /// e.g. `service-catalog`. Lowercase letters, digits, `-` and `_`
+    fn name(&self) -> &str;
+
+    /// Heading shown above the section
+    fn title(&self) -> &str;
+
+    /// Handlebars source rendered with `data` as its context
+    fn partial(&self) -> &str;
+
+    /// What the partial renders, or `None` to leave the section out of this report
+    fn data(&self, results: &AnalysisResults) -> Result<Option<Value>>;
+}
+
+/// A section rendered for one report, as the HTML template places it
+#[derive(Debug, Serialize)]
+pub(crate) struct RenderedSection {
+    name: String,
+    title: String,
+    html: String,
+}
+
+/// Sections added to a reporter, each with its partial registered in `handlebars`
+#[derive(Default)]
+pub(crate) struct Sections {
+    sections: Vec<Box<dyn ReportSection>>,
+}
+
+impl Sections {
+    pub(crate) fn add(&mut self, handlebars: &mut Handlebars<'static>, section: Box<dyn ReportSection>) -> Result<()> {
+        let name = section.name();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_') {
+            anyhow::bail!("Invalid report section name '{}': use lowercase letters, digits, '-' and '_'", name);
+        }
+        if self.sections.iter().any(|existing| existing.name() == name) {
+            anyhow::bail!("Report section '{}' is already registered", name);
+        }
+        handlebars.register_template_string(&template_name(name), section.partial())
+            .with_context(|| format!("Invalid template for report section '{}'", name))?;
+        self.sections.push(section);
+        Ok(())
+    }
+
+    /// Render every section with data for `results`, in the order they were added
+    pub(crate) fn render(&self, handlebars: &Handlebars<'static>, results: &AnalysisResults) -> Result<Vec<RenderedSection>> {
+        let mut rendered = Vec::new();
+        for section in &self.sections {
+            let name = section.name();
+            let Some(data) = section.data(results).with_context(|| format!("Report section '{}' failed", name))? else {
+                continue;
+            };
+            let html = handlebars.render(&template_name(name), &data)
+                .with_context(|| format!("Failed to render report section '{}'", name))?;
+            rendered.push(RenderedSection { name: name.to_string(), title: section.title().to_string(), html });
+        }
+        Ok(rendered)
+    }
+}
+
+/// Kept apart from the built-in `html_report` template
+fn template_name(section: &str) -> String {
+    format!("section:{}", section)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reporter;
+    use codemetrics_core::analyzers::CodeAnalyzer;
+    use serde_json::json;
+
+    struct ServiceCatalog;
+
+    impl ReportSection for ServiceCatalog {
+        fn name(&self) -> &str {
+            "service-catalog"
+        }
+
+        fn title(&self) -> &str {
+            "Service Catalog"
+        }
+
+        fn partial(&self) -> &str {
+            r#"<a href="{{url}}">{{service}}</a> ({{files}} files)"#
+        }
+
+        fn data(&self, results: &AnalysisResults) -> Result<Option<Value>> {
+            Ok(Some(json!({
+                "service": "billing <api>",
+                "url": "https://catalog.example.com/billing",
+                "files": results.files_analyzed,
+            })))
+        }
+    }
+
+    #[test]
+    fn test_sections_render_into_the_html_report() {
+        let results = CodeAnalyzer::new().aggregate_results(Vec::new()).unwrap();
+        let mut reporter = Reporter::new("html");
+        reporter.add_section(ServiceCatalog).unwrap();
+
+        let html = reporter.render(&results).unwrap();
+        assert!(html.contains(r#"id="section-service-catalog""#));
+        assert!(html.contains("<h2>Service Catalog</h2>"));
+        // Data is escaped by the partial, its markup is not escaped again
+        assert!(html.contains(r#"<a href="https://catalog.example.com/billing">billing &lt;api&gt;</a> (0 files)"#));
+        assert!(reporter.add_section(ServiceCatalog).is_err());
+    }
+}
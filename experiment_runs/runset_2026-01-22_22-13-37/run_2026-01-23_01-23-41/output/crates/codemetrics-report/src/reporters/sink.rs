@@ -0,0 +1,161 @@
+//! Destinations for rendered reports
+//!
+//! Every report writer hands its output to an `OutputSink` instead of touching
+//! stdout or the filesystem itself. CI wrappers can collect artifacts in memory
+//! or push them straight to an HTTP endpoint or S3 bucket (behind the
+//! `http-sink` and `s3-sink` features) without going through temp files.
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::PathBuf;
+
+pub trait OutputSink {
+    /// Deliver one artifact; `name` is a file name such as `report.html`
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<()>;
+}
+
+pub struct StdoutSink;
+
+impl OutputSink for StdoutSink {
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<()> {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(content)?;
+        // Binary artifacts are piped to a file and must arrive byte for byte
+        if !content.ends_with(b"\n") && !name.ends_with(".parquet") && !name.ends_with(".xlsx") {
+            stdout.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes to a fixed path, ignoring the artifact name
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl OutputSink for FileSink {
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<()> {
+        std::fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write {} to {}", name, self.path.display()))
+    }
+}
+
+/// Writes each artifact under its own name into a directory, creating it if needed
+pub struct DirectorySink {
+    dir: PathBuf,
+}
+
+impl DirectorySink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl OutputSink for DirectorySink {
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.dir.join(name);
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {} to {}", name, path.display()))
+    }
+}
+
+/// Keeps every artifact, for embedders that serve or post-process reports
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    pub artifacts: Vec<(String, Vec<u8>)>,
+}
+
+impl MemorySink {
+    /// The most recent artifact written under `name`
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        self.artifacts.iter().rev()
+            .find(|(artifact, _)| artifact == name)
+            .map(|(_, content)| content.as_slice())
+    }
+}
+
+impl OutputSink for MemorySink {
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<()> {
+        self.artifacts.push((name.to_string(), content.to_vec()));
+        Ok(())
+    }
+}
+
+/// PUTs each artifact to `<base_url>/<name>`
+#[cfg(feature = "http-sink")]
+pub struct HttpSink {
+    base_url: String,
+    token: Option<String>,
+}
+
+#[cfg(feature = "http-sink")]
+impl HttpSink {
+    pub fn new(base_url: impl Into<String>, token: Option<String>) -> Self {
+        Self { base_url: base_url.into(), token }
+    }
+}
+
+#[cfg(feature = "http-sink")]
+impl OutputSink for HttpSink {
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<()> {
+        let url = format!("{}/{}", self.base_url.trim_end_matches('/'), name);
+        let mut request = ureq::put(&url).set("Content-Type", content_type(name));
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+        request.send_bytes(content)
+            .with_context(|| format!("Failed to upload {} to {}", name, url))?;
+        Ok(())
+    }
+}
+
+/// Uploads each artifact to `s3://<bucket>/<prefix><name>`, with credentials from the environment
+#[cfg(feature = "s3-sink")]
+pub struct S3Sink {
+    bucket: Box<s3::Bucket>,
+    prefix: String,
+}
+
+#[cfg(feature = "s3-sink")]
+impl S3Sink {
+    pub fn new(bucket: &str, region: &str, prefix: impl Into<String>) -> Result<Self> {
+        let region: s3::Region = region.parse()
+            .with_context(|| format!("Unknown S3 region '{}'", region))?;
+        let credentials = s3::creds::Credentials::default()
+            .context("No AWS credentials found in the environment")?;
+        let bucket = s3::Bucket::new(bucket, region, credentials)
+            .with_context(|| format!("Failed to open S3 bucket '{}'", bucket))?;
+        Ok(Self { bucket, prefix: prefix.into() })
+    }
+}
+
+#[cfg(feature = "s3-sink")]
+impl OutputSink for S3Sink {
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<()> {
+        let key = format!("{}{}", self.prefix, name);
+        let response = self.bucket.put_object_with_content_type(&key, content, content_type(name))
+            .with_context(|| format!("Failed to upload {} to S3", key))?;
+        if response.status_code() >= 300 {
+            anyhow::bail!("S3 rejected {} with status {}", key, response.status_code());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "http-sink", feature = "s3-sink"))]
+fn content_type(name: &str) -> &'static str {
+    match name.rsplit('.').next() {
+        Some("html") => "text/html; charset=utf-8",
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("json") | Some("sarif") => "application/json",
+        _ => "text/plain; charset=utf-8",
+    }
+}
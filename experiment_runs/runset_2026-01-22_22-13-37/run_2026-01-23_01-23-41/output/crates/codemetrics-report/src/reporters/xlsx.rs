@@ -0,0 +1,164 @@
+//! Excel workbook with Files, Functions and Issues sheets
+//!
+//! For reviews held in a spreadsheet: each sheet has a bold, frozen header row
+//! so it can be filtered and sorted as is. Only built with the `xlsx` feature,
+//! which pulls in `rust_xlsxwriter`.
+
+use anyhow::{Context, Result};
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+
+use codemetrics_core::analyzers::AnalysisResults;
+
+use super::annotations;
+
+/// A cell value; numbers stay numeric so spreadsheet formulas work on them
+#[derive(Debug, PartialEq)]
+enum Cell {
+    Text(String),
+    Number(u32),
+    Empty,
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell::Text(text)
+    }
+}
+
+impl From<u32> for Cell {
+    fn from(number: u32) -> Self {
+        Cell::Number(number)
+    }
+}
+
+impl From<Option<u32>> for Cell {
+    fn from(number: Option<u32>) -> Self {
+        number.map_or(Cell::Empty, Cell::Number)
+    }
+}
+
+/// The workbook for `results`; Functions needs `results.functions`
+pub fn write_workbook(results: &AnalysisResults) -> Result<Vec<u8>> {
+    let mut workbook = Workbook::new();
+    write_sheet(
+        workbook.add_worksheet(),
+        "Files",
+        &["file", "language", "loc", "functions", "function_loc", "total_complexity", "max_complexity", "level"],
+        file_rows(results),
+    )?;
+    write_sheet(
+        workbook.add_worksheet(),
+        "Functions",
+        &["file", "language", "name", "line_start", "line_end", "complexity", "cognitive_complexity", "loc", "parameters", "nesting_depth", "level"],
+        function_rows(results),
+    )?;
+    write_sheet(
+        workbook.add_worksheet(),
+        "Issues",
+        &["rule", "level", "file", "line_start", "line_end", "message"],
+        issue_rows(results),
+    )?;
+    workbook.save_to_buffer().context("Failed to write the workbook")
+}
+
+/// Every analyzed file, those without functions too
+fn file_rows(results: &AnalysisResults) -> Vec<Vec<Cell>> {
+    results.file_totals.iter().map(|(path, file)| vec![
+        path.clone().into(),
+        file.language.clone().into(),
+        file.lines.into(),
+        (file.functions as u32).into(),
+        file.function_lines.into(),
+        file.total_complexity.into(),
+        file.max_complexity.into(),
+        format!("{:?}", results.thresholds.file_level(file.total_complexity)).into(),
+    ]).collect()
+}
+
+fn function_rows(results: &AnalysisResults) -> Vec<Vec<Cell>> {
+    results.functions.iter().map(|function| vec![
+        function.file_path.clone().into(),
+        function.language.clone().into(),
+        function.name.clone().into(),
+        function.line_start.into(),
+        function.line_end.into(),
+        function.complexity.into(),
+        function.cognitive_complexity.into(),
+        function.lines.into(),
+        function.parameters.into(),
+        function.nesting_depth.into(),
+        format!("{:?}", function.level).into(),
+    ]).collect()
+}
+
+fn issue_rows(results: &AnalysisResults) -> Vec<Vec<Cell>> {
+    annotations::collect(results).into_iter().map(|issue| vec![
+        issue.rule_id.to_string().into(),
+        format!("{:?}", issue.level).into(),
+        issue.file_path.into(),
+        issue.lines.map(|(start, _)| start).into(),
+        issue.lines.map(|(_, end)| end).into(),
+        issue.message.into(),
+    ]).collect()
+}
+
+fn write_sheet(sheet: &mut Worksheet, name: &str, header: &[&str], rows: Vec<Vec<Cell>>) -> Result<()> {
+    let bold = Format::new().set_bold();
+    sheet.set_name(name)?;
+    for (column, title) in header.iter().enumerate() {
+        sheet.write_string_with_format(0, column as u16, *title, &bold)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+
+    for (index, row) in rows.into_iter().enumerate() {
+        let row_number = index as u32 + 1;
+        for (column, cell) in row.into_iter().enumerate() {
+            match cell {
+                Cell::Text(text) => sheet.write_string(row_number, column as u16, text)?,
+                Cell::Number(number) => sheet.write_number(row_number, column as u16, number)?,
+                Cell::Empty => continue,
+            };
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codemetrics_core::analyzers::{AnalysisConfig, CodeAnalyzer};
+    use codemetrics_core::parsers::LanguageParser;
+
+    #[test]
+    fn test_every_file_gets_a_row_with_its_totals() {
+        let parser = LanguageParser::new();
+        let parsed = vec![
+            parser.parse_file("src/cart.rs", "fn total(x: i32) -> i32 {\n    if x > 0 { x } else { 0 }\n}\n").unwrap(),
+            parser.parse_file("src/lib.rs", "pub mod cart;\n").unwrap(),
+        ];
+        let analyzer = CodeAnalyzer::with_config(AnalysisConfig::builder().function_records(true).build().unwrap());
+        let results = analyzer.aggregate_results(parsed.into_iter().map(Ok).collect()).unwrap();
+
+        let files = file_rows(&results);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0][..7], [
+            Cell::Text("src/cart.rs".to_string()),
+            Cell::Text("Rust".to_string()),
+            Cell::Number(3),
+            Cell::Number(1),
+            Cell::Number(3),
+            Cell::Number(3),
+            Cell::Number(3),
+        ]);
+        // A file without functions still has its own row
+        assert_eq!(files[1][0], Cell::Text("src/lib.rs".to_string()));
+        assert_eq!(files[1][3], Cell::Number(0));
+
+        let functions = function_rows(&results);
+        assert_eq!(functions[0][2], Cell::Text("total".to_string()));
+        assert_eq!(functions[0][6], Cell::Empty);
+
+        // xlsx is a zip container
+        assert!(write_workbook(&results).unwrap().starts_with(b"PK"));
+    }
+}
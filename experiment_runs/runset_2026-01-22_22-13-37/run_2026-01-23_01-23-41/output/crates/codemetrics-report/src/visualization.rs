@@ -3,11 +3,15 @@ use handlebars::Handlebars;
 use serde_json::json;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::core::types::{ProjectAnalysis, FileAnalysis, DependencyGraph, CodeIssue, IssueSeverity};
-use crate::dependency_analyzer::{DependencyAnalysisResult, CircularDependency, ModuleCoupling};
-use crate::ast_analyzer::FunctionAnalysis;
+use codemetrics_core::core::types::{ProjectAnalysis, FileAnalysis, IssueSeverity};
+use codemetrics_core::dependency_analyzer::{DependencyAnalysisResult, GraphView};
+use codemetrics_core::ast_analyzer::FunctionAnalysis;
+use codemetrics_core::dead_code::is_test_path;
+
+/// Port the dashboard connects to when opened as a file, and `serve`'s default
+pub const DEFAULT_DASHBOARD_PORT: u16 = 8080;
 
 /// Advanced visualization and reporting system
 pub struct VisualizationEngine {
@@ -15,24 +19,35 @@ pub struct VisualizationEngine {
 }
 
 /// Configuration for visualization output
+#[derive(serde::Serialize)]
 pub struct VisualizationConfig {
     pub include_dependency_graph: bool,
+    /// Initial granularity of the dependency graph; the report can switch views
+    pub dependency_view: GraphView,
     pub include_complexity_heatmap: bool,
+    /// Directory → file → function treemap, sized by lines of code and colored by complexity
+    pub include_treemap: bool,
     pub include_issue_dashboard: bool,
     pub include_function_metrics: bool,
     pub interactive_features: bool,
     pub dark_theme: bool,
+    /// Inline the stylesheet and the report's own script into the HTML instead of
+    /// writing `assets/`, for pages served on their own; Chart.js and D3 still load from CDNs
+    pub inline_assets: bool,
 }
 
 impl Default for VisualizationConfig {
     fn default() -> Self {
         Self {
             include_dependency_graph: true,
+            dependency_view: GraphView::Module,
             include_complexity_heatmap: true,
+            include_treemap: true,
             include_issue_dashboard: true,
             include_function_metrics: true,
             interactive_features: true,
             dark_theme: false,
+            inline_assets: false,
         }
     }
 }
@@ -75,8 +90,10 @@ impl VisualizationEngine {
         fs::write(output_path, html_content)
             .with_context(|| format!("Failed to write HTML report to {}", output_path.display()))?;
 
-        // Copy static assets
-        self.copy_static_assets(output_path.parent().unwrap_or(Path::new(".")))?;
+        // Inlined assets are already in the page
+        if !config.inline_assets {
+            self.copy_static_assets(output_path.parent().unwrap_or(Path::new(".")))?;
+        }
 
         println!("✨ Interactive HTML report generated: {}", output_path.display());
         Ok(())
@@ -86,13 +103,13 @@ impl VisualizationEngine {
     pub fn generate_dashboard(
         &self,
         project_analysis: &ProjectAnalysis,
-        dependency_analysis: Option<&DependencyAnalysisResult>,
+        _dependency_analysis: Option<&DependencyAnalysisResult>,
         config: &VisualizationConfig,
         output_path: &Path,
     ) -> Result<()> {
         let template_data = json!({
             "project": {
-                "name": project_analysis.files.get(0)
+                "name": project_analysis.files.first()
                     .map(|f| f.file_path.parent()
                         .and_then(|p| p.file_name())
                         .and_then(|n| n.to_str())
@@ -105,9 +122,11 @@ impl VisualizationEngine {
             "real_time": true,
             "config": {
                 "dark_theme": config.dark_theme,
+                "inline_assets": config.inline_assets,
                 "refresh_interval": 5000, // 5 seconds
             },
-            "websocket_port": 8080,
+            "websocket_port": DEFAULT_DASHBOARD_PORT,
+            "assets": inline_assets(config),
         });
 
         let dashboard_html = self.handlebars
@@ -121,6 +140,29 @@ impl VisualizationEngine {
         Ok(())
     }
 
+    /// The live dashboard page for `codemetrics serve`, which fills it from
+    /// WebSocket updates on `websocket_port`
+    pub fn render_live_dashboard(&self, project_name: &str, config: &VisualizationConfig, websocket_port: u16) -> Result<String> {
+        let template_data = json!({
+            "project": {
+                "name": project_name,
+                "analysis_time": chrono::Local::now().format("%H:%M:%S").to_string(),
+            },
+            "real_time": true,
+            "config": {
+                "dark_theme": config.dark_theme,
+                "inline_assets": config.inline_assets,
+                "refresh_interval": 5000, // 5 seconds
+            },
+            "websocket_port": websocket_port,
+            "assets": inline_assets(config),
+        });
+
+        self.handlebars
+            .render("dashboard", &template_data)
+            .context("Failed to render dashboard")
+    }
+
     /// Prepare comprehensive template data for visualization
     fn prepare_template_data(
         &self,
@@ -136,6 +178,7 @@ impl VisualizationEngine {
                 "total_analysis_time": project_analysis.total_analysis_time_ms,
             },
             "config": config,
+            "assets": inline_assets(config),
         });
 
         // Add file analysis data
@@ -149,6 +192,11 @@ impl VisualizationEngine {
             data["complexity_heatmap"] = self.generate_complexity_heatmap_data(&project_analysis.files)?;
         }
 
+        // Add treemap data
+        if config.include_treemap {
+            data["treemap"] = self.generate_treemap_data(&project_analysis.files, functions)?;
+        }
+
         // Add issue dashboard data
         if config.include_issue_dashboard {
             data["issue_dashboard"] = self.generate_issue_dashboard_data(&project_analysis.files)?;
@@ -157,7 +205,7 @@ impl VisualizationEngine {
         // Add dependency graph data
         if config.include_dependency_graph {
             if let Some(dep_analysis) = dependency_analysis {
-                data["dependency_graph"] = self.generate_dependency_graph_data(dep_analysis)?;
+                data["dependency_graph"] = self.generate_dependency_graph_data(dep_analysis, config.dependency_view)?;
             }
         }
 
@@ -217,6 +265,50 @@ impl VisualizationEngine {
         }))
     }
 
+    /// Generate the treemap hierarchy, rooted at the files' common directory
+    fn generate_treemap_data(
+        &self,
+        files: &[FileAnalysis],
+        functions: &HashMap<String, Vec<FunctionAnalysis>>,
+    ) -> Result<serde_json::Value> {
+        let base = common_directory(files.iter().map(|file| file.file_path.as_path()));
+        let mut root = TreemapNode::directory(
+            base.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| ".".to_string()),
+        );
+
+        for file in files {
+            let relative = file.file_path.strip_prefix(&base).unwrap_or(&file.file_path);
+            let mut directory = &mut root;
+            for component in relative.parent().into_iter().flat_map(Path::iter) {
+                directory = directory.subdirectory(&component.to_string_lossy());
+            }
+
+            let file_functions = functions.get(file.file_path.to_string_lossy().as_ref());
+            let children: Vec<TreemapNode> = file_functions.into_iter().flatten()
+                .map(|function| TreemapNode {
+                    name: function.name.clone(),
+                    kind: "function",
+                    line: Some(function.start_line),
+                    loc: function.lines_of_code.max(1),
+                    complexity: function.cyclomatic_complexity,
+                    children: Vec::new(),
+                })
+                .collect();
+            directory.children.push(TreemapNode {
+                name: relative.file_name().unwrap_or(relative.as_os_str()).to_string_lossy().into_owned(),
+                kind: "file",
+                line: None,
+                loc: file.metrics.lines_of_code,
+                complexity: children.iter().map(|function| function.complexity).max()
+                    .unwrap_or(file.metrics.cyclomatic_complexity),
+                children,
+            });
+        }
+
+        root.finish();
+        Ok(serde_json::to_value(root)?)
+    }
+
     /// Generate data for issue dashboard
     fn generate_issue_dashboard_data(&self, files: &[FileAnalysis]) -> Result<serde_json::Value> {
         let mut issue_counts = HashMap::new();
@@ -277,8 +369,8 @@ impl VisualizationEngine {
     }
 
     /// Generate data for dependency graph visualization
-    fn generate_dependency_graph_data(&self, dep_analysis: &DependencyAnalysisResult) -> Result<serde_json::Value> {
-        let nodes: Vec<serde_json::Value> = dep_analysis.graph.nodes.iter()
+    fn generate_dependency_graph_data(&self, dep_analysis: &DependencyAnalysisResult, view: GraphView) -> Result<serde_json::Value> {
+        let mut nodes: Vec<serde_json::Value> = dep_analysis.graph.nodes.iter()
             .map(|node| {
                 let coupling = dep_analysis.module_coupling.iter()
                     .find(|c| c.module_name == node.id);
@@ -294,11 +386,13 @@ impl VisualizationEngine {
                         "instability": c.instability,
                     })),
                     "depth": dep_analysis.dependency_depth.get(&node.id).unwrap_or(&0),
+                    "external": false,
+                    "test": is_test_path(&node.file_path),
                 })
             })
             .collect();
 
-        let edges: Vec<serde_json::Value> = dep_analysis.graph.edges.iter()
+        let mut edges: Vec<serde_json::Value> = dep_analysis.graph.edges.iter()
             .map(|edge| {
                 json!({
                     "from": edge.from,
@@ -309,6 +403,26 @@ impl VisualizationEngine {
             })
             .collect();
 
+        // External packages become leaf nodes so the report can toggle them
+        for package in dep_analysis.external_dependencies.keys() {
+            nodes.push(json!({
+                "id": format!("external:{}", package),
+                "label": package,
+                "external": true,
+                "test": false,
+            }));
+        }
+        for (module, packages) in &dep_analysis.external_imports {
+            for package in packages {
+                edges.push(json!({
+                    "from": module,
+                    "to": format!("external:{}", package),
+                    "type": "External",
+                    "symbols": [],
+                }));
+            }
+        }
+
         Ok(json!({
             "nodes": nodes,
             "edges": edges,
@@ -318,8 +432,26 @@ impl VisualizationEngine {
                     "severity": cd.severity,
                 }))
                 .collect::<Vec<_>>(),
+            "package_cycles": dep_analysis.package_cycles.iter()
+                .map(|cd| json!({
+                    "cycle": cd.cycle,
+                    "severity": cd.severity,
+                }))
+                .collect::<Vec<_>>(),
+            "cycles_truncated": dep_analysis.cycles_truncated,
             "unused_exports": dep_analysis.unused_exports,
             "external_dependencies": dep_analysis.external_dependencies,
+            "cargo": dep_analysis.cargo,
+            // Directory-level view for large repos, selectable in the report
+            "collapsed": dep_analysis.collapsed(match view {
+                GraphView::Module => GraphView::Directory { depth: 2 },
+                directory => directory,
+            }),
+            "layers": dep_analysis.layers(),
+            "initial_view": match view {
+                GraphView::Module => "module",
+                GraphView::Directory { .. } => "directory",
+            },
         }))
     }
 
@@ -342,6 +474,8 @@ impl VisualizationEngine {
                     "is_async": func.is_async,
                     "is_recursive": func.is_recursive,
                     "calls": func.calls,
+                    "used_imports": func.used_imports,
+                    "available_imports": func.available_imports,
                 }));
 
                 // Build complexity distribution
@@ -390,7 +524,7 @@ impl VisualizationEngine {
     }
 
     // Helper methods
-    fn calculate_complexity_score(&self, metrics: &crate::core::types::CodeMetrics) -> f64 {
+    fn calculate_complexity_score(&self, metrics: &codemetrics_core::core::types::CodeMetrics) -> f64 {
         let complexity_factor = (metrics.cyclomatic_complexity as f64 / 10.0).min(1.0);
         let nesting_factor = (metrics.max_nesting_depth as f64 / 5.0).min(1.0);
         let maintainability_factor = 1.0 - (metrics.maintainability_index / 100.0);
@@ -409,15 +543,96 @@ impl VisualizationEngine {
     }
 }
 
+/// One treemap cell; directories and files can be drilled into
+#[derive(serde::Serialize)]
+struct TreemapNode {
+    name: String,
+    kind: &'static str,
+    /// Start line of a function
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+    /// Lines of code, the cell's area; a directory's is the sum of its entries'
+    loc: u32,
+    /// Highest function complexity inside, the cell's color
+    complexity: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<TreemapNode>,
+}
+
+impl TreemapNode {
+    fn directory(name: String) -> Self {
+        Self { name, kind: "directory", line: None, loc: 0, complexity: 0, children: Vec::new() }
+    }
+
+    fn subdirectory(&mut self, name: &str) -> &mut TreemapNode {
+        let index = match self.children.iter().position(|child| child.kind == "directory" && child.name == name) {
+            Some(index) => index,
+            None => {
+                self.children.push(TreemapNode::directory(name.to_string()));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[index]
+    }
+
+    /// Sum directory sizes, merge single-directory chains (`src/app/api`) and put larger entries first
+    fn finish(&mut self) {
+        if self.kind != "directory" {
+            return;
+        }
+        while self.children.len() == 1 && self.children[0].kind == "directory" {
+            let only = self.children.remove(0);
+            self.name = format!("{}/{}", self.name, only.name);
+            self.children = only.children;
+        }
+        for child in &mut self.children {
+            child.finish();
+        }
+        self.loc = self.children.iter().map(|child| child.loc).sum();
+        self.complexity = self.children.iter().map(|child| child.complexity).max().unwrap_or(0);
+        self.children.sort_by(|a, b| b.loc.cmp(&a.loc).then_with(|| a.name.cmp(&b.name)));
+    }
+}
+
+/// Deepest directory containing every path
+fn common_directory<'a>(paths: impl Iterator<Item = &'a Path>) -> PathBuf {
+    let mut common: Option<PathBuf> = None;
+    for path in paths {
+        let parent = path.parent().unwrap_or(Path::new(""));
+        let shared = common.get_or_insert_with(|| parent.to_path_buf());
+        while !parent.starts_with(&*shared) {
+            if !shared.pop() {
+                break;
+            }
+        }
+    }
+    common.unwrap_or_default()
+}
+
+/// Stylesheet and script for templates to inline, when `config.inline_assets`
+fn inline_assets(config: &VisualizationConfig) -> serde_json::Value {
+    if !config.inline_assets {
+        return serde_json::Value::Null;
+    }
+    json!({
+        "styles": CSS_STYLES,
+        "visualizations": VISUALIZATION_JS,
+    })
+}
+
 // Template constants
-const HTML_REPORT_TEMPLATE: &str = r#"
+const HTML_REPORT_TEMPLATE: &str = r##"
 <!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>CodeMetrics Report</title>
+    {{#if config.inline_assets}}
+    <style>{{{assets.styles}}}</style>
+    {{else}}
     <link rel="stylesheet" href="assets/styles.css">
+    {{/if}}
     <script src="https://cdn.jsdelivr.net/npm/chart.js"></script>
     <script src="https://d3js.org/d3.v7.min.js"></script>
 </head>
@@ -433,6 +648,7 @@ const HTML_REPORT_TEMPLATE: &str = r#"
     <nav>
         <ul>
             <li><a href="#overview">Overview</a></li>
+            {{#if config.include_treemap}}<li><a href="#treemap">Treemap</a></li>{{/if}}
             {{#if config.include_complexity_heatmap}}<li><a href="#complexity">Complexity</a></li>{{/if}}
             {{#if config.include_issue_dashboard}}<li><a href="#issues">Issues</a></li>{{/if}}
             {{#if config.include_dependency_graph}}<li><a href="#dependencies">Dependencies</a></li>{{/if}}
@@ -468,6 +684,21 @@ const HTML_REPORT_TEMPLATE: &str = r#"
             </div>
         </section>
 
+        {{#if config.include_treemap}}
+        <section id="treemap">
+            <h2>Code Treemap</h2>
+            <p class="treemap-help">Area is lines of code, color the most complex function inside. Click a directory or file to open it.</p>
+            <div id="treemapBreadcrumb" class="treemap-breadcrumb"></div>
+            <div id="codeTreemap"></div>
+            <div class="treemap-legend">
+                <span><i style="background: #22c55e"></i>1-5</span>
+                <span><i style="background: #eab308"></i>6-10</span>
+                <span><i style="background: #f97316"></i>11-20</span>
+                <span><i style="background: #ef4444"></i>&gt;20</span>
+            </div>
+        </section>
+        {{/if}}
+
         {{#if config.include_complexity_heatmap}}
         <section id="complexity">
             <h2>Complexity Heatmap</h2>
@@ -499,8 +730,29 @@ const HTML_REPORT_TEMPLATE: &str = r#"
         {{#if config.include_dependency_graph}}
         <section id="dependencies">
             <h2>Dependency Graph</h2>
+            <div class="graph-controls">
+                <label for="graphViewSelect">View:</label>
+                <select id="graphViewSelect">
+                    <option value="module">Modules</option>
+                    <option value="directory">Directories (collapsed)</option>
+                </select>
+                <input id="graphSearch" type="search" placeholder="Search modules (Enter to focus)">
+                <label for="graphDepth">Neighborhood depth:</label>
+                <select id="graphDepth">
+                    <option value="1">1</option>
+                    <option value="2" selected>2</option>
+                    <option value="3">3</option>
+                </select>
+                <label><input id="graphHideExternal" type="checkbox" checked> Hide external</label>
+                <label><input id="graphHideTests" type="checkbox"> Hide tests</label>
+                <button id="graphReset" type="button">Show all</button>
+            </div>
             <div id="dependencyGraph"></div>
 
+            <h3>Dependency Layers</h3>
+            <p>Longest import chain: {{dependency_graph.layers.max_chain_length}} modules</p>
+            <div id="dependencyLayers"></div>
+
             {{#if dependency_graph.circular_dependencies}}
             <div class="circular-deps">
                 <h3>Circular Dependencies</h3>
@@ -511,6 +763,21 @@ const HTML_REPORT_TEMPLATE: &str = r#"
                 {{/each}}
             </div>
             {{/if}}
+
+            {{#if dependency_graph.package_cycles}}
+            <div class="circular-deps">
+                <h3>Package Cycles</h3>
+                {{#each dependency_graph.package_cycles}}
+                <div class="cycle-item severity-{{severity}}">
+                    {{#each cycle}}{{this}}{{#unless @last}} → {{/unless}}{{/each}}
+                </div>
+                {{/each}}
+            </div>
+            {{/if}}
+
+            {{#if dependency_graph.cycles_truncated}}
+            <p class="cycle-note">Only the first cycles are listed; more exist.</p>
+            {{/if}}
         </section>
         {{/if}}
 
@@ -537,6 +804,7 @@ const HTML_REPORT_TEMPLATE: &str = r#"
                                 <th>Complexity</th>
                                 <th>Lines</th>
                                 <th>Parameters</th>
+                                <th>Imports Used</th>
                             </tr>
                         </thead>
                         <tbody>
@@ -547,6 +815,7 @@ const HTML_REPORT_TEMPLATE: &str = r#"
                             <td>{{cyclomatic_complexity}}</td>
                             <td>{{lines_of_code}}</td>
                             <td>{{parameter_count}}</td>
+                            <td title="{{#each used_imports}}{{this}}{{#unless @last}}, {{/unless}}{{/each}}">{{len used_imports}} / {{#if (eq available_imports null)}}* (glob import){{else}}{{available_imports}}{{/if}}</td>
                         {{#if @index}}</tr>{{/if}}
                         {{/each}}
                         </tbody>
@@ -557,11 +826,16 @@ const HTML_REPORT_TEMPLATE: &str = r#"
         {{/if}}
     </main>
 
+    {{#if config.inline_assets}}
+    <script>{{{assets.visualizations}}}</script>
+    {{else}}
     <script src="assets/visualizations.js"></script>
+    {{/if}}
     <script>
         // Initialize visualizations with data
         const projectData = {{{json project}}};
         const complexityData = {{{json complexity_heatmap}}};
+        const treemapData = {{{json treemap}}};
         const issueData = {{{json issue_dashboard}}};
         const dependencyData = {{{json dependency_graph}}};
         const functionData = {{{json function_metrics}}};
@@ -569,6 +843,7 @@ const HTML_REPORT_TEMPLATE: &str = r#"
         initializeVisualizations({
             project: projectData,
             complexity: complexityData,
+            treemap: treemapData,
             issues: issueData,
             dependencies: dependencyData,
             functions: functionData
@@ -576,16 +851,20 @@ const HTML_REPORT_TEMPLATE: &str = r#"
     </script>
 </body>
 </html>
-"#;
+"##;
 
-const DASHBOARD_TEMPLATE: &str = r#"
+const DASHBOARD_TEMPLATE: &str = r##"
 <!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>CodeMetrics Dashboard</title>
+    {{#if config.inline_assets}}
+    <style>{{{assets.styles}}}</style>
+    {{else}}
     <link rel="stylesheet" href="assets/styles.css">
+    {{/if}}
     <script src="https://cdn.jsdelivr.net/npm/chart.js"></script>
 </head>
 <body{{#if config.dark_theme}} class="dark-theme"{{/if}}>
@@ -620,8 +899,15 @@ const DASHBOARD_TEMPLATE: &str = r#"
     </div>
 
     <script>
-        // WebSocket connection for real-time updates
-        const ws = new WebSocket('ws://localhost:{{websocket_port}}');
+        // Served by `codemetrics serve` the socket is on the same host; opened
+        // as a file, it is the default port on this machine
+        const socketUrl = location.protocol.startsWith('http')
+            ? (location.protocol === 'https:' ? 'wss://' : 'ws://') + location.host + '/ws'
+            : 'ws://localhost:{{websocket_port}}/ws';
+        const ws = new WebSocket(socketUrl);
+        const HISTORY = 50;
+        let trendChart = null;
+        let performanceChart = null;
 
         ws.onmessage = function(event) {
             const data = JSON.parse(event.data);
@@ -637,8 +923,65 @@ const DASHBOARD_TEMPLATE: &str = r#"
         };
 
         function updateDashboard(data) {
-            document.getElementById('lastUpdate').textContent = new Date().toLocaleTimeString();
-            // Update dashboard components
+            const time = new Date(data.timestamp).toLocaleTimeString();
+            document.getElementById('lastUpdate').textContent = time;
+
+            const overview = document.getElementById('overviewMetrics');
+            overview.innerHTML = '';
+            const health = data.health === null ? 'n/a' : Math.round(data.health);
+            for (const [label, value] of [
+                ['Files', data.files_analyzed],
+                ['Lines of code', data.total_lines],
+                ['Average complexity', data.average_complexity.toFixed(2)],
+                ['Complex functions', data.high_complexity_functions],
+                ['Health', health],
+            ]) {
+                const row = document.createElement('div');
+                row.textContent = label + ': ' + value;
+                overview.appendChild(row);
+            }
+
+            trendChart = trendChart || lineChart('complexityTrend', 'Average complexity', '#667eea');
+            performanceChart = performanceChart || lineChart('performanceChart', 'Analysis time (ms)', '#f97316');
+            appendPoint(trendChart, time, data.average_complexity);
+            appendPoint(performanceChart, time, data.analysis_ms);
+
+            // Functions that just changed first, then the most complex ones
+            const alerts = document.getElementById('issueAlerts');
+            alerts.innerHTML = '';
+            for (const change of data.changed) {
+                const item = document.createElement('div');
+                const before = change.before === null ? 'new' : change.before;
+                const after = change.after === null ? 'removed' : change.after;
+                const worse = change.before !== null && change.after !== null && change.after > change.before;
+                item.className = 'issue-item' + (worse ? ' severity-Warning' : '');
+                item.textContent = `${change.name} (${change.file_path}): ${before} → ${after}`;
+                alerts.appendChild(item);
+            }
+            for (const complex of data.most_complex_functions) {
+                const item = document.createElement('div');
+                item.className = 'issue-item';
+                item.textContent = `${complex.name} (${complex.file_path}:${complex.line_start}) complexity ${complex.complexity}`;
+                alerts.appendChild(item);
+            }
+        }
+
+        function lineChart(id, label, color) {
+            return new Chart(document.getElementById(id), {
+                type: 'line',
+                data: { labels: [], datasets: [{ label: label, data: [], borderColor: color }] },
+                options: { responsive: true, scales: { y: { beginAtZero: true } } },
+            });
+        }
+
+        function appendPoint(chart, label, value) {
+            chart.data.labels.push(label);
+            chart.data.datasets[0].data.push(value);
+            if (chart.data.labels.length > HISTORY) {
+                chart.data.labels.shift();
+                chart.data.datasets[0].data.shift();
+            }
+            chart.update();
         }
 
         // Auto-refresh every {{config.refresh_interval}}ms
@@ -650,9 +993,9 @@ const DASHBOARD_TEMPLATE: &str = r#"
     </script>
 </body>
 </html>
-"#;
+"##;
 
-const CSS_STYLES: &str = r#"
+const CSS_STYLES: &str = r##"
 /* CSS styles for the HTML report */
 * {
     margin: 0;
@@ -789,6 +1132,14 @@ section {
     background-color: #fed7d7;
 }
 
+.graph-controls {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 0.5rem;
+    align-items: center;
+    margin-bottom: 0.75rem;
+}
+
 #dependencyGraph,
 #complexityHeatmap {
     min-height: 400px;
@@ -797,6 +1148,53 @@ section {
     background: white;
 }
 
+#codeTreemap {
+    position: relative;
+    height: 500px;
+    overflow: hidden;
+    border-radius: 0.5rem;
+    background: #f7fafc;
+}
+
+.treemap-cell {
+    position: absolute;
+    box-sizing: border-box;
+    border: 1px solid white;
+    padding: 2px 4px;
+    overflow: hidden;
+    white-space: nowrap;
+    text-overflow: ellipsis;
+    font-size: 0.75rem;
+    color: #1a202c;
+}
+
+.treemap-cell.drillable {
+    cursor: pointer;
+}
+
+.treemap-cell.drillable:hover {
+    filter: brightness(1.1);
+}
+
+.treemap-breadcrumb {
+    margin-bottom: 0.5rem;
+}
+
+.treemap-legend {
+    display: flex;
+    gap: 1rem;
+    margin-top: 0.5rem;
+    font-size: 0.85rem;
+}
+
+.treemap-legend i {
+    display: inline-block;
+    width: 0.8rem;
+    height: 0.8rem;
+    margin-right: 0.3rem;
+    border-radius: 2px;
+}
+
 /* Dashboard specific styles */
 .dashboard-grid {
     display: grid;
@@ -837,9 +1235,9 @@ section {
         font-size: 2rem;
     }
 }
-"#;
+"##;
 
-const VISUALIZATION_JS: &str = r#"
+const VISUALIZATION_JS: &str = r##"
 // JavaScript for interactive visualizations
 function initializeVisualizations(data) {
     if (data.project) {
@@ -858,8 +1256,13 @@ function initializeVisualizations(data) {
         initializeComplexityHeatmap(data.complexity);
     }
 
+    if (data.treemap) {
+        initializeTreemap(data.treemap);
+    }
+
     if (data.dependencies) {
         initializeDependencyGraph(data.dependencies);
+        initializeLayerView(data.dependencies);
     }
 }
 
@@ -967,39 +1370,258 @@ function initializeComplexityHeatmap(complexity) {
     // This would create an interactive complexity heatmap
 }
 
+// Directories, files and functions as nested rectangles; clicking a directory
+// or file redraws the treemap with its entries, the breadcrumb goes back up
+function initializeTreemap(tree) {
+    const container = document.getElementById('codeTreemap');
+    const breadcrumb = document.getElementById('treemapBreadcrumb');
+    if (!container || !tree) return;
+
+    const path = [tree];
+    const render = () => {
+        const current = path[path.length - 1];
+        container.innerHTML = '';
+        const bounds = { x: 0, y: 0, width: container.clientWidth, height: container.clientHeight };
+        for (const { node, rect } of squarify((current.children || []).filter(n => n.loc > 0), bounds)) {
+            const cell = document.createElement('div');
+            cell.className = 'treemap-cell' + (node.children ? ' drillable' : '');
+            cell.style.left = rect.x + 'px';
+            cell.style.top = rect.y + 'px';
+            cell.style.width = rect.width + 'px';
+            cell.style.height = rect.height + 'px';
+            cell.style.background = treemapColor(node.complexity);
+            const name = node.line ? `${node.name} (line ${node.line})` : node.name;
+            cell.title = `${name}\n${node.loc} lines, complexity ${node.complexity}`;
+            if (rect.width > 40 && rect.height > 16) cell.textContent = name;
+            if (node.children) {
+                cell.addEventListener('click', () => {
+                    path.push(node);
+                    render();
+                });
+            }
+            container.appendChild(cell);
+        }
+
+        if (!breadcrumb) return;
+        breadcrumb.innerHTML = '';
+        path.forEach((node, depth) => {
+            if (depth > 0) breadcrumb.appendChild(document.createTextNode(' / '));
+            const last = depth === path.length - 1;
+            const link = document.createElement(last ? 'strong' : 'a');
+            link.textContent = node.name;
+            if (!last) {
+                link.href = '#treemap';
+                link.addEventListener('click', event => {
+                    event.preventDefault();
+                    path.length = depth + 1;
+                    render();
+                });
+            }
+            breadcrumb.appendChild(link);
+        });
+    };
+
+    render();
+    window.addEventListener('resize', render);
+}
+
+// Same buckets as the complexity distribution
+function treemapColor(complexity) {
+    if (complexity <= 5) return '#22c55e';
+    if (complexity <= 10) return '#eab308';
+    if (complexity <= 20) return '#f97316';
+    return '#ef4444';
+}
+
+// Squarified treemap layout (Bruls, Huizing and van Wijk): fills rows along the
+// shorter side of the free space while that keeps the cells closer to square
+function squarify(nodes, bounds) {
+    const total = nodes.reduce((sum, n) => sum + n.loc, 0);
+    if (total === 0 || bounds.width <= 0 || bounds.height <= 0) return [];
+
+    const scale = bounds.width * bounds.height / total;
+    const items = nodes.map(node => ({ node, area: node.loc * scale })).sort((a, b) => b.area - a.area);
+    const placed = [];
+    let free = { ...bounds };
+    let row = [];
+
+    const worst = (cells, side) => {
+        const sum = cells.reduce((acc, c) => acc + c.area, 0);
+        const max = Math.max(...cells.map(c => c.area));
+        const min = Math.min(...cells.map(c => c.area));
+        return Math.max(side * side * max / (sum * sum), sum * sum / (side * side * min));
+    };
+
+    const layoutRow = () => {
+        const sum = row.reduce((acc, c) => acc + c.area, 0);
+        if (free.width >= free.height) {
+            // A column along the left edge
+            const width = sum / free.height;
+            let y = free.y;
+            for (const c of row) {
+                const height = c.area / width;
+                placed.push({ node: c.node, rect: { x: free.x, y, width, height } });
+                y += height;
+            }
+            free = { x: free.x + width, y: free.y, width: free.width - width, height: free.height };
+        } else {
+            // A row along the top edge
+            const height = sum / free.width;
+            let x = free.x;
+            for (const c of row) {
+                const width = c.area / height;
+                placed.push({ node: c.node, rect: { x, y: free.y, width, height } });
+                x += width;
+            }
+            free = { x: free.x, y: free.y + height, width: free.width, height: free.height - height };
+        }
+        row = [];
+    };
+
+    for (const item of items) {
+        const side = Math.min(free.width, free.height);
+        if (row.length > 0 && worst([...row, item], side) > worst(row, side)) layoutRow();
+        row.push(item);
+    }
+    if (row.length > 0) layoutRow();
+    return placed;
+}
+
 function initializeDependencyGraph(dependencies) {
+    if (!dependencies) return;
+
+    // The force layout is unreadable beyond ~100 nodes, so the view is filtered
+    // before rendering: hidden externals/tests, then an optional neighborhood
+    const state = {
+        view: dependencies.initial_view || 'module',
+        search: '',
+        focus: null,
+        depth: 2,
+        hideExternal: true,
+        hideTests: false,
+    };
+
+    const byId = id => document.getElementById(id);
+    const rerender = () => {
+        const graph = state.view === 'directory' && dependencies.collapsed ? dependencies.collapsed : dependencies;
+        renderDependencyGraph(filterDependencyGraph(graph, state), state, node => {
+            state.focus = node.id;
+            rerender();
+        });
+    };
+
+    const bind = (id, eventName, update) => {
+        const element = byId(id);
+        if (element) element.addEventListener(eventName, event => { update(element, event); rerender(); });
+        return element;
+    };
+
+    const viewSelect = bind('graphViewSelect', 'change', el => { state.view = el.value; state.focus = null; });
+    if (viewSelect) viewSelect.value = state.view;
+    bind('graphSearch', 'input', el => { state.search = el.value.trim().toLowerCase(); });
+    bind('graphSearch', 'keydown', (el, event) => {
+        if (event.key !== 'Enter') return;
+        const match = dependencies.nodes.find(n => matchesSearch(n, state.search));
+        if (match) state.focus = match.id;
+    });
+    bind('graphDepth', 'change', el => { state.depth = parseInt(el.value, 10) || 1; });
+    bind('graphHideExternal', 'change', el => { state.hideExternal = el.checked; });
+    bind('graphHideTests', 'change', el => { state.hideTests = el.checked; });
+    bind('graphReset', 'click', () => { state.focus = null; });
+
+    rerender();
+}
+
+function matchesSearch(node, search) {
+    return search.length > 0 && (node.label || node.id).toLowerCase().includes(search);
+}
+
+function filterDependencyGraph(graph, state) {
+    let nodes = graph.nodes.filter(n => !(state.hideExternal && n.external) && !(state.hideTests && n.test));
+    let ids = new Set(nodes.map(n => n.id));
+    let edges = graph.edges.filter(e => ids.has(e.from) && ids.has(e.to));
+
+    if (state.focus && ids.has(state.focus)) {
+        const keep = neighborhood(state.focus, edges, state.depth);
+        nodes = nodes.filter(n => keep.has(n.id));
+        edges = edges.filter(e => keep.has(e.from) && keep.has(e.to));
+    }
+
+    return { nodes, edges };
+}
+
+// Nodes within `depth` hops of `start`, following edges in either direction
+function neighborhood(start, edges, depth) {
+    const adjacent = new Map();
+    for (const e of edges) {
+        if (!adjacent.has(e.from)) adjacent.set(e.from, []);
+        if (!adjacent.has(e.to)) adjacent.set(e.to, []);
+        adjacent.get(e.from).push(e.to);
+        adjacent.get(e.to).push(e.from);
+    }
+
+    const seen = new Set([start]);
+    let frontier = [start];
+    for (let hop = 0; hop < depth && frontier.length > 0; hop++) {
+        const next = [];
+        for (const id of frontier) {
+            for (const neighbor of adjacent.get(id) || []) {
+                if (!seen.has(neighbor)) {
+                    seen.add(neighbor);
+                    next.push(neighbor);
+                }
+            }
+        }
+        frontier = next;
+    }
+    return seen;
+}
+
+function renderDependencyGraph(graph, state, onSelect) {
     const container = document.getElementById('dependencyGraph');
-    if (!container || !dependencies) return;
+    if (!container) return;
+    container.innerHTML = '';
 
     // Create an interactive dependency graph using D3.js
     const width = container.clientWidth;
     const height = 400;
 
+    // Module edges count once; collapsed edges carry a weight
+    const nodes = graph.nodes.map(n => ({ ...n, label: n.label || n.id }));
+    const links = graph.edges.map(e => ({ source: e.from, target: e.to, weight: e.weight || 1 }));
+    const fill = d => {
+        if (d.id === state.focus) return '#e53e3e';
+        if (matchesSearch(d, state.search)) return '#f6ad55';
+        return d.external ? '#a0aec0' : '#667eea';
+    };
+
     const svg = d3.select(container)
         .append('svg')
         .attr('width', width)
         .attr('height', height);
 
-    const simulation = d3.forceSimulation(dependencies.nodes)
-        .force('link', d3.forceLink(dependencies.edges).id(d => d.id))
+    const simulation = d3.forceSimulation(nodes)
+        .force('link', d3.forceLink(links).id(d => d.id))
         .force('charge', d3.forceManyBody().strength(-100))
         .force('center', d3.forceCenter(width / 2, height / 2));
 
     // Add links
     const link = svg.append('g')
         .selectAll('line')
-        .data(dependencies.edges)
+        .data(links)
         .enter().append('line')
         .attr('stroke', '#999')
-        .attr('stroke-opacity', 0.6);
+        .attr('stroke-opacity', 0.6)
+        .attr('stroke-width', d => Math.sqrt(d.weight));
 
-    // Add nodes
+    // Add nodes, sized by the number of merged modules
     const node = svg.append('g')
         .selectAll('circle')
-        .data(dependencies.nodes)
+        .data(nodes)
         .enter().append('circle')
-        .attr('r', 5)
-        .attr('fill', '#667eea')
+        .attr('r', d => 5 + Math.sqrt(d.modules || 1))
+        .attr('fill', fill)
+        .on('click', (event, d) => onSelect(d))
         .call(d3.drag()
             .on('start', dragstarted)
             .on('drag', dragged)
@@ -1008,7 +1630,7 @@ function initializeDependencyGraph(dependencies) {
     // Add labels
     const label = svg.append('g')
         .selectAll('text')
-        .data(dependencies.nodes)
+        .data(nodes)
         .enter().append('text')
         .text(d => d.label)
         .attr('font-size', 10)
@@ -1049,6 +1671,54 @@ function initializeDependencyGraph(dependencies) {
     }
 }
 
+// Layered drawing: leaves at the bottom, modules ordered within each layer
+function initializeLayerView(dependencies) {
+    const container = document.getElementById('dependencyLayers');
+    if (!container || !dependencies.layers) return;
+
+    const layers = dependencies.layers.layers;
+    const width = container.clientWidth;
+    const rowHeight = 60;
+    const height = Math.max(layers.length * rowHeight, rowHeight);
+
+    const positions = new Map();
+    layers.forEach((layer, depth) => {
+        layer.forEach((id, i) => {
+            positions.set(id, {
+                x: (i + 1) * width / (layer.length + 1),
+                y: height - (depth + 0.5) * rowHeight,
+            });
+        });
+    });
+
+    const svg = svgElement(container, 'svg', { width, height });
+
+    const edges = svgElement(svg, 'g');
+    for (const e of dependencies.edges) {
+        if (!positions.has(e.from) || !positions.has(e.to)) continue;
+        const from = positions.get(e.from);
+        const to = positions.get(e.to);
+        svgElement(edges, 'line', { x1: from.x, y1: from.y, x2: to.x, y2: to.y, stroke: '#cbd5e0' });
+    }
+
+    const nodes = svgElement(svg, 'g');
+    for (const [id, p] of positions) {
+        const node = svgElement(nodes, 'g', { transform: `translate(${p.x},${p.y})` });
+        svgElement(node, 'circle', { r: 5, fill: '#667eea' });
+        svgElement(node, 'text', { 'font-size': 10, 'text-anchor': 'middle', dy: -8 }).textContent = id;
+    }
+}
+
+// Appends an SVG element with the given attributes to `parent`
+function svgElement(parent, tag, attributes = {}) {
+    const element = document.createElementNS('http://www.w3.org/2000/svg', tag);
+    for (const [name, value] of Object.entries(attributes)) {
+        element.setAttribute(name, value);
+    }
+    parent.appendChild(element);
+    return element;
+}
+
 // Utility functions for data processing and formatting
 function formatFileSize(bytes) {
     const sizes = ['B', 'KB', 'MB', 'GB'];
@@ -1062,4 +1732,38 @@ function formatDuration(ms) {
     if (ms < 60000) return Math.round(ms / 1000) + 's';
     return Math.round(ms / 60000) + 'm';
 }
-"#;
\ No newline at end of file
+"##;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, loc: u32, complexity: u32) -> TreemapNode {
+        TreemapNode { name: name.to_string(), kind: "file", line: None, loc, complexity, children: Vec::new() }
+    }
+
+    #[test]
+    fn test_treemap_directories_sum_and_collapse() {
+        let base = common_directory([Path::new("/repo/src/app/api/users.rs"), Path::new("/repo/src/app/main.rs")].into_iter());
+        assert_eq!(base, PathBuf::from("/repo/src/app"));
+
+        let mut root = TreemapNode::directory("repo".to_string());
+        let api = root.subdirectory("src").subdirectory("api");
+        api.children.push(file("users.rs", 120, 14));
+        api.children.push(file("orders.rs", 300, 6));
+
+        root.finish();
+        assert_eq!(root.name, "repo/src/api");
+        assert_eq!((root.loc, root.complexity), (420, 14));
+        assert_eq!(root.children[0].name, "orders.rs");
+    }
+
+    #[test]
+    fn test_inlined_dashboard_still_loads_chart_js_from_its_cdn() {
+        let config = VisualizationConfig { inline_assets: true, ..VisualizationConfig::default() };
+        let html = VisualizationEngine::new().unwrap().render_live_dashboard("app", &config, 9000).unwrap();
+
+        assert!(html.contains("CSS styles for the HTML report"));
+        assert!(!html.contains("assets/styles.css"));
+        assert!(html.contains(r#"<script src="https://cdn.jsdelivr.net/npm/chart.js"></script>"#));
+    }
+}
@@ -1,3 +0,0 @@
-pub mod tree_sitter_engine;
-
-pub use tree_sitter_engine::{TreeSitterEngine, SupportedLanguage, FunctionInfo};
\ No newline at end of file
@@ -0,0 +1,208 @@
+//! Release plumbing, run as `cargo xtask <task>`
+//!
+//! `dist` builds self-contained binaries for every release target and packs
+//! each with shell completions and man pages into `target/dist`. The
+//! tree-sitter grammars are C code, so the musl and ARM targets need a C
+//! cross-compiler; `cross` is used when installed, otherwise plain `cargo`
+//! with whatever linker and `CC_<target>` the environment provides.
+//!
+//! `reference-corpora` regenerates the calibration tables embedded in
+//! codemetrics-core. Each repository in the manifest is cloned at its pinned
+//! commit under the temporary directory (`target` is excluded from analysis
+//! by default), analyzed with default settings, and counted
+//! under its size bucket once per language; the written table lists every
+//! repository and commit it was measured from.
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use codemetrics_core::analyzers::{AnalysisConfig, CodeAnalyzer};
+use codemetrics_core::calibration;
+
+/// Statically linked Linux builds that run on minimal CI images
+const TARGETS: &[&str] = &["x86_64-unknown-linux-musl", "aarch64-unknown-linux-musl"];
+
+const SHELLS: &[(&str, &str)] = &[
+    ("bash", "codemetrics.bash"),
+    ("zsh", "_codemetrics"),
+    ("fish", "codemetrics.fish"),
+    ("powershell", "_codemetrics.ps1"),
+];
+
+const USAGE: &str = "\
+Usage: cargo xtask <task>
+
+Tasks:
+  dist [TARGET...]   Build release archives (default targets: x86_64/aarch64 musl)
+  completions        Write shell completions to target/dist/completions
+  man                Write man pages to target/dist/man
+  reference-corpora MANIFEST VERSION
+                     Measure the repositories in MANIFEST, one `<git url> <commit>` per line,
+                     into the calibration tables of codemetrics-core";
+
+const REFERENCE_CORPORA: &str = "crates/codemetrics-core/src/calibration/reference_corpora.json";
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let dist = workspace_root().join("target/dist");
+
+    match args.first().map(String::as_str) {
+        Some("dist") => {
+            let targets: Vec<&str> = match &args[1..] {
+                [] => TARGETS.to_vec(),
+                targets => targets.iter().map(String::as_str).collect(),
+            };
+            let host_binary = build(None)?;
+            write_completions(&host_binary, &dist.join("completions"))?;
+            write_man_pages(&host_binary, &dist.join("man"))?;
+            for target in targets {
+                let binary = build(Some(target))?;
+                package(target, &binary, &dist)?;
+            }
+        }
+        Some("completions") => write_completions(&build(None)?, &dist.join("completions"))?,
+        Some("man") => write_man_pages(&build(None)?, &dist.join("man"))?,
+        Some("reference-corpora") if args.len() == 3 => reference_corpora(Path::new(&args[1]), &args[2])?,
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+    Ok(())
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap_or(Path::new(".")).to_path_buf()
+}
+
+/// Release build of the CLI; `None` builds for the host
+fn build(target: Option<&str>) -> Result<PathBuf> {
+    let cargo = match target {
+        Some(_) if has_cross() => "cross".to_string(),
+        _ => std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()),
+    };
+    let mut command = Command::new(&cargo);
+    command.current_dir(workspace_root()).args(["build", "--release", "-p", "codemetrics-cli"]);
+    if let Some(target) = target {
+        command.args(["--target", target]);
+    }
+    run(&mut command)?;
+
+    let mut binary = workspace_root().join("target");
+    if let Some(target) = target {
+        binary.push(target);
+    }
+    Ok(binary.join("release/codemetrics"))
+}
+
+fn has_cross() -> bool {
+    Command::new("cross").arg("--version").output().is_ok_and(|output| output.status.success())
+}
+
+fn write_completions(binary: &Path, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    for (shell, file) in SHELLS {
+        let output = Command::new(binary).args(["completions", shell]).output()
+            .with_context(|| format!("Failed to run {}", binary.display()))?;
+        if !output.status.success() {
+            anyhow::bail!("`codemetrics completions {}` failed", shell);
+        }
+        std::fs::write(dir.join(file), output.stdout)
+            .with_context(|| format!("Failed to write {} completions", shell))?;
+    }
+    Ok(())
+}
+
+fn write_man_pages(binary: &Path, dir: &Path) -> Result<()> {
+    run(Command::new(binary).arg("man").arg(dir))
+}
+
+/// `target/dist/codemetrics-<target>.tar.gz` with the binary, completions and man pages
+fn package(target: &str, binary: &Path, dist: &Path) -> Result<()> {
+    let staging = dist.join(format!("codemetrics-{}", target));
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    std::fs::create_dir_all(&staging)?;
+    std::fs::copy(binary, staging.join("codemetrics"))
+        .with_context(|| format!("Failed to copy {}", binary.display()))?;
+    for extra in ["completions", "man"] {
+        copy_dir(&dist.join(extra), &staging.join(extra))?;
+    }
+
+    let archive = format!("codemetrics-{}.tar.gz", target);
+    run(Command::new("tar").current_dir(dist).args(["czf", &archive]).arg(staging.file_name().unwrap_or_default()))?;
+    println!("Packaged {}", dist.join(archive).display());
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from).with_context(|| format!("Failed to read {}", from.display()))? {
+        let entry = entry?;
+        std::fs::copy(entry.path(), to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+fn run(command: &mut Command) -> Result<()> {
+    let status = command.status().with_context(|| format!("Failed to run {:?}", command))?;
+    if !status.success() {
+        anyhow::bail!("{:?} exited with {}", command, status);
+    }
+    Ok(())
+}
+
+/// Analyze every repository in `manifest` and write the deciles of each language and size bucket
+fn reference_corpora(manifest: &Path, version: &str) -> Result<()> {
+    let content = std::fs::read_to_string(manifest).with_context(|| format!("Failed to read {}", manifest.display()))?;
+    let sources: Vec<(&str, &str)> = content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.split_once(char::is_whitespace).map(|(url, commit)| (url, commit.trim()))
+            .with_context(|| format!("Expected `<git url> <commit>`, got `{}`", line)))
+        .collect::<Result<_>>()?;
+
+    let analyzer = CodeAnalyzer::with_config(AnalysisConfig::builder().quiet(true).build()?);
+    // (language, size) -> metric -> one value per project
+    let mut measured: BTreeMap<(String, &str), BTreeMap<&str, Vec<f64>>> = BTreeMap::new();
+    for (url, commit) in &sources {
+        let directory: String = url.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '-' }).collect();
+        let checkout = std::env::temp_dir().join("codemetrics-corpora").join(directory);
+        if !checkout.exists() {
+            run(Command::new("git").args(["clone", "--quiet", url]).arg(&checkout))?;
+        }
+        run(Command::new("git").arg("-C").arg(&checkout).args(["checkout", "--quiet", "--detach", commit]))?;
+        let (size, metrics) = calibration::project_metrics(&analyzer.analyze_path(&checkout)?);
+        for (language, metric, value) in metrics {
+            measured.entry((language, size)).or_default().entry(metric).or_default().push(value);
+        }
+        println!("Measured {} at {}", url, commit);
+    }
+
+    let distributions: Vec<_> = measured.iter()
+        .map(|((language, size), metrics)| json!({
+            "language": language,
+            "size": size,
+            "projects": metrics.values().map(Vec::len).max().unwrap_or(0),
+            "metrics": metrics.iter()
+                .map(|(metric, values)| (metric.to_string(), calibration::deciles(values)))
+                .collect::<BTreeMap<_, _>>(),
+        }))
+        .collect();
+    let table = json!({
+        "corpus_version": version,
+        "description": "Deciles (p0, p10, ..., p100) of per-project metrics, written by `cargo xtask reference-corpora` from the repositories in `sources`.",
+        "size_buckets": { "small": "< 10k lines", "medium": "10k - 100k lines", "large": "> 100k lines" },
+        "sources": sources.iter().map(|(url, commit)| json!({ "repository": url, "commit": commit })).collect::<Vec<_>>(),
+        "distributions": distributions,
+    });
+    let path = workspace_root().join(REFERENCE_CORPORA);
+    std::fs::write(&path, serde_json::to_string_pretty(&table)? + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Wrote {} distributions from {} repositories to {}", distributions.len(), sources.len(), path.display());
+    Ok(())
+}